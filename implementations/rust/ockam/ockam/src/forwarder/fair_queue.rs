@@ -0,0 +1,176 @@
+//! Deficit round robin scheduling of the messages forwarded by a relay,
+//! so that a chatty source can not starve the others.
+
+use ockam_core::compat::collections::{BTreeMap, VecDeque};
+use ockam_core::compat::string::String;
+use ockam_core::compat::vec::Vec;
+
+/// Bytes a source may send in one round, if it has messages waiting.
+pub(crate) const DEFAULT_QUANTUM: usize = 16 * 1024;
+
+/// Sources whose counters are kept once they have no message waiting.
+const MAX_IDLE_SOURCES: usize = 1024;
+
+/// Counters of the messages forwarded for one source.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SourceStats {
+    /// Identity of the source, or the address of its connection if it is
+    /// not authenticated.
+    pub source: String,
+    /// Messages forwarded.
+    pub forwarded_messages: u64,
+    /// Bytes forwarded.
+    pub forwarded_bytes: u64,
+    /// Messages waiting for their turn.
+    pub queued: usize,
+    /// Rounds in which the source used up its share and let others go first.
+    pub deferred: u64,
+}
+
+struct Source<T> {
+    queue: VecDeque<(usize, T)>,
+    deficit: usize,
+    stats: SourceStats,
+}
+
+/// Messages waiting to be forwarded, per source.
+pub(crate) struct FairQueue<T> {
+    quantum: usize,
+    sources: BTreeMap<String, Source<T>>,
+    /// Sources with messages waiting, in the order of their turns
+    active: VecDeque<String>,
+}
+
+impl<T> FairQueue<T> {
+    pub(crate) fn new(quantum: usize) -> Self {
+        Self {
+            quantum: quantum.max(1),
+            sources: BTreeMap::new(),
+            active: VecDeque::new(),
+        }
+    }
+
+    /// Queue a message of `size` bytes sent by `source`.
+    pub(crate) fn push(&mut self, source: String, size: usize, item: T) {
+        if !self.sources.contains_key(&source) {
+            self.forget_idle_sources();
+        }
+        let s = self
+            .sources
+            .entry(source.clone())
+            .or_insert_with(|| Source {
+                queue: VecDeque::new(),
+                deficit: 0,
+                stats: SourceStats {
+                    source: source.clone(),
+                    ..Default::default()
+                },
+            });
+        if s.queue.is_empty() {
+            s.deficit = self.quantum;
+            self.active.push_back(source);
+        }
+        s.queue.push_back((size, item));
+        s.stats.queued += 1;
+    }
+
+    /// Take the next message to forward.
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        loop {
+            let name = self.active.front()?;
+            let s = self.sources.get_mut(name)?;
+            match s.queue.front() {
+                Some((size, _)) if *size <= s.deficit => {
+                    let (size, item) = s.queue.pop_front()?;
+                    s.deficit -= size;
+                    s.stats.queued -= 1;
+                    s.stats.forwarded_messages += 1;
+                    s.stats.forwarded_bytes += size as u64;
+                    if s.queue.is_empty() {
+                        s.deficit = 0;
+                        self.active.pop_front();
+                    }
+                    return Some(item);
+                }
+                Some(_) => {
+                    // Its share is used up, the next source goes first
+                    s.deficit += self.quantum;
+                    if self.active.len() > 1 {
+                        s.stats.deferred += 1;
+                    }
+                    self.active.rotate_left(1);
+                }
+                None => {
+                    self.active.pop_front();
+                }
+            }
+        }
+    }
+
+    pub(crate) fn stats(&self) -> Vec<SourceStats> {
+        self.sources.values().map(|s| s.stats.clone()).collect()
+    }
+
+    fn forget_idle_sources(&mut self) {
+        let idle = self.sources.len() - self.active.len();
+        if idle >= MAX_IDLE_SOURCES {
+            let name = self
+                .sources
+                .iter()
+                .find(|(_, s)| s.queue.is_empty())
+                .map(|(k, _)| k.clone());
+            if let Some(name) = name {
+                self.sources.remove(&name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sources_take_turns() {
+        let mut q = FairQueue::new(100);
+        for i in 0..4 {
+            q.push("chatty".into(), 100, ("chatty", i));
+        }
+        q.push("quiet".into(), 100, ("quiet", 0));
+
+        let order: Vec<_> = core::iter::from_fn(|| q.pop()).collect();
+        assert_eq!(
+            order,
+            [
+                ("chatty", 0),
+                ("quiet", 0),
+                ("chatty", 1),
+                ("chatty", 2),
+                ("chatty", 3)
+            ]
+        );
+
+        let stats = q.stats();
+        assert_eq!(stats[0].source, "chatty");
+        assert_eq!(stats[0].forwarded_messages, 4);
+        assert_eq!(stats[0].forwarded_bytes, 400);
+        assert_eq!(stats[0].deferred, 1);
+        assert_eq!(stats[1].forwarded_messages, 1);
+        assert_eq!(stats[1].queued, 0);
+    }
+
+    #[test]
+    fn shares_are_counted_in_bytes() {
+        let mut q = FairQueue::new(100);
+        q.push("large".into(), 250, "large");
+        for _ in 0..3 {
+            q.push("small".into(), 50, "small");
+        }
+
+        // The large message waits until its source saved up enough
+        let order: Vec<_> = core::iter::from_fn(|| q.pop()).collect();
+        assert_eq!(order, ["small", "small", "small", "large"]);
+        assert_eq!(q.stats()[0].deferred, 2);
+    }
+}
@@ -0,0 +1,796 @@
+use crate::{Context, DelayedEvent, Message};
+use core::fmt;
+use core::str::from_utf8;
+use core::time::Duration;
+use ockam_core::compat::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+    sync::{Arc, Mutex},
+    vec::Vec,
+};
+use ockam_core::{
+    async_trait, Address, Any, Decodable, LocalMessage, Result, Route, Routed, TransportMessage,
+    Worker,
+};
+use ockam_identity::authenticated_storage::AuthenticatedStorage;
+use ockam_identity::credential::{AttributesStorageUtils, Timestamp};
+use ockam_identity::{IdentityIdentifier, IdentitySecureChannelLocalInfo};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+mod fair_queue;
+
+pub use fair_queue::SourceStats;
+use fair_queue::{FairQueue, DEFAULT_QUANTUM};
+
+type Queue = Arc<Mutex<FairQueue<Queued>>>;
+type Aliases = Arc<Mutex<BTreeMap<Address, Alias>>>;
+
+/// Alias worker to register remote workers under local names.
+///
+/// To talk with this worker, you can use the
+/// [`RemoteForwarder`](crate::remote::RemoteForwarder) which is a
+/// compatible client for this server.
+///
+/// The messages of all the aliases are forwarded in turns per source, the
+/// identity of the secure channel they came through or else their
+/// connection, so that a chatty source can not starve the others.
+///
+/// Several workers may register under the same alias, as long as they do
+/// so through secure channels with the same identity, to share its load.
+/// The messages go to them in turns, or with
+/// [`ForwardingServiceOptions::with_consumer_affinity`] always to the same
+/// one for a given source. A worker registering again, e.g. once it
+/// reconnected, replaces its previous route, and a route which messages
+/// can't be forwarded along anymore is removed.
+///
+/// With [`ForwardingServiceOptions::with_credential_validity`], workers
+/// stay registered only until the credential of their identity expires,
+/// and each renewal of a registration needs a valid credential again. An
+/// alias is removed once none of its workers is registered anymore.
+#[non_exhaustive]
+pub struct ForwardingService {
+    queue: Queue,
+    dispatcher: Address,
+    affinity: bool,
+    validity: Option<Arc<dyn RegistrationValidity>>,
+    /// The aliases registered so far
+    aliases: Aliases,
+}
+
+/// An alias and the identity its workers registered with, if any.
+struct Alias {
+    /// Address at which its forwarder adds workers
+    control: Address,
+    identity: Option<String>,
+}
+
+/// Decides until when the workers of an identity may stay registered
+/// under an alias.
+#[async_trait]
+pub trait RegistrationValidity: Send + Sync + 'static {
+    /// When the registrations of `identity` expire, or `None` if it may
+    /// not register workers.
+    async fn valid_until(&self, identity: &IdentityIdentifier) -> Result<Option<Timestamp>>;
+}
+
+/// Registrations valid as long as the credential attributes their
+/// identity presented to this node.
+pub struct CredentialValidity<S> {
+    storage: S,
+}
+
+impl<S: AuthenticatedStorage> CredentialValidity<S> {
+    /// Check the attributes in `storage`.
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+}
+
+#[async_trait]
+impl<S: AuthenticatedStorage> RegistrationValidity for CredentialValidity<S> {
+    async fn valid_until(&self, identity: &IdentityIdentifier) -> Result<Option<Timestamp>> {
+        AttributesStorageUtils::get_expiry(identity, &self.storage).await
+    }
+}
+
+/// Options of a [`ForwardingService`].
+#[derive(Clone, Default)]
+pub struct ForwardingServiceOptions {
+    consumer_affinity: bool,
+    validity: Option<Arc<dyn RegistrationValidity>>,
+}
+
+impl fmt::Debug for ForwardingServiceOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ForwardingServiceOptions")
+            .field("consumer_affinity", &self.consumer_affinity)
+            .field("validity", &self.validity.is_some())
+            .finish()
+    }
+}
+
+impl ForwardingServiceOptions {
+    /// Options with the default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send the messages of a source always to the same of the workers
+    /// registered under an alias, as stateful services require.
+    pub fn with_consumer_affinity(mut self) -> Self {
+        self.consumer_affinity = true;
+        self
+    }
+
+    /// Only accept workers registering through a secure channel, and only
+    /// until the credential of its identity expires. Renewals of the
+    /// registrations extend them with the current credential.
+    pub fn with_credential_validity(self, storage: impl AuthenticatedStorage) -> Self {
+        self.with_registration_validity(CredentialValidity::new(storage))
+    }
+
+    /// Only accept workers registering through a secure channel, and only
+    /// for as long as `validity` tells.
+    pub fn with_registration_validity(mut self, validity: impl RegistrationValidity) -> Self {
+        self.validity = Some(Arc::new(validity));
+        self
+    }
+}
+
+/// Counters of the messages a [`ForwardingService`] forwarded, per source.
+#[derive(Clone)]
+pub struct ForwardingStats(Queue);
+
+impl ForwardingStats {
+    /// The counters of the sources seen recently.
+    pub fn sources(&self) -> Vec<SourceStats> {
+        self.0.lock().unwrap().stats()
+    }
+}
+
+impl ForwardingService {
+    /// Start a forwarding service. The address of the forwarding service will be
+    /// `"forwarding_service"`.
+    pub async fn create(ctx: &Context) -> Result<()> {
+        Self::create_with_stats(ctx).await?;
+        Ok(())
+    }
+
+    /// Start a forwarding service, and return its counters.
+    pub async fn create_with_stats(ctx: &Context) -> Result<ForwardingStats> {
+        Self::create_with_options(ctx, ForwardingServiceOptions::new()).await
+    }
+
+    /// Start a forwarding service with the given options, and return its
+    /// counters.
+    pub async fn create_with_options(
+        ctx: &Context,
+        options: ForwardingServiceOptions,
+    ) -> Result<ForwardingStats> {
+        let queue = Arc::new(Mutex::new(FairQueue::new(DEFAULT_QUANTUM)));
+        let dispatcher = Address::random_local();
+        ctx.start_worker(
+            dispatcher.clone(),
+            Dispatcher {
+                queue: queue.clone(),
+            },
+        )
+        .await?;
+        let service = Self {
+            queue: queue.clone(),
+            dispatcher,
+            affinity: options.consumer_affinity,
+            validity: options.validity,
+            aliases: Arc::new(Mutex::new(BTreeMap::new())),
+        };
+        ctx.start_worker("forwarding_service", service).await?;
+        Ok(ForwardingStats(queue))
+    }
+}
+
+#[crate::worker]
+impl Worker for ForwardingService {
+    type Context = Context;
+    type Message = Any;
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        let forward_route = msg.return_route();
+        let message = msg.into_local_message();
+        let their_identity = IdentitySecureChannelLocalInfo::find_info(&message)
+            .ok()
+            .map(|i| i.their_identity_id().clone());
+        let identity = their_identity.as_ref().map(|i| i.to_string());
+        let payload = message.into_transport_message().payload;
+        let alias = alias_address(&payload);
+
+        let expires = match &self.validity {
+            None => None,
+            Some(validity) => {
+                let valid_until = match &their_identity {
+                    Some(identity) => validity.valid_until(identity).await?,
+                    None => None,
+                };
+                match valid_until {
+                    Some(t) => Some(u64::from(t)),
+                    None => {
+                        warn!("Refused a registration from {forward_route} without a valid credential");
+                        return Ok(());
+                    }
+                }
+            }
+        };
+
+        let existing = alias.as_ref().and_then(|a| {
+            let aliases = self.aliases.lock().unwrap();
+            aliases
+                .get(a)
+                .map(|e| (e.control.clone(), e.identity.clone()))
+        });
+        if let Some((control, existing_identity)) = existing {
+            if identity.is_none() || existing_identity != identity {
+                warn!(
+                    "Refused to add a worker without the identity of alias {}",
+                    alias.as_ref().map(|a| a.to_string()).unwrap_or_default()
+                );
+                return Ok(());
+            }
+            let add = AddBackend {
+                route: forward_route,
+                payload,
+                expires,
+            };
+            return ctx.send(control, add).await;
+        }
+
+        let address = alias.clone().unwrap_or_else(Address::random_local);
+        let control = Address::random_local();
+        let expiry = Address::random_local();
+        let unreachable = Address::random_local();
+        let forwarder = Forwarder {
+            backends: vec![Backend {
+                route: forward_route.clone(),
+                expires,
+            }],
+            next: 0,
+            affinity: self.affinity,
+            control: control.clone(),
+            payload: Some(payload),
+            queue: self.queue.clone(),
+            dispatcher: self.dispatcher.clone(),
+            expiry: expiry.clone(),
+            expiry_event: None,
+            unreachable: unreachable.clone(),
+            alias: alias.clone(),
+            aliases: self.aliases.clone(),
+        };
+        if let Some(alias) = alias {
+            let entry = Alias {
+                control: control.clone(),
+                identity,
+            };
+            self.aliases.lock().unwrap().insert(alias, entry);
+        }
+        ctx.start_worker(vec![address, control, expiry, unreachable], forwarder)
+            .await?;
+        info!("Created new alias for {}", forward_route);
+
+        Ok(())
+    }
+}
+
+/// Asks the forwarder of an alias to also send to another worker, or to
+/// renew its registration.
+#[derive(Serialize, Deserialize, Message)]
+struct AddBackend {
+    route: Route,
+    /// The registration message, sent back to the worker
+    payload: Vec<u8>,
+    /// Until when the worker stays registered, in seconds since the epoch
+    expires: Option<u64>,
+}
+
+/// Tells the forwarder of an alias that a message could not be forwarded
+/// along the route to one of its workers.
+#[derive(Serialize, Deserialize, Message)]
+struct RemoveBackend {
+    route: Route,
+}
+
+/// A message waiting for its turn, and the worker it goes to.
+struct Queued {
+    message: LocalMessage,
+    route: Route,
+    /// Address of the forwarder of its alias, told when the route fails
+    unreachable: Address,
+}
+
+/// A worker registered under an alias.
+struct Backend {
+    route: Route,
+    /// Until when it stays registered, in seconds since the epoch
+    expires: Option<u64>,
+}
+
+struct Forwarder {
+    /// The workers registered under the alias
+    backends: Vec<Backend>,
+    /// Next backend in turn, without affinity
+    next: usize,
+    affinity: bool,
+    control: Address,
+    // this option will be `None` after this worker is initialized, because
+    // while initializing, the worker will send the payload contained in this
+    // field to the `forward_route`, to indicate a successful connection
+    payload: Option<Vec<u8>>,
+    queue: Queue,
+    dispatcher: Address,
+    /// Address at which the next registration expires
+    expiry: Address,
+    expiry_event: Option<DelayedEvent<()>>,
+    /// Address at which routes that failed are removed
+    unreachable: Address,
+    alias: Option<Address>,
+    aliases: Aliases,
+}
+
+impl Forwarder {
+    /// The route to the worker that gets the messages of `source`.
+    fn backend(&mut self, source: &str) -> Route {
+        let i = if self.affinity {
+            (fnv1a(source) % self.backends.len() as u64) as usize
+        } else {
+            self.next = (self.next + 1) % self.backends.len();
+            self.next
+        };
+        self.backends[i].route.clone()
+    }
+
+    /// Add a worker, or renew its registration, replacing its route if
+    /// it registers along another one.
+    fn add(&mut self, route: Route, expires: Option<u64>) -> bool {
+        let worker = route.iter().last().cloned();
+        match self
+            .backends
+            .iter_mut()
+            .find(|b| b.route.iter().last() == worker.as_ref())
+        {
+            Some(backend) => {
+                backend.route = route;
+                backend.expires = expires;
+                false
+            }
+            None => {
+                self.backends.push(Backend { route, expires });
+                true
+            }
+        }
+    }
+
+    /// Remove the worker at `route`, and the alias if none is left.
+    async fn remove(&mut self, ctx: &Context, route: &Route) -> Result<()> {
+        let registered = self.backends.len();
+        self.backends.retain(|b| &b.route != route);
+        if self.backends.len() == registered {
+            return Ok(());
+        }
+        warn!("Removed unreachable {} from alias {}", route, ctx.address());
+        self.next = 0;
+        if self.backends.is_empty() {
+            return self.stop(ctx).await;
+        }
+        Ok(())
+    }
+
+    /// Remove the alias, unless it was registered again since.
+    async fn stop(&mut self, ctx: &Context) -> Result<()> {
+        if let Some(alias) = &self.alias {
+            let mut aliases = self.aliases.lock().unwrap();
+            if aliases.get(alias).map(|a| &a.control) == Some(&self.control) {
+                aliases.remove(alias);
+            }
+        }
+        ctx.stop_worker(ctx.address()).await
+    }
+
+    /// Remove the workers whose registration expired at `now`, and return
+    /// whether some are left.
+    fn remove_expired(&mut self, now: Option<u64>) -> bool {
+        let registered = self.backends.len();
+        self.backends.retain(|b| match (b.expires, now) {
+            (Some(expires), Some(now)) => expires > now,
+            _ => true,
+        });
+        if self.backends.len() < registered {
+            self.next = 0;
+        }
+        !self.backends.is_empty()
+    }
+
+    /// Remove the expired workers, and the alias if none is left, and
+    /// wait for the next registration to expire otherwise.
+    async fn expire(&mut self, ctx: &Context) -> Result<()> {
+        let now = Timestamp::now().map(u64::from);
+        if !self.remove_expired(now) {
+            info!("The registrations of alias {} expired", ctx.address());
+            return self.stop(ctx).await;
+        }
+
+        let next = self.backends.iter().filter_map(|b| b.expires).min();
+        if let (Some(next), Some(now)) = (next, now) {
+            if self.expiry_event.is_none() {
+                self.expiry_event = Some(DelayedEvent::create(ctx, self.expiry.clone(), ()).await?);
+            }
+            if let Some(event) = &mut self.expiry_event {
+                event
+                    .schedule(Duration::from_secs(next.saturating_sub(now) + 1))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The alias to register under, given a registration payload, or `None`
+/// for a random address.
+fn alias_address(registration_payload: &[u8]) -> Option<Address> {
+    // TODO: assume that the first byte is length, ignore it.
+    // We have to improve this actually parse the payload.
+    match registration_payload.get(1..) {
+        // Sent by ephemeral forwarders
+        Some(b"register") => None,
+        Some(address) => match from_utf8(address) {
+            Ok(v) => Some(Address::from_string(v)),
+            Err(_e) => None,
+        },
+        None => None,
+    }
+}
+
+/// FNV-1a, which unlike the hasher of `std` is the same on every node.
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf2_9ce4_8422_2325, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[crate::worker]
+impl Worker for Forwarder {
+    type Context = Context;
+    type Message = Any;
+
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        let payload = self
+            .payload
+            .take()
+            .expect("payload must be available on init");
+        let msg = TransportMessage::v1(self.backends[0].route.clone(), ctx.address(), payload);
+
+        ctx.forward(LocalMessage::new(msg, Vec::new())).await?;
+        self.expire(ctx).await
+    }
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        if msg.msg_addr() == self.control {
+            let add = AddBackend::decode(msg.payload())?;
+            if self.add(add.route.clone(), add.expires) {
+                info!("Added {} to alias {}", add.route, ctx.address());
+            }
+            let msg = TransportMessage::v1(add.route, ctx.address(), add.payload);
+            ctx.forward(LocalMessage::new(msg, Vec::new())).await?;
+            return self.expire(ctx).await;
+        }
+
+        if msg.msg_addr() == self.expiry {
+            return self.expire(ctx).await;
+        }
+
+        if msg.msg_addr() == self.unreachable {
+            let remove = RemoveBackend::decode(msg.payload())?;
+            return self.remove(ctx, &remove.route).await;
+        }
+
+        let mut message = msg.into_local_message();
+        let source = match IdentitySecureChannelLocalInfo::find_info(&message) {
+            Ok(info) => info.their_identity_id().to_string(),
+            Err(_) => message
+                .transport()
+                .return_route
+                .next()
+                .map(|a| a.to_string())
+                .unwrap_or_default(),
+        };
+        let transport_message = message.transport_mut();
+
+        // Remove my address from the onward_route
+        transport_message.onward_route.step()?;
+
+        // Prepend forward route
+        let route = self.backend(&source);
+        transport_message
+            .onward_route
+            .modify()
+            .prepend_route(route.clone());
+
+        let size = transport_message.payload.len();
+        let queued = Queued {
+            message,
+            route,
+            unreachable: self.unreachable.clone(),
+        };
+        self.queue.lock().unwrap().push(source, size, queued);
+        ctx.send(self.dispatcher.clone(), ()).await
+    }
+}
+
+/// Forwards the queued messages, one per message it receives.
+struct Dispatcher {
+    queue: Queue,
+}
+
+#[crate::worker]
+impl Worker for Dispatcher {
+    type Context = Context;
+    type Message = ();
+
+    async fn handle_message(&mut self, ctx: &mut Self::Context, _: Routed<()>) -> Result<()> {
+        let next = self.queue.lock().unwrap().pop();
+        let queued = match next {
+            Some(queued) => queued,
+            None => return Ok(()),
+        };
+        if let Err(e) = ctx.forward(queued.message).await {
+            warn!("Failed to forward a message along {}: {}", queued.route, e);
+            // The alias may be gone already
+            let remove = RemoveBackend {
+                route: queued.route,
+            };
+            let _ = ctx.send(queued.unreachable, remove).await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::remote::RemoteForwarder;
+    use crate::workers::Echoer;
+    use core::time::Duration;
+    use ockam_core::route;
+    use ockam_identity::authenticated_storage::mem::InMemoryStorage;
+    use ockam_identity::{Identity, TrustEveryonePolicy};
+    use ockam_node::DetachedContext;
+    use ockam_vault::Vault;
+
+    /// Register a worker at `address` under the alias "svc", through
+    /// `channel` if any.
+    async fn register(
+        ctx: &Context,
+        address: &str,
+        channel: Option<&Address>,
+    ) -> Result<DetachedContext> {
+        let mut backend = ctx.new_detached(address).await?;
+        let route = match channel {
+            Some(channel) => route![channel.clone(), "forwarding_service"],
+            None => route!["forwarding_service"],
+        };
+        backend.send(route, "svc".to_string()).await?;
+        let reply = backend.receive::<String>().await?.take().body();
+        assert_eq!(reply, "svc");
+        Ok(backend)
+    }
+
+    /// Open `n` secure channels to this node, all with the same identity.
+    async fn channels(ctx: &Context, n: usize) -> Result<Vec<Address>> {
+        let vault = Vault::create();
+        let storage = InMemoryStorage::new();
+        let relay = Identity::create(ctx, &vault).await?;
+        relay
+            .create_secure_channel_listener("listener", TrustEveryonePolicy, &storage)
+            .await?;
+        let member = Identity::create(ctx, &vault).await?;
+        let mut channels = Vec::new();
+        for _ in 0..n {
+            let channel = member
+                .create_secure_channel(route!["listener"], TrustEveryonePolicy, &storage)
+                .await?;
+            channels.push(channel);
+        }
+        Ok(channels)
+    }
+
+    /// Send 3 messages from each of 4 consumers to the alias "svc", and
+    /// return the consumers seen by each backend.
+    async fn consumers_per_backend(
+        ctx: &Context,
+        backends: &mut [DetachedContext],
+    ) -> Result<Vec<Vec<String>>> {
+        for consumer in ["consumer1", "consumer2", "consumer3", "consumer4"] {
+            let c = ctx.new_detached(consumer).await?;
+            for _ in 0..3 {
+                c.send(route!["svc", "app"], consumer.to_string()).await?;
+            }
+        }
+        let mut seen = Vec::new();
+        for b in backends {
+            let mut consumers = Vec::new();
+            while let Ok(m) = b
+                .receive_duration_timeout::<String>(Duration::from_millis(200))
+                .await
+            {
+                consumers.push(m.take().body());
+            }
+            seen.push(consumers);
+        }
+        Ok(seen)
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__shared_alias__should_take_turns(ctx: &mut Context) -> Result<()> {
+        ForwardingService::create(ctx).await?;
+        let channels = channels(ctx, 2).await?;
+        let mut backends = [
+            register(ctx, "backend1", Some(&channels[0])).await?,
+            register(ctx, "backend2", Some(&channels[1])).await?,
+        ];
+
+        let seen = consumers_per_backend(ctx, &mut backends).await?;
+        assert_eq!(seen[0].len(), 6);
+        assert_eq!(seen[1].len(), 6);
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__consumer_affinity__should_keep_backends(ctx: &mut Context) -> Result<()> {
+        let options = ForwardingServiceOptions::new().with_consumer_affinity();
+        ForwardingService::create_with_options(ctx, options).await?;
+        let channels = channels(ctx, 2).await?;
+        let mut backends = [
+            register(ctx, "backend1", Some(&channels[0])).await?,
+            register(ctx, "backend2", Some(&channels[1])).await?,
+        ];
+
+        let seen = consumers_per_backend(ctx, &mut backends).await?;
+        assert_eq!(seen[0].len() + seen[1].len(), 12);
+        for consumer in &seen[0] {
+            assert!(!seen[1].contains(consumer));
+        }
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__shared_alias__should_need_an_identity(ctx: &mut Context) -> Result<()> {
+        ForwardingService::create(ctx).await?;
+        let _backend = register(ctx, "backend1", None).await?;
+
+        let mut other = ctx.new_detached("backend2").await?;
+        other
+            .send(route!["forwarding_service"], "svc".to_string())
+            .await?;
+        assert!(other
+            .receive_duration_timeout::<String>(Duration::from_millis(200))
+            .await
+            .is_err());
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__unreachable_backend__should_be_removed(ctx: &mut Context) -> Result<()> {
+        ForwardingService::create(ctx).await?;
+        drop(register(ctx, "backend1", None).await?);
+        ctx.sleep(Duration::from_millis(100)).await;
+
+        // The alias goes away with the only route to it
+        ctx.send(route!["svc", "app"], "Hello".to_string()).await?;
+        ctx.sleep(Duration::from_millis(100)).await;
+        let _backend = register(ctx, "backend2", None).await?;
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__local_service__should_count_sources(ctx: &mut Context) -> Result<()> {
+        let stats = ForwardingService::create_with_stats(ctx).await?;
+        ctx.start_worker("echoer", Echoer).await?;
+
+        let info = RemoteForwarder::create_static_without_heartbeats(
+            ctx,
+            route!["forwarding_service"],
+            "blue",
+        )
+        .await?;
+
+        for _ in 0..3 {
+            let resp = ctx
+                .send_and_receive::<_, _, String>(
+                    route![info.remote_address(), "echoer"],
+                    "Hello".to_string(),
+                )
+                .await?;
+            assert_eq!(resp, "Hello");
+        }
+
+        // Each request comes from a new local address, and so a new source
+        let sources = stats.sources();
+        assert_eq!(sources.len(), 3);
+        assert!(sources
+            .iter()
+            .all(|s| s.forwarded_messages == 1 && s.queued == 0));
+
+        ctx.stop().await
+    }
+
+    /// Registrations that expire as soon as they are made.
+    struct ValidUntilNow;
+
+    #[async_trait]
+    impl RegistrationValidity for ValidUntilNow {
+        async fn valid_until(&self, _: &IdentityIdentifier) -> Result<Option<Timestamp>> {
+            Ok(Timestamp::now())
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__registration_validity__should_expire_aliases(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        let options = ForwardingServiceOptions::new().with_registration_validity(ValidUntilNow);
+        ForwardingService::create_with_options(ctx, options).await?;
+
+        // Registrations without an identity are refused
+        let mut backend = ctx.new_detached("backend").await?;
+        backend
+            .send(route!["forwarding_service"], "svc".to_string())
+            .await?;
+        assert!(backend
+            .receive_duration_timeout::<String>(Duration::from_millis(200))
+            .await
+            .is_err());
+
+        let vault = Vault::create();
+        let storage = InMemoryStorage::new();
+        let relay = Identity::create(ctx, &vault).await?;
+        relay
+            .create_secure_channel_listener("listener", TrustEveryonePolicy, &storage)
+            .await?;
+        let member = Identity::create(ctx, &vault).await?;
+        let channel = member
+            .create_secure_channel(route!["listener"], TrustEveryonePolicy, &storage)
+            .await?;
+
+        backend
+            .send(route![channel, "forwarding_service"], "svc".to_string())
+            .await?;
+        let reply = backend.receive::<String>().await?.take().body();
+        assert_eq!(reply, "svc");
+
+        // Once expired, the alias does not forward anymore
+        ctx.sleep(Duration::from_secs(2)).await;
+        let _ = ctx.send(route!["svc", "app"], "Hello".to_string()).await;
+        assert!(backend
+            .receive_duration_timeout::<String>(Duration::from_millis(200))
+            .await
+            .is_err());
+
+        ctx.stop().await
+    }
+}
@@ -38,7 +38,10 @@ mod system;
 mod unique;
 
 pub use error::OckamError;
-pub use forwarder::ForwardingService;
+pub use forwarder::{
+    CredentialValidity, ForwardingService, ForwardingServiceOptions, ForwardingStats,
+    RegistrationValidity, SourceStats,
+};
 pub use metadata::OckamMessage;
 pub use system::{SystemBuilder, SystemHandler, WorkerSystem};
 pub use unique::unique_with_prefix;
@@ -126,5 +129,8 @@ pub use ockam_transport_tcp::{TcpTransport, TCP};
 #[cfg(feature = "ockam_transport_tcp")]
 /// Tcp
 pub mod tcp {
-    pub use ockam_transport_tcp::{InletOptions, OutletOptions};
+    pub use ockam_transport_tcp::{
+        EgressPolicy, InletOptions, InletRoute, ListenerOptions, OutletOptions, ProxyProtocol,
+        ProxyRoutes, ReconnectBackoff, TcpConnectionOptions, UpstreamProxy,
+    };
 }
@@ -16,6 +16,9 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
 
+/// Interval at which static forwarders renew their registration by default.
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
 /// Information about a remotely forwarded worker.
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Message)]
 pub struct RemoteForwarderInfo {
@@ -98,6 +101,23 @@ impl RemoteForwarder {
         ctx: &Context,
         hub_route: impl Into<Route>,
         alias: impl Into<String>,
+    ) -> Result<RemoteForwarderInfo> {
+        Self::create_static_with_heartbeat_interval(
+            ctx,
+            hub_route,
+            alias,
+            Some(DEFAULT_HEARTBEAT_INTERVAL),
+        )
+        .await
+    }
+
+    /// Create and start static RemoteForwarder at predefined address with given Ockam Hub route,
+    /// which renews its registration every `heartbeat_interval`, or never if it is `None`
+    pub async fn create_static_with_heartbeat_interval(
+        ctx: &Context,
+        hub_route: impl Into<Route>,
+        alias: impl Into<String>,
+        heartbeat_interval: Option<Duration>,
     ) -> Result<RemoteForwarderInfo> {
         let address: Address = random();
         let mut child_ctx = ctx.new_detached(address).await?;
@@ -110,15 +130,19 @@ impl RemoteForwarder {
             .append("static_forwarding_service")
             .into();
 
-        let heartbeat =
-            DelayedEvent::create(ctx, addresses.heartbeat_address.clone(), vec![]).await?;
+        let heartbeat = match heartbeat_interval {
+            Some(_) => {
+                Some(DelayedEvent::create(ctx, addresses.heartbeat_address.clone(), vec![]).await?)
+            }
+            None => None,
+        };
         let forwarder = Self::new(
             addresses.clone(),
             registration_route,
             alias.into(),
             child_ctx.address(),
-            Some(heartbeat),
-            Duration::from_secs(5),
+            heartbeat,
+            heartbeat_interval.unwrap_or(DEFAULT_HEARTBEAT_INTERVAL),
         );
 
         debug!(
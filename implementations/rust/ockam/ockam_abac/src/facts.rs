@@ -0,0 +1,174 @@
+//! Local facts, the attributes of the environment of an authorization
+//! request rather than of its subject.
+//!
+//! Policies reference a fact with the key [`FACT_PREFIX`]`<name>`, e.g.
+//! `local.inlet` or `local.tag.prod`. Subject attributes are never looked
+//! up under these keys, so that a credential can't pass for a fact.
+
+use core::fmt::{self, Debug, Formatter};
+
+use crate::{bool, Action, Attributes, Key, Resource, Value};
+use ockam_core::compat::{sync::Arc, vec::Vec};
+
+use alloc::format;
+
+/// Prefix of the keys policies reference facts with
+pub const FACT_PREFIX: &str = "local.";
+
+/// A source of facts, e.g. the tags of a node or the current time
+///
+/// The names of the facts it provides are not prefixed. Closures taking
+/// the [`Resource`] and [`Action`] of a request are providers too.
+pub trait FactsProvider: Send + Sync + 'static {
+    /// The facts about a request for `action` on `resource`
+    fn facts(&self, resource: &Resource, action: &Action) -> Attributes;
+}
+
+impl<F> FactsProvider for F
+where
+    F: Fn(&Resource, &Action) -> Attributes + Send + Sync + 'static,
+{
+    fn facts(&self, resource: &Resource, action: &Action) -> Attributes {
+        self(resource, action)
+    }
+}
+
+/// Facts which don't depend on the request, e.g. the name of an inlet or
+/// the network interface of a node
+#[derive(Debug, Clone, Default)]
+pub struct StaticFacts(Attributes);
+
+impl StaticFacts {
+    /// Create facts from `(name, value)` pairs
+    pub fn new<A>(facts: A) -> Self
+    where
+        A: IntoIterator<Item = (Key, Value)>,
+    {
+        Self(facts.into_iter().collect())
+    }
+
+    /// Add the tags `tags`, each the fact `tag.<tag>` set to `true`
+    pub fn with_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for tag in tags {
+            let key = format!("tag.{}", tag.as_ref());
+            self.0.insert(Key::from(key.as_str()), bool(true));
+        }
+        self
+    }
+}
+
+impl FactsProvider for StaticFacts {
+    fn facts(&self, _: &Resource, _: &Action) -> Attributes {
+        self.0.clone()
+    }
+}
+
+/// The current time: `time`, in seconds since the Unix epoch, and `hour`,
+/// the hour of the day in UTC
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Clock;
+
+#[cfg(feature = "std")]
+impl FactsProvider for Clock {
+    fn facts(&self, _: &Resource, _: &Action) -> Attributes {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        let mut facts = Attributes::new();
+        facts.insert("time".into(), Value::I(now));
+        facts.insert("hour".into(), Value::I(now / 3600 % 24));
+        facts
+    }
+}
+
+/// The facts providers of a policy environment
+#[derive(Clone, Default)]
+pub struct Facts {
+    providers: Vec<Arc<dyn FactsProvider>>,
+}
+
+impl Debug for Facts {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Facts({} providers)", self.providers.len())
+    }
+}
+
+impl Facts {
+    /// Create an environment without facts
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a provider, whose facts replace those of the same name of the
+    /// providers added before
+    pub fn with_provider<P: FactsProvider>(mut self, provider: P) -> Self {
+        self.register(provider);
+        self
+    }
+
+    /// Add a provider, whose facts replace those of the same name of the
+    /// providers added before
+    pub fn register<P: FactsProvider>(&mut self, provider: P) {
+        self.providers.push(Arc::new(provider));
+    }
+
+    /// The facts about a request for `action` on `resource`, under the
+    /// keys policies reference them with
+    pub fn collect(&self, resource: &Resource, action: &Action) -> Attributes {
+        let mut facts = Attributes::new();
+        for provider in &self.providers {
+            for (name, value) in provider.facts(resource, action) {
+                let key = format!("{}{}", FACT_PREFIX, &*name);
+                facts.insert(Key::from(key.as_str()), value);
+            }
+        }
+        facts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eq, string, Subject};
+
+    #[test]
+    fn policies_reference_facts() {
+        // Only over the inlet ops, on nodes tagged prod
+        let policy = eq("local.inlet", string("ops")).and(&eq("local.tag.prod", bool(true)));
+        let (resource, action) = (Resource::from("ops"), Action::from("r"));
+        let subject = Subject::from(1);
+
+        // The inlet is the resource of the request
+        let inlet = |r: &Resource, _: &Action| {
+            let mut facts = Attributes::new();
+            facts.insert("inlet".into(), string(r.path().as_str()));
+            facts
+        };
+        let prod = Facts::new()
+            .with_provider(StaticFacts::default().with_tags(["edge", "prod"]))
+            .with_provider(inlet);
+        let facts = prod.collect(&resource, &action);
+        assert!(policy.evaluate_with(&subject, &resource, &action, &facts));
+        let facts = prod.collect(&Resource::from("dev"), &action);
+        assert!(!policy.evaluate_with(&subject, &resource, &action, &facts));
+
+        let staging = Facts::new()
+            .with_provider(inlet)
+            .with_provider(StaticFacts::default().with_tags(["staging"]));
+        let facts = staging.collect(&resource, &action);
+        assert!(!policy.evaluate_with(&subject, &resource, &action, &facts));
+
+        // Subject attributes don't pass for facts
+        let subject = subject.with_attributes([
+            ("local.inlet".into(), string("ops")),
+            ("local.tag.prod".into(), bool(true)),
+        ]);
+        assert!(!policy.evaluate(&subject, &resource, &action));
+    }
+}
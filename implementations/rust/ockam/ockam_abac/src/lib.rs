@@ -20,10 +20,12 @@ pub mod error;
 /// An example abac backend
 pub mod mem;
 
+mod facts;
 mod policy;
 mod traits;
 mod types;
 
+pub use facts::*;
 pub use policy::*;
 pub use traits::*;
 pub use types::*;
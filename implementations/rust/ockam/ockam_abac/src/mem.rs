@@ -5,7 +5,7 @@ use core::fmt::{self, Debug, Formatter};
 use super::error::AbacError;
 use super::{
     AbacAttributeStorage, AbacAuthorization, AbacPolicyStorage, Action, Attributes, Conditional,
-    Identity, Key, Resource, Subject, Value,
+    Facts, Identity, Key, Resource, Subject, Value,
 };
 use ockam_core::Result;
 use ockam_core::{
@@ -19,6 +19,8 @@ use ockam_core::{
 pub struct Memory {
     /// [`Inner`] implementation of the ABAC traits
     pub(crate) inner: Arc<RwLock<Inner>>,
+    /// Facts of the environment policies are evaluated in
+    facts: Facts,
 }
 
 impl Debug for Memory {
@@ -32,8 +34,15 @@ impl Memory {
     pub fn new() -> Self {
         Self {
             inner: Arc::new(RwLock::new(Inner::new())),
+            facts: Facts::new(),
         }
     }
+
+    /// Evaluate policies with the facts of these providers.
+    pub fn with_facts(mut self, facts: Facts) -> Self {
+        self.facts = facts;
+        self
+    }
 }
 
 /// `Inner` provides implementations of the [`AbacAttributeStorage`],
@@ -95,11 +104,24 @@ impl Inner {
     }
 
     /// Implementation for [`AbacAuthorization::is_authorized`]
+    #[cfg(test)]
     fn is_authorized(&self, subject: &Subject, resource: &Resource, action: &Action) -> bool {
+        self.is_authorized_with(subject, resource, action, &Attributes::new())
+    }
+
+    /// Implementation for [`AbacAuthorization::is_authorized`], in an
+    /// environment with the given facts
+    fn is_authorized_with(
+        &self,
+        subject: &Subject,
+        resource: &Resource,
+        action: &Action,
+        facts: &Attributes,
+    ) -> bool {
         if let Some(attributes) = self.subjects.get(subject.identifier()) {
             if let Some(policy) = self.get_policy(resource, action) {
                 let subject = subject.clone().with_attributes(attributes.clone());
-                return policy.evaluate(&subject, resource, action);
+                return policy.evaluate_with(&subject, resource, action, facts);
             }
         }
         false
@@ -185,8 +207,9 @@ impl AbacAuthorization for Memory {
         resource: &Resource,
         action: &Action,
     ) -> Result<bool> {
+        let facts = self.facts.collect(resource, action);
         match self.inner.read() {
-            Ok(mem) => Ok(mem.is_authorized(subject, resource, action)),
+            Ok(mem) => Ok(mem.is_authorized_with(subject, resource, action, &facts)),
             Err(_) => Err(AbacError::Read.into()),
         }
     }
@@ -1,4 +1,4 @@
-use crate::{Action, Key, Resource, Subject, Value};
+use crate::{Action, Attributes, Key, Resource, Subject, Value, FACT_PREFIX};
 
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use serde::{Deserialize, Serialize};
@@ -32,19 +32,82 @@ impl Conditional {
     ///
     /// TODO add support for resource, action attributes
     pub fn evaluate(&self, subject: &Subject, resource: &Resource, action: &Action) -> bool {
-        let attrs = subject.attributes();
+        self.evaluate_with(subject, resource, action, &Attributes::new())
+    }
+
+    /// Evaluate Policy for the given [`Subject`], [`Resource`],
+    /// [`Action`], in an environment with the given facts, as collected
+    /// by [`Facts::collect`](crate::Facts::collect).
+    pub fn evaluate_with(
+        &self,
+        subject: &Subject,
+        resource: &Resource,
+        action: &Action,
+        facts: &Attributes,
+    ) -> bool {
+        let get = |k: &Key| {
+            if k.starts_with(FACT_PREFIX) {
+                facts.get(k)
+            } else {
+                subject.attributes().get(k)
+            }
+        };
+        let eval = |c: &Conditional| c.evaluate_with(subject, resource, action, facts);
         match self {
-            Conditional::Eq(k, v) => attrs.get(k).map(|a| a == v).unwrap_or(false),
-            Conditional::Lt(k, v) => attrs.get(k).map(|a| a < v).unwrap_or(false),
-            Conditional::Gt(k, v) => attrs.get(k).map(|a| a > v).unwrap_or(false),
-            Conditional::Not(c) => !c.evaluate(subject, resource, action),
-            Conditional::And(cs) => cs.iter().all(|c| c.evaluate(subject, resource, action)),
-            Conditional::Or(cs) => cs.iter().any(|c| c.evaluate(subject, resource, action)),
+            Conditional::Eq(k, v) => get(k).map(|a| a == v).unwrap_or(false),
+            Conditional::Lt(k, v) => get(k).map(|a| a < v).unwrap_or(false),
+            Conditional::Gt(k, v) => get(k).map(|a| a > v).unwrap_or(false),
+            Conditional::Not(c) => !eval(c),
+            Conditional::And(cs) => cs.iter().all(eval),
+            Conditional::Or(cs) => cs.iter().any(eval),
             Conditional::True => true,
             Conditional::False => false,
         }
     }
 
+    /// Decide on a request like [`Conditional::evaluate_with`], also
+    /// returning the part of the policy the decision follows from, e.g.
+    /// the first condition of an `And` that doesn't hold.
+    pub fn decide_with(
+        &self,
+        subject: &Subject,
+        resource: &Resource,
+        action: &Action,
+        facts: &Attributes,
+    ) -> Decision {
+        let decide = |c: &Conditional| c.decide_with(subject, resource, action, facts);
+        match self {
+            Conditional::Not(c) => {
+                let d = decide(c);
+                Decision {
+                    allowed: !d.allowed,
+                    matched: not(d.matched),
+                }
+            }
+            Conditional::And(cs) => {
+                cs.iter()
+                    .map(decide)
+                    .find(|d| !d.allowed)
+                    .unwrap_or_else(|| Decision {
+                        allowed: true,
+                        matched: self.clone(),
+                    })
+            }
+            Conditional::Or(cs) => cs
+                .iter()
+                .map(decide)
+                .find(|d| d.allowed)
+                .unwrap_or_else(|| Decision {
+                    allowed: false,
+                    matched: self.clone(),
+                }),
+            _ => Decision {
+                allowed: self.evaluate_with(subject, resource, action, facts),
+                matched: self.clone(),
+            },
+        }
+    }
+
     /// Create a new `Conditional::And` with the given `Conditional`.
     pub fn and(&self, other: &Conditional) -> Conditional {
         Conditional::And(vec![self.clone(), other.clone()])
@@ -68,6 +131,15 @@ impl Conditional {
     }
 }
 
+/// The decision of a policy on a request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Decision {
+    /// Whether the request is allowed
+    pub allowed: bool,
+    /// The part of the policy the decision follows from
+    pub matched: Conditional,
+}
+
 /// Create a new [`Conditional::Eq`].
 pub fn eq<K: Into<Key>>(k: K, a: Value) -> Conditional {
     Conditional::Eq(k.into(), a)
@@ -97,3 +169,35 @@ pub fn t() -> Conditional {
 pub fn f() -> Conditional {
     Conditional::False
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bool, string};
+
+    #[test]
+    fn decisions_give_the_expression_they_follow_from() {
+        let admin = eq("role", string("admin"));
+        let prod = eq("local.tag.prod", bool(true));
+        let policy = admin.and(&not(prod.clone()));
+        let (resource, action) = (Resource::from("db"), Action::from("r"));
+        let subject = Subject::from(1).with_attributes([("role".into(), string("admin"))]);
+
+        let d = policy.decide_with(&subject, &resource, &action, &Attributes::new());
+        assert!(d.allowed);
+        assert!(matches!(d.matched, Conditional::And(_)));
+
+        let mut facts = Attributes::new();
+        facts.insert("local.tag.prod".into(), bool(true));
+        let d = policy.decide_with(&subject, &resource, &action, &facts);
+        assert!(!d.allowed);
+        assert!(matches!(d.matched, Conditional::Not(c) if matches!(*c, Conditional::Eq(..))));
+
+        let guest = Subject::from(2).with_attributes([("role".into(), string("guest"))]);
+        let d = admin
+            .or(&prod)
+            .decide_with(&guest, &resource, &action, &facts);
+        assert!(d.allowed);
+        assert!(matches!(d.matched, Conditional::Eq(k, _) if &*k == "local.tag.prod"));
+    }
+}
@@ -2,7 +2,7 @@ use ockam_core::compat::{
     collections::BTreeMap,
     string::{String, ToString},
 };
-use ockam_identity::IdentityIdentifier;
+use ockam_identity::{IdentityIdentifier, IdentitySecureChannelLocalInfo};
 
 use serde::{Deserialize, Serialize};
 
@@ -69,6 +69,26 @@ impl From<IdentityIdentifier> for Subject {
     }
 }
 
+/// The sender of a message received over a secure channel, with the
+/// attributes of its credential. Attributes which are not UTF-8 are left
+/// out.
+impl From<&IdentitySecureChannelLocalInfo> for Subject {
+    fn from(info: &IdentitySecureChannelLocalInfo) -> Self {
+        let attributes = info
+            .attributes()
+            .iter()
+            .filter_map(|(k, v)| {
+                let v = core::str::from_utf8(v).ok()?;
+                Some((Key::from(k.as_str()), string(v)))
+            })
+            .collect();
+        Self {
+            identifier: info.their_identity_id().to_string(),
+            attributes,
+        }
+    }
+}
+
 impl Extend<Attribute> for Subject {
     fn extend<A>(&mut self, attributes: A)
     where
@@ -16,9 +16,12 @@ use std::path::{Path, PathBuf};
 use tracing::{trace, warn};
 use types::AddMember;
 
+use crate::capability::{Capabilities, CAPABILITIES};
+
 use self::types::Enroller;
 
-const MEMBER: &str = "member";
+pub(crate) const MEMBER: &str = "member";
+const MEMBER_CAPABILITIES: &str = "member_capabilities";
 
 /// Schema identifier for a project membership credential.
 ///
@@ -26,6 +29,7 @@ const MEMBER: &str = "member";
 ///
 /// - `project_id` : bytes
 /// - `role`: b"member"
+/// - `capabilities`: what the member may create, if the enroller restricted it
 pub const PROJECT_MEMBER_SCHEMA: SchemaId = SchemaId(1);
 pub const PROJECT_ID: &str = "project_id";
 pub const ROLE: &str = "role";
@@ -98,10 +102,21 @@ where
                 ["members"] => match self.check_enroller(&req, from).await {
                     Ok(None) => {
                         let add: AddMember = dec.decode()?;
+                        let key_id = add.member().key_id();
+                        if let Some(c) = add.capabilities() {
+                            if let Err(e) = Capabilities::from_attribute(c.as_bytes()) {
+                                let res = api::bad_request(&req, &e).to_vec()?;
+                                return Ok(res);
+                            }
+                            let c = minicbor::to_vec(c)?;
+                            self.store
+                                .set(key_id, MEMBER_CAPABILITIES.to_string(), c)
+                                .await?;
+                        } else {
+                            self.store.del(key_id, MEMBER_CAPABILITIES).await?;
+                        }
                         let tru = minicbor::to_vec(true)?;
-                        self.store
-                            .set(add.member().key_id(), MEMBER.to_string(), tru)
-                            .await?;
+                        self.store.set(key_id, MEMBER.to_string(), tru).await?;
                         Response::ok(req.id()).to_vec()?
                     }
                     Ok(Some(e)) => e.to_vec()?,
@@ -110,10 +125,18 @@ where
                 // Member wants a credential.
                 ["credential"] => match self.check_member(&req, from).await {
                     Ok(None) => {
-                        let crd = Credential::builder(from.clone())
+                        let capabilities: Option<String> =
+                            match self.store.get(from.key_id(), MEMBER_CAPABILITIES).await? {
+                                Some(c) => Some(minicbor::decode(&c)?),
+                                None => None,
+                            };
+                        let mut crd = Credential::builder(from.clone())
                             .with_schema(PROJECT_MEMBER_SCHEMA)
                             .with_attribute(PROJECT_ID, &self.project)
                             .with_attribute(ROLE, b"member");
+                        if let Some(c) = &capabilities {
+                            crd = crd.with_attribute(CAPABILITIES, c.as_bytes());
+                        }
 
                         let crd = self.ident.issue_credential(crd).await?;
                         Response::ok(req.id()).body(crd).to_vec()?
@@ -209,7 +232,21 @@ impl Client {
     }
 
     pub async fn add_member(&mut self, id: IdentityIdentifier) -> Result<()> {
-        let req = Request::post("/members").body(AddMember::new(id));
+        self.add(AddMember::new(id)).await
+    }
+
+    /// Add a member which may only create what `capabilities` allow.
+    pub async fn add_member_with_capabilities(
+        &mut self,
+        id: IdentityIdentifier,
+        capabilities: &Capabilities,
+    ) -> Result<()> {
+        self.add(AddMember::new(id).with_capabilities(capabilities))
+            .await
+    }
+
+    async fn add(&mut self, member: AddMember) -> Result<()> {
+        let req = Request::post("/members").body(member);
         self.buf = self.request("add-member", "add_member", &req).await?;
         assert_response_match(None, &self.buf);
         let mut d = Decoder::new(&self.buf);
@@ -1,3 +1,4 @@
+use crate::capability::Capabilities;
 use minicbor::{Decode, Encode};
 use ockam_identity::IdentityIdentifier;
 use serde::{Deserialize, Serialize};
@@ -11,7 +12,10 @@ use ockam_core::TypeTag;
 pub struct AddMember {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<2820828>,
-    #[n(1)] member: IdentityIdentifier
+    #[n(1)] member: IdentityIdentifier,
+    /// What the member may create, as the value of the
+    /// [`CAPABILITIES`](crate::capability::CAPABILITIES) attribute
+    #[n(2)] capabilities: Option<String>,
 }
 
 impl AddMember {
@@ -20,12 +24,23 @@ impl AddMember {
             #[cfg(feature = "tag")]
             tag: TypeTag,
             member,
+            capabilities: None,
         }
     }
 
+    /// Only let the member create what `capabilities` allow.
+    pub fn with_capabilities(mut self, capabilities: &Capabilities) -> Self {
+        self.capabilities = Some(capabilities.to_string());
+        self
+    }
+
     pub fn member(&self) -> &IdentityIdentifier {
         &self.member
     }
+
+    pub fn capabilities(&self) -> Option<&str> {
+        self.capabilities.as_deref()
+    }
 }
 
 #[derive(Debug, Default, Deserialize, Serialize)]
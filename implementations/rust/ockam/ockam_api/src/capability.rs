@@ -0,0 +1,220 @@
+//! Capabilities granted to the members of a project.
+//!
+//! An enroller may restrict what a member is allowed to create on the
+//! nodes of a project, e.g. only a forwarder named after the device and an
+//! outlet to its SSH port. The capabilities are carried by the credential
+//! of the member, in the [`CAPABILITIES`] attribute, and enforced by the
+//! [`Delegation`](crate::delegation::Delegation) service of a node.
+
+use crate::nodes::models::forwarder::CreateForwarder;
+use crate::nodes::models::portal::CreateOutlet;
+use core::fmt;
+use core::str::FromStr;
+use minicbor::Decoder;
+use ockam_core::api::{Method, Request};
+
+/// Credential attribute holding the capabilities of a member, as text
+/// separated by commas, e.g. `forwarder:device-42,outlet:22`.
+pub const CAPABILITIES: &str = "capabilities";
+
+/// Something a member is allowed to create.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Capability {
+    /// A forwarder with this alias, a trailing `*` matching any suffix.
+    Forwarder(String),
+    /// An outlet to this `host:port`. Given as a port only, the host is the
+    /// one running the node.
+    Outlet(String),
+}
+
+impl Capability {
+    fn allows(&self, req: &Request, dec: &mut Decoder) -> bool {
+        use Method::*;
+        match (self, req.method(), req.path_segments::<3>().as_slice()) {
+            (Capability::Forwarder(pattern), Some(Post), ["node", "forwarder"]) => {
+                match dec.decode::<CreateForwarder>() {
+                    Ok(body) => body.alias().map_or(false, |a| matches(pattern, a)),
+                    Err(_) => false,
+                }
+            }
+            (Capability::Outlet(target), Some(Post), ["node", "outlet"]) => {
+                match dec.decode::<CreateOutlet>() {
+                    // A proxy outlet reaches more than its own target
+                    Ok(body) => body.egress.is_none() && is_same_target(target, &body.tcp_addr),
+                    Err(_) => false,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Capability::Forwarder(alias) => write!(f, "forwarder:{alias}"),
+            Capability::Outlet(target) => write!(f, "outlet:{target}"),
+        }
+    }
+}
+
+impl FromStr for Capability {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s
+            .split_once(':')
+            .ok_or_else(|| format!("'{s}' is not a capability, e.g. forwarder:<ALIAS>"))?;
+        if value.is_empty() {
+            return Err(format!("capability '{s}' has no value"));
+        }
+        match kind {
+            "forwarder" if value.contains(',') => Err(format!("invalid forwarder alias '{value}'")),
+            "forwarder" => Ok(Capability::Forwarder(value.to_string())),
+            "outlet" => {
+                let port = value.rsplit(':').next().unwrap_or(value);
+                match port.parse::<u16>() {
+                    Ok(_) => Ok(Capability::Outlet(value.to_string())),
+                    Err(_) => Err(format!("invalid outlet port in '{s}'")),
+                }
+            }
+            _ => Err(format!(
+                "unknown capability '{kind}', expected forwarder or outlet"
+            )),
+        }
+    }
+}
+
+/// The capabilities of a member.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities(Vec<Capability>);
+
+impl Capabilities {
+    pub fn new(capabilities: Vec<Capability>) -> Self {
+        Self(capabilities)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Parse the value of the [`CAPABILITIES`] attribute.
+    pub fn from_attribute(value: &[u8]) -> Result<Self, String> {
+        let s = core::str::from_utf8(value).map_err(|e| e.to_string())?;
+        s.split(',')
+            .filter(|c| !c.is_empty())
+            .map(Capability::from_str)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    /// The value of the [`CAPABILITIES`] attribute.
+    pub fn to_attribute(&self) -> Vec<u8> {
+        self.to_string().into_bytes()
+    }
+
+    /// Check if one of the capabilities allows the request `req`, whose
+    /// body is read from `dec`.
+    pub fn allows(&self, req: &Request, dec: &Decoder) -> bool {
+        self.0.iter().any(|c| c.allows(req, &mut dec.clone()))
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for c in &self.0 {
+            if !first {
+                f.write_str(",")?;
+            }
+            write!(f, "{c}")?;
+            first = false;
+        }
+        Ok(())
+    }
+}
+
+fn matches(pattern: &str, alias: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => alias.starts_with(prefix),
+        None => pattern == alias,
+    }
+}
+
+/// Compare the target of an outlet capability with the one requested.
+fn is_same_target(allowed: &str, requested: &str) -> bool {
+    const LOCAL_HOSTS: [&str; 3] = ["127.0.0.1", "localhost", "[::1]"];
+    let (host, port) = match requested.rsplit_once(':') {
+        Some(hp) => hp,
+        None => return false,
+    };
+    match allowed.rsplit_once(':') {
+        Some(_) => allowed == requested,
+        None => allowed == port && LOCAL_HOSTS.contains(&host),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_multiaddr::MultiAddr;
+
+    fn allows(caps: &str, req: &[u8]) -> bool {
+        let caps = Capabilities::from_attribute(caps.as_bytes()).unwrap();
+        let mut dec = Decoder::new(req);
+        let req: Request = dec.decode().unwrap();
+        caps.allows(&req, &dec)
+    }
+
+    fn create_forwarder(alias: Option<&str>) -> Vec<u8> {
+        let addr = MultiAddr::from_str("/dnsaddr/relay.example.com/tcp/4000").unwrap();
+        let body = CreateForwarder::at_node(addr, alias.map(|a| a.to_string()), false, vec![]);
+        Request::post("/node/forwarder")
+            .body(body)
+            .to_vec()
+            .unwrap()
+    }
+
+    fn create_outlet(target: &str) -> Vec<u8> {
+        let body = CreateOutlet::new(target, "outlet", None, false);
+        Request::post("/node/outlet").body(body).to_vec().unwrap()
+    }
+
+    #[test]
+    fn capabilities_round_trip() {
+        let s = "forwarder:device-*,outlet:22,outlet:db.local:5432";
+        let caps = Capabilities::from_attribute(s.as_bytes()).unwrap();
+        assert_eq!(caps.to_attribute(), s.as_bytes());
+        assert!(Capabilities::from_attribute(b"inlet:22").is_err());
+        assert!(Capabilities::from_attribute(b"outlet:ssh").is_err());
+        assert!(Capabilities::from_attribute(b"forwarder:").is_err());
+    }
+
+    #[test]
+    fn only_granted_requests_are_allowed() {
+        let caps = "forwarder:device-42,outlet:22";
+        assert!(allows(caps, &create_forwarder(Some("device-42"))));
+        assert!(!allows(caps, &create_forwarder(Some("device-43"))));
+        assert!(!allows(caps, &create_forwarder(None)));
+        assert!(allows(caps, &create_outlet("127.0.0.1:22")));
+        assert!(!allows(caps, &create_outlet("127.0.0.1:80")));
+        assert!(!allows(caps, &create_outlet("10.0.0.1:22")));
+        let proxy = CreateOutlet::new("127.0.0.1:22", "outlet", None, false)
+            .with_egress(vec!["*:*".into()]);
+        let proxy = Request::post("/node/outlet").body(proxy).to_vec().unwrap();
+        assert!(!allows(caps, &proxy));
+        assert!(!allows(
+            caps,
+            &Request::get("/node/forwarder").to_vec().unwrap()
+        ));
+
+        assert!(allows(
+            "forwarder:device-*",
+            &create_forwarder(Some("device-1"))
+        ));
+        assert!(allows(
+            "outlet:db.local:5432",
+            &create_outlet("db.local:5432")
+        ));
+    }
+}
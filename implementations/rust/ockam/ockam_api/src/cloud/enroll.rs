@@ -24,6 +24,7 @@ pub enum AuthenticateToken<'a> {
     EnrollmentToken(enrollment_token::AuthenticateEnrollmentToken<'a>),
 }
 
+#[cfg(feature = "cloud")]
 mod node {
     use minicbor::Decoder;
     use tracing::trace;
@@ -37,6 +38,8 @@ mod node {
         AuthenticateEnrollmentToken, EnrollmentToken, RequestEnrollmentToken,
     };
     use crate::cloud::CloudRequestWrapper;
+    use crate::cloud::CloudService;
+    use crate::nodes::service::progress::Progress;
     use crate::nodes::NodeManager;
     use ockam_identity::credential::Attributes;
 
@@ -44,12 +47,14 @@ mod node {
 
     const TARGET: &str = "ockam_api::cloud::enroll";
 
-    impl NodeManager {
+    impl CloudService {
         /// Executes an enrollment process to generate a new set of access tokens using the auth0 flow.
         pub(crate) async fn enroll_auth0(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
+            progress: &Progress,
         ) -> Result<Vec<u8>> {
             let req_wrapper: CloudRequestWrapper<AuthenticateAuth0Token> = dec.decode()?;
             let cloud_route = req_wrapper.route()?;
@@ -57,12 +62,14 @@ mod node {
             let req_body = AuthenticateToken::Auth0(req_body);
 
             trace!(target: TARGET, "executing auth0 flow");
-            self.authenticate_token(ctx, cloud_route, req_body).await
+            self.authenticate_token(node, ctx, cloud_route, req_body, progress)
+                .await
         }
 
         /// Generates a token that will be associated to the passed attributes.
         pub(crate) async fn generate_enrollment_token(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
         ) -> Result<Vec<u8>> {
@@ -76,6 +83,7 @@ mod node {
 
             let req_builder = Request::post("v0/").body(req_body);
             self.request_controller(
+                node,
                 ctx,
                 label,
                 "request_enrollment_token",
@@ -88,9 +96,11 @@ mod node {
 
         /// Authenticates a token generated by `generate_enrollment_token`.
         pub(crate) async fn authenticate_enrollment_token(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
+            progress: &Progress,
         ) -> Result<Vec<u8>> {
             let req_wrapper: CloudRequestWrapper<EnrollmentToken> = dec.decode()?;
             let cloud_route = req_wrapper.route()?;
@@ -99,14 +109,17 @@ mod node {
                 AuthenticateToken::EnrollmentToken(AuthenticateEnrollmentToken::new(req_body));
 
             trace!(target: TARGET, "authenticating token");
-            self.authenticate_token(ctx, cloud_route, req_body).await
+            self.authenticate_token(node, ctx, cloud_route, req_body, progress)
+                .await
         }
 
         async fn authenticate_token(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             cloud_route: Route,
             body: AuthenticateToken<'_>,
+            progress: &Progress,
         ) -> Result<Vec<u8>> {
             // TODO: add AuthenticateAuth0Token to schema.cddl and use it here
             let schema = None;
@@ -115,26 +128,30 @@ mod node {
                 AuthenticateToken::Auth0(body) => {
                     api_service = "auth0_authenticator";
                     let req_builder = Request::post("v0/enroll").body(body);
-                    self.request_controller(
+                    self.request_controller_with_progress(
+                        node,
                         ctx,
                         api_service,
                         schema,
                         cloud_route,
                         api_service,
                         req_builder,
+                        progress,
                     )
                     .await
                 }
                 AuthenticateToken::EnrollmentToken(body) => {
                     api_service = "enrollment_token_authenticator";
                     let req_builder = Request::post("v0/enroll").body(body);
-                    self.request_controller(
+                    self.request_controller_with_progress(
+                        node,
                         ctx,
                         api_service,
                         schema,
                         cloud_route,
                         api_service,
                         req_builder,
+                        progress,
                     )
                     .await
                 }
@@ -259,7 +276,8 @@ pub mod enrollment_token {
     }
 
     #[derive(Encode, Debug)]
-    #[cfg_attr(test, derive(Decode, Clone))]
+    #[cfg_attr(any(test, feature = "mock-orchestrator"), derive(Decode))]
+    #[cfg_attr(test, derive(Clone))]
     #[rustfmt::skip]
     #[cbor(map)]
     pub struct AuthenticateEnrollmentToken<'a> {
@@ -0,0 +1,570 @@
+//! In-memory test double of the Ockam Orchestrator, to run the CLI and the
+//! node manager against locally, without network access nor accounts.
+//!
+//! The mock node plays every role: it is the controller (spaces, projects,
+//! enrollment), the project node (relays) and the project authority. It
+//! hosts a single project, whose authority is a direct authenticator.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use minicbor::Decoder;
+use ockam::ForwardingService;
+use ockam_core::api::{self, Method, Request, Response};
+use ockam_core::compat::rand::random;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::vault::{
+    SecretAttributes, SecretKey, SecretPersistence, SecretType, CURVE25519_SECRET_LENGTH_U32,
+};
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+use ockam_core::{self, AsyncTryClone, CowStr, Result, Routed, Worker};
+use ockam_identity::authenticated_storage::mem::InMemoryStorage;
+use ockam_identity::authenticated_storage::AuthenticatedStorage;
+use ockam_identity::credential::Timestamp;
+use ockam_identity::{
+    Identity, IdentityIdentifier, IdentitySecureChannelLocalInfo, IdentityVault,
+    TrustEveryonePolicy,
+};
+use ockam_multiaddr::MultiAddr;
+use ockam_node::Context;
+use tracing::trace;
+
+use crate::authenticator::direct::{self, MEMBER};
+use crate::cloud::enroll::enrollment_token::{AuthenticateEnrollmentToken, EnrollmentToken};
+use crate::cloud::enroll::Token;
+use crate::cloud::project::{AddEnroller, CreateProject, Enroller, Project};
+use crate::cloud::space::{CreateSpace, Space};
+use crate::cloud::subscription::Subscription;
+use crate::error::ApiError;
+use crate::DefaultAddress;
+
+const TARGET: &str = "ockam_api::cloud::mock";
+
+/// Name of the file listing the enrollers of the project.
+const ENROLLERS_FILE: &str = "enrollers.json";
+
+/// The services of the Orchestrator the controller requests are sent to.
+const SERVICES: [&str; 5] = [
+    "spaces",
+    "projects",
+    "auth0_authenticator",
+    "enrollment_token_authenticator",
+    "subscriptions",
+];
+
+/// Address of the secure channel listener of the project authority.
+const AUTHORITY_LISTENER: &str = "authority_api";
+
+/// A running mock Orchestrator.
+pub struct MockOrchestrator {
+    mock: Arc<Mock>,
+}
+
+impl MockOrchestrator {
+    /// Start the mock on the node of `ctx` with `identity`, reachable from
+    /// other nodes at `access_route`, e.g. `/ip4/127.0.0.1/tcp/6252` for a
+    /// TCP listener of the node. The enrollers of the project are kept in `dir`.
+    pub async fn start<V: IdentityVault>(
+        ctx: &Context,
+        identity: Identity<V>,
+        access_route: &MultiAddr,
+        dir: &Path,
+    ) -> Result<Self> {
+        let authority = identity.async_try_clone().await?;
+        Self::start_with_authority(ctx, identity, authority, access_route, dir).await
+    }
+
+    /// Start the mock like [`MockOrchestrator::start`], with `authority` as
+    /// the identity of the project authority.
+    pub async fn start_with_authority<V: IdentityVault>(
+        ctx: &Context,
+        identity: Identity<V>,
+        authority: Identity<V>,
+        access_route: &MultiAddr,
+        dir: &Path,
+    ) -> Result<Self> {
+        let storage = InMemoryStorage::new();
+        // The project is identified by its authority
+        let project_id = authority.identifier().key_id()[1..33].to_string();
+        let enrollers = dir.join(ENROLLERS_FILE);
+        std::fs::create_dir_all(dir).map_err(|e| ApiError::generic(&e.to_string()))?;
+        write_enrollers(&enrollers, &BTreeMap::new())?;
+
+        let mock = Arc::new(Mock {
+            state: Mutex::new(State::default()),
+            storage: storage.clone(),
+            project_id: project_id.clone(),
+            identifier: identity.identifier().clone(),
+            authority: hex::encode(authority.export().await?),
+            access_route: format!(
+                "{access_route}/service/{}",
+                DefaultAddress::SECURE_CHANNEL_LISTENER
+            ),
+            authority_access_route: format!("{access_route}/service/{AUTHORITY_LISTENER}"),
+            enrollers: enrollers.clone(),
+        });
+        for service in SERVICES {
+            let worker = MockService {
+                service,
+                mock: mock.clone(),
+            };
+            ctx.start_worker(service, worker).await?;
+        }
+        ForwardingService::create(ctx).await?;
+        identity
+            .create_secure_channel_listener(
+                DefaultAddress::SECURE_CHANNEL_LISTENER,
+                TrustEveryonePolicy,
+                &storage,
+            )
+            .await?;
+
+        authority
+            .create_secure_channel_listener(AUTHORITY_LISTENER, TrustEveryonePolicy, &storage)
+            .await?;
+        let server = direct::Server::new(
+            project_id.as_bytes().to_vec(),
+            storage,
+            &enrollers,
+            authority,
+        );
+        ctx.start_worker(DefaultAddress::AUTHENTICATOR, server)
+            .await?;
+
+        Ok(MockOrchestrator { mock })
+    }
+
+    /// The identifier to set as `OCKAM_CONTROLLER_IDENTITY_ID`.
+    pub fn identifier(&self) -> &IdentityIdentifier {
+        &self.mock.identifier
+    }
+
+    /// The id of the single project the mock may host.
+    pub fn project_id(&self) -> &str {
+        &self.mock.project_id
+    }
+
+    /// Create the project, and the space it belongs to, without going
+    /// through the controller API, e.g. to start with a project.
+    pub fn create_project(&self, space_name: &str, project_name: &str) -> Result<Project<'static>> {
+        let mut state = self.mock.state.lock().unwrap();
+        if state.project.is_some() {
+            return Err(ApiError::generic(
+                "the mock orchestrator hosts a single project",
+            ));
+        }
+        let space = Space {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            id: CowStr::from(space_name.to_string()),
+            name: CowStr::from(space_name.to_string()),
+            users: Vec::new(),
+        };
+        let create = CreateProject::new::<_, String>(project_name, None, &[], &[]);
+        let project = self.mock.project(&space, &create);
+        state.spaces.insert(space_name.to_string(), space);
+        state.project = Some(project.clone());
+        Ok(project)
+    }
+
+    /// Let `enroller` add members to the project.
+    pub fn add_enroller(&self, enroller: &IdentityIdentifier) -> Result<()> {
+        let add = AddEnroller::new(enroller.to_string(), None);
+        self.mock.add_enroller(&self.mock.identifier, add)?;
+        Ok(())
+    }
+}
+
+struct Mock {
+    state: Mutex<State>,
+    /// Attributes of the members, shared with the authority
+    storage: InMemoryStorage,
+    project_id: String,
+    identifier: IdentityIdentifier,
+    /// Exported identity of the project authority
+    authority: String,
+    /// Route of the project node
+    access_route: String,
+    authority_access_route: String,
+    enrollers: PathBuf,
+}
+
+#[derive(Default)]
+struct State {
+    spaces: BTreeMap<String, Space<'static>>,
+    project: Option<Project<'static>>,
+    enrollers: BTreeMap<String, Enroller<'static>>,
+    tokens: BTreeSet<String>,
+}
+
+struct MockService {
+    service: &'static str,
+    mock: Arc<Mock>,
+}
+
+#[ockam_core::worker]
+impl Worker for MockService {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, c: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        let mut dec = Decoder::new(m.as_body());
+        let req: Request = dec.decode()?;
+        let res = match IdentitySecureChannelLocalInfo::find_info(m.local_message()) {
+            Ok(i) => {
+                self.on_request(i.their_identity_id(), &req, &mut dec)
+                    .await?
+            }
+            Err(_) => api::forbidden(&req, "secure channel required").to_vec()?,
+        };
+        c.send(m.return_route(), res).await
+    }
+}
+
+impl MockService {
+    async fn on_request(
+        &self,
+        from: &IdentityIdentifier,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        trace! {
+            target: TARGET,
+            service = %self.service,
+            from    = %from,
+            method  = ?req.method(),
+            path    = %req.path(),
+            "request"
+        }
+        let method = match req.method() {
+            Some(m) => m,
+            None => return Ok(api::invalid_method(req).to_vec()?),
+        };
+        let path = req.path_segments::<5>();
+        let res = match (self.service, method, path.as_slice()) {
+            // ==*== Spaces ==*==
+            ("spaces", Method::Post, ["v0"] | ["v0", ""]) => {
+                let create: CreateSpace = dec.decode()?;
+                let space = Space {
+                    #[cfg(feature = "tag")]
+                    tag: TypeTag,
+                    id: random_id(),
+                    name: create.name.to_owned(),
+                    users: create.users.iter().map(|u| u.to_owned()).collect(),
+                };
+                let mut state = self.mock.state.lock().unwrap();
+                state.spaces.insert(space.id.to_string(), space.clone());
+                Response::ok(req.id()).body(space).to_vec()?
+            }
+            ("spaces", Method::Get, ["v0"] | ["v0", ""]) => {
+                let state = self.mock.state.lock().unwrap();
+                let spaces: Vec<_> = state.spaces.values().collect();
+                Response::ok(req.id()).body(spaces).to_vec()?
+            }
+            ("spaces", Method::Get, ["v0", id]) => {
+                let state = self.mock.state.lock().unwrap();
+                match state.spaces.get(*id) {
+                    Some(space) => Response::ok(req.id()).body(space).to_vec()?,
+                    None => Response::not_found(req.id()).to_vec()?,
+                }
+            }
+            ("spaces", Method::Delete, ["v0", id]) => {
+                let mut state = self.mock.state.lock().unwrap();
+                if state.spaces.remove(*id).is_none() {
+                    return Ok(Response::not_found(req.id()).to_vec()?);
+                }
+                if state.project.as_ref().map_or(false, |p| p.space_id == *id) {
+                    state.project = None
+                }
+                Response::ok(req.id()).to_vec()?
+            }
+
+            // ==*== Projects ==*==
+            ("projects", Method::Post, ["v0"] | ["v0", ""]) => {
+                // Enrollment tokens are redeemed against any project
+                let token = random_id();
+                let mut state = self.mock.state.lock().unwrap();
+                state.tokens.insert(token.to_string());
+                let token = EnrollmentToken::new(Token::new(token));
+                Response::ok(req.id()).body(token).to_vec()?
+            }
+            ("projects", Method::Post, ["v0", space_id]) => {
+                let create: CreateProject = dec.decode()?;
+                let project = {
+                    let mut state = self.mock.state.lock().unwrap();
+                    let space = match state.spaces.get(*space_id) {
+                        Some(space) => space,
+                        None => return Ok(Response::not_found(req.id()).to_vec()?),
+                    };
+                    if state.project.is_some() {
+                        let msg = "the mock orchestrator hosts a single project";
+                        return Ok(api::bad_request(req, msg).to_vec()?);
+                    }
+                    let project = self.mock.project(space, &create);
+                    state.project = Some(project.clone());
+                    project
+                };
+                // The creator of a project administers it
+                self.mock
+                    .add_enroller(from, AddEnroller::new(from.to_string(), None))?;
+                Response::ok(req.id()).body(project).to_vec()?
+            }
+            ("projects", Method::Get, ["v0"] | ["v0", ""]) => {
+                let state = self.mock.state.lock().unwrap();
+                let projects: Vec<_> = state.project.iter().collect();
+                Response::ok(req.id()).body(projects).to_vec()?
+            }
+            ("projects", Method::Get, ["v0", id]) => match self.mock.project_with_id(id) {
+                Some(project) => Response::ok(req.id()).body(project).to_vec()?,
+                None => Response::not_found(req.id()).to_vec()?,
+            },
+            ("projects", Method::Delete, ["v0", _space_id, id]) => {
+                if self.mock.project_with_id(id).is_none() {
+                    return Ok(Response::not_found(req.id()).to_vec()?);
+                }
+                let mut state = self.mock.state.lock().unwrap();
+                state.project = None;
+                state.enrollers.clear();
+                write_enrollers(&self.mock.enrollers, &state.enrollers)?;
+                Response::ok(req.id()).to_vec()?
+            }
+            ("projects", Method::Post, ["v0", id, "enrollers"]) => {
+                if self.mock.project_with_id(id).is_none() {
+                    return Ok(Response::not_found(req.id()).to_vec()?);
+                }
+                let add: AddEnroller = dec.decode()?;
+                let enroller = self.mock.add_enroller(from, add)?;
+                Response::ok(req.id()).body(enroller).to_vec()?
+            }
+            ("projects", Method::Get, ["v0", id, "enrollers"]) => {
+                if self.mock.project_with_id(id).is_none() {
+                    return Ok(Response::not_found(req.id()).to_vec()?);
+                }
+                let state = self.mock.state.lock().unwrap();
+                let enrollers: Vec<_> = state.enrollers.values().collect();
+                Response::ok(req.id()).body(enrollers).to_vec()?
+            }
+            ("projects", Method::Delete, ["v0", id, "enrollers", enroller]) => {
+                if self.mock.project_with_id(id).is_none() {
+                    return Ok(Response::not_found(req.id()).to_vec()?);
+                }
+                let mut state = self.mock.state.lock().unwrap();
+                state.enrollers.remove(*enroller);
+                write_enrollers(&self.mock.enrollers, &state.enrollers)?;
+                Response::ok(req.id()).to_vec()?
+            }
+
+            // ==*== Enroll ==*==
+            // Any Auth0 token is accepted, there are no accounts
+            ("auth0_authenticator", Method::Post, ["v0", "enroll"]) => {
+                Response::ok(req.id()).to_vec()?
+            }
+            ("enrollment_token_authenticator", Method::Post, ["v0", "enroll"]) => {
+                let auth: AuthenticateEnrollmentToken = dec.decode()?;
+                let redeemed = {
+                    let mut state = self.mock.state.lock().unwrap();
+                    state.tokens.remove(auth.token.0.as_ref())
+                };
+                if !redeemed {
+                    return Ok(api::forbidden(req, "unknown enrollment token").to_vec()?);
+                }
+                let member = minicbor::to_vec(true)?;
+                self.mock
+                    .storage
+                    .set(from.key_id(), MEMBER.to_string(), member)
+                    .await?;
+                Response::ok(req.id()).to_vec()?
+            }
+
+            // ==*== Subscriptions ==*==
+            ("subscriptions", Method::Get, ["v0"] | ["v0", ""]) => {
+                let subscriptions: Vec<Subscription> = Vec::new();
+                Response::ok(req.id()).body(subscriptions).to_vec()?
+            }
+            ("subscriptions", ..) => {
+                let msg = "subscriptions are not supported by the mock orchestrator";
+                api::bad_request(req, msg).to_vec()?
+            }
+
+            _ => api::unknown_path(req).to_vec()?,
+        };
+        Ok(res)
+    }
+}
+
+impl Mock {
+    fn project(&self, space: &Space, create: &CreateProject) -> Project<'static> {
+        Project {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            id: CowStr::from(self.project_id.clone()),
+            name: create.name.to_owned(),
+            space_name: space.name.to_owned(),
+            services: create.services.iter().map(|s| s.to_owned()).collect(),
+            access_route: CowStr::from(self.access_route.clone()),
+            users: create.users.iter().map(|u| u.to_owned()).collect(),
+            space_id: space.id.to_owned(),
+            identity: Some(self.identifier.clone()),
+            authority_access_route: Some(CowStr::from(self.authority_access_route.clone())),
+            authority_identity: Some(CowStr::from(self.authority.clone())),
+            relay_access_routes: None,
+        }
+    }
+
+    fn project_with_id(&self, id: &str) -> Option<Project<'static>> {
+        let state = self.state.lock().unwrap();
+        state.project.clone().filter(|p| p.id == id)
+    }
+
+    /// Add an enroller to the project, which the authority picks up on its
+    /// next request.
+    fn add_enroller(
+        &self,
+        from: &IdentityIdentifier,
+        add: AddEnroller,
+    ) -> Result<Enroller<'static>> {
+        let created_at = Timestamp::now().map(u64::from).unwrap_or_default();
+        let enroller = Enroller {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            identity_id: add.identity_id.to_owned(),
+            description: add.description.map(|d| d.to_owned()),
+            added_by: CowStr::from(from.to_string()),
+            created_at: CowStr::from(created_at.to_string()),
+        };
+        let mut state = self.state.lock().unwrap();
+        state
+            .enrollers
+            .insert(enroller.identity_id.to_string(), enroller.clone());
+        write_enrollers(&self.enrollers, &state.enrollers)?;
+        Ok(enroller)
+    }
+}
+
+/// Create the identity derived from `seed`, always the same one. Anyone
+/// knowing the seed has its secret key: only use it for tests and demos.
+pub async fn seeded_identity<V: IdentityVault>(
+    ctx: &Context,
+    vault: &V,
+    seed: &str,
+) -> Result<Identity<V>> {
+    let secret = vault.sha256(seed.as_bytes()).await?;
+    let attributes = SecretAttributes::new(
+        SecretType::Ed25519,
+        SecretPersistence::Persistent,
+        CURVE25519_SECRET_LENGTH_U32,
+    );
+    let key = vault
+        .secret_import(SecretKey::new(secret.to_vec()), attributes)
+        .await?;
+    Identity::create_with_key(ctx, vault, &key).await
+}
+
+fn random_id() -> CowStr<'static> {
+    CowStr::from(hex::encode(random::<[u8; 16]>()))
+}
+
+/// Write the enrollers in the format read by the direct authenticator.
+fn write_enrollers(path: &Path, enrollers: &BTreeMap<String, Enroller>) -> Result<()> {
+    let mut ids = HashMap::new();
+    for id in enrollers.keys() {
+        let id: IdentityIdentifier = id.parse()?;
+        ids.insert(id, direct::types::Enroller::default());
+    }
+    let contents = serde_json::to_string(&ids).map_err(|e| ApiError::generic(&e.to_string()))?;
+    std::fs::write(path, contents).map_err(|e| ApiError::generic(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::enroll::enrollment_token::RequestEnrollmentToken;
+    use ockam::vault::Vault;
+    use ockam_core::route;
+    use ockam_identity::credential::Attributes;
+    use ockam_identity::TrustIdentifierPolicy;
+    use ockam_multiaddr::proto::Service;
+
+    /// Send `req` to `route`, returning the body of the successful response.
+    async fn call(ctx: &Context, route: ockam_core::Route, req: Vec<u8>) -> Result<Vec<u8>> {
+        let buf: Vec<u8> = ctx.send_and_receive(route, req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(api::Status::Ok));
+        Ok(buf[dec.position()..].to_vec())
+    }
+
+    #[ockam_macros::test]
+    async fn create_a_project_and_enroll_a_member(ctx: &mut Context) -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::create();
+        let orchestrator = Identity::create(ctx, &vault).await?;
+        let mut route = MultiAddr::default();
+        route.push_back(Service::new("mock"))?;
+        let mock = MockOrchestrator::start(ctx, orchestrator, &route, dir.path()).await?;
+        let storage = InMemoryStorage::new();
+
+        // An administrator creates the project
+        let admin = Identity::create(ctx, &vault).await?;
+        let policy = TrustIdentifierPolicy::new(mock.identifier().clone());
+        let sc = admin
+            .create_secure_channel(DefaultAddress::SECURE_CHANNEL_LISTENER, policy, &storage)
+            .await?;
+        let req = Request::post("/v0/").body(CreateSpace::new("space", &["admin"]));
+        let buf = call(ctx, route![sc.clone(), "spaces"], req.to_vec()?).await?;
+        let space: Space = minicbor::decode(&buf)?;
+        let create = CreateProject::new("default", None, &["admin"], &[]);
+        let req = Request::post(format!("/v0/{}", space.id)).body(create);
+        let buf = call(ctx, route![sc.clone(), "projects"], req.to_vec()?).await?;
+        let project: Project = minicbor::decode(&buf)?;
+        assert_eq!(project.id, mock.project_id());
+        assert_eq!(project.access_route, "/service/mock/service/api");
+        assert!(project.is_ready());
+
+        // Only one project is hosted
+        let create = CreateProject::new("other", None, &["admin"], &[]);
+        let req = Request::post(format!("/v0/{}", space.id)).body(create);
+        let buf: Vec<u8> = ctx
+            .send_and_receive(route![sc.clone(), "projects"], req.to_vec()?)
+            .await?;
+        let res: Response = minicbor::decode(&buf)?;
+        assert_eq!(res.status(), Some(api::Status::BadRequest));
+
+        let req = Request::post("v0/").body(RequestEnrollmentToken::new(Attributes::new()));
+        let buf = call(ctx, route![sc, "projects"], req.to_vec()?).await?;
+        let token: EnrollmentToken = minicbor::decode(&buf)?;
+
+        // A member redeems the token, then gets a credential from the authority
+        let member = Identity::create(ctx, &vault).await?;
+        let policy = TrustIdentifierPolicy::new(mock.identifier().clone());
+        let sc = member
+            .create_secure_channel(DefaultAddress::SECURE_CHANNEL_LISTENER, policy, &storage)
+            .await?;
+        let mut client =
+            direct::Client::new(route![sc.clone(), DefaultAddress::AUTHENTICATOR], ctx).await?;
+        assert!(client.credential().await.is_err());
+        let auth = AuthenticateEnrollmentToken::new(token);
+        let req = Request::post("v0/enroll").body(auth);
+        call(
+            ctx,
+            route![sc, "enrollment_token_authenticator"],
+            req.to_vec()?,
+        )
+        .await?;
+        client.credential().await?;
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn seeded_identities_are_deterministic(ctx: &mut Context) -> Result<()> {
+        let relay = seeded_identity(ctx, &Vault::create(), "relay").await?;
+        let again = seeded_identity(ctx, &Vault::create(), "relay").await?;
+        let authority = seeded_identity(ctx, &Vault::create(), "authority").await?;
+        assert_eq!(relay.identifier(), again.identifier());
+        assert_ne!(relay.identifier(), authority.identifier());
+
+        ctx.stop().await
+    }
+}
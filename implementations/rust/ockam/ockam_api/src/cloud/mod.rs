@@ -10,6 +10,8 @@ use ockam_multiaddr::MultiAddr;
 use crate::error::ApiError;
 
 pub mod enroll;
+#[cfg(feature = "mock-orchestrator")]
+pub mod mock;
 pub mod project;
 pub mod space;
 pub mod subscription;
@@ -19,6 +21,7 @@ pub mod subscription;
 ///
 /// How to use: when running a command that spawns a background node or use an embedded node
 /// add the env variable. `OCKAM_CONTROLLER_IDENTITY_ID={identity.id-contents} ockam ...`
+#[cfg(feature = "cloud")]
 pub(crate) const OCKAM_CONTROLLER_IDENTITY_ID: &str = "OCKAM_CONTROLLER_IDENTITY_ID";
 
 /// A wrapper around a cloud request with extra fields.
@@ -54,33 +57,132 @@ impl<'a, T> CloudRequestWrapper<'a, T> {
 /// A CloudRequestWrapper without an internal request.
 pub type BareCloudRequestWrapper<'a> = CloudRequestWrapper<'a, ()>;
 
+/// Node manager service forwarding requests to the Orchestrator controller
+#[cfg(feature = "cloud")]
+pub(crate) struct CloudService {
+    controller_identity_id: ockam_identity::IdentityIdentifier,
+}
+
 impl<'a> BareCloudRequestWrapper<'a> {
     pub fn bare(route: &MultiAddr) -> Self {
         Self::new((), route)
     }
 }
 
+#[cfg(feature = "cloud")]
 mod node {
     use std::env;
     use std::str::FromStr;
 
-    use minicbor::Encode;
+    use minicbor::{Decoder, Encode};
     use rust_embed::EmbeddedFile;
 
-    use ockam_core::api::RequestBuilder;
-    use ockam_core::{self, route, Address, Result, Route};
+    use ockam_core::api::{Method, Request, RequestBuilder};
+    use ockam_core::{self, async_trait, route, Address, Result, Route};
     use ockam_identity::{IdentityIdentifier, TrustIdentifierPolicy};
     use ockam_node::api::request;
     use ockam_node::Context;
 
-    use crate::cloud::OCKAM_CONTROLLER_IDENTITY_ID;
+    use crate::cloud::{CloudService, OCKAM_CONTROLLER_IDENTITY_ID};
     use crate::error::ApiError;
+    use crate::nodes::service::progress::Progress;
+    use crate::nodes::service::service_registry::NodeService;
     use crate::nodes::NodeManager;
     use crate::StaticFiles;
 
     const TARGET: &str = "ockam_api::nodemanager::service";
 
-    impl NodeManager {
+    #[async_trait]
+    impl NodeService for CloudService {
+        async fn handle_request(
+            &self,
+            node: &NodeManager,
+            ctx: &mut Context,
+            _this: &Address,
+            req: &Request<'_>,
+            dec: &mut Decoder<'_>,
+            progress: &Progress,
+        ) -> Result<Option<Vec<u8>>> {
+            use Method::*;
+            let method = match req.method() {
+                Some(m) => m,
+                None => return Ok(None),
+            };
+            let r = match (method, req.path_segments::<5>().as_slice()) {
+                // ==*== Spaces ==*==
+                (Post, ["v0", "spaces"]) => self.create_space(node, ctx, dec).await?,
+                (Get, ["v0", "spaces"]) => self.list_spaces(node, ctx, dec).await?,
+                (Get, ["v0", "spaces", id]) => self.get_space(node, ctx, dec, id).await?,
+                (Delete, ["v0", "spaces", id]) => self.delete_space(node, ctx, dec, id).await?,
+
+                // ==*== Project' enrollers ==*==
+                (Post, ["v0", "project-enrollers", project_id]) => {
+                    self.add_project_enroller(node, ctx, dec, project_id)
+                        .await?
+                }
+                (Get, ["v0", "project-enrollers", project_id]) => {
+                    self.list_project_enrollers(node, ctx, dec, project_id)
+                        .await?
+                }
+                (Delete, ["v0", "project-enrollers", project_id, identity_id]) => {
+                    self.delete_project_enroller(node, ctx, dec, project_id, identity_id)
+                        .await?
+                }
+
+                // ==*== Projects ==*==
+                (Post, ["v0", "projects", space_id]) => {
+                    self.create_project(node, ctx, dec, space_id, progress)
+                        .await?
+                }
+                (Get, ["v0", "projects"]) => self.list_projects(node, ctx, dec).await?,
+                (Get, ["v0", "projects", project_id]) => {
+                    self.get_project(node, ctx, dec, project_id).await?
+                }
+                (Delete, ["v0", "projects", space_id, project_id]) => {
+                    self.delete_project(node, ctx, dec, space_id, project_id)
+                        .await?
+                }
+
+                // ==*== Enroll ==*==
+                (Post, ["v0", "enroll", "auth0"]) => {
+                    self.enroll_auth0(node, ctx, dec, progress).await?
+                }
+                (Get, ["v0", "enroll", "token"]) => {
+                    self.generate_enrollment_token(node, ctx, dec).await?
+                }
+                (Put, ["v0", "enroll", "token"]) => {
+                    self.authenticate_enrollment_token(node, ctx, dec, progress)
+                        .await?
+                }
+
+                // ==*== Subscriptions ==*==
+                (Post, ["subscription"]) => self.activate_subscription(node, ctx, dec).await?,
+                (Get, ["subscription", id]) => self.get_subscription(node, ctx, dec, id).await?,
+                (Get, ["subscription"]) => self.list_subscriptions(node, ctx, dec).await?,
+                (Put, ["subscription", id, "contact_info"]) => {
+                    self.update_subscription_contact_info(node, ctx, dec, id)
+                        .await?
+                }
+                (Put, ["subscription", id, "space_id"]) => {
+                    self.update_subscription_space(node, ctx, dec, id).await?
+                }
+                (Put, ["subscription", id, "unsubscribe"]) => {
+                    self.unsubscribe(node, ctx, dec, id).await?
+                }
+
+                _ => return Ok(None),
+            };
+            Ok(Some(r))
+        }
+    }
+
+    impl CloudService {
+        pub(crate) fn new() -> Result<Self> {
+            Ok(Self {
+                controller_identity_id: Self::load_controller_identity_id()?,
+            })
+        }
+
         /// Load controller identity id from file.
         ///
         /// If the env var `OCKAM_CONTROLLER_IDENTITY_ID` is set, that will be used to
@@ -112,8 +214,10 @@ mod node {
             self.controller_identity_id.clone()
         }
 
+        #[allow(clippy::too_many_arguments)]
         pub(super) async fn request_controller<T>(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             label: &str,
             schema: impl Into<Option<&str>>,
@@ -121,20 +225,61 @@ mod node {
             api_service: &str,
             req: RequestBuilder<'_, T>,
         ) -> Result<Vec<u8>>
+        where
+            T: Encode<()>,
+        {
+            let progress = Progress::disabled();
+            self.request_controller_with_progress(
+                node,
+                ctx,
+                label,
+                schema,
+                cloud_route,
+                api_service,
+                req,
+                &progress,
+            )
+            .await
+        }
+
+        /// Like `request_controller` but reports the phases of the request.
+        #[allow(clippy::too_many_arguments)]
+        pub(super) async fn request_controller_with_progress<T>(
+            &self,
+            node: &NodeManager,
+            ctx: &mut Context,
+            label: &str,
+            schema: impl Into<Option<&str>>,
+            cloud_route: impl Into<Route>,
+            api_service: &str,
+            req: RequestBuilder<'_, T>,
+            progress: &Progress,
+        ) -> Result<Vec<u8>>
         where
             T: Encode<()>,
         {
             let cloud_route = cloud_route.into();
-            let sc = self.controller_secure_channel(cloud_route).await?;
+            let phase = "Establishing secure channel to Orchestrator";
+            progress.started(ctx, phase).await;
+            let sc = self.controller_secure_channel(node, cloud_route).await?;
+            progress.completed(ctx, phase).await;
             let route = route![&sc.to_string(), api_service];
+            progress.started(ctx, label).await;
             let res = request(ctx, label, schema, route, req).await;
+            if res.is_ok() {
+                progress.completed(ctx, label).await;
+            }
             ctx.stop_worker(sc).await?;
             res
         }
 
         /// Returns a secure channel between the node and the controller.
-        async fn controller_secure_channel(&mut self, route: impl Into<Route>) -> Result<Address> {
-            let identity = self.identity()?;
+        async fn controller_secure_channel(
+            &self,
+            node: &NodeManager,
+            route: impl Into<Route>,
+        ) -> Result<Address> {
+            let identity = node.identity().await?;
             let route = route.into();
             // Create secure channel for the given route using the orchestrator identity.
             trace!(target: TARGET, %route, "Creating orchestrator secure channel");
@@ -142,7 +287,7 @@ mod node {
                 .create_secure_channel(
                     route,
                     TrustIdentifierPolicy::new(self.controller_identity_id()),
-                    &self.authenticated_storage,
+                    &node.authenticated_storage,
                 )
                 .await?;
             debug!(target: TARGET, %addr, "Orchestrator secure channel created");
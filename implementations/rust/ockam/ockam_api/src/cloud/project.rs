@@ -60,6 +60,11 @@ pub struct Project<'a> {
     #[cbor(b(10))]
     #[serde(borrow)]
     pub authority_identity: Option<CowStr<'a>>,
+
+    /// Routes to other relays of the project, in addition to `access_route`
+    #[cbor(b(11))]
+    #[serde(borrow)]
+    pub relay_access_routes: Option<Vec<CowStr<'a>>>,
 }
 
 impl Clone for Project<'_> {
@@ -83,6 +88,10 @@ impl Project<'_> {
             identity: self.identity.clone(),
             authority_access_route: self.authority_access_route.as_ref().map(|x| x.to_owned()),
             authority_identity: self.authority_identity.as_ref().map(|x| x.to_owned()),
+            relay_access_routes: self
+                .relay_access_routes
+                .as_ref()
+                .map(|v| v.iter().map(|x| x.to_owned()).collect()),
         }
     }
 
@@ -102,6 +111,15 @@ impl Project<'_> {
         MultiAddr::from_str(&self.access_route).map_err(|e| ApiError::generic(&e.to_string()))
     }
 
+    /// The routes to all relays of the project, starting with `access_route`.
+    pub fn access_routes(&self) -> Result<Vec<MultiAddr>> {
+        let mut routes = vec![self.access_route()?];
+        for r in self.relay_access_routes.iter().flatten() {
+            routes.push(MultiAddr::from_str(r).map_err(|e| ApiError::generic(&e.to_string()))?)
+        }
+        Ok(routes)
+    }
+
     // Converts the `access_route` MultiAddr into a single Address, which will
     // return the host and port of the project node.
     // Ex: if access_route is "/dnsaddr/node.dnsaddr.com/tcp/4000/service/api",
@@ -160,8 +178,7 @@ pub struct AddEnroller<'a> {
     #[b(2)] pub description: Option<CowStr<'a>>,
 }
 
-#[derive(Encode, Decode, Serialize, Debug)]
-#[cfg_attr(test, derive(Clone))]
+#[derive(Encode, Decode, Serialize, Debug, Clone)]
 #[rustfmt::skip]
 #[cbor(map)]
 pub struct Enroller<'a> {
@@ -186,6 +203,7 @@ impl<'a> AddEnroller<'a> {
     }
 }
 
+#[cfg(feature = "cloud")]
 mod node {
     use minicbor::Decoder;
     use tracing::trace;
@@ -194,19 +212,23 @@ mod node {
     use ockam_core::{self, Result};
     use ockam_node::Context;
 
+    use crate::cloud::CloudService;
     use crate::cloud::{BareCloudRequestWrapper, CloudRequestWrapper};
+    use crate::nodes::service::progress::Progress;
     use crate::nodes::NodeManager;
 
     use super::*;
 
     const TARGET: &str = "ockam_api::cloud::project";
 
-    impl NodeManager {
+    impl CloudService {
         pub(crate) async fn create_project(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
             space_id: &str,
+            progress: &Progress,
         ) -> Result<Vec<u8>> {
             let req_wrapper: CloudRequestWrapper<CreateProject> = dec.decode()?;
             let cloud_route = req_wrapper.route()?;
@@ -216,19 +238,22 @@ mod node {
             trace!(target: TARGET, %space_id, project_name = %req_body.name, "creating project");
 
             let req_builder = Request::post(format!("/v0/{space_id}")).body(req_body);
-            self.request_controller(
+            self.request_controller_with_progress(
+                node,
                 ctx,
                 label,
                 "create_project",
                 cloud_route,
                 "projects",
                 req_builder,
+                progress,
             )
             .await
         }
 
         pub(crate) async fn list_projects(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
         ) -> Result<Vec<u8>> {
@@ -239,12 +264,13 @@ mod node {
             trace!(target: TARGET, "listing projects");
 
             let req_builder = Request::get("/v0");
-            self.request_controller(ctx, label, None, cloud_route, "projects", req_builder)
+            self.request_controller(node, ctx, label, None, cloud_route, "projects", req_builder)
                 .await
         }
 
         pub(crate) async fn get_project(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
             project_id: &str,
@@ -256,12 +282,13 @@ mod node {
             trace!(target: TARGET, %project_id, "getting project");
 
             let req_builder = Request::get(format!("/v0/{project_id}"));
-            self.request_controller(ctx, label, None, cloud_route, "projects", req_builder)
+            self.request_controller(node, ctx, label, None, cloud_route, "projects", req_builder)
                 .await
         }
 
         pub(crate) async fn delete_project(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
             space_id: &str,
@@ -274,12 +301,13 @@ mod node {
             trace!(target: TARGET, %space_id, %project_id, "deleting project");
 
             let req_builder = Request::delete(format!("/v0/{space_id}/{project_id}"));
-            self.request_controller(ctx, label, None, cloud_route, "projects", req_builder)
+            self.request_controller(node, ctx, label, None, cloud_route, "projects", req_builder)
                 .await
         }
 
         pub(crate) async fn add_project_enroller(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
             project_id: &str,
@@ -292,12 +320,13 @@ mod node {
             trace!(target: TARGET, %project_id, "adding enroller");
 
             let req_builder = Request::post(format!("/v0/{project_id}/enrollers")).body(req_body);
-            self.request_controller(ctx, label, None, cloud_route, "projects", req_builder)
+            self.request_controller(node, ctx, label, None, cloud_route, "projects", req_builder)
                 .await
         }
 
         pub(crate) async fn list_project_enrollers(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
             project_id: &str,
@@ -309,12 +338,13 @@ mod node {
             trace!(target: TARGET, %project_id, "listing enrollers");
 
             let req_builder = Request::get(format!("/v0/{project_id}/enrollers"));
-            self.request_controller(ctx, label, None, cloud_route, "projects", req_builder)
+            self.request_controller(node, ctx, label, None, cloud_route, "projects", req_builder)
                 .await
         }
 
         pub(crate) async fn delete_project_enroller(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
             project_id: &str,
@@ -328,7 +358,7 @@ mod node {
 
             let req_builder =
                 Request::delete(format!("/v0/{project_id}/enrollers/{enroller_identity_id}"));
-            self.request_controller(ctx, label, None, cloud_route, "projects", req_builder)
+            self.request_controller(node, ctx, label, None, cloud_route, "projects", req_builder)
                 .await
         }
     }
@@ -360,6 +390,8 @@ mod tests {
                 authority_access_route: bool::arbitrary(g).then(|| String::arbitrary(g).into()),
                 authority_identity: bool::arbitrary(g)
                     .then(|| hex::encode(<Vec<u8>>::arbitrary(g)).into()),
+                relay_access_routes: bool::arbitrary(g)
+                    .then(|| vec![String::arbitrary(g).into(), String::arbitrary(g).into()]),
             })
         }
     }
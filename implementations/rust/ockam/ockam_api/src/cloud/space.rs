@@ -57,6 +57,7 @@ impl<'a> CreateSpace<'a> {
     }
 }
 
+#[cfg(feature = "cloud")]
 mod node {
     use minicbor::Decoder;
     use tracing::trace;
@@ -66,14 +67,16 @@ mod node {
     use ockam_node::Context;
 
     use crate::cloud::space::CreateSpace;
+    use crate::cloud::CloudService;
     use crate::cloud::{BareCloudRequestWrapper, CloudRequestWrapper};
     use crate::nodes::NodeManager;
 
     const TARGET: &str = "ockam_api::cloud::space";
 
-    impl NodeManager {
+    impl CloudService {
         pub(crate) async fn create_space(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
         ) -> Result<Vec<u8>> {
@@ -86,6 +89,7 @@ mod node {
 
             let req_builder = Request::post("/v0/").body(req_body);
             self.request_controller(
+                node,
                 ctx,
                 label,
                 "create_space",
@@ -97,7 +101,8 @@ mod node {
         }
 
         pub(crate) async fn list_spaces(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
         ) -> Result<Vec<u8>> {
@@ -108,12 +113,13 @@ mod node {
             trace!(target: TARGET, "listing spaces");
 
             let req_builder = Request::get("/v0/");
-            self.request_controller(ctx, label, None, cloud_route, "spaces", req_builder)
+            self.request_controller(node, ctx, label, None, cloud_route, "spaces", req_builder)
                 .await
         }
 
         pub(crate) async fn get_space(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
             id: &str,
@@ -125,12 +131,13 @@ mod node {
             trace!(target: TARGET, space = %id, space = %id, "getting space");
 
             let req_builder = Request::get(format!("/v0/{id}"));
-            self.request_controller(ctx, label, None, cloud_route, "spaces", req_builder)
+            self.request_controller(node, ctx, label, None, cloud_route, "spaces", req_builder)
                 .await
         }
 
         pub(crate) async fn delete_space(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
             id: &str,
@@ -142,7 +149,7 @@ mod node {
             trace!(target: TARGET, space = %id, "deleting space");
 
             let req_builder = Request::delete(format!("/v0/{id}"));
-            self.request_controller(ctx, label, None, cloud_route, "spaces", req_builder)
+            self.request_controller(node, ctx, label, None, cloud_route, "spaces", req_builder)
                 .await
         }
     }
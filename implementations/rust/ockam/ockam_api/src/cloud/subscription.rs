@@ -85,6 +85,7 @@ pub struct Subscription<'a> {
     pub space_id: Option<CowStr<'a>>,
 }
 
+#[cfg(feature = "cloud")]
 mod node {
     use minicbor::Decoder;
     use tracing::trace;
@@ -93,6 +94,7 @@ mod node {
     use ockam_core::{self, Result};
     use ockam_node::Context;
 
+    use crate::cloud::CloudService;
     use crate::cloud::{BareCloudRequestWrapper, CloudRequestWrapper};
     use crate::nodes::NodeManager;
 
@@ -101,9 +103,10 @@ mod node {
     const TARGET: &str = "ockam_api::cloud::subscription";
     const API_SERVICE: &str = "subscriptions";
 
-    impl NodeManager {
+    impl CloudService {
         pub(crate) async fn activate_subscription(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
         ) -> Result<Vec<u8>> {
@@ -116,6 +119,7 @@ mod node {
 
             let req_builder = Request::post("/v0/activate").body(req_body);
             self.request_controller(
+                node,
                 ctx,
                 label,
                 "activate_request",
@@ -127,7 +131,8 @@ mod node {
         }
 
         pub(crate) async fn get_subscription(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
             id: &str,
@@ -139,12 +144,21 @@ mod node {
             trace!(target: TARGET, subscription = %id, "getting subscription");
 
             let req_builder = Request::get(format!("/v0/{}", id));
-            self.request_controller(ctx, label, None, cloud_route, API_SERVICE, req_builder)
-                .await
+            self.request_controller(
+                node,
+                ctx,
+                label,
+                None,
+                cloud_route,
+                API_SERVICE,
+                req_builder,
+            )
+            .await
         }
 
         pub(crate) async fn list_subscriptions(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
         ) -> Result<Vec<u8>> {
@@ -155,12 +169,21 @@ mod node {
             trace!(target: TARGET, "listing subscriptions");
 
             let req_builder = Request::get("/v0/");
-            self.request_controller(ctx, label, None, cloud_route, API_SERVICE, req_builder)
-                .await
+            self.request_controller(
+                node,
+                ctx,
+                label,
+                None,
+                cloud_route,
+                API_SERVICE,
+                req_builder,
+            )
+            .await
         }
 
         pub(crate) async fn update_subscription_contact_info(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
             id: &str,
@@ -173,12 +196,21 @@ mod node {
             trace!(target: TARGET, subscription = %id, "updating subscription contact info");
 
             let req_builder = Request::put(format!("/v0/{}/contact_info", id)).body(req_body);
-            self.request_controller(ctx, label, None, cloud_route, API_SERVICE, req_builder)
-                .await
+            self.request_controller(
+                node,
+                ctx,
+                label,
+                None,
+                cloud_route,
+                API_SERVICE,
+                req_builder,
+            )
+            .await
         }
 
         pub(crate) async fn update_subscription_space(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
             id: &str,
@@ -191,12 +223,21 @@ mod node {
             trace!(target: TARGET, subscription = %id, "updating subscription space");
 
             let req_builder = Request::put(format!("/v0/{}/space_id", id)).body(req_body);
-            self.request_controller(ctx, label, None, cloud_route, API_SERVICE, req_builder)
-                .await
+            self.request_controller(
+                node,
+                ctx,
+                label,
+                None,
+                cloud_route,
+                API_SERVICE,
+                req_builder,
+            )
+            .await
         }
 
         pub(crate) async fn unsubscribe(
-            &mut self,
+            &self,
+            node: &NodeManager,
             ctx: &mut Context,
             dec: &mut Decoder<'_>,
             id: &str,
@@ -208,8 +249,16 @@ mod node {
             trace!(target: TARGET, subscription = %id, "unsubscribing");
 
             let req_builder = Request::put(format!("/v0/{}/unsubscribe", id));
-            self.request_controller(ctx, label, None, cloud_route, API_SERVICE, req_builder)
-                .await
+            self.request_controller(
+                node,
+                ctx,
+                label,
+                None,
+                cloud_route,
+                API_SERVICE,
+                req_builder,
+            )
+            .await
         }
     }
 }
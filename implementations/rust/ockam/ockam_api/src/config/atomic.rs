@@ -66,8 +66,11 @@ impl<V: ConfigValues> AtomicUpdater<V> {
             }
         };
 
+        // Only the user owning the configuration can read it
+        crate::config::system::set_private_file(&tmp_path)?;
+
         // First write the file
-        let json: String = serde_json::to_string_pretty(&*inner)?;
+        let json: String = super::to_versioned_json(&*inner)?;
         new_f.write_all(json.as_bytes())?;
 
         // Then rename it over the existing config
@@ -2,6 +2,7 @@
 
 use crate::config::{
     lookup::{ConfigLookup, InternetAddress},
+    migrations::{insert_default, Migration},
     ConfigValues,
 };
 use crate::HexByteVec;
@@ -10,9 +11,10 @@ use ockam_core::Result;
 use ockam_identity::{IdentityIdentifier, IdentityVault, PublicIdentity};
 use ockam_multiaddr::MultiAddr;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::{
     env,
+    net::SocketAddr,
     path::{Path, PathBuf},
 };
 
@@ -43,6 +45,55 @@ pub struct OckamConfig {
     pub default_vault_path: Option<PathBuf>,
     /// Default node
     pub default: Option<String>,
+
+    /// Remote nodes managed by this CLI, by name
+    #[serde(default)]
+    pub fleet: BTreeMap<String, FleetMember>,
+
+    /// How identities are shown by the CLI
+    #[serde(default)]
+    pub identity_format: IdentityFormat,
+    /// Local names of identities, accepted wherever an identity is
+    #[serde(default)]
+    pub petnames: BTreeMap<String, IdentityIdentifier>,
+}
+
+/// How the CLI shows identity identifiers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentityFormat {
+    /// The full hex identifier, e.g. `P6c20e8...`
+    #[default]
+    Hex,
+    /// The shorter base32 form with a checksum, e.g. `Imfrg...`
+    Base32,
+    /// The local petname of the identity, or its base32 form if it has none
+    Petname,
+}
+
+impl std::str::FromStr for IdentityFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(IdentityFormat::Hex),
+            "base32" => Ok(IdentityFormat::Base32),
+            "petname" => Ok(IdentityFormat::Petname),
+            _ => Err(format!(
+                "invalid identity format '{s}', expected one of: hex, base32, petname"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for IdentityFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdentityFormat::Hex => f.write_str("hex"),
+            IdentityFormat::Base32 => f.write_str("base32"),
+            IdentityFormat::Petname => f.write_str("petname"),
+        }
+    }
 }
 
 fn default_nodes() -> BTreeMap<String, NodeConfig> {
@@ -62,8 +113,41 @@ impl ConfigValues for OckamConfig {
             default_identity: None,
             default_vault_path: None,
             default: None,
+            fleet: BTreeMap::new(),
+            identity_format: IdentityFormat::default(),
+            petnames: BTreeMap::new(),
         }
     }
+
+    fn migrations() -> &'static [Migration] {
+        const MIGRATIONS: &[Migration] = &[
+            Migration {
+                description: "add the fleet of remote nodes",
+                up: |m| {
+                    insert_default(m, "fleet", serde_json::json!({}));
+                    Ok(())
+                },
+                down: |m| {
+                    m.remove("fleet");
+                    Ok(())
+                },
+            },
+            Migration {
+                description: "add the identity format and petnames",
+                up: |m| {
+                    insert_default(m, "identity_format", serde_json::json!("hex"));
+                    insert_default(m, "petnames", serde_json::json!({}));
+                    Ok(())
+                },
+                down: |m| {
+                    m.remove("identity_format");
+                    m.remove("petnames");
+                    Ok(())
+                },
+            },
+        ];
+        MIGRATIONS
+    }
 }
 
 impl OckamConfig {
@@ -94,6 +178,25 @@ Otherwise your OS or OS configuration may not be supported!",
     }
 }
 
+/// A remote node managed through its delegation service
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FleetMember {
+    /// Route to the secure channel listener of the node
+    pub route: MultiAddr,
+    /// Expected identity of the node
+    pub identity: Option<IdentityIdentifier>,
+    /// Tags used to select groups of nodes
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+}
+
+impl FleetMember {
+    /// Check if the node has all the given tags.
+    pub fn has_tags(&self, tags: &[String]) -> bool {
+        tags.iter().all(|t| self.tags.contains(t))
+    }
+}
+
 /// Per-node runtime configuration
 ///
 /// ## Updates
@@ -117,6 +220,105 @@ pub struct NodeConfig {
 
     pub pid: Option<i32>,
     pub state_dir: Option<PathBuf>,
+
+    /// Where the logs of the node are written when it runs in the background
+    #[serde(default)]
+    pub log_sink: LogSink,
+
+    /// The node only forwards messages and has no vault nor identity
+    #[serde(default)]
+    pub relay: bool,
+
+    /// Where the node serves its health probes over plain HTTP, if anywhere
+    #[serde(default)]
+    pub health_address: Option<SocketAddr>,
+
+    /// URL of the proxy the node connects to its peers through, if any
+    #[serde(default)]
+    pub tcp_proxy: Option<String>,
+
+    /// The unprivileged user the node switches to once set up, if it is
+    /// started as root
+    #[serde(default)]
+    pub user: Option<String>,
+
+    /// The group the node switches to with its user, by default the group
+    /// of the user
+    #[serde(default)]
+    pub group: Option<String>,
+}
+
+/// Destination of the logs of a background node
+///
+/// The string form, used on the command line, is one of `file`,
+/// `journald`, `syslog` (the local `/dev/log` socket),
+/// `syslog:<path>` (a local UNIX datagram socket) or
+/// `syslog:<host>:<port>` (a remote UDP syslog server).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogSink {
+    /// Flat files in the node state directory
+    #[default]
+    File,
+    /// A syslog daemon
+    Syslog(SyslogAddress),
+    /// The systemd journal, using its native protocol
+    Journald,
+}
+
+/// Address of a syslog daemon
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyslogAddress {
+    /// A UNIX datagram socket
+    Unix(PathBuf),
+    /// A UDP `host:port` address
+    Udp(String),
+}
+
+impl Default for SyslogAddress {
+    fn default() -> Self {
+        SyslogAddress::Unix(PathBuf::from("/dev/log"))
+    }
+}
+
+impl std::fmt::Display for LogSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogSink::File => f.write_str("file"),
+            LogSink::Journald => f.write_str("journald"),
+            LogSink::Syslog(SyslogAddress::Unix(p)) => write!(f, "syslog:{}", p.display()),
+            LogSink::Syslog(SyslogAddress::Udp(a)) => write!(f, "syslog:{a}"),
+        }
+    }
+}
+
+impl std::str::FromStr for LogSink {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "file" => return Ok(LogSink::File),
+            "journald" => return Ok(LogSink::Journald),
+            "syslog" => return Ok(LogSink::Syslog(SyslogAddress::default())),
+            _ => {}
+        }
+        match s.strip_prefix("syslog:") {
+            Some(p) if p.starts_with('/') => Ok(LogSink::Syslog(SyslogAddress::Unix(p.into()))),
+            Some(a) if is_host_port(a) => Ok(LogSink::Syslog(SyslogAddress::Udp(a.to_string()))),
+            _ => Err(format!(
+                "invalid log sink '{s}', expected one of: file, journald, syslog, \
+                 syslog:<path>, syslog:<host>:<port>"
+            )),
+        }
+    }
+}
+
+fn is_host_port(s: &str) -> bool {
+    match s.rsplit_once(':') {
+        Some((host, port)) => !host.is_empty() && port.parse::<u16>().is_ok(),
+        None => false,
+    }
 }
 
 fn default_name() -> String {
@@ -146,6 +348,12 @@ impl AuthoritiesConfig {
         self.authorities.iter()
     }
 
+    /// Add all the authorities of another configuration, replacing
+    /// the ones with the same identifier.
+    pub fn extend(&mut self, other: AuthoritiesConfig) {
+        self.authorities.extend(other.authorities)
+    }
+
     pub async fn to_public_identities<V>(&self, vault: &V) -> Result<Vec<PublicIdentity>>
     where
         V: IdentityVault,
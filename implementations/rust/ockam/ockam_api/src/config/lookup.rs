@@ -17,6 +17,8 @@ use std::{
 pub struct LookupMeta {
     /// Append any project name that is encountered during look-up
     pub project: VecDeque<Name>,
+    /// Append any contact name that is encountered during look-up
+    pub contact: VecDeque<Name>,
 }
 
 pub type Name = String;
@@ -107,6 +109,33 @@ impl ConfigLookup {
         self.map.retain(|k, _| !k.starts_with("/project/"));
     }
 
+    /// Store a contact route and pinned identity as lookup
+    pub fn set_contact(&mut self, name: &str, contact: ContactLookup) {
+        self.map
+            .insert(format!("/contact/{}", name), LookupValue::Contact(contact));
+    }
+
+    pub fn get_contact(&self, name: &str) -> Option<&ContactLookup> {
+        self.map
+            .get(&format!("/contact/{}", name))
+            .and_then(|value| match value {
+                LookupValue::Contact(contact) => Some(contact),
+                _ => None,
+            })
+    }
+
+    pub fn remove_contact(&mut self, name: &str) -> Option<LookupValue> {
+        self.map.remove(&format!("/contact/{}", name))
+    }
+
+    /// All contacts, by name
+    pub fn contacts(&self) -> impl Iterator<Item = (&str, &ContactLookup)> {
+        self.map.iter().filter_map(|(k, v)| match v {
+            LookupValue::Contact(c) => Some((k.strip_prefix("/contact/")?, c)),
+            _ => None,
+        })
+    }
+
     pub fn has_unresolved_projects(&self, meta: &LookupMeta) -> bool {
         meta.project
             .iter()
@@ -131,6 +160,7 @@ pub enum LookupValue {
     Address(InternetAddress),
     Space(SpaceLookup),
     Project(ProjectLookup),
+    Contact(ContactLookup),
 }
 
 /// An internet address abstraction (v6/v4/dns)
@@ -221,6 +251,9 @@ pub struct SpaceLookup {
 pub struct ProjectLookup {
     /// How to reach the node hosting this project
     pub node_route: MultiAddr,
+    /// How to reach the other relays of this project
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relay_routes: Vec<MultiAddr>,
     /// Identifier of this project
     pub id: String,
     /// Identifier of the IDENTITY of the project (for secure-channel)
@@ -229,6 +262,24 @@ pub struct ProjectLookup {
     pub authority: Option<ProjectAuthority>,
 }
 
+impl ProjectLookup {
+    /// The routes to all relays of this project, starting with `node_route`.
+    pub fn access_routes(&self) -> Vec<MultiAddr> {
+        let mut routes = vec![self.node_route.clone()];
+        routes.extend(self.relay_routes.iter().cloned());
+        routes
+    }
+}
+
+/// Represents a named peer
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ContactLookup {
+    /// How to reach the peer
+    pub route: MultiAddr,
+    /// Identifier of the IDENTITY of the peer, pinned for its secure channels
+    pub identity_id: IdentityIdentifier,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ProjectAuthority {
     id: IdentityIdentifier,
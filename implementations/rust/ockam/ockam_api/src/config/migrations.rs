@@ -0,0 +1,237 @@
+//! Versions of the schemas of the configuration files, and the steps
+//! between them.
+//!
+//! Each file records the version of its schema under `schema_version`,
+//! files written before versioning have version 0. Older files are
+//! migrated when they are loaded, after a copy of them is saved next to
+//! them. Newer files are refused: only the release which wrote them knows
+//! how to migrate them back, with `ockam state migrate --to`.
+
+use anyhow::{anyhow, bail, Context};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Key of the schema version in a configuration file.
+pub const VERSION_KEY: &str = "schema_version";
+
+/// A step from one version of a schema to the next, and back.
+///
+/// The step at index `i` of a list of migrations goes from version `i`
+/// to version `i + 1`.
+pub struct Migration {
+    pub description: &'static str,
+    pub up: fn(&mut Map<String, Value>) -> anyhow::Result<()>,
+    pub down: fn(&mut Map<String, Value>) -> anyhow::Result<()>,
+}
+
+/// The latest version of a schema with the given migrations.
+pub fn latest(migrations: &[Migration]) -> u32 {
+    migrations.len() as u32
+}
+
+/// The version of the schema of a configuration.
+pub fn version(json: &Value) -> u32 {
+    json.get(VERSION_KEY)
+        .and_then(Value::as_u64)
+        .unwrap_or_default() as u32
+}
+
+/// Record the version of the schema of a configuration.
+pub fn set_version(json: &mut Value, version: u32) {
+    if let Value::Object(map) = json {
+        map.insert(VERSION_KEY.to_string(), version.into());
+    }
+}
+
+/// Migrate a configuration to version `to`, and return the descriptions
+/// of the steps taken.
+///
+/// Nothing is changed if a step fails.
+pub fn migrate(json: &mut Value, migrations: &[Migration], to: u32) -> anyhow::Result<Vec<String>> {
+    let from = version(json);
+    if to > latest(migrations) {
+        bail!(
+            "schema version {to} is unknown, the latest one is {}",
+            latest(migrations)
+        );
+    }
+    if from > latest(migrations) {
+        bail!(
+            "schema version {from} is newer than the latest one known, {}",
+            latest(migrations)
+        );
+    }
+    let mut map = match json {
+        Value::Object(map) => map.clone(),
+        _ => bail!("the configuration is not a JSON object"),
+    };
+    let mut steps = Vec::new();
+    if from <= to {
+        for (v, m) in migrations
+            .iter()
+            .enumerate()
+            .take(to as usize)
+            .skip(from as usize)
+        {
+            (m.up)(&mut map).with_context(|| format!("migration to version {} failed", v + 1))?;
+            steps.push(format!("{} -> {}: {}", v, v + 1, m.description));
+        }
+    } else {
+        for (v, m) in migrations
+            .iter()
+            .enumerate()
+            .take(from as usize)
+            .skip(to as usize)
+            .rev()
+        {
+            (m.down)(&mut map).with_context(|| format!("migration to version {v} failed"))?;
+            steps.push(format!("{} -> {}: undo {}", v + 1, v, m.description));
+        }
+    }
+    *json = Value::Object(map);
+    set_version(json, to);
+    Ok(steps)
+}
+
+/// The result of the migration of a configuration file.
+#[derive(Debug)]
+pub struct Report {
+    pub path: PathBuf,
+    pub from: u32,
+    pub to: u32,
+    pub steps: Vec<String>,
+    /// Copy of the file before the migration, unless nothing was written
+    pub backup: Option<PathBuf>,
+}
+
+/// Migrate a configuration file to version `to`, or to the latest one.
+///
+/// With `dry_run`, the steps are checked but the file is left as is.
+pub fn migrate_file(
+    path: &Path,
+    migrations: &[Migration],
+    to: Option<u32>,
+    dry_run: bool,
+) -> anyhow::Result<Report> {
+    let buf = fs::read_to_string(path).with_context(|| format!("failed to read {path:?}"))?;
+    let mut json: Value =
+        serde_json::from_str(&buf).with_context(|| format!("failed to parse {path:?}"))?;
+    let from = version(&json);
+    let to = to.unwrap_or_else(|| latest(migrations));
+    let steps = migrate(&mut json, migrations, to).with_context(|| format!("{path:?}"))?;
+    let mut backup = None;
+    if !dry_run && from != to {
+        let b = backup_path(path, from)?;
+        fs::copy(path, &b).with_context(|| format!("failed to back up {path:?}"))?;
+        super::system::set_private_file(&b)?;
+        write(path, &json)?;
+        backup = Some(b);
+    }
+    Ok(Report {
+        path: path.to_path_buf(),
+        from,
+        to,
+        steps,
+        backup,
+    })
+}
+
+/// Where a file is copied before it is migrated from version `from`.
+pub fn backup_path(path: &Path, from: u32) -> anyhow::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow!("invalid configuration path {path:?}"))?;
+    Ok(path.with_file_name(format!("{name}.v{from}.bak")))
+}
+
+/// Write a configuration file atomically.
+fn write(path: &Path, json: &Value) -> anyhow::Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(json)?)?;
+    super::system::set_private_file(&tmp)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Insert `key` with `value` unless it is present.
+pub(crate) fn insert_default(map: &mut Map<String, Value>, key: &str, value: Value) {
+    map.entry(key).or_insert(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const MIGRATIONS: &[Migration] = &[
+        Migration {
+            description: "add a list of peers",
+            up: |m| {
+                insert_default(m, "peers", json!([]));
+                Ok(())
+            },
+            down: |m| {
+                m.remove("peers");
+                Ok(())
+            },
+        },
+        Migration {
+            description: "rename host to address",
+            up: |m| {
+                let host = m.remove("host").ok_or_else(|| anyhow!("no host"))?;
+                m.insert("address".into(), host);
+                Ok(())
+            },
+            down: |m| {
+                let address = m.remove("address").ok_or_else(|| anyhow!("no address"))?;
+                m.insert("host".into(), address);
+                Ok(())
+            },
+        },
+    ];
+
+    #[test]
+    fn migrate_forward_and_backward() {
+        let mut json = json!({ "host": "127.0.0.1" });
+        let steps = migrate(&mut json, MIGRATIONS, 2).unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(
+            json,
+            json!({ "address": "127.0.0.1", "peers": [], "schema_version": 2 })
+        );
+
+        let steps = migrate(&mut json, MIGRATIONS, 0).unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(json, json!({ "host": "127.0.0.1", "schema_version": 0 }));
+    }
+
+    #[test]
+    fn failed_migrations_change_nothing() {
+        let mut json = json!({ "port": 4000 });
+        assert!(migrate(&mut json, MIGRATIONS, 2).is_err());
+        assert_eq!(json, json!({ "port": 4000 }));
+
+        // Versions this release does not know about are refused
+        let mut json = json!({ "schema_version": 3 });
+        assert!(migrate(&mut json, MIGRATIONS, 2).is_err());
+    }
+
+    #[test]
+    fn migrate_files_with_a_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, r#"{ "host": "127.0.0.1" }"#).unwrap();
+
+        let report = migrate_file(&path, MIGRATIONS, None, true).unwrap();
+        assert_eq!((report.from, report.to), (0, 2));
+        assert!(report.backup.is_none());
+        assert!(fs::read_to_string(&path).unwrap().contains("host"));
+
+        let report = migrate_file(&path, MIGRATIONS, None, false).unwrap();
+        let backup = report.backup.unwrap();
+        assert!(fs::read_to_string(backup).unwrap().contains("host"));
+        assert!(fs::read_to_string(&path).unwrap().contains("address"));
+    }
+}
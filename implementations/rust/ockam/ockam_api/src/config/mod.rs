@@ -1,8 +1,9 @@
 use crate::config::atomic::AtomicUpdater;
+use crate::config::migrations::Migration;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::{
-    fs::{create_dir_all, File},
+    fs::File,
     io::{Read, Write},
     path::{Path, PathBuf},
     sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
@@ -11,9 +12,23 @@ use std::{
 pub mod atomic;
 pub mod cli;
 pub mod lookup;
+pub mod migrations;
+pub mod system;
 
 pub trait ConfigValues: Serialize + DeserializeOwned {
     fn default_values(config_dir: &Path) -> Self;
+
+    /// The steps between the versions of the schema of the configuration.
+    fn migrations() -> &'static [Migration] {
+        &[]
+    }
+}
+
+/// Serialise a configuration, with the version of its schema.
+pub(crate) fn to_versioned_json<V: ConfigValues>(values: &V) -> serde_json::Result<String> {
+    let mut json = serde_json::to_value(values)?;
+    migrations::set_version(&mut json, migrations::latest(V::migrations()));
+    serde_json::to_string_pretty(&json)
 }
 
 #[derive(Clone, Debug)]
@@ -48,9 +63,9 @@ impl<V: ConfigValues> Config<V> {
 
     /// Attempt to load a config.  If none exists, one is created and then returned.
     pub fn load(config_dir: &Path, config_name: &str) -> Self {
-        if let Err(e) = create_dir_all(config_dir) {
+        if let Err(e) = system::create_private_dir(config_dir) {
             eprintln!(
-                "failed to create configuration directory {:?}: {}",
+                "failed to create configuration directory {:?}: {:#}",
                 config_dir, e
             );
             std::process::exit(-1);
@@ -61,9 +76,9 @@ impl<V: ConfigValues> Config<V> {
 
         let create_new = || {
             let new_inner = V::default_values(config_dir);
-            let json: String =
-                serde_json::to_string_pretty(&new_inner).expect("failed to serialise config");
+            let json: String = to_versioned_json(&new_inner).expect("failed to serialise config");
             let mut f = File::create(&config_path).expect("failed to create default config file");
+            system::set_private_file(&config_path).expect("failed to protect config file");
             f.write_all(json.as_bytes())
                 .expect("failed to write config");
             new_inner
@@ -76,6 +91,31 @@ impl<V: ConfigValues> Config<V> {
                 if buf.is_empty() {
                     create_new()
                 } else {
+                    let latest = migrations::latest(V::migrations());
+                    match serde_json::from_str::<serde_json::Value>(&buf) {
+                        Ok(json) if migrations::version(&json) > latest => {
+                            eprintln!(
+                                "{} was written by a newer release of ockam, with schema \
+                                 version {}. Run `ockam state migrate --to {}` with that \
+                                 release to use it with this one.",
+                                config_path.display(),
+                                migrations::version(&json),
+                                latest
+                            );
+                            std::process::exit(-1);
+                        }
+                        Ok(json) if migrations::version(&json) < latest => {
+                            if let Err(e) =
+                                migrations::migrate_file(&config_path, V::migrations(), None, false)
+                            {
+                                eprintln!("failed to migrate {}: {:#}", config_path.display(), e);
+                                std::process::exit(-1);
+                            }
+                            buf = std::fs::read_to_string(&config_path)
+                                .expect("failed to read config");
+                        }
+                        _ => {}
+                    }
                     serde_json::from_str(&buf).unwrap_or_else(|_| {
                         panic!(
                             "Failed to parse config.  Try deleting {}",
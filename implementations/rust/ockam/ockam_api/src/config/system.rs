@@ -0,0 +1,167 @@
+//! System-wide trust configuration and per-user state permissions
+//!
+//! On hosts shared by several users, e.g. build machines, an
+//! administrator can install a read-only trust configuration in
+//! `/etc/ockam` (or the directory set in `OCKAM_SYSTEM_CONFIG`).  The
+//! authorities listed in its `authorities.json` are trusted by the
+//! nodes of every user, in addition to their own.  The configuration
+//! is only used if it cannot be modified by other users.
+//!
+//! The state of each user, configuration and node directories, is
+//! kept private to that user.
+
+use crate::config::cli::AuthoritiesConfig;
+use anyhow::{anyhow, Context};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the system configuration directory.
+pub const OCKAM_SYSTEM_CONFIG: &str = "OCKAM_SYSTEM_CONFIG";
+
+/// Default system configuration directory.
+pub const DEFAULT_SYSTEM_CONFIG_DIR: &str = "/etc/ockam";
+
+/// The system configuration directory.
+pub fn system_config_dir() -> PathBuf {
+    match std::env::var(OCKAM_SYSTEM_CONFIG) {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(DEFAULT_SYSTEM_CONFIG_DIR),
+    }
+}
+
+/// Load the authorities trusted system-wide, if any.
+///
+/// An error is returned if the file, or its directory, can be
+/// modified by users other than root and the current user.
+pub fn load_authorities(dir: &Path) -> anyhow::Result<Option<AuthoritiesConfig>> {
+    let path = dir.join("authorities.json");
+    let mut f = match File::open(&path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).context(format!("failed to open {}", path.display())),
+    };
+    check_not_shared(dir)?;
+    check_not_shared(&path)?;
+    let mut buf = String::new();
+    f.read_to_string(&mut buf)
+        .context(format!("failed to read {}", path.display()))?;
+    let authorities =
+        serde_json::from_str(&buf).context(format!("failed to parse {}", path.display()))?;
+    Ok(Some(authorities))
+}
+
+/// Create a directory, and its missing parents, only accessible by the
+/// current user.
+///
+/// The permissions of an existing directory are left untouched but
+/// it must belong to the current user.
+pub fn create_private_dir(path: &Path) -> anyhow::Result<()> {
+    if path.exists() {
+        return check_owned(path);
+    }
+    fs::create_dir_all(path).context(format!("failed to create {}", path.display()))?;
+    set_private(path, 0o700)
+}
+
+/// Make a file only readable and writable by the current user.
+pub fn set_private_file(path: &Path) -> anyhow::Result<()> {
+    set_private(path, 0o600)
+}
+
+#[cfg(unix)]
+fn set_private(path: &Path, mode: u32) -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        .context(format!("failed to set permissions of {}", path.display()))
+}
+
+#[cfg(not(unix))]
+fn set_private(_: &Path, _: u32) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn check_owned(path: &Path) -> anyhow::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = fs::metadata(path).context(format!("failed to read {}", path.display()))?;
+    let uid = unsafe { libc::geteuid() };
+    if meta.uid() != uid {
+        return Err(anyhow!(
+            "{} belongs to another user (uid {}), refusing to use it",
+            path.display(),
+            meta.uid()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_owned(_: &Path) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn check_not_shared(path: &Path) -> anyhow::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = fs::metadata(path).context(format!("failed to read {}", path.display()))?;
+    let uid = unsafe { libc::geteuid() };
+    if meta.uid() != 0 && meta.uid() != uid {
+        return Err(anyhow!(
+            "{} must belong to root or to the current user",
+            path.display()
+        ));
+    }
+    if meta.mode() & 0o022 != 0 {
+        return Err(anyhow!(
+            "{} must not be writable by group or other users",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_not_shared(_: &Path) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn set_mode(path: &Path, mode: u32) {
+        fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap()
+    }
+
+    #[test]
+    fn system_authorities() {
+        let dir = tempfile::tempdir().unwrap();
+        set_mode(dir.path(), 0o755);
+        assert!(load_authorities(dir.path()).unwrap().is_none());
+
+        let path = dir.path().join("authorities.json");
+        fs::write(&path, r#"{"authorities":{}}"#).unwrap();
+        set_mode(&path, 0o644);
+        assert!(load_authorities(dir.path()).unwrap().is_some());
+
+        // Other users could add authorities
+        set_mode(&path, 0o666);
+        assert!(load_authorities(dir.path()).is_err());
+        set_mode(&path, 0o644);
+        set_mode(dir.path(), 0o777);
+        assert!(load_authorities(dir.path()).is_err());
+    }
+
+    #[test]
+    fn private_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a").join("b");
+        create_private_dir(&path).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        // Existing directories are reused
+        create_private_dir(&path).unwrap();
+    }
+}
@@ -0,0 +1,344 @@
+//! Delegation of node manager requests to remote identities.
+//!
+//! A node running the [`Delegation`] service lets a fixed set of remote
+//! identities, connected through a secure channel, create inlets and
+//! forwarders on its behalf, query its status and rotate its credential.
+//! This is what allows a single controller to manage a fleet of nodes it
+//! can only reach over Ockam routes.
+//!
+//! The service may also accept the identities whose credential grants them
+//! [`Capabilities`], for the requests these capabilities allow only.
+
+use crate::capability::{Capabilities, CAPABILITIES};
+use core::time::Duration;
+use minicbor::Decoder;
+use ockam_core::api::{self, Error, Id, Method, Request, Response};
+use ockam_core::{self, Address, Result, Routed, Worker};
+use ockam_identity::authenticated_storage::AuthenticatedStorage;
+use ockam_identity::credential::AttributesStorageUtils;
+use ockam_identity::{IdentityIdentifier, IdentitySecureChannelLocalInfo};
+use ockam_node::{tokio, Context};
+use tracing::{error, trace, warn};
+
+const TARGET: &str = "ockam_api::delegation";
+
+/// Time to wait for the response of a request without a timeout.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Worker forwarding the requests of authorized identities to a node manager.
+pub struct Delegation<S> {
+    authorized: Vec<IdentityIdentifier>,
+    node_manager: Address,
+    /// Where the attributes of the identities which presented a
+    /// credential are stored
+    storage: S,
+    capabilities: bool,
+}
+
+impl<S: AuthenticatedStorage> Delegation<S> {
+    pub fn new(
+        authorized: Vec<IdentityIdentifier>,
+        node_manager: impl Into<Address>,
+        storage: S,
+    ) -> Self {
+        Self {
+            authorized,
+            node_manager: node_manager.into(),
+            storage,
+            capabilities: false,
+        }
+    }
+
+    /// Also accept the requests allowed by the capabilities of the
+    /// credential an identity presented.
+    pub fn with_capabilities(mut self) -> Self {
+        self.capabilities = true;
+        self
+    }
+
+    /// Check if `req`, whose body is read from `dec`, may be handled on
+    /// behalf of the identity `id`.
+    ///
+    /// Returns the reason to reject the request otherwise.
+    async fn check(
+        &self,
+        id: Option<&IdentityIdentifier>,
+        req: &Request<'_>,
+        dec: &Decoder<'_>,
+    ) -> Result<Option<&'static str>> {
+        let id = match id {
+            Some(id) => id,
+            None => return Ok(Some("secure channel required")),
+        };
+        if !self.authorized.contains(id) {
+            return match self.capabilities_of(id).await? {
+                Some(c) if c.allows(req, dec) => Ok(None),
+                Some(_) => Ok(Some(
+                    "request is not allowed by the capabilities of identity",
+                )),
+                None => Ok(Some("identity is not authorized to delegate requests")),
+            };
+        }
+        if !is_delegable(req) {
+            return Ok(Some("request can not be delegated"));
+        }
+        Ok(None)
+    }
+
+    /// The capabilities granted to `id` by its credential, if any.
+    async fn capabilities_of(&self, id: &IdentityIdentifier) -> Result<Option<Capabilities>> {
+        if !self.capabilities {
+            return Ok(None);
+        }
+        let attrs = AttributesStorageUtils::get_attributes(id, &self.storage).await?;
+        let value = match attrs.as_ref().and_then(|a| a.get(CAPABILITIES)) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        match Capabilities::from_attribute(value) {
+            Ok(c) => Ok(Some(c)),
+            Err(err) => {
+                warn!(target: TARGET, identity = %id, %err, "invalid capabilities");
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Requests which may be sent through the delegation service.
+fn is_delegable(req: &Request) -> bool {
+    use Method::*;
+    matches! {
+        (req.method(), req.path_segments::<4>().as_slice()),
+        (Some(Get), ["node"])
+            | (Some(Get | Post), ["node", "inlet"])
+            | (Some(Get | Post), ["node", "forwarder"])
+            | (Some(Post), ["node", "credentials", "actions", "get"])
+    }
+}
+
+#[ockam_core::worker]
+impl<S: AuthenticatedStorage> Worker for Delegation<S> {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        let mut dec = Decoder::new(msg.as_body());
+        let req: Request = match dec.decode() {
+            Ok(r) => r,
+            Err(e) => {
+                let err = Error::default().with_message(e.to_string());
+                let res = Response::bad_request(Id::default()).body(err).to_vec()?;
+                return ctx.send(msg.return_route(), res).await;
+            }
+        };
+
+        let info = IdentitySecureChannelLocalInfo::find_info(msg.local_message()).ok();
+        let id = info.as_ref().map(|i| i.their_identity_id());
+        if let Some(reason) = self.check(id, &req, &dec).await? {
+            warn! {
+                target: TARGET,
+                identity = ?id,
+                method   = ?req.method(),
+                path     = %req.path(),
+                %reason,
+                "rejected delegated request"
+            }
+            let res = api::forbidden(&req, reason).to_vec()?;
+            return ctx.send(msg.return_route(), res).await;
+        }
+
+        trace! {
+            target: TARGET,
+            id     = %req.id(),
+            method = ?req.method(),
+            path   = %req.path(),
+            "delegated request"
+        }
+
+        // Creating a forwarder may take a while, do not block other requests
+        let node_manager = self.node_manager.clone();
+        let mut ctx = ctx.new_detached(Address::random_local()).await?;
+        let timeout = req.timeout();
+        tokio::spawn(async move {
+            if let Err(err) = forward(&mut ctx, node_manager, msg, timeout).await {
+                error!(target: TARGET, %err, "failed to respond to delegated request")
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Send the request in `msg` to the node manager and its response back.
+async fn forward(
+    ctx: &mut Context,
+    node_manager: Address,
+    msg: Routed<Vec<u8>>,
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let return_route = msg.return_route();
+    ctx.send(node_manager, msg.body()).await?;
+    let res = ctx
+        .receive_duration_timeout::<Vec<u8>>(timeout.unwrap_or(DEFAULT_TIMEOUT))
+        .await?
+        .take()
+        .body();
+    ctx.send(return_route, res).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::models::forwarder::ForwarderList;
+    use crate::nodes::models::portal::CreateOutlet;
+    use crate::nodes::NodeManager;
+    use ockam::identity::authenticated_storage::mem::InMemoryStorage;
+    use ockam::identity::credential::Credential;
+    use ockam::identity::{Identity, TrustEveryonePolicy};
+    use ockam::vault::Vault;
+    use ockam_core::api::Status;
+    use ockam_core::route;
+
+    #[ockam_macros::test]
+    async fn only_authorized_requests_are_delegated(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+
+        let b = Identity::create(ctx, &Vault::create()).await?;
+        b.create_secure_channel_listener("api", TrustEveryonePolicy, &InMemoryStorage::new())
+            .await?;
+        let controller = Identity::create(ctx, &Vault::create()).await?;
+        let stranger = Identity::create(ctx, &Vault::create()).await?;
+        let delegation = Delegation::new(
+            vec![controller.identifier().clone()],
+            node_manager.recipient(),
+            InMemoryStorage::new(),
+        );
+        ctx.start_worker("delegation", delegation).await?;
+
+        let controller_sc = controller
+            .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+            .await?;
+        let stranger_sc = stranger
+            .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+            .await?;
+
+        // Authorized identity, delegable request
+        let req = Request::get("/node/forwarder");
+        let buf = ockam_node::api::request(
+            ctx,
+            "",
+            None,
+            route![controller_sc.clone(), "delegation"],
+            req,
+        )
+        .await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let forwarders: ForwarderList = dec.decode()?;
+        assert!(forwarders.list.is_empty());
+
+        // Authorized identity, request which is not delegable
+        let req = Request::get("/node/secure_channel");
+        let buf = ockam_node::api::request(ctx, "", None, route![controller_sc, "delegation"], req)
+            .await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Forbidden));
+
+        // Identity which is not authorized
+        let req = Request::get("/node/forwarder");
+        let buf =
+            ockam_node::api::request(ctx, "", None, route![stranger_sc, "delegation"], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Forbidden));
+
+        // No secure channel
+        let req = Request::get("/node/forwarder");
+        let buf = ockam_node::api::request(ctx, "", None, route!["delegation"], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Forbidden));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn capabilities_limit_delegated_requests(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+
+        let authority = Identity::create(ctx, &Vault::create()).await?;
+        let storage = InMemoryStorage::new();
+        let b = Identity::create(ctx, &Vault::create()).await?;
+        b.create_secure_channel_listener("api", TrustEveryonePolicy, &storage)
+            .await?;
+        b.start_credentials_exchange_worker(
+            vec![authority.to_public().await?],
+            "credentials",
+            false,
+            storage.clone(),
+        )
+        .await?;
+        let delegation =
+            Delegation::new(vec![], node_manager.recipient(), storage).with_capabilities();
+        ctx.start_worker("delegation", delegation).await?;
+
+        let device = Identity::create(ctx, &Vault::create()).await?;
+        let credential = Credential::builder(device.identifier().clone())
+            .with_attribute(CAPABILITIES, b"forwarder:device-42,outlet:22");
+        let credential = authority.issue_credential(credential).await?;
+        device.set_credential(Some(credential)).await;
+        let sc = device
+            .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+            .await?;
+
+        // Before the credential is presented
+        let req = Request::post("/node/outlet").body(CreateOutlet::new(
+            "127.0.0.1:22",
+            "ssh",
+            None,
+            false,
+        ));
+        let buf =
+            ockam_node::api::request(ctx, "", None, route![sc.clone(), "delegation"], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Forbidden));
+
+        device
+            .present_credential(route![sc.clone(), "credentials"])
+            .await?;
+
+        // Granted by a capability
+        let req = Request::post("/node/outlet").body(CreateOutlet::new(
+            "127.0.0.1:22",
+            "ssh",
+            None,
+            false,
+        ));
+        let buf =
+            ockam_node::api::request(ctx, "", None, route![sc.clone(), "delegation"], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        // Not granted by any capability
+        let req = Request::post("/node/outlet").body(CreateOutlet::new(
+            "127.0.0.1:80",
+            "http",
+            None,
+            false,
+        ));
+        let buf =
+            ockam_node::api::request(ctx, "", None, route![sc.clone(), "delegation"], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Forbidden));
+
+        let req = Request::get("/node/forwarder");
+        let buf = ockam_node::api::request(ctx, "", None, route![sc, "delegation"], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Forbidden));
+
+        ctx.stop().await
+    }
+}
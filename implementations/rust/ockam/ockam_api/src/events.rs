@@ -0,0 +1,514 @@
+//! Events of a node, posted to a webhook.
+//!
+//! Parts of the node publish [`NodeEvent`]s through an [`Events`] handle.
+//! The events are delivered in the background, as JSON, to the webhook
+//! configured for the node, so that it can be integrated with chat or
+//! paging services without running an external agent.
+//!
+//! The last events are also kept in memory, numbered in the order they
+//! were published, so that supervisors can follow them with
+//! `GET /node/events` from the number of the last event they have seen.
+//!
+//! When the webhook has a secret, every event carries an
+//! `X-Ockam-Signature: sha256=<hex>` header with the HMAC-SHA256 of the
+//! body, keyed with the secret.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ockam_core::access_control::AccessControl;
+use ockam_core::compat::collections::VecDeque;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::{async_trait, LocalMessage, Result};
+use ockam_identity::credential::Timestamp;
+use ockam_node::tokio;
+use ockam_node::tokio::sync::mpsc;
+use ockam_node::tokio::time::{interval, MissedTickBehavior};
+use serde::Serialize;
+
+use crate::error::ApiError;
+use crate::nodes::config::WebhookConfig;
+
+/// Time between two checks of the credential and of the denials.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Number of denied messages within `CHECK_INTERVAL` reported as a spike.
+const DENIAL_SPIKE: u64 = 20;
+
+/// Warn about an expiring credential at most this long before it expires.
+const EXPIRY_WARNING: Duration = Duration::from_secs(24 * 3600);
+
+/// Upper bound on the time to deliver an event.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Number of events waiting for delivery before new ones are dropped.
+const QUEUE_SIZE: usize = 256;
+
+/// Number of past events kept in memory.
+const LOG_SIZE: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub(crate) enum NodeEvent {
+    /// The session of a forwarder is unresponsive, its recovery started
+    ForwarderDown {
+        session: String,
+        description: Option<String>,
+        address: String,
+    },
+    /// The session of a forwarder was recovered
+    ForwarderRecovered {
+        session: String,
+        description: Option<String>,
+        address: String,
+    },
+    /// The recovery of a forwarder was given up after too many failures
+    ForwarderFailed {
+        session: String,
+        description: Option<String>,
+        alias: Option<String>,
+        address: String,
+        failures: u32,
+        reason: String,
+    },
+    /// A monitored secure channel is unresponsive, its re-establishment started
+    SecureChannelDown {
+        session: String,
+        description: Option<String>,
+        address: String,
+        /// The address the channel leads to
+        to: String,
+    },
+    /// A monitored secure channel was re-established
+    SecureChannelRecovered {
+        session: String,
+        description: Option<String>,
+        address: String,
+        to: String,
+    },
+    /// The re-establishment of a secure channel was given up after too many failures
+    SecureChannelFailed {
+        session: String,
+        description: Option<String>,
+        address: String,
+        to: String,
+        failures: u32,
+        reason: String,
+    },
+    /// The credential of the node expires soon
+    CredentialExpiring {
+        /// Unix time, in seconds
+        expires_at: u64,
+        /// Seconds
+        expires_in: u64,
+    },
+    /// The credential of the node could not be renewed, it is tried again
+    CredentialRenewalFailed {
+        /// Why the credential was renewed
+        reason: String,
+        error: String,
+        failures: u32,
+    },
+    /// Unusually many messages were denied by access controls
+    PolicyDenialSpike {
+        denials: u64,
+        /// Seconds
+        period: u64,
+    },
+}
+
+impl NodeEvent {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            NodeEvent::ForwarderDown { .. } => "forwarder-down",
+            NodeEvent::ForwarderRecovered { .. } => "forwarder-recovered",
+            NodeEvent::ForwarderFailed { .. } => "forwarder-failed",
+            NodeEvent::SecureChannelDown { .. } => "secure-channel-down",
+            NodeEvent::SecureChannelRecovered { .. } => "secure-channel-recovered",
+            NodeEvent::SecureChannelFailed { .. } => "secure-channel-failed",
+            NodeEvent::CredentialExpiring { .. } => "credential-expiring",
+            NodeEvent::CredentialRenewalFailed { .. } => "credential-renewal-failed",
+            NodeEvent::PolicyDenialSpike { .. } => "policy-denial-spike",
+        }
+    }
+}
+
+/// The JSON document posted to the webhook.
+#[derive(Debug, Serialize)]
+struct Envelope<'a> {
+    node: &'a str,
+    /// Unix time, in seconds
+    time: u64,
+    #[serde(flatten)]
+    event: &'a NodeEvent,
+}
+
+/// Handle to publish the events of a node.
+#[derive(Debug, Clone)]
+pub(crate) struct Events {
+    tx: mpsc::Sender<NodeEvent>,
+    state: Arc<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    webhook: Mutex<Option<WebhookConfig>>,
+    denials: AtomicU64,
+    credential: Mutex<Option<Validity>>,
+    log: Mutex<Log>,
+}
+
+/// The last `LOG_SIZE` events published.
+#[derive(Debug, Default)]
+struct Log {
+    /// Number of the last event
+    last: u64,
+    events: VecDeque<Recorded>,
+}
+
+/// An event kept in memory.
+#[derive(Debug, Clone)]
+pub(crate) struct Recorded {
+    /// Number of the event, starting at 1
+    pub(crate) seq: u64,
+    /// Unix time, in seconds
+    pub(crate) time: u64,
+    pub(crate) event: NodeEvent,
+}
+
+#[derive(Debug)]
+struct Validity {
+    created: u64,
+    expires: u64,
+    warned: bool,
+}
+
+impl Events {
+    /// Create the events of node `node` and start delivering them.
+    pub(crate) fn new(node: String, webhook: Option<WebhookConfig>) -> Self {
+        let (tx, rx) = mpsc::channel(QUEUE_SIZE);
+        let state = Arc::new(State {
+            webhook: Mutex::new(webhook),
+            ..Default::default()
+        });
+        tokio::spawn(run(node, rx, state.clone()));
+        Self { tx, state }
+    }
+
+    /// Publish an event, without waiting for its delivery.
+    pub(crate) fn emit(&self, event: NodeEvent) {
+        self.state.record(event.clone(), now());
+        if let Err(err) = self.tx.try_send(event) {
+            warn!(%err, "Dropped node event")
+        }
+    }
+
+    /// The events kept in memory which were published after event `seq`.
+    pub(crate) fn since(&self, seq: u64) -> Vec<Recorded> {
+        self.state.since(seq)
+    }
+
+    pub(crate) fn webhook(&self) -> Option<WebhookConfig> {
+        self.state.webhook.lock().unwrap().clone()
+    }
+
+    pub(crate) fn set_webhook(&self, webhook: Option<WebhookConfig>) {
+        *self.state.webhook.lock().unwrap() = webhook
+    }
+
+    /// Record the validity of the new credential of the node.
+    pub(crate) fn credential_renewed(&self, created: Timestamp, expires: Timestamp) {
+        *self.state.credential.lock().unwrap() = Some(Validity {
+            created: created.into(),
+            expires: expires.into(),
+            warned: false,
+        })
+    }
+
+    /// Wrap an access control, so that its denials are reported.
+    pub(crate) fn count_denials(&self, inner: Arc<dyn AccessControl>) -> Arc<dyn AccessControl> {
+        Arc::new(CountDenials {
+            inner,
+            state: self.state.clone(),
+        })
+    }
+}
+
+impl State {
+    fn record(&self, event: NodeEvent, time: u64) {
+        let mut log = self.log.lock().unwrap();
+        log.last += 1;
+        let seq = log.last;
+        if log.events.len() == LOG_SIZE {
+            log.events.pop_front();
+        }
+        log.events.push_back(Recorded { seq, time, event })
+    }
+
+    fn since(&self, seq: u64) -> Vec<Recorded> {
+        let log = self.log.lock().unwrap();
+        log.events.iter().filter(|r| r.seq > seq).cloned().collect()
+    }
+
+    /// Events derived from the state of the node at Unix time `now`.
+    fn check(&self, now: u64) -> Vec<NodeEvent> {
+        let mut events = Vec::new();
+        let denials = self.denials.swap(0, Ordering::Relaxed);
+        if denials >= DENIAL_SPIKE {
+            events.push(NodeEvent::PolicyDenialSpike {
+                denials,
+                period: CHECK_INTERVAL.as_secs(),
+            })
+        }
+        if let Some(v) = self.credential.lock().unwrap().as_mut() {
+            // Short-lived credentials are reported in the last fifth of their validity
+            let warning = EXPIRY_WARNING
+                .as_secs()
+                .min(v.expires.saturating_sub(v.created) / 5);
+            if !v.warned && v.expires.saturating_sub(now) <= warning {
+                v.warned = true;
+                events.push(NodeEvent::CredentialExpiring {
+                    expires_at: v.expires,
+                    expires_in: v.expires.saturating_sub(now),
+                })
+            }
+        }
+        events
+    }
+}
+
+/// Deliver the events received on `rx`, and the ones derived from `state`
+/// every `CHECK_INTERVAL`, until all handles are dropped.
+async fn run(node: String, mut rx: mpsc::Receiver<NodeEvent>, state: Arc<State>) {
+    let mut ticks = interval(CHECK_INTERVAL);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        let events = tokio::select! {
+            e = rx.recv() => match e {
+                Some(e) => vec![e],
+                None => return,
+            },
+            _ = ticks.tick() => state.check(now()),
+        };
+        for event in events {
+            let webhook = match state.webhook.lock().unwrap().clone() {
+                Some(w) => w,
+                None => {
+                    debug!(event = %event.name(), "No webhook for node event");
+                    continue;
+                }
+            };
+            let result = tokio::time::timeout(DELIVERY_TIMEOUT, deliver(&node, &webhook, &event));
+            match result.await {
+                Ok(Ok(())) => debug!(event = %event.name(), "Delivered node event"),
+                Ok(Err(err)) => warn!(event = %event.name(), %err, "Failed to deliver node event"),
+                Err(_) => warn!(event = %event.name(), "Delivery of node event timed out"),
+            }
+        }
+    }
+}
+
+async fn deliver(node: &str, webhook: &WebhookConfig, event: &NodeEvent) -> Result<()> {
+    let envelope = Envelope {
+        node,
+        time: now(),
+        event,
+    };
+    let body = serde_json::to_vec(&envelope).map_err(|e| ApiError::generic(&e.to_string()))?;
+    let mut headers = vec![("X-Ockam-Event", event.name().to_string())];
+    if let Some(secret) = &webhook.secret {
+        headers.push((
+            "X-Ockam-Signature",
+            format!("sha256={}", sign(secret, &body)?),
+        ));
+    }
+    post(&webhook.url, body, &headers).await
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Post a JSON `body` to `url`.
+#[cfg(feature = "webhooks")]
+pub(crate) async fn post(url: &str, body: Vec<u8>, headers: &[(&str, String)]) -> Result<()> {
+    let mut req = reqwest::Client::new()
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json");
+    for (name, value) in headers {
+        req = req.header(*name, value)
+    }
+    let res = req
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| ApiError::generic(&e.to_string()))?;
+    res.error_for_status()
+        .map_err(|e| ApiError::generic(&e.to_string()))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "webhooks"))]
+pub(crate) async fn post(_url: &str, _body: Vec<u8>, _headers: &[(&str, String)]) -> Result<()> {
+    Err(ApiError::generic(
+        "webhooks are not supported by this build",
+    ))
+}
+
+/// The hex encoded HMAC-SHA256 of `body`.
+#[cfg(feature = "webhooks")]
+fn sign(secret: &str, body: &[u8]) -> Result<String> {
+    use hmac::{Hmac, Mac, NewMac};
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| ApiError::generic(&e.to_string()))?;
+    mac.update(body);
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(not(feature = "webhooks"))]
+fn sign(_secret: &str, _body: &[u8]) -> Result<String> {
+    Err(ApiError::generic(
+        "webhooks are not supported by this build",
+    ))
+}
+
+/// Access control counting the messages denied by another one.
+#[derive(Debug)]
+struct CountDenials {
+    inner: Arc<dyn AccessControl>,
+    state: Arc<State>,
+}
+
+#[async_trait]
+impl AccessControl for CountDenials {
+    async fn is_authorized(&self, msg: &LocalMessage) -> Result<bool> {
+        let allowed = self.inner.is_authorized(msg).await?;
+        if !allowed {
+            self.state.denials.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(allowed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expiring_credentials_are_reported_once() {
+        let state = State::default();
+        *state.credential.lock().unwrap() = Some(Validity {
+            created: 0,
+            expires: 30 * 24 * 3600,
+            warned: false,
+        });
+        assert!(state.check(0).is_empty());
+        let events = state.check(29 * 24 * 3600 + 1);
+        assert_eq!(
+            events,
+            vec![NodeEvent::CredentialExpiring {
+                expires_at: 30 * 24 * 3600,
+                expires_in: 24 * 3600 - 1,
+            }]
+        );
+        assert!(state.check(29 * 24 * 3600 + 2).is_empty());
+    }
+
+    #[test]
+    fn the_last_events_are_kept() {
+        let state = State::default();
+        for denials in 0..LOG_SIZE as u64 + 2 {
+            state.record(
+                NodeEvent::PolicyDenialSpike {
+                    denials,
+                    period: 60,
+                },
+                0,
+            );
+        }
+        assert_eq!(state.since(0).len(), LOG_SIZE);
+        assert_eq!(state.since(0).first().map(|r| r.seq), Some(3));
+        let seqs: Vec<u64> = state.since(LOG_SIZE as u64).iter().map(|r| r.seq).collect();
+        assert_eq!(seqs, vec![LOG_SIZE as u64 + 1, LOG_SIZE as u64 + 2]);
+    }
+
+    #[test]
+    fn denial_spikes_are_reported() {
+        let state = State::default();
+        state.denials.store(DENIAL_SPIKE - 1, Ordering::Relaxed);
+        assert!(state.check(0).is_empty());
+        state.denials.store(DENIAL_SPIKE, Ordering::Relaxed);
+        assert_eq!(
+            state.check(0),
+            vec![NodeEvent::PolicyDenialSpike {
+                denials: DENIAL_SPIKE,
+                period: CHECK_INTERVAL.as_secs(),
+            }]
+        );
+        // The counter is reset on every check
+        assert!(state.check(0).is_empty());
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[ockam_macros::test]
+    async fn events_are_posted_to_the_webhook(ctx: &mut ockam_node::Context) -> Result<()> {
+        use std::io::{Read, Write};
+
+        // A webhook answering a single request, and handing it to the test
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/events", listener.local_addr().unwrap());
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = Vec::new();
+            let mut chunk = [0; 4096];
+            while !String::from_utf8_lossy(&buf).contains("\"event\"") {
+                let n = stream.read(&mut chunk).unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .unwrap();
+            tx.send(String::from_utf8(buf).unwrap()).unwrap();
+        });
+
+        let webhook = WebhookConfig {
+            url,
+            secret: Some("secret".to_string()),
+        };
+        let events = Events::new("n1".to_string(), Some(webhook));
+        events.emit(NodeEvent::ForwarderRecovered {
+            session: "key".to_string(),
+            description: None,
+            address: "/service/api".to_string(),
+        });
+
+        let req = tokio::task::spawn_blocking(move || rx.recv_timeout(Duration::from_secs(10)))
+            .await
+            .unwrap()
+            .unwrap();
+        let req = req.to_lowercase();
+        assert!(req.starts_with("post /events"));
+        assert!(req.contains("x-ockam-event: forwarder-recovered"));
+        assert!(req.contains("x-ockam-signature: sha256="));
+        assert!(req.contains(r#""node":"n1""#));
+        assert!(req.contains(r#""event":"forwarder-recovered""#));
+
+        ctx.stop().await
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[test]
+    fn events_are_signed_with_hmac_sha256() {
+        // RFC 4231, test case 2
+        let sig = sign("Jefe", b"what do ya want for nothing?").unwrap();
+        assert_eq!(
+            sig,
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
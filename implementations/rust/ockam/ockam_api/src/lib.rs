@@ -1,16 +1,20 @@
 pub mod auth;
 pub mod authenticator;
+pub mod capability;
 pub mod cloud;
 pub mod config;
+pub mod delegation;
 pub mod echoer;
 pub mod error;
 pub mod identity;
 pub mod nodes;
+pub mod relays;
 pub mod uppercase;
 pub mod vault;
 pub mod verifier;
 
-mod session;
+mod events;
+pub mod session;
 mod util;
 pub use util::*;
 
@@ -32,12 +36,14 @@ impl DefaultAddress {
     pub const SECURE_CHANNEL_LISTENER: &'static str = "api";
     pub const AUTHENTICATOR: &'static str = "authenticator";
     pub const VERIFIER: &'static str = "verifier";
+    pub const DELEGATION_SERVICE: &'static str = "delegation";
 }
 
 use core::fmt;
 use minicbor::{Decode, Encode};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+#[cfg(feature = "cloud")]
 #[derive(rust_embed::RustEmbed)]
 #[folder = "./static"]
 pub(crate) struct StaticFiles;
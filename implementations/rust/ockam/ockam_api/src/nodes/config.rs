@@ -1,3 +1,4 @@
+use crate::config::migrations::{insert_default, Migration};
 use crate::config::ConfigValues;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
@@ -12,10 +13,35 @@ pub struct NodeManConfig {
     pub identity: Option<Vec<u8>>,
     /// Identity was overridden
     pub identity_was_overridden: bool,
+    /// Webhook receiving the node events
+    #[serde(default)]
+    pub webhook: Option<WebhookConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Key used to sign the events, if any
+    pub secret: Option<String>,
 }
 
 impl ConfigValues for NodeManConfig {
     fn default_values(_config_dir: &Path) -> Self {
         Self::default()
     }
+
+    fn migrations() -> &'static [Migration] {
+        const MIGRATIONS: &[Migration] = &[Migration {
+            description: "add the webhook receiving the node events",
+            up: |m| {
+                insert_default(m, "webhook", serde_json::Value::Null);
+                Ok(())
+            },
+            down: |m| {
+                m.remove("webhook");
+                Ok(())
+            },
+        }];
+        MIGRATIONS
+    }
 }
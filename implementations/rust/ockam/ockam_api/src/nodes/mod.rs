@@ -1,12 +1,19 @@
-mod config;
+pub(crate) mod config;
 pub mod registry;
 
 pub mod service;
 
+pub mod stdio;
+
 pub mod models;
 
+pub mod privileges;
+
 /// A const address to bind and send messages to
 pub const NODEMANAGER_ADDR: &str = "_internal.nodemanager";
 
 /// The main node-manager service running on remote nodes
-pub use service::{IdentityOverride, NodeManager};
+pub use service::{IdentityOverride, NodeManager, NodeManagerWorker};
+
+/// The configuration a node keeps in its state directory
+pub use config::NodeManConfig;
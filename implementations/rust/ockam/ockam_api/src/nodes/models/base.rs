@@ -45,3 +45,30 @@ impl<'a> NodeStatus<'a> {
         }
     }
 }
+
+/// Response body for the readiness of a node
+///
+/// A node is live as soon as it answers requests, and ready once it should
+/// receive traffic: its services are started, it holds a credential when
+/// it is a member of a project and its forwarders are up.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct NodeReadiness<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2391645>,
+    #[n(1)] pub ready: bool,
+    /// Why the node is not ready, empty when it is
+    #[b(2)] pub reasons: Vec<Cow<'a, str>>,
+}
+
+impl<'a> NodeReadiness<'a> {
+    pub fn new(reasons: Vec<Cow<'a, str>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            ready: reasons.is_empty(),
+            reasons,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use minicbor::{Decode, Encode};
+
+use ockam_core::CowBytes;
+use ockam_identity::IdentityIdentifier;
+use ockam_multiaddr::MultiAddr;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Request body when instructing a node to send a request to the
+/// delegation service of another node
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Delegate<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5861347>,
+    /// Route to the secure channel listener of the other node.
+    #[n(1)] at: MultiAddr,
+    /// Expected identity of the other node.
+    #[n(2)] authorized: Option<IdentityIdentifier>,
+    /// The encoded request to delegate.
+    #[b(3)] request: CowBytes<'a>,
+}
+
+impl<'a> Delegate<'a> {
+    pub fn new(
+        at: MultiAddr,
+        authorized: Option<IdentityIdentifier>,
+        request: impl Into<CowBytes<'a>>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            at,
+            authorized,
+            request: request.into(),
+        }
+    }
+
+    pub fn at(&self) -> &MultiAddr {
+        &self.at
+    }
+
+    pub fn authorized(&self) -> Option<IdentityIdentifier> {
+        self.authorized.clone()
+    }
+
+    pub fn request(&self) -> &[u8] {
+        &self.request
+    }
+}
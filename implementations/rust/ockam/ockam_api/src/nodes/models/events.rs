@@ -0,0 +1,144 @@
+//! Node events request/response types
+
+use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Request body to set the webhook receiving the node events
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SetWebhook<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<1862073>,
+    #[b(1)] url: CowStr<'a>,
+    /// Key used to sign the events with HMAC-SHA256
+    #[b(2)] secret: Option<CowStr<'a>>,
+}
+
+impl<'a> SetWebhook<'a> {
+    pub fn new(url: impl Into<CowStr<'a>>, secret: Option<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            url: url.into(),
+            secret,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn secret(&self) -> Option<&str> {
+        self.secret.as_deref()
+    }
+}
+
+/// Response body describing the webhook of a node
+///
+/// The signing key is never returned.
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct WebhookInfo<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<6695138>,
+    #[b(1)] pub url: CowStr<'a>,
+    #[n(2)] pub signed: bool,
+}
+
+impl<'a> WebhookInfo<'a> {
+    pub fn new(url: impl Into<CowStr<'a>>, signed: bool) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            url: url.into(),
+            signed,
+        }
+    }
+}
+
+/// Request body to list the events of a node
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ListEvents {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4021957>,
+    /// Only list the events published after this one
+    #[n(1)] since: Option<u64>,
+}
+
+impl ListEvents {
+    pub fn new(since: Option<u64>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            since,
+        }
+    }
+
+    pub fn since(&self) -> u64 {
+        self.since.unwrap_or_default()
+    }
+}
+
+/// An event published by a node
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct EventRecord<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<7410329>,
+    /// Number of the event, in the order of publication
+    #[n(1)] pub seq: u64,
+    /// Unix time, in seconds
+    #[n(2)] pub time: u64,
+    #[b(3)] pub name: CowStr<'a>,
+    /// The event as posted to the webhook, in JSON
+    #[b(4)] pub data: CowStr<'a>,
+}
+
+impl<'a> EventRecord<'a> {
+    pub fn new(
+        seq: u64,
+        time: u64,
+        name: impl Into<CowStr<'a>>,
+        data: impl Into<CowStr<'a>>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            seq,
+            time,
+            name: name.into(),
+            data: data.into(),
+        }
+    }
+}
+
+/// Response body listing the events of a node
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct EventList<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<2830641>,
+    #[b(1)] pub events: Vec<EventRecord<'a>>,
+}
+
+impl<'a> EventList<'a> {
+    pub fn new(events: Vec<EventRecord<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            events,
+        }
+    }
+}
@@ -1,10 +1,15 @@
+use core::time::Duration;
+
 use minicbor::{Decode, Encode};
 
-use ockam::remote::RemoteForwarderInfo;
+use ockam::remote::{RemoteForwarderInfo, DEFAULT_HEARTBEAT_INTERVAL};
 use ockam_core::CowStr;
 use ockam_identity::IdentityIdentifier;
 use ockam_multiaddr::MultiAddr;
 
+use crate::nodes::models::secure_channel::CredentialExchangeMode;
+use crate::session::RetryPolicy;
+
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
 
@@ -24,10 +29,32 @@ pub struct CreateForwarder<'a> {
     /// The orchestrator address used to resolve the project address
     /// and authorised identity.
     #[n(4)] cloud_addr: Option<MultiAddr>,
-    /// An authorised identity for secure channels.
+    /// The authorised identities for secure channels, any of which the
+    /// other side may use, e.g. a cluster of relay nodes.
     /// Only set for non-project addresses as for projects the project's
     /// authorised identity will be used.
-    #[n(5)] authorized: Option<IdentityIdentifier>
+    #[n(5)] authorized: Option<Vec<IdentityIdentifier>>,
+    /// Seconds after which the secure channel of the forwarder is rotated.
+    #[n(6)] max_age: Option<u64>,
+    /// Seconds a recovery of the forwarder's session may take.
+    #[n(7)] recovery_timeout: Option<u64>,
+    /// Seconds the creation of the forwarder's secure channel may take.
+    #[n(8)] connect_timeout: Option<u64>,
+    /// Seconds before retrying a failed recovery the first time.
+    #[n(9)] retry_initial_delay: Option<u64>,
+    /// Maximum seconds between two recovery attempts.
+    #[n(10)] retry_max_delay: Option<u64>,
+    /// Failed recovery attempts after which the recovery is abandoned.
+    #[n(11)] retry_max_attempts: Option<u32>,
+    /// Seconds after which the forwarder is deleted, with its session and
+    /// secure channel.
+    #[n(12)] ttl: Option<u64>,
+    /// Seconds between two renewals of the registration of a static
+    /// forwarder, 0 to never renew it.
+    #[n(13)] heartbeat_interval: Option<u64>,
+    /// Present our credential and require one from the other side of the
+    /// forwarder's secure channel, instead of only presenting ours.
+    #[n(14)] mutual: Option<bool>,
 }
 
 impl<'a> CreateForwarder<'a> {
@@ -40,6 +67,15 @@ impl<'a> CreateForwarder<'a> {
             at_rust_node: false,
             cloud_addr: Some(cloud),
             authorized: None,
+            max_age: None,
+            recovery_timeout: None,
+            connect_timeout: None,
+            retry_initial_delay: None,
+            retry_max_delay: None,
+            retry_max_attempts: None,
+            ttl: None,
+            heartbeat_interval: None,
+            mutual: None,
         }
     }
 
@@ -47,7 +83,7 @@ impl<'a> CreateForwarder<'a> {
         address: MultiAddr,
         alias: Option<String>,
         at_rust_node: bool,
-        auth: Option<IdentityIdentifier>,
+        auth: Vec<IdentityIdentifier>,
     ) -> Self {
         Self {
             #[cfg(feature = "tag")]
@@ -56,10 +92,80 @@ impl<'a> CreateForwarder<'a> {
             alias: alias.map(|s| s.into()),
             at_rust_node,
             cloud_addr: None,
-            authorized: auth,
+            authorized: if auth.is_empty() { None } else { Some(auth) },
+            max_age: None,
+            recovery_timeout: None,
+            connect_timeout: None,
+            retry_initial_delay: None,
+            retry_max_delay: None,
+            retry_max_attempts: None,
+            ttl: None,
+            heartbeat_interval: None,
+            mutual: None,
         }
     }
 
+    /// Rotate the secure channel of the forwarder after `max_age`.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age.as_secs());
+        self
+    }
+
+    /// Allow a recovery of the forwarder's session to take `timeout`.
+    pub fn with_recovery_timeout(mut self, timeout: Duration) -> Self {
+        self.recovery_timeout = Some(timeout.as_secs());
+        self
+    }
+
+    /// Allow the creation of the forwarder's secure channel to take `timeout`.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout.as_secs());
+        self
+    }
+
+    /// Wait `delay` before retrying a failed recovery the first time.
+    pub fn with_retry_initial_delay(mut self, delay: Duration) -> Self {
+        self.retry_initial_delay = Some(delay.as_secs());
+        self
+    }
+
+    /// Wait at most `delay` between two recovery attempts.
+    pub fn with_retry_max_delay(mut self, delay: Duration) -> Self {
+        self.retry_max_delay = Some(delay.as_secs());
+        self
+    }
+
+    /// Give up recovering the forwarder after `attempts` failed attempts.
+    pub fn with_retry_max_attempts(mut self, attempts: u32) -> Self {
+        self.retry_max_attempts = Some(attempts);
+        self
+    }
+
+    /// Delete the forwarder once `ttl` has elapsed.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl.as_secs());
+        self
+    }
+
+    /// Renew the registration of the forwarder every `interval`.
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = Some(interval.as_secs().max(1));
+        self
+    }
+
+    /// Never renew the registration of the forwarder, e.g. on metered links.
+    pub fn without_heartbeats(mut self) -> Self {
+        self.heartbeat_interval = Some(0);
+        self
+    }
+
+    /// Exchange credentials in both directions over the secure channel, so
+    /// that the relay side can verify ours before accepting the registration.
+    pub fn with_mutual_credential_exchange(mut self) -> Self {
+        self.mutual = Some(true);
+        self
+    }
+
     pub fn address(&self) -> &MultiAddr {
         &self.address
     }
@@ -72,13 +178,64 @@ impl<'a> CreateForwarder<'a> {
         self.at_rust_node
     }
 
-    pub fn authorized(&self) -> Option<IdentityIdentifier> {
+    pub fn authorized(&self) -> Option<Vec<IdentityIdentifier>> {
         self.authorized.clone()
     }
 
     pub fn cloud_addr(&self) -> Option<&MultiAddr> {
         self.cloud_addr.as_ref()
     }
+
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age.map(Duration::from_secs)
+    }
+
+    pub fn recovery_timeout(&self) -> Option<Duration> {
+        self.recovery_timeout.map(Duration::from_secs)
+    }
+
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout.map(Duration::from_secs)
+    }
+
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl.map(Duration::from_secs)
+    }
+
+    /// Interval between two renewals of the registration, if renewed.
+    pub fn heartbeat_interval(&self) -> Option<Duration> {
+        match self.heartbeat_interval {
+            None => Some(DEFAULT_HEARTBEAT_INTERVAL),
+            Some(0) => None,
+            Some(secs) => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    /// How credentials are exchanged over the forwarder's secure channel.
+    pub fn credential_exchange_mode(&self) -> CredentialExchangeMode {
+        if self.mutual == Some(true) {
+            CredentialExchangeMode::Mutual
+        } else {
+            CredentialExchangeMode::Oneway
+        }
+    }
+
+    /// How failed recoveries are retried, with defaults for unset values.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        let default = RetryPolicy::default();
+        RetryPolicy {
+            initial_delay: self
+                .retry_initial_delay
+                .map(Duration::from_secs)
+                .unwrap_or(default.initial_delay),
+            max_delay: self
+                .retry_max_delay
+                .map(Duration::from_secs)
+                .unwrap_or(default.max_delay),
+            max_attempts: self.retry_max_attempts.or(default.max_attempts),
+            ..default
+        }
+    }
 }
 
 /// Response body when creating a forwarder
@@ -92,6 +249,12 @@ pub struct ForwarderInfo<'a> {
     #[b(1)] forwarding_route: CowStr<'a>,
     #[b(2)] remote_address: CowStr<'a>,
     #[b(3)] worker_address: CowStr<'a>,
+    #[b(4)] alias: Option<CowStr<'a>>,
+    /// Whether a session recovers the forwarder when it breaks.
+    #[n(5)] session: Option<bool>,
+    /// The identity authenticated on the other end of the forwarder's
+    /// secure channel, if it uses one.
+    #[b(6)] remote_identity: Option<CowStr<'a>>,
 }
 
 impl<'a> ForwarderInfo<'a> {
@@ -102,6 +265,37 @@ impl<'a> ForwarderInfo<'a> {
     pub fn remote_address(&'a self) -> &'a str {
         &self.remote_address
     }
+
+    pub fn worker_address(&'a self) -> &'a str {
+        &self.worker_address
+    }
+
+    pub fn alias(&self) -> Option<&str> {
+        self.alias.as_deref()
+    }
+
+    pub fn has_session(&self) -> bool {
+        self.session.unwrap_or(false)
+    }
+
+    pub fn remote_identity(&self) -> Option<&str> {
+        self.remote_identity.as_deref()
+    }
+
+    pub fn with_alias(mut self, alias: impl Into<CowStr<'a>>) -> Self {
+        self.alias = Some(alias.into());
+        self
+    }
+
+    pub fn with_session(mut self, session: bool) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    pub fn with_remote_identity(mut self, identity: Option<&IdentityIdentifier>) -> Self {
+        self.remote_identity = identity.map(|i| i.to_string().into());
+        self
+    }
 }
 
 impl<'a> From<RemoteForwarderInfo> for ForwarderInfo<'a> {
@@ -112,6 +306,75 @@ impl<'a> From<RemoteForwarderInfo> for ForwarderInfo<'a> {
             forwarding_route: inner.forwarding_route().to_string().into(),
             remote_address: inner.remote_address().to_string().into(),
             worker_address: inner.worker_address().to_string().into(),
+            alias: None,
+            session: None,
+            remote_identity: None,
+        }
+    }
+}
+
+/// Response body describing a forwarder and the health of its session
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ForwarderStatus<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<4418905>,
+    #[b(1)] pub remote_address: CowStr<'a>,
+    #[b(2)] pub forwarding_route: CowStr<'a>,
+    #[b(3)] pub alias: Option<CowStr<'a>>,
+    /// Address of the secure channel the forwarder uses, if any
+    #[b(4)] pub secure_channel: Option<CowStr<'a>>,
+    /// Whether a session recovers the forwarder when it breaks
+    #[n(5)] pub session: bool,
+    /// Unix time in seconds when a ping was last answered
+    #[n(6)] pub last_pong: Option<u64>,
+    #[n(7)] pub recoveries: u64,
+    /// Whether the session is down or does not answer pings
+    #[n(8)] pub degraded: bool,
+    /// Seconds until the forwarder expires, if it has a TTL
+    #[n(9)] pub expires_in: Option<u64>,
+    /// The identity authenticated on the other end of the secure channel
+    #[b(10)] pub remote_identity: Option<CowStr<'a>>,
+}
+
+impl<'a> ForwarderStatus<'a> {
+    pub fn new(info: &ForwarderInfo<'a>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            remote_address: info.remote_address.clone(),
+            forwarding_route: info.forwarding_route.clone(),
+            alias: info.alias.clone(),
+            secure_channel: None,
+            session: false,
+            last_pong: None,
+            recoveries: 0,
+            degraded: false,
+            expires_in: None,
+            remote_identity: info.remote_identity.clone(),
+        }
+    }
+}
+
+/// Response body when returning a list of forwarders
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ForwarderList<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<6393471>,
+    #[b(1)] pub list: Vec<ForwarderInfo<'a>>
+}
+
+impl<'a> ForwarderList<'a> {
+    pub fn new(list: Vec<ForwarderInfo<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
         }
     }
 }
@@ -155,7 +418,7 @@ mod tests {
                     route_to_multiaddr(&route).unwrap(),
                     None,
                     false,
-                    None,
+                    vec![],
                 ))
                 .encode(&mut buf)?;
             buf
@@ -189,6 +452,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn mutual_credential_exchange_is_encoded() {
+        let addr: MultiAddr = "/dnsaddr/localhost/tcp/4000/secure/api".parse().unwrap();
+        let req = CreateForwarder::at_node(addr.clone(), None, false, vec![]);
+        assert!(matches!(
+            req.credential_exchange_mode(),
+            CredentialExchangeMode::Oneway
+        ));
+
+        let req =
+            CreateForwarder::at_node(addr, None, false, vec![]).with_mutual_credential_exchange();
+        let buf = minicbor::to_vec(&req).unwrap();
+        let req: CreateForwarder = minicbor::decode(&buf).unwrap();
+        assert!(matches!(
+            req.credential_exchange_mode(),
+            CredentialExchangeMode::Mutual
+        ));
+    }
+
     struct Echoer;
 
     #[ockam::worker]
@@ -0,0 +1,162 @@
+//! Scheduled jobs request/response types
+
+use core::fmt;
+use core::str::FromStr;
+use core::time::Duration;
+
+use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+use ockam_multiaddr::MultiAddr;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// The action performed by a job every time it runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+#[serde(rename_all = "kebab-case")]
+pub enum JobKind {
+    /// Get a fresh credential from the project authority
+    #[n(0)] RotateCredential,
+    /// Resolve the route to a project through the orchestrator
+    #[n(1)] ResolveProject,
+}
+
+impl fmt::Display for JobKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobKind::RotateCredential => f.write_str("rotate-credential"),
+            JobKind::ResolveProject => f.write_str("resolve-project"),
+        }
+    }
+}
+
+impl FromStr for JobKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rotate-credential" => Ok(JobKind::RotateCredential),
+            "resolve-project" => Ok(JobKind::ResolveProject),
+            _ => Err(format!("unknown job kind {s}")),
+        }
+    }
+}
+
+/// Request body to schedule a recurring job
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateJob<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<8107726>,
+    #[n(1)] kind: JobKind,
+    /// Seconds between two runs
+    #[n(2)] interval: u64,
+    /// Project id, for `ResolveProject` jobs
+    #[b(3)] project: Option<CowStr<'a>>,
+    /// Orchestrator address, for `ResolveProject` jobs
+    #[n(4)] cloud_addr: Option<MultiAddr>,
+}
+
+impl<'a> CreateJob<'a> {
+    pub fn rotate_credential(interval: Duration) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            kind: JobKind::RotateCredential,
+            interval: interval.as_secs(),
+            project: None,
+            cloud_addr: None,
+        }
+    }
+
+    pub fn resolve_project(
+        interval: Duration,
+        project: impl Into<CowStr<'a>>,
+        cloud_addr: MultiAddr,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            kind: JobKind::ResolveProject,
+            interval: interval.as_secs(),
+            project: Some(project.into()),
+            cloud_addr: Some(cloud_addr),
+        }
+    }
+
+    pub fn kind(&self) -> JobKind {
+        self.kind
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval)
+    }
+
+    pub fn project(&self) -> Option<&str> {
+        self.project.as_deref()
+    }
+
+    pub fn cloud_addr(&self) -> Option<&MultiAddr> {
+        self.cloud_addr.as_ref()
+    }
+}
+
+/// Response body describing a scheduled job
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct JobStatus<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<3010297>,
+    #[b(1)] pub id: CowStr<'a>,
+    #[n(2)] pub kind: JobKind,
+    /// Seconds between two runs
+    #[n(3)] pub interval: u64,
+    #[n(4)] pub runs: u64,
+    #[n(5)] pub failures: u64,
+    /// Unix time of the last run, in seconds
+    #[n(6)] pub last_run: Option<u64>,
+    /// Outcome of the last run, or its error
+    #[b(7)] pub last_result: Option<CowStr<'a>>,
+}
+
+impl<'a> JobStatus<'a> {
+    pub fn new(id: impl Into<CowStr<'a>>, kind: JobKind, interval: Duration) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            id: id.into(),
+            kind,
+            interval: interval.as_secs(),
+            runs: 0,
+            failures: 0,
+            last_run: None,
+            last_result: None,
+        }
+    }
+}
+
+/// Response body when returning a list of jobs
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct JobList<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<5542218>,
+    #[b(1)] pub list: Vec<JobStatus<'a>>
+}
+
+impl<'a> JobList<'a> {
+    pub fn new(list: Vec<JobStatus<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Session medic request/response types
+
+use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Request body to change the settings of the medic
+///
+/// Settings which are not set are left unchanged.
+#[derive(Debug, Clone, Default, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct UpdateMedic {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5183362>,
+    #[n(1)] paused: Option<bool>,
+    /// Milliseconds
+    #[n(2)] interval: Option<u64>,
+}
+
+impl UpdateMedic {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pause(mut self, paused: bool) -> Self {
+        self.paused = Some(paused);
+        self
+    }
+
+    pub fn interval(mut self, interval: core::time::Duration) -> Self {
+        self.interval = Some(interval.as_millis() as u64);
+        self
+    }
+
+    pub fn paused(&self) -> Option<bool> {
+        self.paused
+    }
+
+    pub fn interval_millis(&self) -> Option<u64> {
+        self.interval
+    }
+}
+
+/// Response body describing the medic and the sessions it checks
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct MedicStatus<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<2967140>,
+    #[n(1)] pub paused: bool,
+    /// Milliseconds
+    #[n(2)] pub interval: u64,
+    /// Number of times the medic was restarted after a failure
+    #[n(3)] pub restarts: u64,
+    #[b(4)] pub sessions: Vec<SessionStatus<'a>>,
+}
+
+impl<'a> MedicStatus<'a> {
+    pub fn new(
+        paused: bool,
+        interval: u64,
+        restarts: u64,
+        sessions: Vec<SessionStatus<'a>>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            paused,
+            interval,
+            restarts,
+            sessions,
+        }
+    }
+}
+
+/// A session checked by the medic
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SessionStatus<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<8531207>,
+    #[b(1)] pub key: CowStr<'a>,
+    #[b(2)] pub address: CowStr<'a>,
+    #[b(3)] pub description: Option<CowStr<'a>>,
+    #[n(4)] pub up: bool,
+    /// Round-trip time of the last answered ping, in milliseconds
+    #[n(5)] pub rtt: Option<u64>,
+    #[n(6)] pub recoveries: u64,
+    /// Steps of the last recovery, the last one failed if the session is down
+    #[b(7)] pub last_recovery: Vec<RecoveryStep<'a>>,
+    /// Seconds after which the session is rotated
+    #[n(8)] pub max_age: Option<u64>,
+    /// Failed recovery attempts since the session went down
+    #[n(9)] pub failed_attempts: Option<u32>,
+    /// Whether the medic gave up recovering the session
+    #[n(10)] pub given_up: Option<bool>,
+    /// Round-trip time of the secure channel of the session, in
+    /// microseconds, if its keepalive measures it
+    #[n(11)] pub channel_rtt_micros: Option<u64>,
+}
+
+impl<'a> SessionStatus<'a> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        key: impl Into<CowStr<'a>>,
+        address: impl Into<CowStr<'a>>,
+        description: Option<CowStr<'a>>,
+        up: bool,
+        rtt: Option<u64>,
+        recoveries: u64,
+        last_recovery: Vec<RecoveryStep<'a>>,
+        max_age: Option<u64>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            key: key.into(),
+            address: address.into(),
+            description,
+            up,
+            rtt,
+            recoveries,
+            last_recovery,
+            max_age,
+            failed_attempts: None,
+            given_up: None,
+            channel_rtt_micros: None,
+        }
+    }
+}
+
+/// A step of the recovery of a session
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RecoveryStep<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<4092713>,
+    #[b(1)] pub step: CowStr<'a>,
+    /// Error of the step in the last recovery, if it failed
+    #[b(2)] pub error: Option<CowStr<'a>>,
+    /// Number of times the step failed since the session was created
+    #[n(3)] pub failures: u64,
+}
+
+impl<'a> RecoveryStep<'a> {
+    pub fn new(step: impl Into<CowStr<'a>>, error: Option<CowStr<'a>>, failures: u64) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            step: step.into(),
+            error,
+            failures,
+        }
+    }
+}
@@ -4,10 +4,18 @@
 /// its own
 pub mod base;
 pub mod credentials;
+pub mod delegate;
+pub mod events;
 pub mod forwarder;
 pub mod identity;
+pub mod jobs;
+pub mod medic;
+pub mod monitors;
+pub mod policy;
 pub mod portal;
+pub mod progress;
 pub mod secure_channel;
 pub mod services;
+pub mod stats;
 pub mod transport;
 pub mod vault;
@@ -0,0 +1,200 @@
+//! SLO monitors request/response types
+
+use core::fmt;
+use core::str::FromStr;
+use core::time::Duration;
+
+use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// The indicator checked by a monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+#[serde(rename_all = "kebab-case")]
+pub enum MonitorKind {
+    /// Round-trip time of the session pings, in milliseconds
+    #[n(0)] Rtt,
+    /// Number of times a session was recovered
+    #[n(1)] Recoveries,
+}
+
+impl fmt::Display for MonitorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MonitorKind::Rtt => f.write_str("rtt"),
+            MonitorKind::Recoveries => f.write_str("recoveries"),
+        }
+    }
+}
+
+impl FromStr for MonitorKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rtt" => Ok(MonitorKind::Rtt),
+            "recoveries" => Ok(MonitorKind::Recoveries),
+            _ => Err(format!("unknown monitor kind {s}")),
+        }
+    }
+}
+
+/// Where the alerts of a monitor are sent
+#[derive(Debug, Clone, Default, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AlertHook<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<4416075>,
+    /// URL to which alerts are posted as JSON
+    #[b(1)] pub webhook: Option<CowStr<'a>>,
+    /// Shell command run with the alert as JSON on its standard input
+    #[b(2)] pub command: Option<CowStr<'a>>,
+}
+
+impl<'a> AlertHook<'a> {
+    pub fn new(webhook: Option<CowStr<'a>>, command: Option<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            webhook,
+            command,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.webhook.is_none() && self.command.is_none()
+    }
+
+    pub fn to_owned<'r>(&self) -> AlertHook<'r> {
+        AlertHook {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            webhook: self.webhook.as_ref().map(|w| w.to_owned()),
+            command: self.command.as_ref().map(|c| c.to_owned()),
+        }
+    }
+}
+
+/// Request body to create a monitor
+///
+/// A monitor alerts when, for a session:
+///
+/// - `Rtt`: the ping round-trip time stays above `threshold` milliseconds
+///   for `window` seconds.
+/// - `Recoveries`: the session was recovered more than `threshold` times
+///   in the last `window` seconds.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateMonitor<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2630941>,
+    #[n(1)] kind: MonitorKind,
+    #[n(2)] threshold: u64,
+    /// Seconds
+    #[n(3)] window: u64,
+    #[b(4)] hook: AlertHook<'a>,
+}
+
+impl<'a> CreateMonitor<'a> {
+    pub fn rtt(max: Duration, window: Duration, hook: AlertHook<'a>) -> Self {
+        Self::new(MonitorKind::Rtt, max.as_millis() as u64, window, hook)
+    }
+
+    pub fn recoveries(max: u64, window: Duration, hook: AlertHook<'a>) -> Self {
+        Self::new(MonitorKind::Recoveries, max, window, hook)
+    }
+
+    fn new(kind: MonitorKind, threshold: u64, window: Duration, hook: AlertHook<'a>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            kind,
+            threshold,
+            window: window.as_secs(),
+            hook,
+        }
+    }
+
+    pub fn kind(&self) -> MonitorKind {
+        self.kind
+    }
+
+    pub fn threshold(&self) -> u64 {
+        self.threshold
+    }
+
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window)
+    }
+
+    pub fn hook(&self) -> &AlertHook<'a> {
+        &self.hook
+    }
+}
+
+/// Response body describing a monitor
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct MonitorStatus<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<7720384>,
+    #[b(1)] pub id: CowStr<'a>,
+    #[n(2)] pub kind: MonitorKind,
+    #[n(3)] pub threshold: u64,
+    /// Seconds
+    #[n(4)] pub window: u64,
+    #[b(5)] pub hook: AlertHook<'a>,
+    /// Keys of the sessions currently violating the objective
+    #[b(6)] pub firing: Vec<CowStr<'a>>,
+    /// Number of alerts sent
+    #[n(7)] pub alerts: u64,
+    /// Error of the last alert which could not be sent
+    #[b(8)] pub last_error: Option<CowStr<'a>>,
+}
+
+impl<'a> MonitorStatus<'a> {
+    pub fn new(id: impl Into<CowStr<'a>>, req: &CreateMonitor<'_>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            id: id.into(),
+            kind: req.kind,
+            threshold: req.threshold,
+            window: req.window,
+            hook: req.hook.to_owned(),
+            firing: Vec::new(),
+            alerts: 0,
+            last_error: None,
+        }
+    }
+}
+
+/// Response body when returning a list of monitors
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct MonitorList<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<3914826>,
+    #[b(1)] pub list: Vec<MonitorStatus<'a>>
+}
+
+impl<'a> MonitorList<'a> {
+    pub fn new(list: Vec<MonitorStatus<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
+        }
+    }
+}
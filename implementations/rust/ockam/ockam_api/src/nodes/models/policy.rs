@@ -0,0 +1,71 @@
+//! ABAC policy request types
+
+use std::collections::BTreeMap;
+
+use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Request body to evaluate a policy on a hypothetical request, without
+/// deploying it
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct EvaluatePolicy<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5183440>,
+    /// The policy, as the JSON of an ABAC `Conditional`
+    #[b(1)] pub policy: CowStr<'a>,
+    #[b(2)] pub resource: CowStr<'a>,
+    #[b(3)] pub action: CowStr<'a>,
+    /// The credential attributes of the subject, and the local facts,
+    /// under their `local.` keys
+    #[b(4)] pub attributes: BTreeMap<CowStr<'a>, CowStr<'a>>,
+}
+
+impl<'a> EvaluatePolicy<'a> {
+    pub fn new(
+        policy: impl Into<CowStr<'a>>,
+        resource: impl Into<CowStr<'a>>,
+        action: impl Into<CowStr<'a>>,
+        attributes: BTreeMap<String, String>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            policy: policy.into(),
+            resource: resource.into(),
+            action: action.into(),
+            attributes: attributes
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        }
+    }
+}
+
+/// Response body giving the decision of a policy
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyDecision<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<7302591>,
+    #[n(1)] pub allowed: bool,
+    /// The part of the policy the decision follows from, as JSON
+    #[b(2)] pub matched: CowStr<'a>,
+}
+
+impl<'a> PolicyDecision<'a> {
+    pub fn new(allowed: bool, matched: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            allowed,
+            matched: matched.into(),
+        }
+    }
+}
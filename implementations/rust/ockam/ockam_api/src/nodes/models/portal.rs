@@ -3,6 +3,7 @@
 use minicbor::{Decode, Encode};
 use ockam_core::compat::borrow::Cow;
 
+use crate::nodes::models::transport::SocketOptions;
 use ockam_core::CowStr;
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
@@ -24,6 +25,22 @@ pub struct CreateInlet<'a> {
     #[b(3)] pub alias: Option<CowStr<'a>>,
     /// Enable credentials authorization
     #[n(4)] pub check_credential: bool,
+    /// Other routes to the outlet, used when the outlet route is not healthy
+    #[b(5)] pub backup_routes: Option<Vec<CowStr<'a>>>,
+    /// Terminate TLS on the inlet
+    #[n(6)] pub tls: Option<bool>,
+    /// PEM file with the TLS certificate chain, self-signed if not given
+    #[b(7)] pub tls_cert: Option<CowStr<'a>>,
+    /// PEM file with the TLS private key
+    #[b(8)] pub tls_key: Option<CowStr<'a>>,
+    /// Proxy protocol spoken with clients, e.g. "socks5", which lets them
+    /// pick the target the proxy outlet connects to
+    #[b(9)] pub proxy: Option<CowStr<'a>>,
+    /// Routes of a proxy inlet to the outlets dedicated to some targets, as
+    /// `rule=route`, e.g. "db.internal:5432=/node/n1/service/db"
+    #[b(10)] pub proxy_routes: Option<Vec<CowStr<'a>>>,
+    /// Options of the socket the inlet listens on
+    #[b(11)] pub socket: Option<SocketOptions<'a>>,
 }
 
 impl<'a> CreateInlet<'a> {
@@ -40,8 +57,48 @@ impl<'a> CreateInlet<'a> {
             outlet_route: outlet_route.into(),
             alias: alias.into(),
             check_credential,
+            backup_routes: None,
+            tls: None,
+            tls_cert: None,
+            tls_key: None,
+            proxy: None,
+            proxy_routes: None,
+            socket: None,
         }
     }
+
+    pub fn with_socket_options(mut self, socket: SocketOptions<'a>) -> Self {
+        self.socket = Some(socket);
+        self
+    }
+
+    pub fn with_backup_routes(mut self, routes: Vec<CowStr<'a>>) -> Self {
+        self.backup_routes = Some(routes);
+        self
+    }
+
+    /// Terminate TLS with the given certificate and key files, or with a
+    /// self-signed certificate if none are given.
+    pub fn with_tls(mut self, cert_and_key: Option<(CowStr<'a>, CowStr<'a>)>) -> Self {
+        self.tls = Some(true);
+        if let Some((cert, key)) = cert_and_key {
+            self.tls_cert = Some(cert);
+            self.tls_key = Some(key);
+        }
+        self
+    }
+
+    /// Speak a proxy protocol with clients.
+    pub fn with_proxy(mut self, protocol: impl Into<CowStr<'a>>) -> Self {
+        self.proxy = Some(protocol.into());
+        self
+    }
+
+    /// Send the connections of a proxy to some targets to dedicated outlets.
+    pub fn with_proxy_routes(mut self, routes: Vec<CowStr<'a>>) -> Self {
+        self.proxy_routes = Some(routes);
+        self
+    }
 }
 
 /// Request body to create an inlet or outlet
@@ -59,6 +116,17 @@ pub struct CreateOutlet<'a> {
     #[b(3)] pub alias: Option<CowStr<'a>>,
     /// Enable credentials authorization
     #[n(4)] pub check_credential: bool,
+    /// Connect to the target over TLS, expecting a certificate for this name
+    #[b(5)] pub tls_server_name: Option<CowStr<'a>>,
+    /// PEM file with the CA certificates of the target, the system's if not given
+    #[b(6)] pub tls_ca: Option<CowStr<'a>>,
+    /// PEM file with the client certificate chain presented to the target
+    #[b(7)] pub tls_cert: Option<CowStr<'a>>,
+    /// PEM file with the private key of the client certificate
+    #[b(8)] pub tls_key: Option<CowStr<'a>>,
+    /// Make a proxy outlet, which connects to the targets requested by
+    /// proxy inlets if one of these egress rules allows them
+    #[b(9)] pub egress: Option<Vec<CowStr<'a>>>,
 }
 
 impl<'a> CreateOutlet<'a> {
@@ -75,7 +143,34 @@ impl<'a> CreateOutlet<'a> {
             worker_addr: worker_addr.into(),
             alias: alias.into(),
             check_credential,
+            tls_server_name: None,
+            tls_ca: None,
+            tls_cert: None,
+            tls_key: None,
+            egress: None,
+        }
+    }
+
+    /// Make a proxy outlet allowing the targets matched by `rules`.
+    pub fn with_egress(mut self, rules: Vec<CowStr<'a>>) -> Self {
+        self.egress = Some(rules);
+        self
+    }
+
+    /// Connect to the target over TLS.
+    pub fn with_tls(
+        mut self,
+        server_name: impl Into<CowStr<'a>>,
+        ca: Option<CowStr<'a>>,
+        cert_and_key: Option<(CowStr<'a>, CowStr<'a>)>,
+    ) -> Self {
+        self.tls_server_name = Some(server_name.into());
+        self.tls_ca = ca;
+        if let Some((cert, key)) = cert_and_key {
+            self.tls_cert = Some(cert);
+            self.tls_key = Some(key);
         }
+        self
     }
 }
 
@@ -91,6 +186,15 @@ pub struct InletStatus<'a> {
     #[b(3)] pub alias: Cow<'a, str>,
     /// An optional status payload
     #[b(4)] pub payload: Option<Cow<'a, str>>,
+    /// The route to the outlet, when listing inlets
+    #[b(5)] pub outlet_route: Option<Cow<'a, str>>,
+    /// Whether credentials are checked, when listing inlets
+    #[n(6)] pub check_credential: Option<bool>,
+    /// The certificate presented to clients, if the inlet terminates TLS
+    #[b(7)] pub tls_cert: Option<Cow<'a, str>>,
+    /// The identity authenticated on the other end of the secure channel
+    /// to the outlet, if the outlet is reached through one
+    #[b(8)] pub outlet_identity: Option<Cow<'a, str>>,
 }
 
 impl<'a> InletStatus<'a> {
@@ -102,6 +206,10 @@ impl<'a> InletStatus<'a> {
             worker_addr: "".into(),
             alias: "".into(),
             payload: Some(reason.into()),
+            outlet_route: None,
+            check_credential: None,
+            tls_cert: None,
+            outlet_identity: None,
         }
     }
 
@@ -118,6 +226,10 @@ impl<'a> InletStatus<'a> {
             worker_addr: worker_addr.into(),
             alias: alias.into(),
             payload: payload.into(),
+            outlet_route: None,
+            check_credential: None,
+            tls_cert: None,
+            outlet_identity: None,
         }
     }
 }
@@ -134,6 +246,8 @@ pub struct OutletStatus<'a> {
     #[b(3)] pub alias: Cow<'a, str>,
     /// An optional status payload
     #[b(4)] pub payload: Option<Cow<'a, str>>,
+    /// Whether credentials are checked, when listing outlets
+    #[n(5)] pub check_credential: Option<bool>,
 }
 
 impl<'a> OutletStatus<'a> {
@@ -145,6 +259,7 @@ impl<'a> OutletStatus<'a> {
             worker_addr: "".into(),
             alias: "".into(),
             payload: Some(reason.into()),
+            check_credential: None,
         }
     }
 
@@ -161,6 +276,7 @@ impl<'a> OutletStatus<'a> {
             worker_addr: worker_addr.into(),
             alias: alias.into(),
             payload: payload.into(),
+            check_credential: None,
         }
     }
 }
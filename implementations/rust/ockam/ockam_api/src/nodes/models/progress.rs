@@ -0,0 +1,47 @@
+use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Body of an interim `Processing` response reporting the progress of
+/// a long running request.
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ProgressEvent<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<4174562>,
+    /// Human readable name of the phase.
+    #[b(1)] phase: CowStr<'a>,
+    #[n(2)] state: PhaseState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+#[serde(rename_all = "lowercase")]
+pub enum PhaseState {
+    #[n(0)] Started,
+    #[n(1)] Completed,
+}
+
+impl<'a> ProgressEvent<'a> {
+    pub fn new(phase: impl Into<CowStr<'a>>, state: PhaseState) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: Default::default(),
+            phase: phase.into(),
+            state,
+        }
+    }
+
+    pub fn phase(&self) -> &str {
+        &self.phase
+    }
+
+    pub fn state(&self) -> PhaseState {
+        self.state
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+use std::fmt;
 use std::time::Duration;
 
 use minicbor::{Decode, Encode};
@@ -6,15 +8,16 @@ use crate::nodes::registry::SecureChannelInfo;
 use ockam_core::compat::borrow::Cow;
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
-use ockam_core::{route, Address, CowStr, Result};
-use ockam_identity::IdentityIdentifier;
+use ockam_core::{route, Address, CowBytes, CowStr, Result};
+use ockam_identity::{IdentityIdentifier, KeyAgreement, PreSharedKey, SecureChannelStats};
 use ockam_multiaddr::MultiAddr;
 use serde::Serialize;
 
 use crate::error::ApiError;
 use crate::route_to_multiaddr;
+use crate::session::RetryPolicy;
 
-#[derive(Debug, Clone, Copy, Decode, Encode)]
+#[derive(Debug, Clone, Copy, Decode, Encode, Serialize, PartialEq, Eq)]
 #[rustfmt::skip]
 #[cbor(index_only)]
 pub enum CredentialExchangeMode {
@@ -23,6 +26,83 @@ pub enum CredentialExchangeMode {
     #[n(2)] Mutual,
 }
 
+/// How the two sides of a secure channel agree on its keys, see [`KeyAgreement`]
+#[derive(Debug, Clone, Copy, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+pub enum KeyAgreementMode {
+    #[n(0)] Classical,
+    /// X25519 combined with ML-KEM-768 (Kyber)
+    #[n(1)] Hybrid,
+}
+
+/// A pre-shared key authenticating secure channels, see [`PreSharedKey`]
+#[derive(Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PreSharedKeyArg<'a> {
+    #[b(1)] pub id: CowStr<'a>,
+    #[b(2)] pub secret: CowBytes<'a>,
+}
+
+impl<'a> PreSharedKeyArg<'a> {
+    pub fn new(id: impl Into<CowStr<'a>>, secret: impl Into<CowBytes<'a>>) -> Self {
+        Self {
+            id: id.into(),
+            secret: secret.into(),
+        }
+    }
+
+    pub fn to_psk(&self) -> Result<PreSharedKey> {
+        PreSharedKey::new(self.id.to_string(), self.secret.to_vec())
+    }
+}
+
+impl fmt::Debug for PreSharedKeyArg<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreSharedKeyArg")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An authority trusted by a secure channel or listener, instead of those
+/// of the node, see [`CreateSecureChannelRequest::with_authorities`]
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AuthorityArg<'a> {
+    /// The exported identity of the authority
+    #[b(1)] pub identity: CowBytes<'a>,
+    /// Where to get a credential from the authority, if the node needs one
+    #[b(2)] pub route: Option<CowStr<'a>>,
+}
+
+impl<'a> AuthorityArg<'a> {
+    pub fn new(identity: impl Into<CowBytes<'a>>, route: Option<&MultiAddr>) -> Self {
+        Self {
+            identity: identity.into(),
+            route: route.map(|r| r.to_string().into()),
+        }
+    }
+
+    pub fn to_owned<'r>(&self) -> AuthorityArg<'r> {
+        AuthorityArg {
+            identity: self.identity.to_owned(),
+            route: self.route.as_ref().map(|r| r.to_owned()),
+        }
+    }
+}
+
+impl From<KeyAgreementMode> for KeyAgreement {
+    fn from(mode: KeyAgreementMode) -> Self {
+        match mode {
+            KeyAgreementMode::Classical => KeyAgreement::Classical,
+            KeyAgreementMode::Hybrid => KeyAgreement::Hybrid,
+        }
+    }
+}
+
 /// Request body when instructing a node to create a Secure Channel
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
@@ -33,7 +113,39 @@ pub struct CreateSecureChannelRequest<'a> {
     #[b(1)] pub addr: CowStr<'a>,
     #[b(2)] pub authorized_identifiers: Option<Vec<CowStr<'a>>>,
     #[n(3)] pub credential_exchange_mode: CredentialExchangeMode,
-    #[n(4)] pub timeout: Option<Duration>
+    #[n(4)] pub timeout: Option<Duration>,
+    /// Monitor the channel and re-establish it when it breaks
+    #[n(5)] pub monitor: Option<bool>,
+    /// The channel this one replaces, which is not reused
+    #[b(6)] pub replaces: Option<CowStr<'a>>,
+    /// Close the channel once it carried no messages for this long
+    #[n(7)] pub idle_timeout: Option<Duration>,
+    /// How the keys of the channel are agreed on, classical by default
+    #[n(8)] pub key_agreement: Option<KeyAgreementMode>,
+    /// Ping the other side when the channel is quiet for this long, and
+    /// close it when it stops answering
+    #[n(9)] pub keepalive: Option<Duration>,
+    /// Use the secure channel listeners of the address as relays, with a
+    /// channel to each over the previous one, so that they only learn the
+    /// next hop
+    #[n(10)] pub onion: Option<bool>,
+    /// Authenticate the channel with a pre-shared key, instead of
+    /// presenting credentials
+    #[b(11)] pub psk: Option<PreSharedKeyArg<'a>>,
+    /// Give up creating the channel after this many failed attempts, it
+    /// is only attempted once by default
+    #[n(12)] pub retry_max_attempts: Option<u32>,
+    /// Time before retrying a failed attempt the first time
+    #[n(13)] pub retry_initial_delay: Option<Duration>,
+    /// Maximum time between two attempts
+    #[n(14)] pub retry_max_delay: Option<Duration>,
+    /// The trust context of the channel: credentials issued by these
+    /// authorities are presented and verified over it, instead of those
+    /// of the node
+    #[b(15)] pub authorities: Option<Vec<AuthorityArg<'a>>>,
+    /// Ping the other side every keepalive interval, quiet or not, to
+    /// measure the round-trip time of the channel
+    #[n(16)] pub measure_rtt: Option<bool>,
 }
 
 impl<'a> CreateSecureChannelRequest<'a> {
@@ -50,6 +162,103 @@ impl<'a> CreateSecureChannelRequest<'a> {
                 .map(|x| x.into_iter().map(|y| y.to_string().into()).collect()),
             credential_exchange_mode,
             timeout: None,
+            monitor: None,
+            replaces: None,
+            idle_timeout: None,
+            key_agreement: None,
+            keepalive: None,
+            onion: None,
+            psk: None,
+            retry_max_attempts: None,
+            retry_initial_delay: None,
+            retry_max_delay: None,
+            authorities: None,
+            measure_rtt: None,
+        }
+    }
+
+    pub fn with_monitor(mut self) -> Self {
+        self.monitor = Some(true);
+        self
+    }
+
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    pub fn with_key_agreement(mut self, key_agreement: KeyAgreementMode) -> Self {
+        self.key_agreement = Some(key_agreement);
+        self
+    }
+
+    pub fn with_keepalive(mut self, interval: Duration) -> Self {
+        self.keepalive = Some(interval);
+        self
+    }
+
+    /// Measure the round-trip time of the channel with its keepalives.
+    pub fn with_rtt(mut self) -> Self {
+        self.measure_rtt = Some(true);
+        self
+    }
+
+    pub fn with_onion(mut self) -> Self {
+        self.onion = Some(true);
+        self
+    }
+
+    pub fn with_psk(mut self, psk: PreSharedKeyArg<'a>) -> Self {
+        self.psk = Some(psk);
+        self
+    }
+
+    /// Give up creating the channel after `attempts` failed attempts.
+    pub fn with_retry_max_attempts(mut self, attempts: u32) -> Self {
+        self.retry_max_attempts = Some(attempts);
+        self
+    }
+
+    /// Wait `delay` before retrying a failed attempt the first time.
+    pub fn with_retry_initial_delay(mut self, delay: Duration) -> Self {
+        self.retry_initial_delay = Some(delay);
+        self
+    }
+
+    /// Wait at most `delay` between two attempts.
+    pub fn with_retry_max_delay(mut self, delay: Duration) -> Self {
+        self.retry_max_delay = Some(delay);
+        self
+    }
+
+    /// Present and verify credentials of `authorities` over the channel,
+    /// instead of those of the node.
+    pub fn with_authorities(mut self, authorities: Vec<AuthorityArg<'a>>) -> Self {
+        self.authorities = Some(authorities);
+        self
+    }
+
+    pub fn monitor(&self) -> bool {
+        self.monitor.unwrap_or(false)
+    }
+
+    pub fn key_agreement(&self) -> KeyAgreement {
+        self.key_agreement
+            .map(KeyAgreement::from)
+            .unwrap_or_default()
+    }
+
+    /// How failed attempts to create the channel are retried, if they are.
+    pub fn retry_policy(&self) -> Option<RetryPolicy> {
+        let default = RetryPolicy::default();
+        match self.retry_max_attempts {
+            Some(n) if n > 1 => Some(RetryPolicy {
+                initial_delay: self.retry_initial_delay.unwrap_or(default.initial_delay),
+                max_delay: self.retry_max_delay.unwrap_or(default.max_delay),
+                max_attempts: Some(n),
+                ..default
+            }),
+            _ => None,
         }
     }
 }
@@ -96,6 +305,15 @@ pub struct CreateSecureChannelListenerRequest<'a> {
     #[n(0)] tag: TypeTag<8112242>,
     #[b(1)] pub addr: Cow<'a, str>,
     #[b(2)] pub authorized_identifiers: Option<Vec<CowStr<'a>>>,
+    /// How the keys of the channels are agreed on, classical by default
+    #[n(3)] pub key_agreement: Option<KeyAgreementMode>,
+    /// Credential attributes the initiators must have presented to the node
+    #[b(4)] pub required_attributes: Option<BTreeMap<CowStr<'a>, CowStr<'a>>>,
+    /// Only accept the channels authenticated with this pre-shared key
+    #[b(5)] pub psk: Option<PreSharedKeyArg<'a>>,
+    /// The trust context of the channels: only credentials issued by these
+    /// authorities give attributes to their messages
+    #[b(6)] pub authorities: Option<Vec<AuthorityArg<'a>>>,
 }
 
 impl<'a> CreateSecureChannelListenerRequest<'a> {
@@ -106,8 +324,55 @@ impl<'a> CreateSecureChannelListenerRequest<'a> {
             addr: addr.to_string().into(),
             authorized_identifiers: authorized_identifiers
                 .map(|x| x.into_iter().map(|y| y.to_string().into()).collect()),
+            key_agreement: None,
+            required_attributes: None,
+            psk: None,
+            authorities: None,
         }
     }
+
+    pub fn with_key_agreement(mut self, key_agreement: KeyAgreementMode) -> Self {
+        self.key_agreement = Some(key_agreement);
+        self
+    }
+
+    pub fn key_agreement(&self) -> KeyAgreement {
+        self.key_agreement
+            .map(KeyAgreement::from)
+            .unwrap_or_default()
+    }
+
+    pub fn with_psk(mut self, psk: PreSharedKeyArg<'a>) -> Self {
+        self.psk = Some(psk);
+        self
+    }
+
+    /// Only give the attributes of credentials issued by `authorities` to
+    /// the messages of the channels, instead of those of the node.
+    pub fn with_authorities(mut self, authorities: Vec<AuthorityArg<'a>>) -> Self {
+        self.authorities = Some(authorities);
+        self
+    }
+
+    pub fn with_required_attributes(mut self, attributes: BTreeMap<String, String>) -> Self {
+        self.required_attributes = Some(
+            attributes
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        );
+        self
+    }
+
+    /// The required attributes, as the credential storage holds them
+    pub fn required_attributes(&self) -> Option<Vec<(String, Vec<u8>)>> {
+        self.required_attributes.as_ref().map(|attrs| {
+            attrs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.as_bytes().to_vec()))
+                .collect()
+        })
+    }
 }
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
@@ -175,6 +440,19 @@ pub struct ShowSecureChannelResponse<'a> {
     #[b(1)] pub channel: Option<Cow<'a, str>>,
     #[b(2)] pub route: Option<Cow<'a, str>>,
     #[b(4)] pub authorized_identifiers: Option<Vec<CowStr<'a>>>,
+    #[b(5)] pub their_identity_id: Option<CowStr<'a>>,
+    /// Seconds since the Unix epoch
+    #[n(6)] pub created_at: Option<u64>,
+    #[n(7)] pub credential_exchange_mode: Option<CredentialExchangeMode>,
+    /// Messages sent and received through the channel, keepalives excluded
+    #[n(8)] pub sent: Option<u64>,
+    #[n(9)] pub received: Option<u64>,
+    /// Identities at the other side of the channels this one goes over,
+    /// outermost first
+    #[b(10)] pub hops: Option<Vec<CowStr<'a>>>,
+    /// Round-trip time of the channel in microseconds, if its keepalive
+    /// measures it
+    #[n(11)] pub rtt_micros: Option<u64>,
 }
 
 impl<'a> ShowSecureChannelResponse<'a> {
@@ -190,6 +468,24 @@ impl<'a> ShowSecureChannelResponse<'a> {
                         .map(|ids| ids.iter().map(|iid| iid.to_string().into()).collect())
                 })
                 .unwrap_or(None),
+            their_identity_id: None,
+            created_at: info.map(|info| info.created_at()),
+            credential_exchange_mode: info.map(|info| info.credential_exchange_mode()),
+            sent: None,
+            received: None,
+            hops: None,
+            rtt_micros: None,
+        }
+    }
+
+    pub fn with_stats(mut self, stats: Option<SecureChannelStats>) -> Self {
+        if let Some(stats) = stats {
+            self.their_identity_id = Some(stats.their_identity_id.to_string().into());
+            self.sent = Some(stats.sent as u64);
+            self.received = Some(stats.received as u64);
+            self.hops = Some(stats.hops.iter().map(|id| id.to_string().into()).collect());
+            self.rtt_micros = stats.rtt.map(|rtt| rtt.as_micros() as u64);
         }
+        self
     }
 }
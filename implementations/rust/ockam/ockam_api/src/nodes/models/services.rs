@@ -2,6 +2,7 @@ use std::path::Path;
 
 use minicbor::{bytes::ByteSlice, Decode, Encode};
 use ockam_core::compat::borrow::Cow;
+use ockam_identity::IdentityIdentifier;
 
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
@@ -192,3 +193,47 @@ impl<'a> StartCredentialsService<'a> {
         self.oneway
     }
 }
+
+/// Request body when instructing a node to start a Delegation service
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartDelegationService<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2367021>,
+    #[b(1)] addr: &'a str,
+    /// Identities allowed to send requests through the service
+    #[n(2)] authorized: Vec<IdentityIdentifier>,
+    /// Also accept the requests allowed by the capabilities in the
+    /// credential of an identity
+    #[n(3)] capabilities: Option<bool>,
+}
+
+impl<'a> StartDelegationService<'a> {
+    pub fn new(addr: &'a str, authorized: Vec<IdentityIdentifier>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr,
+            authorized,
+            capabilities: None,
+        }
+    }
+
+    pub fn with_capabilities(mut self) -> Self {
+        self.capabilities = Some(true);
+        self
+    }
+
+    pub fn address(&self) -> &'a str {
+        self.addr
+    }
+
+    pub fn authorized(&self) -> &[IdentityIdentifier] {
+        &self.authorized
+    }
+
+    pub fn capabilities(&self) -> bool {
+        self.capabilities.unwrap_or(false)
+    }
+}
@@ -0,0 +1,87 @@
+//! Node stats request/response types
+
+use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Request body to enable or disable the accounting of the workers of a
+/// node
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SetWorkerAccounting {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5163902>,
+    #[n(1)] pub enabled: bool,
+}
+
+impl SetWorkerAccounting {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            enabled,
+        }
+    }
+}
+
+/// What a worker of a node consumed handling its messages
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct WorkerStats<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<3902716>,
+    #[b(1)] pub address: CowStr<'a>,
+    #[n(2)] pub messages: u64,
+    /// Cumulative time spent handling the messages, in microseconds
+    #[n(3)] pub handler_micros: u64,
+    /// Bytes of the messages handled, an estimate of what the worker
+    /// allocated to handle them
+    #[n(4)] pub allocated_bytes: u64,
+}
+
+impl<'a> WorkerStats<'a> {
+    pub fn new(
+        address: impl Into<CowStr<'a>>,
+        messages: u64,
+        handler_micros: u64,
+        allocated_bytes: u64,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            address: address.into(),
+            messages,
+            handler_micros,
+            allocated_bytes,
+        }
+    }
+}
+
+/// Response body listing the stats of the workers of a node
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct WorkerStatsList<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<7240589>,
+    /// Whether the workers are accounted for, there are no stats otherwise
+    #[n(1)] pub enabled: bool,
+    #[b(2)] pub workers: Vec<WorkerStats<'a>>,
+}
+
+impl<'a> WorkerStatsList<'a> {
+    pub fn new(enabled: bool, workers: Vec<WorkerStats<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            enabled,
+            workers,
+        }
+    }
+}
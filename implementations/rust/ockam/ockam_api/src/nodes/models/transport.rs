@@ -1,5 +1,7 @@
 use minicbor::{Decode, Encode};
+use ockam::tcp::ListenerOptions;
 use ockam_core::compat::borrow::Cow;
+use ockam_core::CowStr;
 use std::fmt::{self, Display};
 
 #[cfg(feature = "tag")]
@@ -21,6 +23,8 @@ pub struct CreateTransport<'a> {
     #[n(2)] pub tm: TransportMode,
     /// The address payload for the transport
     #[n(3)] pub addr: Cow<'a, str>,
+    /// Options of the socket of a listener
+    #[b(4)] pub socket: Option<SocketOptions<'a>>,
 }
 
 impl<'a> CreateTransport<'a> {
@@ -31,8 +35,84 @@ impl<'a> CreateTransport<'a> {
             tt,
             tm,
             addr: addr.into(),
+            socket: None,
         }
     }
+
+    pub fn with_socket_options(mut self, socket: SocketOptions<'a>) -> Self {
+        self.socket = Some(socket);
+        self
+    }
+}
+
+/// Options of the socket a TCP listener or inlet listens on
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SocketOptions<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6143587>,
+    /// Only accept connections received on this network interface
+    #[b(1)] pub interface: Option<CowStr<'a>>,
+    /// Whether a listener on an IPv6 address only accepts IPv6 connections
+    #[n(2)] pub only_v6: Option<bool>,
+    /// Share the address with other sockets, with SO_REUSEPORT
+    #[n(3)] pub reuse_port: bool,
+    /// The maximum number of connections waiting to be accepted
+    #[n(4)] pub backlog: Option<u32>,
+    /// Listen on the socket passed by the service manager, e.g. systemd
+    #[n(5)] pub socket_activation: bool,
+    /// The number of processors accepting connections, with SO_REUSEPORT
+    #[n(6)] pub accept_workers: Option<u32>,
+}
+
+impl<'a> SocketOptions<'a> {
+    pub fn new(
+        interface: Option<CowStr<'a>>,
+        only_v6: Option<bool>,
+        reuse_port: bool,
+        backlog: Option<u32>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            interface,
+            only_v6,
+            reuse_port,
+            backlog,
+            socket_activation: false,
+            accept_workers: None,
+        }
+    }
+
+    pub fn with_socket_activation(mut self, socket_activation: bool) -> Self {
+        self.socket_activation = socket_activation;
+        self
+    }
+
+    pub fn with_accept_workers(mut self, accept_workers: Option<u32>) -> Self {
+        self.accept_workers = accept_workers;
+        self
+    }
+
+    pub fn listener_options(&self) -> ListenerOptions {
+        let mut options = ListenerOptions::new()
+            .with_reuse_port(self.reuse_port)
+            .with_socket_activation(self.socket_activation);
+        if let Some(interface) = &self.interface {
+            options = options.with_interface(interface.as_ref())
+        }
+        if let Some(only_v6) = self.only_v6 {
+            options = options.with_only_v6(only_v6)
+        }
+        if let Some(backlog) = self.backlog {
+            options = options.with_backlog(backlog)
+        }
+        if let Some(n) = self.accept_workers {
+            options = options.with_accept_workers(n as usize)
+        }
+        options
+    }
 }
 
 /// Request to delete a transport
@@ -60,6 +140,34 @@ impl<'a> DeleteTransport<'a> {
     }
 }
 
+/// Request to move a listener to another address
+///
+/// The listener keeps its transport ID.  It only stops listening on its
+/// former address once listening on the new one succeeded.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RebindTransport<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)]
+    tag: TypeTag<8265714>,
+    /// The transport ID to rebind
+    #[n(1)] pub tid: Cow<'a, str>,
+    /// The new address to listen on
+    #[n(2)] pub addr: Cow<'a, str>,
+}
+
+impl<'a> RebindTransport<'a> {
+    pub fn new<S: Into<Cow<'a, str>>>(tid: S, addr: S) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            tid: tid.into(),
+            addr: addr.into(),
+        }
+    }
+}
+
 /// Encode which type of transport is being requested
 // TODO: we have a TransportType in ockam_core.  Do we really want to
 // mirror this kind of type here?
@@ -73,6 +181,10 @@ pub enum TransportType {
     #[n(1)] Ble,
     /// Websocket transport
     #[n(2)] WebSocket,
+    /// Node API requests read from stdin, answered on stdout
+    #[n(3)] Stdio,
+    /// Ockam QUIC transport
+    #[n(4)] Quic,
 }
 
 impl Display for TransportType {
@@ -81,6 +193,8 @@ impl Display for TransportType {
             Self::Tcp => "TCP",
             Self::Ble => "BLE",
             Self::WebSocket => "Websocket",
+            Self::Stdio => "stdio",
+            Self::Quic => "QUIC",
         })
     }
 }
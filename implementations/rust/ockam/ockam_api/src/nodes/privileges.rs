@@ -0,0 +1,106 @@
+//! Dropping the privileges of a node started as root, e.g. to listen on a
+//! privileged port like 443, once it no longer needs them
+//!
+//! The sockets a node bound while root stay open, whatever user it then
+//! runs as. Its state directory is given to that user so that it can keep
+//! writing its state, the directories above it must be readable by that
+//! user too, e.g. with `OCKAM_HOME=/var/lib/ockam`.
+
+use std::path::Path;
+
+use ockam::Result;
+
+use crate::error::ApiError;
+
+/// Switch the process to `user` and to `group`, by default the group of
+/// `user`, giving them the directory `dir` first
+///
+/// Nothing is done when the process already runs as them. Otherwise the
+/// process must run as root, and can't get its privileges back.
+#[cfg(unix)]
+pub fn drop_privileges(user: &str, group: Option<&str>, dir: Option<&Path>) -> Result<()> {
+    use nix::unistd::{self, Group, Uid, User};
+
+    let u = User::from_name(user)
+        .map_err(ApiError::wrap)?
+        .ok_or_else(|| ApiError::message(format!("unknown user {}", user)))?;
+    let gid = match group {
+        Some(group) => {
+            Group::from_name(group)
+                .map_err(ApiError::wrap)?
+                .ok_or_else(|| ApiError::message(format!("unknown group {}", group)))?
+                .gid
+        }
+        None => u.gid,
+    };
+    if unistd::geteuid() == u.uid && unistd::getegid() == gid {
+        return Ok(());
+    }
+    if !unistd::geteuid().is_root() {
+        return Err(ApiError::message(format!(
+            "only root can switch to user {}",
+            user
+        )));
+    }
+
+    if let Some(dir) = dir {
+        chown_all(dir, u.uid, gid)?;
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    unistd::setgroups(&[gid]).map_err(ApiError::wrap)?;
+    // nix doesn't wrap setgroups on Apple platforms
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    if unsafe { libc::setgroups(1, &gid.as_raw()) } != 0 {
+        return Err(ApiError::wrap(std::io::Error::last_os_error()));
+    }
+    unistd::setgid(gid).map_err(ApiError::wrap)?;
+    unistd::setuid(u.uid).map_err(ApiError::wrap)?;
+    if !u.uid.is_root() && unistd::setuid(Uid::from_raw(0)).is_ok() {
+        return Err(ApiError::generic("failed to drop the privileges of root"));
+    }
+    info!(user, gid = %gid, "Dropped the privileges of root");
+    Ok(())
+}
+
+/// Give `path` and everything below it to `uid` and `gid`, without
+/// following symbolic links
+#[cfg(unix)]
+fn chown_all(path: &Path, uid: nix::unistd::Uid, gid: nix::unistd::Gid) -> Result<()> {
+    use nix::unistd::{fchownat, FchownatFlags};
+
+    fchownat(
+        None,
+        path,
+        Some(uid),
+        Some(gid),
+        FchownatFlags::NoFollowSymlink,
+    )
+    .map_err(|e| ApiError::message(format!("failed to chown {}: {}", path.display(), e)))?;
+    let meta = std::fs::symlink_metadata(path).map_err(ApiError::wrap)?;
+    if meta.is_dir() {
+        let entries = std::fs::read_dir(path).map_err(ApiError::wrap)?;
+        for entry in entries {
+            chown_all(&entry.map_err(ApiError::wrap)?.path(), uid, gid)?;
+        }
+    }
+    Ok(())
+}
+
+/// Switching users is not supported on this platform
+#[cfg(not(unix))]
+pub fn drop_privileges(user: &str, _: Option<&str>, _: Option<&Path>) -> Result<()> {
+    Err(ApiError::message(format!(
+        "switching to user {} is not supported on this platform",
+        user
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_users_are_refused() {
+        assert!(drop_privileges("no-such-ockam-user", None, None).is_err());
+    }
+}
@@ -1,7 +1,9 @@
-use crate::nodes::service::Alias;
+use crate::nodes::models::secure_channel::CredentialExchangeMode;
 use ockam_core::compat::collections::BTreeMap;
 use ockam_core::{Address, Route};
-use ockam_identity::IdentityIdentifier;
+use ockam_identity::{IdentityIdentifier, KeyAgreement};
+use ockam_node::tokio::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Default)]
 pub(crate) struct SecureChannelRegistry {
@@ -22,9 +24,20 @@ impl SecureChannelRegistry {
         addr: Address,
         route: Route,
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+        key_agreement: KeyAgreement,
     ) {
-        self.channels
-            .push(SecureChannelInfo::new(route, addr, authorized_identifiers))
+        self.channels.push(SecureChannelInfo::new(
+            route,
+            addr,
+            authorized_identifiers,
+            key_agreement,
+        ))
+    }
+
+    pub fn set_credential_exchange_mode(&mut self, addr: &Address, mode: CredentialExchangeMode) {
+        if let Some(c) = self.channels.iter_mut().find(|x| x.addr() == addr) {
+            c.credential_exchange_mode = mode
+        }
     }
 
     pub fn remove_by_addr(&mut self, addr: &Address) {
@@ -43,6 +56,11 @@ pub struct SecureChannelInfo {
     // Local address of the created channel
     addr: Address,
     authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+    key_agreement: KeyAgreement,
+    // Credentials presented once the channel was created
+    credential_exchange_mode: CredentialExchangeMode,
+    // Seconds since the Unix epoch
+    created_at: u64,
 }
 
 impl SecureChannelInfo {
@@ -50,11 +68,18 @@ impl SecureChannelInfo {
         route: Route,
         addr: Address,
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+        key_agreement: KeyAgreement,
     ) -> Self {
         Self {
             addr,
             route,
             authorized_identifiers,
+            key_agreement,
+            credential_exchange_mode: CredentialExchangeMode::None,
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
         }
     }
 
@@ -69,6 +94,18 @@ impl SecureChannelInfo {
     pub fn authorized_identifiers(&self) -> Option<&Vec<IdentityIdentifier>> {
         self.authorized_identifiers.as_ref()
     }
+
+    pub fn key_agreement(&self) -> KeyAgreement {
+        self.key_agreement
+    }
+
+    pub fn credential_exchange_mode(&self) -> CredentialExchangeMode {
+        self.credential_exchange_mode
+    }
+
+    pub fn created_at(&self) -> u64 {
+        self.created_at
+    }
 }
 
 #[derive(Default)]
@@ -98,13 +135,24 @@ pub(crate) struct CredentialsServiceInfo {}
 #[derive(Default)]
 pub(crate) struct AuthenticatorServiceInfo {}
 
+#[derive(Default)]
+pub(crate) struct DelegationServiceInfo {}
+
 pub(crate) struct InletInfo {
     pub(crate) bind_addr: String,
     pub(crate) worker_addr: Address,
+    pub(crate) outlet_route: String,
+    pub(crate) check_credential: bool,
+    pub(crate) tls_cert: Option<String>,
 }
 
 impl InletInfo {
-    pub(crate) fn new(bind_addr: &str, worker_addr: Option<&Address>) -> Self {
+    pub(crate) fn new(
+        bind_addr: &str,
+        worker_addr: Option<&Address>,
+        outlet_route: &str,
+        check_credential: bool,
+    ) -> Self {
         let worker_addr = match worker_addr {
             Some(addr) => addr.clone(),
             None => Address::from_string(""),
@@ -112,6 +160,9 @@ impl InletInfo {
         Self {
             bind_addr: bind_addr.to_owned(),
             worker_addr,
+            outlet_route: outlet_route.to_owned(),
+            check_credential,
+            tls_cert: None,
         }
     }
 }
@@ -119,10 +170,15 @@ impl InletInfo {
 pub(crate) struct OutletInfo {
     pub(crate) tcp_addr: String,
     pub(crate) worker_addr: Address,
+    pub(crate) check_credential: bool,
 }
 
 impl OutletInfo {
-    pub(crate) fn new(tcp_addr: &str, worker_addr: Option<&Address>) -> Self {
+    pub(crate) fn new(
+        tcp_addr: &str,
+        worker_addr: Option<&Address>,
+        check_credential: bool,
+    ) -> Self {
         let worker_addr = match worker_addr {
             Some(addr) => addr.clone(),
             None => Address::from_string(""),
@@ -130,25 +186,23 @@ impl OutletInfo {
         Self {
             tcp_addr: tcp_addr.to_owned(),
             worker_addr,
+            check_credential,
         }
     }
 }
 
 #[derive(Default)]
 pub(crate) struct Registry {
-    pub(crate) secure_channels: SecureChannelRegistry,
-    pub(crate) secure_channel_listeners: BTreeMap<Address, SecureChannelListenerInfo>,
-    pub(crate) vault_services: BTreeMap<Address, VaultServiceInfo>,
-    pub(crate) identity_services: BTreeMap<Address, IdentityServiceInfo>,
-    pub(crate) authenticated_services: BTreeMap<Address, AuthenticatedServiceInfo>,
-    pub(crate) uppercase_services: BTreeMap<Address, UppercaseServiceInfo>,
-    pub(crate) echoer_services: BTreeMap<Address, EchoerServiceInfo>,
-    pub(crate) verifier_services: BTreeMap<Address, VerifierServiceInfo>,
-    pub(crate) credentials_services: BTreeMap<Address, CredentialsServiceInfo>,
+    pub(crate) secure_channels: RwLock<SecureChannelRegistry>,
+    pub(crate) secure_channel_listeners: RwLock<BTreeMap<Address, SecureChannelListenerInfo>>,
+    pub(crate) vault_services: RwLock<BTreeMap<Address, VaultServiceInfo>>,
+    pub(crate) identity_services: RwLock<BTreeMap<Address, IdentityServiceInfo>>,
+    pub(crate) authenticated_services: RwLock<BTreeMap<Address, AuthenticatedServiceInfo>>,
+    pub(crate) uppercase_services: RwLock<BTreeMap<Address, UppercaseServiceInfo>>,
+    pub(crate) echoer_services: RwLock<BTreeMap<Address, EchoerServiceInfo>>,
+    pub(crate) verifier_services: RwLock<BTreeMap<Address, VerifierServiceInfo>>,
+    pub(crate) credentials_services: RwLock<BTreeMap<Address, CredentialsServiceInfo>>,
+    pub(crate) delegation_services: RwLock<BTreeMap<Address, DelegationServiceInfo>>,
     #[cfg(feature = "direct-authenticator")]
-    pub(crate) authenticator_service: BTreeMap<Address, AuthenticatorServiceInfo>,
-
-    // FIXME: wow this is a terrible way to store data
-    pub(crate) inlets: BTreeMap<Alias, InletInfo>,
-    pub(crate) outlets: BTreeMap<Alias, OutletInfo>,
+    pub(crate) authenticator_service: RwLock<BTreeMap<Address, AuthenticatorServiceInfo>>,
 }
@@ -2,11 +2,17 @@
 
 use std::collections::BTreeMap;
 use std::error::Error as _;
+use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use minicbor::Decoder;
 
-use ockam::{Address, Context, ForwardingService, Result, Routed, TcpTransport, Worker};
+use ockam::{
+    Address, Context, ForwardingService, ForwardingServiceOptions, Result, Routed, TcpTransport,
+    Worker,
+};
 use ockam_core::api::{Error, Method, Request, Response, Status};
 use ockam_core::compat::{
     boxed::Box,
@@ -15,36 +21,72 @@ use ockam_core::compat::{
 };
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::AsyncTryClone;
-use ockam_identity::{Identity, IdentityIdentifier, PublicIdentity};
+use ockam_identity::{Identity, KeyAgreement, PublicIdentity};
 use ockam_multiaddr::MultiAddr;
 use ockam_node::tokio;
+use ockam_node::tokio::sync::{Notify, RwLock};
 use ockam_node::tokio::task::JoinHandle;
+#[cfg(feature = "quic")]
+use ockam_transport_quic::QuicTransport;
 use ockam_vault::storage::FileStorage;
-use ockam_vault::Vault;
+use ockam_vault::{Vault, VerifyingVault};
 
 use super::registry::Registry;
+#[cfg(feature = "cloud")]
+use crate::cloud::CloudService;
 use crate::config::{cli::AuthoritiesConfig, Config};
 use crate::error::ApiError;
+use crate::events::Events;
 use crate::lmdb::LmdbStorage;
 use crate::nodes::config::NodeManConfig;
 use crate::nodes::models::base::NodeStatus;
 use crate::nodes::models::transport::{TransportMode, TransportType};
+use crate::nodes::service::progress::Progress;
 use crate::session::{Medic, Sessions};
 use crate::DefaultAddress;
+use delegate::DelegateService;
+use events::EventsService;
+use forwarder::{ForwarderService, FORWARDERS_FILE};
+use jobs::JobService;
+use medic::MedicService;
+use message::MessageService;
+use monitors::MonitorService;
+use policy::PolicyService;
+use portals::PortalService;
+use service_registry::ServiceRegistry;
+use stats::StatsService;
+use webhook::WebhookService;
 
 pub mod message;
+pub(crate) mod progress;
+pub(crate) mod service_registry;
 
 mod credentials;
+mod delegate;
+mod events;
 mod forwarder;
+mod health;
 mod identity;
+mod jobs;
+mod medic;
+mod monitors;
+mod policy;
 mod portals;
+mod reconnect;
+mod renewal;
 mod secure_channel;
 mod services;
+mod stats;
 mod transport;
 mod vault;
+mod webhook;
 
 const TARGET: &str = "ockam_api::nodemanager::service";
 
+/// Lifetime of the tickets with which secure channels to this node are
+/// resumed, e.g. when a forwarder's session is recovered after a TCP drop
+pub(crate) const SECURE_CHANNEL_RESUMPTION: Duration = Duration::from_secs(60 * 60);
+
 pub(crate) type Alias = String;
 
 /// Generate a new alias for some user created extension
@@ -87,28 +129,46 @@ impl AsRef<[AuthorityInfo]> for Authorities {
 
 pub(crate) struct AuthorityInfo {
     identity: PublicIdentity,
-    addr: MultiAddr,
+    /// Where to get a credential from, for the authorities of a trust
+    /// context which the node may not be a member of
+    addr: Option<MultiAddr>,
 }
 
 /// Node manager provides a messaging API to interact with the current node
+///
+/// All mutable state lives behind its own lock, so that independent
+/// requests can be handled concurrently through a shared reference.
+/// See [`NodeManagerWorker`] for the worker serving the API.
+///
+/// The node manager itself only serves the resources shared by the whole
+/// node (vault, identity, transports, secure channels and built-in services).
+/// Everything else is served by services registered in its service registry.
 pub struct NodeManager {
     node_name: String,
     node_dir: PathBuf,
     config: Config<NodeManConfig>,
     api_transport_id: Alias,
-    transports: BTreeMap<Alias, (TransportType, TransportMode, String)>,
+    transports: RwLock<BTreeMap<Alias, (TransportType, TransportMode, String)>>,
     tcp_transport: TcpTransport,
-    pub(crate) controller_identity_id: IdentityIdentifier,
+    #[cfg(feature = "quic")]
+    quic_transport: QuicTransport,
     skip_defaults: bool,
     enable_credential_checks: bool,
-    vault: Option<Vault>,
-    identity: Option<Identity<Vault>>,
+    vault: RwLock<Option<Vault>>,
+    identity: RwLock<Option<Arc<Identity<Vault>>>>,
     project_id: Option<Vec<u8>>,
     authorities: Option<Authorities>,
     pub(crate) authenticated_storage: LmdbStorage,
     pub(crate) registry: Registry,
-    sessions: Arc<Mutex<Sessions>>,
+    services: ServiceRegistry,
+    /// Whether the services are started, e.g. restored their state
+    started: AtomicBool,
+    /// Notified when a peer refuses the credential of the node
+    credential_refused: Notify,
     medic: JoinHandle<Result<(), ockam_core::Error>>,
+    /// The sessions of the medic, for the monitored secure channels
+    sessions: Arc<Mutex<Sessions>>,
+    pub(crate) events: Events,
 }
 
 pub struct IdentityOverride {
@@ -117,15 +177,19 @@ pub struct IdentityOverride {
 }
 
 impl NodeManager {
-    pub(crate) fn identity(&self) -> Result<&Identity<Vault>> {
+    pub(crate) async fn identity(&self) -> Result<Arc<Identity<Vault>>> {
         self.identity
-            .as_ref()
+            .read()
+            .await
+            .clone()
             .ok_or_else(|| ApiError::generic("Identity doesn't exist"))
     }
 
-    pub(crate) fn vault(&self) -> Result<&Vault> {
+    pub(crate) async fn vault(&self) -> Result<Vault> {
         self.vault
-            .as_ref()
+            .read()
+            .await
+            .clone()
             .ok_or_else(|| ApiError::generic("Vault doesn't exist"))
     }
 
@@ -215,7 +279,13 @@ impl NodeManager {
         let identity_info = config.readlock_inner().identity.clone();
         let identity = match identity_info {
             Some(identity) => match vault.as_ref() {
-                Some(vault) => Some(Identity::import(ctx, &identity, vault).await?),
+                Some(vault) => {
+                    let identity = Identity::import(ctx, &identity, vault).await?;
+                    identity
+                        .set_secure_channel_resumption(Some(SECURE_CHANNEL_RESUMPTION))
+                        .await;
+                    Some(Arc::new(identity))
+                }
                 None => None,
             },
             None => None,
@@ -231,38 +301,63 @@ impl NodeManager {
             ));
         }
 
-        let medic = Medic::new();
-        let sessions = medic.sessions();
+        let webhook = config.readlock_inner().webhook.clone();
+        let events = Events::new(node_name.clone(), webhook);
+        let medic = Medic::new(events.clone());
+
+        let mut services = ServiceRegistry::default();
+        services
+            .register(PortalService::default())
+            .register(ForwarderService::new(
+                medic.handle().sessions(),
+                node_dir.join(FORWARDERS_FILE),
+            ))
+            .register(MessageService)
+            .register(JobService::default())
+            .register(MonitorService::new(medic.handle().sessions()))
+            .register(MedicService::new(medic.handle()))
+            .register(DelegateService)
+            .register(WebhookService)
+            .register(EventsService)
+            .register(StatsService)
+            .register(PolicyService);
+        #[cfg(feature = "cloud")]
+        services.register(CloudService::new()?);
 
         let mut s = Self {
             node_name,
             node_dir,
             config,
             api_transport_id,
-            transports,
+            transports: RwLock::new(transports),
             tcp_transport,
-            controller_identity_id: Self::load_controller_identity_id()?,
+            #[cfg(feature = "quic")]
+            quic_transport: QuicTransport::create(ctx).await?,
             skip_defaults,
             enable_credential_checks,
-            vault,
-            identity,
+            vault: RwLock::new(vault),
+            identity: RwLock::new(identity),
             project_id,
             authorities: None,
             authenticated_storage,
             registry: Default::default(),
+            services,
+            started: AtomicBool::new(false),
+            credential_refused: Notify::new(),
+            sessions: medic.handle().sessions(),
             medic: {
                 let ctx = ctx.async_try_clone().await?;
                 tokio::spawn(medic.start(ctx))
             },
-            sessions,
+            events,
         };
 
         if !skip_defaults {
             s.create_defaults(ctx).await?;
+        }
 
-            if let Some(ac) = ac {
-                s.configure_authorities(ac).await?;
-            }
+        if let Some(ac) = ac {
+            s.configure_authorities(ac).await?;
         }
 
         s.start_echoer_service_impl(ctx, DefaultAddress::ECHO_SERVICE.into())
@@ -272,14 +367,16 @@ impl NodeManager {
     }
 
     async fn configure_authorities(&mut self, ac: &AuthoritiesConfig) -> Result<()> {
-        let vault = self.vault()?;
+        // Importing the authorities only needs public key operations,
+        // so that nodes without a vault, like relays, can check them.
+        let vault = VerifyingVault;
 
         let mut v = Vec::new();
 
         for a in ac.authorities() {
             v.push(AuthorityInfo {
-                identity: PublicIdentity::import(a.1.identity(), vault).await?,
-                addr: a.1.access_route().clone(),
+                identity: PublicIdentity::import(a.1.identity(), &vault).await?,
+                addr: Some(a.1.access_route().clone()),
             })
         }
 
@@ -288,7 +385,7 @@ impl NodeManager {
         Ok(())
     }
 
-    async fn create_defaults(&mut self, ctx: &Context) -> Result<()> {
+    async fn create_defaults(&self, ctx: &Context) -> Result<()> {
         // Create default vault and identity, if they don't exists already
         self.create_vault_impl(None, true).await?;
         self.create_identity_impl(ctx, true).await?;
@@ -296,7 +393,7 @@ impl NodeManager {
         Ok(())
     }
 
-    async fn initialize_defaults(&mut self, ctx: &Context) -> Result<()> {
+    async fn initialize_defaults(&self, ctx: &Context) -> Result<()> {
         // Start services
         self.start_vault_service_impl(ctx, DefaultAddress::VAULT_SERVICE.into())
             .await?;
@@ -307,11 +404,20 @@ impl NodeManager {
         self.start_uppercase_service_impl(ctx, DefaultAddress::UPPERCASE_SERVICE.into())
             .await?;
 
-        ForwardingService::create(ctx).await?;
+        // Members only keep their relays as long as their credentials
+        let mut forwarding = ForwardingServiceOptions::new();
+        if self.enable_credential_checks {
+            forwarding = forwarding.with_credential_validity(self.authenticated_storage.clone());
+        }
+        ForwardingService::create_with_options(ctx, forwarding).await?;
 
         self.create_secure_channel_listener_impl(
             DefaultAddress::SECURE_CHANNEL_LISTENER.into(),
             None, // Not checking identifiers here in favor of credentials check
+            None,
+            KeyAgreement::default(),
+            None,
+            None,
         )
         .await?;
 
@@ -329,10 +435,12 @@ impl NodeManager {
     //////// Request matching and response handling ////////
 
     async fn handle_request(
-        &mut self,
+        &self,
         ctx: &mut Context,
+        this: &Address,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
+        progress: &Progress,
     ) -> Result<Vec<u8>> {
         debug! {
             target: TARGET,
@@ -360,14 +468,19 @@ impl NodeManager {
                     "Running",
                     ctx.list_workers().await?.len() as u32,
                     std::process::id() as i32,
-                    self.transports.len() as u32,
+                    self.transports.read().await.len() as u32,
                 ))
                 .to_vec()?,
+            (Get, ["node", "live"]) => Response::ok(req.id()).to_vec()?,
+            (Get, ["node", "ready"]) => Response::ok(req.id())
+                .body(self.readiness().await)
+                .to_vec()?,
 
             // ==*== Tcp Connection ==*==
             // TODO: Get all tcp connections
             (Get, ["node", "tcp", "connection"]) => self
                 .get_tcp_con_or_list(req, TransportMode::Connect)
+                .await
                 .to_vec()?,
             (Post, ["node", "tcp", "connection"]) => {
                 self.add_transport(req, dec).await?.to_vec()?
@@ -379,11 +492,13 @@ impl NodeManager {
             // ==*== Tcp Listeners ==*==
             (Get, ["node", "tcp", "listener"]) => self
                 .get_tcp_con_or_list(req, TransportMode::Listen)
+                .await
                 .to_vec()?,
             (Post, ["node", "tcp", "listener"]) => self.add_transport(req, dec).await?.to_vec()?,
             (Delete, ["node", "tcp", "listener"]) => {
                 self.delete_transport(req, dec).await?.to_vec()?
             }
+            (Put, ["node", "tcp", "listener"]) => self.rebind_listener(req, dec).await?.to_vec()?,
 
             // ==*== Vault ==*==
             (Post, ["node", "vault"]) => self.create_vault(req, dec).await?.to_vec()?,
@@ -407,13 +522,14 @@ impl NodeManager {
 
             // ==*== Secure channels ==*==
             // TODO: Change to RequestBuilder format
-            (Get, ["node", "secure_channel"]) => self.list_secure_channels(req).to_vec()?,
+            (Get, ["node", "secure_channel"]) => self.list_secure_channels(req).await?.to_vec()?,
             (Get, ["node", "secure_channel_listener"]) => {
-                self.list_secure_channel_listener(req).to_vec()?
-            }
-            (Post, ["node", "secure_channel"]) => {
-                self.create_secure_channel(req, dec).await?.to_vec()?
+                self.list_secure_channel_listener(req).await.to_vec()?
             }
+            (Post, ["node", "secure_channel"]) => self
+                .create_secure_channel(ctx, this, req, dec)
+                .await?
+                .to_vec()?,
             (Delete, ["node", "secure_channel"]) => {
                 self.delete_secure_channel(req, dec).await?.to_vec()?
             }
@@ -454,96 +570,37 @@ impl NodeManager {
                 .start_credentials_service(ctx, req, dec)
                 .await?
                 .to_vec()?,
+            (Post, ["node", "services", "delegation"]) => self
+                .start_delegation_service(ctx, this, req, dec)
+                .await?
+                .to_vec()?,
 
-            // ==*== Forwarder commands ==*==
-            (Post, ["node", "forwarder"]) => self.create_forwarder(ctx, req.id(), dec).await?,
-
-            // ==*== Inlets & Outlets ==*==
-            (Get, ["node", "inlet"]) => self.get_inlets(req).to_vec()?,
-            (Get, ["node", "outlet"]) => self.get_outlets(req).to_vec()?,
-            (Post, ["node", "inlet"]) => self.create_inlet(req, dec).await?.to_vec()?,
-            (Post, ["node", "outlet"]) => self.create_outlet(req, dec).await?.to_vec()?,
-            (Delete, ["node", "portal"]) => todo!(),
-
-            // ==*== Spaces ==*==
-            (Post, ["v0", "spaces"]) => self.create_space(ctx, dec).await?,
-            (Get, ["v0", "spaces"]) => self.list_spaces(ctx, dec).await?,
-            (Get, ["v0", "spaces", id]) => self.get_space(ctx, dec, id).await?,
-            (Delete, ["v0", "spaces", id]) => self.delete_space(ctx, dec, id).await?,
-
-            // ==*== Project' enrollers ==*==
-            (Post, ["v0", "project-enrollers", project_id]) => {
-                self.add_project_enroller(ctx, dec, project_id).await?
-            }
-            (Get, ["v0", "project-enrollers", project_id]) => {
-                self.list_project_enrollers(ctx, dec, project_id).await?
-            }
-            (Delete, ["v0", "project-enrollers", project_id, identity_id]) => {
-                self.delete_project_enroller(ctx, dec, project_id, identity_id)
-                    .await?
-            }
-
-            // ==*== Projects ==*==
-            (Post, ["v0", "projects", space_id]) => self.create_project(ctx, dec, space_id).await?,
-            (Get, ["v0", "projects"]) => self.list_projects(ctx, dec).await?,
-            (Get, ["v0", "projects", project_id]) => self.get_project(ctx, dec, project_id).await?,
-            (Delete, ["v0", "projects", space_id, project_id]) => {
-                self.delete_project(ctx, dec, space_id, project_id).await?
-            }
-
-            // ==*== Enroll ==*==
-            (Post, ["v0", "enroll", "auth0"]) => self.enroll_auth0(ctx, dec).await?,
-            (Get, ["v0", "enroll", "token"]) => self.generate_enrollment_token(ctx, dec).await?,
-            (Put, ["v0", "enroll", "token"]) => {
-                self.authenticate_enrollment_token(ctx, dec).await?
-            }
-
-            // ==*== Subscriptions ==*==
-            (Post, ["subscription"]) => self.activate_subscription(ctx, dec).await?,
-            (Get, ["subscription", id]) => self.get_subscription(ctx, dec, id).await?,
-            (Get, ["subscription"]) => self.list_subscriptions(ctx, dec).await?,
-            (Put, ["subscription", id, "contact_info"]) => {
-                self.update_subscription_contact_info(ctx, dec, id).await?
-            }
-            (Put, ["subscription", id, "space_id"]) => {
-                self.update_subscription_space(ctx, dec, id).await?
-            }
-            (Put, ["subscription", id, "unsubscribe"]) => self.unsubscribe(ctx, dec, id).await?,
-
-            // ==*== Messages ==*==
-            (Post, ["v0", "message"]) => self.send_message(ctx, req, dec).await?,
-
-            // ==*== Catch-all for Unimplemented APIs ==*==
-            _ => {
-                warn!(%method, %path, "Called invalid endpoint");
-                Response::bad_request(req.id())
-                    .body(format!("Invalid endpoint: {}", path))
-                    .to_vec()?
-            }
+            // ==*== Registered services, or catch-all for Unimplemented APIs ==*==
+            _ => match self
+                .services
+                .handle_request(self, ctx, this, req, dec, progress)
+                .await?
+            {
+                Some(r) => r,
+                None => {
+                    warn!(%method, %path, "Called invalid endpoint");
+                    Response::bad_request(req.id())
+                        .body(format!("Invalid endpoint: {}", path))
+                        .to_vec()?
+                }
+            },
         };
         Ok(r)
     }
 }
 
-#[ockam::worker]
-impl Worker for NodeManager {
-    type Message = Vec<u8>;
-    type Context = Context;
-
-    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
-        if !self.skip_defaults {
-            self.initialize_defaults(ctx).await?;
-        }
-
-        Ok(())
-    }
-
-    async fn shutdown(&mut self, _: &mut Self::Context) -> Result<()> {
-        self.medic.abort();
-        Ok(())
-    }
-
-    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Vec<u8>>) -> Result<()> {
+impl NodeManager {
+    async fn handle_message(
+        &self,
+        ctx: &mut Context,
+        this: &Address,
+        msg: Routed<Vec<u8>>,
+    ) -> Result<()> {
         let mut dec = Decoder::new(msg.as_body());
         let req: Request = match dec.decode() {
             Ok(r) => r,
@@ -553,7 +610,21 @@ impl Worker for NodeManager {
             }
         };
 
-        let r = match self.handle_request(ctx, &req, &mut dec).await {
+        let progress = Progress::new(&req, msg.return_route());
+        let r = match req.timeout() {
+            Some(t) => {
+                let f = self.handle_request(ctx, this, &req, &mut dec, &progress);
+                tokio::time::timeout(t, f)
+                    .await
+                    .unwrap_or_else(|_| Err(ApiError::generic("request timed out")))
+            }
+            None => {
+                self.handle_request(ctx, this, &req, &mut dec, &progress)
+                    .await
+            }
+        };
+
+        let r = match r {
             Ok(r) => r,
             Err(err) => {
                 error! {
@@ -583,6 +654,98 @@ impl Worker for NodeManager {
     }
 }
 
+/// Worker serving the [`NodeManager`] API.
+///
+/// Every request is handled in its own task with a detached context, so a
+/// slow request (e.g. creating a forwarder) does not block others (e.g.
+/// listing secure channels).
+pub struct NodeManagerWorker {
+    node_manager: Arc<NodeManager>,
+    /// Address of the plain HTTP endpoint for health probes, if any
+    health_address: Option<SocketAddr>,
+    health: Option<JoinHandle<()>>,
+    /// Renews the credential of member nodes
+    renewal: Option<JoinHandle<()>>,
+}
+
+impl NodeManagerWorker {
+    pub fn new(node_manager: NodeManager) -> Self {
+        Self {
+            node_manager: Arc::new(node_manager),
+            health_address: None,
+            health: None,
+            renewal: None,
+        }
+    }
+
+    /// Also serve `GET /live` and `GET /ready` over plain HTTP at `addr`,
+    /// for the probes which can't send requests to the node manager,
+    /// e.g. those of Kubernetes or load balancers.
+    pub fn with_health_address(mut self, addr: SocketAddr) -> Self {
+        self.health_address = Some(addr);
+        self
+    }
+}
+
+#[ockam::worker]
+impl Worker for NodeManagerWorker {
+    type Message = Vec<u8>;
+    type Context = Context;
+
+    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        if !self.node_manager.skip_defaults {
+            self.node_manager.initialize_defaults(ctx).await?;
+        }
+
+        if let Some(addr) = self.health_address {
+            let listener = health::bind(addr).await?;
+            let node_manager = self.node_manager.clone();
+            self.health = Some(tokio::spawn(health::serve(node_manager, listener)));
+        }
+
+        if self.node_manager.authorities.is_some() {
+            let node_manager = self.node_manager.clone();
+            self.renewal = Some(tokio::spawn(renewal::renew_credential(node_manager)));
+        }
+
+        // Services may send requests to the node manager, which only
+        // handles them once initialized.
+        let node_manager = self.node_manager.clone();
+        let this = ctx.address();
+        let ctx = ctx.new_detached(Address::random_local()).await?;
+        tokio::spawn(async move {
+            let services = &node_manager.services;
+            services.start(&node_manager, &ctx, &this).await;
+            node_manager.started.store(true, Ordering::Release)
+        });
+
+        Ok(())
+    }
+
+    async fn shutdown(&mut self, _: &mut Self::Context) -> Result<()> {
+        self.node_manager.medic.abort();
+        if let Some(health) = self.health.take() {
+            health.abort()
+        }
+        if let Some(renewal) = self.renewal.take() {
+            renewal.abort()
+        }
+        Ok(())
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Vec<u8>>) -> Result<()> {
+        let node_manager = self.node_manager.clone();
+        let this = msg.msg_addr();
+        let mut ctx = ctx.new_detached(Address::random_local()).await?;
+        tokio::spawn(async move {
+            if let Err(err) = node_manager.handle_message(&mut ctx, &this, msg).await {
+                error!(target: TARGET, %err, "failed to respond to request")
+            }
+        });
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use crate::nodes::NodeManager;
@@ -593,13 +756,18 @@ pub(crate) mod tests {
     impl NodeManager {
         pub(crate) async fn test_create(ctx: &Context) -> Result<Route> {
             let node_dir = tempfile::tempdir().unwrap();
+            Self::test_create_in(ctx, node_dir.into_path()).await
+        }
+
+        /// Create a node manager whose node directory is `node_dir`.
+        pub(crate) async fn test_create_in(ctx: &Context, node_dir: PathBuf) -> Result<Route> {
             let node_manager = "manager";
             let transport = TcpTransport::create(ctx).await?;
             let node_address = transport.listen("127.0.0.1:0").await?;
-            let mut node_man = NodeManager::create(
+            let node_man = NodeManager::create(
                 ctx,
                 "node".to_string(),
-                node_dir.into_path(),
+                node_dir,
                 None,
                 true,
                 false,
@@ -619,7 +787,8 @@ pub(crate) mod tests {
             node_man.create_identity_impl(ctx, false).await?;
 
             // Initialize node_man worker and return its route
-            ctx.start_worker(node_manager, node_man).await?;
+            ctx.start_worker(node_manager, NodeManagerWorker::new(node_man))
+                .await?;
             Ok(route![node_manager])
         }
     }
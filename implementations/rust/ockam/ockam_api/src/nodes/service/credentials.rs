@@ -2,20 +2,24 @@ use crate::authenticator::direct::Client;
 use crate::error::ApiError;
 use crate::multiaddr_to_route;
 use crate::nodes::models::credentials::{GetCredentialRequest, PresentCredentialRequest};
-use crate::nodes::service::map_multiaddr_err;
+use crate::nodes::models::secure_channel::AuthorityArg;
+use crate::nodes::service::{map_multiaddr_err, Authorities, AuthorityInfo};
 use crate::nodes::NodeManager;
 use crate::DefaultAddress;
 use minicbor::Decoder;
 use ockam::Result;
 use ockam_core::api::{Request, Response, ResponseBuilder};
-use ockam_core::{route, AsyncTryClone};
+use ockam_core::route;
+use ockam_identity::credential::Credential;
+use ockam_identity::{Identity, KeyAgreement, PublicIdentity};
 use ockam_multiaddr::MultiAddr;
+use ockam_vault::{Vault, VerifyingVault};
 use std::str::FromStr;
 
 impl NodeManager {
-    pub(super) async fn get_credential_impl(&mut self, overwrite: bool) -> Result<()> {
+    pub(super) async fn get_credential_impl(&self, overwrite: bool) -> Result<()> {
         debug!("Credential check: looking for identity");
-        let identity = self.identity()?.async_try_clone().await?;
+        let identity = self.identity().await?;
 
         if identity.credential().await.is_some() && !overwrite {
             return Err(ApiError::generic("credential already exists"));
@@ -30,11 +34,38 @@ impl NodeManager {
             .first()
             .ok_or_else(|| ApiError::generic("No known Authority"))?;
 
-        debug!("Getting credential from : {}", authority.addr);
+        let credential = self.request_credential(&identity, authority).await?;
+
+        // Borrow checker issues...
+        let authorities = self.authorities()?;
+
+        let data = identity
+            .verify_self_credential(&credential, authorities.public_identities().iter())
+            .await?;
+        debug!("Verified self credential");
+        self.events
+            .credential_renewed(data.created_at(), data.expires_at());
+
+        identity.set_credential(Some(credential.to_owned())).await;
+
+        Ok(())
+    }
+
+    /// Get a credential from `authority`, over a secure channel to it
+    async fn request_credential(
+        &self,
+        identity: &Identity<Vault>,
+        authority: &AuthorityInfo,
+    ) -> Result<Credential<'static>> {
+        let addr = authority
+            .addr
+            .as_ref()
+            .ok_or_else(|| ApiError::generic("no route to the authority"))?;
+        debug!("Getting credential from : {}", addr);
 
         let allowed = vec![authority.identity.identifier().clone()];
 
-        let route = match multiaddr_to_route(&authority.addr) {
+        let route = match multiaddr_to_route(addr) {
             Some(route) => route,
             None => {
                 error!("INVALID ROUTE");
@@ -44,30 +75,88 @@ impl NodeManager {
 
         debug!("Create secure channel to project authority");
         let sc = self
-            .create_secure_channel_internal(&identity, route, Some(allowed), None)
+            .create_secure_channel_internal(
+                identity,
+                route,
+                Some(allowed),
+                None,
+                None,
+                KeyAgreement::default(),
+            )
             .await?;
         debug!("Created secure channel to project authority");
 
-        // Borrow checker issues...
-        let authorities = self.authorities()?;
-
         let mut client =
             Client::new(route![sc, DefaultAddress::AUTHENTICATOR], identity.ctx()).await?;
         let credential = client.credential().await?;
         debug!("Got credential");
+        Ok(credential.to_owned())
+    }
 
-        identity
-            .verify_self_credential(&credential, authorities.public_identities().iter())
-            .await?;
-        debug!("Verified self credential");
+    /// The credential to present over the channels with the trust context
+    /// of `authorities`: a valid one the node holds, or a new one from the
+    /// first of them it can reach.
+    pub(super) async fn credential_for(
+        &self,
+        authorities: &Authorities,
+    ) -> Result<Credential<'static>> {
+        let identity = self.identity().await?;
+        let public_identities = authorities.public_identities();
+        for authority in &public_identities {
+            if let Some(credential) = identity.credential_issued_by(authority.identifier()).await {
+                let valid = identity
+                    .verify_self_credential(&credential, public_identities.iter())
+                    .await
+                    .is_ok();
+                if valid {
+                    return Ok(credential);
+                }
+            }
+        }
 
-        identity.set_credential(Some(credential.to_owned())).await;
+        let mut last_err = ApiError::generic("no credential of the trusted authorities");
+        for authority in authorities.as_ref().iter().filter(|a| a.addr.is_some()) {
+            let credential = match self.request_credential(&identity, authority).await {
+                Ok(credential) => credential,
+                Err(err) => {
+                    let authority = authority.identity.identifier();
+                    warn!(%err, %authority, "Failed to get a credential from the authority");
+                    last_err = err;
+                    continue;
+                }
+            };
+            identity
+                .verify_self_credential(&credential, public_identities.iter())
+                .await?;
+            identity.add_credential(credential.clone()).await?;
+            return Ok(credential);
+        }
+        Err(last_err)
+    }
 
-        Ok(())
+    /// Import the authorities of a trust context
+    pub(super) async fn import_authorities(
+        &self,
+        authorities: &[AuthorityArg<'_>],
+    ) -> Result<Authorities> {
+        // Like those of the node, they only need public key operations
+        let vault = VerifyingVault;
+        let mut v = Vec::new();
+        for a in authorities {
+            let addr = match &a.route {
+                Some(r) => Some(MultiAddr::from_str(r).map_err(map_multiaddr_err)?),
+                None => None,
+            };
+            v.push(AuthorityInfo {
+                identity: PublicIdentity::import(&a.identity, &vault).await?,
+                addr,
+            })
+        }
+        Ok(Authorities::new(v))
     }
 
     pub(super) async fn get_credential(
-        &mut self,
+        &self,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder> {
@@ -92,7 +181,7 @@ impl NodeManager {
             None => return Err(ApiError::generic("invalid credentials service route")),
         };
 
-        let identity = self.identity()?;
+        let identity = self.identity().await?;
 
         if request.oneway {
             identity.present_credential(route).await?;
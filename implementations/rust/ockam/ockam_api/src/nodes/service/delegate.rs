@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+use minicbor::Decoder;
+use ockam::{Address, Context, Result};
+use ockam_core::api::{Method, Request};
+use ockam_core::{async_trait, route};
+use ockam_identity::{KeyAgreement, TrustEveryonePolicy, TrustIdentifierPolicy};
+use ockam_node::tokio::time::timeout;
+
+use crate::error::ApiError;
+use crate::nodes::models::delegate::Delegate;
+use crate::nodes::service::progress::Progress;
+use crate::nodes::service::service_registry::NodeService;
+use crate::nodes::NodeManager;
+use crate::{multiaddr_to_route, DefaultAddress};
+
+/// Time to wait for the response of a delegated request without a timeout.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Time to wait for the secure channel to the other node.
+const SC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time to wait for the other node to accept our credential.
+const CREDENTIAL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Service sending requests to the delegation service of other nodes.
+///
+/// Every request is sent through a new secure channel created with the
+/// identity of this node, which the other node must have authorized, or
+/// whose credential grants the capabilities the request needs. The
+/// response of the other node is returned as is.
+pub(crate) struct DelegateService;
+
+#[async_trait]
+impl NodeService for DelegateService {
+    async fn handle_request(
+        &self,
+        node: &NodeManager,
+        ctx: &mut Context,
+        _this: &Address,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        _progress: &Progress,
+    ) -> Result<Option<Vec<u8>>> {
+        match (req.method(), req.path_segments::<2>().as_slice()) {
+            (Some(Method::Post), ["node", "delegate"]) => {
+                self.delegate(node, ctx, dec).await.map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl DelegateService {
+    async fn delegate(
+        &self,
+        node: &NodeManager,
+        ctx: &mut Context,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let body: Delegate = dec.decode()?;
+        let delegated: Request = Decoder::new(body.request()).decode()?;
+        let route = multiaddr_to_route(body.at())
+            .ok_or_else(|| ApiError::generic(&format!("Invalid route: {}", body.at())))?;
+
+        info! {
+            at     = %body.at(),
+            method = ?delegated.method(),
+            path   = %delegated.path(),
+            "Delegating request"
+        }
+
+        // A dedicated channel, so that the identity of the other node is
+        // always checked against the one given in the request
+        let identity = node.identity().await?;
+        let sc = match body.authorized() {
+            Some(id) => {
+                identity
+                    .create_secure_channel_extended(
+                        route,
+                        TrustIdentifierPolicy::new(id),
+                        &node.authenticated_storage,
+                        SC_TIMEOUT,
+                        KeyAgreement::default(),
+                    )
+                    .await?
+            }
+            None => {
+                identity
+                    .create_secure_channel_extended(
+                        route,
+                        TrustEveryonePolicy,
+                        &node.authenticated_storage,
+                        SC_TIMEOUT,
+                        KeyAgreement::default(),
+                    )
+                    .await?
+            }
+        };
+        if identity.credential().await.is_some() {
+            let to = route![sc.clone(), DefaultAddress::CREDENTIAL_SERVICE];
+            match timeout(CREDENTIAL_TIMEOUT, identity.present_credential(to)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => debug!(%sc, %err, "Credential not presented"),
+                Err(_) => debug!(%sc, "Credential presentation timed out"),
+            }
+        }
+        let res = self.send(ctx, &sc, &delegated, body.request()).await;
+        if let Err(err) = identity.stop_secure_channel(&sc).await {
+            warn!(%sc, %err, "Failed to stop the delegation secure channel")
+        }
+        res
+    }
+
+    async fn send(
+        &self,
+        ctx: &mut Context,
+        sc: &Address,
+        delegated: &Request<'_>,
+        request: &[u8],
+    ) -> Result<Vec<u8>> {
+        let timeout = delegated.timeout().unwrap_or(DEFAULT_TIMEOUT);
+        ctx.send(
+            route![sc.clone(), DefaultAddress::DELEGATION_SERVICE],
+            request.to_vec(),
+        )
+        .await?;
+        let res = ctx.receive_duration_timeout::<Vec<u8>>(timeout).await?;
+        Ok(res.take().body())
+    }
+}
@@ -0,0 +1,83 @@
+use minicbor::Decoder;
+use ockam::{Address, Context, Result};
+use ockam_core::api::{Method, Request, Response};
+use ockam_core::async_trait;
+
+use crate::error::ApiError;
+use crate::nodes::models::events::{EventList, EventRecord, ListEvents};
+use crate::nodes::service::progress::Progress;
+use crate::nodes::service::service_registry::NodeService;
+use crate::nodes::NodeManager;
+
+/// Service listing the last events of the node.
+///
+/// Supervisors follow the events by asking for the ones published after
+/// the last one they have seen.
+pub(crate) struct EventsService;
+
+#[async_trait]
+impl NodeService for EventsService {
+    async fn handle_request(
+        &self,
+        node: &NodeManager,
+        _ctx: &mut Context,
+        _this: &Address,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        _progress: &Progress,
+    ) -> Result<Option<Vec<u8>>> {
+        let r = match (req.method(), req.path_segments::<2>().as_slice()) {
+            (Some(Method::Get), ["node", "events"]) => {
+                let since = if req.has_body() {
+                    dec.decode::<ListEvents>()?.since()
+                } else {
+                    0
+                };
+                let events = node
+                    .events
+                    .since(since)
+                    .into_iter()
+                    .map(|r| {
+                        let data = serde_json::to_string(&r.event)
+                            .map_err(|e| ApiError::generic(&e.to_string()))?;
+                        Ok(EventRecord::new(r.seq, r.time, r.event.name(), data))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Response::ok(req.id())
+                    .body(EventList::new(events))
+                    .to_vec()?
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(r))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::api::Status;
+
+    #[ockam_macros::test]
+    async fn list_events(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+
+        let req = Request::get("/node/events");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let list: EventList = dec.decode()?;
+        assert!(list.events.is_empty());
+
+        let req = Request::get("/node/events").body(ListEvents::new(Some(10)));
+        let buf = ockam_node::api::request(ctx, "", None, node_manager, req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let list: EventList = dec.decode()?;
+        assert!(list.events.is_empty());
+
+        ctx.stop().await
+    }
+}
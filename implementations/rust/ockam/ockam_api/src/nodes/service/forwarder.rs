@@ -1,49 +1,250 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
-use minicbor::Decoder;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Decoder, Encode};
 
 use ockam::remote::RemoteForwarder;
 use ockam::{Address, Result};
-use ockam_core::api::{Error, Id, Request, Response, Status};
-use ockam_core::AsyncTryClone;
-use ockam_identity::IdentityIdentifier;
+use ockam_core::api::{Method, Request, Response, ResponseBuilder, Status};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::sync::Mutex;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, AsyncTryClone};
+use ockam_identity::{IdentityIdentifier, KeyAgreement};
 use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Project, Secure, Tcp};
 use ockam_multiaddr::{Match, MultiAddr, Protocol};
-use ockam_node::tokio::time::timeout;
+use ockam_node::tokio::sync::RwLock;
+use ockam_node::tokio::time::{sleep, sleep_until, Instant};
 use ockam_node::Context;
 
-use crate::cloud::project::Project as ProjectData;
-use crate::cloud::CloudRequestWrapper;
 use crate::error::ApiError;
-use crate::nodes::models::forwarder::{CreateForwarder, ForwarderInfo};
-use crate::nodes::models::secure_channel::{
-    CreateSecureChannelRequest, CreateSecureChannelResponse, CredentialExchangeMode,
-    DeleteSecureChannelRequest,
+use crate::nodes::models::forwarder::{
+    CreateForwarder, ForwarderInfo, ForwarderList, ForwarderStatus,
 };
+use crate::nodes::service::progress::Progress;
+use crate::nodes::service::reconnect::{resolve_project, step, Reconnect};
+use crate::nodes::service::service_registry::NodeService;
 use crate::nodes::NodeManager;
-use crate::session::Session;
+use crate::relays::RelaySelector;
+use crate::session::{Key, Recovery, Session, Sessions, Status as SessionStatus, Step, ALIAS};
 use crate::{multiaddr_to_addr, multiaddr_to_route, try_address_to_multiaddr};
 
+/// Default time a recovery of a forwarder's session may take.
 const MAX_RECOVERY_TIME: Duration = Duration::from_secs(10);
+/// Default time the creation of a forwarder's secure channel may take.
 const MAX_CONNECT_TIME: Duration = Duration::from_secs(5);
-const IDENTITY: &str = "authorized_identity";
+const IDENTITIES: &str = "authorized_identities";
+/// Times the forwarders stored by a previous run of the node are tried to
+/// be recreated.
+const RESTORE_ATTEMPTS: u32 = 8;
+/// Maximum time between two attempts to recreate stored forwarders.
+const MAX_RESTORE_DELAY: Duration = Duration::from_secs(60);
 
-impl NodeManager {
-    pub(super) async fn create_forwarder(
-        &mut self,
+/// Name of the file in the node directory storing the forwarders to
+/// recreate when the node restarts.
+pub(crate) const FORWARDERS_FILE: &str = "forwarders.cbor";
+
+/// Service creating forwarders, which are recovered when their session
+/// breaks
+pub(crate) struct ForwarderService {
+    sessions: Arc<Mutex<Sessions>>,
+    /// The forwarders created by this node, by remote address
+    forwarders: Arc<RwLock<BTreeMap<String, Forwarder>>>,
+    /// The relays selected for projects with several relays
+    relays: RelaySelector,
+    /// The requests of the forwarders to recreate when the node restarts
+    store: ForwarderStore,
+    /// The forwarders stored by a previous run of the node, read before
+    /// any request is handled, and those which could not be restored
+    stored: Mutex<BTreeMap<String, ByteVec>>,
+}
+
+/// A forwarder created by this node and the session recovering it, if any.
+struct Forwarder {
+    info: ForwarderInfo<'static>,
+    session: Option<Key>,
+    /// The secure channel created for the forwarder, if any
+    channel: Option<MultiAddr>,
+    /// When the forwarder is deleted, if it has a TTL
+    expires_at: Option<Instant>,
+    /// The key of its request in the store, if it is recreated when the
+    /// node restarts
+    stored: Option<String>,
+}
+
+impl Forwarder {
+    /// Whether the forwarder has the given alias or remote address.
+    fn matches(&self, alias: &str) -> bool {
+        self.info.alias() == Some(alias) || self.info.remote_address() == alias
+    }
+
+    /// The identity on the other end of the forwarder's secure channel.
+    async fn remote_identity(&self, node: &NodeManager) -> Option<IdentityIdentifier> {
+        let route = multiaddr_to_route(self.channel.as_ref()?)?;
+        node.peer_identity(&route).await
+    }
+}
+
+impl ForwarderService {
+    pub(crate) fn new(sessions: Arc<Mutex<Sessions>>, store: PathBuf) -> Self {
+        let store = ForwarderStore::new(store);
+        let stored = store.load().unwrap_or_else(|e| {
+            error!(err = %e, "failed to load stored forwarders");
+            BTreeMap::new()
+        });
+        Self {
+            sessions,
+            forwarders: Default::default(),
+            relays: RelaySelector::new(),
+            store,
+            stored: Mutex::new(stored),
+        }
+    }
+}
+
+#[async_trait]
+impl NodeService for ForwarderService {
+    async fn handle_request(
+        &self,
+        node: &NodeManager,
+        ctx: &mut Context,
+        this: &Address,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        progress: &Progress,
+    ) -> Result<Option<Vec<u8>>> {
+        match (req.method(), req.path_segments::<5>().as_slice()) {
+            (Some(Method::Get), ["node", "forwarder"]) => {
+                Ok(Some(self.list_forwarders(node, req).await.to_vec()?))
+            }
+            (Some(Method::Post), ["node", "forwarder"]) => self
+                .create_forwarder(node, ctx, this, req, dec, progress)
+                .await
+                .map(Some),
+            (Some(Method::Get), ["node", "forwarder", alias]) => {
+                Ok(Some(self.show_forwarder(node, req, alias).await?))
+            }
+            (Some(Method::Delete), ["node", "forwarder", alias]) => Ok(Some(
+                self.delete_forwarder(node, ctx, req, alias)
+                    .await
+                    .to_vec()?,
+            )),
+            _ => Ok(None),
+        }
+    }
+
+    async fn start(&self, node: &NodeManager, ctx: &Context, this: &Address) -> Result<()> {
+        self.restore(node, ctx, this).await
+    }
+
+    async fn unready(&self, _node: &NodeManager) -> Vec<String> {
+        let mut reasons: Vec<String> = self
+            .stored
+            .lock()
+            .unwrap()
+            .values()
+            .filter_map(|buf| minicbor::decode::<CreateForwarder>(buf).ok())
+            .map(|req| format!("forwarder at {} is not restored", req.address()))
+            .collect();
+        let forwarders = self.forwarders.read().await;
+        let sessions = self.sessions.lock().unwrap();
+        for f in forwarders.values() {
+            let session = f.session.and_then(|k| sessions.session(&k));
+            if session.map(|s| s.status()) == Some(SessionStatus::Down) {
+                reasons.push(format!("forwarder {} is down", f.info.remote_address()))
+            }
+        }
+        reasons
+    }
+}
+
+impl ForwarderService {
+    async fn list_forwarders(
+        &self,
+        node: &NodeManager,
+        req: &Request<'_>,
+    ) -> ResponseBuilder<ForwarderList<'static>> {
+        let forwarders = self.forwarders.read().await;
+        let mut identities = Vec::with_capacity(forwarders.len());
+        for f in forwarders.values() {
+            identities.push(f.remote_identity(node).await)
+        }
+        let list = {
+            let sessions = self.sessions.lock().unwrap();
+            forwarders
+                .values()
+                .zip(identities)
+                .map(|(f, identity)| {
+                    let attached = f.session.and_then(|k| sessions.session(&k)).is_some();
+                    f.info
+                        .clone()
+                        .with_session(attached)
+                        .with_remote_identity(identity.as_ref())
+                })
+                .collect()
+        };
+        Response::ok(req.id()).body(ForwarderList::new(list))
+    }
+
+    async fn create_forwarder(
+        &self,
+        node: &NodeManager,
         ctx: &mut Context,
-        rid: Id,
+        this: &Address,
+        rheader: &Request<'_>,
         dec: &mut Decoder<'_>,
+        progress: &Progress,
     ) -> Result<Vec<u8>> {
+        let rid = rheader.id();
         let req: CreateForwarder = dec.decode()?;
 
         debug!(addr = %req.address(), alias = ?req.alias(), "Handling CreateForwarder request");
 
-        let addr = self.connect(ctx, &req).await?;
+        let timeout = req.connect_timeout().or_else(|| rheader.timeout());
+        match self
+            .create(node, ctx, this, &req, timeout, progress, None)
+            .await?
+        {
+            Ok(b) => Ok(Response::ok(rid).body(b).to_vec()?),
+            Err(err) => {
+                error!(?err, "Failed to create forwarder");
+                Ok(Response::builder(rid, Status::InternalServerError)
+                    .body(err.to_string())
+                    .to_vec()?)
+            }
+        }
+    }
+
+    /// Create a forwarder, and its secure channel if needed.
+    ///
+    /// Forwarders without a TTL are stored, under `stored` if they were
+    /// restored, so that they are recreated when the node restarts.
+    ///
+    /// The inner error is the failure to register the forwarder, once
+    /// connected to the remote node.
+    #[allow(clippy::too_many_arguments)]
+    async fn create(
+        &self,
+        node: &NodeManager,
+        ctx: &Context,
+        this: &Address,
+        req: &CreateForwarder<'_>,
+        timeout: Option<Duration>,
+        progress: &Progress,
+        stored: Option<String>,
+    ) -> Result<Result<ForwarderInfo<'static>>> {
+        let phase = format!("Connecting to {}", req.address());
+        progress.started(ctx, &phase).await;
+        let addr = connect(node, ctx, this, &self.relays, req, timeout).await?;
+        progress.completed(ctx, &phase).await;
+        let channel = (&addr != req.address()).then(|| addr.clone());
         let route = multiaddr_to_route(&addr)
             .ok_or_else(|| ApiError::message("invalid address: {addr}"))?;
 
+        progress.started(ctx, "Creating forwarder").await;
+        let mut session = None;
         let forwarder = if req.at_rust_node() {
             if let Some(alias) = req.alias() {
                 RemoteForwarder::create_static_without_heartbeats(ctx, route, alias).await
@@ -52,247 +253,654 @@ impl NodeManager {
             }
         } else {
             let f = if let Some(alias) = req.alias() {
-                RemoteForwarder::create_static(ctx, route, alias).await
+                let interval = req.heartbeat_interval();
+                RemoteForwarder::create_static_with_heartbeat_interval(ctx, route, alias, interval)
+                    .await
             } else {
                 RemoteForwarder::create(ctx, route).await
             };
-            if f.is_ok() {
+            if let Ok(info) = &f {
                 let c = Arc::new(ctx.async_try_clone().await?);
                 let mut s = Session::new(addr);
-                if let Some(id) = req.authorized() {
-                    // Save the authenticated identity so that we can use it if the
+                s.set_description(match req.alias() {
+                    Some(alias) => format!("forwarder {alias} at {}", req.address()),
+                    None => format!("forwarder at {}", req.address()),
+                });
+                if let Some(alias) = req.alias() {
+                    s.put(ALIAS, alias.to_string())
+                }
+                if let Some(ids) = req.authorized() {
+                    // Save the authenticated identities so that we can use them if the
                     // secure channel needs to be recreated:
-                    s.put(IDENTITY, id)
+                    s.put(IDENTITIES, ids)
                 }
-                let this = ctx.address();
-                enable_recovery(
-                    &mut s,
-                    this,
-                    c,
-                    req.address().clone(),
-                    req.cloud_addr().cloned(),
-                    req.alias().map(|a| a.to_string()),
-                );
-                self.sessions.lock().unwrap().add(s);
+                let r = Recreate {
+                    channel: Reconnect {
+                        manager: this.clone(),
+                        ctx: c,
+                        addr: req.address().clone(),
+                        cloud: req.cloud_addr().cloned(),
+                        auth: s.get::<Vec<IdentityIdentifier>>(IDENTITIES).cloned(),
+                        mode: req.credential_exchange_mode(),
+                        relays: self.relays.clone(),
+                        connect_timeout: req.connect_timeout().unwrap_or(MAX_CONNECT_TIME),
+                        key_agreement: None,
+                        keepalive: None,
+                        measure_rtt: None,
+                        onion: None,
+                        authorities: None,
+                    },
+                    alias: req.alias().map(|a| a.to_string()),
+                    recovery_timeout: req.recovery_timeout().unwrap_or(MAX_RECOVERY_TIME),
+                    heartbeat_interval: req.heartbeat_interval(),
+                    forwarders: self.forwarders.clone(),
+                    key: info.remote_address().to_string(),
+                };
+                enable_recovery(&mut s, r, req.max_age());
+                s.set_retry_policy(req.retry_policy());
+                session = Some(self.sessions.lock().unwrap().add(s));
             }
             f
         };
 
         match forwarder {
             Ok(info) => {
-                let b = ForwarderInfo::from(info);
+                progress.completed(ctx, "Creating forwarder").await;
+                let identity = match channel.as_ref().and_then(multiaddr_to_route) {
+                    Some(r) => node.peer_identity(&r).await,
+                    None => None,
+                };
+                let b = ForwarderInfo::from(info)
+                    .with_session(session.is_some())
+                    .with_remote_identity(identity.as_ref());
+                let b = match req.alias() {
+                    Some(alias) => b.with_alias(alias.to_string()),
+                    None => b,
+                };
                 debug!(
                     forwarding_route = %b.forwarding_route(),
                     remote_address = %b.remote_address(),
                     "CreateForwarder request processed, sending back response"
                 );
-                Ok(Response::ok(rid).body(b).to_vec()?)
+                let key = b.remote_address().to_string();
+                let expires_at = req.ttl().map(|ttl| Instant::now() + ttl);
+                let stored = match stored {
+                    Some(id) => Some(id),
+                    None if expires_at.is_none() => self.store(req),
+                    None => None,
+                };
+                let f = Forwarder {
+                    info: b.clone(),
+                    session,
+                    channel,
+                    expires_at,
+                    stored,
+                };
+                self.forwarders.write().await.insert(key.clone(), f);
+                if let Some(t) = expires_at {
+                    let c = ctx.async_try_clone().await?;
+                    let forwarders = self.forwarders.clone();
+                    ockam_node::tokio::spawn(expire(c, this.clone(), forwarders, key, t));
+                }
+                Ok(Ok(b))
             }
-            Err(err) => {
-                error!(?err, "Failed to create forwarder");
-                Ok(Response::builder(rid, Status::InternalServerError)
-                    .body(err.to_string())
-                    .to_vec()?)
+            Err(err) => Ok(Err(err)),
+        }
+    }
+}
+
+impl ForwarderService {
+    /// Describe the forwarder with the given alias or remote address and
+    /// the health of its session.
+    async fn show_forwarder(
+        &self,
+        node: &NodeManager,
+        req: &Request<'_>,
+        alias: &str,
+    ) -> Result<Vec<u8>> {
+        let forwarders = self.forwarders.read().await;
+        let f = match forwarders.values().find(|f| f.matches(alias)) {
+            Some(f) => f,
+            None => return Ok(Response::not_found(req.id()).to_vec()?),
+        };
+        let mut status = ForwarderStatus::new(&f.info);
+        status.secure_channel = f.channel.as_ref().map(|a| a.to_string().into());
+        status.remote_identity = f.remote_identity(node).await.map(|i| i.to_string().into());
+        status.expires_in = f
+            .expires_at
+            .map(|t| t.saturating_duration_since(Instant::now()).as_secs());
+        let sessions = self.sessions.lock().unwrap();
+        if let Some(s) = f.session.and_then(|k| sessions.session(&k)) {
+            status.session = true;
+            status.last_pong = s
+                .last_pong()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs());
+            status.recoveries = s.recoveries() as u64;
+            status.degraded =
+                s.status() == SessionStatus::Down || s.pending_pings() > 0 || s.has_given_up();
+        }
+        Ok(Response::ok(req.id()).body(status).to_vec()?)
+    }
+
+    /// Delete the forwarders with the given alias or remote address.
+    ///
+    /// Their sessions are removed so that they are not recovered anymore,
+    /// and the secure channels created for them are deleted.
+    async fn delete_forwarder(
+        &self,
+        node: &NodeManager,
+        ctx: &Context,
+        req: &Request<'_>,
+        alias: &str,
+    ) -> ResponseBuilder {
+        let deleted: Vec<Forwarder> = {
+            let mut forwarders = self.forwarders.write().await;
+            let keys: Vec<String> = forwarders
+                .iter()
+                .filter(|(_, f)| f.matches(alias))
+                .map(|(k, _)| k.clone())
+                .collect();
+            keys.iter().filter_map(|k| forwarders.remove(k)).collect()
+        };
+        if deleted.is_empty() {
+            return Response::not_found(req.id());
+        }
+        for f in deleted {
+            if let Some(id) = &f.stored {
+                if let Err(e) = self.store.remove(id) {
+                    warn!(remote_address = %f.info.remote_address(), err = %e, "failed to unstore forwarder")
+                }
+            }
+            if let Some(k) = &f.session {
+                self.sessions.lock().unwrap().remove(k);
+            }
+            let worker = Address::from(f.info.worker_address());
+            if let Err(e) = ctx.stop_worker(worker.clone()).await {
+                debug!(%worker, err = %e, "failed to stop forwarder worker")
             }
+            if let Some(channel) = &f.channel {
+                match multiaddr_to_addr(channel) {
+                    Some(a) => {
+                        if let Err(e) = node.delete_secure_channel_impl(&a).await {
+                            debug!(addr = %channel, err = %e, "failed to delete secure channel")
+                        }
+                    }
+                    None => debug!(addr = %channel, "could not map to address"),
+                }
+            }
+            info!(remote_address = %f.info.remote_address(), "Deleted forwarder");
         }
+        Response::ok(req.id())
     }
+}
 
-    /// Resolve project ID (if any) and create secure channel if necessary.
-    async fn connect(&mut self, ctx: &mut Context, req: &CreateForwarder<'_>) -> Result<MultiAddr> {
-        if let Some(p) = req.address().first() {
-            if p.code() == Project::CODE {
-                let p = p
-                    .cast::<Project>()
-                    .ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
-                let m = req
-                    .cloud_addr()
-                    .ok_or_else(|| ApiError::generic("request has no cloud address"))?;
-                let (mut a, i) = self.resolve_project(ctx, &p, m).await?;
-                a.try_extend(req.address().iter().skip(1))?;
-                debug!(addr = %a, "creating secure channel");
-                let r =
-                    multiaddr_to_route(&a).ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
-                let i = Some(vec![i]);
-                let m = CredentialExchangeMode::Oneway;
-                let a = self.create_secure_channel_impl(r, i, m, None).await?;
-                return try_address_to_multiaddr(&a);
+impl ForwarderService {
+    /// Store the request of a forwarder, returning its key in the store.
+    fn store(&self, req: &CreateForwarder<'_>) -> Option<String> {
+        let id = Address::random_local().without_type().to_owned();
+        match self.store.insert(&id, req) {
+            Ok(()) => Some(id),
+            Err(e) => {
+                warn!(addr = %req.address(), err = %e, "failed to store forwarder");
+                None
             }
         }
-        if req.address().matches(
-            0,
-            &[
-                Match::any([DnsAddr::CODE, Ip4::CODE, Ip6::CODE]),
-                Tcp::CODE.into(),
-                Secure::CODE.into(),
-            ],
-        ) {
-            debug!(addr = %req.address(), "creating secure channel");
-            let r = multiaddr_to_route(req.address())
-                .ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
-            let i = req.authorized().map(|i| vec![i]);
-            let m = CredentialExchangeMode::Oneway;
-            let a = self.create_secure_channel_impl(r, i, m, None).await?;
-            return try_address_to_multiaddr(&a);
+    }
+
+    /// Recreate the forwarders stored by a previous run of the node.
+    ///
+    /// Project addresses are resolved again, as the project may have moved.
+    /// Forwarders which can not be recreated, e.g. because the network is
+    /// not up yet, are tried again with an increasing delay, and stay stored
+    /// for the next restart if they can not be recreated at all.
+    async fn restore(&self, node: &NodeManager, ctx: &Context, this: &Address) -> Result<()> {
+        let mut pending = std::mem::take(&mut *self.stored.lock().unwrap());
+        let mut delay = Duration::from_secs(1);
+        for attempt in 1..=RESTORE_ATTEMPTS {
+            if pending.is_empty() {
+                break;
+            }
+            if attempt > 1 {
+                sleep(delay).await;
+                delay = (delay * 2).min(MAX_RESTORE_DELAY);
+            }
+            let mut failed = BTreeMap::new();
+            for (id, buf) in pending {
+                let req: CreateForwarder = match minicbor::decode(&buf) {
+                    Ok(req) => req,
+                    Err(e) => {
+                        error!(%id, err = %e, "invalid stored forwarder");
+                        continue;
+                    }
+                };
+                let timeout = req.connect_timeout();
+                let progress = Progress::disabled();
+                let r = self
+                    .create(node, ctx, this, &req, timeout, &progress, Some(id.clone()))
+                    .await;
+                match r {
+                    Ok(Ok(info)) => {
+                        info!(addr = %req.address(), remote_address = %info.remote_address(), "Restored forwarder")
+                    }
+                    Ok(Err(e)) | Err(e) => {
+                        warn!(addr = %req.address(), %attempt, err = %e, "failed to restore forwarder");
+                        failed.insert(id, buf);
+                    }
+                }
+            }
+            pending = failed;
+        }
+        for buf in pending.values() {
+            if let Ok(req) = minicbor::decode::<CreateForwarder>(buf) {
+                error!(addr = %req.address(), "Could not restore forwarder, trying again at the next restart")
+            }
         }
-        Ok(req.address().clone())
+        // Keeps the node unready until it restarts
+        *self.stored.lock().unwrap() = pending;
+        Ok(())
     }
+}
 
-    /// Resolve the project name to an address and authorised identity.
-    async fn resolve_project(
-        &mut self,
-        ctx: &mut Context,
-        project: &str,
-        cloud: &MultiAddr,
-    ) -> Result<(MultiAddr, IdentityIdentifier)> {
-        debug!(%project, %cloud, "resolving project");
-        let req = minicbor::to_vec(&CloudRequestWrapper::bare(cloud))?;
-        let vec = self
-            .get_project(ctx, &mut Decoder::new(&req), project)
-            .await?;
-        let (addr, auth) = project_data(&vec)?;
-        debug!(%project, %addr, "resolved project");
-        Ok((addr, auth))
+/// The requests of the forwarders of a node, stored in its node directory.
+struct ForwarderStore {
+    path: PathBuf,
+    /// Serializes the updates of the file
+    lock: Mutex<()>,
+}
+
+#[derive(Debug, Default, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+struct StoredForwarders {
+    /// The encoded requests of the forwarders, by key
+    #[n(1)] requests: BTreeMap<String, ByteVec>,
+}
+
+impl ForwarderStore {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// The encoded requests of the stored forwarders, by key.
+    fn load(&self) -> Result<BTreeMap<String, ByteVec>> {
+        let buf = match std::fs::read(&self.path) {
+            Ok(buf) => buf,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(e) => return Err(ockam_core::Error::new(Origin::Node, Kind::Io, e)),
+        };
+        let s: StoredForwarders = minicbor::decode(&buf).map_err(ApiError::from)?;
+        Ok(s.requests)
+    }
+
+    fn insert(&self, id: &str, req: &CreateForwarder<'_>) -> Result<()> {
+        let buf = minicbor::to_vec(req)?;
+        self.update(|m| {
+            m.insert(id.to_string(), buf.into());
+        })
+    }
+
+    fn remove(&self, id: &str) -> Result<()> {
+        self.update(|m| {
+            m.remove(id);
+        })
+    }
+
+    /// Update the stored requests and write them back atomically.
+    fn update(&self, f: impl FnOnce(&mut BTreeMap<String, ByteVec>)) -> Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut requests = self.load()?;
+        f(&mut requests);
+        let buf = minicbor::to_vec(StoredForwarders { requests })?;
+        let tmp = self.path.with_extension("cbor.tmp");
+        let io = |e| ockam_core::Error::new(Origin::Node, Kind::Io, e);
+        std::fs::write(&tmp, buf).map_err(io)?;
+        // The requests may contain the identities authorized to relay.
+        crate::config::system::set_private_file(&tmp)
+            .map_err(|e| ockam_core::Error::new(Origin::Node, Kind::Io, e))?;
+        std::fs::rename(&tmp, &self.path).map_err(io)
     }
 }
 
-/// Resolve the project name to an address and authorised identity.
+/// Delete the forwarder with the given remote address once its TTL has
+/// elapsed, unless it was deleted or replaced meanwhile.
 ///
-/// Uses message passing since callers of this fuunction do not posses a
-/// unique reference to `NodeManager`.
-async fn resolve_project(
+/// The deletion goes through the node manager at address `manager`, which
+/// also removes the session and secure channel of the forwarder.
+async fn expire(
+    ctx: Context,
     manager: Address,
-    ctx: &Context,
-    project: &str,
-    cloud: &MultiAddr,
-) -> Result<(MultiAddr, IdentityIdentifier)> {
-    debug!(%project, %cloud, "resolving project");
-    let req = Request::get(format!("/v0/projects/{project}"))
-        .body(CloudRequestWrapper::bare(cloud))
-        .to_vec()?;
-    let vec: Vec<u8> = ctx.send_and_receive(manager, req).await?;
-    let (addr, auth) = project_data(&vec)?;
-    debug!(%project, %addr, "resolved project");
-    Ok((addr, auth))
+    forwarders: Arc<RwLock<BTreeMap<String, Forwarder>>>,
+    key: String,
+    expires_at: Instant,
+) {
+    sleep_until(expires_at).await;
+    let current = forwarders.read().await.get(&key).and_then(|f| f.expires_at);
+    if current != Some(expires_at) {
+        return;
+    }
+    info!(remote_address = %key, "Forwarder expired");
+    let req = match Request::delete(format!("/node/forwarder/{key}")).to_vec() {
+        Ok(req) => req,
+        Err(e) => return warn!(remote_address = %key, err = %e, "failed to encode request"),
+    };
+    if let Err(e) = ctx.send_and_receive::<_, _, Vec<u8>>(manager, req).await {
+        warn!(remote_address = %key, err = %e, "failed to delete expired forwarder")
+    }
 }
 
-/// Extract the project address and identity from response bytes.
-fn project_data(bytes: &[u8]) -> Result<(MultiAddr, IdentityIdentifier)> {
-    let mut dec = Decoder::new(bytes);
-    let res: Response = dec.decode()?;
-    if res.status() != Some(Status::Ok) {
-        return Err(ApiError::generic("failed to get project info"));
+/// Resolve project ID (if any) and create secure channel if necessary.
+async fn connect(
+    node: &NodeManager,
+    ctx: &Context,
+    this: &Address,
+    relays: &RelaySelector,
+    req: &CreateForwarder<'_>,
+    timeout: Option<Duration>,
+) -> Result<MultiAddr> {
+    if let Some(p) = req.address().first() {
+        if p.code() == Project::CODE {
+            let p = p
+                .cast::<Project>()
+                .ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
+            let m = req
+                .cloud_addr()
+                .ok_or_else(|| ApiError::generic("request has no cloud address"))?;
+            let (mut a, i) = resolve_project(this.clone(), ctx, relays, &p, m).await?;
+            a.try_extend(req.address().iter().skip(1))?;
+            debug!(addr = %a, "creating secure channel");
+            let r = multiaddr_to_route(&a).ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
+            let i = Some(vec![i]);
+            let m = req.credential_exchange_mode();
+            let a = node
+                .create_secure_channel_impl(
+                    r,
+                    i,
+                    m,
+                    timeout,
+                    None,
+                    KeyAgreement::default(),
+                    None,
+                    None,
+                )
+                .await?;
+            return try_address_to_multiaddr(&a);
+        }
     }
-    let res: ProjectData = dec.decode()?;
-    let addr = res.access_route()?;
-    let auth = res
-        .identity
-        .ok_or_else(|| ApiError::generic("project has no identity"))?;
-    Ok((addr, auth))
+    if req.address().matches(
+        0,
+        &[
+            Match::any([DnsAddr::CODE, Ip4::CODE, Ip6::CODE]),
+            Tcp::CODE.into(),
+            Secure::CODE.into(),
+        ],
+    ) {
+        debug!(addr = %req.address(), "creating secure channel");
+        let r = multiaddr_to_route(req.address())
+            .ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
+        let i = req.authorized();
+        let m = req.credential_exchange_mode();
+        let a = node
+            .create_secure_channel_impl(r, i, m, timeout, None, KeyAgreement::default(), None, None)
+            .await?;
+        return try_address_to_multiaddr(&a);
+    }
+    Ok(req.address().clone())
 }
 
 /// Configure the session for automatic recovery.
-fn enable_recovery(
-    session: &mut Session,
-    manager: Address,
-    ctx: Arc<Context>,
-    addr: MultiAddr,
-    cloud: Option<MultiAddr>,
+///
+/// With a `max_age`, the secure channel of the forwarder is also rotated
+/// once it gets older. The new channel and forwarder are created before
+/// the old channel is deleted, so that the forwarder stays reachable.
+///
+/// A recreated secure channel is resumed in one round trip with the ticket
+/// of the previous channel to the same route, if its listener issued one.
+fn enable_recovery(session: &mut Session, r: Recreate, max_age: Option<Duration>) {
+    if let Some(max_age) = max_age {
+        if r.channel.has_secure_channel() {
+            session.set_max_age(max_age)
+        }
+    }
+    session.set_replacement(move |prev| Box::pin(r.clone().run(prev)))
+}
+
+/// Creates a new forwarder, and its secure channel if needed.
+#[derive(Clone)]
+struct Recreate {
+    /// Creates the secure channel of the forwarder
+    channel: Reconnect,
     alias: Option<String>,
-) {
-    let auth = session.get::<IdentityIdentifier>(IDENTITY).cloned();
-    session.set_replacement(move |prev| {
-        let ctx = ctx.clone();
-        let addr = addr.clone();
-        let cloud = cloud.clone();
-        let alias = alias.clone();
-        let auth = auth.clone();
-        let manager = manager.clone();
-        Box::pin(async move {
-            debug!(%prev, %addr, "creating new remote forwarder");
-            let f = async {
-                let a = if let Some(p) = addr.first() {
-                    if p.code() == Project::CODE {
-                        let p = p
-                            .cast::<Project>()
-                            .ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
-                        let c = cloud.ok_or_else(|| ApiError::message("missing cloud address"))?;
-                        let (mut a, i) = resolve_project(manager.clone(), &ctx, &p, &c).await?;
-                        a.try_extend(addr.iter().skip(1))?;
-                        replace_sec_chan(&ctx, &manager, &prev, &a, Some(i)).await?
-                    } else if addr.matches(
-                        0,
-                        &[
-                            Match::any([DnsAddr::CODE, Ip4::CODE, Ip6::CODE]),
-                            Tcp::CODE.into(),
-                            Secure::CODE.into(),
-                        ],
-                    ) {
-                        replace_sec_chan(&ctx, &manager, &prev, &addr, auth).await?
-                    } else {
-                        addr.clone()
-                    }
-                } else {
-                    addr.clone()
-                };
-                let r = multiaddr_to_route(&a)
-                    .ok_or_else(|| ApiError::message(format!("invalid multiaddr: {a}")))?;
-                if let Some(alias) = &alias {
-                    RemoteForwarder::create_static(&ctx, r, alias).await?;
-                } else {
-                    RemoteForwarder::create(&ctx, r).await?;
-                }
-                Ok(a)
+    /// Time the whole recovery may take
+    recovery_timeout: Duration,
+    /// Interval between two renewals of the registration, if renewed
+    heartbeat_interval: Option<Duration>,
+    /// The forwarders of the node, updated with the new forwarder
+    forwarders: Arc<RwLock<BTreeMap<String, Forwarder>>>,
+    /// The entry of this forwarder in `forwarders`
+    key: String,
+}
+
+impl Recreate {
+    /// Replace the forwarder using the secure channel `prev`.
+    ///
+    /// The new secure channel and forwarder are created first, `prev` is
+    /// only deleted once the new forwarder is registered. If that fails,
+    /// the new channel is deleted instead.
+    async fn run(self, prev: MultiAddr) -> Recovery {
+        let Reconnect { ctx, addr, .. } = &self.channel;
+        debug!(%prev, %addr, "creating new remote forwarder");
+        let deadline = Instant::now() + self.recovery_timeout;
+        let mut rec = Recovery::new();
+        let mut new_channel = None;
+        let f = async {
+            let a = if self.channel.has_secure_channel() {
+                let a = self
+                    .channel
+                    .secure_channel(&prev, deadline, &mut rec)
+                    .await?;
+                new_channel = Some(a.clone());
+                a
+            } else {
+                addr.clone()
             };
-            match timeout(MAX_RECOVERY_TIME, f).await {
-                Err(_) => {
-                    warn!(%addr, "timeout creating new remote forwarder");
-                    Err(ApiError::generic("timeout"))
+            let r = multiaddr_to_route(&a)
+                .ok_or_else(|| ApiError::message(format!("invalid multiaddr: {a}")))?;
+            let r = if let Some(alias) = &self.alias {
+                let interval = self.heartbeat_interval;
+                let f =
+                    RemoteForwarder::create_static_with_heartbeat_interval(ctx, r, alias, interval);
+                step(deadline, f).await
+            } else {
+                step(deadline, RemoteForwarder::create(ctx, r)).await
+            };
+            rec.step(Step::Forwarder, &r);
+            let info = ForwarderInfo::from(r?).with_session(true);
+            let mut forwarders = self.forwarders.write().await;
+            match forwarders.get_mut(&self.key) {
+                Some(f) => {
+                    f.info = match &self.alias {
+                        Some(alias) => info.with_alias(alias.clone()),
+                        None => info,
+                    };
+                    f.channel = new_channel.clone();
                 }
-                Ok(Err(e)) => {
-                    warn!(%addr, err = %e, "error creating new remote forwarder");
-                    Err(e)
+                None => {
+                    // The forwarder was deleted while it was being recovered.
+                    let _ = ctx.stop_worker(info.worker_address()).await;
+                    return Err(ApiError::generic("forwarder was deleted"));
                 }
-                Ok(Ok(a)) => Ok(a),
             }
-        })
-    })
+            Ok(a)
+        };
+        let r = f.await;
+        // Only keep one of the two channels.
+        if let Some(new) = &new_channel {
+            self.channel
+                .delete(if r.is_ok() { &prev } else { new })
+                .await
+        }
+        if let Err(e) = &r {
+            warn!(%addr, err = %e, "error creating new remote forwarder");
+        }
+        rec.finish(r)
+    }
 }
 
-async fn replace_sec_chan(
-    ctx: &Context,
-    manager: &Address,
-    prev: &MultiAddr,
-    addr: &MultiAddr,
-    auth: Option<IdentityIdentifier>,
-) -> Result<MultiAddr> {
-    debug!(%addr, %prev, "recreating secure channel");
-    let req = {
-        let a = multiaddr_to_addr(prev)
-            .ok_or_else(|| ApiError::message(format!("could not map to address: {prev}")))?;
-        DeleteSecureChannelRequest::new(&a)
-    };
-    let req = Request::delete("/node/secure_channel").body(req).to_vec()?;
-    let vec: Vec<u8> = ctx.send_and_receive(manager.clone(), req).await?;
-    let mut d = Decoder::new(&vec);
-    let res: Response = d.decode()?;
-    if res.status() != Some(Status::Ok) && res.has_body() {
-        let e: Error = d.decode()?;
-        debug!(%addr, %prev, err = ?e.message(), "failed to delete secure channel");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::ForwardingService;
+
+    #[ockam_macros::test]
+    async fn create_list_and_delete_forwarders(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+        ForwardingService::create(ctx).await?;
+
+        let body = CreateForwarder::at_node(
+            "/service/forwarding_service".parse().unwrap(),
+            Some("forward_to_blue".to_string()),
+            true,
+            vec![],
+        );
+        let req = Request::post("/node/forwarder").body(body);
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let forwarder: ForwarderInfo = dec.decode()?;
+        assert_eq!(forwarder.alias(), Some("forward_to_blue"));
+        assert!(!forwarder.has_session());
+
+        let req = Request::get("/node/forwarder");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let forwarders: ForwarderList = dec.decode()?;
+        assert_eq!(forwarders.list.len(), 1);
+
+        let req = Request::get("/node/forwarder/forward_to_blue");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let status: ForwarderStatus = dec.decode()?;
+        assert_eq!(status.remote_address, "forward_to_blue");
+        assert!(status.secure_channel.is_none());
+        assert!(!status.session);
+        assert!(!status.degraded);
+
+        let req = Request::delete("/node/forwarder/forward_to_blue");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let req = Request::get("/node/forwarder");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let forwarders: ForwarderList = dec.decode()?;
+        assert!(forwarders.list.is_empty());
+
+        let req = Request::delete("/node/forwarder/forward_to_blue");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::NotFound));
+
+        let req = Request::get("/node/forwarder/forward_to_blue");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager, req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::NotFound));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn forwarders_expire_after_their_ttl(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+        ForwardingService::create(ctx).await?;
+
+        let body = CreateForwarder::at_node(
+            "/service/forwarding_service".parse().unwrap(),
+            Some("forward_to_ci".to_string()),
+            true,
+            vec![],
+        )
+        .with_ttl(Duration::from_secs(1));
+        let req = Request::post("/node/forwarder").body(body);
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let req = Request::get("/node/forwarder/forward_to_ci");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let status: ForwarderStatus = dec.decode()?;
+        assert!(status.expires_in.is_some());
+
+        ockam_node::tokio::time::sleep(Duration::from_millis(1500)).await;
+
+        let req = Request::get("/node/forwarder");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager, req).await?;
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let forwarders: ForwarderList = dec.decode()?;
+        assert!(forwarders.list.is_empty());
+
+        ctx.stop().await
     }
-    let auth = auth.map(|a| vec![a]);
-    let mut req = CreateSecureChannelRequest::new(addr, auth, CredentialExchangeMode::Oneway);
-    req.timeout = Some(MAX_CONNECT_TIME);
-    let req = Request::post("/node/secure_channel").body(req).to_vec()?;
-    let vec: Vec<u8> = ctx.send_and_receive(manager.clone(), req).await?;
-    let mut d = Decoder::new(&vec);
-    let res: Response = d.decode()?;
-    if res.status() != Some(Status::Ok) {
-        if res.has_body() {
-            let e: Error = d.decode()?;
-            warn!(%addr, %prev, err = ?e.message(), "failed to create secure channel");
+
+    #[ockam_macros::test]
+    async fn forwarders_are_restored_on_restart(ctx: &mut Context) -> Result<()> {
+        let node_dir = tempfile::tempdir().unwrap().into_path();
+        let store = ForwarderStore::new(node_dir.join(FORWARDERS_FILE));
+        let at = |alias: &str| {
+            CreateForwarder::at_node(
+                "/service/forwarding_service".parse().unwrap(),
+                Some(alias.to_string()),
+                true,
+                vec![],
+            )
+        };
+        store.insert("stored", &at("forward_to_red"))?;
+
+        ForwardingService::create(ctx).await?;
+        let node_manager = NodeManager::test_create_in(ctx, node_dir).await?;
+
+        let mut restored = false;
+        for _ in 0..20 {
+            let req = Request::get("/node/forwarder/forward_to_red");
+            let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+            let res: Response = Decoder::new(&buf).decode()?;
+            if res.status() == Some(Status::Ok) {
+                restored = true;
+                break;
+            }
+            sleep(Duration::from_millis(100)).await;
         }
-        return Err(ApiError::generic("error creating secure channel"));
+        assert!(restored);
+        assert_eq!(store.load()?.len(), 1);
+
+        // Forwarders with a TTL are not recreated.
+        let req = Request::post("/node/forwarder")
+            .body(at("forward_to_ci").with_ttl(Duration::from_secs(60)));
+        ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        assert_eq!(store.load()?.len(), 1);
+
+        let req = Request::post("/node/forwarder").body(at("forward_to_green"));
+        ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        assert_eq!(store.load()?.len(), 2);
+
+        let req = Request::delete("/node/forwarder/forward_to_red");
+        ockam_node::api::request(ctx, "", None, node_manager, req).await?;
+        let stored = store.load()?;
+        assert_eq!(stored.len(), 1);
+        assert!(!stored.contains_key("stored"));
+
+        ctx.stop().await
     }
-    let res: CreateSecureChannelResponse = d.decode()?;
-    res.addr()
 }
@@ -0,0 +1,171 @@
+//! Liveness and readiness of a node, for orchestrators and load balancers
+
+use std::borrow::Cow;
+use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ockam::Result;
+use ockam_node::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use ockam_node::tokio::net::{TcpListener, TcpStream};
+use ockam_node::tokio::time::timeout;
+
+use crate::error::ApiError;
+use crate::nodes::models::base::NodeReadiness;
+use crate::nodes::NodeManager;
+
+/// Time a probe may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// Probes only send a request line and a few headers
+const MAX_REQUEST_LEN: usize = 4096;
+
+impl NodeManager {
+    /// The node is ready once its services are started, it holds a
+    /// credential if it is a member of a project, and every service is
+    /// ready, e.g. its forwarders are up.
+    pub(super) async fn readiness(&self) -> NodeReadiness<'static> {
+        let mut reasons = Vec::new();
+        if !self.started.load(Ordering::Acquire) {
+            reasons.push(Cow::Borrowed("services are starting"));
+        }
+        if self.authorities.is_some() {
+            if let Ok(identity) = self.identity().await {
+                if identity.credential().await.is_none() {
+                    reasons.push(Cow::Borrowed("not enrolled, no credential"));
+                }
+            }
+        }
+        let unready = self.services.unready(self).await;
+        reasons.extend(unready.into_iter().map(Cow::Owned));
+        NodeReadiness::new(reasons)
+    }
+}
+
+/// Bind the plain HTTP endpoint serving `GET /live` and `GET /ready`.
+pub(super) async fn bind(addr: SocketAddr) -> Result<TcpListener> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| ApiError::generic(&format!("failed to bind {addr}: {e}")))?;
+    info!(%addr, "Serving health probes");
+    Ok(listener)
+}
+
+/// Answer the probes connecting to `listener`, one connection at a time
+/// since answering never waits for the node.
+pub(super) async fn serve(node_manager: Arc<NodeManager>, listener: TcpListener) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                if let Err(err) = respond(&node_manager, stream).await {
+                    debug!(%peer, %err, "failed to answer health probe")
+                }
+            }
+            Err(err) => warn!(%err, "failed to accept health probe"),
+        }
+    }
+}
+
+async fn respond(node_manager: &NodeManager, mut stream: TcpStream) -> std::io::Result<()> {
+    let (status, body) = match timeout(READ_TIMEOUT, read_path(&mut stream)).await {
+        Ok(Ok(Some(path))) if path == "/live" => ("200 OK", "live\n".to_string()),
+        Ok(Ok(Some(path))) if path == "/ready" => {
+            let readiness = node_manager.readiness().await;
+            if readiness.ready {
+                ("200 OK", "ready\n".to_string())
+            } else {
+                let reasons: Vec<_> = readiness.reasons.iter().map(|r| format!("{r}\n")).collect();
+                ("503 Service Unavailable", reasons.concat())
+            }
+        }
+        Ok(Ok(Some(_))) => ("404 Not Found", "not found\n".to_string()),
+        Ok(Ok(None)) | Err(_) => ("400 Bad Request", "bad request\n".to_string()),
+        Ok(Err(err)) => return Err(err),
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// The path of a `GET` request, `None` for other requests.
+async fn read_path(stream: &mut TcpStream) -> std::io::Result<Option<String>> {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 512];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        if buf.len() > MAX_REQUEST_LEN {
+            return Ok(None);
+        }
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    Ok(parse_path(&buf))
+}
+
+fn parse_path(request: &[u8]) -> Option<String> {
+    let line = request.split(|b| *b == b'\n').next()?;
+    let line = std::str::from_utf8(line).ok()?;
+    match line.trim_end().split(' ').collect::<Vec<_>>().as_slice() {
+        ["GET", path, version] if version.starts_with("HTTP/") => {
+            // Probes may add a query, e.g. to bust caches
+            Some(path.split('?').next()?.to_string())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minicbor::Decoder;
+    use ockam_core::api::{Request, Response, Status};
+    use ockam_node::tokio::time::sleep;
+    use ockam_node::Context;
+
+    #[ockam_macros::test]
+    async fn live_then_ready(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+
+        let req = Request::get("/node/live");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        // Ready once the services are started, without anything to restore
+        let mut ready = false;
+        for _ in 0..50 {
+            let req = Request::get("/node/ready");
+            let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+            let mut dec = Decoder::new(&buf);
+            let res: Response = dec.decode()?;
+            assert_eq!(res.status(), Some(Status::Ok));
+            let readiness: NodeReadiness = dec.decode()?;
+            assert_eq!(readiness.ready, readiness.reasons.is_empty());
+            if readiness.ready {
+                ready = true;
+                break;
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+        assert!(ready);
+
+        ctx.stop().await
+    }
+
+    #[test]
+    fn parse_probe_paths() {
+        let req = b"GET /ready HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        assert_eq!(parse_path(req), Some("/ready".to_string()));
+        assert_eq!(
+            parse_path(b"GET /live?t=1 HTTP/1.0\r\n\r\n"),
+            Some("/live".to_string())
+        );
+        assert_eq!(parse_path(b"POST /live HTTP/1.1\r\n\r\n"), None);
+        assert_eq!(parse_path(b"GET /live\r\n\r\n"), None);
+    }
+}
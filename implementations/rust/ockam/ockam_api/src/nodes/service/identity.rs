@@ -2,19 +2,22 @@ use super::map_anyhow_err;
 use crate::nodes::models::identity::{
     CreateIdentityResponse, LongIdentityResponse, ShortIdentityResponse,
 };
+use crate::nodes::service::SECURE_CHANNEL_RESUMPTION;
 use crate::nodes::NodeManager;
 use ockam::identity::{Identity, IdentityIdentifier};
 use ockam::{Context, Result};
 use ockam_core::api::{Request, Response, ResponseBuilder};
 use ockam_core::errcode::{Kind, Origin};
+use std::sync::Arc;
 
 impl NodeManager {
     pub(super) async fn create_identity_impl(
-        &mut self,
+        &self,
         ctx: &Context,
         reuse_if_exists: bool,
     ) -> Result<IdentityIdentifier> {
-        if let Some(identity) = &self.identity {
+        let mut guard = self.identity.write().await;
+        if let Some(identity) = guard.as_ref() {
             return if reuse_if_exists {
                 debug!("Using existing identity");
                 Ok(identity.identifier().clone())
@@ -27,9 +30,12 @@ impl NodeManager {
             };
         }
 
-        let vault = self.vault()?;
+        let vault = self.vault().await?;
 
-        let identity = Identity::create(ctx, vault).await?;
+        let identity = Identity::create(ctx, &vault).await?;
+        identity
+            .set_secure_channel_resumption(Some(SECURE_CHANNEL_RESUMPTION))
+            .await;
         let identifier = identity.identifier().clone();
         let exported_identity = identity.export().await?;
 
@@ -38,13 +44,13 @@ impl NodeManager {
             .persist_config_updates()
             .map_err(map_anyhow_err)?;
 
-        self.identity = Some(identity);
+        *guard = Some(Arc::new(identity));
 
         Ok(identifier)
     }
 
     pub(super) async fn create_identity(
-        &mut self,
+        &self,
         ctx: &Context,
         req: &Request<'_>,
     ) -> Result<ResponseBuilder<CreateIdentityResponse<'_>>> {
@@ -56,10 +62,10 @@ impl NodeManager {
     }
 
     pub(super) async fn long_identity(
-        &mut self,
+        &self,
         req: &Request<'_>,
     ) -> Result<ResponseBuilder<LongIdentityResponse<'_>>> {
-        let identity = self.identity()?;
+        let identity = self.identity().await?;
         let identity = identity.export().await?;
 
         let response = Response::ok(req.id()).body(LongIdentityResponse::new(identity));
@@ -67,10 +73,10 @@ impl NodeManager {
     }
 
     pub(super) async fn short_identity(
-        &mut self,
+        &self,
         req: &Request<'_>,
     ) -> Result<ResponseBuilder<ShortIdentityResponse<'_>>> {
-        let identity = self.identity()?;
+        let identity = self.identity().await?;
         let identifier = identity.identifier();
 
         let response =
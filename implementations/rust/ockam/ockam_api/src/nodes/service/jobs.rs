@@ -0,0 +1,278 @@
+use std::sync::{Arc, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use minicbor::Decoder;
+use ockam::{Address, Context, Result};
+use ockam_core::api::{self, Method, Request, Response, ResponseBuilder};
+use ockam_core::async_trait;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_multiaddr::MultiAddr;
+use ockam_node::tokio;
+use ockam_node::tokio::sync::RwLock;
+use ockam_node::tokio::task::JoinHandle;
+use ockam_node::tokio::time::{interval_at, Instant, MissedTickBehavior};
+
+use crate::cloud::project::Project;
+use crate::cloud::CloudRequestWrapper;
+use crate::error::ApiError;
+use crate::nodes::models::credentials::GetCredentialRequest;
+use crate::nodes::models::jobs::{CreateJob, JobKind, JobList, JobStatus};
+use crate::nodes::service::progress::Progress;
+use crate::nodes::service::random_alias;
+use crate::nodes::service::service_registry::NodeService;
+use crate::nodes::NodeManager;
+
+/// Upper bound on the time a single run of a job may take.
+const MAX_RUN_TIME: Duration = Duration::from_secs(60);
+
+type Jobs = RwLock<BTreeMap<String, Job>>;
+
+/// Service scheduling recurring jobs on the node.
+///
+/// Every run of a job is a request sent to the node manager API, so jobs
+/// go through the same code paths as the equivalent CLI commands.
+#[derive(Default)]
+pub(crate) struct JobService {
+    jobs: Arc<Jobs>,
+}
+
+#[async_trait]
+impl NodeService for JobService {
+    async fn handle_request(
+        &self,
+        _node: &NodeManager,
+        ctx: &mut Context,
+        this: &Address,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        _progress: &Progress,
+    ) -> Result<Option<Vec<u8>>> {
+        let r = match (req.method(), req.path_segments::<5>().as_slice()) {
+            (Some(Method::Get), ["node", "jobs"]) => self.list_jobs(req).await.to_vec()?,
+            (Some(Method::Post), ["node", "jobs"]) => self.create_job(ctx, this, req, dec).await?,
+            (Some(Method::Delete), ["node", "jobs", id]) => {
+                self.delete_job(req, id).await.to_vec()?
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(r))
+    }
+}
+
+impl JobService {
+    async fn list_jobs(&self, req: &Request<'_>) -> ResponseBuilder<JobList<'static>> {
+        let list = self
+            .jobs
+            .read()
+            .await
+            .values()
+            .map(|j| j.status.clone())
+            .collect();
+        Response::ok(req.id()).body(JobList::new(list))
+    }
+
+    async fn create_job(
+        &self,
+        ctx: &Context,
+        this: &Address,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let body: CreateJob = dec.decode()?;
+        if body.interval().is_zero() {
+            return Ok(api::bad_request(req, "job interval must be at least one second").to_vec()?);
+        }
+        let action = match body.kind() {
+            JobKind::RotateCredential => Action::RotateCredential,
+            JobKind::ResolveProject => match (body.project(), body.cloud_addr()) {
+                (Some(project), Some(cloud)) => Action::ResolveProject {
+                    project: project.to_string(),
+                    cloud: cloud.clone(),
+                },
+                _ => {
+                    let msg = "resolve-project jobs need a project and an orchestrator address";
+                    return Ok(api::bad_request(req, msg).to_vec()?);
+                }
+            },
+        };
+
+        let id = random_alias();
+        info!(%id, kind = %body.kind(), interval = ?body.interval(), "Scheduling job");
+        let status = JobStatus::new(id.clone(), body.kind(), body.interval());
+        let ctx = ctx.new_detached(Address::random_local()).await?;
+        let handle = tokio::spawn(run(
+            ctx,
+            this.clone(),
+            id.clone(),
+            action,
+            body.interval(),
+            Arc::downgrade(&self.jobs),
+        ));
+        self.jobs.write().await.insert(
+            id,
+            Job {
+                status: status.clone(),
+                handle,
+            },
+        );
+        Ok(Response::ok(req.id()).body(status).to_vec()?)
+    }
+
+    async fn delete_job(&self, req: &Request<'_>, id: &str) -> ResponseBuilder {
+        match self.jobs.write().await.remove(id) {
+            Some(_) => {
+                info!(%id, "Deleted job");
+                Response::ok(req.id())
+            }
+            None => Response::not_found(req.id()),
+        }
+    }
+}
+
+struct Job {
+    status: JobStatus<'static>,
+    handle: JoinHandle<()>,
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        self.handle.abort()
+    }
+}
+
+enum Action {
+    RotateCredential,
+    ResolveProject { project: String, cloud: MultiAddr },
+}
+
+impl Action {
+    /// Send the request corresponding to this action to the node manager at
+    /// `this` and describe its outcome.
+    async fn run(&self, ctx: &mut Context, this: &Address) -> Result<String> {
+        match self {
+            Action::RotateCredential => {
+                let req = Request::post("/node/credentials/actions/get")
+                    .body(GetCredentialRequest::new(true))
+                    .timeout(MAX_RUN_TIME);
+                let buf = ockam_node::api::request(ctx, "job", None, this.clone(), req).await?;
+                api::check_response(&mut Decoder::new(&buf))?;
+                Ok("credential rotated".to_string())
+            }
+            Action::ResolveProject { project, cloud } => {
+                let req = Request::get(format!("/v0/projects/{project}"))
+                    .body(CloudRequestWrapper::bare(cloud))
+                    .timeout(MAX_RUN_TIME);
+                let buf = ockam_node::api::request(ctx, "job", None, this.clone(), req).await?;
+                let mut dec = Decoder::new(&buf);
+                api::check_response(&mut dec)?;
+                let project: Project = dec.decode()?;
+                Ok(format!("project route is {}", project.access_route()?))
+            }
+        }
+    }
+}
+
+/// Run `action` every `interval`, recording the outcome of every run in
+/// the status of the job, until the job is deleted.
+async fn run(
+    mut ctx: Context,
+    this: Address,
+    id: String,
+    action: Action,
+    interval: Duration,
+    jobs: Weak<Jobs>,
+) {
+    let mut ticks = interval_at(Instant::now() + interval, interval);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticks.tick().await;
+        let result = match tokio::time::timeout(MAX_RUN_TIME, action.run(&mut ctx, &this)).await {
+            Ok(r) => r,
+            Err(_) => Err(ApiError::generic("job timed out")),
+        };
+        let jobs = match jobs.upgrade() {
+            Some(jobs) => jobs,
+            None => return,
+        };
+        let mut jobs = jobs.write().await;
+        let status = match jobs.get_mut(&id) {
+            Some(job) => &mut job.status,
+            None => return,
+        };
+        status.runs += 1;
+        status.last_run = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs());
+        match result {
+            Ok(msg) => {
+                debug!(%id, %msg, "Job completed");
+                status.last_result = Some(msg.into());
+            }
+            Err(err) => {
+                warn!(%id, %err, "Job failed");
+                status.failures += 1;
+                status.last_result = Some(err.to_string().into());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::api::Status;
+
+    #[ockam_macros::test]
+    async fn create_list_and_delete_jobs(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+
+        // Jobs need a non-zero interval
+        let req = Request::post("/node/jobs").body(CreateJob::rotate_credential(Duration::ZERO));
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::BadRequest));
+
+        // A job failing to rotate the credential, as there is no authority
+        let req =
+            Request::post("/node/jobs").body(CreateJob::rotate_credential(Duration::from_secs(1)));
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let job: JobStatus = dec.decode()?;
+        assert_eq!(job.kind, JobKind::RotateCredential);
+        let id = job.id.to_string();
+
+        // Wait for the first run
+        let mut runs = 0;
+        for _ in 0..30 {
+            ctx.sleep(Duration::from_millis(100)).await;
+            let req = Request::get("/node/jobs");
+            let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+            let mut dec = Decoder::new(&buf);
+            let _: Response = dec.decode()?;
+            let jobs: JobList = dec.decode()?;
+            assert_eq!(jobs.list.len(), 1);
+            runs = jobs.list[0].runs;
+            if runs > 0 {
+                assert_eq!(jobs.list[0].failures, runs);
+                assert!(jobs.list[0].last_result.is_some());
+                break;
+            }
+        }
+        assert!(runs > 0);
+
+        let req = Request::delete(format!("/node/jobs/{id}"));
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let req = Request::delete(format!("/node/jobs/{id}"));
+        let buf = ockam_node::api::request(ctx, "", None, node_manager, req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::NotFound));
+
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,213 @@
+use core::time::Duration;
+
+use minicbor::Decoder;
+use ockam::{Address, Context, Result};
+use ockam_core::api::{self, Method, Request, Response, ResponseBuilder};
+use ockam_core::async_trait;
+use ockam_multiaddr::MultiAddr;
+
+use crate::multiaddr_to_addr;
+use crate::nodes::models::medic::{MedicStatus, RecoveryStep, SessionStatus, UpdateMedic};
+use crate::nodes::service::progress::Progress;
+use crate::nodes::service::service_registry::NodeService;
+use crate::nodes::NodeManager;
+use crate::session::{MedicHandle, Status, SECURE_CHANNEL};
+
+/// Shortest time allowed between two checks of the sessions.
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Service controlling the medic which keeps the sessions of the node alive.
+///
+/// The medic can be paused, e.g. during a planned maintenance of the
+/// remote nodes, its checks made more or less frequent, and a session
+/// can be recovered without waiting for it to become unresponsive.
+pub(crate) struct MedicService {
+    medic: MedicHandle,
+}
+
+impl MedicService {
+    pub(crate) fn new(medic: MedicHandle) -> Self {
+        Self { medic }
+    }
+}
+
+#[async_trait]
+impl NodeService for MedicService {
+    async fn handle_request(
+        &self,
+        node: &NodeManager,
+        _ctx: &mut Context,
+        _this: &Address,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        _progress: &Progress,
+    ) -> Result<Option<Vec<u8>>> {
+        let r = match (req.method(), req.path_segments::<5>().as_slice()) {
+            (Some(Method::Get), ["node", "medic"]) => self.status(node, req).await.to_vec()?,
+            (Some(Method::Put), ["node", "medic"]) => self.update(node, req, dec).await?,
+            (Some(Method::Post), ["node", "medic", "sessions", key, "recover"]) => {
+                self.recover(req, key)?
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(r))
+    }
+}
+
+impl MedicService {
+    async fn status(
+        &self,
+        node: &NodeManager,
+        req: &Request<'_>,
+    ) -> ResponseBuilder<MedicStatus<'static>> {
+        let control = self.medic.control();
+        let sessions: Vec<_> = self
+            .medic
+            .sessions()
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(k, s)| {
+                let mut status = SessionStatus::new(
+                    k.to_string(),
+                    s.address().to_string(),
+                    s.description().map(|d| d.to_string().into()),
+                    s.status() == Status::Up,
+                    s.rtt().map(|d| d.as_millis() as u64),
+                    s.recoveries() as u64,
+                    s.last_recovery()
+                        .iter()
+                        .map(|o| {
+                            RecoveryStep::new(
+                                o.step.to_string(),
+                                o.error.clone().map(Into::into),
+                                s.step_failures(o.step),
+                            )
+                        })
+                        .collect(),
+                    s.max_age().map(|d| d.as_secs()),
+                );
+                status.failed_attempts = Some(s.failed_attempts());
+                status.given_up = Some(s.has_given_up());
+                let channel = s
+                    .get::<MultiAddr>(SECURE_CHANNEL)
+                    .and_then(|_| multiaddr_to_addr(s.address()));
+                (status, channel)
+            })
+            .collect();
+        // The round-trip times of secure channels are measured by their
+        // keepalives, not by the medic
+        let identity = node.identity().await.ok();
+        let mut statuses = Vec::with_capacity(sessions.len());
+        for (mut status, channel) in sessions {
+            if let (Some(identity), Some(channel)) = (&identity, channel) {
+                if let Some(stats) = identity.secure_channel_stats(&channel).await {
+                    status.channel_rtt_micros = stats.rtt.map(|rtt| rtt.as_micros() as u64)
+                }
+            }
+            statuses.push(status)
+        }
+        Response::ok(req.id()).body(MedicStatus::new(
+            control.paused,
+            control.delay.as_millis() as u64,
+            self.medic.restarts(),
+            statuses,
+        ))
+    }
+
+    async fn update(
+        &self,
+        node: &NodeManager,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let body: UpdateMedic = dec.decode()?;
+        if let Some(ms) = body.interval_millis() {
+            let delay = Duration::from_millis(ms);
+            if delay < MIN_INTERVAL {
+                let msg = "the interval between checks must be at least 100ms";
+                return Ok(api::bad_request(req, msg).to_vec()?);
+            }
+            info!(interval = ?delay, "Setting session checks interval");
+            self.medic.set_delay(delay)
+        }
+        if let Some(paused) = body.paused() {
+            if paused {
+                info!("Pausing session checks")
+            } else {
+                info!("Resuming session checks")
+            }
+            self.medic.set_paused(paused)
+        }
+        Ok(self.status(node, req).await.to_vec()?)
+    }
+
+    /// Recover the session whose key starts with `key`.
+    fn recover(&self, req: &Request<'_>, key: &str) -> Result<Vec<u8>> {
+        let found: Vec<_> = self
+            .medic
+            .sessions()
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(k, _)| *k)
+            .filter(|k| k.to_string().starts_with(key))
+            .collect();
+        match found.as_slice() {
+            [k] if self.medic.recover(k) => {
+                info!(key = %k, "Recovering session");
+                Ok(Response::ok(req.id()).to_vec()?)
+            }
+            [_, _, ..] if !key.is_empty() => {
+                let msg = format!("several sessions start with {key}");
+                Ok(api::bad_request(req, &msg).to_vec()?)
+            }
+            _ => Ok(Response::not_found(req.id()).to_vec()?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::api::Status;
+
+    #[ockam_macros::test]
+    async fn pause_resume_and_recover(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+
+        let req = Request::get("/node/medic");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let status: MedicStatus = dec.decode()?;
+        assert!(!status.paused);
+        assert!(status.sessions.is_empty());
+
+        let body = UpdateMedic::new()
+            .pause(true)
+            .interval(Duration::from_secs(10));
+        let req = Request::put("/node/medic").body(body);
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let status: MedicStatus = dec.decode()?;
+        assert!(status.paused);
+        assert_eq!(status.interval, 10_000);
+
+        let body = UpdateMedic::new().interval(Duration::from_millis(1));
+        let req = Request::put("/node/medic").body(body);
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::BadRequest));
+
+        let req = Request::post("/node/medic/sessions/00ff/recover");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager, req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::NotFound));
+
+        ctx.stop().await
+    }
+}
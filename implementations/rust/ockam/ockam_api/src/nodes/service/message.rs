@@ -39,21 +39,47 @@ impl<'a> SendMessage<'a> {
     }
 }
 
+/// Service sending messages on behalf of clients
+pub(crate) struct MessageService;
+
 mod node {
     use minicbor::Decoder;
     use tracing::trace;
 
-    use ockam_core::api::{Request, Response, Status};
-    use ockam_core::{self, Result};
+    use ockam_core::api::{Method, Request, Response, Status};
+    use ockam_core::{self, async_trait, Address, Result};
     use ockam_node::Context;
 
+    use super::MessageService;
+    use crate::nodes::service::progress::Progress;
+    use crate::nodes::service::service_registry::NodeService;
     use crate::nodes::NodeManager;
 
     const TARGET: &str = "ockam_api::message";
 
-    impl NodeManager {
-        pub(crate) async fn send_message(
-            &mut self,
+    #[async_trait]
+    impl NodeService for MessageService {
+        async fn handle_request(
+            &self,
+            _node: &NodeManager,
+            ctx: &mut Context,
+            _this: &Address,
+            req: &Request<'_>,
+            dec: &mut Decoder<'_>,
+            _progress: &Progress,
+        ) -> Result<Option<Vec<u8>>> {
+            match (req.method(), req.path_segments::<5>().as_slice()) {
+                (Some(Method::Post), ["v0", "message"]) => {
+                    self.send_message(ctx, req, dec).await.map(Some)
+                }
+                _ => Ok(None),
+            }
+        }
+    }
+
+    impl MessageService {
+        async fn send_message(
+            &self,
             ctx: &mut Context,
             req: &Request<'_>,
             dec: &mut Decoder<'_>,
@@ -0,0 +1,446 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use minicbor::Decoder;
+use ockam::{Address, Context, Result};
+use ockam_core::api::{self, Method, Request, Response, ResponseBuilder};
+use ockam_core::async_trait;
+use ockam_core::compat::collections::{BTreeMap, HashMap, HashSet};
+use ockam_core::compat::sync::Mutex;
+use ockam_node::tokio;
+use ockam_node::tokio::sync::RwLock;
+use ockam_node::tokio::task::JoinHandle;
+use ockam_node::tokio::time::{interval, Instant, MissedTickBehavior};
+use serde::Serialize;
+
+use crate::error::ApiError;
+use crate::events;
+use crate::nodes::models::monitors::{
+    AlertHook, CreateMonitor, MonitorKind, MonitorList, MonitorStatus,
+};
+use crate::nodes::service::progress::Progress;
+use crate::nodes::service::random_alias;
+use crate::nodes::service::service_registry::NodeService;
+use crate::nodes::NodeManager;
+use crate::session::{Key, Sessions, Status};
+
+/// Time between two checks of the sessions.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Upper bound on the time an alert hook may take.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+type Monitors = RwLock<BTreeMap<String, Monitor>>;
+
+/// Service checking service level objectives on the sessions of the node.
+///
+/// Every monitor checks one indicator of all sessions and sends an alert
+/// to its hook when a session starts or stops violating the objective.
+pub(crate) struct MonitorService {
+    sessions: Arc<Mutex<Sessions>>,
+    monitors: Arc<Monitors>,
+}
+
+impl MonitorService {
+    pub(crate) fn new(sessions: Arc<Mutex<Sessions>>) -> Self {
+        Self {
+            sessions,
+            monitors: Default::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl NodeService for MonitorService {
+    async fn handle_request(
+        &self,
+        node: &NodeManager,
+        _ctx: &mut Context,
+        _this: &Address,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        _progress: &Progress,
+    ) -> Result<Option<Vec<u8>>> {
+        let r = match (req.method(), req.path_segments::<5>().as_slice()) {
+            (Some(Method::Get), ["node", "monitors"]) => self.list_monitors(req).await.to_vec()?,
+            (Some(Method::Post), ["node", "monitors"]) => {
+                self.create_monitor(node, req, dec).await?
+            }
+            (Some(Method::Delete), ["node", "monitors", id]) => {
+                self.delete_monitor(req, id).await.to_vec()?
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(r))
+    }
+}
+
+impl MonitorService {
+    async fn list_monitors(&self, req: &Request<'_>) -> ResponseBuilder<MonitorList<'static>> {
+        let list = self
+            .monitors
+            .read()
+            .await
+            .values()
+            .map(|m| m.status.clone())
+            .collect();
+        Response::ok(req.id()).body(MonitorList::new(list))
+    }
+
+    async fn create_monitor(
+        &self,
+        node: &NodeManager,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let body: CreateMonitor = dec.decode()?;
+        if body.hook().is_empty() {
+            return Ok(api::bad_request(req, "monitors need a webhook or a command").to_vec()?);
+        }
+        if body.kind() == MonitorKind::Rtt && body.threshold() == 0 {
+            return Ok(api::bad_request(req, "rtt threshold must be at least 1ms").to_vec()?);
+        }
+        if body.kind() == MonitorKind::Recoveries && body.window().is_zero() {
+            return Ok(api::bad_request(req, "recoveries window must be at least 1s").to_vec()?);
+        }
+
+        let id = random_alias();
+        info! {
+            %id,
+            kind      = %body.kind(),
+            threshold = %body.threshold(),
+            window    = ?body.window(),
+            "Creating monitor"
+        }
+        let status = MonitorStatus::new(id.clone(), &body);
+        let slo = Slo::new(body.kind(), body.threshold(), body.window());
+        let handle = tokio::spawn(run(
+            node.node_name.clone(),
+            id.clone(),
+            slo,
+            body.hook().to_owned(),
+            self.sessions.clone(),
+            Arc::downgrade(&self.monitors),
+        ));
+        self.monitors.write().await.insert(
+            id,
+            Monitor {
+                status: status.clone(),
+                handle,
+            },
+        );
+        Ok(Response::ok(req.id()).body(status).to_vec()?)
+    }
+
+    async fn delete_monitor(&self, req: &Request<'_>, id: &str) -> ResponseBuilder {
+        match self.monitors.write().await.remove(id) {
+            Some(_) => {
+                info!(%id, "Deleted monitor");
+                Response::ok(req.id())
+            }
+            None => Response::not_found(req.id()),
+        }
+    }
+}
+
+struct Monitor {
+    status: MonitorStatus<'static>,
+    handle: JoinHandle<()>,
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        self.handle.abort()
+    }
+}
+
+/// An objective on one indicator of the sessions.
+struct Slo {
+    kind: MonitorKind,
+    threshold: u64,
+    window: Duration,
+    /// Since when a session has been violating the objective
+    violating: HashMap<Key, Instant>,
+    /// Sessions for which an alert was sent
+    firing: HashSet<Key>,
+}
+
+/// Alert sent to the hook of a monitor, as JSON.
+#[derive(Debug, Serialize)]
+struct Alert {
+    node: String,
+    monitor: String,
+    kind: MonitorKind,
+    state: AlertState,
+    session: String,
+    description: Option<String>,
+    address: String,
+    /// Milliseconds for `rtt` monitors, recoveries for `recoveries` monitors
+    value: u64,
+    threshold: u64,
+    /// Seconds
+    window: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum AlertState {
+    Firing,
+    Resolved,
+}
+
+impl Slo {
+    fn new(kind: MonitorKind, threshold: u64, window: Duration) -> Self {
+        Self {
+            kind,
+            threshold,
+            window,
+            violating: HashMap::new(),
+            firing: HashSet::new(),
+        }
+    }
+
+    /// Check all sessions at time `now`.
+    ///
+    /// Returns the alerts of the sessions which started or stopped
+    /// violating the objective since the last check.
+    fn check(&mut self, now: Instant, sessions: &Sessions) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        for (key, session) in sessions.iter() {
+            let value = match self.kind {
+                MonitorKind::Rtt => match (session.status(), session.rtt()) {
+                    (Status::Up, Some(rtt)) => rtt.as_millis() as u64,
+                    _ => 0,
+                },
+                MonitorKind::Recoveries => match now.checked_sub(self.window) {
+                    Some(since) => session.recoveries_since(since) as u64,
+                    None => session.recoveries() as u64,
+                },
+            };
+            let state = if value > self.threshold {
+                let since = *self.violating.entry(*key).or_insert(now);
+                let sustained = match self.kind {
+                    MonitorKind::Rtt => now.duration_since(since) >= self.window,
+                    MonitorKind::Recoveries => true,
+                };
+                if sustained && self.firing.insert(*key) {
+                    AlertState::Firing
+                } else {
+                    continue;
+                }
+            } else {
+                self.violating.remove(key);
+                if self.firing.remove(key) {
+                    AlertState::Resolved
+                } else {
+                    continue;
+                }
+            };
+            alerts.push(Alert {
+                node: String::new(),
+                monitor: String::new(),
+                kind: self.kind,
+                state,
+                session: key.to_string(),
+                description: session.description().map(|d| d.to_string()),
+                address: session.address().to_string(),
+                value,
+                threshold: self.threshold,
+                window: self.window.as_secs(),
+            })
+        }
+        // Forget the sessions which were removed
+        self.violating.retain(|k, _| sessions.session(k).is_some());
+        self.firing.retain(|k| sessions.session(k).is_some());
+        alerts
+    }
+}
+
+/// Check `slo` every `CHECK_INTERVAL` and send its alerts to `hook`, until
+/// the monitor is deleted.
+async fn run(
+    node: String,
+    id: String,
+    mut slo: Slo,
+    hook: AlertHook<'static>,
+    sessions: Arc<Mutex<Sessions>>,
+    monitors: Weak<Monitors>,
+) {
+    let mut ticks = interval(CHECK_INTERVAL);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticks.tick().await;
+        let alerts = {
+            let sessions = sessions.lock().unwrap();
+            slo.check(Instant::now(), &sessions)
+        };
+        for mut alert in alerts {
+            alert.node = node.clone();
+            alert.monitor = id.clone();
+            warn! {
+                monitor = %id,
+                session = %alert.session,
+                state   = ?alert.state,
+                value   = %alert.value,
+                "Service level objective alert"
+            }
+            let result = match tokio::time::timeout(HOOK_TIMEOUT, notify(&hook, &alert)).await {
+                Ok(r) => r,
+                Err(_) => Err(ApiError::generic("alert hook timed out")),
+            };
+            let monitors = match monitors.upgrade() {
+                Some(monitors) => monitors,
+                None => return,
+            };
+            let mut monitors = monitors.write().await;
+            let status = match monitors.get_mut(&id) {
+                Some(m) => &mut m.status,
+                None => return,
+            };
+            match result {
+                Ok(()) => status.alerts += 1,
+                Err(err) => {
+                    warn!(monitor = %id, %err, "Failed to send alert");
+                    status.last_error = Some(err.to_string().into());
+                }
+            }
+        }
+        if let Some(monitors) = monitors.upgrade() {
+            if let Some(m) = monitors.write().await.get_mut(&id) {
+                m.status.firing = slo.firing.iter().map(|k| k.to_string().into()).collect();
+            }
+        }
+    }
+}
+
+/// Send `alert` to the webhook and the command of `hook`.
+async fn notify(hook: &AlertHook<'_>, alert: &Alert) -> Result<()> {
+    let json = serde_json::to_vec(alert).map_err(|e| ApiError::generic(&e.to_string()))?;
+    if let Some(url) = &hook.webhook {
+        events::post(url, json.clone(), &[]).await?;
+    }
+    if let Some(cmd) = &hook.command {
+        let cmd = cmd.to_string();
+        tokio::task::spawn_blocking(move || exec(&cmd, &json))
+            .await
+            .map_err(|e| ApiError::generic(&e.to_string()))??;
+    }
+    Ok(())
+}
+
+/// Run `cmd` with a shell, writing `input` to its standard input.
+fn exec(cmd: &str, input: &[u8]) -> Result<()> {
+    let err = |e: std::io::Error| ApiError::generic(&format!("failed to run {cmd}: {e}"));
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(cmd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(err)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The command may not read its input
+        let _ = stdin.write_all(input);
+    }
+    let status = child.wait().map_err(err)?;
+    if !status.success() {
+        return Err(ApiError::generic(&format!("{cmd} failed with {status}")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::Session;
+    use ockam_core::api::Status;
+
+    #[test]
+    fn recoveries_above_threshold_fire_and_resolve() {
+        let mut sessions = Sessions::new();
+        let mut s = Session::new("/service/api".parse().unwrap());
+        for _ in 0..3 {
+            s.add_recovery()
+        }
+        let key = sessions.add(s);
+
+        let mut slo = Slo::new(MonitorKind::Recoveries, 2, Duration::from_secs(60));
+        let now = Instant::now();
+        let alerts = slo.check(now, &sessions);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].state, AlertState::Firing);
+        assert_eq!(alerts[0].value, 3);
+        assert_eq!(alerts[0].session, key.to_string());
+
+        // No new alert while the objective is still violated
+        assert!(slo.check(now, &sessions).is_empty());
+
+        // The recoveries leave the window
+        let alerts = slo.check(now + Duration::from_secs(61), &sessions);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].state, AlertState::Resolved);
+        assert_eq!(alerts[0].value, 0);
+    }
+
+    #[test]
+    fn command_hooks_get_alerts_on_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("alert.json");
+        let json = br#"{"state":"firing"}"#;
+        exec(&format!("cat > {}", out.display()), json).unwrap();
+        assert_eq!(std::fs::read(&out).unwrap(), json);
+        assert!(exec("exit 3", json).is_err());
+    }
+
+    #[ockam_macros::test]
+    async fn create_list_and_delete_monitors(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+
+        // Monitors need a hook
+        let body = CreateMonitor::rtt(
+            Duration::from_millis(500),
+            Duration::from_secs(300),
+            AlertHook::default(),
+        );
+        let req = Request::post("/node/monitors").body(body);
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::BadRequest));
+
+        let body = CreateMonitor::recoveries(
+            3,
+            Duration::from_secs(3600),
+            AlertHook::new(None, Some("cat > /dev/null".into())),
+        );
+        let req = Request::post("/node/monitors").body(body);
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let monitor: MonitorStatus = dec.decode()?;
+        assert_eq!(monitor.kind, MonitorKind::Recoveries);
+        let id = monitor.id.to_string();
+
+        let req = Request::get("/node/monitors");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let monitors: MonitorList = dec.decode()?;
+        assert_eq!(monitors.list.len(), 1);
+        assert!(monitors.list[0].firing.is_empty());
+
+        let req = Request::delete(format!("/node/monitors/{id}"));
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let req = Request::delete(format!("/node/monitors/{id}"));
+        let buf = ockam_node::api::request(ctx, "", None, node_manager, req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::NotFound));
+
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,116 @@
+use minicbor::Decoder;
+use ockam::abac::{Action, Attributes, Conditional, Key, Resource, Subject, Value, FACT_PREFIX};
+use ockam::{Address, Context, Result};
+use ockam_core::api::{self, Method, Request, Response};
+use ockam_core::async_trait;
+
+use crate::error::ApiError;
+use crate::nodes::models::policy::{EvaluatePolicy, PolicyDecision};
+use crate::nodes::service::progress::Progress;
+use crate::nodes::service::service_registry::NodeService;
+use crate::nodes::NodeManager;
+
+/// Service evaluating ABAC policies on hypothetical requests, so that
+/// they can be checked before they are deployed.
+///
+/// The subject of a request only has the attributes it is given, which
+/// are strings as those of credentials. The attributes under `local.`
+/// keys are the facts of the request instead, whose values are booleans
+/// and integers when they read as such.
+pub(crate) struct PolicyService;
+
+#[async_trait]
+impl NodeService for PolicyService {
+    async fn handle_request(
+        &self,
+        _node: &NodeManager,
+        _ctx: &mut Context,
+        _this: &Address,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        _progress: &Progress,
+    ) -> Result<Option<Vec<u8>>> {
+        let r = match (req.method(), req.path_segments::<3>().as_slice()) {
+            (Some(Method::Post), ["node", "policy", "evaluate"]) => {
+                let body: EvaluatePolicy = dec.decode()?;
+                let policy: Conditional = match serde_json::from_str(&body.policy) {
+                    Ok(policy) => policy,
+                    Err(e) => {
+                        let msg = format!("invalid policy: {}", e);
+                        return Ok(Some(api::bad_request(req, &msg).to_vec()?));
+                    }
+                };
+                let (subject, facts) = hypothetical_request(&body);
+                let resource = Resource::from(&*body.resource);
+                let action = Action::from(&*body.action);
+                let decision = policy.decide_with(&subject, &resource, &action, &facts);
+                let matched = serde_json::to_string(&decision.matched).map_err(ApiError::wrap)?;
+                Response::ok(req.id())
+                    .body(PolicyDecision::new(decision.allowed, matched))
+                    .to_vec()?
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(r))
+    }
+}
+
+/// The subject and facts of the request of `body`
+fn hypothetical_request(body: &EvaluatePolicy) -> (Subject, Attributes) {
+    let mut attributes = Vec::new();
+    let mut facts = Attributes::new();
+    for (k, v) in &body.attributes {
+        if k.starts_with(FACT_PREFIX) {
+            let v = if let Ok(b) = v.parse() {
+                Value::B(b)
+            } else if let Ok(n) = v.parse() {
+                Value::I(n)
+            } else {
+                Value::S(v.to_string())
+            };
+            facts.insert(Key::from(&**k), v);
+        } else {
+            attributes.push((Key::from(&**k), Value::S(v.to_string())));
+        }
+    }
+    // Policies don't reference the identifier of subjects
+    (Subject::from(0).with_attributes(attributes), facts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::abac::{eq, string};
+    use ockam_core::api::Status;
+    use std::collections::BTreeMap;
+
+    #[ockam_macros::test]
+    async fn policies_are_evaluated_on_hypothetical_requests(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+        let policy =
+            eq("role", string("admin")).and(&eq("local.tag.prod", ockam::abac::bool(true)));
+        let policy = serde_json::to_string(&policy).unwrap();
+
+        let mut attributes = BTreeMap::new();
+        attributes.insert("role".to_string(), "admin".to_string());
+        attributes.insert("local.tag.prod".to_string(), "false".to_string());
+        let body = EvaluatePolicy::new(policy.as_str(), "db", "r", attributes);
+        let req = Request::post("/node/policy/evaluate").body(body);
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let decision: PolicyDecision = dec.decode()?;
+        assert!(!decision.allowed);
+        let matched: Conditional = serde_json::from_str(&decision.matched).unwrap();
+        assert!(matches!(matched, Conditional::Eq(k, _) if &*k == "local.tag.prod"));
+
+        let body = EvaluatePolicy::new("{\"Eq\":", "db", "r", BTreeMap::new());
+        let req = Request::post("/node/policy/evaluate").body(body);
+        let buf = ockam_node::api::request(ctx, "", None, node_manager, req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::BadRequest));
+
+        ctx.stop().await
+    }
+}
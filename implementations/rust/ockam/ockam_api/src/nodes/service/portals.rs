@@ -1,60 +1,252 @@
 use crate::authenticator::direct::{PROJECT_ID, ROLE};
+use crate::error::ApiError;
 use crate::multiaddr_to_route;
 use crate::nodes::models::portal::{
     CreateInlet, CreateOutlet, InletList, InletStatus, OutletList, OutletStatus,
 };
 use crate::nodes::registry::{InletInfo, OutletInfo};
-use crate::nodes::service::{map_multiaddr_err, random_alias};
+use crate::nodes::service::progress::Progress;
+use crate::nodes::service::service_registry::NodeService;
+use crate::nodes::service::{map_multiaddr_err, random_alias, Alias};
 use crate::nodes::NodeManager;
+use crate::DefaultAddress;
 use minicbor::Decoder;
-use ockam::tcp::{InletOptions, OutletOptions};
-use ockam::{Address, Result};
-use ockam_core::api::{Request, Response, ResponseBuilder};
-use ockam_core::{AccessControl, AllowAll};
+use ockam::tcp::{
+    EgressPolicy, InletOptions, InletRoute, OutletOptions, ProxyProtocol, ProxyRoutes,
+};
+use ockam::{Address, Context, Result, Route};
+use ockam_core::api::{Method, Request, Response, ResponseBuilder};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::{async_trait, AccessControl, AllowAll};
 use ockam_identity::credential::access_control::CredentialAccessControl;
 use ockam_multiaddr::MultiAddr;
+use ockam_node::tokio;
+use ockam_node::tokio::sync::RwLock;
+use ockam_node::tokio::task::JoinHandle;
+#[cfg(feature = "tls")]
+use ockam_transport_tcp::tls::{InletTls, OutletTls, SelfSignedCert};
+#[cfg(feature = "tls")]
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
-impl NodeManager {
-    pub(super) fn get_inlets(&self, req: &Request<'_>) -> ResponseBuilder<InletList<'_>> {
-        Response::ok(req.id()).body(InletList::new(
-            self.registry
-                .inlets
-                .iter()
-                .map(|(alias, info)| {
-                    InletStatus::new(
-                        &info.bind_addr,
-                        info.worker_addr.to_string(),
-                        alias,
-                        None,
-                        // FIXME route.as_ref().map(|r| r.to_string().into()),
-                    )
-                })
-                .collect(),
-        ))
+/// Time between two health checks of the routes of an inlet.
+const ROUTE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Time (in seconds) to wait for an answer to a health check.
+const ROUTE_CHECK_TIMEOUT: u64 = 3;
+
+/// Terminate TLS on an inlet, returning its options and certificate path.
+///
+/// Without a certificate and key, a self-signed certificate for the inlet is
+/// stored in the node directory. It is reused when an inlet with the same
+/// alias is created again, so that clients only need to trust it once.
+#[cfg(feature = "tls")]
+fn with_inlet_tls(
+    options: InletOptions,
+    node_dir: &Path,
+    alias: &str,
+    bind_addr: &str,
+    cert_and_key: Option<(&str, &str)>,
+) -> Result<(InletOptions, PathBuf)> {
+    let tls = inlet_tls(node_dir, alias, bind_addr, cert_and_key)?;
+    let cert = tls.cert_path().to_path_buf();
+    Ok((options.with_tls(tls), cert))
+}
+
+#[cfg(not(feature = "tls"))]
+fn with_inlet_tls(
+    _options: InletOptions,
+    _node_dir: &Path,
+    _alias: &str,
+    _bind_addr: &str,
+    _cert_and_key: Option<(&str, &str)>,
+) -> Result<(InletOptions, PathBuf)> {
+    Err(ApiError::generic("this node was built without TLS support"))
+}
+
+#[cfg(feature = "tls")]
+fn inlet_tls(
+    node_dir: &Path,
+    alias: &str,
+    bind_addr: &str,
+    cert_and_key: Option<(&str, &str)>,
+) -> Result<InletTls> {
+    if let Some((cert, key)) = cert_and_key {
+        return InletTls::from_pem_files(cert, key);
+    }
+    let dir = node_dir.join("tls");
+    let cert = dir.join(format!("{alias}.crt"));
+    let key = dir.join(format!("{alias}.key"));
+    if !cert.exists() || !key.exists() {
+        std::fs::create_dir_all(&dir).map_err(|e| ApiError::generic(&e.to_string()))?;
+        let mut names = vec!["localhost", "127.0.0.1", "::1"];
+        let host = bind_addr
+            .rsplit_once(':')
+            .map(|(h, _)| h.trim_matches(|c| c == '[' || c == ']'));
+        if let Some(host) = host {
+            let specified = matches!(host.parse::<IpAddr>(), Ok(ip) if !ip.is_unspecified());
+            if specified && !names.contains(&host) {
+                names.push(host)
+            }
+        }
+        SelfSignedCert::generate(&names)?.write(&cert, &key)?;
+        info!(cert = %cert.display(), "generated a self-signed certificate for inlet {alias}");
+    }
+    InletTls::from_pem_files(cert, key)
+}
+
+/// Connect an outlet to its target over TLS.
+#[cfg(feature = "tls")]
+fn with_outlet_tls(
+    options: OutletOptions,
+    server_name: &str,
+    ca: Option<PathBuf>,
+    client: Option<(PathBuf, PathBuf)>,
+) -> Result<OutletOptions> {
+    Ok(options.with_tls(OutletTls::new(server_name, ca, client)?))
+}
+
+#[cfg(not(feature = "tls"))]
+fn with_outlet_tls(
+    _options: OutletOptions,
+    _server_name: &str,
+    _ca: Option<PathBuf>,
+    _client: Option<(PathBuf, PathBuf)>,
+) -> Result<OutletOptions> {
+    Err(ApiError::generic("this node was built without TLS support"))
+}
+
+/// Parse the `rule=route` routes of a proxy inlet.
+fn parse_proxy_routes<'a>(routes: impl Iterator<Item = &'a str>) -> Result<ProxyRoutes, String> {
+    let mut parsed = ProxyRoutes::new();
+    for r in routes {
+        let invalid = || format!("invalid proxy route: {r}");
+        let (rule, addr) = r.split_once('=').ok_or_else(invalid)?;
+        let ma = MultiAddr::from_str(addr).map_err(|_| invalid())?;
+        let route = multiaddr_to_route(&ma).ok_or_else(invalid)?;
+        parsed = parsed.add(rule, route).map_err(|e| e.to_string())?;
+    }
+    Ok(parsed)
+}
+
+/// Service managing the TCP inlets and outlets of a node
+#[derive(Default)]
+pub(crate) struct PortalService {
+    // FIXME: wow this is a terrible way to store data
+    inlets: RwLock<BTreeMap<Alias, InletInfo>>,
+    outlets: RwLock<BTreeMap<Alias, OutletInfo>>,
+    /// Inlets with backup routes and the tasks choosing their route
+    watchers: RwLock<BTreeMap<Alias, RouteWatcher>>,
+}
+
+/// The routes of an inlet and the task moving it to a healthy one
+struct RouteWatcher {
+    current: InletRoute,
+    routes: Vec<(String, Route)>,
+    task: JoinHandle<()>,
+}
+
+impl RouteWatcher {
+    /// The multiaddr of the route currently used by the inlet.
+    fn current(&self) -> Option<&str> {
+        let current = self.current.get();
+        self.routes
+            .iter()
+            .find(|(_, r)| *r == current)
+            .map(|(a, _)| a.as_str())
+    }
+}
+
+#[async_trait]
+impl NodeService for PortalService {
+    async fn handle_request(
+        &self,
+        node: &NodeManager,
+        ctx: &mut Context,
+        _this: &Address,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        _progress: &Progress,
+    ) -> Result<Option<Vec<u8>>> {
+        use Method::*;
+        let r = match (req.method(), req.path_segments::<5>().as_slice()) {
+            (Some(Get), ["node", "inlet"]) => self.get_inlets(node, req).await.to_vec()?,
+            (Some(Get), ["node", "outlet"]) => self.get_outlets(req).await.to_vec()?,
+            (Some(Post), ["node", "inlet"]) => {
+                self.create_inlet(node, ctx, req, dec).await?.to_vec()?
+            }
+            (Some(Delete), ["node", "inlet", alias]) => {
+                self.delete_inlet(node, req, alias).await?.to_vec()?
+            }
+            (Some(Post), ["node", "outlet"]) => {
+                self.create_outlet(node, req, dec).await?.to_vec()?
+            }
+            (Some(Delete), ["node", "portal"]) => todo!(),
+            _ => return Ok(None),
+        };
+        Ok(Some(r))
     }
+}
 
-    pub(super) fn get_outlets(&self, req: &Request<'_>) -> ResponseBuilder<OutletList<'_>> {
+impl PortalService {
+    async fn get_inlets(
+        &self,
+        node: &NodeManager,
+        req: &Request<'_>,
+    ) -> ResponseBuilder<InletList<'_>> {
+        let watchers = self.watchers.read().await;
+        let inlets = self.inlets.read().await;
+        let mut list = Vec::with_capacity(inlets.len());
+        for (alias, info) in inlets.iter() {
+            let route = watchers
+                .get(alias)
+                .and_then(|w| w.current())
+                .unwrap_or(&info.outlet_route);
+            let mut status = InletStatus::new(
+                info.bind_addr.clone(),
+                info.worker_addr.to_string(),
+                alias.clone(),
+                None,
+            );
+            status.outlet_route = Some(route.to_string().into());
+            status.check_credential = Some(info.check_credential);
+            status.tls_cert = info.tls_cert.clone().map(Into::into);
+            let outlet = MultiAddr::from_str(route).ok();
+            if let Some(r) = outlet.as_ref().and_then(multiaddr_to_route) {
+                status.outlet_identity = node.peer_identity(&r).await.map(|i| i.to_string().into());
+            }
+            list.push(status)
+        }
+        Response::ok(req.id()).body(InletList::new(list))
+    }
+
+    async fn get_outlets(&self, req: &Request<'_>) -> ResponseBuilder<OutletList<'_>> {
         Response::ok(req.id()).body(OutletList::new(
-            self.registry
-                .outlets
+            self.outlets
+                .read()
+                .await
                 .iter()
                 .map(|(alias, info)| {
-                    OutletStatus::new(
-                        &info.tcp_addr,
+                    let mut status = OutletStatus::new(
+                        info.tcp_addr.clone(),
                         info.worker_addr.to_string(),
-                        alias,
+                        alias.clone(),
                         None,
-                        // FIXME route.as_ref().map(|r| r.to_string().into()),
-                    )
+                    );
+                    status.check_credential = Some(info.check_credential);
+                    status
                 })
                 .collect(),
         ))
     }
 
-    pub(super) async fn create_inlet<'a>(
-        &mut self,
+    async fn create_inlet<'a>(
+        &self,
+        node: &NodeManager,
+        ctx: &Context,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder<InletStatus<'a>>> {
@@ -63,48 +255,138 @@ impl NodeManager {
             outlet_route,
             alias,
             check_credential,
+            backup_routes,
+            tls,
+            tls_cert,
+            tls_key,
+            proxy,
+            proxy_routes,
+            socket,
             ..
         } = dec.decode()?;
         let bind_addr = bind_addr.to_string();
+        let route_str = outlet_route.to_string();
 
         let alias = alias.map(|a| a.0.into()).unwrap_or_else(random_alias);
 
         info!("Handling request to create inlet portal");
 
-        let outlet_route = MultiAddr::from_str(&outlet_route).map_err(map_multiaddr_err)?;
-        let outlet_route = match multiaddr_to_route(&outlet_route) {
-            Some(route) => route,
-            None => {
-                return Ok(Response::bad_request(req.id())
-                    .body(InletStatus::bad_request("invalid outlet route")))
+        // A proxy without an outlet route only uses its proxy routes
+        let no_outlet = proxy.is_some() && outlet_route.is_empty();
+        let mut routes = Vec::new();
+        for addr in Some(outlet_route.as_ref())
+            .filter(|_| !no_outlet)
+            .into_iter()
+            .chain(backup_routes.iter().flatten().map(|r| r.as_ref()))
+        {
+            let ma = MultiAddr::from_str(addr).map_err(map_multiaddr_err)?;
+            match multiaddr_to_route(&ma) {
+                Some(route) => routes.push((ma.cost(), addr.to_string(), route)),
+                None => {
+                    return Ok(Response::bad_request(req.id())
+                        .body(InletStatus::bad_request("invalid outlet route")))
+                }
             }
+        }
+        // The cheapest routes come first, otherwise the given order is kept.
+        routes.sort_by_key(|(cost, _, _)| *cost);
+        let routes: Vec<(String, Route)> = routes.into_iter().map(|(_, a, r)| (a, r)).collect();
+
+        let access_control = Self::access_control(node, check_credential)?;
+        let first = match routes.first() {
+            Some((_, r)) => r.clone(),
+            None => Route::new().into(),
         };
+        let mut options = InletOptions::new(bind_addr.clone(), first, access_control);
+        if let Some(socket) = &socket {
+            options = options.with_listener_options(socket.listener_options())
+        }
+        let current = options.outlet_route().clone();
 
-        let access_control = self.access_control(check_credential)?;
-        let options = InletOptions::new(bind_addr.clone(), outlet_route, access_control);
+        match parse_proxy_routes(proxy_routes.iter().flatten().map(|r| r.as_ref())) {
+            Ok(r) => options = options.with_proxy_routes(r),
+            Err(e) => {
+                return Ok(Response::bad_request(req.id()).body(InletStatus::new(
+                    bind_addr,
+                    "",
+                    alias,
+                    Some(e.into()),
+                )))
+            }
+        }
+
+        match proxy.as_deref() {
+            None => {}
+            Some("socks5") => options = options.with_proxy(ProxyProtocol::Socks5),
+            Some("http") => options = options.with_proxy(ProxyProtocol::HttpConnect),
+            Some(other) => {
+                return Ok(Response::bad_request(req.id()).body(InletStatus::new(
+                    bind_addr,
+                    "",
+                    alias,
+                    Some(format!("unsupported proxy protocol: {other}").into()),
+                )))
+            }
+        }
+
+        let mut cert_path = None;
+        if tls == Some(true) {
+            let cert_and_key = tls_cert.as_deref().zip(tls_key.as_deref());
+            match with_inlet_tls(options, &node.node_dir, &alias, &bind_addr, cert_and_key) {
+                Ok((tls_options, cert)) => {
+                    cert_path = Some(cert.display().to_string());
+                    options = tls_options
+                }
+                Err(e) => {
+                    return Ok(Response::bad_request(req.id()).body(InletStatus::new(
+                        bind_addr,
+                        "",
+                        alias,
+                        Some(e.to_string().into()),
+                    )))
+                }
+            }
+        }
 
-        let res = self.tcp_transport.create_inlet_extended(options).await;
+        let res = node.tcp_transport.create_inlet_extended(options).await;
+        let outlet_identity = node.peer_identity(&current.get()).await;
 
         Ok(match res {
             Ok((worker_addr, _)) => {
+                if routes.len() > 1 {
+                    let ctx = ctx.new_detached(Address::random_local()).await?;
+                    let task = tokio::spawn(watch_routes(
+                        ctx,
+                        current.clone(),
+                        routes.iter().map(|(_, r)| r.clone()).collect(),
+                    ));
+                    let watcher = RouteWatcher {
+                        current,
+                        routes,
+                        task,
+                    };
+                    if let Some(w) = self.watchers.write().await.insert(alias.clone(), watcher) {
+                        w.task.abort()
+                    }
+                }
+
                 // TODO: Use better way to store inlets?
-                self.registry.inlets.insert(
-                    alias.clone(),
-                    InletInfo::new(&bind_addr, Some(&worker_addr)),
-                );
+                let mut info =
+                    InletInfo::new(&bind_addr, Some(&worker_addr), &route_str, check_credential);
+                info.tls_cert = cert_path.clone();
+                self.inlets.write().await.insert(alias.clone(), info);
 
-                Response::ok(req.id()).body(InletStatus::new(
-                    bind_addr,
-                    worker_addr.to_string(),
-                    alias,
-                    None,
-                ))
+                let mut status = InletStatus::new(bind_addr, worker_addr.to_string(), alias, None);
+                status.tls_cert = cert_path.map(Into::into);
+                status.outlet_identity = outlet_identity.map(|i| i.to_string().into());
+                Response::ok(req.id()).body(status)
             }
             Err(e) => {
                 // TODO: Use better way to store inlets?
-                self.registry
-                    .inlets
-                    .insert(alias.clone(), InletInfo::new(&bind_addr, None));
+                self.inlets.write().await.insert(
+                    alias.clone(),
+                    InletInfo::new(&bind_addr, None, &route_str, check_credential),
+                );
 
                 Response::bad_request(req.id()).body(InletStatus::new(
                     bind_addr,
@@ -116,24 +398,55 @@ impl NodeManager {
         })
     }
 
-    fn access_control(&self, check_credential: bool) -> Result<Arc<dyn AccessControl>> {
+    async fn delete_inlet(
+        &self,
+        node: &NodeManager,
+        req: &Request<'_>,
+        alias: &str,
+    ) -> Result<ResponseBuilder> {
+        let info = match self.inlets.write().await.remove(alias) {
+            Some(info) => info,
+            None => return Ok(Response::not_found(req.id())),
+        };
+
+        info!(%alias, "Handling request to delete inlet portal");
+
+        if let Some(w) = self.watchers.write().await.remove(alias) {
+            w.task.abort()
+        }
+
+        // Inlets which failed to start have no listener to stop
+        if !info.worker_addr.address().is_empty() {
+            node.tcp_transport.stop_inlet(info.worker_addr).await?;
+        }
+
+        Ok(Response::ok(req.id()))
+    }
+
+    fn access_control(
+        node: &NodeManager,
+        check_credential: bool,
+    ) -> Result<Arc<dyn AccessControl>> {
         if check_credential {
-            let project_id = self.project_id()?;
+            let project_id = node.project_id()?;
             let required_attributes = vec![
                 (PROJECT_ID.to_string(), project_id.clone()),
                 (ROLE.to_string(), b"member".to_vec()),
             ];
-            Ok(Arc::new(CredentialAccessControl::new(
-                &required_attributes,
-                self.authenticated_storage.clone(),
-            )))
+            Ok(node
+                .events
+                .count_denials(Arc::new(CredentialAccessControl::new(
+                    &required_attributes,
+                    node.authenticated_storage.clone(),
+                ))))
         } else {
             Ok(Arc::new(AllowAll))
         }
     }
 
-    pub(super) async fn create_outlet<'a>(
-        &mut self,
+    async fn create_outlet<'a>(
+        &self,
+        node: &NodeManager,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder<OutletStatus<'a>>> {
@@ -142,26 +455,65 @@ impl NodeManager {
             worker_addr,
             alias,
             check_credential,
+            tls_server_name,
+            tls_ca,
+            tls_cert,
+            tls_key,
+            egress,
             ..
         } = dec.decode()?;
-        let tcp_addr = tcp_addr.to_string();
+        let mut tcp_addr = tcp_addr.to_string();
 
         let alias = alias.map(|a| a.0.into()).unwrap_or_else(random_alias);
 
         info!("Handling request to create outlet portal");
         let worker_addr = Address::from(worker_addr.as_ref());
 
-        let access_control = self.access_control(check_credential)?;
-        let options = OutletOptions::new(worker_addr.clone(), tcp_addr.clone(), access_control);
+        let access_control = Self::access_control(node, check_credential)?;
+        let mut options = match egress {
+            Some(rules) => match EgressPolicy::parse(&rules) {
+                Ok(egress) => {
+                    // A proxy outlet has no target of its own, so its rules are shown instead
+                    tcp_addr = format!("proxy to {egress}");
+                    OutletOptions::proxy(worker_addr.clone(), egress, access_control)
+                }
+                Err(e) => {
+                    return Ok(Response::bad_request(req.id()).body(OutletStatus::new(
+                        tcp_addr,
+                        worker_addr.to_string(),
+                        alias,
+                        Some(e.to_string().into()),
+                    )))
+                }
+            },
+            None => OutletOptions::new(worker_addr.clone(), tcp_addr.clone(), access_control),
+        };
+        if let Some(server_name) = tls_server_name {
+            let ca = tls_ca.map(|p| PathBuf::from(p.as_ref()));
+            let client = tls_cert
+                .zip(tls_key)
+                .map(|(c, k)| (PathBuf::from(c.as_ref()), PathBuf::from(k.as_ref())));
+            match with_outlet_tls(options, &server_name, ca, client) {
+                Ok(tls_options) => options = tls_options,
+                Err(e) => {
+                    return Ok(Response::bad_request(req.id()).body(OutletStatus::new(
+                        tcp_addr,
+                        worker_addr.to_string(),
+                        alias,
+                        Some(e.to_string().into()),
+                    )))
+                }
+            }
+        }
 
-        let res = self.tcp_transport.create_outlet_extended(options).await;
+        let res = node.tcp_transport.create_outlet_extended(options).await;
 
         Ok(match res {
             Ok(_) => {
                 // TODO: Use better way to store outlets?
-                self.registry.outlets.insert(
+                self.outlets.write().await.insert(
                     alias.clone(),
-                    OutletInfo::new(&tcp_addr, Some(&worker_addr)),
+                    OutletInfo::new(&tcp_addr, Some(&worker_addr), check_credential),
                 );
 
                 Response::ok(req.id()).body(OutletStatus::new(
@@ -173,9 +525,10 @@ impl NodeManager {
             }
             Err(e) => {
                 // TODO: Use better way to store outlets?
-                self.registry
-                    .outlets
-                    .insert(alias.clone(), OutletInfo::new(&tcp_addr, None));
+                self.outlets.write().await.insert(
+                    alias.clone(),
+                    OutletInfo::new(&tcp_addr, None, check_credential),
+                );
 
                 Response::bad_request(req.id()).body(OutletStatus::new(
                     tcp_addr,
@@ -187,3 +540,43 @@ impl NodeManager {
         })
     }
 }
+
+/// Periodically move an inlet to the first of its routes which answers
+/// health checks.
+///
+/// Routes are tried in order of cost, so an inlet returns to its
+/// cheapest route as soon as it is healthy again.
+async fn watch_routes(ctx: Context, current: InletRoute, routes: Vec<Route>) {
+    let mut interval = tokio::time::interval(ROUTE_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let mut healthy = None;
+        for route in &routes {
+            if is_healthy(&ctx, route).await {
+                healthy = Some(route);
+                break;
+            }
+        }
+        match healthy {
+            Some(route) if *route != current.get() => {
+                info!(%route, "inlet moving to another route");
+                current.set(route.clone())
+            }
+            Some(_) => {}
+            None => warn!("no healthy route to the outlet of the inlet"),
+        }
+    }
+}
+
+/// Check if the node at the end of a route to an outlet answers.
+async fn is_healthy(ctx: &Context, route: &Route) -> bool {
+    let echo: Route = route
+        .clone()
+        .modify()
+        .pop_back()
+        .append(DefaultAddress::ECHO_SERVICE)
+        .into();
+    ctx.send_and_receive_with_timeout::<_, _, Vec<u8>>(echo, vec![0u8], ROUTE_CHECK_TIMEOUT)
+        .await
+        .is_ok()
+}
@@ -0,0 +1,87 @@
+use ockam::{Context, Route};
+use ockam_core::api::{Id, Request, Response};
+
+use crate::nodes::models::progress::{PhaseState, ProgressEvent};
+
+/// Reports the phases of a long running request back to the client.
+///
+/// Events are only sent if the client indicated in the request header
+/// that it accepts interim `Processing` responses.
+pub(crate) struct Progress {
+    re: Id,
+    route: Option<Route>,
+}
+
+impl Progress {
+    pub(crate) fn new(req: &Request<'_>, return_route: Route) -> Self {
+        Self {
+            re: req.id(),
+            route: req.wants_progress().then_some(return_route),
+        }
+    }
+
+    /// A reporter which does not send any events.
+    pub(crate) fn disabled() -> Self {
+        Self {
+            re: Id::default(),
+            route: None,
+        }
+    }
+
+    pub(crate) async fn started(&self, ctx: &Context, phase: &str) {
+        self.send(ctx, phase, PhaseState::Started).await
+    }
+
+    pub(crate) async fn completed(&self, ctx: &Context, phase: &str) {
+        self.send(ctx, phase, PhaseState::Completed).await
+    }
+
+    async fn send(&self, ctx: &Context, phase: &str, state: PhaseState) {
+        if let Some(route) = &self.route {
+            let res = Response::processing(self.re).body(ProgressEvent::new(phase, state));
+            let res = match res.to_vec() {
+                Ok(v) => ctx.send(route.clone(), v).await,
+                Err(e) => Err(e.into()),
+            };
+            if let Err(err) = res {
+                debug!(re = %self.re, %phase, %err, "failed to send progress event")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minicbor::Decoder;
+    use ockam::Result;
+    use ockam_core::api::Status;
+    use ockam_core::route;
+
+    use super::*;
+
+    #[ockam_macros::test]
+    async fn progress_events_are_opt_in(ctx: &mut Context) -> Result<()> {
+        let (req, _) = Request::post("/node/forwarder").into_parts();
+        Progress::new(&req, route![ctx.address()])
+            .started(ctx, "ignored")
+            .await;
+
+        let (req, _) = Request::post("/node/forwarder").progress(true).into_parts();
+        let progress = Progress::new(&req, route![ctx.address()]);
+        progress.started(ctx, "Connecting").await;
+        progress.completed(ctx, "Connecting").await;
+
+        for state in [PhaseState::Started, PhaseState::Completed] {
+            let buf = ctx.receive::<Vec<u8>>().await?.take().body();
+            let mut dec = Decoder::new(&buf);
+            let res: Response = dec.decode()?;
+            assert_eq!(res.status(), Some(Status::Processing));
+            assert_eq!(res.re(), req.id());
+            let event: ProgressEvent = dec.decode()?;
+            assert_eq!(event.phase(), "Connecting");
+            assert_eq!(event.state(), state);
+        }
+
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,241 @@
+//! Re-creation of the secure channels of sessions.
+//!
+//! Sessions are replaced from a task of the medic, without access to the
+//! node manager, so channels are created and deleted by sending requests
+//! to the node manager.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use minicbor::Decoder;
+
+use ockam::{Address, Result};
+use ockam_core::api::{Error, Request, Response, Status};
+use ockam_identity::IdentityIdentifier;
+use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Project, Secure, Tcp};
+use ockam_multiaddr::{Match, MultiAddr, Protocol};
+use ockam_node::tokio::time::{timeout_at, Instant};
+use ockam_node::Context;
+
+use crate::cloud::project::Project as ProjectData;
+use crate::cloud::CloudRequestWrapper;
+use crate::error::ApiError;
+use crate::multiaddr_to_addr;
+use crate::nodes::models::secure_channel::{
+    AuthorityArg, CreateSecureChannelRequest, CreateSecureChannelResponse, CredentialExchangeMode,
+    DeleteSecureChannelRequest, KeyAgreementMode,
+};
+use crate::relays::RelaySelector;
+use crate::session::{Recovery, Session, Step};
+
+/// Creates new secure channels to an address, resolving its project if any.
+#[derive(Clone)]
+pub(super) struct Reconnect {
+    /// The node manager creating the channels
+    pub(super) manager: Address,
+    pub(super) ctx: Arc<Context>,
+    /// The address the channels lead to
+    pub(super) addr: MultiAddr,
+    /// The orchestrator resolving projects
+    pub(super) cloud: Option<MultiAddr>,
+    pub(super) auth: Option<Vec<IdentityIdentifier>>,
+    pub(super) mode: CredentialExchangeMode,
+    pub(super) relays: RelaySelector,
+    /// Time the creation of a secure channel may take
+    pub(super) connect_timeout: Duration,
+    pub(super) key_agreement: Option<KeyAgreementMode>,
+    /// Interval of the keepalives of the channels, if any
+    pub(super) keepalive: Option<Duration>,
+    /// Whether the keepalives measure the round-trip time of the channels
+    pub(super) measure_rtt: Option<bool>,
+    /// Whether the channels go through onion relays
+    pub(super) onion: Option<bool>,
+    /// The trust context of the channels, if not that of the node
+    pub(super) authorities: Option<Vec<AuthorityArg<'static>>>,
+}
+
+impl Reconnect {
+    pub(super) fn is_project(&self) -> bool {
+        self.addr.first().map(|p| p.code()) == Some(Project::CODE)
+    }
+
+    /// Whether a secure channel is created to reach the address.
+    pub(super) fn has_secure_channel(&self) -> bool {
+        self.is_project()
+            || self.addr.matches(
+                0,
+                &[
+                    Match::any([DnsAddr::CODE, Ip4::CODE, Ip6::CODE]),
+                    Tcp::CODE.into(),
+                    Secure::CODE.into(),
+                ],
+            )
+    }
+
+    /// Create a new secure channel replacing `prev`, recording the steps
+    /// taken in `rec`.
+    pub(super) async fn secure_channel(
+        &self,
+        prev: &MultiAddr,
+        deadline: Instant,
+        rec: &mut Recovery,
+    ) -> Result<MultiAddr> {
+        let (a, auth) = if self.is_project() {
+            let p = self
+                .addr
+                .first()
+                .ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
+            let p = p
+                .cast::<Project>()
+                .ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
+            let c = self
+                .cloud
+                .as_ref()
+                .ok_or_else(|| ApiError::message("missing cloud address"))?;
+            let r = resolve_project(self.manager.clone(), &self.ctx, &self.relays, &p, c);
+            let r = step(deadline, r).await;
+            rec.step(Step::ResolveProject, &r);
+            let (mut a, i) = r?;
+            a.try_extend(self.addr.iter().skip(1))?;
+            (a, Some(vec![i]))
+        } else {
+            (self.addr.clone(), self.auth.clone())
+        };
+        let mut req = CreateSecureChannelRequest::new(&a, auth, self.mode);
+        req.key_agreement = self.key_agreement;
+        req.keepalive = self.keepalive;
+        req.measure_rtt = self.measure_rtt;
+        req.onion = self.onion;
+        req.authorities = self.authorities.clone();
+        let r = create_sec_chan(&self.ctx, &self.manager, req, prev, self.connect_timeout);
+        let r = step(deadline, r).await;
+        rec.step(Step::SecureChannel, &r);
+        r
+    }
+
+    pub(super) async fn delete(&self, addr: &MultiAddr) {
+        if let Err(e) = delete_sec_chan(&self.ctx, &self.manager, addr).await {
+            debug!(%addr, err = %e, "failed to delete secure channel")
+        }
+    }
+
+    /// Replace the secure channel `prev` of a session.
+    ///
+    /// The new channel is created before `prev` is deleted, so that a
+    /// channel rotated because of its age stays usable meanwhile.
+    pub(super) async fn replace(self, prev: MultiAddr, recovery_timeout: Duration) -> Recovery {
+        debug!(%prev, addr = %self.addr, "creating new secure channel");
+        let deadline = Instant::now() + recovery_timeout;
+        let mut rec = Recovery::new();
+        let r = self.secure_channel(&prev, deadline, &mut rec).await;
+        match &r {
+            Ok(_) => self.delete(&prev).await,
+            Err(e) => warn!(addr = %self.addr, err = %e, "error creating new secure channel"),
+        }
+        rec.finish(r)
+    }
+}
+
+/// Configure a session to replace its secure channel with `r`.
+pub(super) fn enable_reconnect(session: &mut Session, r: Reconnect, recovery_timeout: Duration) {
+    session.set_replacement(move |prev| Box::pin(r.clone().replace(prev, recovery_timeout)))
+}
+
+/// Resolve the project name to an address and authorised identity.
+///
+/// Uses message passing since projects are looked up by the cloud service
+/// of the node manager at address `manager`. If the project has several
+/// relays, the address of the one selected by `relays` is returned.
+pub(super) async fn resolve_project(
+    manager: Address,
+    ctx: &Context,
+    relays: &RelaySelector,
+    project: &str,
+    cloud: &MultiAddr,
+) -> Result<(MultiAddr, IdentityIdentifier)> {
+    debug!(%project, %cloud, "resolving project");
+    let req = Request::get(format!("/v0/projects/{project}"))
+        .body(CloudRequestWrapper::bare(cloud))
+        .to_vec()?;
+    let vec: Vec<u8> = ctx.send_and_receive(manager, req).await?;
+    let (addrs, auth) = project_data(&vec)?;
+    let addr = relays
+        .select(project, &addrs)
+        .await
+        .ok_or_else(|| ApiError::generic("project has no access route"))?;
+    debug!(%project, %addr, "resolved project");
+    Ok((addr, auth))
+}
+
+/// Extract the project addresses and identity from response bytes.
+fn project_data(bytes: &[u8]) -> Result<(Vec<MultiAddr>, IdentityIdentifier)> {
+    let mut dec = Decoder::new(bytes);
+    let res: Response = dec.decode()?;
+    if res.status() != Some(Status::Ok) {
+        return Err(ApiError::generic("failed to get project info"));
+    }
+    let res: ProjectData = dec.decode()?;
+    let addr = res.access_routes()?;
+    let auth = res
+        .identity
+        .ok_or_else(|| ApiError::generic("project has no identity"))?;
+    Ok((addr, auth))
+}
+
+/// Run a step of a recovery until the deadline of the whole recovery.
+pub(super) async fn step<T, F>(deadline: Instant, f: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    match timeout_at(deadline, f).await {
+        Ok(r) => r,
+        Err(_) => Err(ApiError::generic("timeout")),
+    }
+}
+
+async fn delete_sec_chan(ctx: &Context, manager: &Address, addr: &MultiAddr) -> Result<()> {
+    debug!(%addr, "deleting secure channel");
+    let req = {
+        let a = multiaddr_to_addr(addr)
+            .ok_or_else(|| ApiError::message(format!("could not map to address: {addr}")))?;
+        DeleteSecureChannelRequest::new(&a)
+    };
+    let req = Request::delete("/node/secure_channel").body(req).to_vec()?;
+    let vec: Vec<u8> = ctx.send_and_receive(manager.clone(), req).await?;
+    let mut d = Decoder::new(&vec);
+    let res: Response = d.decode()?;
+    if res.status() != Some(Status::Ok) && res.has_body() {
+        let e: Error = d.decode()?;
+        debug!(%addr, err = ?e.message(), "failed to delete secure channel");
+    }
+    Ok(())
+}
+
+/// Create the secure channel of `req`, which is not the channel `prev`
+/// even if that one leads to the same address.
+async fn create_sec_chan(
+    ctx: &Context,
+    manager: &Address,
+    mut req: CreateSecureChannelRequest<'_>,
+    prev: &MultiAddr,
+    timeout: Duration,
+) -> Result<MultiAddr> {
+    let addr = req.addr.to_string();
+    debug!(%addr, "creating secure channel");
+    req.timeout = Some(timeout);
+    req.replaces = multiaddr_to_addr(prev).map(|a| a.to_string().into());
+    let req = Request::post("/node/secure_channel").body(req).to_vec()?;
+    let vec: Vec<u8> = ctx.send_and_receive(manager.clone(), req).await?;
+    let mut d = Decoder::new(&vec);
+    let res: Response = d.decode()?;
+    if res.status() != Some(Status::Ok) {
+        if res.has_body() {
+            let e: Error = d.decode()?;
+            warn!(%addr, err = ?e.message(), "failed to create secure channel");
+        }
+        return Err(ApiError::generic("error creating secure channel"));
+    }
+    let res: CreateSecureChannelResponse = d.decode()?;
+    res.addr()
+}
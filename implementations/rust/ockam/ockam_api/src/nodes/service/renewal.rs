@@ -0,0 +1,138 @@
+//! Renewal of the credential of a member node, before it expires or once
+//! it is not accepted anymore
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::Error;
+use ockam_identity::credential::Timestamp;
+use ockam_node::tokio;
+use ockam_node::tokio::time::{sleep, Instant};
+
+use crate::events::NodeEvent;
+use crate::nodes::NodeManager;
+
+/// Time between two checks of the credential
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Time before a failed renewal is tried again, doubled after every failure
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(10);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(10 * 60);
+
+impl NodeManager {
+    /// Renew the credential as soon as possible if `err`, the failure of
+    /// its presentation, is a peer refusing it.
+    pub(super) fn check_presentation(&self, err: &Error) {
+        let code = err.code();
+        if code.origin == Origin::Application && code.kind == Kind::Invalid {
+            self.credential_refused.notify_one()
+        }
+    }
+
+    /// Why the credential of the node must be renewed, if it must.
+    async fn renewal_reason(&self, refused: bool) -> Option<&'static str> {
+        let identity = self.identity().await.ok()?;
+        // Nodes without credential get one when they first need it
+        let credential = identity.credential().await?;
+        let authorities = self.authorities().ok()?.public_identities();
+        match identity
+            .verify_self_credential(&credential, authorities.iter())
+            .await
+        {
+            // Expired, or issued by an authority which is not trusted anymore
+            Err(_) => Some("the credential is invalid"),
+            Ok(_) if refused => Some("the credential was refused"),
+            Ok(data) => {
+                let (created, expires) = (data.created_at(), data.expires_at());
+                let now = Timestamp::now()?;
+                renewal_due(created.into(), expires.into(), now.into())
+                    .then_some("the credential expires soon")
+            }
+        }
+    }
+}
+
+/// Credentials are renewed in the last third of their validity, before
+/// their expiry is reported.
+fn renewal_due(created: u64, expires: u64, now: u64) -> bool {
+    expires.saturating_sub(now) <= expires.saturating_sub(created) / 3
+}
+
+fn retry_delay(failures: u32) -> Duration {
+    let factor = 2u32.saturating_pow(failures.saturating_sub(1).min(16));
+    MIN_RETRY_DELAY.saturating_mul(factor).min(MAX_RETRY_DELAY)
+}
+
+/// Renew the credential of the node from its authority whenever it expires
+/// soon, becomes invalid or is refused, reporting the failures as events.
+pub(super) async fn renew_credential(node_manager: Arc<NodeManager>) {
+    let mut failures = 0;
+    let mut refused = false;
+    let mut renewed_at: Option<Instant> = None;
+    loop {
+        let delay = match failures {
+            0 => CHECK_INTERVAL,
+            n => retry_delay(n),
+        };
+        tokio::select! {
+            _ = sleep(delay) => {}
+            _ = node_manager.credential_refused.notified() => {
+                // A peer refusing a fresh credential is not fixed by a new one
+                if renewed_at.map_or(true, |t| t.elapsed() >= CHECK_INTERVAL) {
+                    refused = true
+                }
+            }
+        }
+        let reason = match node_manager.renewal_reason(refused).await {
+            Some(reason) => reason,
+            None => {
+                failures = 0;
+                continue;
+            }
+        };
+        info!(%reason, "Renewing the credential of the node");
+        match node_manager.get_credential_impl(true).await {
+            Ok(()) => {
+                info!("Renewed the credential of the node");
+                failures = 0;
+                refused = false;
+                renewed_at = Some(Instant::now());
+            }
+            Err(err) => {
+                failures += 1;
+                warn!(%reason, %err, %failures, "Failed to renew the credential of the node");
+                let event = NodeEvent::CredentialRenewalFailed {
+                    reason: reason.to_string(),
+                    error: err.to_string(),
+                    failures,
+                };
+                node_manager.events.emit(event)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credentials_are_renewed_in_the_last_third_of_their_validity() {
+        let day = 24 * 3600;
+        assert!(!renewal_due(0, 30 * day, 0));
+        assert!(!renewal_due(0, 30 * day, 20 * day - 1));
+        assert!(renewal_due(0, 30 * day, 20 * day));
+        assert!(renewal_due(0, 30 * day, 31 * day));
+        // Short-lived credentials too
+        assert!(!renewal_due(100, 400, 299));
+        assert!(renewal_due(100, 400, 300));
+    }
+
+    #[test]
+    fn failed_renewals_are_retried_with_backoff() {
+        assert_eq!(retry_delay(1), MIN_RETRY_DELAY);
+        assert_eq!(retry_delay(2), MIN_RETRY_DELAY * 2);
+        assert_eq!(retry_delay(4), MIN_RETRY_DELAY * 8);
+        assert_eq!(retry_delay(100), MAX_RETRY_DELAY);
+    }
+}
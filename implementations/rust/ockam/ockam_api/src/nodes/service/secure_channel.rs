@@ -7,20 +7,84 @@ use crate::nodes::models::secure_channel::{
     CredentialExchangeMode, DeleteSecureChannelRequest, DeleteSecureChannelResponse,
     ShowSecureChannelRequest, ShowSecureChannelResponse,
 };
+use crate::nodes::registry::SecureChannelRegistry;
+use crate::nodes::service::reconnect::{enable_reconnect, Reconnect};
+use crate::nodes::service::Authorities;
 use crate::nodes::NodeManager;
-use crate::DefaultAddress;
+use crate::relays::RelaySelector;
+use crate::session::{Key, RetryPolicy, Session, Sessions, Status, SECURE_CHANNEL};
+use crate::{multiaddr_to_onion_routes, try_address_to_multiaddr, DefaultAddress};
 use minicbor::Decoder;
 use ockam::identity::TrustEveryonePolicy;
-use ockam::{Address, Result, Route};
+use ockam::{Address, Context, Result, Route, Routed, Worker};
 use ockam_core::api::{Request, Response, ResponseBuilder};
+use ockam_core::compat::sync::{Arc, Mutex};
 use ockam_core::{route, AsyncTryClone};
-use ockam_identity::{Identity, IdentityIdentifier, TrustMultiIdentifiersPolicy};
+use ockam_identity::{
+    Identity, IdentityIdentifier, KeepalivePolicy, KeyAgreement, PreSharedKey, SecureChannelDead,
+    TrustAttributesPolicy, TrustMultiIdentifiersPolicy, TrustPolicy,
+};
 use ockam_multiaddr::MultiAddr;
+use ockam_node::tokio::time::{sleep, Instant};
 use ockam_vault::Vault;
 
+/// Time the re-establishment of a monitored secure channel may take.
+const MAX_RECOVERY_TIME: Duration = Duration::from_secs(10);
+/// Default time the creation of a monitored secure channel may take.
+const MAX_CONNECT_TIME: Duration = Duration::from_secs(5);
+/// How many times per idle timeout a channel is checked for traffic.
+const IDLE_CHECKS: u32 = 4;
+/// Unanswered keepalives after which a secure channel is closed.
+const KEEPALIVE_MISSES: u32 = 3;
+
+/// The route of a secure channel, through onion relays if any.
+#[derive(Clone)]
+pub(crate) struct ChannelRoute {
+    /// Routes to the secure channel listeners of the relays, each from
+    /// the previous relay
+    relays: Vec<Route>,
+    /// Route to the listener of the channel, from the last relay if any
+    route: Route,
+}
+
+impl ChannelRoute {
+    /// Use the secure channel listeners of `addr` as relays, with a channel
+    /// to each over the channel to the previous one, so that each relay
+    /// only learns the next hop.
+    pub(crate) fn onion(addr: &MultiAddr) -> Result<Self> {
+        let mut routes = multiaddr_to_onion_routes(addr)
+            .ok_or_else(|| ApiError::generic("Invalid Multiaddr"))?;
+        let route = routes
+            .pop()
+            .ok_or_else(|| ApiError::generic("Invalid Multiaddr"))?;
+        Ok(Self {
+            relays: routes,
+            route,
+        })
+    }
+
+    /// The whole route of a channel, through its relays.
+    fn join(relays: &[Route], route: &Route) -> Route {
+        let mut joined = route.clone();
+        for relay in relays.iter().rev() {
+            joined.modify().prepend_route(relay.clone());
+        }
+        joined
+    }
+}
+
+impl From<Route> for ChannelRoute {
+    fn from(route: Route) -> Self {
+        Self {
+            relays: Vec::new(),
+            route,
+        }
+    }
+}
+
 impl NodeManager {
-    async fn get_credential_if_needed(&mut self) -> Result<()> {
-        let identity = self.identity()?;
+    async fn get_credential_if_needed(&self) -> Result<()> {
+        let identity = self.identity().await?;
 
         if identity.credential().await.is_some() {
             debug!("Credential check: credential already exists...");
@@ -35,40 +99,61 @@ impl NodeManager {
     }
 
     pub(crate) async fn create_secure_channel_internal(
-        &mut self,
+        &self,
         identity: &Identity<Vault>,
-        sc_route: Route,
+        sc_route: impl Into<ChannelRoute>,
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
         timeout: Option<Duration>,
+        replaces: Option<&Address>,
+        key_agreement: KeyAgreement,
     ) -> Result<Address> {
-        // If channel was already created, do nothing.
-        if let Some(channel) = self.registry.secure_channels.get_by_route(&sc_route) {
-            let addr = channel.addr();
+        let ChannelRoute { relays, route } = sc_route.into();
+        let sc_route = ChannelRoute::join(&relays, &route);
+        // If channel was already created, do nothing, unless it is the one being replaced
+        // or its keys are not agreed on as requested. Onion channels are always new.
+        let onion = !relays.is_empty();
+        let cached = |channels: &SecureChannelRegistry| {
+            channels
+                .get_by_route(&sc_route)
+                .filter(|_| !onion)
+                .filter(|c| Some(c.addr()) != replaces)
+                .filter(|c| {
+                    c.key_agreement() == KeyAgreement::Hybrid
+                        || key_agreement == KeyAgreement::Classical
+                })
+                .map(|c| c.addr().clone())
+        };
+        if let Some(addr) = cached(&*self.registry.secure_channels.read().await) {
             debug!(%addr, "Using cached secure channel");
-            return Ok(addr.clone());
+            return Ok(addr);
         }
         // Else, create it.
 
         debug!(%sc_route, "Creating secure channel");
         let timeout = timeout.unwrap_or(Duration::from_secs(120));
+        // Without relays, this creates a regular secure channel
         let sc_addr = match authorized_identifiers.clone() {
             Some(ids) => {
                 identity
-                    .create_secure_channel_extended(
-                        sc_route.clone(),
+                    .create_onion_secure_channel(
+                        relays,
+                        route,
                         TrustMultiIdentifiersPolicy::new(ids),
                         &self.authenticated_storage,
                         timeout,
+                        key_agreement,
                     )
                     .await
             }
             None => {
                 identity
-                    .create_secure_channel_extended(
-                        sc_route.clone(),
+                    .create_onion_secure_channel(
+                        relays,
+                        route,
                         TrustEveryonePolicy,
                         &self.authenticated_storage,
                         timeout,
+                        key_agreement,
                     )
                     .await
             }
@@ -76,24 +161,91 @@ impl NodeManager {
 
         debug!(%sc_route, %sc_addr, "Created secure channel");
 
-        self.registry
-            .secure_channels
-            .insert(sc_addr.clone(), sc_route, authorized_identifiers);
+        // Another request may have created a channel to the same route meanwhile
+        let mut channels = self.registry.secure_channels.write().await;
+        if let Some(addr) = cached(&channels) {
+            drop(channels);
+            debug!(%addr, "Using the secure channel created concurrently");
+            identity.stop_secure_channel(&sc_addr).await?;
+            return Ok(addr);
+        }
+        channels.insert(
+            sc_addr.clone(),
+            sc_route,
+            authorized_identifiers,
+            key_agreement,
+        );
 
         Ok(sc_addr)
     }
 
+    /// Create a secure channel and present credentials over it, those of
+    /// `authorities` if it has its own trust context, retrying with `retry`
+    /// when it fails. `timeout` bounds every attempt.
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn create_secure_channel_impl(
-        &mut self,
-        sc_route: Route,
+        &self,
+        sc_route: impl Into<ChannelRoute>,
+        authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+        credential_exchange_mode: CredentialExchangeMode,
+        timeout: Option<Duration>,
+        replaces: Option<&Address>,
+        key_agreement: KeyAgreement,
+        authorities: Option<&Authorities>,
+        retry: Option<&RetryPolicy>,
+    ) -> Result<Address> {
+        let sc_route = sc_route.into();
+        let mut failures = 0;
+        loop {
+            let result = self
+                .create_secure_channel_attempt(
+                    sc_route.clone(),
+                    authorized_identifiers.clone(),
+                    credential_exchange_mode,
+                    timeout,
+                    replaces,
+                    key_agreement,
+                    authorities,
+                )
+                .await;
+            let err = match result {
+                Ok(addr) => return Ok(addr),
+                Err(err) => err,
+            };
+            failures += 1;
+            match retry {
+                Some(policy) if !policy.gives_up_after(failures) => {
+                    let delay = policy.delay(failures);
+                    warn!(%err, %failures, ?delay, "Failed to create secure channel, retrying");
+                    sleep(delay).await
+                }
+                _ => return Err(err),
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn create_secure_channel_attempt(
+        &self,
+        sc_route: ChannelRoute,
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
         credential_exchange_mode: CredentialExchangeMode,
         timeout: Option<Duration>,
+        replaces: Option<&Address>,
+        key_agreement: KeyAgreement,
+        authorities: Option<&Authorities>,
     ) -> Result<Address> {
-        let identity = self.identity()?.async_try_clone().await?;
+        let identity = self.identity().await?;
 
         let sc_addr = self
-            .create_secure_channel_internal(&identity, sc_route, authorized_identifiers, timeout)
+            .create_secure_channel_internal(
+                &identity,
+                sc_route,
+                authorized_identifiers,
+                timeout,
+                replaces,
+                key_agreement,
+            )
             .await?;
 
         let actual_exchange_mode = if self.enable_credential_checks {
@@ -108,43 +260,141 @@ impl NodeManager {
             }
             CredentialExchangeMode::Oneway => {
                 debug!(%sc_addr, "One-way credential presentation");
-                self.get_credential_if_needed().await?;
-                identity
-                    .present_credential(route![sc_addr.clone(), DefaultAddress::CREDENTIAL_SERVICE])
-                    .await?;
+                let route = route![sc_addr.clone(), DefaultAddress::CREDENTIAL_SERVICE];
+                match authorities {
+                    Some(authorities) => {
+                        let credential = self.credential_for(authorities).await?;
+                        identity.present_credential_with(route, &credential).await?
+                    }
+                    None => {
+                        self.get_credential_if_needed().await?;
+                        identity.present_credential(route).await.map_err(|err| {
+                            self.check_presentation(&err);
+                            err
+                        })?
+                    }
+                }
                 debug!(%sc_addr, "One-way credential presentation success");
             }
             CredentialExchangeMode::Mutual => {
                 debug!(%sc_addr, "Mutual credential presentation");
-                self.get_credential_if_needed().await?;
-                let authorities = self.authorities()?;
-                identity
-                    .present_credential_mutual(
-                        route![sc_addr.clone(), DefaultAddress::CREDENTIAL_SERVICE],
-                        &authorities.public_identities(),
-                        &self.authenticated_storage,
-                    )
-                    .await?;
+                let route = route![sc_addr.clone(), DefaultAddress::CREDENTIAL_SERVICE];
+                match authorities {
+                    Some(authorities) => {
+                        let credential = self.credential_for(authorities).await?;
+                        identity
+                            .present_credential_mutual_with(
+                                route,
+                                &credential,
+                                &authorities.public_identities(),
+                                &self.authenticated_storage,
+                            )
+                            .await?
+                    }
+                    None => {
+                        self.get_credential_if_needed().await?;
+                        let authorities = self.authorities()?;
+                        identity
+                            .present_credential_mutual(
+                                route,
+                                &authorities.public_identities(),
+                                &self.authenticated_storage,
+                            )
+                            .await
+                            .map_err(|err| {
+                                self.check_presentation(&err);
+                                err
+                            })?
+                    }
+                }
                 debug!(%sc_addr, "Mutual credential presentation success");
             }
         }
+        self.registry
+            .secure_channels
+            .write()
+            .await
+            .set_credential_exchange_mode(&sc_addr, actual_exchange_mode);
 
         // Return secure channel address
         Ok(sc_addr)
     }
 
+    /// Create a secure channel authenticated with a pre-shared key. It is
+    /// always a new one, and no credentials are presented over it.
+    pub(super) async fn create_psk_secure_channel_impl(
+        &self,
+        sc_route: Route,
+        psk: PreSharedKey,
+        authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+        timeout: Option<Duration>,
+    ) -> Result<Address> {
+        let identity = self.identity().await?;
+        debug!(%sc_route, psk = %psk.id(), "Creating secure channel with a pre-shared key");
+        let timeout = timeout.unwrap_or(Duration::from_secs(120));
+        let sc_addr = match authorized_identifiers.clone() {
+            Some(ids) => {
+                identity
+                    .create_psk_secure_channel(
+                        sc_route.clone(),
+                        psk,
+                        TrustMultiIdentifiersPolicy::new(ids),
+                        &self.authenticated_storage,
+                        timeout,
+                    )
+                    .await
+            }
+            None => {
+                identity
+                    .create_psk_secure_channel(
+                        sc_route.clone(),
+                        psk,
+                        TrustEveryonePolicy,
+                        &self.authenticated_storage,
+                        timeout,
+                    )
+                    .await
+            }
+        }?;
+        debug!(%sc_route, %sc_addr, "Created secure channel");
+
+        let mut registry = self.registry.secure_channels.write().await;
+        registry.insert(
+            sc_addr.clone(),
+            sc_route,
+            authorized_identifiers,
+            KeyAgreement::Classical,
+        );
+        registry.set_credential_exchange_mode(&sc_addr, CredentialExchangeMode::None);
+        Ok(sc_addr)
+    }
+
     pub(super) async fn create_secure_channel<'a>(
-        &mut self,
+        &self,
+        ctx: &Context,
+        this: &Address,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder<CreateSecureChannelResponse<'a>>> {
+        let body: CreateSecureChannelRequest = dec.decode()?;
+        let monitor = body.monitor();
+        let key_agreement = body.key_agreement();
+        let retry = body.retry_policy();
         let CreateSecureChannelRequest {
             addr,
             authorized_identifiers,
             credential_exchange_mode,
             timeout,
+            replaces,
+            idle_timeout,
+            key_agreement: key_agreement_mode,
+            keepalive,
+            measure_rtt,
+            onion,
+            psk,
+            authorities: authority_args,
             ..
-        } = dec.decode()?;
+        } = body;
 
         info!("Handling request to create a new secure channel: {}", addr);
 
@@ -162,17 +412,121 @@ impl NodeManager {
 
         // TODO: Improve error handling + move logic into CreateSecureChannelRequest
         let addr = MultiAddr::try_from(addr.as_ref()).map_err(map_multiaddr_err)?;
-        let route = crate::multiaddr_to_route(&addr)
-            .ok_or_else(|| ApiError::generic("Invalid Multiaddr"))?;
+        let route = if onion == Some(true) {
+            ChannelRoute::onion(&addr)?
+        } else {
+            crate::multiaddr_to_route(&addr)
+                .ok_or_else(|| ApiError::generic("Invalid Multiaddr"))?
+                .into()
+        };
+        let replaces = replaces.map(|a| Address::from(a.as_ref()));
+
+        // The channel has its own trust context, whose authorities the peer
+        // may present credentials of over it too
+        let authorities = match &authority_args {
+            Some(args) => {
+                let authorities = self.import_authorities(args).await?;
+                let identity = self.identity().await?;
+                identity
+                    .add_trusted_authorities(authorities.public_identities())
+                    .await;
+                Some(authorities)
+            }
+            None => None,
+        };
 
-        let channel = self
-            .create_secure_channel_impl(
-                route,
-                authorized_identifiers,
-                credential_exchange_mode,
-                timeout,
-            )
-            .await?;
+        let channel = match psk {
+            Some(psk) => {
+                // Recovering or relaying the channel would need the key again
+                if monitor || onion == Some(true) {
+                    return Err(ApiError::generic(
+                        "A secure channel with a pre-shared key can't be monitored or onion routed",
+                    ));
+                }
+                if authorities.is_some() {
+                    return Err(ApiError::generic(
+                        "A secure channel with a pre-shared key presents no credentials",
+                    ));
+                }
+                let ChannelRoute { route, .. } = route;
+                self.create_psk_secure_channel_impl(
+                    route,
+                    psk.to_psk()?,
+                    authorized_identifiers.clone(),
+                    timeout.or_else(|| req.timeout()),
+                )
+                .await?
+            }
+            None => {
+                self.create_secure_channel_impl(
+                    route,
+                    authorized_identifiers.clone(),
+                    credential_exchange_mode,
+                    timeout.or_else(|| req.timeout()),
+                    replaces.as_ref(),
+                    key_agreement,
+                    authorities.as_ref(),
+                    retry.as_ref(),
+                )
+                .await?
+            }
+        };
+
+        if monitor {
+            let r = Reconnect {
+                manager: this.clone(),
+                ctx: Arc::new(ctx.async_try_clone().await?),
+                addr: addr.clone(),
+                cloud: None,
+                auth: authorized_identifiers,
+                mode: credential_exchange_mode,
+                relays: RelaySelector::new(),
+                connect_timeout: timeout.unwrap_or(MAX_CONNECT_TIME),
+                key_agreement: key_agreement_mode,
+                keepalive,
+                measure_rtt,
+                onion,
+                authorities: authority_args.map(|a| a.iter().map(|a| a.to_owned()).collect()),
+            };
+            let mut s = Session::new(try_address_to_multiaddr(&channel)?);
+            s.set_description(format!("secure channel to {addr}"));
+            s.put(SECURE_CHANNEL, addr);
+            enable_reconnect(&mut s, r, MAX_RECOVERY_TIME);
+            let key = self.sessions.lock().unwrap().add(s);
+            info!(%channel, session = %key, "Monitoring secure channel");
+        }
+
+        if let Some(t) = keepalive {
+            let identity = self.identity().await?;
+            let mut policy = KeepalivePolicy::new(t, KEEPALIVE_MISSES);
+            if measure_rtt == Some(true) {
+                policy = policy.with_rtt()
+            }
+            identity
+                .start_secure_channel_keepalive(&channel, policy)
+                .await?;
+            // Sessions over a dead channel are recovered right away
+            let watcher = Address::random_local();
+            let worker = RecoverDeadChannels {
+                sessions: self.sessions.clone(),
+            };
+            ctx.start_worker(watcher.clone(), worker).await?;
+            identity.watch_secure_channel(&channel, watcher).await?;
+            info!(%channel, interval = ?t, "Keeping secure channel alive");
+        }
+
+        if let Some(t) = idle_timeout {
+            let c = ctx.async_try_clone().await?;
+            let identity = self.identity().await?;
+            info!(%channel, idle_timeout = ?t, "Closing secure channel when idle");
+            ockam_node::tokio::spawn(close_when_idle(
+                c,
+                this.clone(),
+                identity,
+                channel.clone(),
+                t,
+            ));
+        }
 
         let response = Response::ok(req.id()).body(CreateSecureChannelResponse::new(&channel));
 
@@ -180,7 +534,7 @@ impl NodeManager {
     }
 
     pub(super) async fn delete_secure_channel<'a>(
-        &mut self,
+        &self,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder<DeleteSecureChannelResponse<'a>>> {
@@ -191,18 +545,10 @@ impl NodeManager {
             body.channel
         );
 
-        let identity = self.identity()?;
-
         let sc_address = Address::from(body.channel.as_ref());
 
-        debug!(%sc_address, "Deleting secure channel");
-
-        let res = match identity.stop_secure_channel(&sc_address).await {
-            Ok(()) => {
-                trace!(%sc_address, "Removed secure channel");
-                self.registry.secure_channels.remove_by_addr(&sc_address);
-                Some(sc_address)
-            }
+        let res = match self.delete_secure_channel_impl(&sc_address).await {
+            Ok(()) => Some(sc_address),
             Err(err) => {
                 trace!(%sc_address, "Error removing secure channel: {err}");
                 None
@@ -212,22 +558,68 @@ impl NodeManager {
         Ok(Response::ok(req.id()).body(DeleteSecureChannelResponse::new(res)))
     }
 
-    pub(super) fn list_secure_channels(
-        &mut self,
+    pub(super) async fn delete_secure_channel_impl(&self, sc_address: &Address) -> Result<()> {
+        debug!(%sc_address, "Deleting secure channel");
+        self.stop_monitoring(sc_address);
+        self.identity()
+            .await?
+            .stop_secure_channel(sc_address)
+            .await?;
+        trace!(%sc_address, "Removed secure channel");
+        self.registry
+            .secure_channels
+            .write()
+            .await
+            .remove_by_addr(sc_address);
+        Ok(())
+    }
+
+    /// Stop monitoring the channel, unless it is deleted because it was
+    /// re-established.
+    fn stop_monitoring(&self, sc_address: &Address) {
+        let addr = match try_address_to_multiaddr(sc_address) {
+            Ok(a) => a,
+            Err(_) => return,
+        };
+        let mut sessions = self.sessions.lock().unwrap();
+        let keys: Vec<Key> = sessions
+            .iter()
+            .filter(|(_, s)| s.get::<MultiAddr>(SECURE_CHANNEL).is_some())
+            .filter(|(_, s)| s.address() == &addr)
+            .filter(|(_, s)| s.status() == Status::Up && !s.is_rotating())
+            .map(|(k, _)| *k)
+            .collect();
+        for k in keys {
+            debug!(%sc_address, session = %k, "Stop monitoring secure channel");
+            sessions.remove(&k);
+        }
+    }
+
+    /// The identity authenticated on the other end of the secure channel
+    /// `route` starts with, `None` if it does not start with one of ours.
+    pub(crate) async fn peer_identity(&self, route: &Route) -> Option<IdentityIdentifier> {
+        let channel = route.next().ok()?;
+        let identity = self.identity().await.ok()?;
+        let stats = identity.secure_channel_stats(channel).await?;
+        Some(stats.their_identity_id)
+    }
+
+    pub(super) async fn list_secure_channels(
+        &self,
         req: &Request<'_>,
-    ) -> ResponseBuilder<Vec<String>> {
-        Response::ok(req.id()).body(
-            self.registry
-                .secure_channels
-                .list()
-                .iter()
-                .map(|v| v.addr().to_string())
-                .collect(),
-        )
+    ) -> Result<ResponseBuilder<Vec<ShowSecureChannelResponse<'static>>>> {
+        let identity = self.identity().await?;
+        let channels = self.registry.secure_channels.read().await.list().to_vec();
+        let mut list = Vec::with_capacity(channels.len());
+        for info in &channels {
+            let stats = identity.secure_channel_stats(info.addr()).await;
+            list.push(ShowSecureChannelResponse::new(Some(info)).with_stats(stats));
+        }
+        Ok(Response::ok(req.id()).body(list))
     }
 
     pub(super) async fn show_secure_channel<'a>(
-        &mut self,
+        &self,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder<ShowSecureChannelResponse<'a>>> {
@@ -237,61 +629,120 @@ impl NodeManager {
 
         debug!(%sc_address, "On show secure channel");
 
-        let info = self.registry.secure_channels.get_by_addr(&sc_address);
+        let info = self
+            .registry
+            .secure_channels
+            .read()
+            .await
+            .get_by_addr(&sc_address)
+            .cloned();
+        let stats = match info {
+            Some(_) => {
+                self.identity()
+                    .await?
+                    .secure_channel_stats(&sc_address)
+                    .await
+            }
+            None => None,
+        };
 
-        Ok(Response::ok(req.id()).body(ShowSecureChannelResponse::new(info)))
+        Ok(Response::ok(req.id())
+            .body(ShowSecureChannelResponse::new(info.as_ref()).with_stats(stats)))
     }
 
     pub(super) async fn create_secure_channel_listener_impl(
-        &mut self,
+        &self,
         addr: Address,
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+        required_attributes: Option<Vec<(String, Vec<u8>)>>,
+        key_agreement: KeyAgreement,
+        psk: Option<PreSharedKey>,
+        authorities: Option<Authorities>,
     ) -> Result<()> {
         info!(
             "Handling request to create a new secure channel listener: {}",
             addr
         );
 
-        let identity = self.identity()?;
+        let identity = self.identity().await?;
 
-        match authorized_identifiers {
-            Some(ids) => {
+        let mut trust_policy: Arc<dyn TrustPolicy> = match authorized_identifiers {
+            Some(ids) => Arc::new(TrustMultiIdentifiersPolicy::new(ids)),
+            None => Arc::new(TrustEveryonePolicy),
+        };
+        if let Some(attributes) = required_attributes {
+            let attributes_policy =
+                TrustAttributesPolicy::new(&attributes, self.authenticated_storage.clone());
+            trust_policy = Arc::new(trust_policy.and(attributes_policy));
+        }
+
+        match (authorities, psk) {
+            (Some(authorities), psk) => {
+                // The credential presented back to the initiators, if the
+                // node can get one
+                if let Err(err) = self.credential_for(&authorities).await {
+                    warn!(%addr, %err, "No credential of the trust context of the listener");
+                }
                 identity
-                    .create_secure_channel_listener(
+                    .create_trust_context_secure_channel_listener(
                         addr.clone(),
-                        TrustMultiIdentifiersPolicy::new(ids),
+                        authorities.public_identities(),
+                        psk,
+                        key_agreement,
+                        trust_policy,
                         &self.authenticated_storage,
                     )
-                    .await
+                    .await?
             }
-            None => {
+            (None, Some(psk)) => {
                 identity
-                    .create_secure_channel_listener(
+                    .create_psk_secure_channel_listener(
                         addr.clone(),
-                        TrustEveryonePolicy,
+                        psk,
+                        trust_policy,
                         &self.authenticated_storage,
                     )
-                    .await
+                    .await?
             }
-        }?;
+            (None, None) => {
+                identity
+                    .create_secure_channel_listener_extended(
+                        addr.clone(),
+                        trust_policy,
+                        &self.authenticated_storage,
+                        key_agreement,
+                    )
+                    .await?
+            }
+        }
 
         self.registry
             .secure_channel_listeners
+            .write()
+            .await
             .insert(addr, Default::default());
 
         Ok(())
     }
 
     pub(super) async fn create_secure_channel_listener(
-        &mut self,
+        &self,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder<()>> {
+        let body: CreateSecureChannelListenerRequest = dec.decode()?;
+        let key_agreement = body.key_agreement();
+        let required_attributes = body.required_attributes();
+        let psk = body.psk.as_ref().map(|p| p.to_psk()).transpose()?;
+        let authorities = match &body.authorities {
+            Some(args) => Some(self.import_authorities(args).await?),
+            None => None,
+        };
         let CreateSecureChannelListenerRequest {
             addr,
             authorized_identifiers,
             ..
-        } = dec.decode()?;
+        } = body;
 
         let authorized_identifiers = match authorized_identifiers {
             Some(ids) => {
@@ -310,24 +761,180 @@ impl NodeManager {
             return Ok(Response::bad_request(req.id()));
         }
 
-        self.create_secure_channel_listener_impl(addr, authorized_identifiers)
-            .await?;
+        self.create_secure_channel_listener_impl(
+            addr,
+            authorized_identifiers,
+            required_attributes,
+            key_agreement,
+            psk,
+            authorities,
+        )
+        .await?;
 
         let response = Response::ok(req.id());
 
         Ok(response)
     }
 
-    pub(super) fn list_secure_channel_listener(
-        &mut self,
+    pub(super) async fn list_secure_channel_listener(
+        &self,
         req: &Request<'_>,
     ) -> ResponseBuilder<Vec<String>> {
         Response::ok(req.id()).body(
             self.registry
                 .secure_channel_listeners
+                .read()
+                .await
                 .iter()
                 .map(|(addr, _)| addr.to_string())
                 .collect(),
         )
     }
 }
+
+/// Delete the secure channel once it carried no messages for `idle_timeout`,
+/// or stop watching it when it is deleted otherwise.
+async fn close_when_idle(
+    ctx: Context,
+    manager: Address,
+    identity: Arc<Identity<Vault>>,
+    channel: Address,
+    idle_timeout: Duration,
+) {
+    let mut messages = identity.secure_channel_messages(&channel).await;
+    let mut idle_since = Instant::now();
+    while idle_since.elapsed() < idle_timeout {
+        sleep(idle_timeout / IDLE_CHECKS).await;
+        let current = identity.secure_channel_messages(&channel).await;
+        if current.is_none() {
+            return;
+        }
+        if current != messages {
+            messages = current;
+            idle_since = Instant::now();
+        }
+    }
+    info!(%channel, "Secure channel idle, closing it");
+    let req = match Request::delete("/node/secure_channel")
+        .body(DeleteSecureChannelRequest::new(&channel))
+        .to_vec()
+    {
+        Ok(req) => req,
+        Err(e) => return warn!(%channel, err = %e, "failed to encode request"),
+    };
+    if let Err(e) = ctx.send_and_receive::<_, _, Vec<u8>>(manager, req).await {
+        warn!(%channel, err = %e, "failed to delete idle secure channel")
+    }
+}
+
+/// Requests the recovery of the session of a secure channel found dead by
+/// its keepalive, then stops.
+struct RecoverDeadChannels {
+    sessions: Arc<Mutex<Sessions>>,
+}
+
+#[ockam_core::worker]
+impl Worker for RecoverDeadChannels {
+    type Message = SecureChannelDead;
+    type Context = Context;
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Context,
+        msg: Routed<SecureChannelDead>,
+    ) -> Result<()> {
+        let channel = msg.body().channel().clone();
+        warn!(%channel, "Secure channel stopped answering keepalives");
+        if let Ok(addr) = try_address_to_multiaddr(&channel) {
+            let mut sessions = self.sessions.lock().unwrap();
+            if let Some((_, s)) = sessions.iter_mut().find(|(_, s)| s.address() == &addr) {
+                s.request_recovery();
+            };
+        }
+        ctx.stop_worker(ctx.address()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::models::identity::ShortIdentityResponse;
+    use ockam_core::api::Status;
+
+    async fn list_secure_channels(ctx: &mut Context, node_manager: &Route) -> Result<Vec<String>> {
+        let req = Request::get("/node/secure_channel");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let list: Vec<ShowSecureChannelResponse> = dec.decode()?;
+        Ok(list
+            .into_iter()
+            .filter_map(|c| c.channel.map(|c| c.to_string()))
+            .collect())
+    }
+
+    #[ockam_macros::test]
+    async fn idle_secure_channels_are_closed(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+
+        let body = CreateSecureChannelListenerRequest::new(&"listener".into(), None);
+        let req = Request::post("/node/secure_channel_listener").body(body);
+        ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+
+        let body = CreateSecureChannelRequest::new(
+            &"/service/listener".parse().unwrap(),
+            None,
+            CredentialExchangeMode::None,
+        )
+        .with_idle_timeout(Duration::from_secs(1));
+        let req = Request::post("/node/secure_channel").body(body);
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let channel: CreateSecureChannelResponse = dec.decode()?;
+        let channel = Address::from(channel.addr.as_ref());
+
+        // Traffic keeps the channel open past its idle timeout.
+        for _ in 0..6 {
+            ctx.send(route![channel.clone(), ctx.address()], "ping".to_string())
+                .await?;
+            ctx.receive::<String>().await?;
+            sleep(Duration::from_millis(300)).await;
+        }
+        assert_eq!(
+            list_secure_channels(ctx, &node_manager).await?,
+            vec![channel.to_string()]
+        );
+
+        // The channel is listed with its peer and traffic.
+        let req = Request::post("/node/identity/actions/show/short");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let identity: ShortIdentityResponse = dec.decode()?;
+        let req = Request::get("/node/secure_channel");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let list: Vec<ShowSecureChannelResponse> = dec.decode()?;
+        let info = &list[0];
+        assert_eq!(
+            info.their_identity_id.as_deref(),
+            Some(identity.identity_id.as_ref())
+        );
+        assert_eq!((info.sent, info.received), (Some(6), Some(0)));
+        assert_eq!(
+            info.credential_exchange_mode,
+            Some(CredentialExchangeMode::None)
+        );
+        assert!(info.created_at.is_some());
+        // The channel does not go over other secure channels.
+        assert_eq!(info.hops.as_ref().map(|h| h.len()), Some(0));
+
+        sleep(Duration::from_millis(1500)).await;
+        assert!(list_secure_channels(ctx, &node_manager).await?.is_empty());
+
+        ctx.stop().await
+    }
+}
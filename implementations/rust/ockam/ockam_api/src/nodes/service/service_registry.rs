@@ -0,0 +1,97 @@
+//! Registry of the optional services making up the node manager API
+
+use minicbor::Decoder;
+
+use ockam::{Address, Context, Result};
+use ockam_core::api::Request;
+use ockam_core::async_trait;
+use ockam_core::compat::{boxed::Box, string::String};
+
+use crate::nodes::service::progress::Progress;
+use crate::nodes::NodeManager;
+
+/// A part of the node manager API with its own routes and state.
+///
+/// Services have access to the resources shared by the whole node (vault,
+/// identity, secure channels, transports) through the [`NodeManager`].
+#[async_trait]
+pub(crate) trait NodeService: Send + Sync + 'static {
+    /// Handle the request if its method and path belong to this service.
+    ///
+    /// Returns `Ok(None)` if the request is not served by this service.
+    async fn handle_request(
+        &self,
+        node: &NodeManager,
+        ctx: &mut Context,
+        this: &Address,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        progress: &Progress,
+    ) -> Result<Option<Vec<u8>>>;
+
+    /// Start the service once the node manager at `this` handles requests,
+    /// e.g. to restore the state it persisted in the node directory.
+    async fn start(&self, _node: &NodeManager, _ctx: &Context, _this: &Address) -> Result<()> {
+        Ok(())
+    }
+
+    /// Reasons why the node should not receive traffic yet, e.g. state
+    /// which is not restored, empty when the service is ready.
+    async fn unready(&self, _node: &NodeManager) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// The services registered with a node manager.
+///
+/// Requests not handled by the node manager itself are offered to every
+/// service in registration order, until one of them handles it.
+#[derive(Default)]
+pub(crate) struct ServiceRegistry {
+    services: Vec<Box<dyn NodeService>>,
+}
+
+impl ServiceRegistry {
+    pub(crate) fn register<S: NodeService>(&mut self, service: S) -> &mut Self {
+        self.services.push(Box::new(service));
+        self
+    }
+
+    pub(crate) async fn handle_request(
+        &self,
+        node: &NodeManager,
+        ctx: &mut Context,
+        this: &Address,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        progress: &Progress,
+    ) -> Result<Option<Vec<u8>>> {
+        for service in &self.services {
+            let r = service
+                .handle_request(node, ctx, this, req, dec, progress)
+                .await?;
+            if r.is_some() {
+                return Ok(r);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Start all services, in registration order.
+    pub(crate) async fn start(&self, node: &NodeManager, ctx: &Context, this: &Address) {
+        for service in &self.services {
+            if let Err(err) = service.start(node, ctx, this).await {
+                error!(%err, "failed to start node manager service")
+            }
+        }
+    }
+
+    /// Reasons why the services are not ready, in registration order.
+    pub(crate) async fn unready(&self, node: &NodeManager) -> Vec<String> {
+        let mut reasons = Vec::new();
+        for service in &self.services {
+            reasons.extend(service.unready(node).await)
+        }
+        reasons
+    }
+}
@@ -4,10 +4,10 @@ use crate::error::ApiError;
 use crate::identity::IdentityService;
 use crate::nodes::models::services::{
     StartAuthenticatedServiceRequest, StartAuthenticatorRequest, StartCredentialsService,
-    StartEchoerServiceRequest, StartIdentityServiceRequest, StartUppercaseServiceRequest,
-    StartVaultServiceRequest, StartVerifierService,
+    StartDelegationService, StartEchoerServiceRequest, StartIdentityServiceRequest,
+    StartUppercaseServiceRequest, StartVaultServiceRequest, StartVerifierService,
 };
-use crate::nodes::registry::{CredentialsServiceInfo, VerifierServiceInfo};
+use crate::nodes::registry::{CredentialsServiceInfo, DelegationServiceInfo, VerifierServiceInfo};
 use crate::nodes::NodeManager;
 use crate::uppercase::Uppercase;
 use crate::vault::VaultService;
@@ -17,28 +17,29 @@ use ockam_core::api::{Request, Response, ResponseBuilder};
 
 impl NodeManager {
     pub(super) async fn start_vault_service_impl(
-        &mut self,
+        &self,
         ctx: &Context,
         addr: Address,
     ) -> Result<()> {
-        if self.registry.vault_services.contains_key(&addr) {
+        // Hold the lock until the service is registered, so that concurrent
+        // requests don't start two services at the same address
+        let mut services = self.registry.vault_services.write().await;
+        if services.contains_key(&addr) {
             return Err(ApiError::generic("Vault service at this address exists"));
         }
 
-        let vault = self.vault()?.async_try_clone().await?;
+        let vault = self.vault().await?.async_try_clone().await?;
         let service = VaultService::new(vault);
 
         ctx.start_worker(addr.clone(), service).await?;
 
-        self.registry
-            .vault_services
-            .insert(addr, Default::default());
+        services.insert(addr, Default::default());
 
         Ok(())
     }
 
     pub(super) async fn start_vault_service(
-        &mut self,
+        &self,
         ctx: &Context,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
@@ -56,26 +57,25 @@ impl NodeManager {
     }
 
     pub(super) async fn start_identity_service_impl(
-        &mut self,
+        &self,
         ctx: &Context,
         addr: Address,
     ) -> Result<()> {
-        if self.registry.identity_services.contains_key(&addr) {
+        let mut services = self.registry.identity_services.write().await;
+        if services.contains_key(&addr) {
             return Err(ApiError::generic("Identity service at this address exists"));
         }
 
-        let vault = self.vault()?.async_try_clone().await?;
+        let vault = self.vault().await?.async_try_clone().await?;
         IdentityService::create(ctx, addr.clone(), vault).await?;
 
-        self.registry
-            .identity_services
-            .insert(addr, Default::default());
+        services.insert(addr, Default::default());
 
         Ok(())
     }
 
     pub(super) async fn start_identity_service(
-        &mut self,
+        &self,
         ctx: &Context,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
@@ -93,7 +93,7 @@ impl NodeManager {
     }
 
     pub(super) async fn start_verifier_service<'a>(
-        &mut self,
+        &self,
         ctx: &Context,
         req: &'a Request<'_>,
         dec: &mut Decoder<'_>,
@@ -101,38 +101,69 @@ impl NodeManager {
         let body: StartVerifierService = dec.decode()?;
         let addr: Address = body.address().into();
 
-        if self.registry.verifier_services.contains_key(&addr) {
+        let mut services = self.registry.verifier_services.write().await;
+        if services.contains_key(&addr) {
             return Err(ApiError::generic("verifier exists at this address"));
         }
 
-        let vault = if let Some(v) = &self.vault {
-            v.async_try_clone().await?
-        } else {
-            return Err(ApiError::generic("vault not found"));
+        let vault = match self.vault.read().await.as_ref() {
+            Some(v) => v.async_try_clone().await?,
+            None => return Err(ApiError::generic("vault not found")),
         };
 
         let vs = crate::verifier::Verifier::new(vault);
         ctx.start_worker(addr.clone(), vs).await?;
 
-        self.registry
-            .verifier_services
-            .insert(addr, VerifierServiceInfo::default());
+        services.insert(addr, VerifierServiceInfo::default());
+
+        Ok(Response::ok(req.id()))
+    }
+
+    pub(super) async fn start_delegation_service<'a>(
+        &self,
+        ctx: &Context,
+        this: &Address,
+        req: &'a Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder> {
+        let body: StartDelegationService = dec.decode()?;
+        let addr: Address = body.address().into();
+
+        let mut services = self.registry.delegation_services.write().await;
+        if services.contains_key(&addr) {
+            return Err(ApiError::generic(
+                "delegation service exists at this address",
+            ));
+        }
+
+        let mut ds = crate::delegation::Delegation::new(
+            body.authorized().to_vec(),
+            this.clone(),
+            self.authenticated_storage.clone(),
+        );
+        if body.capabilities() {
+            ds = ds.with_capabilities();
+        }
+        ctx.start_worker(addr.clone(), ds).await?;
+
+        services.insert(addr, DelegationServiceInfo::default());
 
         Ok(Response::ok(req.id()))
     }
 
     pub(super) async fn start_credentials_service_impl<'a>(
-        &mut self,
+        &self,
         addr: Address,
         oneway: bool,
     ) -> Result<()> {
-        if self.registry.credentials_services.contains_key(&addr) {
+        let mut services = self.registry.credentials_services.write().await;
+        if services.contains_key(&addr) {
             return Err(ApiError::generic(
                 "credentials service exists at this address",
             ));
         }
 
-        let identity = self.identity()?;
+        let identity = self.identity().await?;
 
         let authorities = self.authorities()?;
 
@@ -145,15 +176,13 @@ impl NodeManager {
             )
             .await?;
 
-        self.registry
-            .credentials_services
-            .insert(addr, CredentialsServiceInfo::default());
+        services.insert(addr, CredentialsServiceInfo::default());
 
         Ok(())
     }
 
     pub(super) async fn start_credentials_service<'a>(
-        &mut self,
+        &self,
         _ctx: &Context,
         req: &'a Request<'_>,
         dec: &mut Decoder<'_>,
@@ -168,11 +197,12 @@ impl NodeManager {
     }
 
     pub(super) async fn start_authenticated_service_impl(
-        &mut self,
+        &self,
         ctx: &Context,
         addr: Address,
     ) -> Result<()> {
-        if self.registry.authenticated_services.contains_key(&addr) {
+        let mut services = self.registry.authenticated_services.write().await;
+        if services.contains_key(&addr) {
             return Err(ApiError::generic(
                 "Authenticated service at this address exists",
             ));
@@ -182,15 +212,13 @@ impl NodeManager {
         let server = Server::new(s);
         ctx.start_worker(addr.clone(), server).await?;
 
-        self.registry
-            .authenticated_services
-            .insert(addr, Default::default());
+        services.insert(addr, Default::default());
 
         Ok(())
     }
 
     pub(super) async fn start_authenticated_service(
-        &mut self,
+        &self,
         ctx: &Context,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
@@ -208,11 +236,12 @@ impl NodeManager {
     }
 
     pub(super) async fn start_uppercase_service_impl(
-        &mut self,
+        &self,
         ctx: &Context,
         addr: Address,
     ) -> Result<()> {
-        if self.registry.uppercase_services.contains_key(&addr) {
+        let mut services = self.registry.uppercase_services.write().await;
+        if services.contains_key(&addr) {
             return Err(ApiError::generic(
                 "Uppercase service at this address exists",
             ));
@@ -220,15 +249,13 @@ impl NodeManager {
 
         ctx.start_worker(addr.clone(), Uppercase).await?;
 
-        self.registry
-            .uppercase_services
-            .insert(addr, Default::default());
+        services.insert(addr, Default::default());
 
         Ok(())
     }
 
     pub(super) async fn start_uppercase_service(
-        &mut self,
+        &self,
         ctx: &Context,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
@@ -246,25 +273,24 @@ impl NodeManager {
     }
 
     pub(super) async fn start_echoer_service_impl(
-        &mut self,
+        &self,
         ctx: &Context,
         addr: Address,
     ) -> Result<()> {
-        if self.registry.echoer_services.contains_key(&addr) {
+        let mut services = self.registry.echoer_services.write().await;
+        if services.contains_key(&addr) {
             return Err(ApiError::generic("Echoer service at this address exists"));
         }
 
         ctx.start_worker(addr.clone(), Echoer).await?;
 
-        self.registry
-            .echoer_services
-            .insert(addr, Default::default());
+        services.insert(addr, Default::default());
 
         Ok(())
     }
 
     pub(super) async fn start_echoer_service(
-        &mut self,
+        &self,
         ctx: &Context,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
@@ -282,7 +308,7 @@ impl NodeManager {
     }
 
     pub(super) async fn start_authenticator_service<'a>(
-        &mut self,
+        &self,
         ctx: &Context,
         req: &'a Request<'_>,
         dec: &mut Decoder<'_>,
@@ -304,23 +330,22 @@ impl NodeManager {
 
     #[cfg(feature = "direct-authenticator")]
     pub(super) async fn start_direct_authenticator_service_impl(
-        &mut self,
+        &self,
         ctx: &Context,
         addr: Address,
         path: &std::path::Path,
         proj: &[u8],
     ) -> Result<()> {
         use crate::nodes::registry::AuthenticatorServiceInfo;
-        if self.registry.authenticator_service.contains_key(&addr) {
+        let mut services = self.registry.authenticator_service.write().await;
+        if services.contains_key(&addr) {
             return Err(ApiError::generic("authenticator service already started"));
         }
         let db = self.authenticated_storage.async_try_clone().await?;
-        let id = self.identity()?.async_try_clone().await?;
+        let id = (*self.identity().await?).async_try_clone().await?;
         let au = crate::authenticator::direct::Server::new(proj.to_vec(), db, path, id);
         ctx.start_worker(addr.clone(), au).await?;
-        self.registry
-            .authenticator_service
-            .insert(addr, AuthenticatorServiceInfo::default());
+        services.insert(addr, AuthenticatorServiceInfo::default());
         Ok(())
     }
 }
@@ -0,0 +1,95 @@
+use minicbor::Decoder;
+use ockam::{Address, Context, Result};
+use ockam_core::api::{Method, Request, Response};
+use ockam_core::async_trait;
+
+use crate::nodes::models::stats::{SetWorkerAccounting, WorkerStats, WorkerStatsList};
+use crate::nodes::service::progress::Progress;
+use crate::nodes::service::service_registry::NodeService;
+use crate::nodes::NodeManager;
+
+/// Service reporting what the workers of the node consume.
+///
+/// Accounting is disabled by default, operators enable it to find the
+/// services busying a small device, the most costly first.
+pub(crate) struct StatsService;
+
+#[async_trait]
+impl NodeService for StatsService {
+    async fn handle_request(
+        &self,
+        _node: &NodeManager,
+        ctx: &mut Context,
+        _this: &Address,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        _progress: &Progress,
+    ) -> Result<Option<Vec<u8>>> {
+        let r = match (req.method(), req.path_segments::<2>().as_slice()) {
+            (Some(Method::Get), ["node", "stats"]) => {
+                let mut workers = ctx
+                    .worker_stats()
+                    .into_iter()
+                    .map(|(addr, s)| {
+                        WorkerStats::new(
+                            addr.to_string(),
+                            s.messages,
+                            s.handler_time.as_micros() as u64,
+                            s.allocated_bytes,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                workers.sort_by(|a, b| b.handler_micros.cmp(&a.handler_micros));
+                Response::ok(req.id())
+                    .body(WorkerStatsList::new(ctx.worker_accounting(), workers))
+                    .to_vec()?
+            }
+            (Some(Method::Put), ["node", "stats"]) => {
+                let body: SetWorkerAccounting = dec.decode()?;
+                ctx.set_worker_accounting(body.enabled);
+                Response::ok(req.id()).to_vec()?
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(r))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::api::Status;
+
+    #[ockam_macros::test]
+    async fn enable_worker_accounting(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+
+        let req = Request::get("/node/stats");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let list: WorkerStatsList = dec.decode()?;
+        assert!(!list.enabled);
+        assert!(list.workers.is_empty());
+
+        let req = Request::put("/node/stats").body(SetWorkerAccounting::new(true));
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        // The node manager accounts for a request once it has handled it
+        let req = Request::get("/node/stats");
+        ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let req = Request::get("/node/stats");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager, req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let list: WorkerStatsList = dec.decode()?;
+        assert!(list.enabled);
+        assert!(list.workers.iter().any(|w| w.messages > 0));
+
+        ctx.stop().await
+    }
+}
@@ -1,5 +1,8 @@
+#[cfg(not(feature = "quic"))]
+use crate::error::ApiError;
 use crate::nodes::models::transport::{
-    CreateTransport, DeleteTransport, TransportList, TransportMode, TransportStatus,
+    CreateTransport, DeleteTransport, RebindTransport, TransportList, TransportMode,
+    TransportStatus, TransportType,
 };
 use crate::nodes::service::{random_alias, Alias};
 use crate::nodes::NodeManager;
@@ -8,26 +11,36 @@ use ockam::Result;
 use ockam_core::api::{Request, Response, ResponseBuilder};
 
 impl NodeManager {
-    pub(super) fn get_tcp_con_or_list(
+    pub(super) async fn get_tcp_con_or_list(
         &self,
         req: &Request<'_>,
         mode: TransportMode,
     ) -> ResponseBuilder<TransportList<'_>> {
         Response::ok(req.id()).body(TransportList::new(
             self.transports
+                .read()
+                .await
                 .iter()
                 .filter(|(_, (_, tm, _))| *tm == mode)
-                .map(|(tid, (tt, tm, addr))| TransportStatus::new(*tt, *tm, addr, tid))
+                .map(|(tid, (tt, tm, addr))| {
+                    TransportStatus::new(*tt, *tm, addr.clone(), tid.clone())
+                })
                 .collect(),
         ))
     }
 
     pub(super) async fn add_transport<'a>(
-        &mut self,
+        &self,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder<TransportStatus<'a>>> {
-        let CreateTransport { tt, tm, addr, .. } = dec.decode()?;
+        let CreateTransport {
+            tt,
+            tm,
+            addr,
+            socket,
+            ..
+        } = dec.decode()?;
 
         use {super::TransportType::*, TransportMode::*};
 
@@ -38,23 +51,47 @@ impl NodeManager {
         let addr = addr.to_string();
 
         let res = match (tt, tm) {
-            (Tcp, Listen) => self
+            (Tcp, Listen) => {
+                let options = socket.map(|s| s.listener_options()).unwrap_or_default();
+                self.tcp_transport
+                    .listen_with_options(&addr, options)
+                    .await
+                    .map(|socket| socket.to_string())
+            }
+            (Tcp, Connect) => self
                 .tcp_transport
+                .connect(&addr)
+                .await
+                .map(|ockam_addr| ockam_addr.to_string()),
+            #[cfg(feature = "quic")]
+            (Quic, Listen) => self
+                .quic_transport
                 .listen(&addr)
                 .await
                 .map(|socket| socket.to_string()),
-            (Tcp, Connect) => self
-                .tcp_transport
+            #[cfg(feature = "quic")]
+            (Quic, Connect) => self
+                .quic_transport
                 .connect(&addr)
                 .await
                 .map(|ockam_addr| ockam_addr.to_string()),
+            #[cfg(not(feature = "quic"))]
+            (Quic, _) => Err(ApiError::generic(
+                "this node was built without QUIC support",
+            )),
             _ => unimplemented!(),
         };
 
         let response = match res {
-            Ok(_) => {
+            Ok(res) => {
+                // Listeners are known by the address they are bound to,
+                // e.g. with the port picked for port 0, to be stopped
+                let addr = if tm == Listen { res } else { addr };
                 let tid = random_alias();
-                self.transports.insert(tid.clone(), (tt, tm, addr.clone()));
+                self.transports
+                    .write()
+                    .await
+                    .insert(tid.clone(), (tt, tm, addr.clone()));
                 Response::ok(req.id()).body(TransportStatus::new(tt, tm, addr, tid))
             }
             Err(msg) => Response::bad_request(req.id()).body(TransportStatus::new(
@@ -69,7 +106,7 @@ impl NodeManager {
     }
 
     pub(super) async fn delete_transport(
-        &mut self,
+        &self,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder<()>> {
@@ -83,17 +120,157 @@ impl NodeManager {
             return Ok(Response::bad_request(req.id()));
         }
 
-        match self.transports.get(&tid) {
+        let mut transports = self.transports.write().await;
+        match transports.get(&tid) {
             Some(t) if t.1 == TransportMode::Listen => {
-                warn!("It is not currently supported to destroy LISTEN transports");
-                Ok(Response::bad_request(req.id()))
+                let addr = match (t.0, t.2.parse()) {
+                    (TransportType::Tcp, Ok(addr)) => addr,
+                    _ => {
+                        warn!("It is only supported to destroy TCP LISTEN transports");
+                        return Ok(Response::bad_request(req.id()));
+                    }
+                };
+                self.tcp_transport.stop_listener(addr).await?;
+                transports.remove(&tid);
+                Ok(Response::ok(req.id()))
             }
             Some(t) => {
-                self.tcp_transport.disconnect(&t.2).await?;
-                self.transports.remove(&tid);
+                match t.0 {
+                    #[cfg(feature = "quic")]
+                    TransportType::Quic => self.quic_transport.disconnect(&t.2).await?,
+                    _ => self.tcp_transport.disconnect(&t.2).await?,
+                }
+                transports.remove(&tid);
                 Ok(Response::ok(req.id()))
             }
             None => Ok(Response::bad_request(req.id())),
         }
     }
+
+    /// Move a TCP listener to another address, without downtime if the
+    /// new address is free: the listener is only stopped once the new one
+    /// is bound.
+    pub(super) async fn rebind_listener<'a>(
+        &self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<TransportStatus<'a>>> {
+        let RebindTransport { tid, addr, .. } = dec.decode()?;
+        info!("Handling request to rebind transport {} to {}", tid, addr);
+
+        let tid: Alias = tid.into();
+        if self.api_transport_id == tid {
+            warn!("It is not supported to rebind the API transport");
+            return Ok(Response::bad_request(req.id()).body(TransportStatus::new(
+                TransportType::Tcp,
+                TransportMode::Listen,
+                "the API transport can't be rebound".to_string(),
+                tid,
+            )));
+        }
+
+        let mut transports = self.transports.write().await;
+        let old = match transports.get(&tid) {
+            Some((TransportType::Tcp, TransportMode::Listen, old)) => old.clone(),
+            _ => {
+                return Ok(Response::bad_request(req.id()).body(TransportStatus::new(
+                    TransportType::Tcp,
+                    TransportMode::Listen,
+                    format!("no TCP listener {tid}"),
+                    tid,
+                )))
+            }
+        };
+
+        // Listening on the new address may fail, e.g. when it is in use,
+        // in which case the listener keeps its former address
+        let new = match self.tcp_transport.listen(addr.as_ref()).await {
+            Ok(new) => new,
+            Err(e) => {
+                return Ok(Response::bad_request(req.id()).body(TransportStatus::new(
+                    TransportType::Tcp,
+                    TransportMode::Listen,
+                    e.to_string(),
+                    tid,
+                )))
+            }
+        };
+        if let Ok(old) = old.parse() {
+            self.tcp_transport.stop_listener(old).await?;
+        }
+        transports.insert(
+            tid.clone(),
+            (TransportType::Tcp, TransportMode::Listen, new.to_string()),
+        );
+
+        Ok(Response::ok(req.id()).body(TransportStatus::new(
+            TransportType::Tcp,
+            TransportMode::Listen,
+            new.to_string(),
+            tid,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::Context;
+    use ockam_core::api::Status;
+
+    #[ockam_macros::test]
+    async fn listeners_are_rebound_and_deleted(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+
+        let body = CreateTransport::new(TransportType::Tcp, TransportMode::Listen, "127.0.0.1:0");
+        let req = Request::post("/node/tcp/listener").body(body);
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let status: TransportStatus = dec.decode()?;
+        let tid = status.tid.to_string();
+        let first = status.payload.to_string();
+        assert_ne!(first, "127.0.0.1:0");
+
+        // An address in use is refused, the listener keeps its address
+        let busy = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let busy = busy.local_addr().unwrap().to_string();
+        let req = Request::put("/node/tcp/listener").body(RebindTransport::new(&*tid, &*busy));
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::BadRequest));
+
+        let req = Request::put("/node/tcp/listener").body(RebindTransport::new(&*tid, &*first));
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::BadRequest));
+
+        let req =
+            Request::put("/node/tcp/listener").body(RebindTransport::new(&*tid, "127.0.0.1:0"));
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let status: TransportStatus = dec.decode()?;
+        assert_eq!(status.tid, tid);
+        assert_ne!(status.payload, first);
+
+        // The former address is released
+        std::net::TcpListener::bind(&first).unwrap();
+
+        let req = Request::delete("/node/tcp/listener").body(DeleteTransport::new(&*tid, false));
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let req = Request::get("/node/tcp/listener");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager, req).await?;
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let list: TransportList = dec.decode()?;
+        assert!(list.list.iter().all(|t| t.tid != tid));
+
+        ctx.stop().await
+    }
 }
@@ -16,11 +16,12 @@ impl NodeManager {
     }
 
     pub(super) async fn create_vault_impl(
-        &mut self,
+        &self,
         path: Option<PathBuf>,
         reuse_if_exists: bool,
     ) -> Result<()> {
-        if self.vault.is_some() {
+        let mut guard = self.vault.write().await;
+        if guard.is_some() {
             return if reuse_if_exists {
                 debug!("Using existing vault");
                 Ok(())
@@ -43,13 +44,13 @@ impl NodeManager {
             .persist_config_updates()
             .map_err(map_anyhow_err)?;
 
-        self.vault = Some(vault);
+        *guard = Some(vault);
 
         Ok(())
     }
 
     pub(super) async fn create_vault(
-        &mut self,
+        &self,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder> {
@@ -0,0 +1,134 @@
+use minicbor::Decoder;
+use ockam::{Address, Context, Result};
+use ockam_core::api::{self, Method, Request, Response};
+use ockam_core::async_trait;
+
+use crate::nodes::config::WebhookConfig;
+use crate::nodes::models::events::{SetWebhook, WebhookInfo};
+use crate::nodes::service::map_anyhow_err;
+use crate::nodes::service::progress::Progress;
+use crate::nodes::service::service_registry::NodeService;
+use crate::nodes::NodeManager;
+
+/// Service configuring the webhook to which the node events are posted.
+///
+/// The webhook is saved in the node configuration, so that it is used
+/// again when the node restarts.
+pub(crate) struct WebhookService;
+
+#[async_trait]
+impl NodeService for WebhookService {
+    async fn handle_request(
+        &self,
+        node: &NodeManager,
+        _ctx: &mut Context,
+        _this: &Address,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        _progress: &Progress,
+    ) -> Result<Option<Vec<u8>>> {
+        let r = match (req.method(), req.path_segments::<2>().as_slice()) {
+            (Some(Method::Get), ["node", "webhook"]) => match node.events.webhook() {
+                Some(w) => Response::ok(req.id())
+                    .body(WebhookInfo::new(w.url, w.secret.is_some()))
+                    .to_vec()?,
+                None => Response::not_found(req.id()).to_vec()?,
+            },
+            (Some(Method::Put), ["node", "webhook"]) => self.set_webhook(node, req, dec)?,
+            (Some(Method::Delete), ["node", "webhook"]) => self.delete_webhook(node, req)?,
+            _ => return Ok(None),
+        };
+        Ok(Some(r))
+    }
+}
+
+impl WebhookService {
+    fn set_webhook(
+        &self,
+        node: &NodeManager,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let body: SetWebhook = dec.decode()?;
+        if !(body.url().starts_with("http://") || body.url().starts_with("https://")) {
+            let msg = "webhook url must start with http:// or https://";
+            return Ok(api::bad_request(req, msg).to_vec()?);
+        }
+        let webhook = WebhookConfig {
+            url: body.url().to_string(),
+            secret: body.secret().map(|s| s.to_string()),
+        };
+        info!(url = %webhook.url, signed = %webhook.secret.is_some(), "Setting webhook");
+        self.save(node, Some(webhook))?;
+        let info = WebhookInfo::new(body.url(), body.secret().is_some());
+        Ok(Response::ok(req.id()).body(info).to_vec()?)
+    }
+
+    fn delete_webhook(&self, node: &NodeManager, req: &Request<'_>) -> Result<Vec<u8>> {
+        if node.events.webhook().is_none() {
+            return Ok(Response::not_found(req.id()).to_vec()?);
+        }
+        info!("Deleting webhook");
+        self.save(node, None)?;
+        Ok(Response::ok(req.id()).to_vec()?)
+    }
+
+    fn save(&self, node: &NodeManager, webhook: Option<WebhookConfig>) -> Result<()> {
+        node.config.writelock_inner().webhook = webhook.clone();
+        node.config
+            .persist_config_updates()
+            .map_err(map_anyhow_err)?;
+        node.events.set_webhook(webhook);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::api::Status;
+
+    #[ockam_macros::test]
+    async fn set_show_and_delete_webhook(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+
+        let req = Request::get("/node/webhook");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::NotFound));
+
+        // Only HTTP(S) urls
+        let req = Request::put("/node/webhook").body(SetWebhook::new("ftp://example.com", None));
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::BadRequest));
+
+        let body = SetWebhook::new("https://example.com/events", Some("secret".into()));
+        let req = Request::put("/node/webhook").body(body);
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        // The secret is not returned
+        let req = Request::get("/node/webhook");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let info: WebhookInfo = dec.decode()?;
+        assert_eq!(info.url, "https://example.com/events");
+        assert!(info.signed);
+
+        let req = Request::delete("/node/webhook");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager.clone(), req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let req = Request::delete("/node/webhook");
+        let buf = ockam_node::api::request(ctx, "", None, node_manager, req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::NotFound));
+
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,175 @@
+//! Node API requests read from a byte stream, e.g. stdin, and their
+//! responses written to another one, e.g. stdout
+//!
+//! Requests and responses are framed by their length, a big-endian `u32`,
+//! followed by their CBOR encoded header and body, so that a node can be
+//! driven by a supervisor, an SSH session or a test harness without any
+//! listening socket.
+
+use minicbor::Decoder;
+use ockam::{Address, Context, Result, Route};
+use ockam_core::api::{self, Request};
+use ockam_node::tokio;
+use ockam_node::tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use ockam_node::tokio::sync::mpsc;
+
+use crate::error::ApiError;
+
+/// Longest request or response accepted
+pub const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// Send the requests read from `reader` to the node manager at `manager`
+/// and write their responses to `writer`, until `reader` is closed.
+///
+/// Requests are handled concurrently, so responses are written once
+/// ready, with the id of their request. Frames which are not requests
+/// are skipped.
+pub async fn serve<R, W>(ctx: &Context, manager: Route, mut reader: R, writer: W) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(32);
+    let responses = tokio::spawn(write_responses(writer, rx));
+    while let Some(frame) = read_frame(&mut reader).await.map_err(io_error)? {
+        if let Err(err) = Decoder::new(&frame).decode::<Request>() {
+            warn!(%err, "Skipping a frame which is not a request");
+            continue;
+        }
+        let mut ctx = ctx.new_detached(Address::random_local()).await?;
+        let (manager, tx) = (manager.clone(), tx.clone());
+        tokio::spawn(async move {
+            match respond(&mut ctx, manager, frame).await {
+                Ok(res) => {
+                    let _ = tx.send(res).await;
+                }
+                Err(err) => error!(%err, "Failed to respond to a request"),
+            }
+        });
+    }
+    // The requests still being handled are answered before returning
+    drop(tx);
+    responses
+        .await
+        .map_err(|e| ApiError::generic(&e.to_string()))?
+        .map_err(io_error)
+}
+
+/// The response of the node manager to `frame`, or an error response if
+/// there is none.
+async fn respond(ctx: &mut Context, manager: Route, frame: Vec<u8>) -> Result<Vec<u8>> {
+    ctx.send(manager, frame.clone()).await?;
+    match ctx.receive::<Vec<u8>>().await {
+        Ok(res) => Ok(res.take().body()),
+        Err(err) => {
+            let req: Request = Decoder::new(&frame).decode()?;
+            Ok(api::internal_error(&req, &err.to_string()).to_vec()?)
+        }
+    }
+}
+
+async fn write_responses<W>(mut writer: W, mut rx: mpsc::Receiver<Vec<u8>>) -> std::io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    while let Some(res) = rx.recv().await {
+        write_frame(&mut writer, &res).await?
+    }
+    Ok(())
+}
+
+/// The next frame, `None` once `reader` is closed.
+pub async fn read_frame<R>(reader: &mut R) -> std::io::Result<Option<Vec<u8>>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut len = [0; 4];
+    match reader.read_exact(&mut len).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        let msg = format!("frame of {len} bytes, the maximum is {MAX_FRAME_LEN}");
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+    }
+    let mut frame = vec![0; len];
+    reader.read_exact(&mut frame).await?;
+    Ok(Some(frame))
+}
+
+pub async fn write_frame<W>(writer: &mut W, frame: &[u8]) -> std::io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let len = frame.len();
+    if len > MAX_FRAME_LEN {
+        let msg = format!("frame of {len} bytes, the maximum is {MAX_FRAME_LEN}");
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+    }
+    writer.write_all(&(len as u32).to_be_bytes()).await?;
+    writer.write_all(frame).await?;
+    writer.flush().await
+}
+
+fn io_error(e: std::io::Error) -> ockam_core::Error {
+    ApiError::generic(&e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::NodeManager;
+    use ockam_core::api::{Response, Status};
+    use ockam_node::tokio::io::duplex;
+
+    #[ockam_macros::test]
+    async fn requests_are_answered_over_a_stream(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+        let (mut client, server) = duplex(64 * 1024);
+        let (reader, writer) = tokio::io::split(server);
+
+        let req = Request::get("/node").to_vec()?;
+        write_frame(&mut client, &req).await.map_err(io_error)?;
+        // Not a request
+        write_frame(&mut client, b"\xff").await.map_err(io_error)?;
+        let req = Request::get("/node/live").to_vec()?;
+        write_frame(&mut client, &req).await.map_err(io_error)?;
+
+        let (mut rx, mut tx) = tokio::io::split(client);
+        let server = serve(ctx, node_manager, reader, writer);
+        let client = async move {
+            let mut statuses = Vec::new();
+            for _ in 0..2 {
+                let res = read_frame(&mut rx).await.map_err(io_error)?.unwrap();
+                let res: Response = Decoder::new(&res).decode()?;
+                statuses.push(res.status());
+            }
+            // Closing the stream stops serving it
+            tx.shutdown().await.map_err(io_error)?;
+            Ok::<_, ockam_core::Error>(statuses)
+        };
+        let (served, statuses) = tokio::join!(server, client);
+        served?;
+        assert_eq!(statuses?, vec![Some(Status::Ok), Some(Status::Ok)]);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn frames_are_length_prefixed(ctx: &mut Context) -> Result<()> {
+        let mut buf = Vec::new();
+        write_frame(&mut buf, b"abc").await.map_err(io_error)?;
+        assert_eq!(buf, b"\x00\x00\x00\x03abc");
+        let mut reader = buf.as_slice();
+        let frame = read_frame(&mut reader).await.map_err(io_error)?;
+        assert_eq!(frame.as_deref(), Some(&b"abc"[..]));
+        assert_eq!(read_frame(&mut reader).await.map_err(io_error)?, None);
+
+        let mut reader = &u32::MAX.to_be_bytes()[..];
+        assert!(read_frame(&mut reader).await.is_err());
+
+        ctx.stop().await
+    }
+}
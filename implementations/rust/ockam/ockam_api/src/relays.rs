@@ -0,0 +1,188 @@
+//! Selection of the relay to connect to, when a project has several.
+
+use crate::multiaddr_to_route;
+use ockam::TCP;
+use ockam_core::compat::collections::HashMap;
+use ockam_multiaddr::MultiAddr;
+use ockam_node::tokio::net::TcpStream;
+use ockam_node::tokio::task::JoinSet;
+use ockam_node::tokio::time::{timeout, Instant};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Time to wait for a relay to accept a connection.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A relay is only replaced by a faster one if its latency is higher
+/// than this factor times the latency of the faster one ...
+const STICKINESS_FACTOR: f64 = 1.5;
+
+/// ... and at least this much higher.
+const STICKINESS_MARGIN: Duration = Duration::from_millis(20);
+
+/// Measure the time it takes to open a TCP connection to the node of
+/// an address.
+///
+/// Returns `None` if the address has no TCP part or if the node can not
+/// be reached.
+pub async fn probe(addr: &MultiAddr) -> Option<Duration> {
+    let socket_addr = multiaddr_to_route(addr)?
+        .iter()
+        .next()
+        .filter(|a| a.transport_type() == TCP)?
+        .address()
+        .to_string();
+    let start = Instant::now();
+    match timeout(PROBE_TIMEOUT, TcpStream::connect(&socket_addr)).await {
+        Ok(Ok(_)) => Some(start.elapsed()),
+        Ok(Err(e)) => {
+            debug!(%addr, err = %e, "relay is not reachable");
+            None
+        }
+        Err(_) => {
+            debug!(%addr, "relay did not answer in time");
+            None
+        }
+    }
+}
+
+/// Measure the latency of all addresses at once.
+pub async fn probe_all(addrs: &[MultiAddr]) -> Vec<(MultiAddr, Duration)> {
+    let mut probes = JoinSet::new();
+    for a in addrs {
+        let a = a.clone();
+        probes.spawn(async move {
+            let t = probe(&a).await;
+            (a, t)
+        });
+    }
+    let mut latencies = Vec::new();
+    while let Some(r) = probes.join_next().await {
+        if let Ok((a, Some(t))) = r {
+            latencies.push((a, t))
+        }
+    }
+    latencies
+}
+
+/// Pick the relay with the lowest latency, but stay with the `previous`
+/// one unless another relay is clearly faster.
+///
+/// The cost of a relay address (see [`MultiAddr::cost`]) is added to its
+/// latency. Returns `None` if no relay could be measured.
+pub fn choose(
+    previous: Option<&MultiAddr>,
+    latencies: &[(MultiAddr, Duration)],
+) -> Option<MultiAddr> {
+    let weighted: Vec<(&MultiAddr, Duration)> = latencies
+        .iter()
+        .map(|(a, t)| (a, *t + Duration::from_millis(a.cost().into())))
+        .collect();
+    let (fastest, best) = weighted.iter().min_by_key(|(_, t)| *t)?;
+    if let Some(prev) = previous {
+        if let Some((_, t)) = weighted.iter().find(|(a, _)| a.is_equivalent(prev)) {
+            if *t <= best.mul_f64(STICKINESS_FACTOR) || *t <= *best + STICKINESS_MARGIN {
+                return Some(prev.clone());
+            }
+        }
+    }
+    Some((*fastest).clone())
+}
+
+/// Remembers the relay selected for each project, so that connections
+/// stick to a relay for as long as it stays reasonably fast.
+#[derive(Debug, Clone, Default)]
+pub struct RelaySelector {
+    selected: Arc<Mutex<HashMap<String, MultiAddr>>>,
+}
+
+impl RelaySelector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Select one of the relays of `project`.
+    ///
+    /// A single relay is returned as is. Otherwise all relays are probed
+    /// and the fastest one is selected. If none can be reached, the first
+    /// one is returned, so that connecting reports a meaningful error.
+    pub async fn select(&self, project: &str, relays: &[MultiAddr]) -> Option<MultiAddr> {
+        if relays.len() < 2 {
+            return relays.first().cloned();
+        }
+        let latencies = probe_all(relays).await;
+        let mut selected = self.selected.lock().unwrap();
+        let choice = choose(selected.get(project), &latencies).unwrap_or_else(|| relays[0].clone());
+        if selected.get(project) != Some(&choice) {
+            info!(%project, relay = %choice, "selected relay");
+            selected.insert(project.to_string(), choice.clone());
+        }
+        Some(choice)
+    }
+
+    /// The relay currently selected for `project`, if any.
+    pub fn selected(&self, project: &str) -> Option<MultiAddr> {
+        self.selected.lock().unwrap().get(project).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_node::tokio;
+    use std::str::FromStr;
+
+    fn addr(port: u16) -> MultiAddr {
+        MultiAddr::from_str(&format!("/ip4/127.0.0.1/tcp/{port}/service/api")).unwrap()
+    }
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn choose_fastest_relay() {
+        let l = vec![(addr(1), ms(80)), (addr(2), ms(10)), (addr(3), ms(40))];
+        assert_eq!(choose(None, &l), Some(addr(2)));
+        assert_eq!(choose(Some(&addr(4)), &l), Some(addr(2)));
+        assert_eq!(choose(None, &[]), None)
+    }
+
+    #[test]
+    fn add_cost_to_latency() {
+        let costly = MultiAddr::from_str("/ip4/127.0.0.1/tcp/1/cost/100/service/api").unwrap();
+        let l = vec![(costly.clone(), ms(10)), (addr(2), ms(50))];
+        assert_eq!(choose(None, &l), Some(addr(2)));
+        let l = vec![(costly.clone(), ms(10)), (addr(2), ms(200))];
+        assert_eq!(choose(None, &l), Some(costly))
+    }
+
+    #[test]
+    fn stick_to_reasonably_fast_relay() {
+        // within the margin
+        let l = vec![(addr(1), ms(25)), (addr(2), ms(10))];
+        assert_eq!(choose(Some(&addr(1)), &l), Some(addr(1)));
+        // within the factor
+        let l = vec![(addr(1), ms(140)), (addr(2), ms(100))];
+        assert_eq!(choose(Some(&addr(1)), &l), Some(addr(1)));
+        // clearly slower
+        let l = vec![(addr(1), ms(200)), (addr(2), ms(100))];
+        assert_eq!(choose(Some(&addr(1)), &l), Some(addr(2)));
+    }
+
+    #[ockam_macros::test]
+    async fn select_reachable_relay(ctx: &mut ockam_node::Context) -> ockam_core::Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        // Nothing listens on the first relay once its listener is dropped.
+        let closed = {
+            let l = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            l.local_addr().unwrap().port()
+        };
+        let relays = RelaySelector::new();
+        let selected = relays.select("p", &[addr(closed), addr(port)]).await;
+        assert_eq!(selected, Some(addr(port)));
+        assert_eq!(relays.selected("p"), Some(addr(port)));
+        ctx.stop().await
+    }
+}
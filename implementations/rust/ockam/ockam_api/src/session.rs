@@ -1,30 +1,70 @@
+mod liveness;
 mod sessions;
 
+use crate::events::{Events, NodeEvent};
 use crate::{multiaddr_to_route, DefaultAddress};
+use core::sync::atomic::{AtomicU64, Ordering};
 use minicbor::{Decode, Encode};
 use ockam::{LocalMessage, Route, TransportMessage, Worker};
 use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Address, Decodable, Encodable, Error, Routed, LOCAL};
 use ockam_multiaddr::MultiAddr;
 use ockam_node::tokio;
-use ockam_node::tokio::sync::mpsc;
-use ockam_node::tokio::task::JoinSet;
-use ockam_node::tokio::time::{timeout, Duration};
+use ockam_node::tokio::sync::{mpsc, Mutex as AsyncMutex, Notify};
+use ockam_node::tokio::task::{JoinError, JoinHandle, JoinSet};
+use ockam_node::tokio::time::{timeout_at, Duration, Instant};
 use ockam_node::Context;
-use sessions::{Key, Ping, Status};
+use sessions::Ping;
 use tracing as log;
 
-pub use sessions::{Session, Sessions};
+pub use liveness::{LivenessCheck, Probe, TcpProbe};
+pub use sessions::{Key, Recovery, RetryPolicy, Session, Sessions, Status, Step};
 
 const MAX_FAILURES: usize = 3;
 const DELAY: Duration = Duration::from_secs(3);
 
+/// Time before a failed rotation is tried again.
+const ROTATION_RETRY: Duration = Duration::from_secs(60);
+
+/// Key of the alias of a forwarder in the data of its session.
+pub(crate) const ALIAS: &str = "alias";
+
+/// Key of the address a monitored secure channel leads to, in the data of
+/// its session. Sessions without it belong to forwarders.
+pub(crate) const SECURE_CHANNEL: &str = "secure_channel";
+
+/// The medic checks the health of all sessions and replaces broken ones.
+///
+/// It runs as its own task, supervised by [`Medic::start`] which restarts
+/// it if it ever panics, and is controlled at runtime through a
+/// [`MedicHandle`].
 #[derive(Debug)]
 pub struct Medic {
-    delay: Duration,
-    sessions: Arc<Mutex<Sessions>>,
+    handle: MedicHandle,
     pings: JoinSet<(Key, Result<(), Error>)>,
-    replacements: JoinSet<(Key, Result<MultiAddr, Error>)>,
+    probes: JoinSet<(Key, Ping, Result<(), Error>)>,
+    replacements: JoinSet<(Key, Recovery)>,
+    rotations: JoinSet<(Key, Recovery)>,
+    events: Events,
+}
+
+/// Runtime settings of the medic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Control {
+    /// Sessions are neither pinged nor replaced while paused.
+    pub paused: bool,
+    /// Time between two checks of the sessions.
+    pub delay: Duration,
+}
+
+/// Shared access to the sessions and the settings of a running medic.
+#[derive(Debug, Clone)]
+pub struct MedicHandle {
+    sessions: Arc<Mutex<Sessions>>,
+    control: Arc<Mutex<Control>>,
+    wakeup: Arc<Notify>,
+    restarts: Arc<AtomicU64>,
 }
 
 #[derive(Debug, Copy, Clone, Encode, Decode)]
@@ -34,85 +74,262 @@ pub struct Message {
     #[n(1)] ping: Ping,
 }
 
+impl MedicHandle {
+    pub fn sessions(&self) -> Arc<Mutex<Sessions>> {
+        self.sessions.clone()
+    }
+
+    pub fn control(&self) -> Control {
+        *self.control.lock().unwrap()
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.control.lock().unwrap().paused = paused;
+        self.wakeup.notify_one()
+    }
+
+    pub fn set_delay(&self, delay: Duration) {
+        self.control.lock().unwrap().delay = delay;
+        self.wakeup.notify_one()
+    }
+
+    /// Recover a session right away, even if it is healthy.
+    ///
+    /// Returns `false` if there is no session with this key.
+    pub fn recover(&self, k: &Key) -> bool {
+        match self.sessions.lock().unwrap().session_mut(k) {
+            Some(s) => s.request_recovery(),
+            None => return false,
+        }
+        self.wakeup.notify_one();
+        true
+    }
+
+    /// Number of times the medic was restarted after a panic.
+    pub fn restarts(&self) -> u64 {
+        self.restarts.load(Ordering::Relaxed)
+    }
+}
+
 impl Medic {
-    pub fn new() -> Self {
+    pub(crate) fn new(events: Events) -> Self {
         Self {
-            delay: DELAY,
-            sessions: Arc::new(Mutex::new(Sessions::new())),
+            handle: MedicHandle {
+                sessions: Arc::new(Mutex::new(Sessions::new())),
+                control: Arc::new(Mutex::new(Control {
+                    paused: false,
+                    delay: DELAY,
+                })),
+                wakeup: Arc::new(Notify::new()),
+                restarts: Arc::new(AtomicU64::new(0)),
+            },
             pings: JoinSet::new(),
+            probes: JoinSet::new(),
             replacements: JoinSet::new(),
+            rotations: JoinSet::new(),
+            events,
         }
     }
 
-    pub fn sessions(&self) -> Arc<Mutex<Sessions>> {
-        self.sessions.clone()
+    pub fn handle(&self) -> MedicHandle {
+        self.handle.clone()
     }
 
+    /// Run the medic until the returned future is dropped.
+    ///
+    /// If the medic panics, it is restarted after a delay with the same
+    /// sessions and settings.
     pub async fn start(self, ctx: Context) -> Result<(), Error> {
-        let ctx = ctx.new_detached(Address::random_local()).await?;
+        let ctx = Arc::new(ctx.new_detached(Address::random_local()).await?);
         let (tx, rx) = mpsc::channel(32);
         ctx.start_worker(Collector::address(), Collector(tx))
             .await?;
-        self.go(ctx, rx).await
+        let rx = Arc::new(AsyncMutex::new(rx));
+        let (handle, events) = (self.handle.clone(), self.events.clone());
+        let mut medic = self;
+        loop {
+            let task = AbortOnDrop(tokio::spawn(medic.go(ctx.clone(), rx.clone())));
+            match task.join().await {
+                Err(e) if e.is_panic() => {
+                    log::error!("session medic panicked, restarting it");
+                    handle.restarts.fetch_add(1, Ordering::Relaxed);
+                    tokio::time::sleep(DELAY).await
+                }
+                Err(e) => return Err(Error::new(Origin::Node, Kind::Cancelled, e)),
+            }
+            medic = Medic {
+                handle: handle.clone(),
+                pings: JoinSet::new(),
+                probes: JoinSet::new(),
+                replacements: JoinSet::new(),
+                rotations: JoinSet::new(),
+                events: events.clone(),
+            }
+        }
     }
 
     /// Continuously check all sessions.
     ///
-    /// This method never returns. It will ping all healthy sessions and
-    /// trigger replacements for the unhealthy ones.
-    async fn go(mut self, ctx: Context, mut rx: mpsc::Receiver<Message>) -> ! {
-        let ctx = Arc::new(ctx);
+    /// This method never returns. It will ping, or probe with their
+    /// liveness check, all healthy sessions and trigger replacements for
+    /// the unhealthy ones.
+    async fn go(mut self, ctx: Arc<Context>, rx: Arc<AsyncMutex<mpsc::Receiver<Message>>>) -> ! {
+        let mut rx = rx.lock().await;
+        self.resume();
         loop {
-            log::trace!("check sessions");
-            {
-                let mut sessions = self.sessions.lock().unwrap();
-                for (&key, session) in sessions.iter_mut() {
-                    if session.pings().len() < MAX_FAILURES {
-                        let m = Message::new(session.key());
-                        session.add_ping(m.ping);
-                        log::trace!(%key, ping = %m.ping, "send ping");
-                        let l = {
-                            let v = Encodable::encode(&m).expect("message can be encoded");
-                            let r: Route = if let Some(r) = multiaddr_to_route(session.address()) {
-                                r.clone()
-                                    .modify()
-                                    .append(DefaultAddress::ECHO_SERVICE)
-                                    .into()
-                            } else {
-                                log::error! {
-                                    %key,
-                                    addr = %session.address(),
-                                    "failed to convert address to route"
-                                }
-                                continue;
-                            };
-                            let t = TransportMessage::v1(r, Collector::address(), v);
-                            LocalMessage::new(t, Vec::new())
+            let control = self.handle.control();
+            if control.paused {
+                log::trace!("session checks are paused");
+            } else {
+                log::trace!("check sessions");
+                self.rotate();
+                self.check(&ctx, control.delay)
+            }
+            self.recover_requested();
+            let deadline = Instant::now() + control.delay;
+            // Wake-ups handle explicit recoveries, a new setting starts a new check.
+            while let Ok(()) = timeout_at(deadline, self.get_results(&mut rx)).await {
+                self.recover_requested();
+                if self.handle.control() != control {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Pick up the sessions of a previous run of the medic.
+    ///
+    /// Replacements and rotations in progress were aborted with it, so
+    /// broken sessions are replaced again.
+    fn resume(&mut self) {
+        let sessions = self.handle.sessions();
+        let mut sessions = sessions.lock().unwrap();
+        for (_, session) in sessions.iter_mut() {
+            session.set_rotating(false);
+            // Sessions it gave up on stay down until a recovery is requested.
+            if session.status() == Status::Down && !session.has_given_up() {
+                session.set_status(Status::Up);
+                session.request_recovery()
+            }
+        }
+    }
+
+    /// Ping or probe the sessions, or replace them if they did not answer.
+    ///
+    /// A probe counts as a ping, answered if it succeeds within `delay`.
+    fn check(&mut self, ctx: &Arc<Context>, delay: Duration) {
+        let sessions = self.handle.sessions();
+        let mut sessions = sessions.lock().unwrap();
+        for (&key, session) in sessions.iter_mut() {
+            if session.pending_pings() < MAX_FAILURES {
+                let m = Message::new(session.key());
+                session.add_ping(m.ping);
+                if let Some(c) = session.liveness_check() {
+                    log::trace!(%key, ping = %m.ping, "probe session");
+                    let probe = c.probe(session.address());
+                    self.probes.spawn(async move {
+                        let r = match tokio::time::timeout(delay, probe).await {
+                            Ok(r) => r,
+                            Err(_) => Err(Error::new(Origin::Node, Kind::Timeout, "probe timeout")),
                         };
-                        let sender = ctx.clone();
-                        self.pings
-                            .spawn(async move { (key, sender.forward(l).await) });
+                        (key, m.ping, r)
+                    });
+                    continue;
+                }
+                log::trace!(%key, ping = %m.ping, "send ping");
+                let l = {
+                    let v = Encodable::encode(&m).expect("message can be encoded");
+                    let r: Route = if let Some(r) = multiaddr_to_route(session.address()) {
+                        r.clone()
+                            .modify()
+                            .append(DefaultAddress::ECHO_SERVICE)
+                            .into()
                     } else {
-                        match session.status() {
-                            Status::Up => {
-                                log::warn!(%key, "session unresponsive");
-                                let f = session.replacement(session.address().clone());
-                                session.set_status(Status::Down);
-                                log::info!(%key, "replacing session");
-                                self.replacements.spawn(async move { (key, f.await) });
-                            }
-                            Status::Down => {
-                                log::warn!(%key, "session is down");
-                            }
+                        log::error! {
+                            %key,
+                            addr = %session.address(),
+                            "failed to convert address to route"
                         }
+                        continue;
+                    };
+                    let t = TransportMessage::v1(r, Collector::address(), v);
+                    LocalMessage::new(t, Vec::new())
+                };
+                let sender = ctx.clone();
+                self.pings
+                    .spawn(async move { (key, sender.forward(l).await) });
+            } else {
+                match session.status() {
+                    Status::Up if session.is_rotating() => {
+                        log::warn!(%key, "session unresponsive, waiting for its rotation");
+                    }
+                    Status::Up => {
+                        log::warn!(%key, "session unresponsive");
+                        self.replace(key, session)
+                    }
+                    Status::Down => {
+                        log::warn!(%key, "session is down");
                     }
                 }
             }
+        }
+    }
 
-            let _ = timeout(self.delay, self.get_results(&mut rx)).await;
+    /// Rotate the sessions which are older than their maximum age.
+    ///
+    /// Only sessions answering their pings are rotated, broken ones are
+    /// replaced instead.
+    fn rotate(&mut self) {
+        let sessions = self.handle.sessions();
+        let mut sessions = sessions.lock().unwrap();
+        for (&key, session) in sessions.iter_mut() {
+            if !session.rotation_due() {
+                continue;
+            }
+            log::info!(%key, "rotating session");
+            let f = session.replacement(session.address().clone());
+            session.set_rotating(true);
+            self.rotations.spawn(async move { (key, f.await) });
         }
     }
 
+    /// Replace the sessions for which a recovery was requested.
+    fn recover_requested(&mut self) {
+        let sessions = self.handle.sessions();
+        let mut sessions = sessions.lock().unwrap();
+        for (&key, session) in sessions.iter_mut() {
+            if session.take_recovery_request() {
+                match session.status() {
+                    Status::Up => {
+                        log::info!(%key, "recovery requested");
+                        self.replace(key, session)
+                    }
+                    Status::Down if session.has_given_up() => {
+                        log::info!(%key, "recovery requested, replacing session again");
+                        session.reset_attempts();
+                        let f = session.replacement(session.address().clone());
+                        self.replacements.spawn(async move { (key, f.await) });
+                    }
+                    Status::Down => {
+                        log::info!(%key, "recovery requested, session is already being replaced");
+                    }
+                }
+            }
+        }
+    }
+
+    fn replace(&mut self, key: Key, session: &mut Session) {
+        let f = session.replacement(session.address().clone());
+        session.set_status(Status::Down);
+        session.add_recovery();
+        self.events.emit(event(key, session, Change::Down));
+        log::info!(%key, "replacing session");
+        self.replacements.spawn(async move { (key, f.await) });
+    }
+
+    /// Process the results of pings and replacements.
+    ///
+    /// Returns when the medic is woken up by its handle.
     async fn get_results(&mut self, rx: &mut mpsc::Receiver<Message>) {
         loop {
             tokio::select! {
@@ -122,42 +339,166 @@ impl Medic {
                     Some(Ok((k, Err(e)))) => log::debug!(key = %k, err = %e, "failed to send ping"),
                     Some(Ok((k, Ok(())))) => log::trace!(key = %k, "sent ping"),
                 },
+                p = self.probes.join_next(), if !self.probes.is_empty() => match p {
+                    None                     => log::debug!("no probes"),
+                    Some(Err(e))             => log::error!("task failed: {e:?}"),
+                    Some(Ok((k, _, Err(e)))) => log::debug!(key = %k, err = %e, "probe failed"),
+                    Some(Ok((k, p, Ok(())))) => {
+                        if let Some(s) = self.handle.sessions.lock().unwrap().session_mut(&k) {
+                            if s.pong(p) {
+                                log::trace!(key = %k, ping = %p, rtt = ?s.rtt(), "probe succeeded");
+                            }
+                        }
+                    }
+                },
                 r = self.replacements.join_next(), if !self.replacements.is_empty() => match r {
                     None                  => log::debug!("no replacements"),
                     Some(Err(e))          => log::error!("task failed: {e:?}"),
-                    Some(Ok((k, Err(e)))) => {
-                        let mut sessions = self.sessions.lock().unwrap();
-                        if let Some(s) = sessions.session_mut(&k) {
-                            log::warn!(key = %k, err = %e, "replacing session failed");
-                            let f = s.replacement(s.address().clone());
-                            log::info!(key = %k, "replacing session");
-                            self.replacements.spawn(async move { (k, f.await) });
+                    Some(Ok((k, r))) => {
+                        let (steps, result) = r.into_parts();
+                        let mut sessions = self.handle.sessions.lock().unwrap();
+                        let s = match sessions.session_mut(&k) {
+                            Some(s) => s,
+                            None => continue,
+                        };
+                        s.add_recovery_steps(steps);
+                        match result {
+                            Ok(a) => {
+                                log::info!(key = %k, addr = %a, "replacement is up");
+                                s.set_status(Status::Up);
+                                s.set_address(a);
+                                s.clear_pings();
+                                s.reset_attempts();
+                                self.events.emit(event(k, s, Change::Recovered));
+                            }
+                            Err(e) => {
+                                let step = s.last_recovery().last().map(|o| o.step);
+                                log::warn!(key = %k, err = %e, ?step, "replacing session failed");
+                                let failures = s.add_failed_attempt();
+                                if s.retry_policy().gives_up_after(failures) {
+                                    log::error!(key = %k, failures, "giving up replacing session");
+                                    s.set_given_up(true);
+                                    let reason = e.to_string();
+                                    self.events.emit(event(k, s, Change::Failed { failures, reason }));
+                                    continue;
+                                }
+                                let delay = s.retry_policy().delay(failures);
+                                let f = s.replacement(s.address().clone());
+                                log::info!(key = %k, ?delay, "replacing session");
+                                self.replacements.spawn(async move {
+                                    tokio::time::sleep(delay).await;
+                                    (k, f.await)
+                                });
+                            }
                         }
                     }
-                    Some(Ok((k, Ok(a)))) => {
-                        let mut sessions = self.sessions.lock().unwrap();
+                },
+                r = self.rotations.join_next(), if !self.rotations.is_empty() => match r {
+                    None                  => log::debug!("no rotations"),
+                    Some(Err(e))          => log::error!("task failed: {e:?}"),
+                    Some(Ok((k, r)))      => {
+                        let (_, result) = r.into_parts();
+                        let mut sessions = self.handle.sessions.lock().unwrap();
                         if let Some(s) = sessions.session_mut(&k) {
-                            log::info!(key = %k, addr = %a, "replacement is up");
-                            s.set_status(Status::Up);
-                            s.set_address(a);
-                            s.clear_pings();
+                            s.set_rotating(false);
+                            match result {
+                                Ok(a) => {
+                                    log::info!(key = %k, addr = %a, "session rotated");
+                                    s.set_address(a);
+                                    s.clear_pings();
+                                }
+                                Err(e) => {
+                                    log::warn!(key = %k, err = %e, "rotating session failed");
+                                    s.postpone_rotation(ROTATION_RETRY)
+                                }
+                            }
                         }
                     }
                 },
                 Some(m) = rx.recv() => {
-                    if let Some(s) = self.sessions.lock().unwrap().session_mut(&m.key) {
-                        if s.pings().contains(&m.ping) {
-                            log::trace!(key = %m.key, ping = %m.ping, "recv pong");
-                            s.clear_pings()
+                    if let Some(s) = self.handle.sessions.lock().unwrap().session_mut(&m.key) {
+                        if s.pong(m.ping) {
+                            log::trace!(key = %m.key, ping = %m.ping, rtt = ?s.rtt(), "recv pong");
                         }
                     }
                 },
-                else => break
+                _ = self.handle.wakeup.notified() => break,
             }
         }
     }
 }
 
+/// A change of the state of a session.
+enum Change {
+    Down,
+    Recovered,
+    Failed { failures: u32, reason: String },
+}
+
+/// The event reporting a change of the state of a session, depending on
+/// whether it belongs to a forwarder or to a monitored secure channel.
+fn event(key: Key, s: &Session, change: Change) -> NodeEvent {
+    let session = key.to_string();
+    let description = s.description().map(|d| d.to_string());
+    let address = s.address().to_string();
+    match (s.get::<MultiAddr>(SECURE_CHANNEL), change) {
+        (Some(to), Change::Down) => NodeEvent::SecureChannelDown {
+            session,
+            description,
+            address,
+            to: to.to_string(),
+        },
+        (Some(to), Change::Recovered) => NodeEvent::SecureChannelRecovered {
+            session,
+            description,
+            address,
+            to: to.to_string(),
+        },
+        (Some(to), Change::Failed { failures, reason }) => NodeEvent::SecureChannelFailed {
+            session,
+            description,
+            address,
+            to: to.to_string(),
+            failures,
+            reason,
+        },
+        (None, Change::Down) => NodeEvent::ForwarderDown {
+            session,
+            description,
+            address,
+        },
+        (None, Change::Recovered) => NodeEvent::ForwarderRecovered {
+            session,
+            description,
+            address,
+        },
+        (None, Change::Failed { failures, reason }) => NodeEvent::ForwarderFailed {
+            session,
+            description,
+            alias: s.get::<String>(ALIAS).cloned(),
+            address,
+            failures,
+            reason,
+        },
+    }
+}
+
+/// Aborts the task when dropped, so that stopping the supervisor also
+/// stops the medic.
+struct AbortOnDrop<T>(JoinHandle<T>);
+
+impl<T> AbortOnDrop<T> {
+    async fn join(mut self) -> Result<T, JoinError> {
+        (&mut self.0).await
+    }
+}
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort()
+    }
+}
+
 impl Message {
     fn new(k: Key) -> Self {
         Self {
@@ -209,3 +550,49 @@ impl Worker for Collector {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::str::FromStr;
+    use core::sync::atomic::AtomicUsize;
+
+    fn counted(s: &mut Session, n: &Arc<AtomicUsize>) {
+        let n = n.clone();
+        s.set_replacement(move |addr| {
+            n.fetch_add(1, Ordering::Relaxed);
+            Box::pin(async move { Recovery::new().finish(Ok(addr)) })
+        })
+    }
+
+    #[ockam_macros::test]
+    async fn liveness_checks_replace_failing_sessions(ctx: &mut Context) -> Result<(), Error> {
+        let medic = Medic::new(Events::new("n".into(), None));
+        let handle = medic.handle();
+        handle.set_delay(Duration::from_millis(20));
+
+        let addr = MultiAddr::from_str("/service/echo").unwrap();
+        let (healthy, broken) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+
+        let mut s = Session::new(addr.clone());
+        counted(&mut s, &healthy);
+        s.set_liveness_check(|_: &MultiAddr| -> Probe { Box::pin(async { Ok(()) }) });
+        handle.sessions().lock().unwrap().add(s);
+
+        let mut s = Session::new(addr);
+        counted(&mut s, &broken);
+        s.set_liveness_check(|_: &MultiAddr| -> Probe {
+            Box::pin(async { Err(Error::new(Origin::Application, Kind::Invalid, "down")) })
+        });
+        handle.sessions().lock().unwrap().add(s);
+
+        let child = ctx.new_detached(Address::random_local()).await?;
+        let task = AbortOnDrop(tokio::spawn(medic.start(child)));
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        drop(task);
+
+        assert_eq!(healthy.load(Ordering::Relaxed), 0);
+        assert!(broken.load(Ordering::Relaxed) > 0);
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,64 @@
+//! Checks of the liveness of sessions.
+//!
+//! By default the medic pings the echo service through a session. A
+//! [`LivenessCheck`] takes the place of the ping, e.g. to probe the TCP
+//! endpoint the session depends on, or to ask the application whether
+//! the session still does its job.
+
+use core::future::Future;
+use core::pin::Pin;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::Error;
+use ockam_multiaddr::MultiAddr;
+
+pub type Probe = Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
+
+/// A check of whether a session is alive.
+///
+/// The medic probes a session once per check interval. A probe which
+/// fails, or does not finish within the interval, counts as a ping
+/// without a response, so a session is replaced once several probes in
+/// a row failed, as with pings.
+pub trait LivenessCheck: Send + Sync + 'static {
+    /// Probe the session whose current address is `addr`.
+    fn probe(&self, addr: &MultiAddr) -> Probe;
+}
+
+/// Functions returning a probe are checks, e.g. application callbacks.
+impl<F> LivenessCheck for F
+where
+    F: Fn(&MultiAddr) -> Probe + Send + Sync + 'static,
+{
+    fn probe(&self, addr: &MultiAddr) -> Probe {
+        self(addr)
+    }
+}
+
+/// Probe a TCP endpoint, e.g. the relay of a session, by connecting to it.
+#[derive(Debug, Clone)]
+pub struct TcpProbe {
+    target: MultiAddr,
+}
+
+impl TcpProbe {
+    /// Probe the TCP part of `target`, e.g. `/dnsaddr/relay.example/tcp/4000`.
+    pub fn new(target: MultiAddr) -> Self {
+        Self { target }
+    }
+}
+
+impl LivenessCheck for TcpProbe {
+    fn probe(&self, _: &MultiAddr) -> Probe {
+        let target = self.target.clone();
+        Box::pin(async move {
+            match crate::relays::probe(&target).await {
+                Some(_) => Ok(()),
+                None => Err(Error::new(
+                    Origin::Transport,
+                    Kind::NotFound,
+                    format!("{target} is not reachable"),
+                )),
+            }
+        })
+    }
+}
@@ -2,17 +2,124 @@ use core::any::Any;
 use core::fmt;
 use core::future::Future;
 use core::pin::Pin;
+use core::time::Duration;
 use minicbor::bytes::ByteArray;
 use minicbor::{Decode, Encode};
-use ockam_core::compat::collections::HashMap;
+use ockam_core::compat::collections::{HashMap, VecDeque};
 use ockam_core::compat::rand;
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
 use ockam_core::Error;
 use ockam_multiaddr::MultiAddr;
+use ockam_node::tokio::time::Instant;
+use std::time::SystemTime;
 use tracing as log;
 
-pub type Replacement = Pin<Box<dyn Future<Output = Result<MultiAddr, Error>> + Send>>;
+use super::LivenessCheck;
 
+/// Number of recoveries remembered per session.
+const MAX_RECOVERIES: usize = 1024;
+
+pub type Replacement = Pin<Box<dyn Future<Output = Recovery> + Send>>;
+
+/// A step of the replacement of a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Step {
+    /// Look up the current address of the project
+    ResolveProject,
+    /// Create a new secure channel to the remote node
+    SecureChannel,
+    /// Register the forwarder at the remote node
+    Forwarder,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::ResolveProject => f.write_str("resolve-project"),
+            Step::SecureChannel => f.write_str("secure-channel"),
+            Step::Forwarder => f.write_str("forwarder"),
+        }
+    }
+}
+
+/// The outcome of one step of a replacement.
+#[derive(Debug, Clone)]
+pub struct StepOutcome {
+    pub step: Step,
+    /// The error of the step, if it failed
+    pub error: Option<String>,
+}
+
+/// The outcome of a replacement attempt.
+///
+/// The steps which were attempted are recorded, so that a step failing
+/// repeatedly, e.g. registering a forwarder through a new secure channel,
+/// can be told apart from the others.
 #[derive(Debug)]
+pub struct Recovery {
+    steps: Vec<StepOutcome>,
+    result: Result<MultiAddr, Error>,
+}
+
+/// How failed replacements of a session are retried.
+///
+/// The delay before a retry doubles after each failed attempt, from
+/// `initial_delay` up to `max_delay`. With jitter, a random delay between
+/// half and all of it is used, so that nodes which lost their sessions at
+/// the same time do not retry in lockstep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Delay before the first retry
+    pub initial_delay: Duration,
+    /// Maximum delay between two attempts
+    pub max_delay: Duration,
+    /// Number of failed attempts after which the medic gives up, if any.
+    ///
+    /// A session it gave up on stays down until its recovery is requested.
+    pub max_attempts: Option<u32>,
+    /// Randomize the delays
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(120),
+            max_attempts: None,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay before retrying after `failures` failed attempts.
+    pub fn delay(&self, failures: u32) -> Duration {
+        self.delay_with(failures, rand::random())
+    }
+
+    /// The delay before retrying, where `r` in `[0, 1)` picks the jitter.
+    fn delay_with(&self, failures: u32, r: f64) -> Duration {
+        let exp = failures.saturating_sub(1).min(31);
+        let d = self
+            .initial_delay
+            .saturating_mul(1 << exp)
+            .min(self.max_delay);
+        if self.jitter {
+            d / 2 + d.mul_f64(r / 2.0)
+        } else {
+            d
+        }
+    }
+
+    /// Whether to give up after `failures` failed attempts.
+    pub fn gives_up_after(&self, failures: u32) -> bool {
+        self.max_attempts.map(|n| failures >= n).unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Default)]
 pub struct Sessions {
     map: HashMap<Key, Session>,
 }
@@ -23,7 +130,21 @@ pub struct Session {
     meta: HashMap<&'static str, Box<dyn Any + Send>>,
     status: Status,
     replace: Box<dyn Fn(MultiAddr) -> Replacement + Send>,
-    pings: Vec<Ping>,
+    liveness: Option<Arc<dyn LivenessCheck>>,
+    pings: Vec<(Ping, Instant)>,
+    rtt: Option<Duration>,
+    last_pong: Option<SystemTime>,
+    recoveries: VecDeque<Instant>,
+    description: Option<String>,
+    recovery_requested: bool,
+    last_recovery: Vec<StepOutcome>,
+    step_failures: HashMap<Step, u64>,
+    max_age: Option<Duration>,
+    rotate_at: Option<Instant>,
+    rotating: bool,
+    retry: RetryPolicy,
+    failed_attempts: u32,
+    given_up: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -39,6 +160,8 @@ impl fmt::Debug for Session {
             .field("addr", &self.addr)
             .field("status", &self.status)
             .field("pings", &self.pings)
+            .field("rtt", &self.rtt)
+            .field("recoveries", &self.recoveries.len())
             .finish()
     }
 }
@@ -62,7 +185,17 @@ impl Sessions {
         k
     }
 
-    #[allow(unused)]
+    pub fn remove(&mut self, k: &Key) -> Option<Session> {
+        let s = self.map.remove(k)?;
+        log::debug! {
+            target: "ockam_api::session",
+            key = %k,
+            addr = %s.address(),
+            "session removed"
+        }
+        Some(s)
+    }
+
     pub fn session(&self, k: &Key) -> Option<&Session> {
         self.map.get(k)
     }
@@ -71,7 +204,6 @@ impl Sessions {
         self.map.get_mut(k)
     }
 
-    #[allow(unused)]
     pub fn iter(&self) -> impl Iterator<Item = (&Key, &Session)> + '_ {
         self.map.iter()
     }
@@ -88,8 +220,22 @@ impl Session {
             addr,
             meta: HashMap::new(),
             status: Status::Up,
-            replace: Box::new(move |r| Box::pin(async move { Ok(r) })),
+            replace: Box::new(move |r| Box::pin(async move { Recovery::new().finish(Ok(r)) })),
+            liveness: None,
             pings: Vec::new(),
+            rtt: None,
+            last_pong: None,
+            recoveries: VecDeque::new(),
+            description: None,
+            recovery_requested: false,
+            last_recovery: Vec::new(),
+            step_failures: HashMap::new(),
+            max_age: None,
+            rotate_at: None,
+            rotating: false,
+            retry: RetryPolicy::default(),
+            failed_attempts: 0,
+            given_up: false,
         }
     }
 
@@ -101,8 +247,12 @@ impl Session {
         &self.addr
     }
 
+    /// Set the address of the session.
+    ///
+    /// A new address starts a new lifetime for the session.
     pub fn set_address(&mut self, a: MultiAddr) {
-        self.addr = a
+        self.addr = a;
+        self.rotate_at = self.max_age.map(|d| Instant::now() + d)
     }
 
     pub fn status(&self) -> Status {
@@ -117,6 +267,13 @@ impl Session {
         (self.replace)(a)
     }
 
+    /// Set how the session is replaced.
+    ///
+    /// Replacements make before they break: the new session must be up
+    /// before the resources of the current one, e.g. its secure channel,
+    /// are released. A failed replacement leaves the current session as
+    /// it was, so that it can be tried again, or keeps working if it was
+    /// only rotated.
     pub fn set_replacement<F>(&mut self, f: F)
     where
         F: Fn(MultiAddr) -> Replacement + Send + 'static,
@@ -124,6 +281,86 @@ impl Session {
         self.replace = Box::new(f)
     }
 
+    /// How the session is checked, if not by pinging the echo service.
+    pub fn liveness_check(&self) -> Option<Arc<dyn LivenessCheck>> {
+        self.liveness.clone()
+    }
+
+    /// Check the session with `c` instead of pinging the echo service
+    /// through it.
+    pub fn set_liveness_check(&mut self, c: impl LivenessCheck) {
+        self.liveness = Some(Arc::new(c))
+    }
+
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
+    /// Rotate the session, using its replacement, once it is older than `d`.
+    pub fn set_max_age(&mut self, d: Duration) {
+        self.max_age = Some(d);
+        self.rotate_at = Some(Instant::now() + d)
+    }
+
+    /// Whether the session is up, answers its pings and should be rotated.
+    pub fn rotation_due(&self) -> bool {
+        self.status == Status::Up
+            && !self.rotating
+            && self.pings.is_empty()
+            && self.rotate_at.map(|t| t <= Instant::now()).unwrap_or(false)
+    }
+
+    pub fn is_rotating(&self) -> bool {
+        self.rotating
+    }
+
+    pub fn set_rotating(&mut self, r: bool) {
+        self.rotating = r
+    }
+
+    /// Try the rotation again after `d`.
+    pub fn postpone_rotation(&mut self, d: Duration) {
+        if self.max_age.is_some() {
+            self.rotate_at = Some(Instant::now() + d)
+        }
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    pub fn set_retry_policy(&mut self, p: RetryPolicy) {
+        self.retry = p
+    }
+
+    /// Number of replacement attempts which failed since the session
+    /// went down.
+    pub fn failed_attempts(&self) -> u32 {
+        self.failed_attempts
+    }
+
+    /// Record a failed replacement attempt, returning the number of
+    /// failed attempts.
+    pub fn add_failed_attempt(&mut self) -> u32 {
+        self.failed_attempts = self.failed_attempts.saturating_add(1);
+        self.failed_attempts
+    }
+
+    /// Whether the medic gave up replacing the session.
+    pub fn has_given_up(&self) -> bool {
+        self.given_up
+    }
+
+    pub fn set_given_up(&mut self, g: bool) {
+        self.given_up = g
+    }
+
+    /// Forget the failed attempts, e.g. once a replacement is up.
+    pub fn reset_attempts(&mut self) {
+        self.failed_attempts = 0;
+        self.given_up = false
+    }
+
     pub fn put<T: Send + 'static>(&mut self, key: &'static str, data: T) {
         self.meta.insert(key, Box::new(data));
     }
@@ -132,17 +369,141 @@ impl Session {
         self.meta.get(key).and_then(|data| data.downcast_ref())
     }
 
-    pub fn pings(&self) -> &[Ping] {
-        &self.pings
+    /// What this session is used for, e.g. "forwarder to /project/default".
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn set_description(&mut self, d: impl Into<String>) {
+        self.description = Some(d.into())
+    }
+
+    /// Number of pings sent without a response.
+    pub fn pending_pings(&self) -> usize {
+        self.pings.len()
     }
 
     pub fn add_ping(&mut self, p: Ping) {
-        self.pings.push(p);
+        self.pings.push((p, Instant::now()));
+    }
+
+    /// Handle the response to ping `p`.
+    ///
+    /// Returns `false` if `p` is not a pending ping of this session.
+    /// Otherwise the round-trip time is updated and all pings are cleared.
+    pub fn pong(&mut self, p: Ping) -> bool {
+        match self.pings.iter().find(|(q, _)| *q == p) {
+            Some((_, sent)) => {
+                self.rtt = Some(sent.elapsed());
+                self.last_pong = Some(SystemTime::now());
+                self.pings.clear();
+                true
+            }
+            None => false,
+        }
     }
 
     pub fn clear_pings(&mut self) {
         self.pings.clear()
     }
+
+    /// The round-trip time of the last answered ping.
+    pub fn rtt(&self) -> Option<Duration> {
+        self.rtt
+    }
+
+    /// When a ping was last answered.
+    pub fn last_pong(&self) -> Option<SystemTime> {
+        self.last_pong
+    }
+
+    /// Record that the session broke and is being recovered.
+    pub fn add_recovery(&mut self) {
+        if self.recoveries.len() == MAX_RECOVERIES {
+            self.recoveries.pop_front();
+        }
+        self.recoveries.push_back(Instant::now());
+    }
+
+    /// Ask for the session to be recovered without waiting for it to
+    /// become unresponsive.
+    pub fn request_recovery(&mut self) {
+        self.recovery_requested = true
+    }
+
+    /// Whether a recovery was requested, clearing the request.
+    pub fn take_recovery_request(&mut self) -> bool {
+        core::mem::take(&mut self.recovery_requested)
+    }
+
+    /// Record the steps of a replacement attempt.
+    pub fn add_recovery_steps(&mut self, steps: Vec<StepOutcome>) {
+        for s in &steps {
+            if s.error.is_some() {
+                *self.step_failures.entry(s.step).or_default() += 1
+            }
+        }
+        self.last_recovery = steps
+    }
+
+    /// The steps of the last replacement attempt.
+    pub fn last_recovery(&self) -> &[StepOutcome] {
+        &self.last_recovery
+    }
+
+    /// Number of times a step failed since the session was created.
+    pub fn step_failures(&self, s: Step) -> u64 {
+        self.step_failures.get(&s).copied().unwrap_or(0)
+    }
+
+    /// Number of recoveries remembered.
+    pub fn recoveries(&self) -> usize {
+        self.recoveries.len()
+    }
+
+    /// Number of recoveries since `t`.
+    pub fn recoveries_since(&self, t: Instant) -> usize {
+        self.recoveries
+            .iter()
+            .rev()
+            .take_while(|r| **r >= t)
+            .count()
+    }
+}
+
+impl Recovery {
+    pub fn new() -> Self {
+        Self {
+            steps: Vec::new(),
+            result: Err(Error::new(
+                Origin::Node,
+                Kind::Internal,
+                "replacement did not finish",
+            )),
+        }
+    }
+
+    /// Record the outcome of a step.
+    pub fn step<T>(&mut self, step: Step, r: &Result<T, Error>) {
+        let error = r.as_ref().err().map(|e| e.to_string());
+        self.steps.push(StepOutcome { step, error })
+    }
+
+    pub fn finish(mut self, r: Result<MultiAddr, Error>) -> Self {
+        self.result = r;
+        self
+    }
+
+    /// Split into the steps and the result.
+    pub fn into_parts(self) -> (Vec<StepOutcome>, Result<MultiAddr, Error>) {
+        (self.steps, self.result)
+    }
+}
+
+impl Default for Recovery {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Encode, Decode)]
@@ -157,7 +518,7 @@ impl Key {
 
 impl fmt::Display for Key {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", hex::encode(&*self.0))
+        write!(f, "{}", hex::encode(*self.0))
     }
 }
 
@@ -176,3 +537,78 @@ impl fmt::Display for Ping {
         write!(f, "{:x}", self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovery_steps() {
+        let mut s = Session::new("/service/prev".parse().unwrap());
+
+        // The secure channel is created but the forwarder is not
+        let mut r = Recovery::new();
+        r.step(Step::SecureChannel, &Ok(()));
+        let e = || Error::new(Origin::Node, Kind::Timeout, "timeout");
+        r.step::<()>(Step::Forwarder, &Err(e()));
+        let (steps, result) = r.finish(Err(e())).into_parts();
+        s.add_recovery_steps(steps);
+        assert!(result.is_err());
+        assert!(s.last_recovery()[0].error.is_none());
+        assert_eq!(s.last_recovery()[1].error.as_deref(), Some("timeout"));
+        assert_eq!(s.step_failures(Step::Forwarder), 1);
+        assert_eq!(s.step_failures(Step::SecureChannel), 0);
+
+        // Failures add up across attempts
+        let mut r = Recovery::new();
+        r.step::<()>(Step::Forwarder, &Err(e()));
+        s.add_recovery_steps(r.into_parts().0);
+        assert_eq!(s.last_recovery().len(), 1);
+        assert_eq!(s.step_failures(Step::Forwarder), 2);
+    }
+
+    #[test]
+    fn rotation_due() {
+        let mut s = Session::new("/service/a".parse().unwrap());
+        assert!(!s.rotation_due());
+        s.set_max_age(Duration::ZERO);
+        assert!(s.rotation_due());
+
+        // Unresponsive sessions are not rotated
+        s.add_ping(Ping::new());
+        assert!(!s.rotation_due());
+        s.clear_pings();
+
+        s.set_rotating(true);
+        assert!(!s.rotation_due());
+        s.set_rotating(false);
+
+        s.set_max_age(Duration::from_secs(3600));
+        assert!(!s.rotation_due());
+    }
+
+    #[test]
+    fn retry_backoff() {
+        let p = RetryPolicy {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+            max_attempts: Some(5),
+            jitter: false,
+        };
+        let delays: Vec<u64> = (1..=6).map(|n| p.delay(n).as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 10, 10]);
+        assert_eq!(p.delay(u32::MAX), Duration::from_secs(10));
+        assert!(!p.gives_up_after(4));
+        assert!(p.gives_up_after(5));
+        assert!(!RetryPolicy::default().gives_up_after(u32::MAX));
+
+        // Jitter keeps between half and all of the delay
+        let p = RetryPolicy { jitter: true, ..p };
+        assert_eq!(p.delay_with(3, 0.0), Duration::from_secs(2));
+        assert_eq!(p.delay_with(3, 0.5), Duration::from_secs(3));
+        for n in 1..10 {
+            let d = p.delay(n);
+            assert!(d >= p.delay_with(n, 0.0) && d <= p.delay_with(n, 1.0));
+        }
+    }
+}
@@ -3,14 +3,24 @@ use crate::error::ApiError;
 use anyhow::anyhow;
 use core::str::FromStr;
 use ockam::{Address, Error, TCP};
-use ockam_core::{Route, LOCAL};
-use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp};
-use ockam_multiaddr::{MultiAddr, Protocol};
+use ockam_core::{Route, TransportType, LOCAL};
+#[cfg(feature = "quic")]
+use ockam_multiaddr::proto::Quic;
+#[cfg(feature = "udp")]
+use ockam_multiaddr::proto::Udp;
+use ockam_multiaddr::proto::{
+    Contact, Cost, DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp,
+};
+use ockam_multiaddr::{MultiAddr, ProtoValue, Protocol};
+#[cfg(feature = "quic")]
+use ockam_transport_quic::QUIC;
+#[cfg(feature = "udp")]
+use ockam_transport_udp::UDP;
 use std::net::{SocketAddrV4, SocketAddrV6};
 
 /// Go through a multiaddr and remove all instances of
-/// `/node/<whatever>` out of it and replaces it with a fully
-/// qualified address to the target
+/// `/node/<whatever>` and `/contact/<whatever>` out of it and replaces
+/// them with a fully qualified address to the target
 pub fn clean_multiaddr(
     input: &MultiAddr,
     lookup: &ConfigLookup,
@@ -44,6 +54,13 @@ pub fn clean_multiaddr(
                 // No substitution done here. It will be done later by `clean_projects_multiaddr`.
                 new_ma.push_back_value(&p).ok()?
             }
+            Contact::CODE => {
+                let name = p.cast::<Contact>()?;
+                let contact = lookup.get_contact(&name)?;
+                // Remember the contact, so that its identity can be pinned.
+                lookup_meta.contact.push_back(name.to_string());
+                new_ma.try_extend(contact.route.iter()).ok()?
+            }
             Space::CODE => panic!("/space/ substitutions are not supported yet!"),
             _ => new_ma.push_back_value(&p).ok()?,
         }
@@ -60,25 +77,22 @@ pub fn multiaddr_to_route(ma: &MultiAddr) -> Option<Route> {
         match p.code() {
             Ip4::CODE => {
                 let ip4 = p.cast::<Ip4>()?;
-                let tcp = it.next()?.cast::<Tcp>()?;
-                let add = Address::new(TCP, SocketAddrV4::new(*ip4, *tcp).to_string());
+                let (transport, port) = transport_port(&it.next()?)?;
+                let add = Address::new(transport, SocketAddrV4::new(*ip4, port).to_string());
                 rb = rb.append(add)
             }
             Ip6::CODE => {
                 let ip6 = p.cast::<Ip6>()?;
-                let tcp = it.next()?.cast::<Tcp>()?;
-                let add = Address::new(TCP, SocketAddrV6::new(*ip6, *tcp, 0, 0).to_string());
+                let (transport, port) = transport_port(&it.next()?)?;
+                let add = Address::new(transport, SocketAddrV6::new(*ip6, port, 0, 0).to_string());
                 rb = rb.append(add)
             }
             DnsAddr::CODE => {
                 let host = p.cast::<DnsAddr>()?;
-                if let Some(p) = it.peek() {
-                    if p.code() == Tcp::CODE {
-                        let tcp = p.cast::<Tcp>()?;
-                        rb = rb.append(Address::new(TCP, format!("{}:{}", &*host, *tcp)));
-                        let _ = it.next();
-                        continue;
-                    }
+                if let Some((transport, port)) = it.peek().and_then(transport_port) {
+                    rb = rb.append(Address::new(transport, format!("{}:{}", &*host, port)));
+                    let _ = it.next();
+                    continue;
                 }
                 rb = rb.append(Address::new(TCP, &*host))
             }
@@ -91,6 +105,9 @@ pub fn multiaddr_to_route(ma: &MultiAddr) -> Option<Route> {
                 rb = rb.append(Address::new(LOCAL, &*local))
             }
 
+            // Costs annotate the route, they are not part of it.
+            Cost::CODE => continue,
+
             // If your code crashes here then the front-end CLI isn't
             // properly calling `clean_multiaddr` before passing it to
             // the backend
@@ -105,20 +122,46 @@ pub fn multiaddr_to_route(ma: &MultiAddr) -> Option<Route> {
     Some(rb.into())
 }
 
-/// Try to convert a multiaddr to an Ockam Address
+/// The transport and port of a `/tcp/...`, `/udp/...` or `/quic/...` component.
+fn transport_port(p: &ProtoValue) -> Option<(TransportType, u16)> {
+    match p.code() {
+        Tcp::CODE => Some((TCP, *p.cast::<Tcp>()?)),
+        #[cfg(feature = "udp")]
+        Udp::CODE => Some((UDP, *p.cast::<Udp>()?)),
+        #[cfg(feature = "quic")]
+        Quic::CODE => Some((QUIC, *p.cast::<Quic>()?)),
+        _ => None,
+    }
+}
+
+/// Split a multi-address after each of its secure channel listeners, and
+/// convert the parts to the routes from one listener to the next.
+///
+/// Components after the last listener are the route from it to another
+/// listener, e.g. a `/service/api` reached through a forwarder.
+pub fn multiaddr_to_onion_routes(ma: &MultiAddr) -> Option<Vec<Route>> {
+    let mut parts: Vec<MultiAddr> = Vec::new();
+    let mut part = MultiAddr::default();
+    for p in ma.iter() {
+        part.push_back_value(&p).ok()?;
+        if p.code() == Secure::CODE {
+            parts.push(std::mem::take(&mut part));
+        }
+    }
+    if !part.is_empty() {
+        parts.push(part)
+    }
+    parts.iter().map(multiaddr_to_route).collect()
+}
+
 pub fn multiaddr_to_addr(ma: &MultiAddr) -> Option<Address> {
     let mut it = ma.iter().peekable();
     let p = it.next()?;
     match p.code() {
         DnsAddr::CODE => {
             let host = p.cast::<DnsAddr>()?;
-            if let Some(p) = it.peek() {
-                if p.code() == Tcp::CODE {
-                    let tcp = p.cast::<Tcp>()?;
-                    return Some(Address::new(TCP, format!("{}:{}", &*host, *tcp)));
-                }
-            }
-            None
+            let (transport, port) = transport_port(it.peek()?)?;
+            Some(Address::new(transport, format!("{}:{}", &*host, port)))
         }
         Service::CODE => {
             let local = p.cast::<Service>()?;
@@ -214,6 +257,52 @@ pub fn is_local_node(ma: &MultiAddr) -> anyhow::Result<bool> {
     }
 }
 
+#[test]
+fn multiaddr_to_route_skips_costs() {
+    let addr: MultiAddr = "/ip4/127.0.0.1/tcp/4000/cost/10/service/echo"
+        .parse()
+        .unwrap();
+    let route = multiaddr_to_route(&addr).unwrap();
+    assert_eq!(route.to_string(), "1#127.0.0.1:4000 => 0#echo");
+}
+
+#[cfg(feature = "udp")]
+#[test]
+fn multiaddr_to_route_supports_udp() {
+    let addr: MultiAddr = "/ip4/127.0.0.1/udp/4000/service/echo".parse().unwrap();
+    let route = multiaddr_to_route(&addr).unwrap();
+    assert_eq!(route.to_string(), "2#127.0.0.1:4000 => 0#echo");
+
+    let addr: MultiAddr = "/dnsaddr/localhost/udp/4000".parse().unwrap();
+    let route = multiaddr_to_route(&addr).unwrap();
+    assert_eq!(route.to_string(), "2#localhost:4000");
+}
+
+#[cfg(feature = "quic")]
+#[test]
+fn multiaddr_to_route_supports_quic() {
+    let addr: MultiAddr = "/ip4/127.0.0.1/quic/4000/service/echo".parse().unwrap();
+    let route = multiaddr_to_route(&addr).unwrap();
+    assert_eq!(route.to_string(), "5#127.0.0.1:4000 => 0#echo");
+}
+
+#[test]
+fn multiaddr_to_onion_routes_splits_at_listeners() {
+    let addr: MultiAddr = "/ip4/127.0.0.1/tcp/4000/secure/api/service/relay/secure/api"
+        .parse()
+        .unwrap();
+    let routes = multiaddr_to_onion_routes(&addr).unwrap();
+    let routes: Vec<String> = routes.iter().map(|r| r.to_string()).collect();
+    assert_eq!(routes, ["1#127.0.0.1:4000 => 0#api", "0#relay => 0#api"]);
+
+    let addr: MultiAddr = "/secure/api/service/forward_to_n2/service/api"
+        .parse()
+        .unwrap();
+    let routes = multiaddr_to_onion_routes(&addr).unwrap();
+    let routes: Vec<String> = routes.iter().map(|r| r.to_string()).collect();
+    assert_eq!(routes, ["0#api", "0#forward_to_n2 => 0#api"]);
+}
+
 #[test]
 fn clean_multiaddr_simple() {
     use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
@@ -235,3 +324,34 @@ fn clean_multiaddr_simple() {
     let new_route = multiaddr_to_route(&new_addr).unwrap();
     println!("{:#?}", new_route);
 }
+
+#[test]
+fn clean_multiaddr_contact() {
+    use crate::config::lookup::ContactLookup;
+
+    let addr: MultiAddr = "/contact/alice/service/api".parse().unwrap();
+
+    let lookup = {
+        let mut map = ConfigLookup::new();
+        map.set_contact(
+            "alice",
+            ContactLookup {
+                route: "/dnsaddr/alice.example.com/tcp/4000".parse().unwrap(),
+                identity_id: "P6c20e814b56579306f55c64e8747e6c1b4a53d9a3f4ca83c252cc2fbfc72fa94"
+                    .try_into()
+                    .unwrap(),
+            },
+        );
+        map
+    };
+
+    let (new_addr, meta) = clean_multiaddr(&addr, &lookup).unwrap();
+    assert_eq!(
+        new_addr.to_string(),
+        "/dnsaddr/alice.example.com/tcp/4000/service/api"
+    );
+    assert_eq!(meta.contact, ["alice"]);
+
+    let unknown: MultiAddr = "/contact/bob/service/api".parse().unwrap();
+    assert!(clean_multiaddr(&unknown, &lookup).is_none());
+}
@@ -7,7 +7,8 @@ use minicbor::{Decoder, Encode};
 use models::*;
 use ockam_core::api::{Error, Id, Method, Request, Response, Status};
 use ockam_core::vault::{
-    AsymmetricVault, Hasher, KeyId, SecretVault, Signature, Signer, SymmetricVault, Verifier,
+    AsymmetricVault, Hasher, KeyId, SecretKey, SecretVault, Signature, Signer, SymmetricVault,
+    Verifier,
 };
 use ockam_core::CowStr;
 use ockam_core::{Result, Routed, Worker};
@@ -150,7 +151,7 @@ impl VaultService {
                     let key_id = match args.secret() {
                         Some(secret) => {
                             self.vault
-                                .secret_import(secret.as_ref(), attributes)
+                                .secret_import(SecretKey::new(secret.to_vec()), attributes)
                                 .await?
                         }
                         None => self.vault.secret_generate(attributes).await?,
@@ -1,3 +1,5 @@
+use core::fmt;
+
 use minicbor::{Decode, Encode};
 use ockam_core::vault::SecretAttributes;
 use ockam_core::CowBytes;
@@ -5,7 +7,7 @@ use ockam_core::CowBytes;
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
 
-#[derive(Debug, Clone, Encode, Decode)]
+#[derive(Clone, Encode, Decode)]
 #[rustfmt::skip]
 #[cbor(map)]
 pub struct CreateSecretRequest<'a> {
@@ -15,6 +17,15 @@ pub struct CreateSecretRequest<'a> {
     #[b(2)] secret: Option<CowBytes<'a>>,
 }
 
+impl fmt::Debug for CreateSecretRequest<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CreateSecretRequest")
+            .field("attributes", &self.attributes)
+            .field("secret", &self.secret.as_ref().map(|_| "<secret omitted>"))
+            .finish()
+    }
+}
+
 impl<'a> CreateSecretRequest<'a> {
     /// Path to the main storage file
     pub fn attributes(&self) -> &SecretAttributes {
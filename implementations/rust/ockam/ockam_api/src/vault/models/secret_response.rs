@@ -1,3 +1,5 @@
+use core::fmt;
+
 use minicbor::{Decode, Encode};
 use ockam_core::vault::{PublicKey, SecretAttributes};
 use ockam_core::{CowBytes, CowStr};
@@ -28,7 +30,7 @@ impl<'a> CreateSecretResponse<'a> {
     }
 }
 
-#[derive(Debug, Clone, Encode, Decode)]
+#[derive(Clone, Encode, Decode)]
 #[rustfmt::skip]
 #[cbor(map)]
 pub struct ExportSecretResponse<'a> {
@@ -37,6 +39,14 @@ pub struct ExportSecretResponse<'a> {
     #[b(1)] secret: CowBytes<'a>,
 }
 
+impl fmt::Debug for ExportSecretResponse<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExportSecretResponse")
+            .field("secret", &"<secret omitted>")
+            .finish()
+    }
+}
+
 impl<'a> ExportSecretResponse<'a> {
     pub fn secret(&self) -> &[u8] {
         &self.secret
@@ -6,6 +6,7 @@ use ockam::route;
 use ockam::vault::Vault;
 use ockam_api::authenticator::direct;
 use ockam_api::authenticator::direct::types::Enroller;
+use ockam_api::capability::{Capabilities, Capability, CAPABILITIES};
 use ockam_core::Result;
 use ockam_identity::{IdentityIdentifier, PublicIdentity, TrustEveryonePolicy};
 use ockam_node::Context;
@@ -40,7 +41,7 @@ async fn credential(ctx: &mut Context) -> Result<()> {
         .await?;
 
     // Add the member via the enroller's connection:
-    let mut c = direct::Client::new(route![e2a, "auth"], ctx).await?;
+    let mut c = direct::Client::new(route![e2a.clone(), "auth"], ctx).await?;
 
     // Enroller is not configured -> fail
     assert!(c.add_member(member.identifier().clone()).await.is_err());
@@ -56,7 +57,7 @@ async fn credential(ctx: &mut Context) -> Result<()> {
         .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
         .await?;
 
-    let mut c = direct::Client::new(route![m2a, "auth"], ctx).await?;
+    let mut c = direct::Client::new(route![m2a.clone(), "auth"], ctx).await?;
 
     // Get a fresh member credential and verify its validity:
     let cred = c.credential().await?;
@@ -70,6 +71,26 @@ async fn credential(ctx: &mut Context) -> Result<()> {
         Some(b"project42".as_slice()),
         data.attributes().get("project_id")
     );
+    assert_eq!(None, data.attributes().get(CAPABILITIES));
+
+    // Restrict what the member may create
+    let mut c = direct::Client::new(route![e2a, "auth"], ctx).await?;
+    let capabilities = Capabilities::new(vec![
+        Capability::Forwarder("device-42".to_string()),
+        Capability::Outlet("22".to_string()),
+    ]);
+    c.add_member_with_capabilities(member.identifier().clone(), &capabilities)
+        .await?;
+
+    let mut c = direct::Client::new(route![m2a, "auth"], ctx).await?;
+    let cred = c.credential().await?;
+    let data = pkey
+        .verify_credential(&cred, member.identifier(), &Vault::create())
+        .await?;
+    assert_eq!(
+        Some(b"forwarder:device-42,outlet:22".as_slice()),
+        data.attributes().get(CAPABILITIES)
+    );
 
     ctx.stop().await
 }
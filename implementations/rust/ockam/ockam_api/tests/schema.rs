@@ -1,4 +1,5 @@
 use cddl_cat::validate_cbor_bytes;
+use core::time::Duration;
 use ockam_core::api::SCHEMA;
 use ockam_core::api::{Error, Id, Method, Request, Response, Status};
 use quickcheck::{quickcheck, Arbitrary, Gen, TestResult};
@@ -12,6 +13,7 @@ const METHODS: &[Method] = &[
 ];
 
 const STATUS: &[Status] = &[
+    Status::Processing,
     Status::Ok,
     Status::BadRequest,
     Status::NotFound,
@@ -31,11 +33,17 @@ struct Er(Error<'static>);
 
 impl Arbitrary for Req {
     fn arbitrary(g: &mut Gen) -> Self {
-        Req(Request::new(
-            *g.choose(METHODS).unwrap(),
-            String::arbitrary(g),
-            bool::arbitrary(g),
-        ))
+        let mut r = Request::builder(*g.choose(METHODS).unwrap(), String::arbitrary(g));
+        if bool::arbitrary(g) {
+            r = r.timeout(Duration::from_millis(u64::arbitrary(g)))
+        }
+        if bool::arbitrary(g) {
+            r = r.progress(bool::arbitrary(g))
+        }
+        if bool::arbitrary(g) {
+            r = r.body(())
+        }
+        Req(r.into_parts().0)
     }
 }
 
@@ -1,10 +1,11 @@
 use minicbor::Decoder;
 use ockam_api::vault::models::{
-    CreateSecretRequest, CreateSecretResponse, PublicKeyResponse, SignRequest, SignResponse,
-    VerifyRequest, VerifyResponse,
+    CreateSecretRequest, CreateSecretResponse, ExportSecretResponse, PublicKeyResponse,
+    SignRequest, SignResponse, VerifyRequest, VerifyResponse,
 };
 use ockam_api::vault::VaultService;
 use ockam_core::api::{Request, Response, Status};
+use ockam_core::vault::test_support::assert_redacted;
 use ockam_core::vault::{SecretAttributes, SecretPersistence, SecretType};
 use ockam_core::{route, Result};
 use ockam_node::Context;
@@ -117,3 +118,14 @@ async fn full_flow(ctx: &mut Context) -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn secrets_are_not_debug_formatted() {
+    let secret = [0x5a; 32];
+    let attributes = SecretAttributes::new(SecretType::X25519, SecretPersistence::Ephemeral, 32);
+    let req = CreateSecretRequest::new_import(attributes, &secret[..]);
+    assert_redacted(&req, &secret);
+    let res = ExportSecretResponse::new(&secret[..]);
+    assert_redacted(&res, &secret);
+    assert_eq!(res.secret(), &secret[..]);
+}
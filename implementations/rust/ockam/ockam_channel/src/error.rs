@@ -18,16 +18,21 @@ pub enum SecureChannelError {
     InvalidHubResponse,
     /// Invalid LocalInfo type
     InvalidLocalInfoType,
+    /// The other side did not agree on a hybrid key exchange.
+    KeyAgreementRefused,
+    /// Hybrid key exchanges need the `hybrid-kem` feature.
+    KeyAgreementUnsupported,
 }
 
 impl From<SecureChannelError> for Error {
     fn from(e: SecureChannelError) -> Self {
         use SecureChannelError::*;
         let kind = match e {
-            KeyExchange | KeyExchangeNotComplete => Kind::Protocol,
+            KeyExchange | KeyExchangeNotComplete | KeyAgreementRefused => Kind::Protocol,
             InvalidInternalState | InvalidNonce | InvalidHubResponse | InvalidLocalInfoType => {
                 Kind::Invalid
             }
+            KeyAgreementUnsupported => Kind::Unsupported,
         };
 
         Self::new(Origin::Channel, kind, e)
@@ -44,6 +49,12 @@ impl core::fmt::Display for SecureChannelError {
             Self::KeyExchangeNotComplete => "key exchange process did not complete.".fmt(f),
             Self::InvalidHubResponse => "invalid response received from the Hub.".fmt(f),
             Self::InvalidLocalInfoType => "invalid LocalInfo type".fmt(f),
+            Self::KeyAgreementRefused => {
+                "the other side did not agree on a hybrid key exchange.".fmt(f)
+            }
+            Self::KeyAgreementUnsupported => {
+                "hybrid key exchanges need the hybrid-kem feature.".fmt(f)
+            }
         }
     }
 }
@@ -0,0 +1,188 @@
+#[cfg(feature = "hybrid-kem")]
+use crate::mlkem;
+use crate::{SecureChannelError, SecureChannelVault};
+use ockam_core::compat::vec::Vec;
+use ockam_core::vault::{KeyId, SecretAttributes, SecretKey, SecretPersistence, SecretType};
+use ockam_core::Result;
+
+/// How the two sides of a secure channel agree on its keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyAgreement {
+    /// The Diffie-Hellman exchanges of the key exchanger alone
+    Classical,
+    /// In addition to the Diffie-Hellman exchanges, an ML-KEM-768 (Kyber)
+    /// shared secret is mixed into the keys, which then stay confidential
+    /// as long as one of the two is not broken.
+    ///
+    /// The initiator sends an encapsulation key with its first handshake
+    /// message and the responder a ciphertext with its answer. Both are
+    /// covered by the handshake hash. Responders answer initiators asking
+    /// for it even if they are `Classical`, but a `Hybrid` side refuses
+    /// channels without it.
+    ///
+    /// Without the `hybrid-kem` feature, channels with a `Hybrid` side
+    /// fail, and `Classical` responders ignore the encapsulation key.
+    Hybrid,
+}
+
+impl Default for KeyAgreement {
+    fn default() -> Self {
+        Self::Classical
+    }
+}
+
+/// Progress of the post-quantum part of a key agreement
+pub(crate) struct HybridHandshake {
+    key_agreement: KeyAgreement,
+    /// Our decapsulation key, until the responder's ciphertext arrives
+    decapsulation_key: Option<SecretKey>,
+    /// Shared secret of the encapsulation, to be mixed into the keys
+    shared_secret: Option<SecretKey>,
+}
+
+impl HybridHandshake {
+    pub(crate) fn new(key_agreement: KeyAgreement) -> Self {
+        Self {
+            key_agreement,
+            decapsulation_key: None,
+            shared_secret: None,
+        }
+    }
+
+    /// The encapsulation key an initiator sends with its first message
+    pub(crate) fn start(&mut self) -> Result<Option<Vec<u8>>> {
+        match self.key_agreement {
+            KeyAgreement::Classical => Ok(None),
+            #[cfg(feature = "hybrid-kem")]
+            KeyAgreement::Hybrid => {
+                let (encapsulation_key, decapsulation_key) = mlkem::generate();
+                self.decapsulation_key = Some(SecretKey::new(decapsulation_key));
+                Ok(Some(encapsulation_key))
+            }
+            #[cfg(not(feature = "hybrid-kem"))]
+            KeyAgreement::Hybrid => Err(SecureChannelError::KeyAgreementUnsupported.into()),
+        }
+    }
+
+    /// Handle the KEM data of the other side before answering it,
+    /// returning the ciphertext a responder sends back
+    pub(crate) fn respond(&mut self, peer_data: Option<Vec<u8>>) -> Result<Option<Vec<u8>>> {
+        match (self.decapsulation_key.take(), peer_data) {
+            // The initiator, receiving the ciphertext
+            #[cfg(feature = "hybrid-kem")]
+            (Some(decapsulation_key), Some(ciphertext)) => {
+                let shared_secret = mlkem::decapsulate(decapsulation_key.as_ref(), &ciphertext)
+                    .ok_or(SecureChannelError::KeyExchange)?;
+                self.shared_secret = Some(SecretKey::new(shared_secret.to_vec()));
+                Ok(None)
+            }
+            // The responder, receiving the encapsulation key
+            #[cfg(feature = "hybrid-kem")]
+            (None, Some(encapsulation_key)) => {
+                let (shared_secret, ciphertext) = mlkem::encapsulate_random(&encapsulation_key)
+                    .ok_or(SecureChannelError::KeyExchange)?;
+                self.shared_secret = Some(SecretKey::new(shared_secret.to_vec()));
+                Ok(Some(ciphertext))
+            }
+            #[cfg(not(feature = "hybrid-kem"))]
+            _ if self.key_agreement == KeyAgreement::Hybrid => {
+                Err(SecureChannelError::KeyAgreementUnsupported.into())
+            }
+            #[cfg(feature = "hybrid-kem")]
+            _ if self.key_agreement == KeyAgreement::Hybrid => {
+                Err(SecureChannelError::KeyAgreementRefused.into())
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The shared secret to mix into the keys, if one was agreed on
+    pub(crate) fn finish(&mut self) -> Result<Option<SecretKey>> {
+        match self.shared_secret.take() {
+            None if self.key_agreement == KeyAgreement::Hybrid => {
+                Err(SecureChannelError::KeyAgreementRefused.into())
+            }
+            shared_secret => Ok(shared_secret),
+        }
+    }
+}
+
+const HYBRID_KEY_INFO: &[u8] = b"ockam hybrid x25519 mlkem768";
+
+/// Derive the key that replaces `key` once `shared_secret` is agreed on,
+/// with HKDF over both: `key`, from the Diffie-Hellman exchanges, is the
+/// salt and the ML-KEM shared secret the input keying material. The new
+/// key stays secret as long as one of them does. `key` is destroyed
+pub(crate) async fn hybrid_key<V: SecureChannelVault>(
+    vault: &V,
+    key: &KeyId,
+    shared_secret: SecretKey,
+) -> Result<KeyId> {
+    let attributes = vault.secret_attributes_get(key).await?;
+    let buffer = |len: usize| {
+        SecretAttributes::new(SecretType::Buffer, SecretPersistence::Ephemeral, len as u32)
+    };
+
+    let classical = vault.secret_export(key).await?;
+    let salt_attributes = buffer(classical.as_ref().len());
+    let salt = vault.secret_import(classical, salt_attributes).await?;
+    let ikm_attributes = buffer(shared_secret.as_ref().len());
+    let ikm = vault.secret_import(shared_secret, ikm_attributes).await?;
+    let mut keys = vault
+        .hkdf_sha256(&salt, HYBRID_KEY_INFO, Some(&ikm), vec![attributes])
+        .await?;
+    vault.secret_destroy(salt).await?;
+    vault.secret_destroy(ikm).await?;
+    vault.secret_destroy(key.clone()).await?;
+
+    keys.pop()
+        .ok_or_else(|| SecureChannelError::InvalidInternalState.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handshake(initiator: KeyAgreement, responder: KeyAgreement) -> Result<bool> {
+        let mut initiator = HybridHandshake::new(initiator);
+        let mut responder = HybridHandshake::new(responder);
+        let ciphertext = responder.respond(initiator.start()?)?;
+        initiator.respond(ciphertext)?;
+        match (initiator.finish()?, responder.finish()?) {
+            (Some(a), Some(b)) => {
+                assert_eq!(a.as_ref(), b.as_ref());
+                Ok(true)
+            }
+            (None, None) => Ok(false),
+            _ => panic!("only one side has a shared secret"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "hybrid-kem")]
+    fn initiators_choose_the_key_agreement() {
+        use KeyAgreement::*;
+        assert!(handshake(Hybrid, Hybrid).unwrap());
+        assert!(handshake(Hybrid, Classical).unwrap());
+        assert!(!handshake(Classical, Classical).unwrap());
+        assert!(handshake(Classical, Hybrid).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "hybrid-kem")]
+    fn hybrid_initiators_need_an_answer() {
+        let mut initiator = HybridHandshake::new(KeyAgreement::Hybrid);
+        assert!(initiator.start().unwrap().is_some());
+        assert!(initiator.respond(None).is_err());
+        assert!(initiator.finish().is_err());
+    }
+
+    #[test]
+    #[cfg(not(feature = "hybrid-kem"))]
+    fn hybrid_key_agreements_are_unsupported() {
+        use KeyAgreement::*;
+        assert!(!handshake(Classical, Classical).unwrap());
+        assert!(handshake(Classical, Hybrid).is_err());
+        assert!(handshake(Hybrid, Classical).is_err());
+    }
+}
@@ -24,7 +24,11 @@ extern crate alloc;
 
 mod common;
 mod error;
+mod key_agreement;
 mod local_info;
+#[cfg(feature = "hybrid-kem")]
+mod mlkem;
+mod rekey;
 mod secure_channel;
 mod secure_channel_decryptor;
 mod secure_channel_encryptor;
@@ -33,7 +37,11 @@ mod traits;
 
 pub use common::*;
 pub use error::*;
+pub use key_agreement::KeyAgreement;
+pub(crate) use key_agreement::*;
 pub use local_info::*;
+pub use rekey::RekeyPolicy;
+pub(crate) use rekey::*;
 pub use secure_channel::*;
 pub use secure_channel_decryptor::*;
 pub(crate) use secure_channel_encryptor::*;
@@ -42,7 +50,7 @@ pub use traits::*;
 
 #[cfg(test)]
 mod tests {
-    use crate::SecureChannel;
+    use crate::{KeyAgreement, RekeyPolicy, SecureChannel};
     use ockam_core::compat::string::{String, ToString};
     use ockam_core::{AsyncTryClone, Result, Route};
     use ockam_key_exchange_core::NewKeyExchanger;
@@ -66,6 +74,8 @@ mod tests {
             Route::new().append("secure_channel_listener"),
             None,
             new_key_exchanger.initiator().await?,
+            RekeyPolicy::default(),
+            KeyAgreement::default(),
             vault,
         )
         .await?;
@@ -79,4 +89,90 @@ mod tests {
         assert_eq!(ctx.receive::<String>().await?, test_msg);
         ctx.stop().await
     }
+
+    #[ockam_macros::test]
+    async fn channel_keeps_working_across_rekeys(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let new_key_exchanger = XXNewKeyExchanger::new(vault.async_try_clone().await?);
+        SecureChannel::create_listener_extended(
+            ctx,
+            "secure_channel_listener".to_string(),
+            new_key_exchanger.async_try_clone().await?,
+            vault.async_try_clone().await?,
+        )
+        .await?;
+        let initiator = SecureChannel::create_extended(
+            ctx,
+            Route::new().append("secure_channel_listener"),
+            None,
+            new_key_exchanger.initiator().await?,
+            RekeyPolicy::new(Some(100), None),
+            KeyAgreement::default(),
+            vault,
+        )
+        .await?;
+
+        for i in 0..20 {
+            let test_msg = format!("Hello, channel {}", i);
+            ctx.send(
+                Route::new().append(initiator.address()).append("app"),
+                test_msg.clone(),
+            )
+            .await?;
+            assert_eq!(ctx.receive::<String>().await?, test_msg);
+        }
+        ctx.stop().await
+    }
+
+    #[cfg(feature = "hybrid-kem")]
+    #[ockam_macros::test]
+    async fn hybrid_channel(ctx: &mut Context) -> Result<()> {
+        use crate::SecureChannelListener;
+        use core::time::Duration;
+        use tokio::time::timeout;
+
+        let vault = Vault::create();
+        let new_key_exchanger = XXNewKeyExchanger::new(vault.async_try_clone().await?);
+        let listener = SecureChannelListener::new(
+            new_key_exchanger.async_try_clone().await?,
+            vault.async_try_clone().await?,
+        )
+        .with_key_agreement(KeyAgreement::Hybrid);
+        ctx.start_worker("secure_channel_listener", listener)
+            .await?;
+
+        // Listeners requiring a hybrid key agreement refuse the others
+        let classical = SecureChannel::create_extended(
+            ctx,
+            Route::new().append("secure_channel_listener"),
+            None,
+            new_key_exchanger.initiator().await?,
+            RekeyPolicy::default(),
+            KeyAgreement::Classical,
+            vault.async_try_clone().await?,
+        );
+        assert!(timeout(Duration::from_secs(1), classical).await.is_err());
+
+        let initiator = SecureChannel::create_extended(
+            ctx,
+            Route::new().append("secure_channel_listener"),
+            None,
+            new_key_exchanger.initiator().await?,
+            RekeyPolicy::new(Some(100), None),
+            KeyAgreement::Hybrid,
+            vault,
+        )
+        .await?;
+
+        for i in 0..5 {
+            let test_msg = format!("Hello, hybrid channel {}", i);
+            ctx.send(
+                Route::new().append(initiator.address()).append("app"),
+                test_msg.clone(),
+            )
+            .await?;
+            assert_eq!(ctx.receive::<String>().await?, test_msg);
+        }
+        ctx.stop().await
+    }
 }
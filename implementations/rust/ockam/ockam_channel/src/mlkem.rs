@@ -0,0 +1,79 @@
+//! ML-KEM-768, the module-lattice key encapsulation mechanism of FIPS 203
+//! standardized from Kyber.
+//!
+//! This wraps the RustCrypto `ml-kem` crate, whose arithmetic runs in
+//! constant time and which is checked against the FIPS 203 known-answer
+//! tests, to add a post-quantum shared secret to the key exchange of a
+//! secure channel, see [`KeyAgreement`](crate::KeyAgreement).
+
+use core::convert::TryFrom;
+use ml_kem::kem::{Decapsulate, Encapsulate};
+use ml_kem::{Ciphertext, Encoded, EncodedSizeUser, KemCore, MlKem768};
+use ockam_core::compat::rand::thread_rng;
+use ockam_core::compat::vec::Vec;
+
+type DecapsulationKey = <MlKem768 as KemCore>::DecapsulationKey;
+type EncapsulationKey = <MlKem768 as KemCore>::EncapsulationKey;
+
+/// A fresh encapsulation key and decapsulation key.
+pub(crate) fn generate() -> (Vec<u8>, Vec<u8>) {
+    let (dk, ek) = MlKem768::generate(&mut thread_rng());
+    (ek.as_bytes().to_vec(), dk.as_bytes().to_vec())
+}
+
+/// Encapsulate a fresh shared secret for the holder of `ek`: returns the
+/// shared secret and the ciphertext, or `None` if `ek` is not an
+/// encapsulation key
+pub(crate) fn encapsulate_random(ek: &[u8]) -> Option<([u8; 32], Vec<u8>)> {
+    let ek = Encoded::<EncapsulationKey>::try_from(ek).ok()?;
+    let ek = EncapsulationKey::from_bytes(&ek);
+    let (c, shared) = ek.encapsulate(&mut thread_rng()).ok()?;
+    Some((shared.into(), c.to_vec()))
+}
+
+/// Returns the shared secret of the ciphertext `c`, or a pseudorandom one
+/// if it was tampered with, or `None` if the key or the ciphertext don't
+/// have the lengths of ML-KEM-768
+pub(crate) fn decapsulate(dk: &[u8], c: &[u8]) -> Option<[u8; 32]> {
+    let dk = Encoded::<DecapsulationKey>::try_from(dk).ok()?;
+    let dk = DecapsulationKey::from_bytes(&dk);
+    let c = Ciphertext::<MlKem768>::try_from(c).ok()?;
+    let shared = dk.decapsulate(&c).ok()?;
+    Some(shared.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn both_sides_share_a_secret() {
+        let (ek, dk) = generate();
+        assert_eq!(ek.len(), 1184);
+        assert_eq!(dk.len(), 2400);
+
+        let (shared, c) = encapsulate_random(&ek).unwrap();
+        assert_eq!(c.len(), 1088);
+        assert_eq!(decapsulate(&dk, &c), Some(shared));
+
+        // Each run gives other keys and secrets
+        let (ek2, dk2) = generate();
+        let (shared2, c2) = encapsulate_random(&ek2).unwrap();
+        assert_ne!(shared, shared2);
+        assert_eq!(decapsulate(&dk2, &c2), Some(shared2));
+    }
+
+    #[test]
+    fn tampered_ciphertexts_are_rejected() {
+        let (ek, dk) = generate();
+        let (shared, mut c) = encapsulate_random(&ek).unwrap();
+        c[10] ^= 1;
+        let rejected = decapsulate(&dk, &c).unwrap();
+        assert_ne!(rejected, shared);
+        // Implicit rejection is deterministic
+        assert_eq!(decapsulate(&dk, &c), Some(rejected));
+
+        assert!(encapsulate_random(&[0; 10]).is_none());
+        assert!(decapsulate(&dk, &c[1..]).is_none());
+    }
+}
@@ -0,0 +1,232 @@
+use crate::{SecureChannelError, SecureChannelVault};
+use core::time::Duration;
+use ockam_core::compat::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use ockam_core::vault::{KeyId, SecretKey};
+use ockam_core::{Decodable, Encodable, Message, Result};
+use serde::{Deserialize, Serialize};
+
+/// When the symmetric keys of a secure channel are replaced.
+///
+/// Each side replaces its sending key once one of the limits is reached,
+/// deriving the new key from the old one as the Noise `REKEY` function
+/// does, and the other side follows. Keys are only replaced if both sides
+/// announced during the key exchange that they are able to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RekeyPolicy {
+    /// Number of plaintext bytes encrypted with a key before it is replaced
+    pub max_bytes: Option<u64>,
+    /// How long a key is used before it is replaced. Only enforced with `std`
+    pub max_age: Option<Duration>,
+}
+
+impl RekeyPolicy {
+    /// Default number of bytes encrypted with a key
+    pub const DEFAULT_MAX_BYTES: u64 = 1 << 30;
+    /// Default lifetime of a key
+    pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+    /// Constructor
+    pub fn new(max_bytes: Option<u64>, max_age: Option<Duration>) -> Self {
+        Self { max_bytes, max_age }
+    }
+
+    /// Only replace keys when their nonces run out
+    pub fn never() -> Self {
+        Self::new(None, None)
+    }
+}
+
+impl Default for RekeyPolicy {
+    fn default() -> Self {
+        Self::new(Some(Self::DEFAULT_MAX_BYTES), Some(Self::DEFAULT_MAX_AGE))
+    }
+}
+
+/// Name under which a side announces that it can follow rekeying
+const REKEY_FEATURE: &str = "rekey";
+
+/// Payload sent with every key exchange message. Implementations that
+/// predate it send an empty payload and ignore the one they receive
+#[derive(Serialize, Deserialize, Debug, Message)]
+struct KeyExchangePayload {
+    features: Vec<String>,
+    /// Encapsulation key or ciphertext of a hybrid key agreement, see
+    /// [`KeyAgreement`](crate::KeyAgreement)
+    kem: Option<Vec<u8>>,
+}
+
+pub(crate) fn key_exchange_payload(kem: Option<Vec<u8>>) -> Result<Vec<u8>> {
+    KeyExchangePayload {
+        features: vec![REKEY_FEATURE.to_string()],
+        kem,
+    }
+    .encode()
+}
+
+/// Whether the other side announced that it can follow rekeying
+pub(crate) fn peer_can_rekey(payload: &[u8]) -> bool {
+    KeyExchangePayload::decode(payload)
+        .map(|p| p.features.iter().any(|f| f == REKEY_FEATURE))
+        .unwrap_or(false)
+}
+
+/// The hybrid key agreement data the other side sent, if any
+pub(crate) fn peer_kem(payload: &[u8]) -> Option<Vec<u8>> {
+    KeyExchangePayload::decode(payload).ok().and_then(|p| p.kem)
+}
+
+/// Once rekeying is agreed on, the upper bits of the nonce carry the
+/// number of times the key was replaced. Before the first replacement the
+/// nonces are the same as without rekeying
+const EPOCH_SHIFT: u32 = 48;
+
+/// Last nonce counter usable with a key
+pub(crate) const MAX_COUNTER: u64 = (1 << EPOCH_SHIFT) - 1;
+
+/// How many keys a received message may skip, e.g. when the messages
+/// that crossed the thresholds were lost
+pub(crate) const MAX_EPOCH_SKIP: u16 = 16;
+
+pub(crate) fn join_nonce(epoch: u16, counter: u64) -> u64 {
+    ((epoch as u64) << EPOCH_SHIFT) | counter
+}
+
+pub(crate) fn split_nonce(nonce: u64) -> (u16, u64) {
+    ((nonce >> EPOCH_SHIFT) as u16, nonce & MAX_COUNTER)
+}
+
+/// Progress of the key used to send messages towards its replacement
+pub(crate) struct Rekeying {
+    policy: RekeyPolicy,
+    epoch: u16,
+    bytes: u64,
+    #[cfg(feature = "std")]
+    since: std::time::Instant,
+}
+
+impl Rekeying {
+    pub(crate) fn new(policy: RekeyPolicy) -> Self {
+        Self {
+            policy,
+            epoch: 0,
+            bytes: 0,
+            #[cfg(feature = "std")]
+            since: std::time::Instant::now(),
+        }
+    }
+
+    pub(crate) fn epoch(&self) -> u16 {
+        self.epoch
+    }
+
+    /// Whether the key must be replaced before sending with `counter`
+    pub(crate) fn is_due(&self, counter: u64) -> bool {
+        if counter == 0 {
+            return false;
+        }
+        if counter > MAX_COUNTER || self.policy.max_bytes.map_or(false, |max| self.bytes >= max) {
+            return true;
+        }
+        #[cfg(feature = "std")]
+        if self
+            .policy
+            .max_age
+            .map_or(false, |max| self.since.elapsed() >= max)
+        {
+            return true;
+        }
+        false
+    }
+
+    pub(crate) fn next_epoch(&mut self) -> Result<()> {
+        self.epoch = self
+            .epoch
+            .checked_add(1)
+            .ok_or(SecureChannelError::InvalidNonce)?;
+        self.bytes = 0;
+        #[cfg(feature = "std")]
+        {
+            self.since = std::time::Instant::now();
+        }
+        Ok(())
+    }
+
+    pub(crate) fn add_bytes(&mut self, bytes: usize) {
+        self.bytes = self.bytes.saturating_add(bytes as u64);
+    }
+}
+
+/// Derive the key that replaces `key`: the first bytes of the encryption
+/// of zeros with the maximum nonce, which is never used for messages
+pub(crate) async fn rekey<V: SecureChannelVault>(vault: &V, key: &KeyId) -> Result<KeyId> {
+    let attributes = vault.secret_attributes_get(key).await?;
+    let length = attributes.length() as usize;
+
+    let mut nonce = [0u8; 12];
+    nonce[4..].copy_from_slice(&u64::MAX.to_be_bytes());
+
+    let zeros = vec![0u8; length];
+    let cipher_text = vault.aead_aes_gcm_encrypt(key, &zeros, &nonce, &[]).await?;
+
+    vault
+        .secret_import(SecretKey::new(cipher_text[..length].to_vec()), attributes)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::vault::{
+        SecretAttributes, SecretPersistence, SecretType, SecretVault, AES256_SECRET_LENGTH_U32,
+    };
+    use ockam_vault::Vault;
+
+    #[test]
+    fn nonce_keeps_epoch_and_counter_apart() {
+        assert_eq!(join_nonce(0, 41), 41);
+        assert_eq!(split_nonce(join_nonce(3, MAX_COUNTER)), (3, MAX_COUNTER));
+        assert!(join_nonce(1, 0) > join_nonce(0, MAX_COUNTER));
+    }
+
+    #[test]
+    fn old_peers_cannot_rekey() {
+        assert!(peer_can_rekey(&key_exchange_payload(None).unwrap()));
+        assert!(!peer_can_rekey(&[]));
+        assert_eq!(
+            peer_kem(&key_exchange_payload(Some(vec![1])).unwrap()),
+            Some(vec![1])
+        );
+        assert_eq!(peer_kem(&[]), None);
+    }
+
+    #[ockam_macros::test]
+    async fn both_sides_derive_the_same_key(ctx: &mut ockam_node::Context) -> Result<()> {
+        let attributes = SecretAttributes::new(
+            SecretType::Aes,
+            SecretPersistence::Ephemeral,
+            AES256_SECRET_LENGTH_U32,
+        );
+        let alice = Vault::create();
+        let bob = Vault::create();
+        let secret = vec![7u8; 32];
+        let alice_key = alice
+            .secret_import(SecretKey::new(secret.clone()), attributes)
+            .await?;
+        let bob_key = bob
+            .secret_import(SecretKey::new(secret), attributes)
+            .await?;
+
+        let alice_key = rekey(&alice, &alice_key).await?;
+        let bob_key = rekey(&bob, &bob_key).await?;
+
+        let alice_secret = alice.secret_export(&alice_key).await?;
+        let bob_secret = bob.secret_export(&bob_key).await?;
+        assert_eq!(alice_secret.as_ref(), bob_secret.as_ref());
+        assert_ne!(alice_secret.as_ref(), &[7u8; 32][..]);
+
+        ctx.stop().await
+    }
+}
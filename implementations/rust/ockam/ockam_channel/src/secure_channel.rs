@@ -1,6 +1,7 @@
 use crate::{
-    KeyExchangeCompleted, SecureChannelDecryptor, SecureChannelKeyExchanger, SecureChannelListener,
-    SecureChannelNewKeyExchanger, SecureChannelVault,
+    KeyAgreement, KeyExchangeCompleted, RekeyPolicy, SecureChannelDecryptor,
+    SecureChannelKeyExchanger, SecureChannelListener, SecureChannelNewKeyExchanger,
+    SecureChannelVault,
 };
 use ockam_core::compat::{rand::random, vec::Vec};
 use ockam_core::{Address, Result, Route};
@@ -14,6 +15,7 @@ use tracing::{debug, info};
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
 pub struct SecureChannelInfo {
     worker_address: Address,
+    decryptor_address: Address,
     auth_hash: [u8; 32],
 }
 
@@ -22,6 +24,10 @@ impl SecureChannelInfo {
     pub fn address(&self) -> Address {
         self.worker_address.clone()
     }
+    /// Return the address of the worker decrypting incoming messages.
+    pub fn decryptor_address(&self) -> Address {
+        self.decryptor_address.clone()
+    }
     /// Return the auth hash.
     pub fn auth_hash(&self) -> [u8; 32] {
         self.auth_hash
@@ -84,6 +90,8 @@ impl SecureChannel {
             route,
             None,
             new_key_exchanger.initiator().await?,
+            RekeyPolicy::default(),
+            KeyAgreement::default(),
             vault.async_try_clone().await?,
         )
         .await
@@ -95,6 +103,8 @@ impl SecureChannel {
         route: impl Into<Route>,
         custom_payload: Option<Vec<u8>>,
         key_exchanger: impl SecureChannelKeyExchanger,
+        rekey_policy: RekeyPolicy,
+        key_agreement: KeyAgreement,
         vault: impl SecureChannelVault,
     ) -> Result<SecureChannelInfo> {
         let address_remote: Address = random();
@@ -112,6 +122,8 @@ impl SecureChannel {
             Some(callback_address.clone()),
             route,
             custom_payload,
+            rekey_policy,
+            key_agreement,
             vault.async_try_clone().await?,
         )
         .await?;
@@ -127,6 +139,7 @@ impl SecureChannel {
 
         let info = SecureChannelInfo {
             worker_address: resp.address().clone(),
+            decryptor_address: address_remote,
             auth_hash: resp.auth_hash(),
         };
 
@@ -1,8 +1,16 @@
 use crate::{
-    ChannelKeys, CreateResponderChannelMessage, KeyExchangeCompleted, Role, SecureChannelEncryptor,
-    SecureChannelError, SecureChannelKeyExchanger, SecureChannelLocalInfo, SecureChannelVault,
+    hybrid_key, key_exchange_payload, peer_can_rekey, peer_kem, rekey, split_nonce, ChannelKeys,
+    CreateResponderChannelMessage, HybridHandshake, KeyAgreement, KeyExchangeCompleted,
+    RekeyPolicy, Rekeying, Role, SecureChannelEncryptor, SecureChannelError,
+    SecureChannelKeyExchanger, SecureChannelLocalInfo, SecureChannelVault, MAX_EPOCH_SKIP,
 };
-use ockam_core::compat::{boxed::Box, string::String, vec::Vec};
+use ockam_core::compat::{
+    boxed::Box,
+    string::String,
+    sync::{Arc, RwLock},
+    vec::Vec,
+};
+use ockam_core::vault::KeyId;
 use ockam_core::{async_trait, route};
 use ockam_core::{
     Address, Any, Decodable, LocalMessage, Result, Route, Routed, TransportMessage, Worker,
@@ -13,6 +21,15 @@ use tracing::{debug, info};
 struct DecryptorReadyState {
     keys: ChannelKeys,
     encryptor_address: Address,
+    remote_route: Arc<RwLock<Route>>,
+    /// Highest nonce received so far, only messages above it can update the route
+    last_nonce: Option<u64>,
+    /// Whether the other side replaces its key, see [`RekeyPolicy`]
+    peer_rekeys: bool,
+    /// Number of times the other side replaced its key
+    epoch: u16,
+    /// Key of the previous epoch, for messages sent just before the switch
+    previous_key: Option<KeyId>,
 }
 
 /// Secure Channel Decryptor
@@ -23,7 +40,12 @@ pub struct SecureChannelDecryptor<V: SecureChannelVault, K: SecureChannelKeyExch
     key_exchange_completed_callback_route: Option<Address>,
     state: Option<DecryptorReadyState>,
     remote_route: Route,
+    transport_route: Option<Route>,
     custom_payload: Option<Vec<u8>>,
+    rekey_policy: RekeyPolicy,
+    /// Whether the other side announced it can follow rekeying
+    peer_can_rekey: bool,
+    hybrid: HybridHandshake,
     vault: V,
     key_exchange_name: String,
 }
@@ -35,15 +57,23 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
         key_exchange_completed_callback_route: Option<Address>,
         remote_route: Route,
         custom_payload: Option<Vec<u8>>,
+        rekey_policy: RekeyPolicy,
+        key_agreement: KeyAgreement,
         vault: V,
     ) -> Result<Self> {
         let key_exchange_name = key_exchanger.name().await?;
+        // Everything before the remote listener is how we reach the other node
+        let transport_route = remote_route.clone().modify().pop_back().into();
         Ok(Self {
             role: Role::Initiator,
             key_exchanger: Some(key_exchanger),
             key_exchange_completed_callback_route,
             remote_route,
+            transport_route: Some(transport_route),
             custom_payload,
+            rekey_policy,
+            peer_can_rekey: false,
+            hybrid: HybridHandshake::new(key_agreement),
             vault,
             key_exchange_name,
             state: None,
@@ -63,20 +93,39 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
             key_exchanger: Some(key_exchanger),
             key_exchange_completed_callback_route,
             remote_route: route![],
+            transport_route: None,
             custom_payload: None,
+            rekey_policy: RekeyPolicy::default(),
+            peer_can_rekey: false,
+            hybrid: HybridHandshake::new(KeyAgreement::default()),
             vault,
             key_exchange_name,
             state: None,
         })
     }
 
+    /// Use given [`RekeyPolicy`] for the messages we send
+    pub fn with_rekey_policy(mut self, rekey_policy: RekeyPolicy) -> Self {
+        self.rekey_policy = rekey_policy;
+        self
+    }
+
+    /// Agree on the keys with the initiator using the given [`KeyAgreement`]
+    pub fn with_key_agreement(mut self, key_agreement: KeyAgreement) -> Self {
+        self.hybrid = HybridHandshake::new(key_agreement);
+        self
+    }
+
     /// Restore 12-byte nonce needed for AES GCM from 8 byte that we use for noise
-    fn convert_nonce_from_small(b: &[u8]) -> Result<[u8; 12]> {
+    fn convert_nonce_from_small(b: &[u8]) -> Result<(u64, [u8; 12])> {
         let bytes: [u8; 8] = b.try_into().map_err(|_| SecureChannelError::InvalidNonce)?;
 
         let nonce = u64::from_be_bytes(bytes);
 
-        Ok(SecureChannelEncryptor::<V>::convert_nonce_from_u64(nonce).1)
+        Ok((
+            nonce,
+            SecureChannelEncryptor::<V>::convert_nonce_from_u64(nonce).1,
+        ))
     }
 
     async fn send_key_exchange_payload(
@@ -110,22 +159,53 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
             .as_mut()
             .ok_or(SecureChannelError::InvalidInternalState)?;
 
+        let mut reply = msg.return_route();
         let transport_message = msg.into_transport_message();
         let payload = transport_message.payload;
         let payload = Vec::<u8>::decode(&payload)?;
 
-        let payload = {
+        let (small_nonce, payload) = {
             if payload.len() < 8 {
                 return Err(SecureChannelError::InvalidNonce.into());
             }
 
-            let nonce = Self::convert_nonce_from_small(&payload.as_slice()[..8])?;
+            let (small_nonce, nonce) = Self::convert_nonce_from_small(&payload.as_slice()[..8])?;
 
-            self.vault
-                .aead_aes_gcm_decrypt(&state.keys.key, &payload[8..], &nonce, &[])
-                .await?
+            let payload = if state.peer_rekeys {
+                Self::decrypt_rekeyed(&self.vault, state, small_nonce, &payload[8..], &nonce)
+                    .await?
+            } else {
+                self.vault
+                    .aead_aes_gcm_decrypt(&state.keys.key, &payload[8..], &nonce, &[])
+                    .await?
+            };
+
+            (small_nonce, payload)
         };
 
+        // The message is authentic, so if it is the newest one and came over a
+        // different connection, the other side has moved and we follow it.
+        // Replayed messages carry old nonces and can't redirect the channel
+        if state.last_nonce.map_or(true, |last| small_nonce > last) {
+            state.last_nonce = Some(small_nonce);
+
+            let mut remote_route = state.remote_route.write().unwrap();
+            let migrated_route: Route = reply
+                .modify()
+                .pop_back()
+                .append(remote_route.recipient())
+                .into();
+            if *remote_route != migrated_route {
+                info!(
+                    "SecureChannel at remote: {} migrating from {} to {}",
+                    ctx.address(),
+                    *remote_route,
+                    migrated_route
+                );
+                *remote_route = migrated_route;
+            }
+        }
+
         let mut transport_message = TransportMessage::decode(&payload)?;
 
         transport_message
@@ -140,6 +220,70 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
         ctx.forward(local_msg).await
     }
 
+    /// Decrypt with the key of the epoch carried by the nonce, following
+    /// the other side to a newer key once a message proves it uses it
+    async fn decrypt_rekeyed(
+        vault: &V,
+        state: &mut DecryptorReadyState,
+        small_nonce: u64,
+        cipher_text: &[u8],
+        nonce: &[u8],
+    ) -> Result<Vec<u8>> {
+        let (epoch, _) = split_nonce(small_nonce);
+
+        if epoch == state.epoch {
+            return vault
+                .aead_aes_gcm_decrypt(&state.keys.key, cipher_text, nonce, &[])
+                .await;
+        }
+
+        if epoch.checked_add(1) == Some(state.epoch) {
+            let key = state
+                .previous_key
+                .as_ref()
+                .ok_or(SecureChannelError::InvalidNonce)?;
+            return vault
+                .aead_aes_gcm_decrypt(key, cipher_text, nonce, &[])
+                .await;
+        }
+
+        if epoch < state.epoch || epoch - state.epoch > MAX_EPOCH_SKIP {
+            return Err(SecureChannelError::InvalidNonce.into());
+        }
+
+        // Keys of the epochs from ours up to the one of the message
+        let mut keys = vec![state.keys.key.clone()];
+        for _ in state.epoch..epoch {
+            let key = rekey(vault, &keys[keys.len() - 1]).await?;
+            keys.push(key);
+        }
+
+        let key = keys.pop().ok_or(SecureChannelError::InvalidInternalState)?;
+        match vault
+            .aead_aes_gcm_decrypt(&key, cipher_text, nonce, &[])
+            .await
+        {
+            Ok(plain_text) => {
+                let previous_key = keys.pop().ok_or(SecureChannelError::InvalidInternalState)?;
+                keys.extend(state.previous_key.replace(previous_key));
+                for old_key in keys {
+                    vault.secret_destroy(old_key).await?;
+                }
+                state.keys.key = key;
+                state.epoch = epoch;
+                Ok(plain_text)
+            }
+            Err(err) => {
+                // Not from the other side after all, forget what we derived
+                vault.secret_destroy(key).await?;
+                for derived_key in keys.into_iter().skip(1) {
+                    vault.secret_destroy(derived_key).await?;
+                }
+                Err(err)
+            }
+        }
+    }
+
     async fn handle_key_exchange(
         &mut self,
         ctx: &mut <Self as Worker>::Context,
@@ -161,10 +305,14 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
         // Update route to a remote
         self.remote_route = reply;
 
-        let _ = key_exchanger.handle_response(payload.as_slice()).await?;
+        let peer_payload = key_exchanger.handle_response(payload.as_slice()).await?;
+        self.peer_can_rekey |= peer_can_rekey(&peer_payload);
 
         if !key_exchanger.is_complete().await? {
-            let payload = key_exchanger.generate_request(&[]).await?;
+            let kem = self.hybrid.respond(peer_kem(&peer_payload))?;
+            let payload = key_exchanger
+                .generate_request(&key_exchange_payload(kem)?)
+                .await?;
             let is_now_complete = key_exchanger.is_complete().await?;
             self.send_key_exchange_payload(ctx, payload, false).await?;
 
@@ -178,41 +326,66 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
             .take()
             .ok_or(SecureChannelError::InvalidInternalState)?;
 
+        // Responders may only know which key exchange runs once it started
+        self.key_exchange_name = key_exchanger.name().await?;
         let keys = key_exchanger.finalize().await?;
+        let shared_secret = self.hybrid.finish()?;
+        let key_agreement = if shared_secret.is_some() {
+            KeyAgreement::Hybrid
+        } else {
+            KeyAgreement::Classical
+        };
+        let (encrypt_key, decrypt_key) = match shared_secret {
+            Some(shared_secret) => (
+                hybrid_key(&self.vault, keys.encrypt_key(), shared_secret.clone()).await?,
+                hybrid_key(&self.vault, keys.decrypt_key(), shared_secret).await?,
+            ),
+            None => (keys.encrypt_key().clone(), keys.decrypt_key().clone()),
+        };
 
         let address_local = Address::random_local();
+        let remote_route = Arc::new(RwLock::new(self.remote_route.clone()));
         let encryptor = SecureChannelEncryptor::new(
             ChannelKeys {
-                key: keys.encrypt_key().clone(),
+                key: encrypt_key,
                 nonce: 0,
             },
-            self.remote_route.clone(),
+            remote_route.clone(),
+            self.transport_route.take(),
+            self.peer_can_rekey
+                .then(|| Rekeying::new(self.rekey_policy)),
             self.vault.async_try_clone().await?,
         );
         ctx.start_worker(address_local.clone(), encryptor).await?;
 
         info!(
-            "Started SecureChannel {} at local: {}, remote: {}",
+            "Started SecureChannel {} at local: {}, remote: {}, key agreement: {:?}",
             self.role.role_str(),
             &address_local,
-            &ctx.address()
+            &ctx.address(),
+            key_agreement
         );
 
         // Notify interested worker about finished key exchange
         if let Some(r) = self.key_exchange_completed_callback_route.take() {
             ctx.send(
                 r,
-                KeyExchangeCompleted::new(address_local.clone(), *keys.h()),
+                KeyExchangeCompleted::new(address_local.clone(), keys.h().to_bytes()),
             )
             .await?;
         }
 
         self.state = Some(DecryptorReadyState {
             keys: ChannelKeys {
-                key: keys.decrypt_key().clone(),
+                key: decrypt_key,
                 nonce: 0,
             },
             encryptor_address: address_local,
+            remote_route,
+            last_nonce: None,
+            peer_rekeys: self.peer_can_rekey,
+            epoch: 0,
+            previous_key: None,
         });
 
         Ok(())
@@ -227,7 +400,10 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> Worker for SecureChann
     async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
         if let Role::Initiator = &self.role {
             if let Some(key_exchanger) = &mut self.key_exchanger {
-                let payload = key_exchanger.generate_request(&[]).await?;
+                let kem = self.hybrid.start()?;
+                let payload = key_exchanger
+                    .generate_request(&key_exchange_payload(kem)?)
+                    .await?;
 
                 self.send_key_exchange_payload(ctx, payload, true).await?;
             } else {
@@ -253,4 +429,14 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> Worker for SecureChann
 
         Ok(())
     }
+
+    async fn shutdown(&mut self, _ctx: &mut Self::Context) -> Result<()> {
+        if let Some(state) = self.state.take() {
+            self.vault.secret_destroy(state.keys.key).await?;
+            if let Some(previous_key) = state.previous_key {
+                self.vault.secret_destroy(previous_key).await?;
+            }
+        }
+        Ok(())
+    }
 }
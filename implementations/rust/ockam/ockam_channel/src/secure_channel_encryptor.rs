@@ -1,21 +1,40 @@
-use crate::{ChannelKeys, SecureChannelError, SecureChannelVault};
+use crate::{join_nonce, rekey, ChannelKeys, Rekeying, SecureChannelError, SecureChannelVault};
 use ockam_core::async_trait;
-use ockam_core::compat::{boxed::Box, vec::Vec};
+use ockam_core::compat::{
+    boxed::Box,
+    sync::{Arc, RwLock},
+    vec::Vec,
+};
 use ockam_core::{Any, Encodable, Result, Route, Routed, TransportMessage, Worker};
 use ockam_node::Context;
-use tracing::debug;
+use tracing::{debug, info};
 
 pub(crate) struct SecureChannelEncryptor<V: SecureChannelVault> {
     keys: ChannelKeys,
-    remote_route: Route,
+    /// Route to the remote decryptor, shared with our decryptor which
+    /// updates it when the other side shows up on a new connection
+    remote_route: Arc<RwLock<Route>>,
+    /// Route the channel was originally created over, without the
+    /// remote listener. Only initiators have one
+    transport_route: Option<Route>,
+    /// Only set if the other side can follow when we replace our key
+    rekeying: Option<Rekeying>,
     vault: V,
 }
 
 impl<V: SecureChannelVault> SecureChannelEncryptor<V> {
-    pub(crate) fn new(keys: ChannelKeys, remote_route: Route, vault: V) -> Self {
+    pub(crate) fn new(
+        keys: ChannelKeys,
+        remote_route: Arc<RwLock<Route>>,
+        transport_route: Option<Route>,
+        rekeying: Option<Rekeying>,
+        vault: V,
+    ) -> Self {
         Self {
             keys,
             remote_route,
+            transport_route,
+            rekeying,
             vault,
         }
     }
@@ -50,11 +69,30 @@ impl<V: SecureChannelVault> SecureChannelEncryptor<V> {
         let payload = msg.encode()?;
 
         let payload = {
-            let nonce = self.keys.nonce;
-
-            if nonce == u64::MAX {
-                return Err(SecureChannelError::InvalidNonce.into());
-            }
+            let nonce = match &mut self.rekeying {
+                Some(rekeying) => {
+                    if rekeying.is_due(self.keys.nonce) {
+                        rekeying.next_epoch()?;
+                        let key = rekey(&self.vault, &self.keys.key).await?;
+                        let old_key = core::mem::replace(&mut self.keys.key, key);
+                        self.vault.secret_destroy(old_key).await?;
+                        self.keys.nonce = 0;
+                        debug!(
+                            "SecureChannel at local: {} replaced its key, epoch {}",
+                            ctx.address(),
+                            rekeying.epoch()
+                        );
+                    }
+                    rekeying.add_bytes(payload.len());
+                    join_nonce(rekeying.epoch(), self.keys.nonce)
+                }
+                None => {
+                    if self.keys.nonce == u64::MAX {
+                        return Err(SecureChannelError::InvalidNonce.into());
+                    }
+                    self.keys.nonce
+                }
+            };
 
             self.keys.nonce += 1;
 
@@ -72,7 +110,36 @@ impl<V: SecureChannelVault> SecureChannelEncryptor<V> {
             res
         };
 
-        ctx.send(self.remote_route.clone(), payload).await
+        let remote_route = self.remote_route.read().unwrap().clone();
+        let err = match ctx.send(remote_route.clone(), payload.clone()).await {
+            Ok(()) => return Ok(()),
+            Err(err) => err,
+        };
+
+        // The connection we were using is gone. Initiators can re-establish it
+        // through the route the channel was created over, the keys stay the same
+        let transport_route = match &self.transport_route {
+            Some(transport_route) => transport_route,
+            None => return Err(err),
+        };
+        let migrated_route: Route = transport_route
+            .clone()
+            .modify()
+            .append(remote_route.recipient())
+            .into();
+        if migrated_route == remote_route {
+            return Err(err);
+        }
+
+        info!(
+            "SecureChannel at local: {} migrating from {} to {}",
+            ctx.address(),
+            remote_route,
+            migrated_route
+        );
+        *self.remote_route.write().unwrap() = migrated_route.clone();
+
+        ctx.send(migrated_route, payload).await
     }
 }
 
@@ -88,4 +155,8 @@ impl<V: SecureChannelVault> Worker for SecureChannelEncryptor<V> {
     ) -> Result<()> {
         self.handle_encrypt(ctx, msg).await
     }
+
+    async fn shutdown(&mut self, _ctx: &mut Self::Context) -> Result<()> {
+        self.vault.secret_destroy(self.keys.key.clone()).await
+    }
 }
@@ -1,4 +1,6 @@
-use crate::{SecureChannelDecryptor, SecureChannelNewKeyExchanger, SecureChannelVault};
+use crate::{
+    KeyAgreement, SecureChannelDecryptor, SecureChannelNewKeyExchanger, SecureChannelVault,
+};
 use ockam_core::async_trait;
 use ockam_core::compat::rand::random;
 use ockam_core::compat::{boxed::Box, vec::Vec};
@@ -13,6 +15,7 @@ use tracing::debug;
 /// and creates responder SecureChannels
 pub struct SecureChannelListener<V: SecureChannelVault, N: SecureChannelNewKeyExchanger> {
     new_key_exchanger: N,
+    key_agreement: KeyAgreement,
     vault: V,
 }
 
@@ -21,9 +24,16 @@ impl<V: SecureChannelVault, N: SecureChannelNewKeyExchanger> SecureChannelListen
     pub fn new(new_key_exchanger: N, vault: V) -> Self {
         Self {
             new_key_exchanger,
+            key_agreement: KeyAgreement::default(),
             vault,
         }
     }
+
+    /// Agree on the keys of the channels using the given [`KeyAgreement`]
+    pub fn with_key_agreement(mut self, key_agreement: KeyAgreement) -> Self {
+        self.key_agreement = key_agreement;
+        self
+    }
 }
 
 /// SecureChannelListener message wrapper.
@@ -78,7 +88,9 @@ impl<V: SecureChannelVault, N: SecureChannelNewKeyExchanger> Worker
 
         let key_exchanger = self.new_key_exchanger.responder().await?;
         let vault = self.vault.async_try_clone().await?;
-        let decryptor = SecureChannelDecryptor::new_responder(key_exchanger, None, vault).await?;
+        let decryptor = SecureChannelDecryptor::new_responder(key_exchanger, None, vault)
+            .await?
+            .with_key_agreement(self.key_agreement);
 
         ctx.start_worker(vec![address_remote.clone()], decryptor)
             .await?;
@@ -0,0 +1,74 @@
+use std::str::FromStr;
+
+use clap::Args;
+use ockam::identity::IdentityIdentifier;
+use ockam_api::config::lookup::ContactLookup;
+use ockam_multiaddr::proto::Contact;
+use ockam_multiaddr::{MultiAddr, Protocol};
+
+use crate::contact::{ContactCode, HELP_DETAIL};
+use crate::util::exitcode;
+use crate::{help, CommandGlobalOpts};
+
+/// Add a Contact
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct AddCommand {
+    /// Name of the contact (required unless given by --from-qr).
+    name: Option<String>,
+
+    /// Identity of the contact, pinned for the secure channels to it.
+    #[arg(
+        value_name = "IDENTIFIER",
+        value_parser = crate::identity::parse_identifier,
+        required_unless_present = "CODE",
+        conflicts_with = "CODE"
+    )]
+    identity: Option<IdentityIdentifier>,
+
+    /// Route to the node of the contact.
+    #[arg(
+        long,
+        id = "ROUTE",
+        display_order = 900,
+        required_unless_present = "CODE",
+        conflicts_with = "CODE"
+    )]
+    route: Option<MultiAddr>,
+
+    /// Add the contact from the text shown below the QR code of
+    /// `ockam identity qr`, as read by a scanner.
+    #[arg(long, id = "CODE", display_order = 901, value_parser = ContactCode::from_str)]
+    from_qr: Option<ContactCode>,
+}
+
+impl AddCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        let (name, identity, route) = match self.from_qr {
+            Some(code) => (self.name.unwrap_or(code.name), code.identity, code.route),
+            None => match (self.name, self.identity, self.route) {
+                (Some(name), Some(identity), Some(route)) => (name, identity, route),
+                _ => {
+                    eprintln!("a name, an identifier and a route are required without --from-qr");
+                    std::process::exit(exitcode::USAGE);
+                }
+            },
+        };
+        if route.iter().any(|p| p.code() == Contact::CODE) {
+            eprintln!("the route of a contact can not contain other contacts");
+            std::process::exit(exitcode::USAGE);
+        }
+        let contact = ContactLookup {
+            route,
+            identity_id: identity,
+        };
+        if let Err(e) = opts.config.add_contact(&name, contact) {
+            eprintln!("{e}");
+            std::process::exit(exitcode::CANTCREAT);
+        }
+        if let Err(e) = opts.config.persist_config_updates() {
+            eprintln!("{e}");
+            std::process::exit(exitcode::IOERR);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use core::fmt;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use ockam::identity::IdentityIdentifier;
+use ockam_multiaddr::MultiAddr;
+
+const PREFIX: &str = "ockam-contact:";
+
+/// A contact as exchanged out of band, e.g. through a QR code:
+/// `ockam-contact:<name>:<identifier>:<route>`.
+///
+/// The route comes last since it may itself contain colons.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ContactCode {
+    pub(crate) name: String,
+    pub(crate) identity: IdentityIdentifier,
+    pub(crate) route: MultiAddr,
+}
+
+impl fmt::Display for ContactCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let identity = self
+            .identity
+            .to_base32()
+            .unwrap_or_else(|| self.identity.to_string());
+        write!(f, "{PREFIX}{}:{identity}:{}", self.name, self.route)
+    }
+}
+
+impl FromStr for ContactCode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let invalid = || anyhow!("'{s}' is not a contact code");
+        let mut parts = s
+            .trim()
+            .strip_prefix(PREFIX)
+            .ok_or_else(invalid)?
+            .splitn(3, ':');
+        let (name, identity, route) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(n), Some(i), Some(r)) if !n.is_empty() => (n, i, r),
+            _ => return Err(invalid()),
+        };
+        Ok(Self {
+            name: name.to_string(),
+            identity: IdentityIdentifier::from_str(identity)
+                .map_err(|_| anyhow!("'{identity}' is not an identity identifier"))?,
+            route: MultiAddr::from_str(route).map_err(|_| anyhow!("'{route}' is not a route"))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDENTIFIER: &str = "P6c20e814b56579306f55c64e8747e6c1b4a53d9a3f4ca83c252cc2fbfc72fa94";
+
+    #[test]
+    fn codes_round_trip() {
+        let code = ContactCode {
+            name: "alice".to_string(),
+            identity: IdentityIdentifier::from_str(IDENTIFIER).unwrap(),
+            route: MultiAddr::from_str("/ip6/::1/tcp/4000").unwrap(),
+        };
+        let text = code.to_string();
+        assert!(text.starts_with("ockam-contact:alice:"));
+        assert_eq!(ContactCode::from_str(&text).unwrap(), code);
+    }
+
+    #[test]
+    fn invalid_codes_are_rejected() {
+        for s in [
+            "alice:P6c20:/ip4/127.0.0.1/tcp/4000",
+            "ockam-contact::P6c20e814b56579306f55c64e8747e6c1b4a53d9a3f4ca83c252cc2fbfc72fa94:/ip4/127.0.0.1/tcp/4000",
+            "ockam-contact:alice:nope:/ip4/127.0.0.1/tcp/4000",
+            "ockam-contact:alice",
+        ] {
+            assert!(ContactCode::from_str(s).is_err(), "{s}");
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use clap::Args;
+
+use crate::contact::HELP_DETAIL;
+use crate::util::exitcode;
+use crate::{help, CommandGlobalOpts};
+
+/// Delete a Contact
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct DeleteCommand {
+    /// Name of the contact.
+    name: String,
+}
+
+impl DeleteCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        if let Err(e) = opts.config.remove_contact(&self.name) {
+            eprintln!("{e}");
+            std::process::exit(exitcode::DATAERR);
+        }
+        if let Err(e) = opts.config.persist_config_updates() {
+            eprintln!("{e}");
+            std::process::exit(exitcode::IOERR);
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use clap::Args;
+use ockam_api::config::lookup::ContactLookup;
+use serde::{Serialize, Serializer};
+
+use crate::contact::HELP_DETAIL;
+use crate::identity;
+use crate::util::output::Output;
+use crate::util::{exitcode, OckamConfig};
+use crate::{help, CommandGlobalOpts, OutputFormat};
+
+/// List Contacts
+#[derive(Clone, Debug, Args)]
+#[command(help_template = help::template(HELP_DETAIL))]
+pub struct ListCommand {}
+
+impl ListCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        let list = ContactList(opts.config.contacts(), opts.config.clone());
+        let out = match opts.global_args.output_format {
+            OutputFormat::Plain => list.output(),
+            OutputFormat::Json => serde_json::to_string_pretty(&list).map_err(Into::into),
+        };
+        match out {
+            Ok(out) => println!("{out}"),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+    }
+}
+
+struct ContactList(Vec<(String, ContactLookup)>, OckamConfig);
+
+impl Serialize for ContactList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(name, contact)| (name, contact)))
+    }
+}
+
+impl Output for ContactList {
+    fn output(&self) -> anyhow::Result<String> {
+        if self.0.is_empty() {
+            return Ok("No contacts found".to_string());
+        }
+        let list = self
+            .0
+            .iter()
+            .map(|(name, contact)| {
+                let id = identity::display(&self.1, &contact.identity_id.to_string());
+                format!(
+                    "Contact {name}:\n  Route: {}\n  Identity: {id}",
+                    contact.route
+                )
+            })
+            .collect::<Vec<_>>();
+        Ok(list.join("\n\n"))
+    }
+}
@@ -0,0 +1,68 @@
+use clap::{Args, Subcommand};
+
+pub(crate) use add::AddCommand;
+pub(crate) use code::ContactCode;
+pub(crate) use delete::DeleteCommand;
+pub(crate) use list::ListCommand;
+
+use crate::{help, CommandGlobalOpts};
+
+mod add;
+mod code;
+mod delete;
+mod list;
+
+const HELP_DETAIL: &str = "\
+About:
+    Contacts are named peers, with a route to reach them and a pinned identity. A contact can
+    be used in routes as /contact/<NAME>, which is replaced by its route, and in place of an
+    identity identifier, e.g. with --authorized or --authorized-identifier.
+
+    A secure channel created to a contact only accepts the pinned identity of the contact,
+    unless other identities are authorized with --authorized.
+
+Examples:
+```sh
+    # Add alice as a contact
+    $ ockam contact add alice P6c20e814b56579306f55c64e8747e6c1b4a53d9a3f4ca83c252cc2fbfc72fa94 \\
+        --route /dnsaddr/alice.example.com/tcp/4000
+
+    # Or add her from the code shown below the QR code of `ockam identity qr`
+    $ ockam contact add --from-qr ockam-contact:alice:<IDENTIFIER>:/dnsaddr/alice.example.com/tcp/4000
+
+    # Create a secure channel to alice, which must present her identity
+    $ ockam secure-channel create --from /node/n1 --to /contact/alice/service/api
+
+    # Only accept alice on a secure channel listener
+    $ ockam secure-channel-listener create l1 --at /node/n1 --authorized-identifier alice
+```
+";
+
+/// Manage Contacts
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    help_template = help::template(HELP_DETAIL)
+)]
+pub struct ContactCommand {
+    #[command(subcommand)]
+    subcommand: ContactSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum ContactSubcommand {
+    Add(AddCommand),
+    Delete(DeleteCommand),
+    List(ListCommand),
+}
+
+impl ContactCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        match self.subcommand {
+            ContactSubcommand::Add(c) => c.run(opts),
+            ContactSubcommand::Delete(c) => c.run(opts),
+            ContactSubcommand::List(c) => c.run(opts),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use std::net::SocketAddr;
+
+use clap::Args;
+
+use ockam::identity::Identity;
+use ockam::vault::Vault;
+use ockam::Context;
+use ockam_api::cloud::mock::MockOrchestrator;
+
+use crate::dev::{listen, run_foreground, state_dir};
+
+/// Run an in-memory mock of the Ockam Orchestrator in foreground
+#[derive(Clone, Debug, Args)]
+pub struct MockOrchestratorCommand {
+    /// TCP address the mock listens on
+    #[arg(long, default_value = "127.0.0.1:6252", display_order = 900)]
+    address: SocketAddr,
+}
+
+impl MockOrchestratorCommand {
+    pub fn run(self) {
+        run_foreground(run_impl, self)
+    }
+}
+
+async fn run_impl(ctx: Context, cmd: MockOrchestratorCommand) -> anyhow::Result<()> {
+    let (addr, access_route) = listen(&ctx, cmd.address).await?;
+    let identity = Identity::create(&ctx, &Vault::create()).await?;
+    let mock = MockOrchestrator::start(
+        &ctx,
+        identity,
+        &access_route,
+        &state_dir("mock-orchestrator"),
+    )
+    .await?;
+
+    println!("Mock Orchestrator listening on {addr}, point the CLI to it with:\n");
+    println!("export OCKAM_CONTROLLER_ADDR={access_route}/service/api");
+    println!("export OCKAM_CONTROLLER_IDENTITY_ID={}", mock.identifier());
+    Ok(())
+}
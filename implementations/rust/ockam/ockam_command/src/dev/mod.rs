@@ -0,0 +1,118 @@
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use clap::{Args, Subcommand};
+
+use ockam::{Context, NodeBuilder, TcpTransport};
+use ockam_api::config::cli::OckamConfig;
+use ockam_multiaddr::MultiAddr;
+
+pub(crate) use mock_orchestrator::MockOrchestratorCommand;
+pub(crate) use project::ProjectCommand;
+
+use crate::help;
+use crate::util::exitcode;
+
+mod mock_orchestrator;
+mod project;
+
+const HELP_DETAIL: &str = "\
+About:
+    Tools to develop and test applications with Ockam locally.
+
+    The mock Orchestrator is an in-memory stand-in for the Ockam Orchestrator: the controller
+    managing spaces, projects and enrollment, the project node relaying messages, and the
+    project authority. Point the CLI and its nodes to it with the environment variables it
+    prints, then create a space and a project without network access nor accounts. It hosts
+    a single project and forgets everything when it stops.
+
+    A local project runs with the mock Orchestrator, already holding a project whose relay
+    node, authority and echo service run offline. Their identities are the same on every
+    run, so that tutorials and integration tests can rely on them. Nodes join the project
+    with the project information it writes.
+
+Examples:
+```sh
+    # Start a mock Orchestrator in foreground
+    $ ockam dev mock-orchestrator --address 127.0.0.1:6252
+
+    # In another terminal, use it
+    $ export OCKAM_CONTROLLER_ADDR=/ip4/127.0.0.1/tcp/6252/service/api
+    $ export OCKAM_CONTROLLER_IDENTITY_ID=P6c20e814b56579306f55c64e8747e6c1b4a53d9a3f4ca83c252cc2fbfc72fa94
+    $ ockam space create space
+    $ ockam project create space default
+
+    # Run a local project, then create a node of this project
+    $ ockam dev project up --output project.json
+    $ ockam node create n1 --project project.json
+    $ ockam message send hello --from n1 --to /project/default/service/echo
+```
+";
+
+/// Develop and test with Ockam locally
+#[derive(Clone, Debug, Args)]
+#[command(
+    hide = help::hide(),
+    arg_required_else_help = true,
+    subcommand_required = true,
+    help_template = help::template(HELP_DETAIL)
+)]
+pub struct DevCommand {
+    #[command(subcommand)]
+    subcommand: DevSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum DevSubcommand {
+    MockOrchestrator(MockOrchestratorCommand),
+    Project(ProjectCommand),
+}
+
+impl DevCommand {
+    pub fn run(self) {
+        match self.subcommand {
+            DevSubcommand::MockOrchestrator(c) => c.run(),
+            DevSubcommand::Project(c) => c.run(),
+        }
+    }
+}
+
+/// Run `f` on a node which keeps running once it returns, until the
+/// process is stopped.
+fn run_foreground<A, F, Fut>(f: F, a: A)
+where
+    A: Send + 'static,
+    F: FnOnce(Context, A) -> Fut + Send + 'static,
+    Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+{
+    let (ctx, mut executor) = NodeBuilder::without_access_control().no_logging().build();
+    let res = executor.execute(async move {
+        if let Err(e) = f(ctx, a).await {
+            eprintln!("{:#}", e);
+            std::process::exit(exitcode::SOFTWARE);
+        }
+    });
+    if let Err(e) = res {
+        eprintln!("Ockam node failed: {e}");
+        std::process::exit(exitcode::SOFTWARE);
+    }
+}
+
+/// Listen on `address`, returning the bound address and its route.
+async fn listen(ctx: &Context, address: SocketAddr) -> anyhow::Result<(SocketAddr, MultiAddr)> {
+    let tcp = TcpTransport::create(ctx).await?;
+    let addr = tcp.listen(address.to_string()).await?;
+    let route = match addr {
+        SocketAddr::V4(a) => format!("/ip4/{}/tcp/{}", a.ip(), a.port()),
+        SocketAddr::V6(a) => format!("/ip6/{}/tcp/{}", a.ip(), a.port()),
+    };
+    let route = route.parse().context("invalid listener address")?;
+    Ok((addr, route))
+}
+
+/// Directory of the state of the `name` dev tool.
+fn state_dir(name: &str) -> PathBuf {
+    OckamConfig::directories().data_local_dir().join(name)
+}
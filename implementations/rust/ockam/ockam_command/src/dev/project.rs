@@ -0,0 +1,93 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use clap::{Args, Subcommand};
+
+use ockam::identity::IdentityIdentifier;
+use ockam::vault::Vault;
+use ockam::Context;
+use ockam_api::cloud::mock::{seeded_identity, MockOrchestrator};
+use ockam_api::echoer::Echoer;
+use ockam_api::DefaultAddress;
+
+use crate::dev::{listen, run_foreground, state_dir};
+use crate::project::ProjectInfo;
+
+/// Seeds of the identities of the project, the same on every run
+const RELAY_SEED: &str = "ockam dev project relay";
+const AUTHORITY_SEED: &str = "ockam dev project authority";
+
+/// Run a local project
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, subcommand_required = true)]
+pub struct ProjectCommand {
+    #[command(subcommand)]
+    subcommand: ProjectSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum ProjectSubcommand {
+    Up(UpCommand),
+}
+
+impl ProjectCommand {
+    pub fn run(self) {
+        match self.subcommand {
+            ProjectSubcommand::Up(c) => c.run(),
+        }
+    }
+}
+
+/// Run a project in foreground: its relay node, its authority and an echo service
+#[derive(Clone, Debug, Args)]
+pub struct UpCommand {
+    /// TCP address the project listens on
+    #[arg(long, default_value = "127.0.0.1:6252", display_order = 900)]
+    address: SocketAddr,
+
+    /// File to write the project information to, for `ockam node create --project`
+    #[arg(long, default_value = "project.json", display_order = 900)]
+    output: PathBuf,
+
+    /// Identity allowed to enroll members (optional, may be repeated)
+    #[arg(long, value_name = "IDENTIFIER", display_order = 900)]
+    enroller: Vec<IdentityIdentifier>,
+}
+
+impl UpCommand {
+    pub fn run(self) {
+        run_foreground(run_impl, self)
+    }
+}
+
+async fn run_impl(ctx: Context, cmd: UpCommand) -> anyhow::Result<()> {
+    let (addr, access_route) = listen(&ctx, cmd.address).await?;
+    let vault = Vault::create();
+    let relay = seeded_identity(&ctx, &vault, RELAY_SEED).await?;
+    let authority = seeded_identity(&ctx, &vault, AUTHORITY_SEED).await?;
+    let dir = state_dir("dev-project");
+    let mock =
+        MockOrchestrator::start_with_authority(&ctx, relay, authority, &access_route, &dir).await?;
+    let project = mock.create_project("default", "default")?;
+    for enroller in &cmd.enroller {
+        mock.add_enroller(enroller)?;
+    }
+    ctx.start_worker(DefaultAddress::ECHO_SERVICE, Echoer)
+        .await?;
+
+    let info = serde_json::to_string_pretty(&ProjectInfo::from(project))?;
+    std::fs::write(&cmd.output, info)
+        .with_context(|| format!("failed to write {:?}", cmd.output))?;
+
+    println!(
+        "Project listening on {addr}, its information is in {:?}",
+        cmd.output
+    );
+    println!("Start nodes of the project with:\n");
+    println!("ockam node create n1 --project {:?}\n", cmd.output);
+    println!("Its orchestrator is a mock, point the CLI to it with:\n");
+    println!("export OCKAM_CONTROLLER_ADDR={access_route}/service/api");
+    println!("export OCKAM_CONTROLLER_IDENTITY_ID={}", mock.identifier());
+    Ok(())
+}
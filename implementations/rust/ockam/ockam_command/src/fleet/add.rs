@@ -0,0 +1,48 @@
+use std::collections::BTreeSet;
+
+use clap::Args;
+use ockam::identity::IdentityIdentifier;
+use ockam_api::config::cli::FleetMember;
+use ockam_multiaddr::MultiAddr;
+
+use crate::fleet::HELP_DETAIL;
+use crate::util::exitcode;
+use crate::{help, CommandGlobalOpts};
+
+/// Add a node to the Fleet
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct AddCommand {
+    /// Name of the node in the fleet.
+    name: String,
+
+    /// Route to the secure channel listener of the node.
+    #[arg(long, id = "ROUTE", display_order = 900)]
+    route: MultiAddr,
+
+    /// Expected identity of the node (optional).
+    #[arg(long, id = "IDENTIFIER", display_order = 900, value_parser = crate::identity::parse_identifier)]
+    identity: Option<IdentityIdentifier>,
+
+    /// Tag of the node, used to select groups of nodes. Can be repeated.
+    #[arg(long = "tag", id = "TAG", display_order = 900)]
+    tags: Vec<String>,
+}
+
+impl AddCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        let member = FleetMember {
+            route: self.route,
+            identity: self.identity,
+            tags: self.tags.into_iter().collect::<BTreeSet<_>>(),
+        };
+        if let Err(e) = opts.config.add_fleet_member(&self.name, member) {
+            eprintln!("{e}");
+            std::process::exit(exitcode::CANTCREAT);
+        }
+        if let Err(e) = opts.config.persist_config_updates() {
+            eprintln!("{e}");
+            std::process::exit(exitcode::IOERR);
+        }
+    }
+}
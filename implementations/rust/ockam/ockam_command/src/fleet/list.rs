@@ -0,0 +1,68 @@
+use clap::Args;
+use ockam_api::config::cli::FleetMember;
+use serde::{Serialize, Serializer};
+
+use crate::fleet::HELP_DETAIL;
+use crate::identity;
+use crate::util::output::Output;
+use crate::util::{exitcode, OckamConfig};
+use crate::{help, CommandGlobalOpts, OutputFormat};
+
+/// List the nodes of the Fleet
+#[derive(Clone, Debug, Args)]
+#[command(help_template = help::template(HELP_DETAIL))]
+pub struct ListCommand {
+    /// Only list the nodes having this tag. Can be repeated.
+    #[arg(long = "tag", id = "TAG", display_order = 900)]
+    tags: Vec<String>,
+}
+
+impl ListCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        let list = FleetList(opts.config.fleet_members(&self.tags), opts.config.clone());
+        let out = match opts.global_args.output_format {
+            OutputFormat::Plain => list.output(),
+            OutputFormat::Json => serde_json::to_string_pretty(&list).map_err(Into::into),
+        };
+        match out {
+            Ok(out) => println!("{out}"),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(exitcode::SOFTWARE);
+            }
+        }
+    }
+}
+
+struct FleetList(Vec<(String, FleetMember)>, OckamConfig);
+
+impl Serialize for FleetList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(name, member)| (name, member)))
+    }
+}
+
+impl Output for FleetList {
+    fn output(&self) -> anyhow::Result<String> {
+        if self.0.is_empty() {
+            return Ok("No fleet members found".to_string());
+        }
+        let list = self
+            .0
+            .iter()
+            .map(|(name, member)| {
+                let mut out = format!("Node {name}:\n  Route: {}", member.route);
+                if let Some(id) = &member.identity {
+                    let id = identity::display(&self.1, &id.to_string());
+                    out.push_str(&format!("\n  Identity: {id}"));
+                }
+                if !member.tags.is_empty() {
+                    let tags: Vec<&str> = member.tags.iter().map(String::as_str).collect();
+                    out.push_str(&format!("\n  Tags: {}", tags.join(", ")));
+                }
+                out
+            })
+            .collect::<Vec<_>>();
+        Ok(list.join("\n\n"))
+    }
+}
@@ -0,0 +1,256 @@
+use anyhow::anyhow;
+use clap::{Args, Subcommand};
+use futures::stream::{self, StreamExt};
+use minicbor::{Decoder, Encode};
+use ockam::{Context, TcpTransport};
+use ockam_api::clean_multiaddr;
+use ockam_api::config::cli::FleetMember;
+use ockam_api::config::lookup::ConfigLookup;
+use ockam_core::api::{RequestBuilder, Status};
+use ockam_multiaddr::proto::Node;
+use ockam_multiaddr::{MultiAddr, Protocol};
+use serde::Serialize;
+
+pub(crate) use add::AddCommand;
+pub(crate) use list::ListCommand;
+pub(crate) use remove::RemoveCommand;
+pub(crate) use rotate_credential::RotateCredentialCommand;
+pub(crate) use status::StatusCommand;
+
+use crate::error::Error;
+use crate::util::output::Output;
+use crate::util::{api, exitcode, RpcBuilder};
+use crate::{help, CommandGlobalOpts, OutputFormat, Result};
+
+mod add;
+mod list;
+mod remove;
+mod rotate_credential;
+mod status;
+
+/// Maximum number of nodes contacted at the same time.
+const CONCURRENCY: usize = 16;
+
+const HELP_DETAIL: &str = "\
+About:
+    A fleet is a set of remote nodes managed from this machine. Operations on the fleet
+    are sent from a local controller node, through a secure channel, to the delegation
+    service of every selected node. Each node must authorize the identity of the controller:
+
+```sh
+    # On every edge node
+    $ ockam service start delegation --authorized <CONTROLLER_IDENTITY>
+```
+
+    Nodes are selected by tags, an operation only targets the nodes having all the tags given
+    with --tag. The results of all the nodes are reported once every node answered.
+
+Examples:
+```sh
+    # Add two nodes to the fleet
+    $ ockam fleet add edge-1 --route /ip4/10.0.0.1/tcp/4000/service/api --tag eu --tag camera
+    $ ockam fleet add edge-2 --route /ip4/10.0.1.1/tcp/4000/service/api --tag us --tag camera
+
+    # Check the status of the cameras, from the controller node n1
+    $ ockam fleet status --at n1 --tag camera
+
+    # Rotate the credentials of the nodes in the eu
+    $ ockam fleet rotate-credential --at n1 --tag eu
+```
+";
+
+/// Manage a Fleet of remote nodes
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    help_template = help::template(HELP_DETAIL)
+)]
+pub struct FleetCommand {
+    #[command(subcommand)]
+    subcommand: FleetSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum FleetSubcommand {
+    Add(AddCommand),
+    Remove(RemoveCommand),
+    List(ListCommand),
+    Status(StatusCommand),
+    RotateCredential(RotateCredentialCommand),
+}
+
+impl FleetCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        match self.subcommand {
+            FleetSubcommand::Add(c) => c.run(opts),
+            FleetSubcommand::Remove(c) => c.run(opts),
+            FleetSubcommand::List(c) => c.run(opts),
+            FleetSubcommand::Status(c) => c.run(opts),
+            FleetSubcommand::RotateCredential(c) => c.run(opts),
+        }
+    }
+}
+
+/// Options selecting the members of the fleet targeted by an operation
+#[derive(Clone, Debug, Args)]
+pub struct FleetOpts {
+    /// Local node sending the requests, defaults to the default node.
+    #[arg(long, id = "NODE", display_order = 900)]
+    at: Option<String>,
+
+    /// Only target the nodes having this tag. Can be repeated.
+    #[arg(long = "tag", id = "TAG", display_order = 900)]
+    tags: Vec<String>,
+}
+
+/// Outcome of an operation on a member of the fleet
+#[derive(Debug, Serialize)]
+pub struct NodeOutcome {
+    node: String,
+    ok: bool,
+    message: String,
+}
+
+/// Outcomes of an operation on the whole fleet
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct FleetReport(Vec<NodeOutcome>);
+
+impl FleetReport {
+    fn failures(&self) -> usize {
+        self.0.iter().filter(|o| !o.ok).count()
+    }
+}
+
+impl Output for FleetReport {
+    fn output(&self) -> anyhow::Result<String> {
+        let width = self.0.iter().map(|o| o.node.len()).max().unwrap_or(0);
+        let mut lines: Vec<String> = self
+            .0
+            .iter()
+            .map(|o| {
+                let state = if o.ok { "OK" } else { "FAILED" };
+                format!("{:width$}  {state:6}  {}", o.node, o.message)
+            })
+            .collect();
+        let failures = self.failures();
+        lines.push(format!(
+            "{} succeeded, {failures} failed",
+            self.0.len() - failures
+        ));
+        Ok(lines.join("\n"))
+    }
+}
+
+/// Send the request built by `req` to every selected member of the fleet,
+/// and print the outcomes described by `summarize`.
+///
+/// Fails if the request failed on at least one node.
+async fn broadcast<T, R, S>(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    fleet: &FleetOpts,
+    req: R,
+    summarize: S,
+) -> Result<()>
+where
+    T: Encode<()>,
+    R: Fn() -> RequestBuilder<'static, T>,
+    S: Fn(&mut Decoder) -> anyhow::Result<String>,
+{
+    let controller = match &fleet.at {
+        Some(at) => at.clone(),
+        None => opts
+            .config
+            .get_default_node()
+            .ok_or_else(|| anyhow!("no controller node given with --at, nor default node"))?,
+    };
+    let members = opts.config.fleet_members(&fleet.tags);
+    if members.is_empty() {
+        return Err(Error::new(
+            exitcode::USAGE,
+            anyhow!("no fleet member matches the given tags"),
+        ));
+    }
+
+    let lookup = opts.config.lookup();
+    let tcp = TcpTransport::create(ctx).await?;
+    let send = |name: String, member: FleetMember| {
+        let (controller, lookup, tcp) = (&controller, &lookup, &tcp);
+        let (req, summarize) = (&req, &summarize);
+        async move {
+            let result: Result<String> = async {
+                let route = resolve(&member.route, lookup)
+                    .ok_or_else(|| anyhow!("invalid route {}", member.route))?;
+                let mut rpc = RpcBuilder::new(ctx, opts, controller).tcp(tcp)?.build();
+                let req = api::delegate(req(), &route, member.identity.clone())?;
+                rpc.request(req).await?;
+                let (hdr, mut dec) = rpc.check_response()?;
+                if hdr.status() != Some(Status::Ok) {
+                    return Err(anyhow!(rpc.parse_err_msg(hdr, dec)).into());
+                }
+                Ok(summarize(&mut dec)?)
+            }
+            .await;
+            match result {
+                Ok(message) => NodeOutcome {
+                    node: name,
+                    ok: true,
+                    message,
+                },
+                Err(e) => NodeOutcome {
+                    node: name,
+                    ok: false,
+                    message: describe(&e),
+                },
+            }
+        }
+    };
+    let mut outcomes: Vec<NodeOutcome> = stream::iter(members)
+        .map(|(name, member)| send(name, member))
+        .buffer_unordered(CONCURRENCY)
+        .collect()
+        .await;
+    outcomes.sort_by(|a, b| a.node.cmp(&b.node));
+    let report = FleetReport(outcomes);
+
+    let out = match opts.global_args.output_format {
+        OutputFormat::Plain => report.output()?,
+        OutputFormat::Json => serde_json::to_string_pretty(&report).map_err(anyhow::Error::from)?,
+    };
+    println!("{out}");
+
+    match report.failures() {
+        0 => Ok(()),
+        n => Err(Error::new(
+            exitcode::UNAVAILABLE,
+            anyhow!("the operation failed on {n} node(s)"),
+        )),
+    }
+}
+
+/// Replace the `/node/<name>` parts of a member route with their address.
+///
+/// Unlike `clean_multiaddr`, names of nodes unknown to this machine are
+/// reported instead of aborting the whole operation.
+fn resolve(route: &MultiAddr, lookup: &ConfigLookup) -> Option<MultiAddr> {
+    for p in route.iter() {
+        if p.code() == Node::CODE && lookup.get_node(&p.cast::<Node>()?).is_none() {
+            return None;
+        }
+    }
+    clean_multiaddr(route, lookup).map(|(route, _)| route)
+}
+
+/// Describe an error and its causes on a single line.
+fn describe(err: &Error) -> String {
+    let mut causes = Vec::new();
+    let mut source = std::error::Error::source(err);
+    while let Some(s) = source {
+        causes.push(s.to_string());
+        source = s.source();
+    }
+    causes.dedup();
+    causes.join(": ")
+}
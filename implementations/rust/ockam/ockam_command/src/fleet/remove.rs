@@ -0,0 +1,26 @@
+use clap::Args;
+
+use crate::fleet::HELP_DETAIL;
+use crate::util::exitcode;
+use crate::{help, CommandGlobalOpts};
+
+/// Remove a node from the Fleet
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct RemoveCommand {
+    /// Name of the node in the fleet.
+    name: String,
+}
+
+impl RemoveCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        if let Err(e) = opts.config.remove_fleet_member(&self.name) {
+            eprintln!("{e}");
+            std::process::exit(exitcode::DATAERR);
+        }
+        if let Err(e) = opts.config.persist_config_updates() {
+            eprintln!("{e}");
+            std::process::exit(exitcode::IOERR);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::credentials::GetCredentialRequest;
+use ockam_core::api::Request;
+
+use crate::fleet::{broadcast, FleetOpts, HELP_DETAIL};
+use crate::util::node_rpc;
+use crate::{help, CommandGlobalOpts, Result};
+
+/// Get fresh credentials for the nodes of the Fleet
+#[derive(Clone, Debug, Args)]
+#[command(help_template = help::template(HELP_DETAIL))]
+pub struct RotateCredentialCommand {
+    #[command(flatten)]
+    fleet: FleetOpts,
+}
+
+impl RotateCredentialCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, RotateCredentialCommand),
+) -> Result<()> {
+    broadcast(
+        &ctx,
+        &opts,
+        &cmd.fleet,
+        || Request::post("/node/credentials/actions/get").body(GetCredentialRequest::new(true)),
+        |_| Ok("credential rotated".to_string()),
+    )
+    .await
+}
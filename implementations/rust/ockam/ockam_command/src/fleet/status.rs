@@ -0,0 +1,39 @@
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::base::NodeStatus;
+use ockam_core::api::Request;
+
+use crate::fleet::{broadcast, FleetOpts, HELP_DETAIL};
+use crate::util::node_rpc;
+use crate::{help, CommandGlobalOpts, Result};
+
+/// Show the status of the nodes of the Fleet
+#[derive(Clone, Debug, Args)]
+#[command(help_template = help::template(HELP_DETAIL))]
+pub struct StatusCommand {
+    #[command(flatten)]
+    fleet: FleetOpts,
+}
+
+impl StatusCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, StatusCommand)) -> Result<()> {
+    broadcast(
+        &ctx,
+        &opts,
+        &cmd.fleet,
+        || Request::get("/node"),
+        |dec| {
+            let status: NodeStatus = dec.decode()?;
+            Ok(format!(
+                "{} ({}, pid {}, {} workers)",
+                status.status, status.node_name, status.pid, status.workers
+            ))
+        },
+    )
+    .await
+}
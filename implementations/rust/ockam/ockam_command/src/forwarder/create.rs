@@ -1,21 +1,27 @@
+use std::time::Duration;
+
 use anyhow::{anyhow, Context as _};
 use clap::Args;
 use ockam::identity::IdentityIdentifier;
-use ockam_multiaddr::proto::Project;
+use ockam_multiaddr::proto::{Contact, Project};
 use rand::prelude::random;
 
 use ockam::{Context, TcpTransport};
 use ockam_api::is_local_node;
 use ockam_api::nodes::models::forwarder::{CreateForwarder, ForwarderInfo};
-use ockam_core::api::Request;
+use ockam_core::api::{Request, RequestBuilder};
 use ockam_multiaddr::{proto::Node, MultiAddr, Protocol};
 
 use crate::forwarder::HELP_DETAIL;
-use crate::util::api::CloudOpts;
+use crate::util::api::{self, CloudOpts, DelegateOpts};
 use crate::util::output::Output;
-use crate::util::{get_final_element, node_rpc, RpcBuilder};
+use crate::util::{get_final_element, node_rpc, parse_interval, RpcBuilder};
 use crate::Result;
 use crate::{help, CommandGlobalOpts};
+use ockam_api::config::lookup::ConfigLookup;
+
+/// Maximum number of relays a forwarder can be registered at.
+const MAX_RELAYS: usize = 2;
 
 /// Create Forwarders
 #[derive(Clone, Debug, Args)]
@@ -32,17 +38,72 @@ pub struct CreateCommand {
     #[arg(long, id = "NODE", display_order = 900)]
     to: String,
 
-    /// Route to the node at which to create the forwarder (optional)
-    #[arg(long, id = "ROUTE", display_order = 900)]
-    at: MultiAddr,
-
-    /// Authorized identity for secure channel connection (optional)
-    #[arg(long, name = "AUTHORIZED", display_order = 900)]
-    authorized: Option<IdentityIdentifier>,
+    /// Route to the node at which to create the forwarder. Can be given
+    /// twice to register the forwarder at two relays at the same time
+    #[arg(long, id = "ROUTE", required = true, display_order = 900)]
+    at: Vec<MultiAddr>,
+
+    /// Authorized identity for secure channel connection (optional). May
+    /// be repeated when the node rotates between several identities
+    #[arg(long, name = "AUTHORIZED", display_order = 900, value_parser = crate::identity::parse_identifier)]
+    authorized: Vec<IdentityIdentifier>,
+
+    /// Also require a credential from the relay side of the secure channel,
+    /// which then verifies ours before accepting the registration
+    #[arg(long, display_order = 900)]
+    mutual_credentials: bool,
+
+    /// Rotate the secure channel of the forwarder once it is older than
+    /// this, e.g. 1h or 1d (optional)
+    #[arg(long, id = "MAX_AGE", value_parser = parse_interval, display_order = 900)]
+    channel_max_age: Option<Duration>,
+
+    /// Time a recovery of the forwarder may take, e.g. 30s (optional,
+    /// defaults to 10s). Raise it on high-latency links
+    #[arg(long, id = "RECOVERY_TIMEOUT", value_parser = parse_interval, display_order = 900)]
+    recovery_timeout: Option<Duration>,
+
+    /// Time the creation of the forwarder's secure channel may take, e.g.
+    /// 15s (optional, defaults to 5s during recoveries)
+    #[arg(long, id = "CONNECT_TIMEOUT", value_parser = parse_interval, display_order = 900)]
+    connect_timeout: Option<Duration>,
+
+    /// Time before retrying a failed recovery the first time, e.g. 5s
+    /// (optional, defaults to 1s). The delay doubles after each failure
+    #[arg(long, id = "INITIAL_DELAY", value_parser = parse_interval, display_order = 900)]
+    retry_initial_delay: Option<Duration>,
+
+    /// Maximum time between two recovery attempts, e.g. 10m (optional,
+    /// defaults to 2m)
+    #[arg(long, id = "MAX_DELAY", value_parser = parse_interval, display_order = 900)]
+    retry_max_delay: Option<Duration>,
+
+    /// Give up recovering the forwarder after this many failed attempts
+    /// (optional, retries forever by default)
+    #[arg(long, id = "MAX_ATTEMPTS", value_parser = clap::value_parser!(u32).range(1..), display_order = 900)]
+    retry_max_attempts: Option<u32>,
+
+    /// Time between two renewals of the forwarder's registration, e.g. 1m
+    /// (optional, defaults to 5s). Raise it on metered links
+    #[arg(long, id = "HEARTBEAT_INTERVAL", value_parser = parse_interval, display_order = 900)]
+    heartbeat_interval: Option<Duration>,
+
+    /// Never renew the forwarder's registration
+    #[arg(long, conflicts_with = "HEARTBEAT_INTERVAL", display_order = 900)]
+    no_heartbeat: bool,
+
+    /// Delete the forwarder, its session and secure channel after this
+    /// time, e.g. 2h for the relay of a CI job (optional)
+    #[arg(long, id = "TTL", value_parser = parse_interval, display_order = 900)]
+    ttl: Option<Duration>,
 
     /// Orchestrator address to resolve projects present in the `at` argument
     #[command(flatten)]
     cloud_opts: CloudOpts,
+
+    /// Create the forwarder for a remote node instead
+    #[command(flatten)]
+    delegate_opts: DelegateOpts,
 }
 
 impl CreateCommand {
@@ -52,15 +113,40 @@ impl CreateCommand {
 }
 
 async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> Result<()> {
+    if cmd.at.len() > MAX_RELAYS {
+        return Err(anyhow!("--at can be given at most {} times", MAX_RELAYS).into());
+    }
     let tcp = TcpTransport::create(&ctx).await?;
     let api_node = get_final_element(&cmd.to);
-    let at_rust_node = is_local_node(&cmd.at).context("Argument --at is not valid")?;
-
     let lookup = opts.config.lookup();
 
+    // Each relay gets its own registration, secure channel and recovery.
+    for at in &cmd.at {
+        let req = make_api_request(&cmd, &lookup, at)?;
+        let mut rpc = RpcBuilder::new(&ctx, &opts, api_node).tcp(&tcp)?.build();
+        match cmd.delegate_opts.resolve(&lookup)? {
+            Some(to) => {
+                let authorized = cmd.delegate_opts.delegate_identity.clone();
+                rpc.request(api::delegate(req, &to, authorized)?).await?
+            }
+            None => rpc.request(req).await?,
+        }
+        rpc.parse_and_print_response::<ForwarderInfo>()?;
+    }
+
+    Ok(())
+}
+
+/// Construct a request to create a forwarder at the node `at`
+fn make_api_request(
+    cmd: &CreateCommand,
+    lookup: &ConfigLookup,
+    at: &MultiAddr,
+) -> Result<RequestBuilder<'static, CreateForwarder<'static>>> {
     let mut ma = MultiAddr::default();
+    let mut pinned = None;
 
-    for proto in cmd.at.iter() {
+    for proto in at.iter() {
         match proto.code() {
             Node::CODE => {
                 let alias = proto
@@ -80,32 +166,84 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> R
                     .ok_or_else(|| anyhow!("no project found with name {}", &*name))?;
                 ma.push_back(Project::new(&proj.id))?
             }
+            Contact::CODE => {
+                let name = proto
+                    .cast::<Contact>()
+                    .ok_or_else(|| anyhow!("invalid contact address protocol"))?;
+                let contact = lookup
+                    .get_contact(&name)
+                    .ok_or_else(|| anyhow!("no contact found with name {}", &*name))?;
+                ma.try_extend(&contact.route)?;
+                pinned = Some(contact.identity_id.clone())
+            }
             _ => ma.push_back_value(&proto)?,
         }
     }
 
-    let req = {
-        let alias = if at_rust_node {
-            format!("forward_to_{}", cmd.forwarder_name)
-        } else {
-            cmd.forwarder_name.clone()
-        };
-        let body = if Some(Project::CODE) == cmd.at.first().map(|p| p.code()) {
-            if cmd.authorized.is_some() {
-                return Err(anyhow!("--authorized can not be used with project addresses").into());
-            }
-            CreateForwarder::at_project(ma, Some(alias), cmd.cloud_opts.route())
-        } else {
-            CreateForwarder::at_node(ma, Some(alias), at_rust_node, cmd.authorized)
+    // A contact is local or not depending on its route.
+    let at_rust_node = match at.first() {
+        Some(p) if p.code() == Contact::CODE => is_local_node(&ma),
+        _ => is_local_node(at),
+    }
+    .context("Argument --at is not valid")?;
+
+    let alias = if at_rust_node {
+        format!("forward_to_{}", cmd.forwarder_name)
+    } else {
+        cmd.forwarder_name.clone()
+    };
+    let body = if Some(Project::CODE) == at.first().map(|p| p.code()) {
+        if !cmd.authorized.is_empty() {
+            return Err(anyhow!("--authorized can not be used with project addresses").into());
+        }
+        CreateForwarder::at_project(ma, Some(alias), cmd.cloud_opts.route())
+    } else {
+        // A contact's identity is only pinned when none is authorized explicitly.
+        let authorized = match pinned {
+            Some(id) if cmd.authorized.is_empty() => vec![id],
+            _ => cmd.authorized.clone(),
         };
-        Request::post("/node/forwarder").body(body)
+        CreateForwarder::at_node(ma, Some(alias), at_rust_node, authorized)
     };
-
-    let mut rpc = RpcBuilder::new(&ctx, &opts, api_node).tcp(&tcp)?.build();
-    rpc.request(req).await?;
-    rpc.parse_and_print_response::<ForwarderInfo>()?;
-
-    Ok(())
+    let body = if cmd.mutual_credentials {
+        body.with_mutual_credential_exchange()
+    } else {
+        body
+    };
+    let body = match cmd.channel_max_age {
+        Some(d) => body.with_max_age(d),
+        None => body,
+    };
+    let body = match cmd.recovery_timeout {
+        Some(d) => body.with_recovery_timeout(d),
+        None => body,
+    };
+    let body = match cmd.connect_timeout {
+        Some(d) => body.with_connect_timeout(d),
+        None => body,
+    };
+    let body = match cmd.retry_initial_delay {
+        Some(d) => body.with_retry_initial_delay(d),
+        None => body,
+    };
+    let body = match cmd.retry_max_delay {
+        Some(d) => body.with_retry_max_delay(d),
+        None => body,
+    };
+    let body = match cmd.retry_max_attempts {
+        Some(n) => body.with_retry_max_attempts(n),
+        None => body,
+    };
+    let body = match cmd.heartbeat_interval {
+        Some(d) => body.with_heartbeat_interval(d),
+        None if cmd.no_heartbeat => body.without_heartbeats(),
+        None => body,
+    };
+    let body = match cmd.ttl {
+        Some(d) => body.with_ttl(d),
+        None => body,
+    };
+    Ok(Request::post("/node/forwarder").body(body))
 }
 
 impl Output for ForwarderInfo<'_> {
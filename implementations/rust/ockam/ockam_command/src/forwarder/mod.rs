@@ -1,10 +1,16 @@
 use clap::{Args, Subcommand};
 
 pub(crate) use create::CreateCommand;
+pub(crate) use delete::DeleteCommand;
+pub(crate) use list::ListCommand;
+pub(crate) use show::ShowCommand;
 
 use crate::{help, CommandGlobalOpts};
 
 mod create;
+mod delete;
+mod list;
+mod show;
 
 const HELP_DETAIL: &str = "\
 About:
@@ -45,6 +51,92 @@ About:
     In this topology green acts an an encrypted relay between yellow and blue. Yellow and
     blue can be running in completely separate private networks. Green needs to be reachable
     from both yellow and blue and only sees encrypted traffic.
+
+    A node can also create forwarders on behalf of another node, provided that the other node
+    runs a delegation service authorizing its identity
+
+```sh
+    # Let yellow create inlets and forwarders on blue
+    $ ockam service start --node blue delegation --authorized $(ockam identity show --node yellow)
+
+    # From yellow, create a forwarder for blue at green
+    $ ockam forwarder create blue --at /node/green --to /node/yellow --delegate-to /node/blue/service/api
+    /service/forward_to_blue
+```
+
+    Instead of authorizing identities one by one, the delegation service can accept project
+    members whose credential grants them capabilities, and only for what these allow. An
+    enroller restricts a member when adding it to the project.
+
+```sh
+    # Only let the device create a forwarder named device-42 and an outlet to its SSH port
+    $ ockam project enroll --to /project/default/service/authenticator --member $DEVICE_ID \
+        --capability forwarder:device-42 --capability outlet:22
+
+    $ ockam service start --node blue delegation --capabilities
+```
+
+    Over the secure channel of a forwarder, the node presents its credential to the relay side.
+    With --mutual-credentials both sides present and verify credentials, so the relay side can
+    check the node's credential before accepting the registration.
+
+```sh
+    $ ockam forwarder create blue --at /project/default --to /node/blue --mutual-credentials
+```
+
+    The secure channel of a forwarder at a project is replaced when it breaks. It can also be
+    rotated once it gets older than a maximum age, to limit the use of its keys. The new channel
+    and forwarder are created before the old channel is deleted.
+
+```sh
+    $ ockam forwarder create blue --at /project/default --to /node/blue --channel-max-age 1d
+```
+
+    A recovery may take 10 seconds by default, of which creating the secure channel may take 5
+    seconds. On high-latency links these limits can be raised.
+
+```sh
+    $ ockam forwarder create blue --at /project/default --to /node/blue \
+        --recovery-timeout 30s --connect-timeout 15s
+```
+
+    A failed recovery is retried after a delay which doubles after each failure, from 1 second
+    up to 2 minutes, randomized so that many nodes do not retry at the same time. After
+    --retry-max-attempts failures the node gives up, and the forwarder stays down until its
+    recovery is requested with `ockam medic recover`.
+
+```sh
+    $ ockam forwarder create blue --at /project/default --to /node/blue \
+        --retry-initial-delay 5s --retry-max-delay 10m --retry-max-attempts 20
+```
+
+    A forwarder can be registered at two relays at once, so that it stays reachable while
+    one of them is down. Both registrations are active and are recovered independently.
+    Inlets can use the second one as a backup route with `ockam tcp-inlet create --backup-to`.
+
+```sh
+    $ ockam forwarder create blue --at /node/green --at /node/yellow --to /node/blue
+    /service/forward_to_blue
+    /service/forward_to_blue
+```
+
+    The forwarders a node has created, and whether a session recovers them, can be listed.
+    Deleting a forwarder stops its recovery and deletes the secure channel created for it.
+
+    Forwarders without a --ttl are stored in the node's state directory and created again,
+    resolving the project address anew, when the node restarts. Deleted forwarders are not.
+
+```sh
+    $ ockam forwarder list --at /node/blue
+    $ ockam forwarder delete forward_to_blue --at /node/blue
+```
+
+    Showing a forwarder tells when its session last answered a ping, how often it was
+    recovered, and whether it is degraded because it is down or pings go unanswered.
+
+```sh
+    $ ockam forwarder show blue --at /node/blue
+```
 ";
 
 /// Manage Forwarders
@@ -60,14 +152,21 @@ pub struct ForwarderCommand {
 }
 
 #[derive(Clone, Debug, Subcommand)]
+#[allow(clippy::large_enum_variant)]
 pub enum ForwarderSubCommand {
     Create(CreateCommand),
+    Delete(DeleteCommand),
+    List(ListCommand),
+    Show(ShowCommand),
 }
 
 impl ForwarderCommand {
     pub fn run(self, opts: CommandGlobalOpts) {
         match self.subcommand {
             ForwarderSubCommand::Create(c) => c.run(opts),
+            ForwarderSubCommand::Delete(c) => c.run(opts),
+            ForwarderSubCommand::List(c) => c.run(opts),
+            ForwarderSubCommand::Show(c) => c.run(opts),
         }
     }
 }
@@ -1,5 +1,8 @@
+#[cfg(feature = "tui")]
 use crate::terminal::{Terminal, TerminalBackground};
+#[cfg(feature = "tui")]
 use colorful::Colorful;
+#[cfg(feature = "tui")]
 use syntect::{
     easy::HighlightLines,
     highlighting::{Style, ThemeSet},
@@ -36,6 +39,13 @@ pub(crate) fn template(background: &str) -> &'static str {
     Box::leak(highlighted.into_boxed_str())
 }
 
+/// Without the `tui` feature help examples are printed as plain text.
+#[cfg(not(feature = "tui"))]
+pub fn highlight_syntax(input: String) -> String {
+    input
+}
+
+#[cfg(feature = "tui")]
 pub fn highlight_syntax(input: String) -> String {
     let theme_name = match Terminal::detect_background_color() {
         TerminalBackground::Light => "base16-ocean.light",
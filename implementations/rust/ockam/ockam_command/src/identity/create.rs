@@ -1,6 +1,7 @@
 use crate::help;
+use crate::identity::display;
 use crate::node::NodeOpts;
-use crate::util::{api, connect_to, exitcode};
+use crate::util::{api, connect_to, exitcode, OckamConfig};
 use crate::CommandGlobalOpts;
 use clap::Args;
 use ockam::Context;
@@ -20,7 +21,7 @@ impl CreateCommand {
         let cfg = options.config;
         let port = cfg.get_node_port(&self.node_opts.api_node);
 
-        connect_to(port, self, create_identity);
+        connect_to(port, (cfg.clone(), self), create_identity);
 
         Ok(())
     }
@@ -28,7 +29,7 @@ impl CreateCommand {
 
 pub async fn create_identity(
     ctx: Context,
-    _cmd: CreateCommand,
+    (cfg, _cmd): (OckamConfig, CreateCommand),
     mut base_route: Route,
 ) -> anyhow::Result<()> {
     let resp: Vec<u8> = ctx
@@ -42,7 +43,8 @@ pub async fn create_identity(
 
     match response.status() {
         Some(Status::Ok) => {
-            println!("Identity {} created!", result.identity_id)
+            let id = display(&cfg, &result.identity_id);
+            println!("Identity {id} created!")
         }
         _ => {
             eprintln!("An error occurred while creating Identity",);
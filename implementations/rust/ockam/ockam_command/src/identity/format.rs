@@ -0,0 +1,31 @@
+use crate::identity::HELP_DETAIL;
+use crate::util::exitcode;
+use crate::{help, CommandGlobalOpts};
+use clap::Args;
+use ockam_api::config::cli::IdentityFormat;
+
+/// Show or set how identities are displayed
+#[derive(Clone, Debug, Args)]
+#[command(help_template = help::template(HELP_DETAIL))]
+pub struct FormatCommand {
+    /// One of hex, base32 or petname. Shows the current format if omitted
+    format: Option<IdentityFormat>,
+}
+
+impl FormatCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> anyhow::Result<()> {
+        let format = match self.format {
+            Some(f) => f,
+            None => {
+                println!("{}", options.config.identity_format());
+                return Ok(());
+            }
+        };
+        options.config.set_identity_format(format);
+        if let Err(e) = options.config.persist_config_updates() {
+            eprintln!("{e}");
+            std::process::exit(exitcode::IOERR);
+        }
+        Ok(())
+    }
+}
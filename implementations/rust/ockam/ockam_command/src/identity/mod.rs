@@ -1,14 +1,57 @@
 mod create;
+mod format;
+mod petname;
+mod qr;
 mod show;
 
 pub(crate) use create::CreateCommand;
+pub(crate) use format::FormatCommand;
+pub(crate) use petname::PetnameCommand;
+pub(crate) use qr::QrCommand;
 pub(crate) use show::ShowCommand;
 
-use crate::CommandGlobalOpts;
+use crate::util::OckamConfig;
+use crate::{help, CommandGlobalOpts};
 use clap::{Args, Subcommand};
+use ockam::identity::IdentityIdentifier;
+use ockam_api::config::cli::IdentityFormat;
+use std::str::FromStr;
+
+const HELP_DETAIL: &str = "\
+About:
+    Identities are shown as a long hex identifier by default. They can also be shown in a
+    shorter base32 form ending with a checksum, so that a mistyped identifier is rejected
+    instead of naming another identity, or by a petname given to them locally.
+
+```sh
+    # Show identities in their base32 form from now on
+    $ ockam identity format base32
+
+    # Name the identity of node blue, and show identities by their petname
+    $ ockam identity petname set blue $(ockam identity show --node blue)
+    $ ockam identity format petname
+```
+
+    Wherever an identity is expected, e.g. `--authorized`, it may be given in any of
+    these forms.
+
+```sh
+    $ ockam secure-channel create --from /node/green --to /node/blue/service/api --authorized blue
+```
+
+    To pair machines without copying identifiers around, show the identity of a node and the
+    route to reach it as a QR code, and add it as a contact on the other machine from the
+    text read by a scanner, which is also printed below the code.
+
+```sh
+    $ ockam identity qr --node blue --route /ip4/192.168.1.10/tcp/4000
+    $ ockam contact add --from-qr ockam-contact:blue:<IDENTIFIER>:/ip4/192.168.1.10/tcp/4000
+```
+";
 
 /// Manage Identities
 #[derive(Clone, Debug, Args)]
+#[command(help_template = help::template(HELP_DETAIL))]
 pub struct IdentityCommand {
     #[command(subcommand)]
     subcommand: IdentitySubcommand,
@@ -20,6 +63,12 @@ pub enum IdentitySubcommand {
     Create(CreateCommand),
     /// Print short existing identity, `--full` for long identity
     Show(ShowCommand),
+    /// Show or set how identities are displayed
+    Format(FormatCommand),
+    /// Manage the local names of identities
+    Petname(PetnameCommand),
+    /// Show a QR code to add the identity and route of a node as a contact
+    Qr(QrCommand),
 }
 
 impl IdentityCommand {
@@ -27,7 +76,47 @@ impl IdentityCommand {
         match self.subcommand {
             IdentitySubcommand::Create(c) => c.run(options),
             IdentitySubcommand::Show(c) => c.run(options),
+            IdentitySubcommand::Format(c) => c.run(options),
+            IdentitySubcommand::Petname(c) => c.run(options),
+            IdentitySubcommand::Qr(c) => c.run(options),
         }
         .unwrap()
     }
 }
+
+/// Show an identity identifier in the configured format.
+///
+/// Strings which are not identifiers are shown as they are.
+pub(crate) fn display(cfg: &OckamConfig, id: &str) -> String {
+    display_as(cfg, cfg.identity_format(), id)
+}
+
+/// Show an identity identifier in the given format.
+pub(crate) fn display_as(cfg: &OckamConfig, format: IdentityFormat, id: &str) -> String {
+    let i = match IdentityIdentifier::from_str(id) {
+        Ok(i) => i,
+        Err(_) => return id.to_string(),
+    };
+    let petname = match format {
+        IdentityFormat::Hex => return i.to_string(),
+        IdentityFormat::Base32 => None,
+        IdentityFormat::Petname => cfg.petname_of(&i),
+    };
+    petname
+        .or_else(|| i.to_base32())
+        .unwrap_or_else(|| i.to_string())
+}
+
+/// Parse an identity identifier in any of its forms, a petname, or the
+/// name of a contact.
+pub(crate) fn parse_identifier(s: &str) -> anyhow::Result<IdentityIdentifier> {
+    if let Ok(i) = IdentityIdentifier::from_str(s) {
+        return Ok(i);
+    }
+    let cfg = OckamConfig::load();
+    cfg.petname_identity(s)
+        .or_else(|| cfg.lookup().get_contact(s).map(|c| c.identity_id.clone()))
+        .ok_or_else(|| {
+            anyhow::anyhow!("'{s}' is neither an identity identifier, a petname nor a contact")
+        })
+}
@@ -0,0 +1,55 @@
+use crate::identity::{parse_identifier, HELP_DETAIL};
+use crate::util::exitcode;
+use crate::{help, CommandGlobalOpts};
+use clap::{Args, Subcommand};
+use ockam::identity::IdentityIdentifier;
+
+/// Manage the local names of identities
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct PetnameCommand {
+    #[command(subcommand)]
+    subcommand: PetnameSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum PetnameSubcommand {
+    /// Name an identity, replacing the identity of the name if any
+    Set {
+        name: String,
+        /// The identity, in any of its forms
+        #[arg(value_name = "IDENTIFIER", value_parser = parse_identifier)]
+        identity: IdentityIdentifier,
+    },
+    /// Forget a petname
+    Delete { name: String },
+    /// List the petnames and their identities
+    List,
+}
+
+impl PetnameCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> anyhow::Result<()> {
+        let cfg = &options.config;
+        match self.subcommand {
+            PetnameSubcommand::Set { name, identity } => cfg.set_petname(&name, identity),
+            PetnameSubcommand::Delete { name } => {
+                if let Err(e) = cfg.remove_petname(&name) {
+                    eprintln!("{e}");
+                    std::process::exit(exitcode::USAGE);
+                }
+            }
+            PetnameSubcommand::List => {
+                for (name, id) in cfg.petnames() {
+                    let b32 = id.to_base32().unwrap_or_default();
+                    println!("{name}: {id} {b32}");
+                }
+                return Ok(());
+            }
+        }
+        if let Err(e) = cfg.persist_config_updates() {
+            eprintln!("{e}");
+            std::process::exit(exitcode::IOERR);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use crate::contact::ContactCode;
+use crate::util::qr::QrCode;
+use crate::util::{connect_to, exitcode, get_final_element};
+use crate::CommandGlobalOpts;
+use crate::{node::NodeOpts, util::api};
+use clap::Args;
+use ockam::identity::IdentityIdentifier;
+use ockam::{Context, Route};
+use ockam_api::nodes::NODEMANAGER_ADDR;
+use ockam_core::api::Status;
+use ockam_multiaddr::MultiAddr;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, Args)]
+pub struct QrCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+
+    /// Route at which others reach the node, e.g. its address on the LAN.
+    #[arg(long, id = "ROUTE")]
+    route: MultiAddr,
+
+    /// Name others give to this node (optional, defaults to the node name).
+    #[arg(long)]
+    name: Option<String>,
+}
+
+impl QrCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> anyhow::Result<()> {
+        let node = get_final_element(&self.node_opts.api_node);
+        let port = options.config.get_node_port(node);
+
+        connect_to(port, self, show_qr);
+
+        Ok(())
+    }
+}
+
+async fn show_qr(ctx: Context, cmd: QrCommand, mut base_route: Route) -> anyhow::Result<()> {
+    let resp: Vec<u8> = ctx
+        .send_and_receive(
+            base_route.modify().append(NODEMANAGER_ADDR),
+            api::short_identity()?,
+        )
+        .await?;
+
+    let (response, result) = api::parse_short_identity_response(&resp)?;
+    if response.status() != Some(Status::Ok) {
+        eprintln!("An error occurred while getting Identity",);
+        std::process::exit(exitcode::IOERR);
+    }
+
+    let name = cmd
+        .name
+        .unwrap_or_else(|| get_final_element(&cmd.node_opts.api_node).to_string());
+    let code = ContactCode {
+        name,
+        identity: IdentityIdentifier::from_str(&result.identity_id)?,
+        route: cmd.route,
+    };
+    let code = code.to_string();
+    match QrCode::encode(&code) {
+        Ok(qr) => print!("{}", qr.render()),
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(exitcode::DATAERR);
+        }
+    }
+    println!("{code}");
+
+    Ok(())
+}
@@ -1,8 +1,10 @@
-use crate::util::{connect_to, exitcode, get_final_element};
+use crate::identity::display_as;
+use crate::util::{connect_to, exitcode, get_final_element, OckamConfig};
 use crate::CommandGlobalOpts;
 use crate::{node::NodeOpts, util::api};
 use clap::Args;
 use ockam::{Context, Route};
+use ockam_api::config::cli::IdentityFormat;
 use ockam_api::nodes::NODEMANAGER_ADDR;
 use ockam_core::api::Status;
 
@@ -12,6 +14,10 @@ pub struct ShowCommand {
     node_opts: NodeOpts,
     #[arg(short, long)]
     full: bool,
+    /// One of hex, base32 or petname (optional, defaults to the format set
+    /// with `ockam identity format`)
+    #[arg(long)]
+    format: Option<IdentityFormat>,
 }
 
 impl ShowCommand {
@@ -20,7 +26,7 @@ impl ShowCommand {
         let node = get_final_element(&self.node_opts.api_node);
         let port = cfg.get_node_port(node);
 
-        connect_to(port, self, show_identity);
+        connect_to(port, (cfg.clone(), self), show_identity);
 
         Ok(())
     }
@@ -28,7 +34,7 @@ impl ShowCommand {
 
 pub async fn show_identity(
     ctx: Context,
-    cmd: ShowCommand,
+    (cfg, cmd): (OckamConfig, ShowCommand),
     mut base_route: Route,
 ) -> anyhow::Result<()> {
     if cmd.full {
@@ -64,7 +70,8 @@ pub async fn show_identity(
 
         match response.status() {
             Some(Status::Ok) => {
-                println!("{}", result.identity_id)
+                let format = cmd.format.unwrap_or_else(|| cfg.identity_format());
+                println!("{}", display_as(&cfg, format, &result.identity_id))
             }
             _ => {
                 eprintln!("An error occurred while getting Identity",);
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use anyhow::anyhow;
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::jobs::{CreateJob, JobKind, JobStatus};
+use ockam_core::api::Request;
+
+use crate::job::HELP_DETAIL;
+use crate::util::api::CloudOpts;
+use crate::util::{get_final_element, node_rpc, parse_interval, Rpc};
+use crate::{help, CommandGlobalOpts, Result};
+
+/// Create Jobs
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct CreateCommand {
+    /// The action to run: rotate-credential or resolve-project.
+    kind: JobKind,
+
+    /// Node on which to schedule the job.
+    #[arg(long, id = "NODE", display_order = 900)]
+    at: String,
+
+    /// Time between two runs, e.g. 90s, 30m, 12h or 1d.
+    #[arg(long, id = "INTERVAL", value_parser = parse_interval, display_order = 900)]
+    every: Duration,
+
+    /// Name of the project to resolve, for resolve-project jobs.
+    #[arg(long, id = "PROJECT", display_order = 900)]
+    project: Option<String>,
+
+    /// Orchestrator address used to resolve projects
+    #[command(flatten)]
+    cloud_opts: CloudOpts,
+}
+
+impl CreateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> Result<()> {
+    let body = match cmd.kind {
+        JobKind::RotateCredential => CreateJob::rotate_credential(cmd.every),
+        JobKind::ResolveProject => {
+            let name = cmd
+                .project
+                .as_deref()
+                .ok_or_else(|| anyhow!("--project is required for resolve-project jobs"))?;
+            let lookup = opts.config.lookup();
+            let project = lookup
+                .get_project(name)
+                .ok_or_else(|| anyhow!("no project found with name {name}"))?;
+            CreateJob::resolve_project(cmd.every, project.id.clone(), cmd.cloud_opts.route())
+        }
+    };
+    let node = get_final_element(&cmd.at);
+    let mut rpc = Rpc::background(&ctx, &opts, node)?;
+    rpc.request(Request::post("/node/jobs").body(body)).await?;
+    rpc.parse_and_print_response::<JobStatus>()?;
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::jobs::JobList;
+use ockam_core::api::Request;
+
+use crate::job::HELP_DETAIL;
+use crate::util::{get_final_element, node_rpc, Rpc};
+use crate::{help, CommandGlobalOpts, Result};
+
+/// List Jobs
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct ListCommand {
+    /// Node whose jobs to list.
+    #[arg(long, id = "NODE", display_order = 900)]
+    at: String,
+}
+
+impl ListCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, ListCommand)) -> Result<()> {
+    let node = get_final_element(&cmd.at);
+    let mut rpc = Rpc::background(&ctx, &opts, node)?;
+    rpc.request(Request::get("/node/jobs")).await?;
+    rpc.parse_and_print_response::<JobList>()?;
+    Ok(())
+}
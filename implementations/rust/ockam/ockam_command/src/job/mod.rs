@@ -0,0 +1,66 @@
+use clap::{Args, Subcommand};
+
+pub(crate) use create::CreateCommand;
+pub(crate) use delete::DeleteCommand;
+pub(crate) use list::ListCommand;
+
+use crate::{help, CommandGlobalOpts};
+
+mod create;
+mod delete;
+mod list;
+
+const HELP_DETAIL: &str = "\
+About:
+    Jobs are actions a node runs on a schedule, so that routine operations
+    don't depend on an external cron. Every run is recorded, and the outcome
+    of the last one is shown by `ockam job list` and `ockam node show`.
+
+    Supported jobs:
+        - rotate-credential: get a fresh credential from the project authority
+        - resolve-project: resolve the route to a project through the Orchestrator
+
+Examples:
+```sh
+    # Rotate the credential of node n1 every day
+    $ ockam job create rotate-credential --at n1 --every 1d
+
+    # Re-resolve the route to the default project every hour
+    $ ockam job create resolve-project --at n1 --every 1h --project default
+
+    # List the jobs of node n1
+    $ ockam job list --at n1
+
+    # Delete a job
+    $ ockam job delete 5d3fd7cb --at n1
+```
+";
+
+/// Manage Jobs
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    help_template = help::template(HELP_DETAIL)
+)]
+pub struct JobCommand {
+    #[command(subcommand)]
+    subcommand: JobSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum JobSubcommand {
+    Create(CreateCommand),
+    Delete(DeleteCommand),
+    List(ListCommand),
+}
+
+impl JobCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        match self.subcommand {
+            JobSubcommand::Create(c) => c.run(opts),
+            JobSubcommand::Delete(c) => c.run(opts),
+            JobSubcommand::List(c) => c.run(opts),
+        }
+    }
+}
@@ -1,60 +1,91 @@
 //! Orchestrate end-to-end encryption, mutual authentication, key management,
 //! credential management, and authorization policy enforcement — at scale.
 
+#[cfg(feature = "cloud")]
 mod admin;
 mod authenticated;
 mod completion;
 mod configuration;
+mod contact;
 mod credential;
+#[cfg(feature = "mock-orchestrator")]
+mod dev;
+#[cfg(feature = "cloud")]
 mod enroll;
 mod error;
+mod fleet;
 mod forwarder;
 mod help;
 mod identity;
+mod job;
+mod medic;
 mod message;
+mod monitor;
 mod node;
+mod policy;
 mod project;
 mod reset;
 mod secure_channel;
 mod service;
+#[cfg(feature = "cloud")]
 mod space;
+mod state;
+#[cfg(feature = "cloud")]
 mod subscription;
 mod tcp;
+#[cfg(feature = "tui")]
 mod terminal;
+#[cfg(feature = "upgrade-check")]
 mod upgrade;
 mod util;
 mod vault;
 mod version;
+mod webhook;
 
 use anyhow::Context;
 use authenticated::AuthenticatedCommand;
 use completion::CompletionCommand;
 use configuration::ConfigurationCommand;
+use contact::ContactCommand;
 use credential::CredentialCommand;
+#[cfg(feature = "mock-orchestrator")]
+use dev::DevCommand;
+#[cfg(feature = "cloud")]
 use enroll::EnrollCommand;
 use error::Result;
+use fleet::FleetCommand;
 use forwarder::ForwarderCommand;
 use identity::IdentityCommand;
+use job::JobCommand;
+use medic::MedicCommand;
 use message::MessageCommand;
+use monitor::MonitorCommand;
 use node::NodeCommand;
+use policy::PolicyCommand;
 use project::ProjectCommand;
 use rand::prelude::random;
 use reset::ResetCommand;
 use secure_channel::{listener::SecureChannelListenerCommand, SecureChannelCommand};
 use service::ServiceCommand;
+#[cfg(feature = "cloud")]
 use space::SpaceCommand;
+use state::StateCommand;
 use std::path::PathBuf;
 use tcp::{
-    connection::TcpConnectionCommand, inlet::TcpInletCommand, listener::TcpListenerCommand,
-    outlet::TcpOutletCommand,
+    connection::TcpConnectionCommand, http_proxy::HttpProxyCommand, inlet::TcpInletCommand,
+    listener::TcpListenerCommand, outlet::TcpOutletCommand, socks_proxy::SocksProxyCommand,
 };
 use util::{exitcode, exitcode::ExitCode, setup_logging, OckamConfig};
 use vault::VaultCommand;
 use version::Version;
+use webhook::WebhookCommand;
 
+#[cfg(feature = "cloud")]
 use crate::admin::AdminCommand;
+#[cfg(feature = "cloud")]
 use crate::subscription::SubscriptionCommand;
 use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+#[cfg(feature = "upgrade-check")]
 use upgrade::check_if_an_upgrade_is_available;
 
 const ABOUT: &str = "\
@@ -201,6 +232,10 @@ pub struct GlobalArgs {
     )]
     output_format: OutputFormat,
 
+    /// Maximum time in seconds to wait for a node to process a request
+    #[arg(global = true, long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
     // if test_argument_parser is true, command arguments are checked
     // but the command is not executed.
     #[arg(global = true, long, hide = true)]
@@ -249,8 +284,10 @@ impl CommandGlobalOpts {
 
 #[derive(Debug, Subcommand)]
 pub enum OckamSubcommand {
+    #[cfg(feature = "cloud")]
     #[command(display_order = 800)]
     Enroll(EnrollCommand),
+    #[cfg(feature = "cloud")]
     #[command(display_order = 801)]
     Space(SpaceCommand),
     #[command(display_order = 802)]
@@ -270,6 +307,10 @@ pub enum OckamSubcommand {
     TcpOutlet(TcpOutletCommand),
     #[command(display_order = 816)]
     TcpInlet(TcpInletCommand),
+    #[command(display_order = 816)]
+    SocksProxy(SocksProxyCommand),
+    #[command(display_order = 816)]
+    HttpProxy(HttpProxyCommand),
     #[command(display_order = 817)]
     SecureChannelListener(SecureChannelListenerCommand),
     #[command(display_order = 818)]
@@ -278,6 +319,22 @@ pub enum OckamSubcommand {
     Forwarder(ForwarderCommand),
     #[command(display_order = 820)]
     Message(MessageCommand),
+    #[command(display_order = 821)]
+    Job(JobCommand),
+    #[command(display_order = 822)]
+    Fleet(FleetCommand),
+    #[command(display_order = 822)]
+    Contact(ContactCommand),
+    #[command(display_order = 823)]
+    Monitor(MonitorCommand),
+    #[command(display_order = 824)]
+    Webhook(WebhookCommand),
+    #[command(display_order = 825)]
+    Medic(MedicCommand),
+    #[command(display_order = 826)]
+    State(StateCommand),
+    #[command(display_order = 827)]
+    Policy(PolicyCommand),
 
     #[command(display_order = 900)]
     Completion(CompletionCommand),
@@ -287,8 +344,12 @@ pub enum OckamSubcommand {
     Credential(CredentialCommand),
     Service(ServiceCommand),
     Vault(VaultCommand),
+    #[cfg(feature = "cloud")]
     Subscription(SubscriptionCommand),
+    #[cfg(feature = "cloud")]
     Admin(AdminCommand),
+    #[cfg(feature = "mock-orchestrator")]
+    Dev(DevCommand),
 }
 
 pub fn run() {
@@ -297,14 +358,31 @@ pub fn run() {
         .collect::<Vec<_>>();
     let args = input.clone();
     let command: OckamCommand = OckamCommand::parse_from(input);
+    #[cfg(feature = "upgrade-check")]
     if !command.global_args.test_argument_parser {
         check_if_an_upgrade_is_available();
     }
 
+    // Loading the configuration migrates it, so the state is managed before
+    if let OckamSubcommand::State(c) = command.subcommand {
+        if !command.global_args.test_argument_parser {
+            c.run();
+        }
+        return;
+    }
+
     let config = OckamConfig::load();
 
     if !command.global_args.quiet {
-        setup_logging(command.global_args.verbose, command.global_args.no_color);
+        let log_sink = match &command.subcommand {
+            OckamSubcommand::Node(c) => c.log_sink(),
+            _ => None,
+        };
+        setup_logging(
+            command.global_args.verbose,
+            command.global_args.no_color,
+            log_sink.as_ref().map(|(s, i)| (*s, i.as_str())),
+        );
         tracing::debug!("{}", Version::short());
         tracing::debug!("Parsed {:?}", &command);
     }
@@ -333,16 +411,27 @@ pub fn run() {
     match command.subcommand {
         OckamSubcommand::Authenticated(c) => c.run(),
         OckamSubcommand::Configuration(c) => c.run(options),
+        #[cfg(feature = "cloud")]
         OckamSubcommand::Enroll(c) => c.run(options),
         OckamSubcommand::Forwarder(c) => c.run(options),
         OckamSubcommand::Message(c) => c.run(options),
+        OckamSubcommand::Job(c) => c.run(options),
+        OckamSubcommand::Fleet(c) => c.run(options),
+        OckamSubcommand::Contact(c) => c.run(options),
+        OckamSubcommand::Monitor(c) => c.run(options),
+        OckamSubcommand::Webhook(c) => c.run(options),
+        OckamSubcommand::Medic(c) => c.run(options),
+        OckamSubcommand::Policy(c) => c.run(options),
         OckamSubcommand::Node(c) => c.run(options),
         OckamSubcommand::Project(c) => c.run(options),
+        #[cfg(feature = "cloud")]
         OckamSubcommand::Space(c) => c.run(options),
         OckamSubcommand::TcpConnection(c) => c.run(options),
         OckamSubcommand::TcpInlet(c) => c.run(options),
         OckamSubcommand::TcpListener(c) => c.run(options),
         OckamSubcommand::TcpOutlet(c) => c.run(options),
+        OckamSubcommand::SocksProxy(c) => c.run(options),
+        OckamSubcommand::HttpProxy(c) => c.run(options),
         OckamSubcommand::Vault(c) => c.run(options),
         OckamSubcommand::Identity(c) => c.run(options),
         OckamSubcommand::SecureChannel(c) => c.run(options),
@@ -350,9 +439,14 @@ pub fn run() {
         OckamSubcommand::Service(c) => c.run(options),
         OckamSubcommand::Completion(c) => c.run(),
         OckamSubcommand::Credential(c) => c.run(options),
+        #[cfg(feature = "cloud")]
         OckamSubcommand::Subscription(c) => c.run(options),
         OckamSubcommand::Reset(c) => c.run(options),
+        OckamSubcommand::State(c) => c.run(),
+        #[cfg(feature = "cloud")]
         OckamSubcommand::Admin(c) => c.run(options),
+        #[cfg(feature = "mock-orchestrator")]
+        OckamSubcommand::Dev(c) => c.run(),
     }
 }
 
@@ -0,0 +1,72 @@
+use clap::{Args, Subcommand};
+
+pub(crate) use pause::PauseCommand;
+pub(crate) use recover::RecoverCommand;
+pub(crate) use resume::ResumeCommand;
+pub(crate) use set::SetCommand;
+pub(crate) use show::ShowCommand;
+
+use crate::{help, CommandGlobalOpts};
+
+mod pause;
+mod recover;
+mod resume;
+mod set;
+mod show;
+
+const HELP_DETAIL: &str = "\
+About:
+    The medic of a node keeps its sessions alive, e.g. the sessions of its forwarders.
+    It pings every session at a regular interval and replaces the sessions which stop
+    answering. Its checks can be paused, e.g. during a planned maintenance of the
+    remote nodes, and a session can be recovered right away.
+
+Examples:
+```sh
+    # Show the medic of node n1 and its sessions
+    $ ockam medic show --at n1
+
+    # Check the sessions every 10 seconds
+    $ ockam medic set --at n1 --interval 10s
+
+    # Stop and restart checking the sessions
+    $ ockam medic pause --at n1
+    $ ockam medic resume --at n1
+
+    # Recover a session, given the start of its key
+    $ ockam medic recover 5d3fd7cb --at n1
+```
+";
+
+/// Manage the Medic keeping sessions alive
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    help_template = help::template(HELP_DETAIL)
+)]
+pub struct MedicCommand {
+    #[command(subcommand)]
+    subcommand: MedicSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum MedicSubcommand {
+    Show(ShowCommand),
+    Set(SetCommand),
+    Pause(PauseCommand),
+    Resume(ResumeCommand),
+    Recover(RecoverCommand),
+}
+
+impl MedicCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        match self.subcommand {
+            MedicSubcommand::Show(c) => c.run(opts),
+            MedicSubcommand::Set(c) => c.run(opts),
+            MedicSubcommand::Pause(c) => c.run(opts),
+            MedicSubcommand::Resume(c) => c.run(opts),
+            MedicSubcommand::Recover(c) => c.run(opts),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use clap::Args;
+use ockam::Context;
+use ockam_core::api::Request;
+
+use crate::medic::HELP_DETAIL;
+use crate::util::{get_final_element, node_rpc, Rpc};
+use crate::{help, CommandGlobalOpts, Result};
+
+/// Recover a session right away
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct RecoverCommand {
+    /// Key of the session to recover, or its first characters.
+    session: String,
+
+    /// Node on which the session runs.
+    #[arg(long, id = "NODE", display_order = 900)]
+    at: String,
+}
+
+impl RecoverCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, RecoverCommand)) -> Result<()> {
+    let node = get_final_element(&cmd.at);
+    let mut rpc = Rpc::background(&ctx, &opts, node)?;
+    rpc.request(Request::post(format!(
+        "/node/medic/sessions/{}/recover",
+        cmd.session
+    )))
+    .await?;
+    rpc.is_ok()?;
+    println!("Recovering session {}", cmd.session);
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::medic::UpdateMedic;
+use ockam_core::api::Request;
+
+use crate::medic::HELP_DETAIL;
+use crate::util::{get_final_element, node_rpc, Rpc};
+use crate::{help, CommandGlobalOpts, Result};
+
+/// Resume the checks of the sessions of a node
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct ResumeCommand {
+    /// Node whose medic to resume.
+    #[arg(long, id = "NODE", display_order = 900)]
+    at: String,
+}
+
+impl ResumeCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, ResumeCommand)) -> Result<()> {
+    let node = get_final_element(&cmd.at);
+    let body = UpdateMedic::new().pause(false);
+    let mut rpc = Rpc::background(&ctx, &opts, node)?;
+    rpc.request(Request::put("/node/medic").body(body)).await?;
+    rpc.is_ok()?;
+    println!("Medic resumed on node {}", node);
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::medic::{MedicStatus, UpdateMedic};
+use ockam_core::api::Request;
+
+use crate::medic::HELP_DETAIL;
+use crate::util::{get_final_element, node_rpc, parse_interval, Rpc};
+use crate::{help, CommandGlobalOpts, Result};
+
+/// Change the settings of the Medic of a node
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct SetCommand {
+    /// Node whose medic to change.
+    #[arg(long, id = "NODE", display_order = 900)]
+    at: String,
+
+    /// Time between two checks of the sessions, e.g. 10s or 1m.
+    #[arg(long, id = "INTERVAL", value_parser = parse_interval, display_order = 900)]
+    interval: Duration,
+}
+
+impl SetCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, SetCommand)) -> Result<()> {
+    let node = get_final_element(&cmd.at);
+    let body = UpdateMedic::new().interval(cmd.interval);
+    let mut rpc = Rpc::background(&ctx, &opts, node)?;
+    rpc.request(Request::put("/node/medic").body(body)).await?;
+    rpc.parse_and_print_response::<MedicStatus>()?;
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use anyhow::anyhow;
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::monitors::{AlertHook, CreateMonitor, MonitorKind, MonitorStatus};
+use ockam_core::api::Request;
+
+use crate::monitor::HELP_DETAIL;
+use crate::util::{get_final_element, node_rpc, parse_interval, Rpc};
+use crate::{help, CommandGlobalOpts, Result};
+
+/// Create SLO Monitors
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct CreateCommand {
+    /// The indicator to check: rtt or recoveries.
+    kind: MonitorKind,
+
+    /// Node whose sessions to monitor.
+    #[arg(long, id = "NODE", display_order = 900)]
+    at: String,
+
+    /// Objective: a duration such as 500ms or 2s for rtt monitors,
+    /// a number of recoveries for recoveries monitors.
+    #[arg(long, id = "THRESHOLD", display_order = 900)]
+    above: String,
+
+    /// How long the rtt must stay above the threshold, or the period over
+    /// which recoveries are counted, e.g. 90s, 5m or 1h.
+    #[arg(long = "for", id = "PERIOD", value_parser = parse_interval, display_order = 900)]
+    period: Duration,
+
+    /// URL to which alerts are posted.
+    #[arg(
+        long,
+        id = "URL",
+        display_order = 900,
+        required_unless_present = "COMMAND"
+    )]
+    webhook: Option<String>,
+
+    /// Command run with every alert on its standard input.
+    #[arg(long, id = "COMMAND", display_order = 900)]
+    exec: Option<String>,
+}
+
+impl CreateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> Result<()> {
+    let hook = AlertHook::new(
+        cmd.webhook.as_deref().map(Into::into),
+        cmd.exec.as_deref().map(Into::into),
+    );
+    let body = match cmd.kind {
+        MonitorKind::Rtt => {
+            let max = parse_rtt(&cmd.above).map_err(|e| anyhow!(e))?;
+            CreateMonitor::rtt(max, cmd.period, hook)
+        }
+        MonitorKind::Recoveries => {
+            let max = cmd
+                .above
+                .parse()
+                .map_err(|_| anyhow!("invalid number of recoveries {}", cmd.above))?;
+            CreateMonitor::recoveries(max, cmd.period, hook)
+        }
+    };
+    let node = get_final_element(&cmd.at);
+    let mut rpc = Rpc::background(&ctx, &opts, node)?;
+    rpc.request(Request::post("/node/monitors").body(body))
+        .await?;
+    rpc.parse_and_print_response::<MonitorStatus>()?;
+    Ok(())
+}
+
+/// Parse a round-trip time in `ms` or `s`.
+fn parse_rtt(s: &str) -> std::result::Result<Duration, String> {
+    let rtt = if let Some(ms) = s.strip_suffix("ms") {
+        ms.parse().map(Duration::from_millis)
+    } else {
+        s.trim_end_matches('s').parse().map(Duration::from_secs)
+    };
+    match rtt {
+        Ok(rtt) if rtt.as_millis() > 0 => Ok(rtt),
+        _ => Err(format!(
+            "invalid round-trip time {s}, expected e.g. 500ms or 2s"
+        )),
+    }
+}
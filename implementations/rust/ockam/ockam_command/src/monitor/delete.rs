@@ -0,0 +1,35 @@
+use clap::Args;
+use ockam::Context;
+use ockam_core::api::Request;
+
+use crate::monitor::HELP_DETAIL;
+use crate::util::{get_final_element, node_rpc, Rpc};
+use crate::{help, CommandGlobalOpts, Result};
+
+/// Delete SLO Monitors
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct DeleteCommand {
+    /// Id of the monitor to delete.
+    id: String,
+
+    /// Node on which the monitor runs.
+    #[arg(long, id = "NODE", display_order = 900)]
+    at: String,
+}
+
+impl DeleteCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, DeleteCommand)) -> Result<()> {
+    let node = get_final_element(&cmd.at);
+    let mut rpc = Rpc::background(&ctx, &opts, node)?;
+    rpc.request(Request::delete(format!("/node/monitors/{}", cmd.id)))
+        .await?;
+    rpc.is_ok()?;
+    println!("Monitor {} deleted", cmd.id);
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use clap::{Args, Subcommand};
+
+pub(crate) use create::CreateCommand;
+pub(crate) use delete::DeleteCommand;
+pub(crate) use list::ListCommand;
+
+use crate::{help, CommandGlobalOpts};
+
+mod create;
+mod delete;
+mod list;
+
+const HELP_DETAIL: &str = "\
+About:
+    Monitors check service level objectives on the sessions of a node, e.g. the
+    sessions of its forwarders, and send an alert when a session starts or stops
+    violating the objective. Alerts are JSON documents posted to a webhook, or
+    written to the standard input of a local command.
+
+    Supported monitors:
+        - rtt: the ping round-trip time stays above a duration for a while
+        - recoveries: the session was recovered more than a number of times
+          within a period
+
+Examples:
+```sh
+    # Alert when the round-trip time of a session stays above 500ms for 5 minutes
+    $ ockam monitor create rtt --at n1 --above 500ms --for 5m --webhook https://example.com/alerts
+
+    # Alert when a session was recovered more than 3 times in the last hour
+    $ ockam monitor create recoveries --at n1 --above 3 --for 1h --exec \"logger -t ockam\"
+
+    # List the monitors of node n1
+    $ ockam monitor list --at n1
+
+    # Delete a monitor
+    $ ockam monitor delete 5d3fd7cb --at n1
+```
+";
+
+/// Manage SLO Monitors
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    help_template = help::template(HELP_DETAIL)
+)]
+pub struct MonitorCommand {
+    #[command(subcommand)]
+    subcommand: MonitorSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum MonitorSubcommand {
+    Create(CreateCommand),
+    Delete(DeleteCommand),
+    List(ListCommand),
+}
+
+impl MonitorCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        match self.subcommand {
+            MonitorSubcommand::Create(c) => c.run(opts),
+            MonitorSubcommand::Delete(c) => c.run(opts),
+            MonitorSubcommand::List(c) => c.run(opts),
+        }
+    }
+}
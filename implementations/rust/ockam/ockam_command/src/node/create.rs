@@ -24,13 +24,15 @@ use crate::{
     util::{connect_to, embedded_node, find_available_port, startup, OckamConfig},
     CommandGlobalOpts,
 };
-use ockam::{Address, AsyncTryClone, NodeBuilder, TCP};
+use ockam::tcp::{ListenerOptions, UpstreamProxy};
+use ockam::{Address, AsyncTryClone, ForwardingService, NodeBuilder, TCP};
 use ockam::{Context, TcpTransport};
 use ockam_api::{
+    config::cli::LogSink,
     nodes::models::transport::{TransportMode, TransportType},
-    nodes::{NodeManager, NODEMANAGER_ADDR},
+    nodes::{privileges, stdio, NodeManager, NodeManagerWorker, NODEMANAGER_ADDR},
 };
-use ockam_core::LOCAL;
+use ockam_core::{route, LOCAL};
 
 /// Create Nodes
 #[derive(Clone, Debug, Args)]
@@ -86,6 +88,79 @@ pub struct CreateCommand {
 
     #[arg(long, hide = true)]
     pub config: Option<PathBuf>,
+
+    /// Where to write the logs of the node: file, journald, syslog,
+    /// syslog:<path> or syslog:<host>:<port> (UDP).
+    ///
+    /// The logs are written to files in the node state directory by default.
+    #[arg(display_order = 900, long, value_name = "SINK")]
+    pub log_sink: Option<LogSink>,
+
+    /// Run a relay node which only forwards messages.
+    ///
+    /// A relay has no vault nor identity: it can only verify the
+    /// identities of the authorities it trusts.
+    #[arg(display_order = 900, long, conflicts_with = "project")]
+    pub relay: bool,
+
+    /// Serve `GET /live` and `GET /ready` over plain HTTP at this address.
+    ///
+    /// The node is live as soon as it runs, and ready once its services
+    /// are started, it is enrolled if it is a member of a project, and
+    /// its forwarders are up. Unready nodes answer 503 with the reasons.
+    #[arg(display_order = 900, long, value_name = "SOCKET_ADDRESS")]
+    pub health_address: Option<SocketAddr>,
+
+    /// Connect to other nodes, e.g. the orchestrator or remote relays,
+    /// through this proxy: socks5://host:port or http://host:port for an
+    /// HTTP proxy supporting CONNECT.
+    ///
+    /// The OCKAM_TCP_PROXY variable gives a proxy too. Loopback addresses,
+    /// and the targets listed in OCKAM_TCP_NO_PROXY, are connected to
+    /// directly.
+    #[arg(display_order = 900, long, value_name = "URL")]
+    pub tcp_proxy: Option<UpstreamProxy>,
+
+    /// Read the API requests of a foreground node from stdin and write
+    /// their responses to stdout, instead of listening on a TCP socket.
+    ///
+    /// Requests and responses are CBOR encoded, each preceded by its length
+    /// as a big-endian u32. The node stops once stdin is closed.
+    #[arg(
+        display_order = 900,
+        long,
+        requires = "foreground",
+        conflicts_with = "launch_config"
+    )]
+    pub stdio: bool,
+
+    /// Listen on the socket passed by systemd for the TCP listener address,
+    /// if any, with socket activation.
+    ///
+    /// This lets a node managed by systemd listen on a privileged port
+    /// without running as root.
+    #[arg(
+        display_order = 900,
+        long,
+        requires = "foreground",
+        conflicts_with = "stdio"
+    )]
+    pub socket_activation: bool,
+
+    /// Switch to this user once the node is set up, when it is started as
+    /// root, e.g. to listen on a privileged port.
+    ///
+    /// The node state directory is given to the user, who must be able to
+    /// reach it, e.g. with OCKAM_HOME=/var/lib/ockam. The node switches
+    /// once it listens and its launch config is run, before serving any
+    /// request.
+    #[arg(display_order = 900, long, value_name = "USER")]
+    pub user: Option<String>,
+
+    /// Switch to this group with the --user, instead of the group of the
+    /// user.
+    #[arg(display_order = 900, long, value_name = "GROUP", requires = "user")]
+    pub group: Option<String>,
 }
 
 impl Default for CreateCommand {
@@ -102,6 +177,14 @@ impl Default for CreateCommand {
             no_watchdog: false,
             project: None,
             config: None,
+            log_sink: None,
+            relay: false,
+            health_address: None,
+            tcp_proxy: None,
+            stdio: false,
+            socket_activation: false,
+            user: None,
+            group: None,
         }
     }
 }
@@ -118,7 +201,10 @@ impl CreateCommand {
             // Thus we need to create the node dir so that subsequent
             // calls to it don't fail
             if cfg.get_node_dir(&cmd.node_name).is_err() {
-                println!("Creating node directory...");
+                // Stdout only carries the responses of a stdio node
+                if !cmd.stdio {
+                    println!("Creating node directory...");
+                }
                 if let Err(e) = cfg.create_node(&cmd.node_name, addr, verbose) {
                     eprintln!(
                         "failed to update node configuration for '{}': {}",
@@ -126,6 +212,21 @@ impl CreateCommand {
                     );
                     std::process::exit(exitcode::CANTCREAT);
                 }
+                if let Some(sink) = &cmd.log_sink {
+                    cfg.set_node_log_sink(&cmd.node_name, sink.clone())
+                        .expect("should never panic");
+                }
+                cfg.set_node_relay(&cmd.node_name, cmd.relay)
+                    .expect("should never panic");
+                cfg.set_node_health_address(&cmd.node_name, cmd.health_address)
+                    .expect("should never panic");
+                cfg.set_node_tcp_proxy(
+                    &cmd.node_name,
+                    cmd.tcp_proxy.as_ref().map(|p| p.to_string()),
+                )
+                .expect("should never panic");
+                cfg.set_node_user(&cmd.node_name, cmd.user.clone(), cmd.group.clone())
+                    .expect("should never panic");
 
                 // Save the config update
                 if let Err(e) = cfg.persist_config_updates() {
@@ -153,11 +254,11 @@ impl CreateCommand {
             .unwrap();
             connect_to(
                 addr.port(),
-                (cfg.clone(), cmd.node_name, true),
+                (cfg.clone(), cmd.node_name.clone(), true),
                 print_query_status,
             );
-            if let Some(config) = self.config {
-                crate::node::util::run::CommandsRunner::run(&config)
+            if let Some(config) = &cmd.config {
+                crate::node::util::run::CommandsRunner::run(config)
                     .context("Failed to run commands from config")
                     .unwrap();
             }
@@ -193,6 +294,19 @@ impl CreateCommand {
             );
             std::process::exit(exitcode::CANTCREAT);
         }
+        if let Some(sink) = &cmd.log_sink {
+            cfg.set_node_log_sink(&cmd.node_name, sink.clone())
+                .expect("should never panic");
+        }
+        cfg.set_node_relay(&cmd.node_name, cmd.relay)
+            .expect("should never panic");
+        cfg.set_node_health_address(&cmd.node_name, cmd.health_address)
+            .expect("should never panic");
+        let tcp_proxy = cmd.tcp_proxy.as_ref().map(|p| p.to_string());
+        cfg.set_node_tcp_proxy(&cmd.node_name, tcp_proxy.clone())
+            .expect("should never panic");
+        cfg.set_node_user(&cmd.node_name, cmd.user.clone(), cmd.group.clone())
+            .expect("should never panic");
 
         // Save the config update
         if let Err(e) = cfg.persist_config_updates() {
@@ -200,7 +314,9 @@ impl CreateCommand {
             std::process::exit(exitcode::IOERR);
         }
 
-        create_default_identity_if_needed(&ctx, cfg).await?;
+        if !cmd.relay {
+            create_default_identity_if_needed(&ctx, cfg).await?;
+        }
 
         // Construct the arguments list and re-execute the ockam
         // CLI in foreground mode to start the newly created node
@@ -213,6 +329,11 @@ impl CreateCommand {
             &cmd.node_name,
             &cmd.tcp_listener_address,
             cmd.project.as_deref(),
+            cmd.log_sink.as_ref(),
+            cmd.relay,
+            cmd.health_address,
+            tcp_proxy.as_deref(),
+            cmd.user.as_deref().map(|u| (u, cmd.group.as_deref())),
         );
 
         // Unless this CLI was called from another watchdog we
@@ -262,11 +383,11 @@ async fn run_background_node_impl(
     cfg: OckamConfig,
 ) -> Result<()> {
     // This node was initially created as a foreground node
-    if !c.child_process {
+    if !c.child_process && !c.relay {
         create_default_identity_if_needed(ctx, &cfg).await?;
     }
 
-    let identity_override = if c.skip_defaults || c.no_shared_identity {
+    let identity_override = if c.skip_defaults || c.no_shared_identity || c.relay {
         None
     } else {
         Some(get_identity_override(ctx, &cfg).await?)
@@ -285,25 +406,48 @@ async fn run_background_node_impl(
     };
 
     let tcp = TcpTransport::create(ctx).await?;
-    let bind = c.tcp_listener_address;
-    tcp.listen(&bind).await?;
+    if let Some(proxy) = c.tcp_proxy.clone() {
+        tcp.set_proxy(Some(proxy));
+    }
+    let api_transport = if c.stdio {
+        (
+            TransportType::Stdio,
+            TransportMode::Listen,
+            "stdio".to_string(),
+        )
+    } else {
+        let options = ListenerOptions::new().with_socket_activation(c.socket_activation);
+        let bind = tcp
+            .listen_with_options(&c.tcp_listener_address, options)
+            .await?;
+        (TransportType::Tcp, TransportMode::Listen, bind.to_string())
+    };
 
     let node_dir = cfg.get_node_dir(&c.node_name)?;
     let node_man = NodeManager::create(
         ctx,
         c.node_name.clone(),
-        node_dir,
+        node_dir.clone(),
         identity_override,
-        c.skip_defaults || c.launch_config.is_some(),
+        c.skip_defaults || c.launch_config.is_some() || c.relay,
         c.enable_credential_checks,
-        Some(&cfg.authorities(&c.node_name)?.snapshot()),
+        Some(&cfg.trusted_authorities(&c.node_name)?),
         project_id,
-        (TransportType::Tcp, TransportMode::Listen, bind),
+        api_transport,
         tcp.async_try_clone().await?,
     )
     .await?;
 
-    ctx.start_worker(NODEMANAGER_ADDR, node_man).await?;
+    let mut node_manager_worker = NodeManagerWorker::new(node_man);
+    if let Some(health_address) = c.health_address {
+        node_manager_worker = node_manager_worker.with_health_address(health_address);
+    }
+    ctx.start_worker(NODEMANAGER_ADDR, node_manager_worker)
+        .await?;
+
+    if c.relay {
+        ForwardingService::create(ctx).await?;
+    }
 
     if let Some(path) = c.launch_config {
         let node_opts = super::NodeOpts {
@@ -312,6 +456,17 @@ async fn run_background_node_impl(
         start_services(ctx, &tcp, &path, addr, node_opts).await?
     }
 
+    if let Some(user) = &c.user {
+        privileges::drop_privileges(user, c.group.as_deref(), Some(&node_dir))?;
+    }
+
+    if c.stdio {
+        let (stdin, stdout) = (tokio::io::stdin(), tokio::io::stdout());
+        stdio::serve(ctx, route![NODEMANAGER_ADDR], stdin, stdout).await?;
+        ctx.stop().await?;
+        return Ok(());
+    }
+
     Ok(())
 }
 
@@ -360,7 +515,8 @@ async fn start_services(
             let ids = cfg.authorized_identifiers;
             let rte = addr.clone().into();
             println!("starting secure-channel listener ...");
-            secure_channel_listener::create_listener(ctx, adr, ids, rte).await?;
+            secure_channel_listener::create_listener(ctx, adr, ids, None, None, None, None, rte)
+                .await?;
         }
     }
     if let Some(cfg) = config.verifier {
@@ -0,0 +1,472 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context as _};
+use clap::Args;
+use colorful::Colorful;
+use ockam::{Address, Context, TcpTransport};
+use ockam_api::clean_multiaddr;
+use ockam_api::nodes::models::forwarder::ForwarderList;
+use ockam_api::nodes::models::portal::{InletList, OutletList};
+use ockam_core::api::Request;
+use ockam_multiaddr::MultiAddr;
+use serde::{Deserialize, Serialize};
+
+use crate::util::output::Output;
+use crate::util::{get_final_element, node_rpc, OckamConfig, RpcBuilder};
+use crate::{help, CommandGlobalOpts, Result};
+
+const HELP_DETAIL: &str = "\
+About:
+    Compare a node with the desired state declared in a node file, and
+    report the differences without applying them.
+
+    The node file lists the relays, inlets and outlets the node should
+    have. Relays are matched by name, inlets by their listening address
+    and outlets by their worker address:
+
+    name: n1
+    relays:
+      - name: db
+    inlets:
+      - from: 127.0.0.1:5432
+        to: /project/default/service/forward_to_db/secure/api/service/outlet
+        check_credential: true
+    outlets:
+      - from: /service/outlet
+        to: 127.0.0.1:5432
+        check_credential: true
+
+Examples:
+```sh
+    # Report the differences between node n1 and node.yaml
+    $ ockam node diff -f node.yaml
+
+    # Fail when the node has drifted, e.g. in a scheduled job
+    $ ockam node diff -f node.yaml --exit-code
+```
+";
+
+/// Show differences between a node and its node file
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct DiffCommand {
+    /// Node file declaring the desired state of the node.
+    #[arg(short, long, id = "FILE")]
+    file: PathBuf,
+
+    /// Node to compare, if the node file doesn't name one.
+    #[arg(long, id = "NODE")]
+    node: Option<String>,
+
+    /// Exit with status 1 if the node differs from the node file.
+    #[arg(long)]
+    exit_code: bool,
+}
+
+impl DiffCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(rpc, (options, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, DiffCommand)) -> Result<()> {
+    let spec = NodeSpec::read(&cmd.file)?;
+    let node = match (&cmd.node, &spec.name) {
+        (Some(n), _) | (None, Some(n)) => get_final_element(n).to_string(),
+        (None, None) => "default".to_string(),
+    };
+    let desired = spec.normalize(&opts.config)?;
+
+    let tcp = TcpTransport::create(&ctx).await?;
+    let mut rpc = RpcBuilder::new(&ctx, &opts, &node).tcp(&tcp)?.build();
+    rpc.request(Request::get("/node/forwarder")).await?;
+    let relays = rpc
+        .parse_response::<ForwarderList>()?
+        .list
+        .iter()
+        .map(|f| f.remote_address().to_string())
+        .collect();
+    let mut rpc = RpcBuilder::new(&ctx, &opts, &node).tcp(&tcp)?.build();
+    rpc.request(Request::get("/node/inlet")).await?;
+    let inlets = rpc
+        .parse_response::<InletList>()?
+        .list
+        .into_iter()
+        .map(|i| PortalSpec {
+            from: i.bind_addr.to_string(),
+            to: i.outlet_route.map(|r| r.to_string()).unwrap_or_default(),
+            check_credential: i.check_credential.unwrap_or_default(),
+        })
+        .collect();
+    let mut rpc = RpcBuilder::new(&ctx, &opts, &node).tcp(&tcp)?.build();
+    rpc.request(Request::get("/node/outlet")).await?;
+    let outlets = rpc
+        .parse_response::<OutletList>()?
+        .list
+        .into_iter()
+        .map(|o| PortalSpec {
+            from: worker_address(&o.worker_addr),
+            to: o.tcp_addr.to_string(),
+            check_credential: o.check_credential.unwrap_or_default(),
+        })
+        .collect();
+    let actual = NodeState {
+        relays,
+        inlets,
+        outlets,
+    };
+
+    let diff = NodeDiff {
+        node,
+        differences: diff(&desired, &actual),
+    };
+    let drifted = !diff.differences.is_empty();
+    rpc.print_response(diff)?;
+    if drifted && cmd.exit_code {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Desired state of a node, as declared in a node file
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NodeSpec {
+    name: Option<String>,
+    #[serde(default)]
+    relays: Vec<RelaySpec>,
+    #[serde(default)]
+    inlets: Vec<PortalSpec>,
+    #[serde(default)]
+    outlets: Vec<PortalSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RelaySpec {
+    name: String,
+}
+
+/// An inlet or outlet, forwarding connections `from` an address `to` another.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PortalSpec {
+    from: String,
+    to: String,
+    #[serde(default)]
+    check_credential: bool,
+}
+
+impl NodeSpec {
+    fn read(path: &Path) -> Result<Self> {
+        let s =
+            std::fs::read_to_string(path).with_context(|| anyhow!("failed to read {:?}", path))?;
+        let spec =
+            serde_yaml::from_str(&s).with_context(|| anyhow!("invalid node file {:?}", path))?;
+        Ok(spec)
+    }
+
+    /// Resolve addresses the way the corresponding `create` commands do,
+    /// so that they can be compared with the ones reported by the node.
+    fn normalize(self, cfg: &OckamConfig) -> Result<NodeState> {
+        let lookup = cfg.lookup();
+        let inlets = self
+            .inlets
+            .into_iter()
+            .map(|i| {
+                let from: SocketAddr = i
+                    .from
+                    .parse()
+                    .with_context(|| anyhow!("invalid inlet address {}", i.from))?;
+                let to: MultiAddr =
+                    i.to.parse()
+                        .with_context(|| anyhow!("invalid outlet route {}", i.to))?;
+                let (to, _) = clean_multiaddr(&to, &lookup)
+                    .ok_or_else(|| anyhow!("failed to normalize outlet route {}", i.to))?;
+                Ok(PortalSpec {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    check_credential: i.check_credential,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let outlets = self
+            .outlets
+            .into_iter()
+            .map(|o| {
+                let to: SocketAddr =
+                    o.to.parse()
+                        .with_context(|| anyhow!("invalid outlet target {}", o.to))?;
+                Ok(PortalSpec {
+                    from: worker_address(get_final_element(&o.from)),
+                    to: to.to_string(),
+                    check_credential: o.check_credential,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(NodeState {
+            relays: self.relays.into_iter().map(|r| r.name).collect(),
+            inlets,
+            outlets,
+        })
+    }
+}
+
+fn worker_address(addr: &str) -> String {
+    Address::from_string(addr).address().to_string()
+}
+
+/// The relays, inlets and outlets of a node
+#[derive(Debug, Default)]
+struct NodeState {
+    relays: Vec<String>,
+    inlets: Vec<PortalSpec>,
+    outlets: Vec<PortalSpec>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Drift {
+    /// Declared in the node file but absent from the node
+    Missing,
+    /// Present on the node but not declared in the node file
+    Extra,
+    /// Present on both, with different settings
+    Changed,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct Difference {
+    drift: Drift,
+    kind: &'static str,
+    name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    details: Vec<String>,
+}
+
+impl Difference {
+    fn new(drift: Drift, kind: &'static str, name: &str) -> Self {
+        Self {
+            drift,
+            kind,
+            name: name.to_string(),
+            details: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct NodeDiff {
+    node: String,
+    differences: Vec<Difference>,
+}
+
+impl Output for NodeDiff {
+    fn output(&self) -> anyhow::Result<String> {
+        let mut w = String::new();
+        if self.differences.is_empty() {
+            write!(w, "Node {} matches the node file", self.node)?;
+            return Ok(w);
+        }
+        write!(w, "Node {} differs from the node file:", self.node)?;
+        for d in &self.differences {
+            let line = format!("{} {}", d.kind, d.name);
+            match d.drift {
+                Drift::Missing => write!(w, "\n  {}", format!("+ {line} (missing)").green())?,
+                Drift::Extra => write!(w, "\n  {}", format!("- {line} (extra)").red())?,
+                Drift::Changed => write!(w, "\n  {}", format!("~ {line} (changed)").yellow())?,
+            }
+            for detail in &d.details {
+                write!(w, "\n      {detail}")?;
+            }
+        }
+        Ok(w)
+    }
+}
+
+/// Compare the desired state of a node with its actual state.
+fn diff(desired: &NodeState, actual: &NodeState) -> Vec<Difference> {
+    let mut differences = Vec::new();
+
+    // Relays created with `ockam forwarder create` at a node are registered
+    // under a `forward_to_` prefix.
+    let matches = |name: &String, remote: &String| {
+        remote == name || remote.strip_prefix("forward_to_") == Some(name)
+    };
+    for name in &desired.relays {
+        if !actual.relays.iter().any(|r| matches(name, r)) {
+            differences.push(Difference::new(Drift::Missing, "relay", name));
+        }
+    }
+    for remote in &actual.relays {
+        if !desired.relays.iter().any(|d| matches(d, remote)) {
+            differences.push(Difference::new(Drift::Extra, "relay", remote));
+        }
+    }
+
+    compare(&mut differences, "inlet", &desired.inlets, &actual.inlets);
+    compare(
+        &mut differences,
+        "outlet",
+        &desired.outlets,
+        &actual.outlets,
+    );
+    differences
+}
+
+/// Compare portals keyed by their `from` address.
+fn compare(
+    differences: &mut Vec<Difference>,
+    kind: &'static str,
+    desired: &[PortalSpec],
+    actual: &[PortalSpec],
+) {
+    let desired: BTreeMap<_, _> = desired.iter().map(|p| (&p.from, p)).collect();
+    let actual: BTreeMap<_, _> = actual.iter().map(|p| (&p.from, p)).collect();
+    for (name, d) in &desired {
+        match actual.get(name) {
+            None => differences.push(Difference::new(Drift::Missing, kind, name)),
+            Some(a) => {
+                let mut details = vec![];
+                if d.to != a.to {
+                    details.push(format!("to: {} -> {}", a.to, d.to));
+                }
+                if d.check_credential != a.check_credential {
+                    details.push(format!(
+                        "check_credential: {} -> {}",
+                        a.check_credential, d.check_credential
+                    ));
+                }
+                if !details.is_empty() {
+                    differences.push(Difference {
+                        details,
+                        ..Difference::new(Drift::Changed, kind, name)
+                    });
+                }
+            }
+        }
+    }
+    for name in actual.keys() {
+        if !desired.contains_key(name) {
+            differences.push(Difference::new(Drift::Extra, kind, name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inlet(from: &str, to: &str, check_credential: bool) -> PortalSpec {
+        PortalSpec {
+            from: from.to_string(),
+            to: to.to_string(),
+            check_credential,
+        }
+    }
+
+    fn outlet(from: &str, to: &str, check_credential: bool) -> PortalSpec {
+        PortalSpec {
+            from: from.to_string(),
+            to: to.to_string(),
+            check_credential,
+        }
+    }
+
+    #[test]
+    fn parse_node_file() {
+        let spec: NodeSpec = serde_yaml::from_str(
+            r#"
+            name: n1
+            relays:
+              - name: db
+            inlets:
+              - from: 127.0.0.1:5432
+                to: /service/outlet
+            outlets:
+              - from: /service/outlet
+                to: 127.0.0.1:5432
+                check_credential: true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(spec.name.as_deref(), Some("n1"));
+        assert_eq!(spec.relays[0].name, "db");
+        assert_eq!(
+            spec.inlets,
+            vec![inlet("127.0.0.1:5432", "/service/outlet", false)]
+        );
+        assert_eq!(
+            spec.outlets,
+            vec![outlet("/service/outlet", "127.0.0.1:5432", true)]
+        );
+
+        assert!(serde_yaml::from_str::<NodeSpec>("relay: []").is_err());
+    }
+
+    #[test]
+    fn no_differences() {
+        let state = || NodeState {
+            relays: vec!["db".to_string()],
+            inlets: vec![inlet("127.0.0.1:5432", "/service/outlet", false)],
+            outlets: vec![outlet("outlet", "127.0.0.1:5432", true)],
+        };
+        assert!(diff(&state(), &state()).is_empty());
+    }
+
+    #[test]
+    fn relays_match_with_forward_to_prefix() {
+        let desired = NodeState {
+            relays: vec!["db".to_string(), "web".to_string()],
+            ..Default::default()
+        };
+        let actual = NodeState {
+            relays: vec!["forward_to_db".to_string(), "other".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            diff(&desired, &actual),
+            vec![
+                Difference::new(Drift::Missing, "relay", "web"),
+                Difference::new(Drift::Extra, "relay", "other"),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_extra_and_changed_portals() {
+        let desired = NodeState {
+            inlets: vec![
+                inlet("127.0.0.1:5000", "/service/a", true),
+                inlet("127.0.0.1:6000", "/service/b", false),
+            ],
+            outlets: vec![outlet("outlet", "127.0.0.1:5432", true)],
+            ..Default::default()
+        };
+        let actual = NodeState {
+            inlets: vec![
+                inlet("127.0.0.1:5000", "/service/c", false),
+                inlet("127.0.0.1:7000", "/service/b", false),
+            ],
+            outlets: vec![outlet("outlet", "127.0.0.1:5432", true)],
+            ..Default::default()
+        };
+        assert_eq!(
+            diff(&desired, &actual),
+            vec![
+                Difference {
+                    details: vec![
+                        "to: /service/c -> /service/a".to_string(),
+                        "check_credential: false -> true".to_string(),
+                    ],
+                    ..Difference::new(Drift::Changed, "inlet", "127.0.0.1:5000")
+                },
+                Difference::new(Drift::Missing, "inlet", "127.0.0.1:6000"),
+                Difference::new(Drift::Extra, "inlet", "127.0.0.1:7000"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,58 @@
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::events::{EventList, ListEvents};
+use ockam_core::api::Request;
+use std::time::Duration;
+
+use crate::node::HELP_DETAIL;
+use crate::util::{get_final_element, node_rpc, Rpc};
+use crate::{help, CommandGlobalOpts, Result};
+
+/// Time between two polls of the events of a node when following them.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// List the last Events of a node
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct EventsCommand {
+    /// Node whose events to list.
+    #[arg(long, id = "NODE", display_order = 900)]
+    at: String,
+
+    /// Only list the events published after the event with this number.
+    #[arg(long, display_order = 900)]
+    since: Option<u64>,
+
+    /// Keep listing the events as they are published.
+    #[arg(long, short, display_order = 900)]
+    follow: bool,
+}
+
+impl EventsCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, EventsCommand)) -> Result<()> {
+    let node = get_final_element(&cmd.at);
+    if !cmd.follow {
+        let mut rpc = Rpc::background(&ctx, &opts, node)?;
+        rpc.request(Request::get("/node/events").body(ListEvents::new(cmd.since)))
+            .await?;
+        rpc.parse_and_print_response::<EventList>()?;
+        return Ok(());
+    }
+    let mut since = cmd.since;
+    loop {
+        let mut rpc = Rpc::background(&ctx, &opts, node)?;
+        rpc.request(Request::get("/node/events").body(ListEvents::new(since)))
+            .await?;
+        let list = rpc.parse_response::<EventList>()?;
+        for e in list.events {
+            since = Some(e.seq);
+            rpc.print_response(e)?;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
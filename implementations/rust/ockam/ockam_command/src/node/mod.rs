@@ -1,20 +1,27 @@
 mod create;
 mod delete;
+mod diff;
+mod events;
 mod list;
 mod show;
 mod start;
+mod stats;
 mod stop;
 pub mod util;
 
 pub(crate) use create::CreateCommand;
 use delete::DeleteCommand;
+use diff::DiffCommand;
+use events::EventsCommand;
 use list::ListCommand;
 use show::ShowCommand;
 use start::StartCommand;
+use stats::StatsCommand;
 use stop::StopCommand;
 
 use crate::{help, CommandGlobalOpts};
 use clap::{Args, Subcommand};
+use ockam_api::config::cli::LogSink;
 
 const HELP_DETAIL: &str = "\
 About:
@@ -96,9 +103,34 @@ Examples:
     # Create a node, and run it in the foreground with verbose traces
     $ ockam node create n1 --foreground -vvv
 
+    # Create a node which connects to other nodes through a corporate proxy
+    $ ockam node create n1 --tcp-proxy http://proxy.example.com:3128
+
+    # Create a node listening on the socket of a systemd .socket unit, e.g.
+    # with ListenStream=443, from the ExecStart of its .service unit
+    $ ockam node create n1 --foreground --tcp-listener-address 0.0.0.0:443 --socket-activation
+
+    # Create a node as root listening on port 443, then switch it to the
+    # unprivileged user ockam
+    $ sudo OCKAM_HOME=/var/lib/ockam ockam node create n1 --tcp-listener-address 0.0.0.0:443 --user ockam
+
+    # Create a node serving /live and /ready probes over plain HTTP
+    $ ockam node create n1 --health-address 0.0.0.0:8080
+    $ curl http://127.0.0.1:8080/ready
+
+    # Report how a node differs from its node file
+    $ ockam node diff -f node.yaml
+
     # Show information about a specific node
     $ ockam node show n1
 
+    # Follow the events of a node, e.g. forwarders whose recovery failed
+    $ ockam node events --at n1 --follow
+
+    # Account for the workers of a node, then show which ones consume the most
+    $ ockam node stats --at n1 --enable
+    $ ockam node stats --at n1
+
     # List all created nodes
     $ ockam node list
 
@@ -132,12 +164,18 @@ pub enum NodeSubcommand {
     #[command(display_order = 800)]
     Delete(DeleteCommand),
     #[command(display_order = 800)]
+    Diff(DiffCommand),
+    #[command(display_order = 800)]
+    Events(EventsCommand),
+    #[command(display_order = 800)]
     List(ListCommand),
     #[command(display_order = 800)]
     Show(ShowCommand),
     #[command(display_order = 800)]
     Start(StartCommand),
     #[command(display_order = 800)]
+    Stats(StatsCommand),
+    #[command(display_order = 800)]
     Stop(StopCommand),
 }
 
@@ -146,12 +184,27 @@ impl NodeCommand {
         match self.subcommand {
             NodeSubcommand::Create(c) => c.run(options),
             NodeSubcommand::Delete(c) => c.run(options),
+            NodeSubcommand::Diff(c) => c.run(options),
+            NodeSubcommand::Events(c) => c.run(options),
             NodeSubcommand::List(c) => c.run(options),
             NodeSubcommand::Show(c) => c.run(options),
             NodeSubcommand::Start(c) => c.run(options),
+            NodeSubcommand::Stats(c) => c.run(options),
             NodeSubcommand::Stop(c) => c.run(options),
         }
     }
+
+    /// The log sink of a node running in this process, with the
+    /// identifier tagging its logs.
+    pub fn log_sink(&self) -> Option<(&LogSink, String)> {
+        match &self.subcommand {
+            NodeSubcommand::Create(c) if c.foreground => c
+                .log_sink
+                .as_ref()
+                .map(|s| (s, format!("ockam-{}", c.node_name))),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Args)]
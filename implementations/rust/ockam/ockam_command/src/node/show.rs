@@ -3,10 +3,12 @@ use crate::{help, node::HELP_DETAIL, CommandGlobalOpts};
 use anyhow::Context;
 use clap::Args;
 use colorful::Colorful;
+use minicbor::Decoder;
 use ockam::Route;
 use ockam_api::config::cli::NodeConfig;
+use ockam_api::nodes::models::jobs::JobList;
 use ockam_api::nodes::{models::base::NodeStatus, NODEMANAGER_ADDR};
-use ockam_core::api::Status;
+use ockam_core::api::{Request, Response, Status};
 use std::time::Duration;
 
 /// Show Nodes
@@ -41,6 +43,33 @@ impl ShowCommand {
 // clippy to stop complainaing about it.
 #[allow(clippy::too_many_arguments)]
 fn print_node_info(node_cfg: &NodeConfig, node_name: &str, status: &str, default_id: &str) {
+    let status = match status {
+        "UP" => status.light_green(),
+        "DOWN" => status.light_red(),
+        _ => status.white(),
+    };
+    if node_cfg.relay {
+        println!(
+            r#"
+Node:
+  Name: {}
+  Status: {}
+  Relay: true
+  Services:
+    Service:
+      Type: TCP Listener
+      Address: /ip4/127.0.0.1/tcp/{}
+    Service:
+      Type: Forwarding Service
+      Address: /service/forwarding_service
+    Service:
+      Type: Echo
+      Address: /service/echo
+"#,
+            node_name, status, node_cfg.port,
+        );
+        return;
+    }
     println!(
         r#"
 Node:
@@ -65,16 +94,7 @@ Node:
       Address: /service/echo
   Secure Channel Listener Address: /service/api
 "#,
-        node_name,
-        match status {
-            "UP" => status.light_green(),
-            "DOWN" => status.light_red(),
-            _ => status.white(),
-        },
-        node_cfg.port,
-        node_cfg.port,
-        default_id,
-        default_id,
+        node_name, status, node_cfg.port, node_cfg.port, default_id, default_id,
     );
 }
 
@@ -107,25 +127,59 @@ pub async fn print_query_status(
         }
     }
 
-    // Get short id for the node
-    ctx.send(route.clone(), api::short_identity()?).await?;
-    let resp = ctx
-        .receive_duration_timeout::<Vec<u8>>(Duration::from_millis(250))
-        .await
-        .context("Failed to process request for short id")?;
+    // Get short id for the node, relays don't have any
+    let default_id = if node_cfg.relay {
+        String::from("N/A")
+    } else {
+        ctx.send(route.clone(), api::short_identity()?).await?;
+        let resp = ctx
+            .receive_duration_timeout::<Vec<u8>>(Duration::from_millis(250))
+            .await
+            .context("Failed to process request for short id")?;
 
-    let (response, result) = api::parse_short_identity_response(&resp)?;
-    let default_id = match response.status() {
-        Some(Status::Ok) => {
-            format!("{}", result.identity_id)
+        let (response, result) = api::parse_short_identity_response(&resp)?;
+        match response.status() {
+            Some(Status::Ok) => crate::identity::display(&cfg, &result.identity_id),
+            _ => String::from("NOT FOUND"),
         }
-        _ => String::from("NOT FOUND"),
     };
 
     print_node_info(&node_cfg, &node_name, "UP", &default_id);
+
+    // Jobs are optional, older nodes don't serve them
+    ctx.send(route.clone(), Request::get("/node/jobs").to_vec()?)
+        .await?;
+    if let Ok(resp) = ctx
+        .receive_duration_timeout::<Vec<u8>>(Duration::from_millis(250))
+        .await
+    {
+        let mut dec = Decoder::new(&resp);
+        if let (Ok(res), Ok(jobs)) = (dec.decode::<Response>(), dec.decode::<JobList>()) {
+            if res.status() == Some(Status::Ok) {
+                print_jobs(&jobs);
+            }
+        }
+    }
     Ok(())
 }
 
+fn print_jobs(jobs: &JobList) {
+    if jobs.list.is_empty() {
+        return;
+    }
+    println!("  Jobs:");
+    for job in &jobs.list {
+        println!("    Job:");
+        println!("      Id: {}", job.id);
+        println!("      Action: {}", job.kind);
+        println!("      Every: {}s", job.interval);
+        println!("      Runs: {} ({} failed)", job.runs, job.failures);
+        if let Some(result) = &job.last_result {
+            println!("      Last Result: {}", result);
+        }
+    }
+}
+
 async fn query_status(ctx: &mut ockam::Context, route: &Route) -> anyhow::Result<()> {
     ctx.send(route.clone(), api::query_status()?).await?;
 
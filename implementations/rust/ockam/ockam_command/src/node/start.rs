@@ -39,6 +39,12 @@ impl StartCommand {
             }
         }
 
+        let tcp_proxy = cfg_node.tcp_proxy.as_deref();
+        let user = cfg_node
+            .user
+            .as_deref()
+            .map(|u| (u, cfg_node.group.as_deref()));
+
         // Construct the arguments list and re-execute the ockam
         // CLI in foreground mode to re-start the node
         spawn_node(
@@ -50,6 +56,11 @@ impl StartCommand {
             &cfg_node.name,             // The selected node name
             &cfg_node.addr.to_string(), // The selected node api address
             None,                       // No project information available
+            Some(&cfg_node.log_sink),   // Previously user-chosen log sink
+            cfg_node.relay,             // Previously user-chosen relay mode
+            cfg_node.health_address,    // Previously user-chosen health probes address
+            tcp_proxy,                  // Previously user-chosen proxy
+            user,                       // Previously user-chosen unprivileged user
         );
     }
 }
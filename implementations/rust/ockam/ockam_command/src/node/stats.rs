@@ -0,0 +1,45 @@
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::stats::{SetWorkerAccounting, WorkerStatsList};
+use ockam_core::api::Request;
+
+use crate::node::HELP_DETAIL;
+use crate::util::{get_final_element, node_rpc, Rpc};
+use crate::{help, CommandGlobalOpts, Result};
+
+/// Show what the workers of a node consume, the most costly first
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct StatsCommand {
+    /// Node whose workers to show.
+    #[arg(long, id = "NODE", display_order = 900)]
+    at: String,
+
+    /// Start accounting for the messages handled by the workers of the node.
+    #[arg(long, display_order = 900, conflicts_with = "disable")]
+    enable: bool,
+
+    /// Stop accounting for the workers of the node, forgetting their stats.
+    #[arg(long, display_order = 900)]
+    disable: bool,
+}
+
+impl StatsCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, StatsCommand)) -> Result<()> {
+    let node = get_final_element(&cmd.at);
+    if cmd.enable || cmd.disable {
+        let mut rpc = Rpc::background(&ctx, &opts, node)?;
+        let body = SetWorkerAccounting::new(cmd.enable);
+        rpc.request(Request::put("/node/stats").body(body)).await?;
+        rpc.is_ok()?;
+    }
+    let mut rpc = Rpc::background(&ctx, &opts, node)?;
+    rpc.request(Request::get("/node/stats")).await?;
+    rpc.parse_and_print_response::<WorkerStatsList>()?;
+    Ok(())
+}
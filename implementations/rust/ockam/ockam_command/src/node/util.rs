@@ -6,8 +6,9 @@ use ockam::identity::{Identity, PublicIdentity};
 use ockam::{Context, TcpTransport};
 use ockam_api::config::cli;
 use ockam_api::config::cli::OckamConfig as OckamConfigApi;
+use ockam_api::config::system;
 use ockam_api::nodes::models::transport::{TransportMode, TransportType};
-use ockam_api::nodes::{IdentityOverride, NodeManager, NODEMANAGER_ADDR};
+use ockam_api::nodes::{IdentityOverride, NodeManager, NodeManagerWorker, NODEMANAGER_ADDR};
 use ockam_multiaddr::MultiAddr;
 use ockam_vault::storage::FileStorage;
 use ockam_vault::Vault;
@@ -23,7 +24,7 @@ pub async fn start_embedded_node(ctx: &Context, cfg: &OckamConfig) -> Result<Str
     let cmd = CreateCommand::default();
 
     // Create node directory if it doesn't exist
-    tokio::fs::create_dir_all(&cfg.get_node_dir_raw(&cmd.node_name)?).await?;
+    system::create_private_dir(&cfg.get_node_dir_raw(&cmd.node_name)?)?;
 
     // This node was initially created as a foreground node
     if !cmd.child_process {
@@ -49,8 +50,7 @@ pub async fn start_embedded_node(ctx: &Context, cfg: &OckamConfig) -> Result<Str
     };
 
     let tcp = TcpTransport::create(ctx).await?;
-    let bind = cmd.tcp_listener_address;
-    tcp.listen(&bind).await?;
+    let bind = tcp.listen(&cmd.tcp_listener_address).await?;
     let node_dir = cfg.get_node_dir_raw(&cmd.node_name)?;
     let node_man = NodeManager::create(
         ctx,
@@ -59,14 +59,16 @@ pub async fn start_embedded_node(ctx: &Context, cfg: &OckamConfig) -> Result<Str
         identity_override,
         cmd.skip_defaults || cmd.launch_config.is_some(),
         cmd.enable_credential_checks,
-        Some(&cfg.authorities(&cmd.node_name)?.snapshot()),
+        Some(&cfg.trusted_authorities(&cmd.node_name)?),
         project_id,
-        (TransportType::Tcp, TransportMode::Listen, bind),
+        (TransportType::Tcp, TransportMode::Listen, bind.to_string()),
         tcp,
     )
     .await?;
 
-    ctx.start_worker(NODEMANAGER_ADDR, node_man).await?;
+    let node_manager_worker = NodeManagerWorker::new(node_man);
+    ctx.start_worker(NODEMANAGER_ADDR, node_manager_worker)
+        .await?;
 
     Ok(cmd.node_name.clone())
 }
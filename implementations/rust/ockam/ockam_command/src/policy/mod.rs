@@ -0,0 +1,51 @@
+use clap::{Args, Subcommand};
+
+pub(crate) use test::TestCommand;
+
+use crate::{help, CommandGlobalOpts};
+
+mod test;
+
+const HELP_DETAIL: &str = "\
+About:
+    Policies are ABAC expressions, written as the JSON of their conditions, which
+    decide on requests from the credential attributes of their subjects and the
+    local facts of the node, referenced with `local.` keys, e.g. `local.tag.prod`.
+
+    A policy is tested by a node on a hypothetical request, without being deployed,
+    whose subject attributes and facts are given as KEY=VALUE. The decision is
+    given with the part of the policy it follows from.
+
+Examples:
+```sh
+    # Test whether an admin may read the db resource, on a node tagged prod
+    $ ockam policy test --at n1 --resource db --action r \\
+        --attribute role=admin --attribute local.tag.prod=true \\
+        '{\"And\": [{\"Eq\": [\"role\", {\"S\": \"admin\"}]}, {\"Eq\": [\"local.tag.prod\", {\"B\": true}]}]}'
+```
+";
+
+/// Manage ABAC Policies
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    help_template = help::template(HELP_DETAIL)
+)]
+pub struct PolicyCommand {
+    #[command(subcommand)]
+    subcommand: PolicySubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum PolicySubcommand {
+    Test(TestCommand),
+}
+
+impl PolicyCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        match self.subcommand {
+            PolicySubcommand::Test(c) => c.run(opts),
+        }
+    }
+}
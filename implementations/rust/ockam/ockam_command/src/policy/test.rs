@@ -0,0 +1,58 @@
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::policy::{EvaluatePolicy, PolicyDecision};
+use ockam_core::api::Request;
+
+use crate::policy::HELP_DETAIL;
+use crate::util::{get_final_element, node_rpc, Rpc};
+use crate::{help, CommandGlobalOpts, Result};
+
+/// Test a Policy on a hypothetical request
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct TestCommand {
+    /// Node testing the policy.
+    #[arg(long, id = "NODE", display_order = 900)]
+    at: String,
+
+    /// The policy, as JSON
+    #[arg(value_name = "POLICY")]
+    policy: String,
+
+    /// Resource the request is for
+    #[arg(long, display_order = 901)]
+    resource: String,
+
+    /// Action the request is for
+    #[arg(long, display_order = 902)]
+    action: String,
+
+    /// Credential attribute of the subject, or local fact under a `local.`
+    /// key, as KEY=VALUE. Can be repeated
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_attribute, display_order = 903)]
+    attribute: Vec<(String, String)>,
+}
+
+fn parse_attribute(s: &str) -> std::result::Result<(String, String), String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| "expected KEY=VALUE".to_string())?;
+    Ok((key.to_string(), value.to_string()))
+}
+
+impl TestCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, TestCommand)) -> Result<()> {
+    let node = get_final_element(&cmd.at);
+    let mut rpc = Rpc::background(&ctx, &opts, node)?;
+    let attributes = cmd.attribute.into_iter().collect();
+    let body = EvaluatePolicy::new(cmd.policy, cmd.resource, cmd.action, attributes);
+    rpc.request(Request::post("/node/policy/evaluate").body(body))
+        .await?;
+    rpc.parse_and_print_response::<PolicyDecision>()?;
+    Ok(())
+}
@@ -4,12 +4,14 @@ use anyhow::anyhow;
 use ockam::identity::IdentityIdentifier;
 use ockam::Context;
 use ockam_api::authenticator::direct::types::AddMember;
+use ockam_api::capability::{Capabilities, Capability};
 use ockam_api::config::lookup::{ConfigLookup, ProjectAuthority};
 use ockam_api::nodes::models::secure_channel::{
     CreateSecureChannelResponse, CredentialExchangeMode,
 };
 use ockam_core::api::Request;
 use ockam_multiaddr::{proto, MultiAddr, Protocol};
+use std::str::FromStr;
 use tracing::debug;
 
 use crate::node::util::{delete_embedded_node, start_embedded_node};
@@ -29,9 +31,14 @@ pub struct EnrollCommand {
     #[command(flatten)]
     node_opts: NodeOpts,
 
-    #[arg(long, short)]
+    #[arg(long, short, value_parser = crate::identity::parse_identifier)]
     member: IdentityIdentifier,
 
+    /// Only let the member create what this allows, e.g. `forwarder:device-42`
+    /// or `outlet:22` (can be repeated)
+    #[arg(long = "capability", value_name = "CAPABILITY", value_parser = Capability::from_str)]
+    capabilities: Vec<Capability>,
+
     #[arg(long, short)]
     to: MultiAddr,
 }
@@ -69,7 +76,12 @@ impl Runner {
         } else {
             self.cmd.to.clone()
         };
-        let req = Request::post("/members").body(AddMember::new(self.cmd.member.clone()));
+        let mut member = AddMember::new(self.cmd.member.clone());
+        if !self.cmd.capabilities.is_empty() {
+            let capabilities = Capabilities::new(self.cmd.capabilities.clone());
+            member = member.with_capabilities(&capabilities);
+        }
+        let req = Request::post("/members").body(member);
         let mut rpc = RpcBuilder::new(&self.ctx, &self.opts, &node_name)
             .to(&to)?
             .build();
@@ -1,18 +1,27 @@
+#[cfg(feature = "cloud")]
 use anyhow::Context as _;
+#[cfg(feature = "cloud")]
 use clap::Args;
 
 use ockam::identity::IdentityIdentifier;
+#[cfg(feature = "cloud")]
 use ockam::Context;
 use ockam_api::cloud::project::Project;
 use ockam_core::CowStr;
 
+#[cfg(feature = "cloud")]
 use crate::node::util::{delete_embedded_node, start_embedded_node};
+#[cfg(feature = "cloud")]
 use crate::project::util::config;
+#[cfg(feature = "cloud")]
 use crate::util::api::{self, CloudOpts};
+#[cfg(feature = "cloud")]
 use crate::util::{node_rpc, RpcBuilder};
+#[cfg(feature = "cloud")]
 use crate::CommandGlobalOpts;
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "cloud")]
 #[derive(Clone, Debug, Args)]
 pub struct InfoCommand {
     /// Name of the project.
@@ -37,6 +46,8 @@ pub struct ProjectInfo<'a> {
     pub authority_access_route: Option<CowStr<'a>>,
     #[serde(borrow)]
     pub authority_identity: Option<CowStr<'a>>,
+    #[serde(borrow)]
+    pub relay_access_routes: Option<Vec<CowStr<'a>>>,
 }
 
 impl<'a> From<Project<'a>> for ProjectInfo<'a> {
@@ -48,6 +59,7 @@ impl<'a> From<Project<'a>> for ProjectInfo<'a> {
             access_route: p.access_route,
             authority_access_route: p.authority_access_route,
             authority_identity: p.authority_identity,
+            relay_access_routes: p.relay_access_routes,
         }
     }
 }
@@ -61,21 +73,25 @@ impl<'a> From<&ProjectInfo<'a>> for Project<'a> {
             access_route: p.access_route.clone(),
             authority_access_route: p.authority_access_route.clone(),
             authority_identity: p.authority_identity.clone(),
+            relay_access_routes: p.relay_access_routes.clone(),
             ..Default::default()
         }
     }
 }
 
+#[cfg(feature = "cloud")]
 impl InfoCommand {
     pub fn run(self, options: CommandGlobalOpts) {
         node_rpc(rpc, (options, self));
     }
 }
 
+#[cfg(feature = "cloud")]
 async fn rpc(mut ctx: Context, (opts, cmd): (CommandGlobalOpts, InfoCommand)) -> crate::Result<()> {
     run_impl(&mut ctx, opts, cmd).await
 }
 
+#[cfg(feature = "cloud")]
 async fn run_impl(
     ctx: &mut Context,
     opts: CommandGlobalOpts,
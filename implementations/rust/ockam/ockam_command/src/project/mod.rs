@@ -1,11 +1,18 @@
+#[cfg(feature = "cloud")]
 mod add_enroller;
+#[cfg(feature = "cloud")]
 mod create;
+#[cfg(feature = "cloud")]
 mod delete;
+#[cfg(feature = "cloud")]
 mod delete_enroller;
 mod enroll;
 mod info;
+#[cfg(feature = "cloud")]
 mod list;
+#[cfg(feature = "cloud")]
 mod list_enrollers;
+#[cfg(feature = "cloud")]
 mod show;
 pub mod util;
 
@@ -15,14 +22,22 @@ pub use util::config;
 use clap::{Args, Subcommand};
 
 pub use crate::credential::get_credential::GetCredentialCommand;
+#[cfg(feature = "cloud")]
 pub use add_enroller::AddEnrollerCommand;
+#[cfg(feature = "cloud")]
 pub use create::CreateCommand;
+#[cfg(feature = "cloud")]
 pub use delete::DeleteCommand;
+#[cfg(feature = "cloud")]
 pub use delete_enroller::DeleteEnrollerCommand;
 pub use enroll::EnrollCommand;
+#[cfg(feature = "cloud")]
 pub use info::InfoCommand;
+#[cfg(feature = "cloud")]
 pub use list::ListCommand;
+#[cfg(feature = "cloud")]
 pub use list_enrollers::ListEnrollersCommand;
+#[cfg(feature = "cloud")]
 pub use show::ShowCommand;
 
 use crate::CommandGlobalOpts;
@@ -37,13 +52,21 @@ pub struct ProjectCommand {
 
 #[derive(Clone, Debug, Subcommand)]
 pub enum ProjectSubcommand {
+    #[cfg(feature = "cloud")]
     Create(CreateCommand),
+    #[cfg(feature = "cloud")]
     Delete(DeleteCommand),
+    #[cfg(feature = "cloud")]
     List(ListCommand),
+    #[cfg(feature = "cloud")]
     Show(ShowCommand),
+    #[cfg(feature = "cloud")]
     Info(InfoCommand),
+    #[cfg(feature = "cloud")]
     AddEnroller(AddEnrollerCommand),
+    #[cfg(feature = "cloud")]
     ListEnrollers(ListEnrollersCommand),
+    #[cfg(feature = "cloud")]
     DeleteEnroller(DeleteEnrollerCommand),
     Enroll(EnrollCommand),
 }
@@ -51,14 +74,22 @@ pub enum ProjectSubcommand {
 impl ProjectCommand {
     pub fn run(self, options: CommandGlobalOpts) {
         match self.subcommand {
+            #[cfg(feature = "cloud")]
             ProjectSubcommand::Create(c) => c.run(options),
+            #[cfg(feature = "cloud")]
             ProjectSubcommand::Delete(c) => c.run(options),
+            #[cfg(feature = "cloud")]
             ProjectSubcommand::List(c) => c.run(options),
+            #[cfg(feature = "cloud")]
             ProjectSubcommand::Show(c) => c.run(options),
+            #[cfg(feature = "cloud")]
             ProjectSubcommand::AddEnroller(c) => c.run(options),
+            #[cfg(feature = "cloud")]
             ProjectSubcommand::ListEnrollers(c) => c.run(options),
+            #[cfg(feature = "cloud")]
             ProjectSubcommand::DeleteEnroller(c) => c.run(options),
             ProjectSubcommand::Enroll(c) => c.run(options),
+            #[cfg(feature = "cloud")]
             ProjectSubcommand::Info(c) => c.run(options),
         }
     }
@@ -1,3 +1,4 @@
+#[cfg(feature = "cloud")]
 use std::io::Write;
 use std::str::FromStr;
 
@@ -8,10 +9,13 @@ use ockam::identity::IdentityIdentifier;
 use ockam::TcpTransport;
 use ockam_api::cloud::project::Project;
 use ockam_api::config::lookup::{LookupMeta, ProjectLookup};
+#[cfg(feature = "cloud")]
 use ockam_api::multiaddr_to_addr;
 use ockam_api::nodes::models::secure_channel::*;
+use ockam_api::relays::RelaySelector;
 use ockam_multiaddr::{MultiAddr, Protocol};
 
+#[cfg(feature = "cloud")]
 use crate::util::api::CloudOpts;
 use crate::util::{api, RpcBuilder};
 use crate::{CommandGlobalOpts, OckamConfig};
@@ -67,7 +71,12 @@ pub async fn get_projects_secure_channels_from_config_lookup(
             let p = cfg_lookup
                 .get_project(name)
                 .context(format!("Failed to get project {} from config lookup", name))?;
-            (&p.node_route, p.identity_id.to_string())
+            // Connect to the fastest relay if the project has several.
+            let route = RelaySelector::new()
+                .select(&p.id, &p.access_routes())
+                .await
+                .unwrap_or_else(|| p.node_route.clone());
+            (route, p.identity_id.to_string())
         };
         sc.push(
             create_secure_channel_to_project(
@@ -75,7 +84,7 @@ pub async fn get_projects_secure_channels_from_config_lookup(
                 opts,
                 api_node,
                 tcp,
-                project_access_route,
+                &project_access_route,
                 &project_identity_id,
                 credential_exchange_mode,
             )
@@ -110,6 +119,7 @@ async fn create_secure_channel_to_project(
     Ok(sc.addr()?)
 }
 
+#[cfg(feature = "cloud")]
 async fn delete_secure_channel<'a>(
     ctx: &ockam::Context,
     opts: &CommandGlobalOpts,
@@ -124,6 +134,7 @@ async fn delete_secure_channel<'a>(
     Ok(())
 }
 
+#[cfg(feature = "cloud")]
 pub async fn check_project_readiness<'a>(
     ctx: &ockam::Context,
     opts: &CommandGlobalOpts,
@@ -229,11 +240,11 @@ pub mod config {
                 "Project is not ready yet, wait a few seconds and try again"
             ));
         }
-        let proute: MultiAddr = project
-            .access_route
-            .as_ref()
-            .try_into()
-            .context("Invalid project node route")?;
+        let mut proutes = project
+            .access_routes()
+            .context("Invalid project node route")?
+            .into_iter();
+        let proute = proutes.next().context("Invalid project node route")?;
         let pid = project
             .identity
             .as_ref()
@@ -255,6 +266,7 @@ pub mod config {
             project.name.to_string(),
             ProjectLookup {
                 node_route: proute,
+                relay_routes: proutes.collect(),
                 id: project.id.to_string(),
                 identity_id: pid.clone(),
                 authority,
@@ -278,12 +290,14 @@ pub mod config {
         Ok(())
     }
 
+    #[cfg(feature = "cloud")]
     pub fn remove_project(config: &OckamConfig, name: &str) -> Result<()> {
         config.remove_project_alias(name);
         config.persist_config_updates()?;
         Ok(())
     }
 
+    #[cfg(feature = "cloud")]
     pub fn get_project(config: &OckamConfig, name: &str) -> Option<String> {
         let inner = config.writelock_inner();
         inner.lookup.get_project(name).map(|s| s.id.clone())
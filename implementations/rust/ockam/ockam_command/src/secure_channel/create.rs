@@ -1,24 +1,30 @@
 use crate::{
     help,
-    util::{api, exitcode, get_final_element, node_rpc},
+    util::{exitcode, get_final_element, node_rpc, parse_interval},
     CommandGlobalOpts, OutputFormat, Result,
 };
 
+use std::path::PathBuf;
+use std::time::Duration;
+
 use anyhow::Context as _;
 use atty::Stream;
 use clap::Args;
 use colorful::Colorful;
 use serde_json::json;
 
-use crate::secure_channel::HELP_DETAIL;
+use crate::secure_channel::{project_authorities, read_psk, HELP_DETAIL};
 use crate::util::api::CloudOpts;
 use crate::util::RpcBuilder;
 use ockam::{identity::IdentityIdentifier, route, Context, TcpTransport};
 use ockam_api::config::lookup::ConfigLookup;
-use ockam_api::nodes::models::secure_channel::CredentialExchangeMode;
+use ockam_api::nodes::models::secure_channel::{
+    CreateSecureChannelRequest, CredentialExchangeMode, KeyAgreementMode,
+};
 use ockam_api::{
     clean_multiaddr, nodes::models::secure_channel::CreateSecureChannelResponse, route_to_multiaddr,
 };
+use ockam_core::api::Request;
 use ockam_multiaddr::MultiAddr;
 
 /// Create Secure Channels
@@ -33,10 +39,68 @@ pub struct CreateCommand {
     #[arg(value_name = "ROUTE", long, display_order = 800)]
     pub to: MultiAddr,
 
-    /// Identifiers authorized to be presented by the listener
-    #[arg(value_name = "IDENTIFIER", long, short, display_order = 801)]
+    /// Identifiers authorized to be presented by the listener. Defaults to
+    /// the identity of the contact the route leads to, if any
+    #[arg(value_name = "IDENTIFIER", long, short, display_order = 801, value_parser = crate::identity::parse_identifier)]
     pub authorized: Option<Vec<IdentityIdentifier>>,
 
+    /// Monitor the secure channel and re-establish it when it breaks
+    #[arg(long, display_order = 802)]
+    pub monitor: bool,
+
+    /// Close the secure channel once it carried no messages for this
+    /// long, e.g. 30m (optional)
+    #[arg(long, id = "IDLE_TIMEOUT", value_parser = parse_interval, display_order = 802)]
+    pub idle_timeout: Option<Duration>,
+
+    /// Ping the other side of the secure channel when it is quiet for this
+    /// long, e.g. 30s, and re-establish the channel when it stops answering
+    #[arg(long, id = "KEEPALIVE", value_parser = parse_interval, display_order = 802)]
+    pub keepalive: Option<Duration>,
+
+    /// Ping the other side at every keepalive interval, quiet or not, to
+    /// measure the round-trip time of the secure channel
+    #[arg(long, requires = "KEEPALIVE", display_order = 802)]
+    pub measure_rtt: bool,
+
+    /// Also agree on the keys with ML-KEM-768 (Kyber), for post-quantum
+    /// confidentiality. The listener must support it
+    #[arg(long, display_order = 802)]
+    pub hybrid: bool,
+
+    /// Use the secure channel listeners of the address as relays, so that
+    /// each relay only learns the next hop of the messages of the channel
+    #[arg(long, display_order = 802)]
+    pub onion: bool,
+
+    /// Authenticate the channel with the pre-shared key of this name
+    /// instead of presenting credentials. The listener must use the same key
+    #[arg(long, value_name = "ID", display_order = 803, requires = "PSK_FILE")]
+    pub psk_id: Option<String>,
+
+    /// File holding the pre-shared key, hex encoded
+    #[arg(long, id = "PSK_FILE", display_order = 803, requires = "psk_id")]
+    pub psk_file: Option<PathBuf>,
+
+    /// Give up creating the channel after this many failed attempts
+    /// (optional, only attempted once by default)
+    #[arg(long, id = "MAX_ATTEMPTS", value_parser = clap::value_parser!(u32).range(1..), display_order = 804)]
+    pub retry_max_attempts: Option<u32>,
+
+    /// Time before retrying a failed attempt the first time, e.g. 5s
+    /// (optional, defaults to 1s). The delay doubles after each failure
+    #[arg(long, id = "INITIAL_DELAY", value_parser = parse_interval, display_order = 804, requires = "MAX_ATTEMPTS")]
+    pub retry_initial_delay: Option<Duration>,
+
+    /// Maximum time between two attempts, e.g. 1m (optional, defaults to 2m)
+    #[arg(long, id = "MAX_DELAY", value_parser = parse_interval, display_order = 804, requires = "MAX_ATTEMPTS")]
+    pub retry_max_delay: Option<Duration>,
+
+    /// Present and verify credentials of the authority of this project
+    /// instead of those of the node. Can be repeated
+    #[arg(long, value_name = "PROJECT", display_order = 805)]
+    pub trust_project: Vec<String>,
+
     /// Orchestrator address to resolve projects present in the `at` argument
     #[command(flatten)]
     cloud_opts: CloudOpts,
@@ -47,8 +111,8 @@ impl CreateCommand {
         node_rpc(rpc, (options, self));
     }
 
-    // Read the `to` argument and return a MultiAddr
-    // or exit with and error if `to` can't be parsed.
+    // Read the `to` argument and return a MultiAddr, with the identity pinned
+    // by the last contact in it, or exit with and error if `to` can't be parsed.
     async fn parse_to_route(
         &self,
         ctx: &Context,
@@ -56,10 +120,15 @@ impl CreateCommand {
         cloud_addr: &MultiAddr,
         api_node: &str,
         tcp: &TcpTransport,
-    ) -> anyhow::Result<MultiAddr> {
+    ) -> anyhow::Result<(MultiAddr, Option<IdentityIdentifier>)> {
         let config = &opts.config.lookup();
         let (to, meta) = clean_multiaddr(&self.to, config)
             .context(format!("Could not convert {} into route", &self.to))?;
+        let pinned = meta
+            .contact
+            .back()
+            .and_then(|name| config.get_contact(name))
+            .map(|c| c.identity_id.clone());
 
         let projects_sc = crate::project::util::get_projects_secure_channels_from_config_lookup(
             ctx,
@@ -71,7 +140,8 @@ impl CreateCommand {
             CredentialExchangeMode::Oneway,
         )
         .await?;
-        crate::project::util::clean_projects_multiaddr(to, projects_sc)
+        let to = crate::project::util::clean_projects_multiaddr(to, projects_sc)?;
+        Ok((to, pinned))
     }
 
     // Read the `from` argument and return node name
@@ -156,16 +226,53 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> R
 
     let config = &opts.config.lookup();
     let from = &cmd.parse_from_node(config);
-    let to = &cmd
+    let (to, pinned) = cmd
         .parse_to_route(&ctx, &opts, &cmd.cloud_opts.route(), from, &tcp)
         .await?;
+    let to = &to;
 
-    let authorized_identifiers = cmd.authorized.clone();
+    // A contact's identity is only pinned when none is authorized explicitly.
+    let authorized_identifiers = cmd.authorized.clone().or_else(|| pinned.map(|i| vec![i]));
 
     // Delegate the request to create a secure channel to the from node.
     let mut rpc = RpcBuilder::new(&ctx, &opts, from).tcp(&tcp)?.build();
-    let request =
-        api::create_secure_channel(to, authorized_identifiers, CredentialExchangeMode::Mutual);
+    let mut payload =
+        CreateSecureChannelRequest::new(to, authorized_identifiers, CredentialExchangeMode::Mutual);
+    if cmd.monitor {
+        payload = payload.with_monitor()
+    }
+    if let Some(t) = cmd.idle_timeout {
+        payload = payload.with_idle_timeout(t)
+    }
+    if let Some(t) = cmd.keepalive {
+        payload = payload.with_keepalive(t)
+    }
+    if cmd.measure_rtt {
+        payload = payload.with_rtt()
+    }
+    if cmd.hybrid {
+        payload = payload.with_key_agreement(KeyAgreementMode::Hybrid)
+    }
+    if cmd.onion {
+        payload = payload.with_onion()
+    }
+    if let (Some(id), Some(path)) = (&cmd.psk_id, &cmd.psk_file) {
+        payload = payload.with_psk(read_psk(id, path)?)
+    }
+    if let Some(n) = cmd.retry_max_attempts {
+        payload = payload.with_retry_max_attempts(n)
+    }
+    if let Some(d) = cmd.retry_initial_delay {
+        payload = payload.with_retry_initial_delay(d)
+    }
+    if let Some(d) = cmd.retry_max_delay {
+        payload = payload.with_retry_max_delay(d)
+    }
+    if !cmd.trust_project.is_empty() {
+        let authorities = project_authorities(config, &cmd.trust_project)?;
+        payload = payload.with_authorities(authorities)
+    }
+    let request = Request::post("/node/secure_channel").body(payload);
 
     rpc.request(request).await?;
     let response = rpc.parse_response::<CreateSecureChannelResponse>()?;
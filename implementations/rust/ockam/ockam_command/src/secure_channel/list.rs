@@ -2,15 +2,15 @@ use atty::Stream;
 use clap::Args;
 use colorful::Colorful;
 
-use ockam::{Context, TcpTransport};
+use ockam::Context;
 use ockam_api::nodes::models::secure_channel::ShowSecureChannelResponse;
 use ockam_api::route_to_multiaddr;
-use ockam_core::{route, Address};
+use ockam_core::route;
 
 use serde_json::json;
 
 use crate::secure_channel::HELP_DETAIL;
-use crate::util::RpcBuilder;
+use crate::util::{fmt_rtt_micros, RpcBuilder};
 use crate::{
     exitcode, help,
     util::{api, node_rpc},
@@ -34,19 +34,20 @@ impl ListCommand {
     fn print_output(
         &self,
         options: &CommandGlobalOpts,
-        channel_identifiers: Vec<String>,
         show_responses: Vec<ShowSecureChannelResponse>,
     ) -> Result<(), String> {
-        let zipped = channel_identifiers.iter().zip(show_responses);
-
-        if zipped.len() > 0 && has_plain_stderr(options) {
+        if !show_responses.is_empty() && has_plain_stderr(options) {
             println!("\nSecure Channels")
         }
 
-        for (channel_address, show_response) in zipped {
+        for show_response in show_responses {
             let from = &self.at;
 
             let at = {
+                let channel_address = show_response
+                    .channel
+                    .as_deref()
+                    .ok_or("Failed to retrieve address from list channel response")?;
                 let channel_route = &route![channel_address];
                 let channel_multiaddr = route_to_multiaddr(channel_route).ok_or(format!(
                     "Failed to convert route {} to multi-address",
@@ -58,6 +59,7 @@ impl ListCommand {
             let to = {
                 let show_route = show_response
                     .route
+                    .as_ref()
                     .ok_or("Failed to retrieve route from show channel response")?;
                 let parts: Vec<&str> = show_route.split(" => ").collect();
                 if parts.len() != 2 {
@@ -84,30 +86,91 @@ impl ListCommand {
 
             // if output format is json, write json to stdout.
             if options.global_args.output_format == OutputFormat::Json {
-                let json = json!([{ "address": at }]);
+                let json = json!([{
+                    "address": at,
+                    "to": to,
+                    "identity": show_response.their_identity_id,
+                    "hops": show_response.hops,
+                    "created_at": show_response.created_at,
+                    "credential_exchange_mode": show_response.credential_exchange_mode,
+                    "sent": show_response.sent,
+                    "received": show_response.received,
+                    "rtt_micros": show_response.rtt_micros,
+                }]);
                 println!("{}", json);
             }
 
             // if stderr is interactive/tty and we haven't been asked to be quiet
             // and output format is plain then write a plain info to stderr.
             if has_plain_stderr(options) {
+                let peer = show_response
+                    .their_identity_id
+                    .as_ref()
+                    .map(|id| id.to_string())
+                    .unwrap_or_else(|| "unknown".to_string());
+                let hops = match show_response.hops.as_deref() {
+                    Some([]) | None => "none".to_string(),
+                    Some(hops) => hops
+                        .iter()
+                        .map(|id| id.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" -> "),
+                };
+                let traffic = format!(
+                    "{} sent, {} received",
+                    show_response.sent.unwrap_or_default(),
+                    show_response.received.unwrap_or_default()
+                );
+                let rtt = show_response
+                    .rtt_micros
+                    .map(fmt_rtt_micros)
+                    .unwrap_or_else(|| "not measured".to_string());
+                let mode = show_response
+                    .credential_exchange_mode
+                    .map(|m| format!("{:?}", m))
+                    .unwrap_or_else(|| "unknown".to_string());
                 println!("\n    Secure Channel:");
                 if options.global_args.no_color {
-                    eprintln!("      • From: /node/{}", from);
-                    eprintln!("      •   To: {}", to);
-                    eprintln!("      •   At: {}", at);
+                    eprintln!("      •        From: /node/{}", from);
+                    eprintln!("      •          To: {}", to);
+                    eprintln!("      •          At: {}", at);
+                    eprintln!("      •        Peer: {}", peer);
+                    eprintln!("      •        Hops: {}", hops);
+                    eprintln!("      • Credentials: {}", mode);
+                    eprintln!("      •     Traffic: {}", traffic);
+                    eprintln!("      •         Rtt: {}", rtt);
                 } else {
                     // From:
-                    eprint!("{}", "      • From: ".light_magenta());
+                    eprint!("{}", "      •        From: ".light_magenta());
                     eprintln!("{}", format!("/node/{}", from).light_yellow());
 
                     // To:
-                    eprint!("{}", "      •   To: ".light_magenta());
+                    eprint!("{}", "      •          To: ".light_magenta());
                     eprintln!("{}", to.light_yellow());
 
                     // At:
-                    eprint!("{}", "      •   At: ".light_magenta());
+                    eprint!("{}", "      •          At: ".light_magenta());
                     eprintln!("{}", at.light_yellow());
+
+                    // Peer:
+                    eprint!("{}", "      •        Peer: ".light_magenta());
+                    eprintln!("{}", peer.light_yellow());
+
+                    // Hops:
+                    eprint!("{}", "      •        Hops: ".light_magenta());
+                    eprintln!("{}", hops.light_yellow());
+
+                    // Credentials:
+                    eprint!("{}", "      • Credentials: ".light_magenta());
+                    eprintln!("{}", mode.light_yellow());
+
+                    // Traffic:
+                    eprint!("{}", "      •     Traffic: ".light_magenta());
+                    eprintln!("{}", traffic.light_yellow());
+
+                    // Rtt:
+                    eprint!("{}", "      •         Rtt: ".light_magenta());
+                    eprintln!("{}", rtt.light_yellow());
                 }
             }
         }
@@ -126,33 +189,11 @@ async fn rpc(
     ctx: Context,
     (options, command): (CommandGlobalOpts, ListCommand),
 ) -> crate::Result<()> {
-    // We need this TCPTransport handle to ensure that we are using the same transport across
-    // multiple RPC calls. Creating a RPC instance without explicit transport results in a router
-    // instance being registered for the same transport type multiple times which is not allowed
-    let tcp = TcpTransport::create(&ctx).await?;
-    let mut rpc = RpcBuilder::new(&ctx, &options, &command.at)
-        .tcp(&tcp)?
-        .build();
+    let mut rpc = RpcBuilder::new(&ctx, &options, &command.at).build();
     rpc.request(api::list_secure_channels()).await?;
-    let channel_identifiers = rpc.parse_response::<Vec<String>>()?;
-
-    let mut response_rpcs = Vec::with_capacity(channel_identifiers.len());
-    for channel_addr in &channel_identifiers {
-        let mut rpc = RpcBuilder::new(&ctx, &options, &command.at)
-            .tcp(&tcp)?
-            .build();
-        let request = api::show_secure_channel(&Address::from(channel_addr));
-        rpc.request(request).await?;
-        response_rpcs.push(rpc);
-    }
-    let results: Result<Vec<_>, _> = response_rpcs
-        .iter()
-        .map(|rpc| rpc.parse_response::<ShowSecureChannelResponse>())
-        .into_iter()
-        .collect();
-    let responses = results?;
-
-    if let Err(e) = command.print_output(&options, channel_identifiers, responses) {
+    let responses = rpc.parse_response::<Vec<ShowSecureChannelResponse>>()?;
+
+    if let Err(e) = command.print_output(&options, responses) {
         if atty::is(Stream::Stderr)
             && !options.global_args.quiet
             && options.global_args.output_format == OutputFormat::Plain
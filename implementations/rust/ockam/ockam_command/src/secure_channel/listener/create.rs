@@ -1,11 +1,14 @@
-use crate::secure_channel::HELP_DETAIL;
+use crate::secure_channel::{project_authorities, read_psk, HELP_DETAIL};
 use crate::util::{api, connect_to, exitcode, get_final_element};
 use crate::{help, CommandGlobalOpts};
 
 use clap::Args;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 use ockam::identity::IdentityIdentifier;
 
+use ockam_api::nodes::models::secure_channel::{AuthorityArg, KeyAgreementMode, PreSharedKeyArg};
 use ockam_api::nodes::NODEMANAGER_ADDR;
 use ockam_core::api::Status;
 use ockam_core::{Address, Route};
@@ -21,8 +24,39 @@ pub struct CreateCommand {
     address: Address,
 
     /// Authorized Identifiers of secure channel initiators
-    #[arg(short, long, value_name = "IDENTIFIER")]
+    #[arg(short, long, value_name = "IDENTIFIER", value_parser = crate::identity::parse_identifier)]
     authorized_identifier: Option<Vec<IdentityIdentifier>>,
+
+    /// Only accept secure channels whose keys are also agreed on with
+    /// ML-KEM-768 (Kyber), for post-quantum confidentiality
+    #[arg(long)]
+    hybrid: bool,
+
+    /// Credential attribute the initiators must have presented to the
+    /// node, as KEY=VALUE. Can be repeated
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_attribute)]
+    require_attribute: Vec<(String, String)>,
+
+    /// Only accept the secure channels authenticated with the pre-shared
+    /// key of this name
+    #[arg(long, value_name = "ID", requires = "PSK_FILE")]
+    psk_id: Option<String>,
+
+    /// File holding the pre-shared key, hex encoded
+    #[arg(long, id = "PSK_FILE", requires = "psk_id")]
+    psk_file: Option<PathBuf>,
+
+    /// Only give the attributes of credentials issued by the authority of
+    /// this project to the messages of the channels. Can be repeated
+    #[arg(long, value_name = "PROJECT")]
+    trust_project: Vec<String>,
+}
+
+fn parse_attribute(s: &str) -> Result<(String, String), String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| "expected KEY=VALUE".to_string())?;
+    Ok((key.to_string(), value.to_string()))
 }
 
 #[derive(Clone, Debug, Args)]
@@ -37,25 +71,67 @@ impl CreateCommand {
         let cfg = options.config;
         let node = get_final_element(&self.node_opts.at);
         let port = cfg.get_node_port(node);
+        let lookup = cfg.lookup();
 
-        connect_to(port, self, |ctx, cmd, rte| async {
-            create_listener(&ctx, cmd.address, cmd.authorized_identifier, rte).await?;
+        connect_to(port, self, move |ctx, cmd, rte| async move {
+            let key_agreement = if cmd.hybrid {
+                Some(KeyAgreementMode::Hybrid)
+            } else {
+                None
+            };
+            let required_attributes = if cmd.require_attribute.is_empty() {
+                None
+            } else {
+                Some(cmd.require_attribute.into_iter().collect())
+            };
+            let psk = match (&cmd.psk_id, &cmd.psk_file) {
+                (Some(id), Some(path)) => Some(read_psk(id, path)?),
+                _ => None,
+            };
+            let authorities = if cmd.trust_project.is_empty() {
+                None
+            } else {
+                Some(project_authorities(&lookup, &cmd.trust_project)?)
+            };
+            create_listener(
+                &ctx,
+                cmd.address,
+                cmd.authorized_identifier,
+                required_attributes,
+                key_agreement,
+                psk,
+                authorities,
+                rte,
+            )
+            .await?;
             drop(ctx);
             Ok(())
         });
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn create_listener(
     ctx: &ockam::Context,
     addr: Address,
     authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+    required_attributes: Option<BTreeMap<String, String>>,
+    key_agreement: Option<KeyAgreementMode>,
+    psk: Option<PreSharedKeyArg<'_>>,
+    authorities: Option<Vec<AuthorityArg<'_>>>,
     mut base_route: Route,
 ) -> anyhow::Result<()> {
     let resp: Vec<u8> = ctx
         .send_and_receive(
             base_route.modify().append(NODEMANAGER_ADDR),
-            api::create_secure_channel_listener(&addr, authorized_identifiers)?,
+            api::create_secure_channel_listener(
+                &addr,
+                authorized_identifiers,
+                required_attributes,
+                key_agreement,
+                psk,
+                authorities,
+            )?,
         )
         .await?;
 
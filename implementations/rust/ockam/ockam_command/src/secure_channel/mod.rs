@@ -11,7 +11,11 @@ pub use list::ListCommand;
 pub use show::ShowCommand;
 
 use crate::{help, CommandGlobalOpts};
+use anyhow::Context;
 use clap::{Args, Subcommand};
+use ockam_api::config::lookup::ConfigLookup;
+use ockam_api::nodes::models::secure_channel::{AuthorityArg, PreSharedKeyArg};
+use std::path::Path;
 
 const HELP_DETAIL: &str = "\
 About:
@@ -32,6 +36,45 @@ About:
     /service/09738b73c54b81d48531f659aaa22533
 ```
 
+    With `--monitor`, the node checks that the channel is alive and creates a new one,
+    at a new address, when it breaks. The channel shows up in `ockam medic show`.
+
+    With `--idle-timeout`, the node closes the channel once it carried no messages
+    for that long, e.g. `--idle-timeout 30m`.
+
+    With `--keepalive`, the node pings the other side of a quiet channel, e.g. every
+    `--keepalive 30s`, and closes the channel after three unanswered pings. A new
+    channel is then created, as with `--monitor`. With `--measure-rtt` too, the node
+    pings at every interval to measure the round-trip time of the channel, shown by
+    `ockam secure-channel show` and `ockam medic show`.
+
+    With `--hybrid`, the keys of the channel are agreed on with both X25519 and
+    ML-KEM-768 (Kyber), so that recorded traffic stays confidential even if X25519
+    is broken by a quantum computer. Listeners created with `--hybrid` only accept
+    such channels.
+
+    With `--onion`, each `/secure/<listener>` of the address is a relay: a channel is
+    created to each relay in turn, over the channel to the previous one, and the
+    channel to the destination goes over the channel to the last relay. Each relay
+    only decrypts one layer and only learns the next hop, e.g.
+    `--to /node/relay/secure/api/service/forward_to_n2/service/api --onion`.
+
+    With `--psk-id` and `--psk-file`, the channel is authenticated with a key provisioned
+    on both sides, hex encoded in the file, instead of credentials. Listeners created with
+    the same key only accept the channels created with it, e.g. for devices which can't
+    be enrolled in a project.
+
+    With `--retry-max-attempts`, a channel which can't be created, e.g. because the
+    other node is restarting, is attempted again after a delay doubling from
+    `--retry-initial-delay` up to `--retry-max-delay`, until it is created or that many
+    attempts failed.
+
+    With `--trust-project`, credentials issued by the authority of that project are
+    presented and verified over the channel instead of those of the node, which gets
+    one from the authority if it has none. A node can so talk to several projects at
+    the same time. Listeners created with `--trust-project` only give the attributes of
+    credentials issued by those authorities to the messages of their channels.
+
     The Ockam Secure Channels protocol is based on handshake designs proposed in the
     Noise Protocol Framework. The Noise framework proposes several handshake designs
     that make different tradeoffs to achieve various security properties like mutual
@@ -170,3 +213,34 @@ impl SecureChannelCommand {
         }
     }
 }
+
+/// The pre-shared key named `id`, hex encoded in the file at `path`
+/// The authorities of the projects `names`, as the lookup knows them, to
+/// trust over secure channels instead of those of the node
+pub(crate) fn project_authorities(
+    lookup: &ConfigLookup,
+    names: &[String],
+) -> anyhow::Result<Vec<AuthorityArg<'static>>> {
+    names
+        .iter()
+        .map(|name| {
+            let authority = lookup
+                .get_project(name)
+                .with_context(|| format!("Unknown project '{name}'"))?
+                .authority
+                .as_ref()
+                .with_context(|| format!("The project '{name}' has no authority"))?;
+            Ok(AuthorityArg::new(
+                authority.identity().to_vec(),
+                Some(authority.address()),
+            ))
+        })
+        .collect()
+}
+
+pub(crate) fn read_psk(id: &str, path: &Path) -> anyhow::Result<PreSharedKeyArg<'static>> {
+    let hex_key = std::fs::read_to_string(path)
+        .with_context(|| format!("Could not read the pre-shared key in {}", path.display()))?;
+    let secret = hex::decode(hex_key.trim()).context("The pre-shared key is not hex encoded")?;
+    Ok(PreSharedKeyArg::new(id.to_string(), secret))
+}
@@ -4,10 +4,12 @@ use crate::CommandGlobalOpts;
 use anyhow::{anyhow, Context as _, Result};
 use clap::{Args, Subcommand};
 use minicbor::Decoder;
+use ockam::identity::IdentityIdentifier;
 use ockam::Context;
 use ockam_api::error::ApiError;
 use ockam_api::nodes::models::services::{
-    StartAuthenticatorRequest, StartCredentialsService, StartVerifierService,
+    StartAuthenticatorRequest, StartCredentialsService, StartDelegationService,
+    StartVerifierService,
 };
 use ockam_api::nodes::NODEMANAGER_ADDR;
 use ockam_api::DefaultAddress;
@@ -60,6 +62,24 @@ pub enum StartSubCommand {
         #[arg(long)]
         project: String,
     },
+    /// Let remote identities manage this node
+    Delegation {
+        #[arg(long, default_value_t = delegation_default_addr())]
+        addr: String,
+
+        /// Identity allowed to send requests through the service
+        #[arg(
+            long,
+            required_unless_present = "capabilities",
+            value_parser = crate::identity::parse_identifier
+        )]
+        authorized: Vec<IdentityIdentifier>,
+
+        /// Also let identities send the requests allowed by the capabilities
+        /// in their credential
+        #[arg(long)]
+        capabilities: bool,
+    },
 }
 
 fn vault_default_addr() -> String {
@@ -86,6 +106,10 @@ fn authenticator_default_addr() -> String {
     DefaultAddress::AUTHENTICATOR.to_string()
 }
 
+fn delegation_default_addr() -> String {
+    DefaultAddress::DELEGATION_SERVICE.to_string()
+}
+
 impl StartCommand {
     pub fn run(self, options: CommandGlobalOpts) -> Result<()> {
         let cfg = options.config;
@@ -128,6 +152,11 @@ impl StartCommand {
                     Ok(())
                 })
             }
+            StartSubCommand::Delegation { .. } => connect_to(port, self, |ctx, cmd, rte| async {
+                start_delegation_service(&ctx, cmd, rte).await?;
+                drop(ctx);
+                Ok(())
+            }),
         }
 
         Ok(())
@@ -399,3 +428,48 @@ pub async fn start_authenticator_service(
 
     Err(anyhow!("Failed to start authenticator service"))
 }
+
+pub async fn start_delegation_service(
+    ctx: &Context,
+    cmd: StartCommand,
+    mut route: Route,
+) -> Result<()> {
+    let (addr, authorized, capabilities) = match cmd.create_subcommand {
+        StartSubCommand::Delegation {
+            addr,
+            authorized,
+            capabilities,
+        } => (addr, authorized, capabilities),
+        _ => unreachable!(),
+    };
+
+    let mut body = StartDelegationService::new(&addr, authorized);
+    if capabilities {
+        body = body.with_capabilities();
+    }
+    let req = Request::post("/node/services/delegation")
+        .body(body)
+        .to_vec()?;
+
+    let res: Vec<u8> = ctx
+        .send_and_receive(route.modify().append(NODEMANAGER_ADDR), req)
+        .await?;
+
+    let mut dec = Decoder::new(&res);
+    let hdr: Response = dec.decode()?;
+
+    if let Some(Status::Ok) = hdr.status() {
+        println!("Delegation service started at address: {addr}");
+        return Ok(());
+    }
+
+    if hdr.has_body() {
+        if let Ok(err) = dec.decode::<Error>() {
+            if let Some(msg) = err.message() {
+                return Err(anyhow!("Failed to start delegation service: {}", msg));
+            }
+        }
+    }
+
+    Err(anyhow!("Failed to start delegation service"))
+}
@@ -0,0 +1,353 @@
+//! Encrypted archives of the state of the CLI and of its nodes.
+//!
+//! An archive holds the files of the configuration directory, encoded
+//! with CBOR and encrypted with AES-256-GCM. The key is derived from a
+//! passphrase with PBKDF2-HMAC-SHA256, with a random salt. The header of
+//! the archive, which holds the parameters of the derivation, is
+//! authenticated along with the files.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use aes_gcm::aead::{Aead, NewAead, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, bail, Context};
+use hmac::Hmac;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use serde_json::Value;
+use sha2::Sha256;
+
+const MAGIC: &[u8] = b"OCKAMSTATE";
+const FORMAT: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4 + SALT_LEN + NONCE_LEN;
+
+/// Number of PBKDF2 iterations of new archives.
+pub const ITERATIONS: u32 = 100_000;
+
+/// Upper bound on the number of PBKDF2 iterations of the archives read.
+const MAX_ITERATIONS: u32 = 10_000_000;
+
+/// The files of a configuration directory.
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Archive {
+    /// The directory the files were read from
+    #[n(1)] pub root: String,
+    /// Whether the vaults, holding the secret keys, were archived
+    #[n(2)] pub secrets: bool,
+    #[n(3)] pub files: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Entry {
+    /// Path of the file, relative to the root
+    #[n(1)] pub path: String,
+    #[n(2)] pub data: ByteVec,
+}
+
+impl Archive {
+    /// Read the files of the configuration directory `root`.
+    ///
+    /// Logs, locks, backups and temporary files are left out, and so are
+    /// the vaults unless `secrets` is set. Symbolic links are not followed.
+    pub fn read(root: &Path, secrets: bool, exclude: Option<&Path>) -> anyhow::Result<Self> {
+        let mut files = Vec::new();
+        let mut dirs = vec![root.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir).with_context(|| format!("failed to read {dir:?}"))? {
+                let path = entry?.path();
+                let metadata = fs::symlink_metadata(&path)
+                    .with_context(|| format!("failed to read {path:?}"))?;
+                if metadata.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                if !metadata.is_file()
+                    || Some(path.as_path()) == exclude
+                    || !is_state(&path, secrets)
+                {
+                    continue;
+                }
+                let relative = path.strip_prefix(root)?;
+                let relative = relative
+                    .to_str()
+                    .ok_or_else(|| anyhow!("invalid path {path:?}"))?;
+                let data = fs::read(&path).with_context(|| format!("failed to read {path:?}"))?;
+                files.push(Entry {
+                    path: relative.to_string(),
+                    data: data.into(),
+                });
+            }
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        let root = root
+            .to_str()
+            .ok_or_else(|| anyhow!("invalid path {root:?}"))?;
+        Ok(Archive {
+            root: root.to_string(),
+            secrets,
+            files,
+        })
+    }
+
+    /// Write the files to the configuration directory `root`, and return
+    /// their paths.
+    ///
+    /// The paths to the old root found in the JSON files are changed to
+    /// paths to the new one.
+    pub fn write(&self, root: &Path) -> anyhow::Result<Vec<PathBuf>> {
+        let new_root = root
+            .to_str()
+            .ok_or_else(|| anyhow!("invalid path {root:?}"))?;
+        let mut paths = Vec::new();
+        for entry in &self.files {
+            let relative = Path::new(&entry.path);
+            if relative.is_absolute()
+                || relative
+                    .components()
+                    .any(|c| matches!(c, std::path::Component::ParentDir))
+            {
+                bail!("invalid path in archive: {}", entry.path)
+            }
+            let path = root.join(relative);
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).with_context(|| format!("failed to create {dir:?}"))?;
+            }
+            let mut data = entry.data.to_vec();
+            if entry.path.ends_with(".json") && self.root != new_root {
+                if let Ok(mut json) = serde_json::from_slice::<Value>(&data) {
+                    rebase(&mut json, &self.root, new_root);
+                    data = serde_json::to_vec_pretty(&json)?;
+                }
+            }
+            write_private(&path, &data).with_context(|| format!("failed to write {path:?}"))?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// Encode and encrypt the archive with a key derived from `passphrase`.
+    pub fn seal(&self, passphrase: &str, iterations: u32) -> anyhow::Result<Vec<u8>> {
+        let salt: [u8; SALT_LEN] = rand::random();
+        let nonce: [u8; NONCE_LEN] = rand::random();
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(MAGIC);
+        header.push(FORMAT);
+        header.extend_from_slice(&iterations.to_be_bytes());
+        header.extend_from_slice(&salt);
+        header.extend_from_slice(&nonce);
+
+        let key = derive_key(passphrase, &salt, iterations)?;
+        let plaintext = minicbor::to_vec(self)?;
+        let cipher = Aes256Gcm::new(Key::from_slice(&key));
+        let payload = Payload {
+            msg: &plaintext,
+            aad: &header,
+        };
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| anyhow!("failed to encrypt the archive"))?;
+        header.extend_from_slice(&ciphertext);
+        Ok(header)
+    }
+
+    /// Decrypt and decode an archive sealed with `passphrase`.
+    pub fn open(data: &[u8], passphrase: &str) -> anyhow::Result<Self> {
+        if data.len() < HEADER_LEN || !data.starts_with(MAGIC) {
+            bail!("not an ockam state archive")
+        }
+        let (header, ciphertext) = data.split_at(HEADER_LEN);
+        let mut at = MAGIC.len();
+        if header[at] != FORMAT {
+            bail!("unsupported archive format {}", header[at])
+        }
+        at += 1;
+        let iterations = u32::from_be_bytes(header[at..at + 4].try_into()?);
+        if iterations > MAX_ITERATIONS {
+            bail!("invalid number of iterations {iterations}")
+        }
+        at += 4;
+        let salt = &header[at..at + SALT_LEN];
+        let nonce = &header[at + SALT_LEN..];
+
+        let key = derive_key(passphrase, salt, iterations)?;
+        let cipher = Aes256Gcm::new(Key::from_slice(&key));
+        let payload = Payload {
+            msg: ciphertext,
+            aad: header,
+        };
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| anyhow!("failed to decrypt the archive, is the passphrase right?"))?;
+        Ok(minicbor::decode(&plaintext)?)
+    }
+}
+
+/// Remove the files and directories of the configuration directory
+/// `root`, so that no stale file is left next to the restored ones.
+pub fn clear(root: &Path) -> anyhow::Result<()> {
+    for entry in fs::read_dir(root).with_context(|| format!("failed to read {root:?}"))? {
+        let path = entry?.path();
+        let removed = if fs::symlink_metadata(&path)?.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        removed.with_context(|| format!("failed to remove {path:?}"))?;
+    }
+    Ok(())
+}
+
+/// Whether a file is part of the state to archive.
+fn is_state(path: &Path, secrets: bool) -> bool {
+    let name = match path.file_name().and_then(|n| n.to_str()) {
+        Some(n) => n,
+        None => return false,
+    };
+    let transient = name.contains(".log")
+        || name.ends_with("-lock")
+        || name.ends_with(".bak")
+        || name.ends_with(".tmp");
+    !transient && (secrets || !name.ends_with("vault.json"))
+}
+
+/// Replace the prefix `from` of the paths in `json` by `to`.
+fn rebase(json: &mut Value, from: &str, to: &str) {
+    match json {
+        Value::String(s) => {
+            if let Some(rest) = s.strip_prefix(from) {
+                if rest.is_empty() || rest.starts_with(std::path::MAIN_SEPARATOR) {
+                    *s = format!("{to}{rest}")
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(|v| rebase(v, from, to)),
+        Value::Object(map) => map.values_mut().for_each(|v| rebase(v, from, to)),
+        _ => {}
+    }
+}
+
+/// Write a file only readable and writable by the current user.
+///
+/// An existing file is replaced rather than truncated, so that the data is
+/// never readable with its former permissions.
+pub fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let _ = fs::remove_file(path);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(data)
+}
+
+/// PBKDF2-HMAC-SHA256 (RFC 8018) of `passphrase`, for a 256 bits key.
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> anyhow::Result<[u8; 32]> {
+    if iterations == 0 {
+        bail!("invalid number of iterations")
+    }
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(passphrase.as_bytes(), salt, iterations, &mut key);
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pbkdf2_hmac_sha256() {
+        // RFC 7914, section 11
+        let key = derive_key("passwd", b"salt", 1).unwrap();
+        assert_eq!(
+            hex::encode(key),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"
+        );
+    }
+
+    #[test]
+    fn archives_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let state_dir = root.join("node-n1");
+        fs::create_dir_all(&state_dir).unwrap();
+        let config = format!(r#"{{"state_dir":"{}"}}"#, state_dir.display());
+        fs::write(root.join("config.json"), config).unwrap();
+        fs::write(root.join("default_vault.json"), "{}").unwrap();
+        fs::write(state_dir.join("policies.lmdb"), [1, 2, 3]).unwrap();
+        fs::write(state_dir.join("n1.log"), "logs").unwrap();
+
+        let archive = Archive::read(root, false, None).unwrap();
+        let paths: Vec<&str> = archive.files.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["config.json", "node-n1/policies.lmdb"]);
+
+        let sealed = archive.seal("s3cr3t", 10).unwrap();
+        assert!(Archive::open(&sealed, "wrong").is_err());
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(Archive::open(&tampered, "s3cr3t").is_err());
+
+        // Restored elsewhere, the paths follow the state
+        let other = tempfile::tempdir().unwrap();
+        let opened = Archive::open(&sealed, "s3cr3t").unwrap();
+        opened.write(other.path()).unwrap();
+        let config: Value =
+            serde_json::from_slice(&fs::read(other.path().join("config.json")).unwrap()).unwrap();
+        let expected = other.path().join("node-n1");
+        assert_eq!(config["state_dir"], expected.to_str().unwrap());
+        let policies = fs::read(other.path().join("node-n1/policies.lmdb")).unwrap();
+        assert_eq!(policies, vec![1, 2, 3]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restored_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, "{}").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let archive = Archive {
+            root: dir.path().to_str().unwrap().to_string(),
+            secrets: false,
+            files: vec![Entry {
+                path: "config.json".to_string(),
+                data: b"{}".to_vec().into(),
+            }],
+        };
+        archive.write(dir.path()).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_not_followed() {
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("config.json"), "{}").unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.join("linked-dir")).unwrap();
+        std::os::unix::fs::symlink(outside.path().join("secret.txt"), root.join("linked.json"))
+            .unwrap();
+
+        let archive = Archive::read(root, true, None).unwrap();
+        let paths: Vec<&str> = archive.files.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["config.json"]);
+
+        // Clearing removes the links, not what they point to
+        clear(root).unwrap();
+        assert_eq!(fs::read_dir(root).unwrap().count(), 0);
+        assert!(outside.path().join("secret.txt").exists());
+    }
+}
@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Args;
+
+use ockam_api::config::cli::OckamConfig;
+
+use super::archive::{write_private, Archive, ITERATIONS};
+use super::passphrase;
+use crate::util::exitcode;
+
+/// Back up the state of the CLI and of its nodes to an encrypted archive
+///
+/// Stop the nodes first, so that their state is consistent.
+#[derive(Clone, Debug, Args)]
+pub struct BackupCommand {
+    /// Archive to write
+    file: PathBuf,
+
+    /// Leave the vaults, holding the secret keys of the identities, out of the archive
+    #[arg(long, display_order = 900)]
+    exclude_secrets: bool,
+
+    /// File holding the passphrase of the archive (optional, defaults to
+    /// the OCKAM_STATE_PASSPHRASE environment variable)
+    #[arg(long, display_order = 900)]
+    passphrase_file: Option<PathBuf>,
+}
+
+impl BackupCommand {
+    pub fn run(self) {
+        if let Err(e) = run_impl(self) {
+            eprintln!("{:#}", e);
+            std::process::exit(exitcode::SOFTWARE);
+        }
+    }
+}
+
+fn run_impl(cmd: BackupCommand) -> anyhow::Result<()> {
+    let passphrase = passphrase(cmd.passphrase_file.as_deref())?;
+    let root = OckamConfig::directories().config_dir().to_path_buf();
+    if !root.join("config.json").exists() {
+        return Err(anyhow::anyhow!("There is no state to back up"));
+    }
+    let exclude = cmd.file.canonicalize().ok();
+    let archive = Archive::read(&root, !cmd.exclude_secrets, exclude.as_deref())?;
+    let sealed = archive.seal(&passphrase, ITERATIONS)?;
+    write_private(&cmd.file, &sealed).with_context(|| format!("failed to write {:?}", cmd.file))?;
+    println!(
+        "Backed up {} files from {} to {}",
+        archive.files.len(),
+        root.display(),
+        cmd.file.display()
+    );
+    if cmd.exclude_secrets {
+        println!("The secret keys were left out: the restored identities can not be used to sign");
+    }
+    Ok(())
+}
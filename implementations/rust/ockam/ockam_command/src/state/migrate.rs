@@ -0,0 +1,108 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use clap::Args;
+use serde_json::Value;
+
+use ockam_api::config::cli::OckamConfig;
+use ockam_api::config::migrations::{migrate_file, Migration, Report};
+use ockam_api::config::ConfigValues;
+use ockam_api::nodes::NodeManConfig;
+
+use crate::util::exitcode;
+
+/// Migrate the state of the CLI and of its nodes
+///
+/// Stop the nodes first, since they only read their state when they start.
+#[derive(Clone, Debug, Args)]
+pub struct MigrateCommand {
+    /// Version of the schemas to migrate to (optional, defaults to the latest ones)
+    #[arg(long, id = "VERSION", display_order = 900)]
+    to: Option<u32>,
+
+    /// Show the migrations without running them
+    #[arg(long, display_order = 900)]
+    dry_run: bool,
+}
+
+impl MigrateCommand {
+    pub fn run(self) {
+        if let Err(e) = run_impl(self) {
+            eprintln!("{:#}", e);
+            std::process::exit(exitcode::SOFTWARE);
+        }
+    }
+}
+
+fn run_impl(cmd: MigrateCommand) -> anyhow::Result<()> {
+    let config_path = OckamConfig::directories().config_dir().join("config.json");
+    if !config_path.exists() {
+        println!("There is no state to migrate");
+        return Ok(());
+    }
+    let mut failed = false;
+    let mut migrate = |path: PathBuf, migrations: &[Migration]| match migrate_file(
+        &path,
+        migrations,
+        cmd.to,
+        cmd.dry_run,
+    ) {
+        Ok(report) => print_report(&report, cmd.dry_run),
+        Err(e) => {
+            eprintln!("{:#}", e);
+            failed = true;
+        }
+    };
+
+    let nodes = node_config_paths(&config_path)?;
+    migrate(config_path, OckamConfig::migrations());
+    for path in nodes {
+        migrate(path, NodeManConfig::migrations());
+    }
+    if failed {
+        return Err(anyhow::anyhow!("some files could not be migrated"));
+    }
+    Ok(())
+}
+
+/// The configuration files of the nodes listed in the configuration of
+/// the CLI, read without its schema since it may be any version.
+fn node_config_paths(config_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let buf = std::fs::read_to_string(config_path)
+        .with_context(|| format!("failed to read {:?}", config_path))?;
+    let json: Value =
+        serde_json::from_str(&buf).with_context(|| format!("failed to parse {:?}", config_path))?;
+    let nodes = json.get("nodes").and_then(Value::as_object);
+    Ok(nodes
+        .into_iter()
+        .flat_map(|nodes| nodes.values())
+        .filter_map(|node| node.get("state_dir").and_then(Value::as_str))
+        .map(|dir| PathBuf::from(dir).join("config.json"))
+        .filter(|path| path.exists())
+        .collect())
+}
+
+fn print_report(report: &Report, dry_run: bool) {
+    if report.from == report.to {
+        println!(
+            "{}: up to date, version {}",
+            report.path.display(),
+            report.to
+        );
+        return;
+    }
+    let verb = if dry_run { "would migrate" } else { "migrated" };
+    println!(
+        "{}: {} from version {} to {}",
+        report.path.display(),
+        verb,
+        report.from,
+        report.to
+    );
+    for step in &report.steps {
+        println!("  {}", step);
+    }
+    if let Some(backup) = &report.backup {
+        println!("  backup: {}", backup.display());
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use anyhow::Context;
+use clap::{Args, Subcommand};
+
+pub(crate) use backup::BackupCommand;
+pub(crate) use migrate::MigrateCommand;
+pub(crate) use restore::RestoreCommand;
+
+use crate::help;
+
+mod archive;
+mod backup;
+mod migrate;
+mod restore;
+
+/// Environment variable holding the passphrase of the state archives.
+const PASSPHRASE_ENV: &str = "OCKAM_STATE_PASSPHRASE";
+
+const HELP_DETAIL: &str = "\
+About:
+    The state of the CLI and of its nodes is kept in configuration files, which record the
+    version of their schema. Files written by older releases are migrated when they are
+    loaded, after a copy of them is saved next to them, e.g. config.json.v0.bak. Files
+    written by newer releases are refused: migrate them back with the release which wrote
+    them before using an older one.
+
+    The state, i.e. the identities and their vaults, the configurations of the nodes, their
+    policies and their trust contexts, can be backed up to an archive encrypted with a
+    passphrase, and restored from it, e.g. on a new workstation. The passphrase is read
+    from a file, or from the OCKAM_STATE_PASSPHRASE environment variable. Without their
+    secret keys, the restored identities can be shown and verified but can not sign.
+
+Examples:
+```sh
+    # Show the migrations the state needs, without changing it
+    $ ockam state migrate --dry-run
+
+    # Migrate the state to the schemas of this release
+    $ ockam state migrate
+
+    # Migrate the state back to version 0, e.g. before downgrading ockam
+    $ ockam state migrate --to 0
+
+    # Back up the state, without the secret keys
+    $ ockam state backup ockam.bak --exclude-secrets --passphrase-file passphrase.txt
+
+    # Restore the state on another workstation
+    $ ockam state restore ockam.bak --passphrase-file passphrase.txt
+```
+";
+
+/// Manage the state of the CLI and of its nodes
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    help_template = help::template(HELP_DETAIL)
+)]
+pub struct StateCommand {
+    #[command(subcommand)]
+    subcommand: StateSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum StateSubcommand {
+    Migrate(MigrateCommand),
+    Backup(BackupCommand),
+    Restore(RestoreCommand),
+}
+
+impl StateCommand {
+    /// Run the command, without the configuration of the CLI since
+    /// loading it would migrate it.
+    pub fn run(self) {
+        match self.subcommand {
+            StateSubcommand::Migrate(c) => c.run(),
+            StateSubcommand::Backup(c) => c.run(),
+            StateSubcommand::Restore(c) => c.run(),
+        }
+    }
+}
+
+/// The passphrase of a state archive, read from `file` or from the
+/// environment.
+fn passphrase(file: Option<&Path>) -> anyhow::Result<String> {
+    let passphrase = match file {
+        Some(f) => std::fs::read_to_string(f)
+            .with_context(|| format!("failed to read {:?}", f))?
+            .trim_end_matches(['\r', '\n'])
+            .to_string(),
+        None => std::env::var(PASSPHRASE_ENV).map_err(|_| {
+            anyhow::anyhow!("Set the passphrase with --passphrase-file or {PASSPHRASE_ENV}")
+        })?,
+    };
+    if passphrase.is_empty() {
+        return Err(anyhow::anyhow!("The passphrase is empty"));
+    }
+    Ok(passphrase)
+}
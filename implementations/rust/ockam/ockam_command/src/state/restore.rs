@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Args;
+
+use ockam_api::config::cli::OckamConfig;
+
+use super::archive::{clear, Archive};
+use super::passphrase;
+use crate::util::exitcode;
+
+/// Restore the state of the CLI and of its nodes from an encrypted archive
+///
+/// Stop the nodes first, then start them again once the state is restored.
+#[derive(Clone, Debug, Args)]
+pub struct RestoreCommand {
+    /// Archive to read
+    file: PathBuf,
+
+    /// Overwrite the existing state
+    #[arg(long, display_order = 900)]
+    force: bool,
+
+    /// File holding the passphrase of the archive (optional, defaults to
+    /// the OCKAM_STATE_PASSPHRASE environment variable)
+    #[arg(long, display_order = 900)]
+    passphrase_file: Option<PathBuf>,
+}
+
+impl RestoreCommand {
+    pub fn run(self) {
+        if let Err(e) = run_impl(self) {
+            eprintln!("{:#}", e);
+            std::process::exit(exitcode::SOFTWARE);
+        }
+    }
+}
+
+fn run_impl(cmd: RestoreCommand) -> anyhow::Result<()> {
+    let passphrase = passphrase(cmd.passphrase_file.as_deref())?;
+    let root = OckamConfig::directories().config_dir().to_path_buf();
+    if root.join("config.json").exists() && !cmd.force {
+        return Err(anyhow::anyhow!(
+            "There is already a state in {}, use --force to overwrite it",
+            root.display()
+        ));
+    }
+    let data =
+        std::fs::read(&cmd.file).with_context(|| format!("failed to read {:?}", cmd.file))?;
+    let archive = Archive::open(&data, &passphrase)?;
+    // Files of the former state which are not in the archive would be left
+    if cmd.force && root.exists() {
+        clear(&root)?;
+    }
+    let paths = archive.write(&root)?;
+    println!(
+        "Restored {} files from {} to {}",
+        paths.len(),
+        cmd.file.display(),
+        root.display()
+    );
+    if !archive.secrets {
+        println!("The archive has no secret keys: the restored identities can not be used to sign");
+    }
+    Ok(())
+}
@@ -0,0 +1,197 @@
+use crate::util::{bind_to_port_check, connect_to, exitcode, get_final_element};
+use crate::{help, CommandGlobalOpts};
+use clap::Args;
+use minicbor::Decoder;
+use ockam::{Context, Route};
+use ockam_api::config::lookup::ConfigLookup;
+use ockam_api::{
+    clean_multiaddr, nodes::models, nodes::models::portal::InletStatus, nodes::NODEMANAGER_ADDR,
+};
+use ockam_core::api::{Error, Request, Response, Status};
+use ockam_multiaddr::MultiAddr;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+
+const HELP_DETAIL: &str = "\
+Examples:
+
+```sh
+    # Create two nodes
+    $ ockam node create n1
+    $ ockam node create n2
+
+    # Create a TCP outlet from n1 to an internal API
+    $ ockam tcp-outlet create --at /node/n1 --from /service/api --to 10.0.0.5:443
+
+    # Create an HTTP proxy on n2, which sends the tunnels to api.internal:443 to the outlet on n1
+    $ ockam http-proxy create --at /node/n2 --port 3128 --route 'api.internal:443=/node/n1/service/api'
+
+    # Applications configured with the proxy reach the API through the portal
+    $ https_proxy=http://127.0.0.1:3128 curl https://api.internal
+```
+
+    The proxy accepts HTTP CONNECT tunnels, as opened by clients for https URLs, and plain
+    requests for http URLs, which go to port 80 unless the URL has a port:
+
+```sh
+    $ ockam tcp-outlet create --at /node/n1 --from /service/web --to 10.0.0.6:8888
+    $ ockam http-proxy create --at /node/n2 --route 'notebook.internal:8888=/node/n1/service/web'
+    $ http_proxy=http://127.0.0.1:3128 curl http://notebook.internal:8888/api
+```
+
+    After the request, the connection is streamed as is, so chunked bodies and WebSockets, as used
+    by Jupyter or live reloading dev servers, work through the portal. A connection only reaches
+    the host of its first request, and is closed after the response unless it is upgraded.
+
+    Each --route
+    maps the hosts matched by a rule to the outlet dedicated to them. A rule is a host and an
+    optional port, where `*` is any host and `*.example.com` any subdomain of example.com. The
+    first matching route is used. Tunnels to other hosts are refused with 403 Forbidden, unless
+    --to gives the route to a proxy outlet, which connects to the hosts its own rules allow.
+
+```sh
+    $ ockam tcp-outlet create --at /node/n1 --from /service/proxy --allow '*.example.com:443'
+    $ ockam http-proxy create --at /node/n2 --to /node/n1/service/proxy \\
+        --route 'api.internal:443=/node/n1/service/api'
+```
+";
+
+/// Create HTTP proxies
+#[derive(Clone, Debug, Args)]
+#[command(help_template = help::template(HELP_DETAIL))]
+pub struct CreateCommand {
+    /// Node on which to start the proxy.
+    #[arg(long, display_order = 900, id = "NODE")]
+    at: String,
+
+    /// Port on which to accept HTTP connections.
+    #[arg(long, display_order = 900, default_value = "3128")]
+    port: u16,
+
+    /// Address on which to accept HTTP connections.
+    #[arg(long, display_order = 900, default_value = "127.0.0.1")]
+    host: IpAddr,
+
+    /// Route to a proxy outlet, for the hosts without a route.
+    #[arg(long, display_order = 900, id = "ROUTE")]
+    to: Option<MultiAddr>,
+
+    /// Route to the outlet of the hosts matched by a rule, as RULE=ROUTE.
+    #[arg(
+        long,
+        display_order = 900,
+        id = "RULE=ROUTE",
+        value_parser = parse_route,
+        required_unless_present = "ROUTE"
+    )]
+    route: Vec<(String, MultiAddr)>,
+
+    /// Enable credentials authorization
+    #[arg(long, short, display_order = 802)]
+    pub check_credential: bool,
+}
+
+fn parse_route(s: &str) -> Result<(String, MultiAddr), String> {
+    let (rule, route) = s
+        .split_once('=')
+        .ok_or_else(|| "expected RULE=ROUTE".to_string())?;
+    let route = MultiAddr::from_str(route).map_err(|e| e.to_string())?;
+    Ok((rule.to_string(), route))
+}
+
+fn clean(addr: &MultiAddr, lookup: &ConfigLookup) -> MultiAddr {
+    match clean_multiaddr(addr, lookup) {
+        Some((addr, _meta)) => addr,
+        None => {
+            eprintln!("failed to normalize MultiAddr route");
+            std::process::exit(exitcode::USAGE);
+        }
+    }
+}
+
+impl CreateCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> anyhow::Result<()> {
+        let lookup = options.config.lookup();
+        let command = CreateCommand {
+            to: self.to.as_ref().map(|to| clean(to, &lookup)),
+            route: self
+                .route
+                .iter()
+                .map(|(rule, to)| (rule.clone(), clean(to, &lookup)))
+                .collect(),
+            ..self
+        };
+
+        let node = get_final_element(&command.at);
+        let port = options.config.get_node_port(node);
+
+        // Check if the port is used by some other services or process
+        if !bind_to_port_check(&command.bind_addr()) {
+            eprintln!("Another process is listening on the provided port!");
+            std::process::exit(exitcode::IOERR);
+        }
+
+        connect_to(port, command, create_proxy);
+        Ok(())
+    }
+
+    fn bind_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.host, self.port)
+    }
+}
+
+pub async fn create_proxy(
+    ctx: Context,
+    cmd: CreateCommand,
+    mut base_route: Route,
+) -> anyhow::Result<()> {
+    let route = base_route.modify().append(NODEMANAGER_ADDR);
+    let message = make_api_request(&cmd)?;
+    let response: Vec<u8> = ctx.send_and_receive(route, message).await?;
+
+    let mut dec = Decoder::new(&response);
+    let response = dec.decode::<Response>()?;
+
+    match response.status() {
+        Some(Status::Ok) => {
+            let InletStatus { bind_addr, .. } = dec.decode()?;
+            println!("{}", bind_addr);
+        }
+        _ => {
+            match dec
+                .decode::<Error>()
+                .ok()
+                .and_then(|e| e.message().map(String::from))
+            {
+                Some(msg) => eprintln!("Failed to create the proxy: {msg}"),
+                None => eprintln!("An unknown error occurred while creating a proxy..."),
+            }
+            std::process::exit(exitcode::UNAVAILABLE)
+        }
+    }
+
+    Ok(())
+}
+
+/// Construct a request to create an HTTP proxy inlet
+fn make_api_request(cmd: &CreateCommand) -> ockam::Result<Vec<u8>> {
+    let payload = models::portal::CreateInlet::new(
+        cmd.bind_addr().to_string(),
+        cmd.to.as_ref().map(|to| to.to_string()).unwrap_or_default(),
+        None,
+        cmd.check_credential,
+    )
+    .with_proxy("http")
+    .with_proxy_routes(
+        cmd.route
+            .iter()
+            .map(|(rule, to)| format!("{rule}={to}").into())
+            .collect(),
+    );
+
+    let mut buf = vec![];
+    Request::post("/node/inlet")
+        .body(payload)
+        .encode(&mut buf)?;
+    Ok(buf)
+}
@@ -0,0 +1,25 @@
+mod create;
+
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+use create::CreateCommand;
+
+/// Manage HTTP proxies
+#[derive(Clone, Debug, Args)]
+pub struct HttpProxyCommand {
+    #[command(subcommand)]
+    subcommand: HttpProxySubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum HttpProxySubCommand {
+    Create(CreateCommand),
+}
+
+impl HttpProxyCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            HttpProxySubCommand::Create(c) => c.run(options).unwrap(),
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use crate::util::{bind_to_port_check, connect_to, exitcode, get_final_element};
+use crate::tcp::SocketOpts;
+use crate::util::api::{self, DelegateOpts};
+use crate::util::{absolute_path, bind_to_port_check, connect_to, exitcode, get_final_element};
 use crate::{help, CommandGlobalOpts};
 use clap::Args;
 use minicbor::Decoder;
@@ -6,9 +8,10 @@ use ockam::{Context, Route};
 use ockam_api::{
     clean_multiaddr, nodes::models, nodes::models::portal::InletStatus, nodes::NODEMANAGER_ADDR,
 };
-use ockam_core::api::{Request, Response, Status};
+use ockam_core::api::{Error, Request, Response, Status};
 use ockam_multiaddr::MultiAddr;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
 const HELP_DETAIL: &str = "\
 Examples:
@@ -30,6 +33,47 @@ Examples:
     # Access the service via the inlet/outlet pair
     $ curl 127.0.0.1:6000
 ```
+
+    An inlet can be given backup routes to its outlet, for instance through a forwarder
+    registered at a second relay. The inlet checks its routes regularly and new connections
+    use the first healthy one, preferring --to over the backup routes. A route can carry a
+    cost, e.g. `/cost/50`, in which case cheaper routes are preferred.
+
+```sh
+    $ ockam forwarder create n1 --at /node/r1 --at /node/r2 --to /node/n1
+    $ ockam tcp-inlet create --at /node/n2 --from 127.0.0.1:6000 \\
+        --to /node/r1/service/forward_to_n1/service/outlet \\
+        --backup-to /node/r2/service/forward_to_n1/service/outlet
+```
+
+    With --tls the inlet accepts TLS connections, for clients that insist on https. By default
+    the node generates a self-signed certificate for localhost, stored in the node directory,
+    which clients have to trust. Its path is printed when the inlet is created. A certificate issued by a CA, for instance provisioned with an
+    ACME client such as certbot, can be given with --tls-cert and --tls-key. The files are read
+    again when they change, so renewed certificates are used without recreating the inlet.
+
+```sh
+    $ ockam tcp-inlet create --at /node/n2 --from 127.0.0.1:6443 --to /node/n1/service/outlet --tls
+    $ curl --cacert path/to/printed/certificate.crt https://localhost:6443
+```
+
+    The socket of the inlet can be bound to a network interface of a multi-homed gateway with
+    --interface, and with --reuse-port several nodes can run inlets behind the same port.
+
+```sh
+    $ ockam tcp-inlet create --at /node/n2 --from 0.0.0.0:6000 --to /node/n1/service/outlet \\
+        --interface eth1 --reuse-port --backlog 4096
+    $ ockam tcp-inlet create --at /node/n3 --from 0.0.0.0:6000 --to /node/n1/service/outlet \\
+        --interface eth1 --reuse-port --backlog 4096
+```
+
+    A node started by systemd can listen on the sockets of its .socket unit, e.g. on a
+    privileged port, with --socket-activation.
+
+```sh
+    $ ockam tcp-inlet create --at /node/n1 --from 0.0.0.0:443 --to /node/n2/service/outlet \
+        --socket-activation
+```
 ";
 
 /// Create TCP Inlets
@@ -48,9 +92,32 @@ pub struct CreateCommand {
     #[arg(long, display_order = 900, id = "ROUTE")]
     to: MultiAddr,
 
+    /// Route to the same tcp outlet, used when the other routes are not healthy.
+    #[arg(long, display_order = 900, id = "BACKUP_ROUTE")]
+    backup_to: Vec<MultiAddr>,
+
     /// Enable credentials authorization
     #[arg(long, short, display_order = 802)]
     pub check_credential: bool,
+
+    /// Accept TLS connections, with a self-signed certificate unless --tls-cert is given.
+    #[arg(long, display_order = 803)]
+    tls: bool,
+
+    /// PEM file with the certificate chain presented to TLS clients.
+    #[arg(long, display_order = 803, id = "CERT_FILE", requires = "KEY_FILE")]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM file with the private key of the certificate.
+    #[arg(long, display_order = 803, id = "KEY_FILE", requires = "CERT_FILE")]
+    tls_key: Option<PathBuf>,
+
+    #[command(flatten)]
+    socket_opts: SocketOpts,
+
+    /// Create the inlet on a remote node instead
+    #[command(flatten)]
+    delegate_opts: DelegateOpts,
 }
 
 impl CreateCommand {
@@ -64,6 +131,29 @@ impl CreateCommand {
                     std::process::exit(exitcode::USAGE);
                 }
             },
+            backup_to: self
+                .backup_to
+                .iter()
+                .map(|to| match clean_multiaddr(to, &cfg.lookup()) {
+                    Some((addr, _meta)) => addr,
+                    None => {
+                        eprintln!("failed to normalize MultiAddr route");
+                        std::process::exit(exitcode::USAGE);
+                    }
+                })
+                .collect(),
+            tls_cert: self.tls_cert.as_deref().map(absolute_path),
+            tls_key: self.tls_key.as_deref().map(absolute_path),
+            delegate_opts: DelegateOpts {
+                delegate_to: match self.delegate_opts.resolve(&cfg.lookup()) {
+                    Ok(to) => to,
+                    Err(e) => {
+                        eprintln!("{e}");
+                        std::process::exit(exitcode::USAGE);
+                    }
+                },
+                ..self.delegate_opts
+            },
             ..self
         };
 
@@ -71,7 +161,8 @@ impl CreateCommand {
         let port = cfg.get_node_port(node);
 
         // Check if the port is used by some other services or process
-        if !bind_to_port_check(&command.from) {
+        let is_local = command.delegate_opts.delegate_to.is_none();
+        if is_local && !command.socket_opts.shares_address() && !bind_to_port_check(&command.from) {
             eprintln!("Another process is listening on the provided port!");
             std::process::exit(exitcode::IOERR);
         }
@@ -87,23 +178,38 @@ pub async fn create_inlet(
     mut base_route: Route,
 ) -> anyhow::Result<()> {
     let route = base_route.modify().append(NODEMANAGER_ADDR);
-    let message = make_api_request(
-        &cmd.from.to_string(),
-        &cmd.to,
-        &None::<String>,
-        cmd.check_credential,
-    )?;
+    let message = make_api_request(&cmd, &None::<String>)?;
     let response: Vec<u8> = ctx.send_and_receive(route, message).await?;
 
-    let (response, InletStatus { bind_addr, .. }) = parse_inlet_status(&response)?;
+    let mut dec = Decoder::new(&response);
+    let response = dec.decode::<Response>()?;
 
     match response.status() {
         Some(Status::Ok) => {
-            println!("{}", bind_addr)
+            let InletStatus {
+                bind_addr,
+                tls_cert,
+                outlet_identity,
+                ..
+            } = dec.decode()?;
+            println!("{}", bind_addr);
+            if let Some(cert) = tls_cert {
+                eprintln!("TLS certificate: {cert}")
+            }
+            if let Some(identity) = outlet_identity {
+                eprintln!("Outlet identity: {identity}")
+            }
         }
 
         _ => {
-            eprintln!("An unknown error occurred while creating an inlet...");
+            match dec
+                .decode::<Error>()
+                .ok()
+                .and_then(|e| e.message().map(String::from))
+            {
+                Some(msg) => eprintln!("Failed to create the inlet: {msg}"),
+                None => eprintln!("An unknown error occurred while creating an inlet..."),
+            }
             std::process::exit(exitcode::UNAVAILABLE)
         }
     }
@@ -112,29 +218,36 @@ pub async fn create_inlet(
 }
 
 /// Construct a request to create a tcp inlet
-fn make_api_request(
-    bind_addr: &str,
-    outlet_route: &MultiAddr,
-    alias: &Option<String>,
-    check_credential: bool,
-) -> ockam::Result<Vec<u8>> {
-    let payload = models::portal::CreateInlet::new(
-        bind_addr,
-        outlet_route.to_string(),
+fn make_api_request(cmd: &CreateCommand, alias: &Option<String>) -> ockam::Result<Vec<u8>> {
+    let mut payload = models::portal::CreateInlet::new(
+        cmd.from.to_string(),
+        cmd.to.to_string(),
         alias.as_ref().map(|x| x.as_str().into()),
-        check_credential,
-    );
+        cmd.check_credential,
+    )
+    .with_backup_routes(cmd.backup_to.iter().map(|r| r.to_string().into()).collect());
+    if cmd.tls || cmd.tls_cert.is_some() {
+        let cert_and_key = cmd
+            .tls_cert
+            .as_ref()
+            .zip(cmd.tls_key.as_ref())
+            .map(|(c, k)| {
+                (
+                    c.display().to_string().into(),
+                    k.display().to_string().into(),
+                )
+            });
+        payload = payload.with_tls(cert_and_key)
+    }
+    if let Some(socket) = cmd.socket_opts.socket_options() {
+        payload = payload.with_socket_options(socket)
+    }
 
-    let mut buf = vec![];
-    Request::post("/node/inlet")
-        .body(payload)
-        .encode(&mut buf)?;
+    let req = Request::post("/node/inlet").body(payload);
+    let delegate_opts = &cmd.delegate_opts;
+    let buf = match &delegate_opts.delegate_to {
+        Some(to) => api::delegate(req, to, delegate_opts.delegate_identity.clone())?.to_vec()?,
+        None => req.to_vec()?,
+    };
     Ok(buf)
 }
-
-/// Parse the returned status response
-fn parse_inlet_status(resp: &[u8]) -> ockam::Result<(Response, InletStatus<'_>)> {
-    let mut dec = Decoder::new(resp);
-    let response = dec.decode::<Response>()?;
-    Ok((response, dec.decode::<InletStatus>()?))
-}
@@ -1,3 +1,4 @@
+use crate::tcp::SocketOpts;
 use crate::util::{bind_to_port_check, get_final_element};
 use crate::{
     util::{api, connect_to, exitcode},
@@ -19,12 +20,21 @@ pub struct CreateCommand {
 
     /// Address for this listener (eg. 127.0.0.1:7000)
     pub address: String,
+
+    #[command(flatten)]
+    pub socket_opts: SocketOpts,
 }
 
 #[derive(Clone, Debug, Args)]
 pub struct TCPListenerNodeOpts {
     /// Node at which to create the listener
-    #[arg(global = true, long, value_name = "NODE", default_value = "default")]
+    #[arg(
+        global = true,
+        long,
+        visible_alias = "node",
+        value_name = "NODE",
+        default_value = "default"
+    )]
     pub at: String,
 }
 
@@ -42,8 +52,9 @@ impl CreateCommand {
             }
         };
 
-        // Check if the port is used by some other services or process
-        if !bind_to_port_check(&input_addr) {
+        // Check if the port is used by some other services or process,
+        // unless the listener is meant to share it
+        if !self.socket_opts.shares_address() && !bind_to_port_check(&input_addr) {
             eprintln!("Another process is listening on the provided port!");
             std::process::exit(exitcode::IOERR);
         }
@@ -1,16 +1,40 @@
 mod create;
 mod delete;
 mod list;
+mod rebind;
 
 pub(crate) use create::CreateCommand;
 pub(crate) use delete::DeleteCommand;
 pub(crate) use list::ListCommand;
+pub(crate) use rebind::RebindCommand;
 
-use crate::CommandGlobalOpts;
+use crate::{help, CommandGlobalOpts};
 use clap::{Args, Subcommand};
 
+const HELP_DETAIL: &str = "\
+Examples:
+```sh
+    # Listen on another port of a running node
+    $ ockam tcp-listener create --node n1 127.0.0.1:7000
+
+    # Move the listener to a free port, e.g. after an address conflict
+    $ ockam tcp-listener rebind --node n1 <ID> 127.0.0.1:7001
+
+    # Share a port with the listener of another node
+    $ ockam tcp-listener create --node n1 0.0.0.0:7002 --reuse-port
+    $ ockam tcp-listener create --node n2 0.0.0.0:7002 --reuse-port
+
+    # Listen on a socket passed by systemd to a node it started
+    $ ockam tcp-listener create --node n1 0.0.0.0:443 --socket-activation
+
+    # Stop listening
+    $ ockam tcp-listener delete --node n1 <ID>
+```
+";
+
 /// Manage TCP Listeners
 #[derive(Args, Clone, Debug)]
+#[command(help_template = help::template(HELP_DETAIL))]
 pub struct TcpListenerCommand {
     #[command(subcommand)]
     subcommand: TcpListenerSubCommand,
@@ -26,6 +50,9 @@ pub enum TcpListenerSubCommand {
 
     /// List tcp listeners registered on the selected node
     List(ListCommand),
+
+    /// Move a tcp listener of the selected node to another address
+    Rebind(RebindCommand),
 }
 
 impl TcpListenerCommand {
@@ -34,6 +61,7 @@ impl TcpListenerCommand {
             TcpListenerSubCommand::Create(c) => c.run(options),
             TcpListenerSubCommand::Delete(c) => c.run(options),
             TcpListenerSubCommand::List(c) => c.run(options),
+            TcpListenerSubCommand::Rebind(c) => c.run(options),
         }
     }
 }
@@ -0,0 +1,66 @@
+use clap::Args;
+use ockam::{Context, Route};
+use ockam_api::nodes::{models::transport::TransportStatus, NODEMANAGER_ADDR};
+use ockam_core::api::Status;
+
+use crate::util::get_final_element;
+use crate::{
+    node::NodeOpts,
+    util::{api, connect_to, exitcode},
+    CommandGlobalOpts,
+};
+
+#[derive(Clone, Debug, Args)]
+pub struct RebindCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+
+    /// Tcp Listener ID
+    pub id: String,
+
+    /// New address for this listener (eg. 127.0.0.1:7001)
+    pub address: String,
+}
+
+impl RebindCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        let cfg = &options.config;
+        let node = get_final_element(&self.node_opts.api_node);
+        let port = cfg.get_node_port(node);
+        connect_to(port, self, rebind_listener);
+    }
+}
+
+pub async fn rebind_listener(
+    ctx: Context,
+    cmd: RebindCommand,
+    mut base_route: Route,
+) -> anyhow::Result<()> {
+    let resp: Vec<u8> = match ctx
+        .send_and_receive(
+            base_route.modify().append(NODEMANAGER_ADDR),
+            api::rebind_tcp_listener(&cmd)?,
+        )
+        .await
+    {
+        Ok(sr_msg) => sr_msg,
+        Err(e) => {
+            eprintln!("Wasn't able to send or receive `Message`: {}", e);
+            std::process::exit(exitcode::IOERR);
+        }
+    };
+
+    let (response, TransportStatus { payload, .. }) = api::parse_transport_status(&resp)?;
+
+    match response.status() {
+        Some(Status::Ok) => println!("Tcp listener `{}` now listens on {}", cmd.id, payload),
+        _ => {
+            eprintln!(
+                "An error occurred while rebinding the tcp listener: {}",
+                payload
+            );
+            std::process::exit(exitcode::CANTCREAT);
+        }
+    }
+    Ok(())
+}
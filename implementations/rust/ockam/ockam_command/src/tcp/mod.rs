@@ -1,4 +1,69 @@
 pub(crate) mod connection;
+pub(crate) mod http_proxy;
 pub(crate) mod inlet;
 pub(crate) mod listener;
 pub(crate) mod outlet;
+pub(crate) mod socks_proxy;
+
+use clap::Args;
+use ockam_api::nodes::models::transport::SocketOptions;
+
+/// Options of the socket a listener or an inlet listens on
+#[derive(Clone, Debug, Args)]
+pub struct SocketOpts {
+    /// Only accept connections received on this network interface, e.g. eth1 (Linux only)
+    #[arg(long, display_order = 850, value_name = "INTERFACE")]
+    pub interface: Option<String>,
+
+    /// Only accept IPv6 connections on an IPv6 address, instead of IPv4 ones too
+    #[arg(long, display_order = 850)]
+    pub ipv6_only: bool,
+
+    /// Share the address with other listeners, e.g. of other nodes, with SO_REUSEPORT (Unix only)
+    #[arg(long, display_order = 850)]
+    pub reuse_port: bool,
+
+    /// Maximum number of connections waiting to be accepted
+    #[arg(long, display_order = 850, value_name = "CONNECTIONS")]
+    pub backlog: Option<u32>,
+
+    /// Listen on the socket passed by systemd for this address, if any (socket activation)
+    #[arg(long, display_order = 850)]
+    pub socket_activation: bool,
+
+    /// Number of workers accepting connections, each on its own socket bound with SO_REUSEPORT (Unix only)
+    #[arg(long, display_order = 850, value_name = "WORKERS")]
+    pub accept_workers: Option<u32>,
+}
+
+impl SocketOpts {
+    /// The options to send to the node, `None` for the defaults
+    pub fn socket_options(&self) -> Option<SocketOptions<'_>> {
+        if self.interface.is_none()
+            && !self.ipv6_only
+            && !self.reuse_port
+            && self.backlog.is_none()
+            && !self.socket_activation
+            && self.accept_workers.is_none()
+        {
+            return None;
+        }
+        let options = SocketOptions::new(
+            self.interface.as_deref().map(Into::into),
+            if self.ipv6_only { Some(true) } else { None },
+            self.reuse_port,
+            self.backlog,
+        );
+        Some(
+            options
+                .with_socket_activation(self.socket_activation)
+                .with_accept_workers(self.accept_workers),
+        )
+    }
+
+    /// Whether the address may already be in use, by other listeners or
+    /// by systemd holding the socket for the node
+    pub fn shares_address(&self) -> bool {
+        self.reuse_port || self.socket_activation
+    }
+}
@@ -1,4 +1,4 @@
-use crate::util::{connect_to, exitcode, get_final_element};
+use crate::util::{absolute_path, connect_to, exitcode, get_final_element};
 use crate::{help, CommandGlobalOpts};
 use clap::Args;
 use minicbor::Decoder;
@@ -12,6 +12,7 @@ use ockam_api::{
 use ockam_core::api::{Request, Response, Status};
 use ockam_core::route;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
 const HELP_DETAIL: &str = "\
 Examples:
@@ -33,6 +34,26 @@ Examples:
     # Access the service via the inlet/outlet pair
     $ curl 127.0.0.1:6000
 ```
+
+    With --tls the outlet connects to its target over TLS, so that traffic is also protected
+    between the outlet and a target on another machine. The certificate of the target must be
+    valid for --tls-server-name and is verified with the system's root certificates, or with
+    the CA certificates of --tls-ca. With --tls-cert and --tls-key the outlet authenticates
+    itself with a client certificate (mutual TLS). Changed certificate files are read again for
+    new connections.
+
+```sh
+    $ ockam tcp-outlet create --at /node/n1 --from /service/outlet --to 10.0.0.5:443 \
+        --tls --tls-server-name api.internal --tls-ca ca.pem --tls-cert client.pem --tls-key client.key
+```
+
+    With --allow instead of --to, the outlet is a proxy outlet for `ockam socks-proxy` and
+    `ockam http-proxy`, which connects to the host and port requested by each client if one of
+    the rules allows it.
+
+```sh
+    $ ockam tcp-outlet create --at /node/n1 --from /service/proxy --allow '*.example.com:443' --allow 10.0.0.5
+```
 ";
 
 /// Create TCP Outlets
@@ -48,12 +69,43 @@ pub struct CreateCommand {
     from: String,
 
     /// TCP address to send raw tcp traffic.
-    #[arg(long, display_order = 902, id = "SOCKET_ADDRESS")]
-    to: SocketAddr,
+    #[arg(
+        long,
+        display_order = 902,
+        id = "SOCKET_ADDRESS",
+        required_unless_present = "RULE",
+        conflicts_with = "RULE"
+    )]
+    to: Option<SocketAddr>,
+
+    /// Make a proxy outlet, for SOCKS5 and HTTP proxies, which may connect to the targets matched by
+    /// this rule, e.g. `*.example.com:443`.
+    #[arg(long, display_order = 902, id = "RULE", conflicts_with = "tls")]
+    allow: Vec<String>,
 
     /// Enable credentials authorization
     #[arg(long, short, display_order = 802)]
     pub check_credential: bool,
+
+    /// Connect to the target over TLS.
+    #[arg(long, display_order = 803, requires = "SERVER_NAME")]
+    tls: bool,
+
+    /// Name the certificate of the target must be valid for.
+    #[arg(long, display_order = 803, id = "SERVER_NAME", requires = "tls")]
+    tls_server_name: Option<String>,
+
+    /// PEM file with the CA certificates of the target, instead of the system's.
+    #[arg(long, display_order = 803, id = "CA_FILE", requires = "tls")]
+    tls_ca: Option<PathBuf>,
+
+    /// PEM file with the client certificate chain presented to the target.
+    #[arg(long, display_order = 803, id = "CERT_FILE", requires_all = ["tls", "KEY_FILE"])]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM file with the private key of the client certificate.
+    #[arg(long, display_order = 803, id = "KEY_FILE", requires_all = ["tls", "CERT_FILE"])]
+    tls_key: Option<PathBuf>,
 }
 
 impl CreateCommand {
@@ -65,6 +117,9 @@ impl CreateCommand {
 
         let command = CreateCommand {
             from: String::from(get_final_element(&self.from)),
+            tls_ca: self.tls_ca.as_deref().map(absolute_path),
+            tls_cert: self.tls_cert.as_deref().map(absolute_path),
+            tls_key: self.tls_key.as_deref().map(absolute_path),
             ..self
         };
 
@@ -101,10 +156,21 @@ pub async fn create_outlet(
 
 /// Construct a request to create a tcp outlet
 fn make_api_request(cmd: CreateCommand) -> ockam::Result<Vec<u8>> {
-    let tcp_addr = &cmd.to.to_string();
+    let tcp_addr = &cmd.to.map(|a| a.to_string()).unwrap_or_default();
     let worker_addr = cmd.from;
     let alias = (None::<String>).as_ref().map(|x| x.as_str().into());
-    let payload = CreateOutlet::new(tcp_addr, worker_addr, alias, cmd.check_credential);
+    let mut payload = CreateOutlet::new(tcp_addr, worker_addr, alias, cmd.check_credential);
+    if !cmd.allow.is_empty() {
+        payload = payload.with_egress(cmd.allow.into_iter().map(Into::into).collect());
+    }
+    if let Some(server_name) = cmd.tls_server_name {
+        let path = |p: PathBuf| p.display().to_string().into();
+        let cert_and_key = cmd
+            .tls_cert
+            .zip(cmd.tls_key)
+            .map(|(c, k)| (path(c), path(k)));
+        payload = payload.with_tls(server_name, cmd.tls_ca.map(path), cert_and_key);
+    }
 
     let mut buf = vec![];
     Request::post("/node/outlet")
@@ -0,0 +1,145 @@
+use crate::util::{bind_to_port_check, connect_to, exitcode, get_final_element};
+use crate::{help, CommandGlobalOpts};
+use clap::Args;
+use minicbor::Decoder;
+use ockam::{Context, Route};
+use ockam_api::{
+    clean_multiaddr, nodes::models, nodes::models::portal::InletStatus, nodes::NODEMANAGER_ADDR,
+};
+use ockam_core::api::{Error, Request, Response, Status};
+use ockam_multiaddr::MultiAddr;
+use std::net::{IpAddr, SocketAddr};
+
+const HELP_DETAIL: &str = "\
+Examples:
+
+```sh
+    # Create two nodes
+    $ ockam node create n1
+    $ ockam node create n2
+
+    # Create a proxy outlet on n1, which may connect to any host of example.com on port 443
+    $ ockam tcp-outlet create --at /node/n1 --from /service/proxy --allow '*.example.com:443'
+
+    # Create a SOCKS5 proxy on n2, tunneling its connections to the proxy outlet on n1
+    $ ockam socks-proxy create --at /node/n2 --port 1080 --to /node/n1/service/proxy
+
+    # Connections made through the proxy leave the network from n1
+    $ curl --socks5-hostname 127.0.0.1:1080 https://www.example.com
+```
+
+    Clients pick the host and port the proxy outlet connects to, so the outlet only connects
+    to the targets allowed by its --allow rules. A rule is a host and an optional port, where
+    `*` is any host and `*.example.com` any subdomain of example.com. Host names are checked
+    as requested by the client, before they are resolved by the outlet.
+
+    The proxy only supports the CONNECT command, without authentication, so it listens on
+    127.0.0.1 unless --host is given.
+";
+
+/// Create SOCKS5 proxies
+#[derive(Clone, Debug, Args)]
+#[command(help_template = help::template(HELP_DETAIL))]
+pub struct CreateCommand {
+    /// Node on which to start the proxy.
+    #[arg(long, display_order = 900, id = "NODE")]
+    at: String,
+
+    /// Port on which to accept SOCKS5 connections.
+    #[arg(long, display_order = 900, default_value = "1080")]
+    port: u16,
+
+    /// Address on which to accept SOCKS5 connections.
+    #[arg(long, display_order = 900, default_value = "127.0.0.1")]
+    host: IpAddr,
+
+    /// Route to a proxy outlet.
+    #[arg(long, display_order = 900, id = "ROUTE")]
+    to: MultiAddr,
+
+    /// Enable credentials authorization
+    #[arg(long, short, display_order = 802)]
+    pub check_credential: bool,
+}
+
+impl CreateCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> anyhow::Result<()> {
+        let cfg = &options.config;
+        let command = CreateCommand {
+            to: match clean_multiaddr(&self.to, &cfg.lookup()) {
+                Some((addr, _meta)) => addr,
+                None => {
+                    eprintln!("failed to normalize MultiAddr route");
+                    std::process::exit(exitcode::USAGE);
+                }
+            },
+            ..self
+        };
+
+        let node = get_final_element(&command.at);
+        let port = cfg.get_node_port(node);
+
+        // Check if the port is used by some other services or process
+        if !bind_to_port_check(&command.bind_addr()) {
+            eprintln!("Another process is listening on the provided port!");
+            std::process::exit(exitcode::IOERR);
+        }
+
+        connect_to(port, command, create_proxy);
+        Ok(())
+    }
+
+    fn bind_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.host, self.port)
+    }
+}
+
+pub async fn create_proxy(
+    ctx: Context,
+    cmd: CreateCommand,
+    mut base_route: Route,
+) -> anyhow::Result<()> {
+    let route = base_route.modify().append(NODEMANAGER_ADDR);
+    let message = make_api_request(&cmd)?;
+    let response: Vec<u8> = ctx.send_and_receive(route, message).await?;
+
+    let mut dec = Decoder::new(&response);
+    let response = dec.decode::<Response>()?;
+
+    match response.status() {
+        Some(Status::Ok) => {
+            let InletStatus { bind_addr, .. } = dec.decode()?;
+            println!("{}", bind_addr);
+        }
+        _ => {
+            match dec
+                .decode::<Error>()
+                .ok()
+                .and_then(|e| e.message().map(String::from))
+            {
+                Some(msg) => eprintln!("Failed to create the proxy: {msg}"),
+                None => eprintln!("An unknown error occurred while creating a proxy..."),
+            }
+            std::process::exit(exitcode::UNAVAILABLE)
+        }
+    }
+
+    Ok(())
+}
+
+/// Construct a request to create a SOCKS5 inlet
+fn make_api_request(cmd: &CreateCommand) -> ockam::Result<Vec<u8>> {
+    let payload = models::portal::CreateInlet::new(
+        cmd.bind_addr().to_string(),
+        cmd.to.to_string(),
+        None,
+        cmd.check_credential,
+    )
+    .with_proxy("socks5");
+
+    let mut buf = vec![];
+    Request::post("/node/inlet")
+        .body(payload)
+        .encode(&mut buf)?;
+    Ok(buf)
+}
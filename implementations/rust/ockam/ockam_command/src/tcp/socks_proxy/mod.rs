@@ -0,0 +1,25 @@
+mod create;
+
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+use create::CreateCommand;
+
+/// Manage SOCKS5 proxies
+#[derive(Clone, Debug, Args)]
+pub struct SocksProxyCommand {
+    #[command(subcommand)]
+    subcommand: SocksProxySubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum SocksProxySubCommand {
+    Create(CreateCommand),
+}
+
+impl SocksProxyCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            SocksProxySubCommand::Create(c) => c.run(options).unwrap(),
+        }
+    }
+}
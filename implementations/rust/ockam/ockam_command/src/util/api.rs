@@ -1,5 +1,6 @@
 //! API shim to make it nicer to interact with the ockam messaging API
 
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
 use anyhow::Context;
@@ -10,8 +11,12 @@ use tracing::trace;
 
 use ockam::identity::IdentityIdentifier;
 use ockam::Result;
+use ockam_api::clean_multiaddr;
 use ockam_api::cloud::{BareCloudRequestWrapper, CloudRequestWrapper};
-use ockam_api::nodes::models::secure_channel::CredentialExchangeMode;
+use ockam_api::config::lookup::ConfigLookup;
+use ockam_api::nodes::models::secure_channel::{
+    AuthorityArg, CredentialExchangeMode, KeyAgreementMode, PreSharedKeyArg,
+};
 use ockam_api::nodes::*;
 use ockam_core::api::RequestBuilder;
 use ockam_core::api::{Request, Response};
@@ -70,8 +75,11 @@ pub(crate) fn create_tcp_listener(cmd: &crate::tcp::listener::CreateCommand) ->
         cmd.address.clone(),
     );
 
-    let payload =
+    let mut payload =
         models::transport::CreateTransport::new(models::transport::TransportType::Tcp, tt, addr);
+    if let Some(socket) = cmd.socket_opts.socket_options() {
+        payload = payload.with_socket_options(socket)
+    }
     let mut buf = vec![];
     Request::post("/node/tcp/listener")
         .body(payload)
@@ -101,6 +109,19 @@ pub(crate) fn delete_tcp_listener(cmd: &crate::tcp::listener::DeleteCommand) ->
     Ok(buf)
 }
 
+/// Construct a request to move a node tcp listener to another address
+pub(crate) fn rebind_tcp_listener(cmd: &crate::tcp::listener::RebindCommand) -> Result<Vec<u8>> {
+    let mut buf = vec![];
+    Request::put("/node/tcp/listener")
+        .body(models::transport::RebindTransport::new(
+            &cmd.id,
+            &cmd.address,
+        ))
+        .encode(&mut buf)?;
+
+    Ok(buf)
+}
+
 /// Construct a request to create a Vault
 pub(crate) fn create_vault(path: Option<String>) -> Result<Vec<u8>> {
     let mut buf = vec![];
@@ -168,11 +189,21 @@ pub(crate) fn show_secure_channel(
 pub(crate) fn create_secure_channel_listener(
     addr: &Address,
     authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+    required_attributes: Option<BTreeMap<String, String>>,
+    key_agreement: Option<KeyAgreementMode>,
+    psk: Option<PreSharedKeyArg<'_>>,
+    authorities: Option<Vec<AuthorityArg<'_>>>,
 ) -> Result<Vec<u8>> {
-    let payload = models::secure_channel::CreateSecureChannelListenerRequest::new(
+    let mut payload = models::secure_channel::CreateSecureChannelListenerRequest::new(
         addr,
         authorized_identifiers,
     );
+    payload.key_agreement = key_agreement;
+    payload.psk = psk;
+    payload.authorities = authorities;
+    if let Some(attributes) = required_attributes {
+        payload = payload.with_required_attributes(attributes);
+    }
 
     let mut buf = vec![];
     Request::post("/node/secure_channel_listener")
@@ -219,6 +250,16 @@ pub(crate) fn start_authenticated_service(addr: &str) -> Result<Vec<u8>> {
     Ok(buf)
 }
 
+/// Construct a request to send `req` to the delegation service of the node at `at`
+pub(crate) fn delegate<T: minicbor::Encode<()>>(
+    req: RequestBuilder<'_, T>,
+    at: &MultiAddr,
+    authorized: Option<IdentityIdentifier>,
+) -> Result<RequestBuilder<'static, models::delegate::Delegate<'static>>> {
+    let payload = models::delegate::Delegate::new(at.clone(), authorized, req.to_vec()?);
+    Ok(Request::post("/node/delegate").body(payload))
+}
+
 pub(crate) mod credentials {
     use ockam_api::nodes::models::credentials::{GetCredentialRequest, PresentCredentialRequest};
 
@@ -239,6 +280,7 @@ pub(crate) mod credentials {
 }
 
 /// Helpers to create enroll API requests
+#[cfg(feature = "cloud")]
 pub(crate) mod enroll {
     use ockam_api::cloud::enroll::auth0::{Auth0Token, AuthenticateAuth0Token};
 
@@ -257,6 +299,7 @@ pub(crate) mod enroll {
 }
 
 /// Helpers to create spaces API requests
+#[cfg(feature = "cloud")]
 pub(crate) mod space {
     use ockam_api::cloud::space::*;
 
@@ -290,12 +333,15 @@ pub(crate) mod space {
 
 /// Helpers to create projects API requests
 pub(crate) mod project {
+    #[cfg(feature = "cloud")]
     use ockam_api::cloud::project::*;
 
+    #[cfg(feature = "cloud")]
     use crate::project::*;
 
     use super::*;
 
+    #[cfg(feature = "cloud")]
     pub(crate) fn create<'a>(
         project_name: &'a str,
         space_id: &'a str,
@@ -311,6 +357,7 @@ pub(crate) mod project {
         Request::get("v0/projects").body(CloudRequestWrapper::bare(cloud_route))
     }
 
+    #[cfg(feature = "cloud")]
     pub(crate) fn show<'a>(
         id: &str,
         cloud_route: &'a MultiAddr,
@@ -318,6 +365,7 @@ pub(crate) mod project {
         Request::get(format!("v0/projects/{}", id)).body(CloudRequestWrapper::bare(cloud_route))
     }
 
+    #[cfg(feature = "cloud")]
     pub(crate) fn delete<'a>(
         space_id: &'a str,
         project_id: &'a str,
@@ -327,6 +375,7 @@ pub(crate) mod project {
             .body(CloudRequestWrapper::bare(cloud_route))
     }
 
+    #[cfg(feature = "cloud")]
     pub(crate) fn add_enroller(
         cmd: &AddEnrollerCommand,
     ) -> RequestBuilder<CloudRequestWrapper<AddEnroller>> {
@@ -338,6 +387,7 @@ pub(crate) mod project {
             .body(CloudRequestWrapper::new(b, &cmd.cloud_opts.route()))
     }
 
+    #[cfg(feature = "cloud")]
     pub(crate) fn list_enrollers(
         cmd: &ListEnrollersCommand,
     ) -> RequestBuilder<BareCloudRequestWrapper> {
@@ -345,6 +395,7 @@ pub(crate) mod project {
             .body(CloudRequestWrapper::bare(&cmd.cloud_opts.route()))
     }
 
+    #[cfg(feature = "cloud")]
     pub(crate) fn delete_enroller(
         cmd: &DeleteEnrollerCommand,
     ) -> RequestBuilder<BareCloudRequestWrapper> {
@@ -455,3 +506,35 @@ impl CloudOpts {
             .unwrap()
     }
 }
+
+/// Options to handle a request on a remote node, through its delegation service
+#[derive(Clone, Debug, Args)]
+pub struct DelegateOpts {
+    /// Route to the secure channel listener of a remote node which should handle
+    /// the request instead. The identity of the local node must be authorized by
+    /// the delegation service of the remote node (optional)
+    #[arg(long, id = "DELEGATE_ROUTE", display_order = 901)]
+    pub delegate_to: Option<MultiAddr>,
+
+    /// Expected identity of the remote node (optional)
+    #[arg(
+        long,
+        id = "DELEGATE_IDENTITY",
+        requires = "DELEGATE_ROUTE",
+        display_order = 901
+    )]
+    pub delegate_identity: Option<IdentityIdentifier>,
+}
+
+impl DelegateOpts {
+    /// Resolve node names in the route to the remote node, if any.
+    pub fn resolve(&self, lookup: &ConfigLookup) -> anyhow::Result<Option<MultiAddr>> {
+        match &self.delegate_to {
+            Some(ma) => match clean_multiaddr(ma, lookup) {
+                Some((ma, _)) => Ok(Some(ma)),
+                None => Err(anyhow::anyhow!("invalid delegation route {ma}")),
+            },
+            None => Ok(None),
+        }
+    }
+}
@@ -1,15 +1,15 @@
 //! Handle local node configuration
 
-use std::{fs::create_dir_all, net::SocketAddr, ops::Deref, path::PathBuf, sync::RwLockReadGuard};
+use std::{net::SocketAddr, ops::Deref, path::PathBuf, sync::RwLockReadGuard};
 
 use anyhow::{Context, Result};
 use slug::slugify;
 use tracing::{error, trace};
 
 use ockam::identity::IdentityIdentifier;
-pub use ockam_api::config::cli::NodeConfig;
-use ockam_api::config::lookup::ProjectLookup;
-use ockam_api::config::{cli, lookup::ConfigLookup, lookup::InternetAddress, Config};
+pub use ockam_api::config::cli::{LogSink, NodeConfig};
+use ockam_api::config::lookup::{ContactLookup, ProjectLookup};
+use ockam_api::config::{cli, lookup::ConfigLookup, lookup::InternetAddress, system, Config};
 
 use crate::util::exitcode;
 
@@ -42,6 +42,16 @@ pub enum ConfigError {
     NotFound(String),
     #[error("node with name {0} is not local")]
     NotLocal(String),
+    #[error("fleet member with name {0} already exists")]
+    FleetMemberAlreadyExists(String),
+    #[error("fleet member with name {0} does not exist")]
+    FleetMemberNotFound(String),
+    #[error("petname {0} does not exist")]
+    PetnameNotFound(String),
+    #[error("contact with name {0} already exists")]
+    ContactAlreadyExists(String),
+    #[error("contact with name {0} does not exist")]
+    ContactNotFound(String),
 }
 
 impl OckamConfig {
@@ -191,6 +201,16 @@ impl OckamConfig {
         Ok(AuthoritiesConfig::load(path))
     }
 
+    /// Get the authorities trusted by a node: its own and the ones
+    /// of the system-wide configuration.
+    pub fn trusted_authorities(&self, node: &str) -> Result<cli::AuthoritiesConfig> {
+        let mut authorities = self.authorities(node)?.snapshot();
+        if let Some(system) = system::load_authorities(&system::system_config_dir())? {
+            authorities.extend(system)
+        }
+        Ok(authorities)
+    }
+
     ///////////////////// WRITE ACCESSORS //////////////////////////////
 
     pub fn set_default_vault_path(&self, default_vault_path: Option<PathBuf>) {
@@ -210,14 +230,16 @@ impl OckamConfig {
         }
 
         // Setup logging directory and store it
-        let state_dir = inner
+        let data_dir = inner
             .directories
             .as_ref()
             .context("configuration is in an invalid state")?
-            .data_local_dir()
-            .join(slugify(&format!("node-{}", name)));
+            .data_local_dir();
+        let state_dir = data_dir.join(slugify(&format!("node-{}", name)));
 
-        create_dir_all(&state_dir).context("failed to create new node state directory")?;
+        system::create_private_dir(data_dir)
+            .and_then(|_| system::create_private_dir(&state_dir))
+            .context("failed to create new node state directory")?;
 
         // Add this node to the config lookup table
         inner.lookup.set_node(name, bind.into());
@@ -237,6 +259,12 @@ impl OckamConfig {
                 verbose,
                 state_dir: Some(state_dir),
                 pid: None,
+                log_sink: LogSink::default(),
+                relay: false,
+                health_address: None,
+                tcp_proxy: None,
+                user: None,
+                group: None,
             },
         );
         Ok(())
@@ -269,6 +297,68 @@ impl OckamConfig {
         Ok(())
     }
 
+    pub fn set_node_log_sink(&self, name: &str, sink: LogSink) -> Result<()> {
+        let mut inner = self.inner.writelock_inner();
+
+        if !inner.nodes.contains_key(name) {
+            return Err(ConfigError::NotFound(name.to_string()).into());
+        }
+
+        inner.nodes.get_mut(name).unwrap().log_sink = sink;
+        Ok(())
+    }
+
+    pub fn set_node_relay(&self, name: &str, relay: bool) -> Result<()> {
+        let mut inner = self.inner.writelock_inner();
+
+        if !inner.nodes.contains_key(name) {
+            return Err(ConfigError::NotFound(name.to_string()).into());
+        }
+
+        inner.nodes.get_mut(name).unwrap().relay = relay;
+        Ok(())
+    }
+
+    pub fn set_node_health_address(&self, name: &str, addr: Option<SocketAddr>) -> Result<()> {
+        let mut inner = self.inner.writelock_inner();
+
+        if !inner.nodes.contains_key(name) {
+            return Err(ConfigError::NotFound(name.to_string()).into());
+        }
+
+        inner.nodes.get_mut(name).unwrap().health_address = addr;
+        Ok(())
+    }
+
+    pub fn set_node_tcp_proxy(&self, name: &str, proxy: Option<String>) -> Result<()> {
+        let mut inner = self.inner.writelock_inner();
+
+        if !inner.nodes.contains_key(name) {
+            return Err(ConfigError::NotFound(name.to_string()).into());
+        }
+
+        inner.nodes.get_mut(name).unwrap().tcp_proxy = proxy;
+        Ok(())
+    }
+
+    pub fn set_node_user(
+        &self,
+        name: &str,
+        user: Option<String>,
+        group: Option<String>,
+    ) -> Result<()> {
+        let mut inner = self.inner.writelock_inner();
+
+        if !inner.nodes.contains_key(name) {
+            return Err(ConfigError::NotFound(name.to_string()).into());
+        }
+
+        let node = inner.nodes.get_mut(name).unwrap();
+        node.user = user;
+        node.group = group;
+        Ok(())
+    }
+
     pub fn set_node_alias(&self, alias: String, addr: InternetAddress) {
         let mut inner = self.inner.writelock_inner();
         inner.lookup.set_node(&alias, addr);
@@ -312,6 +402,33 @@ impl OckamConfig {
         inner.lookup.remove_projects();
     }
 
+    /// Add a named peer, pinning its identity
+    pub fn add_contact(&self, name: &str, contact: ContactLookup) -> Result<()> {
+        let mut inner = self.inner.writelock_inner();
+        if inner.lookup.get_contact(name).is_some() {
+            return Err(ConfigError::ContactAlreadyExists(name.to_string()).into());
+        }
+        inner.lookup.set_contact(name, contact);
+        Ok(())
+    }
+
+    pub fn remove_contact(&self, name: &str) -> Result<()> {
+        let mut inner = self.inner.writelock_inner();
+        match inner.lookup.remove_contact(name) {
+            Some(_) => Ok(()),
+            None => Err(ConfigError::ContactNotFound(name.to_string()).into()),
+        }
+    }
+
+    pub fn contacts(&self) -> Vec<(String, ContactLookup)> {
+        let inner = self.inner.readlock_inner();
+        inner
+            .lookup
+            .contacts()
+            .map(|(n, c)| (n.to_string(), c.clone()))
+            .collect()
+    }
+
     pub fn set_default_node(&self, name: &String) {
         let mut inner = self.inner.writelock_inner();
         inner.default = Some(name.to_string());
@@ -321,6 +438,82 @@ impl OckamConfig {
         let inner = self.inner.readlock_inner();
         inner.default.clone()
     }
+
+    /// Add a remote node to the fleet
+    pub fn add_fleet_member(&self, name: &str, member: cli::FleetMember) -> Result<()> {
+        let mut inner = self.inner.writelock_inner();
+        if inner.fleet.contains_key(name) {
+            return Err(ConfigError::FleetMemberAlreadyExists(name.to_string()).into());
+        }
+        inner.fleet.insert(name.to_string(), member);
+        Ok(())
+    }
+
+    /// Remove a remote node from the fleet
+    pub fn remove_fleet_member(&self, name: &str) -> Result<()> {
+        let mut inner = self.inner.writelock_inner();
+        match inner.fleet.remove(name) {
+            Some(_) => Ok(()),
+            None => Err(ConfigError::FleetMemberNotFound(name.to_string()).into()),
+        }
+    }
+
+    /// Get the members of the fleet having all the given tags
+    pub fn fleet_members(&self, tags: &[String]) -> Vec<(String, cli::FleetMember)> {
+        let inner = self.inner.readlock_inner();
+        inner
+            .fleet
+            .iter()
+            .filter(|(_, m)| m.has_tags(tags))
+            .map(|(n, m)| (n.clone(), m.clone()))
+            .collect()
+    }
+
+    pub fn identity_format(&self) -> cli::IdentityFormat {
+        self.inner.readlock_inner().identity_format
+    }
+
+    pub fn set_identity_format(&self, format: cli::IdentityFormat) {
+        self.inner.writelock_inner().identity_format = format
+    }
+
+    /// Name an identity, replacing the identity of the name if any
+    pub fn set_petname(&self, name: &str, id: IdentityIdentifier) {
+        let mut inner = self.inner.writelock_inner();
+        inner.petnames.insert(name.to_string(), id);
+    }
+
+    pub fn remove_petname(&self, name: &str) -> Result<()> {
+        let mut inner = self.inner.writelock_inner();
+        match inner.petnames.remove(name) {
+            Some(_) => Ok(()),
+            None => Err(ConfigError::PetnameNotFound(name.to_string()).into()),
+        }
+    }
+
+    /// Get the identity with the given petname
+    pub fn petname_identity(&self, name: &str) -> Option<IdentityIdentifier> {
+        self.inner.readlock_inner().petnames.get(name).cloned()
+    }
+
+    /// Get the petname of an identity, if it has one
+    pub fn petname_of(&self, id: &IdentityIdentifier) -> Option<String> {
+        let inner = self.inner.readlock_inner();
+        inner
+            .petnames
+            .iter()
+            .find(|(_, i)| *i == id)
+            .map(|(n, _)| n.clone())
+    }
+
+    pub fn petnames(&self) -> Vec<(String, IdentityIdentifier)> {
+        let inner = self.inner.readlock_inner();
+        inner
+            .petnames
+            .iter()
+            .map(|(n, i)| (n.clone(), i.clone()))
+            .collect()
+    }
 }
 
 #[derive(Debug)]
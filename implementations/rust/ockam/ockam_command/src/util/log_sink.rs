@@ -0,0 +1,370 @@
+//! Log sinks for nodes running in the background on servers
+//!
+//! Instead of writing to flat files in the node state directory, a
+//! node can send its logs to a syslog daemon, locally over a UNIX
+//! datagram socket or remotely over UDP, or to the systemd journal
+//! using its native protocol.
+
+use std::fmt::{self, Write as _};
+use std::net::UdpSocket;
+use std::os::unix::net::UnixDatagram;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ockam_api::config::cli::{LogSink, SyslogAddress};
+
+use crate::util::redact::Redactor;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Path of the socket on which journald receives native messages.
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// The syslog facility of all the messages: system daemons.
+const FACILITY_DAEMON: u8 = 3;
+
+/// Create the tracing layer sending the logs to the given sink.
+///
+/// The logs are tagged with `ident` so that they can be told apart
+/// from those of the other nodes, and redacted by the `redactor`.
+/// Returns `None` for file logging, which is handled by the regular
+/// formatting layer.
+pub fn layer(
+    sink: &LogSink,
+    ident: &str,
+    redactor: Option<Redactor>,
+) -> std::io::Result<Option<SinkLayer>> {
+    let (socket, format) = match sink {
+        LogSink::File => return Ok(None),
+        LogSink::Syslog(SyslogAddress::Unix(path)) => (Socket::unix(path)?, Format::Rfc3164),
+        LogSink::Syslog(SyslogAddress::Udp(addr)) => {
+            let socket = UdpSocket::bind(if addr.starts_with('[') {
+                "[::]:0"
+            } else {
+                "0.0.0.0:0"
+            })?;
+            socket.connect(addr)?;
+            (Socket::Udp(socket), Format::Rfc5424)
+        }
+        LogSink::Journald => (Socket::unix(Path::new(JOURNALD_SOCKET))?, Format::Journald),
+    };
+    Ok(Some(SinkLayer {
+        socket,
+        format,
+        ident: ident.to_string(),
+        redactor,
+        hostname: hostname(),
+        pid: std::process::id(),
+    }))
+}
+
+/// A tracing layer writing every event as a datagram.
+pub struct SinkLayer {
+    socket: Socket,
+    format: Format,
+    ident: String,
+    redactor: Option<Redactor>,
+    hostname: String,
+    pid: u32,
+}
+
+enum Socket {
+    Unix(UnixDatagram),
+    Udp(UdpSocket),
+}
+
+impl Socket {
+    fn unix(path: &Path) -> std::io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(path)?;
+        Ok(Socket::Unix(socket))
+    }
+
+    fn send(&self, buf: &[u8]) {
+        // Logging must never fail the node, a lost message is dropped.
+        let _ = match self {
+            Socket::Unix(s) => s.send(buf),
+            Socket::Udp(s) => s.send(buf),
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// BSD syslog, as understood by local daemons on `/dev/log`
+    Rfc3164,
+    /// IETF syslog, for remote collectors
+    Rfc5424,
+    /// `KEY=value` fields of the journald native protocol
+    Journald,
+}
+
+impl<S: Subscriber> Layer<S> for SinkLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        if let Some(r) = &self.redactor {
+            fields.redact(r)
+        }
+        let meta = event.metadata();
+        let msg = match self.format {
+            Format::Rfc3164 => self.rfc3164(*meta.level(), meta.target(), &fields),
+            Format::Rfc5424 => {
+                self.rfc5424(*meta.level(), meta.target(), &fields, SystemTime::now())
+            }
+            Format::Journald => self.journald(*meta.level(), meta.target(), &fields),
+        };
+        self.socket.send(&msg)
+    }
+}
+
+impl SinkLayer {
+    fn rfc3164(&self, level: Level, target: &str, fields: &Fields) -> Vec<u8> {
+        // The local daemon adds the timestamp and the hostname itself.
+        format!(
+            "<{}>{}[{}]: {target}: {}",
+            priority(level),
+            self.ident,
+            self.pid,
+            fields.line()
+        )
+        .into_bytes()
+    }
+
+    fn rfc5424(&self, level: Level, target: &str, fields: &Fields, time: SystemTime) -> Vec<u8> {
+        format!(
+            "<{}>1 {} {} {} {} - - {target}: {}",
+            priority(level),
+            rfc3339(time),
+            self.hostname,
+            self.ident,
+            self.pid,
+            fields.line()
+        )
+        .into_bytes()
+    }
+
+    fn journald(&self, level: Level, target: &str, fields: &Fields) -> Vec<u8> {
+        let mut buf = Vec::new();
+        journald_field(&mut buf, "MESSAGE", &fields.message);
+        journald_field(&mut buf, "PRIORITY", &severity(level).to_string());
+        journald_field(&mut buf, "SYSLOG_IDENTIFIER", &self.ident);
+        journald_field(&mut buf, "SYSLOG_PID", &self.pid.to_string());
+        journald_field(&mut buf, "TARGET", target);
+        for (k, v) in &fields.values {
+            if let Some(k) = journald_key(k) {
+                journald_field(&mut buf, &k, v)
+            }
+        }
+        buf
+    }
+}
+
+/// Visitor collecting the message and the other fields of an event.
+#[derive(Default)]
+struct Fields {
+    message: String,
+    values: Vec<(&'static str, String)>,
+}
+
+impl Fields {
+    fn redact(&mut self, r: &Redactor) {
+        self.message = r.redact(&self.message).into_owned();
+        self.values = std::mem::take(&mut self.values)
+            .into_iter()
+            .filter_map(|(k, v)| Some((k, r.redact_field(k, &v)?.into_owned())))
+            .collect();
+    }
+
+    /// The message followed by the other fields as `key=value` pairs.
+    fn line(&self) -> String {
+        let mut line = self.message.clone();
+        for (k, v) in &self.values {
+            let _ = write!(line, " {k}={v}");
+        }
+        line
+    }
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string()
+        } else {
+            self.values.push((field.name(), value.to_string()))
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}")
+        } else {
+            self.values.push((field.name(), format!("{value:?}")))
+        }
+    }
+}
+
+/// Syslog severity of a tracing level.
+fn severity(level: Level) -> u8 {
+    match level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        Level::DEBUG | Level::TRACE => 7,
+    }
+}
+
+fn priority(level: Level) -> u8 {
+    FACILITY_DAEMON * 8 + severity(level)
+}
+
+/// Append a field in the journald native format.
+///
+/// Values spanning several lines use the binary form: the key, a
+/// newline, the little endian 64 bits length and then the value.
+fn journald_field(buf: &mut Vec<u8>, key: &str, value: &str) {
+    buf.extend_from_slice(key.as_bytes());
+    if value.contains('\n') {
+        buf.push(b'\n');
+        buf.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        buf.push(b'=');
+    }
+    buf.extend_from_slice(value.as_bytes());
+    buf.push(b'\n')
+}
+
+/// Journald only accepts keys made of uppercase letters, digits and
+/// underscores which do not start with an underscore.
+fn journald_key(name: &str) -> Option<String> {
+    let key: String = name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' => c.to_ascii_uppercase(),
+            'A'..='Z' | '0'..='9' => c,
+            _ => '_',
+        })
+        .collect();
+    let key = key.trim_start_matches('_');
+    if key.is_empty() || key.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(key.to_string())
+}
+
+fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    nix::unistd::gethostname(&mut buf)
+        .ok()
+        .and_then(|h| h.to_str().ok().map(|h| h.to_string()))
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Format a time as an RFC 3339 UTC timestamp with microseconds.
+fn rfc3339(time: SystemTime) -> String {
+    let d = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = d.as_secs();
+    let (days, rem) = (secs / 86400, secs % 86400);
+    // Civil date from the number of days since 1970-01-01.
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:06}Z",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        d.subsec_micros()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn layer(format: Format) -> SinkLayer {
+        let (socket, _) = UnixDatagram::pair().unwrap();
+        SinkLayer {
+            socket: Socket::Unix(socket),
+            format,
+            ident: "ockam-n1".to_string(),
+            redactor: None,
+            hostname: "host".to_string(),
+            pid: 42,
+        }
+    }
+
+    fn fields() -> Fields {
+        Fields {
+            message: "forwarder created".to_string(),
+            values: vec![("alias", "r1".to_string())],
+        }
+    }
+
+    #[test]
+    fn syslog_messages() {
+        let l = layer(Format::Rfc3164);
+        let msg = l.rfc3164(Level::INFO, "ockam_api", &fields());
+        assert_eq!(
+            msg,
+            b"<30>ockam-n1[42]: ockam_api: forwarder created alias=r1"
+        );
+
+        let l = layer(Format::Rfc5424);
+        let time = UNIX_EPOCH + Duration::from_micros(1_665_830_096_123_456);
+        let msg = l.rfc5424(Level::ERROR, "ockam_api", &fields(), time);
+        assert_eq!(
+            String::from_utf8(msg).unwrap(),
+            "<27>1 2022-10-15T10:34:56.123456Z host ockam-n1 42 - - ockam_api: forwarder created alias=r1"
+        );
+    }
+
+    #[test]
+    fn journald_messages() {
+        let l = layer(Format::Journald);
+        let mut f = fields();
+        f.values.push(("_private", "x".to_string()));
+        f.values.push(("peer.addr", "a\nb".to_string()));
+        let msg = l.journald(Level::WARN, "ockam_api", &f);
+        let mut expected = b"MESSAGE=forwarder created\n\
+            PRIORITY=4\n\
+            SYSLOG_IDENTIFIER=ockam-n1\n\
+            SYSLOG_PID=42\n\
+            TARGET=ockam_api\n\
+            ALIAS=r1\n\
+            PRIVATE=x\n\
+            PEER_ADDR\n"
+            .to_vec();
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.extend_from_slice(b"a\nb\n");
+        assert_eq!(msg, expected);
+    }
+
+    #[test]
+    fn parse_log_sinks() {
+        for s in [
+            "file",
+            "journald",
+            "syslog:/dev/log",
+            "syslog:127.0.0.1:514",
+            "syslog:[::1]:514",
+        ] {
+            assert_eq!(s.parse::<LogSink>().unwrap().to_string(), s)
+        }
+        assert_eq!(
+            "syslog".parse::<LogSink>().unwrap(),
+            LogSink::Syslog(SyslogAddress::default())
+        );
+        assert!("syslog:localhost".parse::<LogSink>().is_err());
+        assert!("stdout".parse::<LogSink>().is_err());
+    }
+}
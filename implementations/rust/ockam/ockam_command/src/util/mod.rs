@@ -1,8 +1,9 @@
 use core::time::Duration;
 use std::{
-    env,
+    env, io,
     net::{SocketAddr, TcpListener},
-    path::Path,
+    path::{Path, PathBuf},
+    time::Instant,
 };
 
 use anyhow::{anyhow, Context as _, Result};
@@ -15,16 +16,22 @@ use tracing_subscriber::{filter::LevelFilter, fmt, EnvFilter};
 pub use addon::AddonCommand;
 pub use config::*;
 use ockam::{route, Address, Context, NodeBuilder, Route, TcpTransport, TCP};
+use ockam_api::config::cli::LogSink;
+use ockam_api::nodes::models::progress::ProgressEvent;
 use ockam_api::nodes::NODEMANAGER_ADDR;
 use ockam_core::api::{RequestBuilder, Response, Status};
 use ockam_multiaddr::MultiAddr;
 
 use crate::node::util::start_embedded_node;
 use crate::util::output::Output;
+use crate::util::redact::{RedactingMakeWriter, Redactor};
 use crate::{CommandGlobalOpts, OutputFormat};
 
 pub mod api;
 pub mod exitcode;
+pub mod log_sink;
+pub mod qr;
+pub mod redact;
 pub mod startup;
 
 mod addon;
@@ -103,6 +110,7 @@ pub struct Rpc<'a> {
 
 impl<'a> Rpc<'a> {
     /// Creates a new RPC to send a request to an embedded node.
+    #[cfg_attr(not(feature = "cloud"), allow(unused))]
     pub async fn embedded(ctx: &'a Context, opts: &'a CommandGlobalOpts) -> Result<Rpc<'a>> {
         let node_name = start_embedded_node(ctx, &opts.config).await?;
         Ok(Rpc {
@@ -136,38 +144,102 @@ impl<'a> Rpc<'a> {
         &self.node_name
     }
 
+    /// Send a request and wait for its response.
+    ///
+    /// If a global `--timeout` was given, the request is bounded by it.
     pub async fn request<T>(&mut self, req: RequestBuilder<'_, T>) -> Result<()>
     where
         T: Encode<()>,
     {
-        let route = self.route_impl(self.ctx).await?;
-        self.buf = self
-            .ctx
-            .send_and_receive(route.clone(), req.to_vec()?)
-            .await
-            .context("Failed to receive response from node")?;
-        Ok(())
+        let timeout = self.opts.global_args.timeout.map(Duration::from_secs);
+        self.request_impl(req, timeout).await
     }
 
+    /// Send a request and wait at most `timeout` for its response.
+    ///
+    /// The timeout is also sent along with the request, so that the node
+    /// can bound the work it does on our behalf.
     #[allow(unused)]
     pub async fn request_with_timeout<T>(
         &mut self,
         req: RequestBuilder<'_, T>,
         timeout: Duration,
     ) -> Result<()>
+    where
+        T: Encode<()>,
+    {
+        self.request_impl(req, Some(timeout)).await
+    }
+
+    /// Send a request and wait for its final response, printing any progress
+    /// events the node reports in the meantime.
+    async fn request_impl<T>(
+        &mut self,
+        req: RequestBuilder<'_, T>,
+        timeout: Option<Duration>,
+    ) -> Result<()>
     where
         T: Encode<()>,
     {
         let mut ctx = self.ctx.new_detached(Address::random_local()).await?;
         let route = self.route_impl(&ctx).await?;
-        ctx.send(route.clone(), req.to_vec()?).await?;
-        self.buf = ctx
-            .receive_duration_timeout::<Vec<u8>>(timeout)
-            .await
-            .context("Failed to receive response from node")?
-            .take()
-            .body();
-        Ok(())
+        let req = match timeout {
+            Some(t) => req.timeout(t),
+            None => req,
+        };
+        ctx.send(route.clone(), req.progress(true).to_vec()?)
+            .await?;
+        // Progress events don't extend the time the response may take
+        let deadline = timeout.map(|t| Instant::now() + t);
+        loop {
+            let res = match deadline {
+                Some(d) => {
+                    let remaining = d.saturating_duration_since(Instant::now());
+                    ctx.receive_duration_timeout::<Vec<u8>>(remaining).await
+                }
+                None => ctx.receive::<Vec<u8>>().await,
+            };
+            let buf = res
+                .context("Failed to receive response from node")?
+                .take()
+                .body();
+            if !self.print_progress(&buf) {
+                self.buf = buf;
+                return Ok(());
+            }
+        }
+    }
+
+    /// Print the progress event contained in `buf`, if it is an interim response.
+    ///
+    /// Events go to stderr, so that the standard output of a command only
+    /// holds its result, e.g. a single JSON document with `--output json`.
+    fn print_progress(&self, buf: &[u8]) -> bool {
+        let mut dec = Decoder::new(buf);
+        match dec.decode::<Response>() {
+            Ok(hdr) if hdr.status() == Some(Status::Processing) => {}
+            _ => return false,
+        }
+        let event: ProgressEvent = match dec.decode() {
+            Ok(e) => e,
+            Err(e) => {
+                debug!(%e, "Failed to decode progress event");
+                return true;
+            }
+        };
+        match self.opts.global_args.output_format {
+            OutputFormat::Plain => {
+                if let Ok(o) = event.output() {
+                    eprintln!("{o}")
+                }
+            }
+            OutputFormat::Json => {
+                if let Ok(o) = serde_json::to_string(&event) {
+                    eprintln!("{o}")
+                }
+            }
+        }
+        true
     }
 
     async fn route_impl(&mut self, ctx: &Context) -> Result<Route> {
@@ -214,6 +286,7 @@ impl<'a> Rpc<'a> {
         Ok(())
     }
 
+    #[cfg_attr(not(feature = "cloud"), allow(unused))]
     pub fn check_response(&self) -> Result<(Response, Decoder)> {
         let mut dec = Decoder::new(&self.buf);
         let hdr = dec
@@ -406,7 +479,11 @@ pub fn find_available_port() -> Result<u16> {
     Ok(address.port())
 }
 
-pub fn setup_logging(verbose: u8, no_color: bool) {
+/// Set up the tracing subscriber.
+///
+/// The logs are printed on the standard output unless a `(sink, ident)`
+/// pair is given, e.g. by a background node logging to syslog.
+pub fn setup_logging(verbose: u8, no_color: bool, sink: Option<(&LogSink, &str)>) {
     let ockam_crates = [
         "ockam",
         "ockam_node",
@@ -437,11 +514,34 @@ pub fn setup_logging(verbose: u8, no_color: bool) {
             .with_default_directive(LevelFilter::TRACE.into())
             .parse_lossy(ockam_crates.map(|c| format!("{c}=trace")).join(",")),
     };
-    let fmt = fmt::Layer::default().with_ansi(!no_color);
+    let redactor = match Redactor::from_env() {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("{e}, redacting all sensitive values");
+            Redactor::parse("all").ok()
+        }
+    };
+    let sink = match sink.map(|(sink, ident)| log_sink::layer(sink, ident, redactor.clone())) {
+        Some(Ok(layer)) => layer,
+        Some(Err(e)) => {
+            eprintln!("Failed to open the log sink, logging to the standard output: {e}");
+            None
+        }
+        None => None,
+    };
+    let fmt = match sink {
+        Some(_) => None,
+        None => Some(
+            fmt::Layer::default()
+                .with_ansi(!no_color)
+                .with_writer(RedactingMakeWriter::new(io::stdout, redactor)),
+        ),
+    };
     let result = tracing_subscriber::registry()
         .with(filter)
         .with(tracing_error::ErrorLayer::default())
         .with(fmt)
+        .with(sink)
         .try_init();
     if result.is_err() {
         eprintln!("Failed to initialise tracing logging.");
@@ -453,6 +553,13 @@ pub fn print_path(p: &Path) -> String {
     p.to_str().unwrap_or("<unprintable>").to_string()
 }
 
+/// Make a path absolute, for paths given to a node running in another directory.
+pub fn absolute_path(p: &Path) -> PathBuf {
+    env::current_dir()
+        .map(|dir| dir.join(p))
+        .unwrap_or_else(|_| p.to_path_buf())
+}
+
 pub fn get_final_element(input_path: &str) -> &str {
     //  Get Node name from Node Path
     //  if Input path has "/", we split the path and return the final element
@@ -472,6 +579,35 @@ pub fn get_final_element(input_path: &str) -> &str {
     };
 }
 
+/// Parse a number of seconds with an optional `s`, `m`, `h` or `d` unit.
+pub fn parse_interval(s: &str) -> std::result::Result<Duration, String> {
+    let (n, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let n: u64 = n.parse().map_err(|_| format!("invalid interval {s}"))?;
+    let secs = match unit {
+        "s" => n,
+        "m" => n * 60,
+        "h" => n * 60 * 60,
+        "d" => n * 60 * 60 * 24,
+        _ => {
+            return Err(format!(
+                "invalid interval unit {unit}, expected s, m, h or d"
+            ))
+        }
+    };
+    if secs == 0 {
+        return Err("interval must be at least one second".to_string());
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+/// A round-trip time given in microseconds, in milliseconds
+pub fn fmt_rtt_micros(micros: u64) -> String {
+    format!("{:.3}ms", micros as f64 / 1000.0)
+}
+
 pub fn comma_separated<T: AsRef<str>>(data: &[T]) -> String {
     use itertools::Itertools;
 
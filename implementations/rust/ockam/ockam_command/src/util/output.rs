@@ -5,12 +5,20 @@ use ockam::identity::credential::Credential;
 use ockam_api::cloud::project::{Enroller, Project};
 
 use crate::project::ProjectInfo;
-use crate::util::comma_separated;
+use crate::util::{comma_separated, fmt_rtt_micros};
 use colorful::Colorful;
 use ockam_api::cloud::space::Space;
+use ockam_api::nodes::models::events::{EventList, EventRecord, WebhookInfo};
+use ockam_api::nodes::models::forwarder::{ForwarderList, ForwarderStatus};
+use ockam_api::nodes::models::jobs::{JobList, JobStatus};
+use ockam_api::nodes::models::medic::MedicStatus;
+use ockam_api::nodes::models::monitors::{MonitorKind, MonitorList, MonitorStatus};
+use ockam_api::nodes::models::policy::PolicyDecision;
+use ockam_api::nodes::models::progress::{PhaseState, ProgressEvent};
 use ockam_api::nodes::models::secure_channel::{
     CreateSecureChannelResponse, ShowSecureChannelResponse,
 };
+use ockam_api::nodes::models::stats::WorkerStatsList;
 use ockam_api::route_to_multiaddr;
 use ockam_core::route;
 
@@ -182,7 +190,7 @@ impl Output for ShowSecureChannelResponse<'_> {
         let s = match &self.channel {
             Some(addr) => {
                 format!(
-                    "\n  Secure Channel:\n{} {}\n{} {}\n{} {}",
+                    "\n  Secure Channel:\n{} {}\n{} {}\n{} {}\n{} {}\n{} {}\n{} {}\n{} {}\n{} {}",
                     "  •         At: ".light_magenta(),
                     route_to_multiaddr(&route![addr.to_string()])
                         .context("Invalid Secure Channel Address")?
@@ -197,7 +205,38 @@ impl Output for ShowSecureChannelResponse<'_> {
                         .iter()
                         .map(|id| id.light_yellow().to_string())
                         .collect::<Vec<String>>()
-                        .join("\n\t")
+                        .join("\n\t"),
+                    "  •       Peer: ".light_magenta(),
+                    self.their_identity_id
+                        .as_deref()
+                        .unwrap_or("unknown")
+                        .light_yellow(),
+                    "  •       Hops: ".light_magenta(),
+                    match self.hops.as_deref() {
+                        Some([]) | None => "none".light_yellow().to_string(),
+                        Some(hops) => hops
+                            .iter()
+                            .map(|id| id.light_yellow().to_string())
+                            .collect::<Vec<String>>()
+                            .join(" -> "),
+                    },
+                    "  • Credential: ".light_magenta(),
+                    self.credential_exchange_mode
+                        .map(|m| format!("{:?}", m))
+                        .unwrap_or_else(|| "unknown".to_string())
+                        .light_yellow(),
+                    "  •    Traffic: ".light_magenta(),
+                    format!(
+                        "{} sent, {} received",
+                        self.sent.unwrap_or_default(),
+                        self.received.unwrap_or_default()
+                    )
+                    .light_yellow(),
+                    "  •        Rtt: ".light_magenta(),
+                    self.rtt_micros
+                        .map(fmt_rtt_micros)
+                        .unwrap_or_else(|| "not measured".to_string())
+                        .light_yellow()
                 )
             }
             None => format!("{}", "Channel not found".red()),
@@ -248,3 +287,254 @@ impl Output for Credential<'_> {
         Ok(self.to_string())
     }
 }
+
+impl Output for ProgressEvent<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        let o = match self.state() {
+            PhaseState::Started => format!("{} {}...", ">".light_green(), self.phase()),
+            PhaseState::Completed => format!("{} {}", "✔".light_green(), self.phase()),
+        };
+        Ok(o)
+    }
+}
+
+impl Output for JobStatus<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        let mut w = String::new();
+        write!(w, "Job {}", self.id)?;
+        write!(w, "\n  Action: {}", self.kind)?;
+        write!(w, "\n  Every: {}s", self.interval)?;
+        write!(w, "\n  Runs: {} ({} failed)", self.runs, self.failures)?;
+        if let Some(last_run) = self.last_run {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_secs();
+            write!(w, "\n  Last Run: {}s ago", now.saturating_sub(last_run))?;
+        }
+        if let Some(result) = &self.last_result {
+            write!(w, "\n  Last Result: {}", result)?;
+        }
+        Ok(w)
+    }
+}
+
+impl Output for JobList<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        if self.list.is_empty() {
+            return Ok("No jobs found".to_string());
+        }
+        let jobs = self
+            .list
+            .iter()
+            .map(|j| j.output())
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(jobs.join("\n"))
+    }
+}
+
+impl Output for MonitorStatus<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        let mut w = String::new();
+        write!(w, "Monitor {}", self.id)?;
+        match self.kind {
+            MonitorKind::Rtt => write!(
+                w,
+                "\n  Objective: rtt below {}ms, alert after {}s",
+                self.threshold, self.window
+            )?,
+            MonitorKind::Recoveries => write!(
+                w,
+                "\n  Objective: at most {} recoveries per {}s",
+                self.threshold, self.window
+            )?,
+        }
+        if let Some(url) = &self.hook.webhook {
+            write!(w, "\n  Webhook: {}", url)?;
+        }
+        if let Some(cmd) = &self.hook.command {
+            write!(w, "\n  Command: {}", cmd)?;
+        }
+        write!(w, "\n  Alerts: {}", self.alerts)?;
+        if !self.firing.is_empty() {
+            write!(w, "\n  Firing: {}", comma_separated(&self.firing))?;
+        }
+        if let Some(err) = &self.last_error {
+            write!(w, "\n  Last Error: {}", err)?;
+        }
+        Ok(w)
+    }
+}
+
+impl Output for MonitorList<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        if self.list.is_empty() {
+            return Ok("No monitors found".to_string());
+        }
+        let monitors = self
+            .list
+            .iter()
+            .map(|m| m.output())
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(monitors.join("\n"))
+    }
+}
+
+impl Output for ForwarderList<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        if self.list.is_empty() {
+            return Ok("No forwarders found".to_string());
+        }
+        let mut w = String::new();
+        for (i, f) in self.list.iter().enumerate() {
+            if i > 0 {
+                writeln!(w)?;
+            }
+            write!(w, "Forwarder {}", f.alias().unwrap_or("-"))?;
+            write!(w, "\n  Forwarding Route: {}", f.forwarding_route())?;
+            write!(w, "\n  Remote Address: /service/{}", f.remote_address())?;
+            if let Some(identity) = f.remote_identity() {
+                write!(w, "\n  Remote Identity: {}", identity)?;
+            }
+            write!(w, "\n  Session: {}", f.has_session())?;
+        }
+        Ok(w)
+    }
+}
+
+impl Output for ForwarderStatus<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        let mut w = String::new();
+        write!(w, "Forwarder {}", self.alias.as_deref().unwrap_or("-"))?;
+        write!(w, "\n  Forwarding Route: {}", self.forwarding_route)?;
+        write!(w, "\n  Remote Address: /service/{}", self.remote_address)?;
+        if let Some(channel) = &self.secure_channel {
+            write!(w, "\n  Secure Channel: {}", channel)?;
+        }
+        if let Some(identity) = &self.remote_identity {
+            write!(w, "\n  Remote Identity: {}", identity)?;
+        }
+        write!(w, "\n  Session: {}", self.session)?;
+        if self.session {
+            match self.last_pong {
+                Some(t) => {
+                    let now = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)?
+                        .as_secs();
+                    write!(w, "\n  Last Pong: {}s ago", now.saturating_sub(t))?
+                }
+                None => write!(w, "\n  Last Pong: never")?,
+            }
+            write!(w, "\n  Recoveries: {}", self.recoveries)?;
+            write!(w, "\n  Degraded: {}", self.degraded)?;
+        }
+        if let Some(t) = self.expires_in {
+            write!(w, "\n  Expires In: {}s", t)?;
+        }
+        Ok(w)
+    }
+}
+
+impl Output for WebhookInfo<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        let mut w = String::new();
+        write!(w, "Webhook:")?;
+        write!(w, "\n  Url: {}", self.url)?;
+        write!(w, "\n  Signed: {}", self.signed)?;
+        Ok(w)
+    }
+}
+
+impl Output for PolicyDecision<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        let mut w = String::new();
+        let decision = if self.allowed { "allowed" } else { "denied" };
+        write!(w, "Decision: {}", decision)?;
+        write!(w, "\n  Matched: {}", self.matched)?;
+        Ok(w)
+    }
+}
+
+impl Output for EventRecord<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        Ok(format!(
+            "#{} {} {}: {}",
+            self.seq, self.time, self.name, self.data
+        ))
+    }
+}
+
+impl Output for EventList<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        if self.events.is_empty() {
+            return Ok("No events".to_string());
+        }
+        let events = self
+            .events
+            .iter()
+            .map(|e| e.output())
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(events.join("\n"))
+    }
+}
+
+impl Output for WorkerStatsList<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        if !self.enabled {
+            return Ok("Worker accounting is disabled".to_string());
+        }
+        let mut w = String::new();
+        write!(w, "Workers:")?;
+        for s in &self.workers {
+            write!(w, "\n  Worker {}", s.address)?;
+            write!(w, "\n    Messages: {}", s.messages)?;
+            let millis = s.handler_micros as f64 / 1000.0;
+            write!(w, "\n    Handler Time: {:.3}ms", millis)?;
+            write!(w, "\n    Allocated: {} bytes", s.allocated_bytes)?;
+        }
+        Ok(w)
+    }
+}
+
+impl Output for MedicStatus<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        let mut w = String::new();
+        write!(w, "Medic:")?;
+        write!(w, "\n  Paused: {}", self.paused)?;
+        write!(w, "\n  Interval: {}ms", self.interval)?;
+        write!(w, "\n  Restarts: {}", self.restarts)?;
+        if self.sessions.is_empty() {
+            write!(w, "\n  Sessions: none")?;
+        }
+        for s in &self.sessions {
+            write!(w, "\n  Session {}", s.key)?;
+            if let Some(d) = &s.description {
+                write!(w, "\n    Description: {}", d)?;
+            }
+            write!(w, "\n    Address: {}", s.address)?;
+            write!(w, "\n    Status: {}", if s.up { "up" } else { "down" })?;
+            if let Some(rtt) = s.rtt {
+                write!(w, "\n    Rtt: {}ms", rtt)?;
+            }
+            if let Some(rtt) = s.channel_rtt_micros {
+                write!(w, "\n    Secure Channel Rtt: {}", fmt_rtt_micros(rtt))?;
+            }
+            write!(w, "\n    Recoveries: {}", s.recoveries)?;
+            if let Some(max_age) = s.max_age {
+                write!(w, "\n    Max Age: {}s", max_age)?;
+            }
+            if !s.last_recovery.is_empty() {
+                write!(w, "\n    Last Recovery:")?;
+            }
+            for r in &s.last_recovery {
+                match &r.error {
+                    Some(e) => write!(w, "\n      {}: failed, {}", r.step, e)?,
+                    None => write!(w, "\n      {}: ok", r.step)?,
+                }
+                if r.failures > 0 {
+                    write!(w, " ({} failures)", r.failures)?;
+                }
+            }
+        }
+        Ok(w)
+    }
+}
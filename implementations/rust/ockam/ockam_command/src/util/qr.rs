@@ -0,0 +1,78 @@
+//! Encode short texts as QR codes and render them in a terminal.
+
+use qrcode::{Color, EcLevel};
+
+/// Modules of a QR code, with error correction level M.
+pub struct QrCode {
+    size: usize,
+    modules: Vec<Color>,
+}
+
+impl QrCode {
+    /// Encode `text` in the smallest version it fits in.
+    pub fn encode(text: &str) -> anyhow::Result<Self> {
+        let qr = qrcode::QrCode::with_error_correction_level(text, EcLevel::M)
+            .map_err(|e| anyhow::anyhow!("failed to encode a QR code: {e}"))?;
+        Ok(Self {
+            size: qr.width(),
+            modules: qr.into_colors(),
+        })
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x] == Color::Dark
+    }
+
+    /// Render with half blocks, two rows of modules per line, dark on
+    /// light whatever the colors of the terminal.
+    pub fn render(&self) -> String {
+        const QUIET_ZONE: usize = 4;
+        let dark = |x: usize, y: usize| {
+            x >= QUIET_ZONE
+                && y >= QUIET_ZONE
+                && x - QUIET_ZONE < self.size
+                && y - QUIET_ZONE < self.size
+                && self.is_dark(x - QUIET_ZONE, y - QUIET_ZONE)
+        };
+        let width = self.size + 2 * QUIET_ZONE;
+        let mut out = String::new();
+        for y in (0..width).step_by(2) {
+            out.push_str("\x1b[30;47m");
+            for x in 0..width {
+                out.push(match (dark(x, y), dark(x, y + 1)) {
+                    (false, false) => ' ',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (true, true) => '█',
+                });
+            }
+            out.push_str("\x1b[0m\n");
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smallest_version_is_used() {
+        assert_eq!(QrCode::encode("ockam").unwrap().size, 21);
+        let code = "a".repeat(150);
+        assert_eq!(QrCode::encode(&code).unwrap().size, 8 * 4 + 17);
+        assert!(QrCode::encode(&"a".repeat(3000)).is_err());
+    }
+
+    #[test]
+    fn finder_patterns_are_drawn() {
+        let qr = QrCode::encode("ockam").unwrap();
+        let last = qr.size - 1;
+        for (x, y) in [(0, 0), (last, 0), (0, last)] {
+            assert!(qr.is_dark(x, y));
+        }
+        assert!(!qr.is_dark(7, 7));
+        assert!(qr.is_dark(3, 3));
+        assert!(qr.render().lines().count() == (qr.size + 8 + 1) / 2);
+    }
+}
@@ -0,0 +1,291 @@
+//! Redaction of sensitive values before logs are emitted
+//!
+//! Identities, routes and payload sizes are logged at debug level
+//! and describe the topology of a deployment.  The policies set in
+//! the `OCKAM_LOG_REDACT` environment variable, a comma separated
+//! list, are applied to every log line:
+//!
+//! - `hash-identities`: replace identity identifiers by a short hash,
+//!   so that log lines about the same identity can still be matched
+//! - `truncate-routes`: only keep the last hop of routes and
+//!   multiaddrs, i.e. the worker or service they lead to
+//! - `drop-payload-sizes`: remove the sizes of the messages
+//! - `all`: all of the above
+
+use std::borrow::Cow;
+use std::env;
+use std::io;
+
+use regex::{Captures, Regex};
+use sha2::{Digest, Sha256};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Environment variable holding the redaction policies.
+pub const OCKAM_LOG_REDACT: &str = "OCKAM_LOG_REDACT";
+
+/// Names of the fields holding payload sizes.
+const SIZE_FIELDS: &[&str] = &["len", "length", "size", "bytes", "payload_size"];
+
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    hash_identities: bool,
+    truncate_routes: bool,
+    drop_payload_sizes: bool,
+    identity: Regex,
+    route: Regex,
+    multiaddr: Regex,
+    size: Regex,
+    ansi: Regex,
+}
+
+impl Redactor {
+    /// Read the redaction policies from the environment, if any.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        match env::var(OCKAM_LOG_REDACT) {
+            Ok(s) if !s.trim().is_empty() => Self::parse(&s).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Parse a comma separated list of redaction policies.
+    pub fn parse(policies: &str) -> Result<Self, String> {
+        let mut r = Redactor {
+            hash_identities: false,
+            truncate_routes: false,
+            drop_payload_sizes: false,
+            identity: Regex::new(r"\bP[0-9a-f]{64}\b").unwrap(),
+            route: Regex::new(r"(?:\d+#[^\s,=\])]+ => )+(\d+#[^\s,\])]+)").unwrap(),
+            multiaddr: Regex::new(
+                r"(?:/(?:ip4|ip6|dnsaddr|tcp|node|project|service|secure|worker)/[^/\s,\])]+){2,}",
+            )
+            .unwrap(),
+            size: Regex::new(r"\b(?:(?:len|length|size|bytes|payload_size)=\d+|\d+ bytes)\b")
+                .unwrap(),
+            ansi: Regex::new(r"\x1b\[[0-9;]*[A-Za-z]").unwrap(),
+        };
+        for p in policies.split(',').map(str::trim) {
+            match p {
+                "hash-identities" => r.hash_identities = true,
+                "truncate-routes" => r.truncate_routes = true,
+                "drop-payload-sizes" => r.drop_payload_sizes = true,
+                "all" => {
+                    r.hash_identities = true;
+                    r.truncate_routes = true;
+                    r.drop_payload_sizes = true
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid redaction policy '{p}', expected one of: hash-identities, \
+                         truncate-routes, drop-payload-sizes, all"
+                    ))
+                }
+            }
+        }
+        Ok(r)
+    }
+
+    /// Redact a log line.
+    ///
+    /// Lines colored with ANSI escape codes, which split field names from
+    /// their values, are matched without them. They keep their colors
+    /// unless something is redacted.
+    pub fn redact<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        if !line.contains('\x1b') {
+            return self.redact_plain(line);
+        }
+        let plain = self.ansi.replace_all(line, "");
+        match self.redact_plain(&plain) {
+            Cow::Borrowed(_) => Cow::Borrowed(line),
+            Cow::Owned(redacted) => Cow::Owned(redacted),
+        }
+    }
+
+    fn redact_plain<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let mut line = Cow::Borrowed(line);
+        if self.hash_identities {
+            line = replace(line, &self.identity, |c| hash_identity(&c[0]));
+        }
+        if self.truncate_routes {
+            line = replace(line, &self.route, |c| format!("… => {}", &c[1]));
+            line = replace(line, &self.multiaddr, |c| truncate_multiaddr(&c[0]));
+        }
+        if self.drop_payload_sizes {
+            line = replace(line, &self.size, |c| {
+                if c[0].ends_with(" bytes") {
+                    "? bytes".to_string()
+                } else {
+                    c[0].split('=').next().unwrap_or_default().to_string() + "=?"
+                }
+            });
+        }
+        line
+    }
+
+    /// Redact the value of a structured field.
+    ///
+    /// Returns `None` if the field must not be emitted at all.
+    pub fn redact_field<'a>(&self, name: &str, value: &'a str) -> Option<Cow<'a, str>> {
+        if self.drop_payload_sizes && SIZE_FIELDS.contains(&name) {
+            return None;
+        }
+        Some(self.redact(value))
+    }
+}
+
+fn replace<'a, F>(line: Cow<'a, str>, re: &Regex, f: F) -> Cow<'a, str>
+where
+    F: Fn(&Captures) -> String,
+{
+    if !re.is_match(&line) {
+        return line;
+    }
+    Cow::Owned(re.replace_all(&line, |c: &Captures| f(c)).into_owned())
+}
+
+/// Replace an identity identifier by the first bytes of its hash.
+fn hash_identity(id: &str) -> String {
+    let digest = Sha256::digest(id.as_bytes());
+    format!("P~{}", hex::encode(&digest[..8]))
+}
+
+/// Only keep the last protocol of a multiaddr.
+fn truncate_multiaddr(addr: &str) -> String {
+    let parts: Vec<&str> = addr.split('/').collect();
+    format!("…/{}", parts[parts.len() - 2..].join("/"))
+}
+
+/// A [`MakeWriter`] redacting the lines written by the formatting layer.
+pub struct RedactingMakeWriter<M> {
+    inner: M,
+    redactor: Option<Redactor>,
+}
+
+impl<M> RedactingMakeWriter<M> {
+    pub fn new(inner: M, redactor: Option<Redactor>) -> Self {
+        Self { inner, redactor }
+    }
+}
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingMakeWriter<M> {
+    type Writer = RedactingWriter<'a, M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter {
+            inner: self.inner.make_writer(),
+            redactor: self.redactor.as_ref(),
+        }
+    }
+}
+
+pub struct RedactingWriter<'a, W> {
+    inner: W,
+    redactor: Option<&'a Redactor>,
+}
+
+impl<'a, W: io::Write> io::Write for RedactingWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The formatting layer writes a whole event at once.
+        match (self.redactor, std::str::from_utf8(buf)) {
+            (Some(r), Ok(s)) => {
+                self.inner.write_all(r.redact(s).as_bytes())?;
+                Ok(buf.len())
+            }
+            _ => self.inner.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "P66d23a5223ff41834d756e55c0ed94dc109603f23e9b0effc26d0597deeb4ef8";
+
+    #[test]
+    fn redact_lines() {
+        let line = format!(
+            "secure channel to {ID} at 0#api => 1#10.0.0.1:4000 => 0#forward_to_n1 \
+             via /ip4/10.0.0.1/tcp/4000/service/api, received message header for 42 bytes, len=42"
+        );
+
+        let r = Redactor::parse("hash-identities").unwrap();
+        let hashed = r.redact(&line);
+        assert!(!hashed.contains(ID));
+        assert!(hashed.starts_with("secure channel to P~"));
+        // The hash is stable
+        assert_eq!(r.redact(ID), hashed[18..36]);
+
+        let r = Redactor::parse("truncate-routes, drop-payload-sizes").unwrap();
+        assert_eq!(
+            r.redact(&line),
+            format!(
+                "secure channel to {ID} at … => 0#forward_to_n1 via …/service/api, \
+                 received message header for ? bytes, len=?"
+            )
+        );
+
+        let r = Redactor::parse("all").unwrap();
+        assert!(!r.redact(&line).contains("10.0.0.1"));
+        assert!(!r.redact(&line).contains(ID));
+
+        // Nothing to redact
+        assert!(matches!(r.redact("node started"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn redact_fields() {
+        let r = Redactor::parse("drop-payload-sizes").unwrap();
+        assert!(r.redact_field("len", "42").is_none());
+        assert_eq!(r.redact_field("alias", "r1").unwrap(), "r1");
+        assert!(Redactor::parse("hash-everything").is_err());
+    }
+
+    #[derive(Clone, Default)]
+    struct Buffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn redact_colored_lines() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_ansi(true)
+            .with_writer(RedactingMakeWriter::new(
+                move || writer.clone(),
+                Redactor::parse("all").ok(),
+            ))
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(
+                len = 42,
+                identity = ID,
+                "sent to 0#api => 1#10.0.0.1:4000 => 0#echo"
+            );
+            tracing::info!(alias = "r1", "node started");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(!lines[0].contains(ID));
+        assert!(!lines[0].contains("42"));
+        assert!(!lines[0].contains("10.0.0.1"));
+        assert!(lines[0].contains("len=?"));
+        // Lines without anything to redact keep their colors
+        assert!(lines[1].contains('\x1b'));
+    }
+}
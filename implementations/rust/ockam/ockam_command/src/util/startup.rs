@@ -7,8 +7,10 @@ use crate::util::OckamConfig;
 use anyhow::Context;
 use nix::sys::signal::{self, Signal};
 use nix::unistd::Pid;
+use ockam_api::config::cli::LogSink;
 use std::collections::VecDeque;
 use std::io::Stdout;
+use std::net::SocketAddr;
 use std::process::Stdio;
 use std::{
     env::current_exe,
@@ -45,6 +47,11 @@ pub fn spawn_node(
     name: &str,
     address: &str,
     project: Option<&Path>,
+    log_sink: Option<&LogSink>,
+    relay: bool,
+    health_address: Option<SocketAddr>,
+    tcp_proxy: Option<&str>,
+    user: Option<(&str, Option<&str>)>,
 ) {
     // On systems with non-obvious path setups (or during
     // development) re-executing the current binary is a more
@@ -99,6 +106,37 @@ pub fn spawn_node(
         args.push("--enable-credential-checks".to_string());
     }
 
+    if relay {
+        args.push("--relay".to_string());
+    }
+
+    if let Some(addr) = health_address {
+        args.push("--health-address".to_string());
+        args.push(addr.to_string());
+    }
+
+    if let Some(proxy) = tcp_proxy {
+        args.push("--tcp-proxy".to_string());
+        args.push(proxy.to_string());
+    }
+
+    if let Some((user, group)) = user {
+        args.push("--user".to_string());
+        args.push(user.to_string());
+        if let Some(group) = group {
+            args.push("--group".to_string());
+            args.push(group.to_string());
+        }
+    }
+
+    match log_sink {
+        None | Some(LogSink::File) => {}
+        Some(sink) => {
+            args.push("--log-sink".to_string());
+            args.push(sink.to_string());
+        }
+    }
+
     args.push(name.to_owned());
 
     let child = Command::new(ockam_exe)
@@ -0,0 +1,31 @@
+use clap::Args;
+use ockam::Context;
+use ockam_core::api::Request;
+
+use crate::util::{get_final_element, node_rpc, Rpc};
+use crate::webhook::HELP_DETAIL;
+use crate::{help, CommandGlobalOpts, Result};
+
+/// Delete the Webhook of a node
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct DeleteCommand {
+    /// Node whose webhook to delete.
+    #[arg(long, id = "NODE", display_order = 900)]
+    at: String,
+}
+
+impl DeleteCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, DeleteCommand)) -> Result<()> {
+    let node = get_final_element(&cmd.at);
+    let mut rpc = Rpc::background(&ctx, &opts, node)?;
+    rpc.request(Request::delete("/node/webhook")).await?;
+    rpc.is_ok()?;
+    println!("Webhook of node {node} deleted");
+    Ok(())
+}
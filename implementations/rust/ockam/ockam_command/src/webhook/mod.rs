@@ -0,0 +1,72 @@
+use clap::{Args, Subcommand};
+
+pub(crate) use delete::DeleteCommand;
+pub(crate) use set::SetCommand;
+pub(crate) use show::ShowCommand;
+
+use crate::{help, CommandGlobalOpts};
+
+mod delete;
+mod set;
+mod show;
+
+const HELP_DETAIL: &str = "\
+About:
+    A node posts its events as JSON to its webhook, e.g. to integrate it with chat
+    or paging services. The events are:
+        - forwarder-down: the session of a forwarder is unresponsive
+        - forwarder-recovered: the session of a forwarder was recovered
+        - forwarder-failed: the recovery of a forwarder was given up
+        - secure-channel-down: a monitored secure channel is unresponsive
+        - secure-channel-recovered: a monitored secure channel was re-established
+        - secure-channel-failed: the recovery of a monitored secure channel was given up
+        - credential-expiring: the credential of the node expires soon
+        - credential-renewal-failed: the credential of the node could not be renewed
+        - policy-denial-spike: unusually many messages were denied by access controls
+
+    With a secret, every event has an `X-Ockam-Signature: sha256=<hex>` header
+    holding the HMAC-SHA256 of the body, keyed with the secret.
+
+    The last events of a node are also listed by `ockam node events`.
+
+Examples:
+```sh
+    # Post the events of node n1 to a webhook, signed with a secret
+    $ ockam webhook set --at n1 --url https://example.com/ockam --secret s3cr3t
+
+    # Show the webhook of node n1
+    $ ockam webhook show --at n1
+
+    # Stop posting the events of node n1
+    $ ockam webhook delete --at n1
+```
+";
+
+/// Manage the Webhook of node events
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    help_template = help::template(HELP_DETAIL)
+)]
+pub struct WebhookCommand {
+    #[command(subcommand)]
+    subcommand: WebhookSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum WebhookSubcommand {
+    Set(SetCommand),
+    Show(ShowCommand),
+    Delete(DeleteCommand),
+}
+
+impl WebhookCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        match self.subcommand {
+            WebhookSubcommand::Set(c) => c.run(opts),
+            WebhookSubcommand::Show(c) => c.run(opts),
+            WebhookSubcommand::Delete(c) => c.run(opts),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::events::{SetWebhook, WebhookInfo};
+use ockam_core::api::Request;
+
+use crate::util::{get_final_element, node_rpc, Rpc};
+use crate::webhook::HELP_DETAIL;
+use crate::{help, CommandGlobalOpts, Result};
+
+/// Set the Webhook of a node
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct SetCommand {
+    /// Node whose events to post.
+    #[arg(long, id = "NODE", display_order = 900)]
+    at: String,
+
+    /// URL to which the events are posted.
+    #[arg(long, id = "URL", display_order = 900)]
+    url: String,
+
+    /// Key used to sign the events (optional).
+    #[arg(long, id = "SECRET", display_order = 900)]
+    secret: Option<String>,
+}
+
+impl SetCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, SetCommand)) -> Result<()> {
+    let node = get_final_element(&cmd.at);
+    let body = SetWebhook::new(cmd.url.as_str(), cmd.secret.as_deref().map(Into::into));
+    let mut rpc = Rpc::background(&ctx, &opts, node)?;
+    rpc.request(Request::put("/node/webhook").body(body))
+        .await?;
+    rpc.parse_and_print_response::<WebhookInfo>()?;
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::events::WebhookInfo;
+use ockam_core::api::Request;
+
+use crate::util::{get_final_element, node_rpc, Rpc};
+use crate::webhook::HELP_DETAIL;
+use crate::{help, CommandGlobalOpts, Result};
+
+/// Show the Webhook of a node
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, help_template = help::template(HELP_DETAIL))]
+pub struct ShowCommand {
+    /// Node whose webhook to show.
+    #[arg(long, id = "NODE", display_order = 900)]
+    at: String,
+}
+
+impl ShowCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, ShowCommand)) -> Result<()> {
+    let node = get_final_element(&cmd.at);
+    let mut rpc = Rpc::background(&ctx, &opts, node)?;
+    rpc.request(Request::get("/node/webhook")).await?;
+    rpc.parse_and_print_response::<WebhookInfo>()?;
+    Ok(())
+}
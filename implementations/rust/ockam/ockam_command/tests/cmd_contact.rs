@@ -0,0 +1,105 @@
+use assert_cmd::prelude::*;
+use ockam::identity::IdentityIdentifier;
+use std::process::Command;
+
+fn ockam(dir: &tempfile::TempDir) -> Result<Command, Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.env("OCKAM_PROJECT_PATH", dir.path());
+    Ok(cmd)
+}
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let id = IdentityIdentifier::from_key_id(&hex::encode([1; 32]));
+
+    // contacts only live in the configuration, adding one needs no node
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["contact", "add", "alice", &id.to_string()])
+        .args(["--route", "/dnsaddr/alice.example.com/tcp/4000"]);
+    cmd.assert().success();
+
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["contact", "list"]);
+    cmd.assert().success();
+
+    let mut cmd = ockam(&dir)?;
+    cmd.arg("--test-argument-parser").args([
+        "secure-channel",
+        "create",
+        "--from",
+        "n1",
+        "--to",
+        "/contact/alice/service/api",
+    ]);
+    cmd.assert().success();
+
+    let mut cmd = ockam(&dir)?;
+    cmd.arg("--test-argument-parser").args([
+        "secure-channel-listener",
+        "create",
+        "l1",
+        "--at",
+        "n1",
+        "--authorized-identifier",
+        "alice",
+    ]);
+    cmd.assert().success();
+
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["contact", "delete", "alice"]);
+    cmd.assert().success();
+
+    // as read from the QR code of `ockam identity qr`, with a new name
+    let code = format!(
+        "ockam-contact:alice:{}:/dnsaddr/alice.example.com/tcp/4000",
+        id.to_base32().unwrap()
+    );
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["contact", "add", "--from-qr", &code]);
+    cmd.assert().success();
+
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["contact", "add", "carol", "--from-qr", &code]);
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let id = IdentityIdentifier::from_key_id(&hex::encode([1; 32]));
+
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["contact", "add", "alice", &id.to_string()])
+        .args(["--route", "/dnsaddr/alice.example.com/tcp/4000"]);
+    cmd.assert().success();
+
+    // the identity of a contact is not replaced silently
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["contact", "add", "alice", &id.to_string()])
+        .args(["--route", "/dnsaddr/mallory.example.com/tcp/4000"]);
+    cmd.assert().failure();
+
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["contact", "add", "bob", &id.to_string()])
+        .args(["--route", "/contact/alice"]);
+    cmd.assert().failure();
+
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["contact", "delete", "bob"]);
+    cmd.assert().failure();
+
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["contact", "add", "--from-qr", "ockam-contact:bob"]);
+    cmd.assert().failure();
+
+    // a code gives both the identity and the route
+    let code = format!("ockam-contact:bob:{id}:/dnsaddr/bob.example.com/tcp/4000");
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["contact", "add", "bob", &id.to_string(), "--from-qr", &code]);
+    cmd.assert().failure();
+
+    Ok(())
+}
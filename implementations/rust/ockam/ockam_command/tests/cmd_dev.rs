@@ -0,0 +1,50 @@
+#![cfg(feature = "mock-orchestrator")]
+
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("dev")
+        .arg("mock-orchestrator")
+        .arg("--address")
+        .arg("127.0.0.1:4000");
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("dev")
+        .arg("project")
+        .arg("up")
+        .arg("--output")
+        .arg("project.json")
+        .arg("--enroller")
+        .arg("P6c20e814b56579306f55c64e8747e6c1b4a53d9a3f4ca83c252cc2fbfc72fa94");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("dev")
+        .arg("mock-orchestrator")
+        .arg("--address")
+        .arg("localhost");
+    cmd.assert().failure();
+
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("dev")
+        .arg("project")
+        .arg("up")
+        .arg("--enroller")
+        .arg("alice");
+    cmd.assert().failure();
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // add fleet member success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("fleet")
+        .arg("add")
+        .arg("edge-1")
+        .arg("--route")
+        .arg("/ip4/10.0.0.1/tcp/4000/service/api")
+        .arg("--tag")
+        .arg("eu")
+        .arg("--tag")
+        .arg("camera");
+    cmd.assert().success();
+
+    // list fleet members with a tag success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("fleet")
+        .arg("list")
+        .arg("--tag")
+        .arg("eu");
+    cmd.assert().success();
+
+    // fleet status success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("fleet")
+        .arg("status")
+        .arg("--at")
+        .arg("n1")
+        .arg("--tag")
+        .arg("camera");
+    cmd.assert().success();
+
+    // fleet credential rotation success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("fleet")
+        .arg("rotate-credential")
+        .arg("--tag")
+        .arg("eu");
+    cmd.assert().success();
+
+    // remove fleet member success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("fleet")
+        .arg("remove")
+        .arg("edge-1");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // route is not a multiaddr
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("fleet")
+        .arg("add")
+        .arg("edge-1")
+        .arg("--route")
+        .arg("10.0.0.1:4000");
+    cmd.assert().failure();
+
+    // missing route
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("fleet")
+        .arg("add")
+        .arg("edge-1");
+    cmd.assert().failure();
+
+    Ok(())
+}
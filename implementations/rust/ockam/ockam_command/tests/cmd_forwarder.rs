@@ -14,5 +14,215 @@ fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
         .arg("node_blue");
     cmd.assert().success();
 
+    // delegate the forwarder creation to another node
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("forwarder")
+        .arg("create")
+        .arg("n1")
+        .arg("--at")
+        .arg("/ip4/127.0.0.1/tcp/8080")
+        .arg("--to")
+        .arg("node_yellow")
+        .arg("--delegate-to")
+        .arg("/ip4/10.0.0.2/tcp/4000/service/api");
+    cmd.assert().success();
+
+    // accept any identity of a cluster of relay nodes
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("forwarder")
+        .arg("create")
+        .arg("n1")
+        .arg("--at")
+        .arg("/ip4/127.0.0.1/tcp/8080/secure/api")
+        .arg("--to")
+        .arg("node_blue")
+        .arg("--authorized")
+        .arg("P0119bdd66458074963dcf492ea938decec1bc01d68093c505abf69987774ce89")
+        .arg("--authorized")
+        .arg("P2c5c6e8c1b7e4d9a3f0b1e2d3c4b5a6978877665544332211ffeeddccbbaa99");
+    cmd.assert().success();
+
+    // rotate the secure channel of the forwarder every day
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("forwarder")
+        .arg("create")
+        .arg("n1")
+        .arg("--at")
+        .arg("/project/default")
+        .arg("--to")
+        .arg("node_blue")
+        .arg("--channel-max-age")
+        .arg("1d");
+    cmd.assert().success();
+
+    // let the relay side verify our credential too
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("forwarder")
+        .arg("create")
+        .arg("n1")
+        .arg("--at")
+        .arg("/project/default")
+        .arg("--to")
+        .arg("node_blue")
+        .arg("--mutual-credentials");
+    cmd.assert().success();
+
+    // register the forwarder at two relays
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("forwarder")
+        .arg("create")
+        .arg("n1")
+        .arg("--at")
+        .arg("/ip4/127.0.0.1/tcp/8080")
+        .arg("--at")
+        .arg("/ip4/127.0.0.1/tcp/8081")
+        .arg("--to")
+        .arg("node_blue");
+    cmd.assert().success();
+
+    // allow recoveries to take longer
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("forwarder")
+        .arg("create")
+        .arg("n1")
+        .arg("--at")
+        .arg("/project/default")
+        .arg("--to")
+        .arg("node_blue")
+        .arg("--recovery-timeout")
+        .arg("30s")
+        .arg("--connect-timeout")
+        .arg("15s");
+    cmd.assert().success();
+
+    // renew the registration less often on a metered link
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("forwarder")
+        .arg("create")
+        .arg("n1")
+        .arg("--at")
+        .arg("/project/default")
+        .arg("--to")
+        .arg("node_blue")
+        .arg("--heartbeat-interval")
+        .arg("1m");
+    cmd.assert().success();
+
+    // unregister the forwarder of a CI job after two hours
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("forwarder")
+        .arg("create")
+        .arg("n1")
+        .arg("--at")
+        .arg("/project/default")
+        .arg("--to")
+        .arg("node_blue")
+        .arg("--ttl")
+        .arg("2h");
+    cmd.assert().success();
+
+    // back off between failed recoveries
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("forwarder")
+        .arg("create")
+        .arg("n1")
+        .arg("--at")
+        .arg("/project/default")
+        .arg("--to")
+        .arg("node_blue")
+        .arg("--retry-initial-delay")
+        .arg("5s")
+        .arg("--retry-max-delay")
+        .arg("10m")
+        .arg("--retry-max-attempts")
+        .arg("20");
+    cmd.assert().success();
+
+    // list the forwarders of a node
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("forwarder")
+        .arg("list")
+        .arg("--at")
+        .arg("n1");
+    cmd.assert().success();
+
+    // show a forwarder
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("forwarder")
+        .arg("show")
+        .arg("blue")
+        .arg("--at")
+        .arg("n1");
+    cmd.assert().success();
+
+    // delete a forwarder
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("forwarder")
+        .arg("delete")
+        .arg("forward_to_blue")
+        .arg("--at")
+        .arg("n1");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // the identity of the remote node requires a route to it
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("forwarder")
+        .arg("create")
+        .arg("n1")
+        .arg("--at")
+        .arg("/ip4/127.0.0.1/tcp/8080")
+        .arg("--to")
+        .arg("node_yellow")
+        .arg("--delegate-identity")
+        .arg("P0119bdd66458074963dcf492ea938decec1bc01d68093c505abf69987774ce89");
+    cmd.assert().failure();
+
+    // a recovery is attempted at least once
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("forwarder")
+        .arg("create")
+        .arg("n1")
+        .arg("--at")
+        .arg("/project/default")
+        .arg("--to")
+        .arg("node_blue")
+        .arg("--retry-max-attempts")
+        .arg("0");
+    cmd.assert().failure();
+
+    // heartbeats are either disabled or given an interval
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("forwarder")
+        .arg("create")
+        .arg("n1")
+        .arg("--at")
+        .arg("/project/default")
+        .arg("--to")
+        .arg("node_blue")
+        .arg("--heartbeat-interval")
+        .arg("1m")
+        .arg("--no-heartbeat");
+    cmd.assert().failure();
+
     Ok(())
 }
@@ -0,0 +1,57 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("http-proxy")
+        .arg("create")
+        .arg("--at")
+        .arg("n2")
+        .arg("--route")
+        .arg("api.internal:443=/node/n1/service/api")
+        .arg("--route")
+        .arg("*.db.internal=/project/default/service/forward_to_n1/service/db");
+    cmd.assert().success();
+
+    // hosts without a route go to a proxy outlet
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("http-proxy")
+        .arg("create")
+        .arg("--at")
+        .arg("n2")
+        .arg("--port")
+        .arg("8080")
+        .arg("--to")
+        .arg("/node/n1/service/proxy");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // a proxy needs somewhere to send tunnels
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("http-proxy")
+        .arg("create")
+        .arg("--at")
+        .arg("n2");
+    cmd.assert().failure();
+
+    // a route maps a rule to a route
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("http-proxy")
+        .arg("create")
+        .arg("--at")
+        .arg("n2")
+        .arg("--route")
+        .arg("/node/n1/service/api");
+    cmd.assert().failure();
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use assert_cmd::prelude::*;
+use ockam::identity::IdentityIdentifier;
+use std::process::Command;
+
+fn ockam(dir: &tempfile::TempDir) -> Result<Command, Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.env("OCKAM_PROJECT_PATH", dir.path())
+        .arg("--test-argument-parser");
+    Ok(cmd)
+}
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let id = IdentityIdentifier::from_key_id(&hex::encode([1; 32]));
+
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["identity", "format", "base32"]);
+    cmd.assert().success();
+
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["identity", "show", "--format", "petname"]);
+    cmd.assert().success();
+
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["identity", "petname", "set", "blue", &id.to_string()]);
+    cmd.assert().success();
+
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["identity", "qr", "--route", "/ip4/192.168.1.10/tcp/4000"]);
+    cmd.assert().success();
+
+    let mut cmd = ockam(&dir)?;
+    cmd.args([
+        "secure-channel",
+        "create",
+        "--from",
+        "n1",
+        "--to",
+        "/service/api",
+    ])
+    .args(["--authorized", &id.to_base32().unwrap()]);
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let id = IdentityIdentifier::from_key_id(&hex::encode([1; 32]));
+
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["identity", "format", "octal"]);
+    cmd.assert().failure();
+
+    // Others need a route to reach the node
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["identity", "qr"]);
+    cmd.assert().failure();
+
+    // Neither an identifier nor a known petname
+    let mut cmd = ockam(&dir)?;
+    cmd.args([
+        "secure-channel",
+        "create",
+        "--from",
+        "n1",
+        "--to",
+        "/service/api",
+    ])
+    .args(["--authorized", "blue"]);
+    cmd.assert().failure();
+
+    // A mistyped base32 identifier
+    let mut typo = id.to_base32().unwrap();
+    typo.replace_range(5..6, if &typo[5..6] == "a" { "b" } else { "a" });
+    let mut cmd = ockam(&dir)?;
+    cmd.args(["identity", "petname", "set", "blue", &typo]);
+    cmd.assert().failure();
+
+    Ok(())
+}
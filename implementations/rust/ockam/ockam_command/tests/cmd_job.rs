@@ -0,0 +1,81 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // create credential rotation job success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("job")
+        .arg("create")
+        .arg("rotate-credential")
+        .arg("--at")
+        .arg("n1")
+        .arg("--every")
+        .arg("1d");
+    cmd.assert().success();
+
+    // create project resolution job success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("job")
+        .arg("create")
+        .arg("resolve-project")
+        .arg("--at")
+        .arg("n1")
+        .arg("--every")
+        .arg("30m")
+        .arg("--project")
+        .arg("default");
+    cmd.assert().success();
+
+    // list jobs success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("job")
+        .arg("list")
+        .arg("--at")
+        .arg("n1");
+    cmd.assert().success();
+
+    // delete job success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("job")
+        .arg("delete")
+        .arg("job-id")
+        .arg("--at")
+        .arg("n1");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // unknown job kind
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("job")
+        .arg("create")
+        .arg("reboot")
+        .arg("--at")
+        .arg("n1")
+        .arg("--every")
+        .arg("1h");
+    cmd.assert().failure();
+
+    // malformed interval
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("job")
+        .arg("create")
+        .arg("rotate-credential")
+        .arg("--at")
+        .arg("n1")
+        .arg("--every")
+        .arg("1w");
+    cmd.assert().failure();
+
+    Ok(())
+}
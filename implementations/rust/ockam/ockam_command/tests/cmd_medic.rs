@@ -0,0 +1,73 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // show medic success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("medic")
+        .arg("show")
+        .arg("--at")
+        .arg("n1");
+    cmd.assert().success();
+
+    // set interval success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("medic")
+        .arg("set")
+        .arg("--at")
+        .arg("n1")
+        .arg("--interval")
+        .arg("10s");
+    cmd.assert().success();
+
+    // pause and resume success
+    for sub in ["pause", "resume"] {
+        let mut cmd = Command::cargo_bin("ockam")?;
+        cmd.arg("--test-argument-parser")
+            .arg("medic")
+            .arg(sub)
+            .arg("--at")
+            .arg("n1");
+        cmd.assert().success();
+    }
+
+    // recover session success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("medic")
+        .arg("recover")
+        .arg("5d3fd7cb")
+        .arg("--at")
+        .arg("n1");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // invalid interval
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("medic")
+        .arg("set")
+        .arg("--at")
+        .arg("n1")
+        .arg("--interval")
+        .arg("0s");
+    cmd.assert().failure();
+
+    // missing session
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("medic")
+        .arg("recover")
+        .arg("--at")
+        .arg("n1");
+    cmd.assert().failure();
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // create rtt monitor success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("monitor")
+        .arg("create")
+        .arg("rtt")
+        .arg("--at")
+        .arg("n1")
+        .arg("--above")
+        .arg("500ms")
+        .arg("--for")
+        .arg("5m")
+        .arg("--webhook")
+        .arg("https://example.com/alerts");
+    cmd.assert().success();
+
+    // create recoveries monitor success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("monitor")
+        .arg("create")
+        .arg("recoveries")
+        .arg("--at")
+        .arg("n1")
+        .arg("--above")
+        .arg("3")
+        .arg("--for")
+        .arg("1h")
+        .arg("--exec")
+        .arg("logger -t ockam");
+    cmd.assert().success();
+
+    // list monitors success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("monitor")
+        .arg("list")
+        .arg("--at")
+        .arg("n1");
+    cmd.assert().success();
+
+    // delete monitor success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("monitor")
+        .arg("delete")
+        .arg("monitor-id")
+        .arg("--at")
+        .arg("n1");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // unknown monitor kind
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("monitor")
+        .arg("create")
+        .arg("bandwidth")
+        .arg("--at")
+        .arg("n1")
+        .arg("--above")
+        .arg("3")
+        .arg("--for")
+        .arg("1h")
+        .arg("--exec")
+        .arg("true");
+    cmd.assert().failure();
+
+    // no alert hook
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("monitor")
+        .arg("create")
+        .arg("rtt")
+        .arg("--at")
+        .arg("n1")
+        .arg("--above")
+        .arg("500ms")
+        .arg("--for")
+        .arg("5m");
+    cmd.assert().failure();
+
+    Ok(())
+}
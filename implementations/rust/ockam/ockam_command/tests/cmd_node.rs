@@ -19,5 +19,203 @@ fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
         .arg("node-name");
     cmd.assert().success();
 
+    // diff node success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("diff")
+        .arg("-f")
+        .arg("node.yaml")
+        .arg("--exit-code");
+    cmd.assert().success();
+
+    // global timeout success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("show")
+        .arg("node-name")
+        .arg("--timeout")
+        .arg("5");
+    cmd.assert().success();
+
+    // create node logging to a remote syslog server success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("create")
+        .arg("node-name")
+        .arg("--log-sink")
+        .arg("syslog:10.0.0.1:514");
+    cmd.assert().success();
+
+    // create relay node success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("create")
+        .arg("relay-name")
+        .arg("--relay");
+    cmd.assert().success();
+
+    // create node serving health probes success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("create")
+        .arg("node-name")
+        .arg("--health-address")
+        .arg("0.0.0.0:8080");
+    cmd.assert().success();
+
+    // create node connecting through a proxy success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("create")
+        .arg("node-name")
+        .arg("--tcp-proxy")
+        .arg("socks5://10.0.0.1:1080");
+    cmd.assert().success();
+
+    // create foreground node listening on a socket passed by systemd success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("create")
+        .arg("node-name")
+        .arg("--foreground")
+        .arg("--tcp-listener-address")
+        .arg("0.0.0.0:443")
+        .arg("--socket-activation");
+    cmd.assert().success();
+
+    // create node switching to an unprivileged user success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("create")
+        .arg("node-name")
+        .arg("--user")
+        .arg("ockam")
+        .arg("--group")
+        .arg("ockam");
+    cmd.assert().success();
+
+    // create foreground node driven over stdio success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("create")
+        .arg("node-name")
+        .arg("--foreground")
+        .arg("--stdio");
+    cmd.assert().success();
+
+    // follow node events success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("events")
+        .arg("--at")
+        .arg("n1")
+        .arg("--since")
+        .arg("12")
+        .arg("--follow");
+    cmd.assert().success();
+
+    // enable worker accounting success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("stats")
+        .arg("--at")
+        .arg("n1")
+        .arg("--enable");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // unknown log sink
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("create")
+        .arg("node-name")
+        .arg("--log-sink")
+        .arg("syslog:10.0.0.1");
+    cmd.assert().failure();
+
+    // health probes address without a port
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("create")
+        .arg("node-name")
+        .arg("--health-address")
+        .arg("0.0.0.0");
+    cmd.assert().failure();
+
+    // proxy without a scheme
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("create")
+        .arg("node-name")
+        .arg("--tcp-proxy")
+        .arg("10.0.0.1:1080");
+    cmd.assert().failure();
+
+    // socket activation without foreground
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("create")
+        .arg("node-name")
+        .arg("--socket-activation");
+    cmd.assert().failure();
+
+    // group without user
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("create")
+        .arg("node-name")
+        .arg("--group")
+        .arg("ockam");
+    cmd.assert().failure();
+
+    // stdio without foreground
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("create")
+        .arg("node-name")
+        .arg("--stdio");
+    cmd.assert().failure();
+
+    // node events without a node
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("events")
+        .arg("--since")
+        .arg("12");
+    cmd.assert().failure();
+
+    // node stats both enabling and disabling accounting
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("node")
+        .arg("stats")
+        .arg("--at")
+        .arg("n1")
+        .arg("--enable")
+        .arg("--disable");
+    cmd.assert().failure();
+
     Ok(())
 }
@@ -0,0 +1,58 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("policy")
+        .arg("test")
+        .arg("--at")
+        .arg("n1")
+        .arg("--resource")
+        .arg("db")
+        .arg("--action")
+        .arg("r")
+        .arg("--attribute")
+        .arg("role=admin")
+        .arg("--attribute")
+        .arg("local.tag.prod=true")
+        .arg(r#"{"Eq": ["role", {"S": "admin"}]}"#);
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // Attributes are KEY=VALUE
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("policy")
+        .arg("test")
+        .arg("--at")
+        .arg("n1")
+        .arg("--resource")
+        .arg("db")
+        .arg("--action")
+        .arg("r")
+        .arg("--attribute")
+        .arg("role")
+        .arg(r#"{"Eq": ["role", {"S": "admin"}]}"#);
+    cmd.assert().failure();
+
+    // The policy is required
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("policy")
+        .arg("test")
+        .arg("--at")
+        .arg("n1")
+        .arg("--resource")
+        .arg("db")
+        .arg("--action")
+        .arg("r");
+    cmd.assert().failure();
+
+    Ok(())
+}
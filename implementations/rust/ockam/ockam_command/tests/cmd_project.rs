@@ -1,6 +1,8 @@
 use assert_cmd::prelude::*;
 use std::process::Command;
 
+const MEMBER: &str = "P6c20e814b56579306f55c64e8747e6c1b4a53d9a3f4ca83c252cc2fbfc72fa94";
+
 #[test]
 fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
     let prefix_args = ["--test-argument-parser", "project"];
@@ -30,5 +32,41 @@ fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
         .arg("project-id");
     cmd.assert().success();
 
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.args(&prefix_args)
+        .arg("enroll")
+        .args(["--to", "/project/default/service/authenticator"])
+        .args(["--member", MEMBER])
+        .args(["--capability", "forwarder:device-42"])
+        .args(["--capability", "outlet:22"]);
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.args(["--test-argument-parser", "service", "start", "delegation"])
+        .arg("--capabilities");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let prefix_args = ["--test-argument-parser", "project"];
+
+    for capability in ["inlet:22", "outlet:ssh", "forwarder:"] {
+        let mut cmd = Command::cargo_bin("ockam")?;
+        cmd.args(&prefix_args)
+            .arg("enroll")
+            .args(["--to", "/project/default/service/authenticator"])
+            .args(["--member", MEMBER])
+            .args(["--capability", capability]);
+        cmd.assert().failure();
+    }
+
+    // Neither authorized identities nor capabilities
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.args(["--test-argument-parser", "service", "start", "delegation"]);
+    cmd.assert().failure();
+
     Ok(())
 }
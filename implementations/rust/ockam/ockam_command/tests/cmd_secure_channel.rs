@@ -0,0 +1,204 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // create a monitored secure channel success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel")
+        .arg("create")
+        .arg("--from")
+        .arg("n1")
+        .arg("--to")
+        .arg("/node/n2/service/api")
+        .arg("--monitor");
+    cmd.assert().success();
+
+    // create a secure channel closed when idle success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel")
+        .arg("create")
+        .arg("--from")
+        .arg("n1")
+        .arg("--to")
+        .arg("/node/n2/service/api")
+        .arg("--idle-timeout")
+        .arg("30m");
+    cmd.assert().success();
+
+    // create a secure channel with keepalives success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel")
+        .arg("create")
+        .arg("--from")
+        .arg("n1")
+        .arg("--to")
+        .arg("/node/n2/service/api")
+        .arg("--keepalive")
+        .arg("30s");
+    cmd.assert().success();
+
+    // create a secure channel measuring its round-trip time success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel")
+        .arg("create")
+        .arg("--from")
+        .arg("n1")
+        .arg("--to")
+        .arg("/node/n2/service/api")
+        .arg("--keepalive")
+        .arg("30s")
+        .arg("--measure-rtt");
+    cmd.assert().success();
+
+    // measuring the round-trip time without keepalives failure
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel")
+        .arg("create")
+        .arg("--from")
+        .arg("n1")
+        .arg("--to")
+        .arg("/node/n2/service/api")
+        .arg("--measure-rtt");
+    cmd.assert().failure();
+
+    // create a hybrid secure channel success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel")
+        .arg("create")
+        .arg("--from")
+        .arg("n1")
+        .arg("--to")
+        .arg("/node/n2/service/api")
+        .arg("--hybrid");
+    cmd.assert().success();
+
+    // create an onion secure channel success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel")
+        .arg("create")
+        .arg("--from")
+        .arg("n1")
+        .arg("--to")
+        .arg("/node/relay/secure/api/service/forward_to_n2/service/api")
+        .arg("--onion");
+    cmd.assert().success();
+
+    // create a hybrid secure channel listener success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel-listener")
+        .arg("create")
+        .arg("listener")
+        .arg("--hybrid");
+    cmd.assert().success();
+
+    // create a secure channel listener requiring attributes success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel-listener")
+        .arg("create")
+        .arg("listener")
+        .arg("--require-attribute")
+        .arg("role=member")
+        .arg("--require-attribute")
+        .arg("team=ops");
+    cmd.assert().success();
+
+    // create a secure channel listener with a malformed attribute failure
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel-listener")
+        .arg("create")
+        .arg("listener")
+        .arg("--require-attribute")
+        .arg("member");
+    cmd.assert().failure();
+
+    // create a secure channel listener with a pre-shared key success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel-listener")
+        .arg("create")
+        .arg("listener")
+        .arg("--psk-id")
+        .arg("sensors")
+        .arg("--psk-file")
+        .arg("sensors.key");
+    cmd.assert().success();
+
+    // create a secure channel with a pre-shared key but no id failure
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel")
+        .arg("create")
+        .arg("--from")
+        .arg("n1")
+        .arg("--to")
+        .arg("/node/n2/service/listener")
+        .arg("--psk-file")
+        .arg("sensors.key");
+    cmd.assert().failure();
+
+    // create a secure channel retried with backoff success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel")
+        .arg("create")
+        .arg("--from")
+        .arg("n1")
+        .arg("--to")
+        .arg("/node/n2/service/api")
+        .arg("--retry-max-attempts")
+        .arg("5")
+        .arg("--retry-initial-delay")
+        .arg("2s");
+    cmd.assert().success();
+
+    // retry delays without attempts failure
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel")
+        .arg("create")
+        .arg("--from")
+        .arg("n1")
+        .arg("--to")
+        .arg("/node/n2/service/api")
+        .arg("--retry-max-delay")
+        .arg("1m");
+    cmd.assert().failure();
+
+    // create a secure channel trusting the authorities of two projects success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel")
+        .arg("create")
+        .arg("--from")
+        .arg("n1")
+        .arg("--to")
+        .arg("/node/n2/service/api")
+        .arg("--trust-project")
+        .arg("default")
+        .arg("--trust-project")
+        .arg("staging");
+    cmd.assert().success();
+
+    // create a secure channel listener trusting the authority of a project success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("secure-channel-listener")
+        .arg("create")
+        .arg("listener")
+        .arg("--trust-project")
+        .arg("staging");
+    cmd.assert().success();
+
+    Ok(())
+}
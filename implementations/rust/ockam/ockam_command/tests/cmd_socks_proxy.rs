@@ -0,0 +1,57 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("socks-proxy")
+        .arg("create")
+        .arg("--at")
+        .arg("n2")
+        .arg("--to")
+        .arg("/node/n1/service/proxy");
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("socks-proxy")
+        .arg("create")
+        .arg("--at")
+        .arg("n2")
+        .arg("--host")
+        .arg("0.0.0.0")
+        .arg("--port")
+        .arg("9050")
+        .arg("--to")
+        .arg("/project/default/service/forward_to_n1/service/proxy");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // the route to the proxy outlet is required
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("socks-proxy")
+        .arg("create")
+        .arg("--at")
+        .arg("n2");
+    cmd.assert().failure();
+
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("socks-proxy")
+        .arg("create")
+        .arg("--at")
+        .arg("n2")
+        .arg("--port")
+        .arg("70000")
+        .arg("--to")
+        .arg("/node/n1/service/proxy");
+    cmd.assert().failure();
+
+    Ok(())
+}
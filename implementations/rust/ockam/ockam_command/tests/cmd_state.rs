@@ -0,0 +1,66 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // show the migrations
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("state")
+        .arg("migrate")
+        .arg("--dry-run");
+    cmd.assert().success();
+
+    // migrate back to a version
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("state")
+        .arg("migrate")
+        .arg("--to")
+        .arg("0");
+    cmd.assert().success();
+
+    // back up the state without the secret keys
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("state")
+        .arg("backup")
+        .arg("ockam.bak")
+        .arg("--exclude-secrets")
+        .arg("--passphrase-file")
+        .arg("passphrase.txt");
+    cmd.assert().success();
+
+    // restore the state over the existing one
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("state")
+        .arg("restore")
+        .arg("ockam.bak")
+        .arg("--force");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // versions are not negative
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("state")
+        .arg("migrate")
+        .arg("--to")
+        .arg("-1");
+    cmd.assert().failure();
+
+    // missing archive
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("state")
+        .arg("restore")
+        .arg("--force");
+    cmd.assert().failure();
+
+    Ok(())
+}
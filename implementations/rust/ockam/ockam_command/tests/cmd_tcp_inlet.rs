@@ -0,0 +1,90 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-inlet")
+        .arg("create")
+        .arg("--at")
+        .arg("n1")
+        .arg("--from")
+        .arg("127.0.0.1:6443")
+        .arg("--to")
+        .arg("/node/n2/service/outlet")
+        .arg("--tls");
+    cmd.assert().success();
+
+    // terminate TLS with a given certificate
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-inlet")
+        .arg("create")
+        .arg("--at")
+        .arg("n1")
+        .arg("--from")
+        .arg("127.0.0.1:6443")
+        .arg("--to")
+        .arg("/node/n2/service/outlet")
+        .arg("--tls-cert")
+        .arg("cert.pem")
+        .arg("--tls-key")
+        .arg("key.pem");
+    cmd.assert().success();
+
+    // listen on a shared port of an interface
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-inlet")
+        .arg("create")
+        .arg("--at")
+        .arg("n1")
+        .arg("--from")
+        .arg("0.0.0.0:6000")
+        .arg("--to")
+        .arg("/node/n2/service/outlet")
+        .arg("--interface")
+        .arg("eth1")
+        .arg("--ipv6-only")
+        .arg("--reuse-port")
+        .arg("--backlog")
+        .arg("4096");
+    cmd.assert().success();
+
+    // listen on a socket passed by systemd
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-inlet")
+        .arg("create")
+        .arg("--at")
+        .arg("n1")
+        .arg("--from")
+        .arg("0.0.0.0:443")
+        .arg("--to")
+        .arg("/node/n2/service/outlet")
+        .arg("--socket-activation");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // a certificate needs a key
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-inlet")
+        .arg("create")
+        .arg("--at")
+        .arg("n1")
+        .arg("--from")
+        .arg("127.0.0.1:6443")
+        .arg("--to")
+        .arg("/node/n2/service/outlet")
+        .arg("--tls-cert")
+        .arg("cert.pem");
+    cmd.assert().failure();
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-listener")
+        .arg("create")
+        .arg("--node")
+        .arg("n1")
+        .arg("127.0.0.1:7000");
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-listener")
+        .arg("create")
+        .arg("--node")
+        .arg("n1")
+        .arg("0.0.0.0:7000")
+        .arg("--reuse-port")
+        .arg("--backlog")
+        .arg("4096");
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-listener")
+        .arg("create")
+        .arg("--node")
+        .arg("n1")
+        .arg("0.0.0.0:443")
+        .arg("--socket-activation");
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-listener")
+        .arg("create")
+        .arg("--node")
+        .arg("n1")
+        .arg("0.0.0.0:7000")
+        .arg("--accept-workers")
+        .arg("4");
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-listener")
+        .arg("rebind")
+        .arg("--node")
+        .arg("n1")
+        .arg("5d3fd7cb")
+        .arg("127.0.0.1:7001");
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-listener")
+        .arg("delete")
+        .arg("--node")
+        .arg("n1")
+        .arg("5d3fd7cb");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // The new address is required
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-listener")
+        .arg("rebind")
+        .arg("5d3fd7cb");
+    cmd.assert().failure();
+
+    // The number of accept workers is a number
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-listener")
+        .arg("create")
+        .arg("127.0.0.1:7000")
+        .arg("--accept-workers")
+        .arg("all");
+    cmd.assert().failure();
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // originate mutual TLS to the target
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-outlet")
+        .arg("create")
+        .arg("--at")
+        .arg("n1")
+        .arg("--from")
+        .arg("/service/outlet")
+        .arg("--to")
+        .arg("10.0.0.5:443")
+        .arg("--tls")
+        .arg("--tls-server-name")
+        .arg("api.internal")
+        .arg("--tls-ca")
+        .arg("ca.pem")
+        .arg("--tls-cert")
+        .arg("client.pem")
+        .arg("--tls-key")
+        .arg("client.key");
+    cmd.assert().success();
+
+    // proxy outlet
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-outlet")
+        .arg("create")
+        .arg("--at")
+        .arg("n1")
+        .arg("--from")
+        .arg("/service/proxy")
+        .arg("--allow")
+        .arg("*.example.com:443")
+        .arg("--allow")
+        .arg("10.0.0.5");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // TLS needs the name of the target
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-outlet")
+        .arg("create")
+        .arg("--at")
+        .arg("n1")
+        .arg("--from")
+        .arg("/service/outlet")
+        .arg("--to")
+        .arg("10.0.0.5:443")
+        .arg("--tls");
+    cmd.assert().failure();
+
+    // a proxy outlet has no target of its own
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-outlet")
+        .arg("create")
+        .arg("--at")
+        .arg("n1")
+        .arg("--from")
+        .arg("/service/proxy")
+        .arg("--to")
+        .arg("10.0.0.5:443")
+        .arg("--allow")
+        .arg("*");
+    cmd.assert().failure();
+
+    // an outlet needs a target or egress rules
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("tcp-outlet")
+        .arg("create")
+        .arg("--at")
+        .arg("n1")
+        .arg("--from")
+        .arg("/service/outlet");
+    cmd.assert().failure();
+
+    Ok(())
+}
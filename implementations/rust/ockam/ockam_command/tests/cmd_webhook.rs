@@ -0,0 +1,52 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn valid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // set signed webhook success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("webhook")
+        .arg("set")
+        .arg("--at")
+        .arg("n1")
+        .arg("--url")
+        .arg("https://example.com/ockam")
+        .arg("--secret")
+        .arg("s3cr3t");
+    cmd.assert().success();
+
+    // show webhook success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("webhook")
+        .arg("show")
+        .arg("--at")
+        .arg("n1");
+    cmd.assert().success();
+
+    // delete webhook success
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("webhook")
+        .arg("delete")
+        .arg("--at")
+        .arg("n1");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn invalid_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    // missing url
+    let mut cmd = Command::cargo_bin("ockam")?;
+    cmd.arg("--test-argument-parser")
+        .arg("webhook")
+        .arg("set")
+        .arg("--at")
+        .arg("n1");
+    cmd.assert().failure();
+
+    Ok(())
+}
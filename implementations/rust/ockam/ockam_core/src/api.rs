@@ -2,10 +2,12 @@
 
 use crate::compat::borrow::Cow;
 use crate::compat::rand;
+use crate::compat::string::ToString;
 use crate::compat::vec::Vec;
 use crate::errcode::{Kind, Origin};
 use crate::Result;
 use core::fmt::{self, Display, Formatter};
+use core::time::Duration;
 use minicbor::encode::{self, Encoder, Write};
 use minicbor::{Decode, Decoder, Encode};
 use tinyvec::ArrayVec;
@@ -39,7 +41,15 @@ pub struct Request<'a> {
     /// how to handle unknown methods.
     #[n(3)] method: Option<Method>,
     /// Indicator if a request body is expected after this header.
-    #[n(4)] has_body: bool
+    #[n(4)] has_body: bool,
+    /// The maximum time the client is willing to wait for a response.
+    ///
+    /// Servers should use it to bound the work they do on behalf of the
+    /// request, e.g. connects and handshakes with other nodes.
+    #[n(5)] timeout: Option<Duration>,
+    /// Indicator if the client accepts interim responses with status
+    /// `Processing` to report progress before the final response.
+    #[n(6)] progress: Option<bool>
 }
 
 /// The response header.
@@ -150,6 +160,7 @@ impl Display for Method {
 #[rustfmt::skip]
 #[cbor(index_only)]
 pub enum Status {
+    #[n(102)] Processing,
     #[n(200)] Ok,
     #[n(400)] BadRequest,
     #[n(401)] Unauthorized,
@@ -164,6 +175,7 @@ pub enum Status {
 impl Display for Status {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         f.write_str(match self {
+            Status::Processing => "102 Processing",
             Status::Ok => "200 Ok",
             Status::BadRequest => "400 BadRequest",
             Status::Unauthorized => "401 Unauthorized",
@@ -205,6 +217,8 @@ impl<'a> Request<'a> {
             method: Some(method),
             path: path.into(),
             has_body,
+            timeout: None,
+            progress: None,
         }
     }
 
@@ -254,6 +268,14 @@ impl<'a> Request<'a> {
     pub fn has_body(&self) -> bool {
         self.has_body
     }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub fn wants_progress(&self) -> bool {
+        self.progress.unwrap_or(false)
+    }
 }
 
 impl Response {
@@ -275,6 +297,10 @@ impl Response {
         }
     }
 
+    pub fn processing(re: Id) -> ResponseBuilder {
+        Response::builder(re, Status::Processing)
+    }
+
     pub fn ok(re: Id) -> ResponseBuilder {
         Response::builder(re, Status::Ok)
     }
@@ -414,6 +440,16 @@ impl<'a, T> RequestBuilder<'a, T> {
         self
     }
 
+    pub fn timeout(mut self, t: Duration) -> Self {
+        self.header.timeout = Some(t);
+        self
+    }
+
+    pub fn progress(mut self, p: bool) -> Self {
+        self.header.progress = Some(p);
+        self
+    }
+
     pub fn header(&self) -> &Request<'a> {
         &self.header
     }
@@ -572,6 +608,26 @@ pub fn is_ok(label: &str, buf: &[u8]) -> Result<()> {
     }
 }
 
+/// Decode the response header, turning failed requests into errors with the
+/// message of their error body, if any.
+///
+/// The body of a successful response is left in `dec`.
+pub fn check_response(dec: &mut Decoder<'_>) -> Result<()> {
+    let res: Response = dec.decode()?;
+    if res.status() == Some(Status::Ok) {
+        return Ok(());
+    }
+    let msg = if res.has_body() {
+        dec.decode::<Error>()
+            .ok()
+            .and_then(|e| e.message().map(|m| m.to_string()))
+    } else {
+        None
+    };
+    let msg = msg.unwrap_or_else(|| format!("request failed with status {:?}", res.status()));
+    Err(crate::Error::new(Origin::Application, Kind::Protocol, msg))
+}
+
 /// Decode response and an optional body.
 pub fn decode_option<'a, 'b, T: Decode<'b, ()>>(
     label: &'a str,
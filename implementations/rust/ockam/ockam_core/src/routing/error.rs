@@ -8,6 +8,13 @@ use crate::{
 pub enum RouteError {
     /// Message had an incomplete route
     IncompleteRoute,
+    /// Message had an onward route with more hops than allowed
+    TooManyHops,
+    /// Message had an onward route going through the same address too
+    /// many times
+    RoutingLoop,
+    /// Message was relayed more times than its TTL allowed
+    TtlExpired,
 }
 
 impl From<RouteError> for Error {
@@ -15,6 +22,9 @@ impl From<RouteError> for Error {
     fn from(err: RouteError) -> Self {
         let kind = match err {
             RouteError::IncompleteRoute => Kind::Misuse,
+            RouteError::TooManyHops | RouteError::RoutingLoop | RouteError::TtlExpired => {
+                Kind::Invalid
+            }
         };
         Error::new(Origin::Core, kind, err)
     }
@@ -25,6 +35,9 @@ impl core::fmt::Display for RouteError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             RouteError::IncompleteRoute => "incomplete route".fmt(f),
+            RouteError::TooManyHops => "route has too many hops".fmt(f),
+            RouteError::RoutingLoop => "route loops back to the same address".fmt(f),
+            RouteError::TtlExpired => "message was relayed too many times".fmt(f),
         }
     }
 }
@@ -0,0 +1,67 @@
+use crate::compat::collections::BTreeMap;
+use crate::{Address, Route, RouteError};
+
+/// Limits of the onward routes a node forwards messages along, so that
+/// relays and nodes are not made to amplify routing loops
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RouteLimits {
+    /// Maximum number of hops of an onward route
+    pub max_hops: usize,
+    /// Maximum number of times the same address appears in an onward
+    /// route, more means the route loops back to it
+    pub max_repeats: usize,
+}
+
+impl RouteLimits {
+    /// Create limits of `max_hops` hops, repeating an address at most
+    /// `max_repeats` times
+    pub fn new(max_hops: usize, max_repeats: usize) -> Self {
+        Self {
+            max_hops,
+            max_repeats,
+        }
+    }
+
+    /// Check that `route` is within these limits
+    pub fn check(&self, route: &Route) -> Result<(), RouteError> {
+        if route.iter().count() > self.max_hops {
+            return Err(RouteError::TooManyHops);
+        }
+        let mut seen: BTreeMap<&Address, usize> = BTreeMap::new();
+        for addr in route.iter() {
+            let n = seen.entry(addr).or_default();
+            *n += 1;
+            if *n > self.max_repeats {
+                return Err(RouteError::RoutingLoop);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for RouteLimits {
+    fn default() -> Self {
+        Self::new(64, 3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route;
+
+    #[test]
+    fn routes_are_checked_against_the_limits() {
+        let limits = RouteLimits::new(4, 2);
+        assert!(limits.check(&route!["a", "b", "c", "d"]).is_ok());
+        assert!(limits.check(&route!["a", "b", "a", "b"]).is_ok());
+        assert!(matches!(
+            limits.check(&route!["a", "b", "c", "d", "e"]),
+            Err(RouteError::TooManyHops)
+        ));
+        assert!(matches!(
+            limits.check(&route!["a", "b", "a", "a"]),
+            Err(RouteError::RoutingLoop)
+        ));
+    }
+}
@@ -1,6 +1,14 @@
-use crate::{compat::vec::Vec, Message, Route};
+use crate::{compat::vec::Vec, Message, Route, RouteError};
 use core::fmt::{self, Display, Formatter};
-use serde::{Deserialize, Serialize};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Hops a message may be relayed along before it is dropped
+pub const DEFAULT_TTL: u8 = 64;
+
+/// The first transport protocol version carrying a TTL
+pub const TTL_VERSION: u8 = 2;
 
 /// A generic transport message type.
 ///
@@ -15,7 +23,7 @@ use serde::{Deserialize, Serialize};
 ///
 /// See `ockam_transport_tcp::workers::sender::TcpSendWorker` for a usage example.
 ///
-#[derive(Serialize, Deserialize, Debug, Clone, Hash, Ord, PartialOrd, Eq, PartialEq, Message)]
+#[derive(Debug, Clone, Hash, Ord, PartialOrd, Eq, PartialEq, Message)]
 pub struct TransportMessage {
     /// The transport protocol version.
     pub version: u8,
@@ -28,6 +36,16 @@ pub struct TransportMessage {
     pub return_route: Route,
     /// The message payload.
     pub payload: Vec<u8>,
+    /// Hops left before the message is dropped.
+    ///
+    /// Nodes decrement it each time they relay the message, so that
+    /// messages caught in a routing loop, e.g. between forwarders pointing
+    /// at each other, are eventually dropped.
+    ///
+    /// It is only sent to other nodes from version [`TTL_VERSION`] on,
+    /// messages of earlier versions start over from [`DEFAULT_TTL`] on
+    /// each node.
+    pub ttl: u8,
 }
 
 impl TransportMessage {
@@ -42,7 +60,100 @@ impl TransportMessage {
             onward_route: onward_route.into(),
             return_route: return_route.into(),
             payload,
+            ttl: DEFAULT_TTL,
+        }
+    }
+
+    /// Create a new v2 transport message with empty return route.
+    ///
+    /// Its TTL is sent along, so only use it with nodes supporting v2.
+    pub fn v2(
+        onward_route: impl Into<Route>,
+        return_route: impl Into<Route>,
+        payload: Vec<u8>,
+    ) -> Self {
+        Self {
+            version: TTL_VERSION,
+            ..Self::v1(onward_route, return_route, payload)
+        }
+    }
+
+    /// Count a hop of this message, failing once it has no hops left.
+    pub fn decrement_ttl(&mut self) -> Result<(), RouteError> {
+        self.ttl = self.ttl.checked_sub(1).ok_or(RouteError::TtlExpired)?;
+        Ok(())
+    }
+}
+
+/// The TTL is only part of messages from version [`TTL_VERSION`] on.
+impl Serialize for TransportMessage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let has_ttl = self.version >= TTL_VERSION;
+        let len = if has_ttl { 5 } else { 4 };
+        let mut s = serializer.serialize_struct("TransportMessage", len)?;
+        s.serialize_field("version", &self.version)?;
+        s.serialize_field("onward_route", &self.onward_route)?;
+        s.serialize_field("return_route", &self.return_route)?;
+        s.serialize_field("payload", &self.payload)?;
+        if has_ttl {
+            s.serialize_field("ttl", &self.ttl)?;
         }
+        s.end()
+    }
+}
+
+/// Messages of versions before [`TTL_VERSION`] get the default TTL.
+impl<'de> Deserialize<'de> for TransportMessage {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TransportMessageVisitor;
+
+        impl<'de> Visitor<'de> for TransportMessageVisitor {
+            type Value = TransportMessage;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("a transport message")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let version: u8 = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let onward_route = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let return_route = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                let payload = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(3, &self))?;
+                let ttl = if version >= TTL_VERSION {
+                    seq.next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(4, &self))?
+                } else {
+                    DEFAULT_TTL
+                };
+                Ok(TransportMessage {
+                    version,
+                    onward_route,
+                    return_route,
+                    payload,
+                    ttl,
+                })
+            }
+        }
+
+        const FIELDS: &[&str] = &["version", "onward_route", "return_route", "payload", "ttl"];
+        deserializer.deserialize_struct("TransportMessage", FIELDS, TransportMessageVisitor)
     }
 }
 
@@ -55,3 +166,40 @@ impl Display for TransportMessage {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{route, Decodable, Encodable};
+
+    #[test]
+    fn v1_messages_have_no_ttl() {
+        let mut msg = TransportMessage::v1(route!["a"], route!["b"], vec![1, 2, 3]);
+        msg.ttl = 3;
+        let encoded = msg.encode().unwrap();
+        assert_eq!(encoded.last(), Some(&3), "the last payload byte");
+
+        let decoded = TransportMessage::decode(&encoded).unwrap();
+        assert_eq!(decoded.payload, msg.payload);
+        assert_eq!(decoded.ttl, DEFAULT_TTL);
+    }
+
+    #[test]
+    fn v2_messages_have_a_ttl() {
+        let mut msg = TransportMessage::v2(route!["a"], route!["b"], vec![1, 2, 3]);
+        msg.ttl = 3;
+        let mut encoded = msg.encode().unwrap();
+        assert_eq!(TransportMessage::decode(&encoded).unwrap(), msg);
+
+        encoded.pop();
+        assert!(TransportMessage::decode(&encoded).is_err());
+    }
+
+    #[test]
+    fn messages_expire() {
+        let mut msg = TransportMessage::v1(route!["a"], route![], vec![]);
+        msg.ttl = 1;
+        assert!(msg.decrement_ttl().is_ok());
+        assert!(matches!(msg.decrement_ttl(), Err(RouteError::TtlExpired)));
+    }
+}
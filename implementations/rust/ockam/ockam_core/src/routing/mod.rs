@@ -9,6 +9,9 @@ pub use address::*;
 mod route;
 pub use route::*;
 
+mod limits;
+pub use limits::*;
+
 mod message;
 pub use message::*;
 
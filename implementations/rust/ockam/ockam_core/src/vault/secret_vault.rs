@@ -13,7 +13,11 @@ pub trait SecretVault {
     /// Generate a fresh secret with the given attributes.
     async fn secret_generate(&self, attributes: SecretAttributes) -> Result<KeyId>;
     /// Import a secret with the given attributes from binary form into the vault.
-    async fn secret_import(&self, secret: &[u8], attributes: SecretAttributes) -> Result<KeyId>;
+    ///
+    /// The secret is taken as a [`SecretKey`], so that it is zeroized once
+    /// imported whatever the implementation of the vault.
+    async fn secret_import(&self, secret: SecretKey, attributes: SecretAttributes)
+        -> Result<KeyId>;
     /// Export a secret key to the binary form represented as [`SecretKey`].
     async fn secret_export(&self, key_id: &KeyId) -> Result<SecretKey>;
     /// Return the attributes for a secret.
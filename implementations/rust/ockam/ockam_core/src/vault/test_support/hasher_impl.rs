@@ -1,4 +1,6 @@
-use crate::vault::{Hasher, SecretAttributes, SecretPersistence, SecretType, SecretVault};
+use crate::vault::{
+    Hasher, SecretAttributes, SecretKey, SecretPersistence, SecretType, SecretVault,
+};
 use hex::encode;
 
 pub async fn sha256(vault: &mut impl Hasher) {
@@ -19,7 +21,7 @@ pub async fn hkdf(vault: &mut (impl Hasher + SecretVault)) {
         salt_value.len() as u32,
     );
     let salt = vault
-        .secret_import(&salt_value[..], attributes)
+        .secret_import(SecretKey::new(salt_value.to_vec()), attributes)
         .await
         .unwrap();
 
@@ -30,7 +32,7 @@ pub async fn hkdf(vault: &mut (impl Hasher + SecretVault)) {
         ikm_value.len() as u32,
     );
     let ikm = vault
-        .secret_import(&ikm_value[..], attributes)
+        .secret_import(SecretKey::new(ikm_value.to_vec()), attributes)
         .await
         .unwrap();
 
@@ -5,6 +5,7 @@
 mod asymmetric_impl;
 mod hasher_impl;
 mod key_id_impl;
+mod redaction;
 mod secret_impl;
 mod signer_impl;
 mod symmetric_impl;
@@ -12,6 +13,7 @@ mod symmetric_impl;
 pub use asymmetric_impl::*;
 pub use hasher_impl::*;
 pub use key_id_impl::*;
+pub use redaction::*;
 pub use secret_impl::*;
 pub use signer_impl::*;
 pub use symmetric_impl::*;
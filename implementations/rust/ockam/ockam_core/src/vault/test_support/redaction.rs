@@ -0,0 +1,29 @@
+use crate::compat::format;
+use core::fmt::Debug;
+
+/// Assert that the `Debug` formatting of `value` reveals nothing of `secret`.
+///
+/// The secret is looked for in hexadecimal, in lower and upper case, and
+/// as the list of its bytes printed by `Debug` for byte slices.
+pub fn assert_redacted<T: Debug + ?Sized>(value: &T, secret: &[u8]) {
+    assert!(!secret.is_empty(), "empty secret");
+    let debug = format!("{:?}", value);
+    let alternate = format!("{:#?}", value);
+    let hex = hex::encode(secret);
+    let bytes = format!("{:?}", secret);
+    let bytes = bytes.trim_start_matches('[').trim_end_matches(']');
+    for output in [&debug, &alternate] {
+        assert!(
+            !output.contains(&hex) && !output.contains(&hex.to_uppercase()),
+            "secret formatted in hexadecimal: {}",
+            output
+        );
+        assert!(
+            !output
+                .replace(char::is_whitespace, "")
+                .contains(&bytes.replace(' ', "")),
+            "secret formatted as bytes: {}",
+            output
+        );
+    }
+}
@@ -1,6 +1,7 @@
+use crate::vault::test_support::assert_redacted;
 use crate::vault::{
-    SecretAttributes, SecretPersistence, SecretType, SecretVault, CURVE25519_PUBLIC_LENGTH_USIZE,
-    CURVE25519_SECRET_LENGTH_U32,
+    SecretAttributes, SecretKey, SecretPersistence, SecretType, SecretVault, VaultEntry,
+    CURVE25519_PUBLIC_LENGTH_USIZE, CURVE25519_SECRET_LENGTH_U32,
 };
 use hex::{decode, encode};
 
@@ -57,7 +58,7 @@ pub async fn secret_import_export(vault: &mut impl SecretVault) {
     let secret_str = "98d589b0dce92c9e2442b3093718138940bff71323f20b9d158218b89c3cec6e";
 
     let secret = vault
-        .secret_import(decode(secret_str).unwrap().as_slice(), attributes)
+        .secret_import(SecretKey::new(decode(secret_str).unwrap()), attributes)
         .await
         .unwrap();
 
@@ -69,7 +70,7 @@ pub async fn secret_import_export(vault: &mut impl SecretVault) {
     let attributes = SecretAttributes::new(SecretType::Buffer, SecretPersistence::Ephemeral, 24u32);
     let secret_str = "5f791cc52297f62c7b8829b15f828acbdb3c613371d21aa1";
     let secret = vault
-        .secret_import(decode(secret_str).unwrap().as_slice(), attributes)
+        .secret_import(SecretKey::new(decode(secret_str).unwrap()), attributes)
         .await
         .unwrap();
 
@@ -100,3 +101,16 @@ pub async fn secret_attributes_get(vault: &mut impl SecretVault) {
         attributes
     );
 }
+
+pub async fn secret_export_is_redacted(vault: &mut impl SecretVault) {
+    let attributes = SecretAttributes::new(
+        SecretType::X25519,
+        SecretPersistence::Ephemeral,
+        CURVE25519_SECRET_LENGTH_U32,
+    );
+    let key_id = vault.secret_generate(attributes).await.unwrap();
+    let secret = vault.secret_export(&key_id).await.unwrap();
+    assert_redacted(&secret, secret.as_ref());
+    let entry = VaultEntry::new(attributes, secret.clone());
+    assert_redacted(&entry, secret.as_ref());
+}
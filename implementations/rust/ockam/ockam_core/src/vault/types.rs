@@ -66,7 +66,10 @@ cfg_if! {
 }
 
 /// Binary representation of a Secret.
-#[derive(Serialize, Deserialize, Clone, Zeroize)]
+///
+/// Its `Debug` formatting omits the key, and it is deliberately not
+/// serializable, so that it ends up neither in logs nor in messages.
+#[derive(Clone, Zeroize)]
 #[zeroize(drop)]
 pub struct SecretKey(SecretKeyVec);
 
@@ -96,6 +99,46 @@ impl AsRef<[u8]> for SecretKey {
     }
 }
 
+/// Hash of a completed key exchange, which binds a channel to the
+/// handshake that created it.
+///
+/// Like a [`SecretKey`], it is zeroized when dropped, compared in constant
+/// time and omitted from `Debug` formatting.
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+pub struct HandshakeHash([u8; 32]);
+
+impl HandshakeHash {
+    /// Create a new handshake hash.
+    pub fn new(h: [u8; 32]) -> Self {
+        Self(h)
+    }
+
+    /// Copy the hash, e.g. to sign it.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl core::fmt::Debug for HandshakeHash {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.pad("<handshake hash omitted>")
+    }
+}
+
+impl Eq for HandshakeHash {}
+impl PartialEq for HandshakeHash {
+    fn eq(&self, o: &Self) -> bool {
+        subtle::ConstantTimeEq::ct_eq(&self.0[..], &o.0[..]).into()
+    }
+}
+
+impl AsRef<[u8]> for HandshakeHash {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
 /// A public key.
 #[derive(Encode, Decode, Serialize, Deserialize, Clone, Debug, Zeroize)]
 #[zeroize(drop)]
@@ -0,0 +1,312 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use minicbor::{Decode, Decoder, Encode};
+use ockam::{route, Address, Context, Route, TcpTransport, TCP};
+use ockam_api::config::cli::{self, NodeConfig};
+use ockam_api::error::ApiError;
+use ockam_api::nodes::models::base::NodeStatus;
+use ockam_api::nodes::models::credentials::GetCredentialRequest;
+use ockam_api::nodes::models::portal::{CreateInlet, InletList, InletStatus};
+use ockam_api::nodes::NODEMANAGER_ADDR;
+use ockam_core::api::{self, Request, RequestBuilder, Response, Status};
+use ockam_core::Result;
+use ockam_multiaddr::MultiAddr;
+use ockam_node::tokio::sync::Mutex;
+use serde::Serialize;
+
+/// Default time to wait for a node to answer a request.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Health of a node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct NodeHealth {
+    /// The name of the node.
+    pub name: String,
+    /// The port of the node's API.
+    pub port: u16,
+    /// Whether the node answers requests.
+    pub state: NodeState,
+}
+
+/// State of a node, as seen through its API.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum NodeState {
+    /// The node answered a status request.
+    Running {
+        /// The process id of the node.
+        pid: i32,
+        /// The number of workers running on the node.
+        workers: u32,
+    },
+    /// The node did not answer a status request.
+    Down {
+        /// Why the node could not be reached.
+        reason: String,
+    },
+}
+
+impl NodeState {
+    /// Whether the node is running.
+    pub fn is_running(&self) -> bool {
+        matches!(self, NodeState::Running { .. })
+    }
+}
+
+/// A tunnel is a TCP inlet of a node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Tunnel {
+    /// The alias of the inlet.
+    pub alias: String,
+    /// The address the inlet listens on.
+    pub bind_addr: String,
+}
+
+impl From<InletStatus<'_>> for Tunnel {
+    fn from(s: InletStatus<'_>) -> Self {
+        Self {
+            alias: s.alias.to_string(),
+            bind_addr: s.bind_addr.to_string(),
+        }
+    }
+}
+
+/// Manage the local nodes of the `ockam` CLI.
+pub struct Desktop {
+    ctx: Context,
+    tcp: TcpTransport,
+    /// The `localhost:<port>` addresses of the nodes connected to.
+    connected: Mutex<BTreeSet<String>>,
+    config_dir: PathBuf,
+    timeout: Duration,
+}
+
+impl Desktop {
+    /// Manage the nodes of the default `ockam` CLI configuration.
+    ///
+    /// This starts a TCP transport on the given node.
+    pub async fn create(ctx: &Context) -> Result<Self> {
+        let dirs = cli::OckamConfig::directories();
+        Self::with_config_dir(ctx, dirs.config_dir()).await
+    }
+
+    /// Manage the nodes of the `ockam` CLI configuration found in `config_dir`.
+    ///
+    /// This starts a TCP transport on the given node.
+    pub async fn with_config_dir(ctx: &Context, config_dir: impl Into<PathBuf>) -> Result<Self> {
+        let ctx = ctx.new_detached(Address::random_local()).await?;
+        let tcp = TcpTransport::create(&ctx).await?;
+        Ok(Self {
+            ctx,
+            tcp,
+            connected: Mutex::new(BTreeSet::new()),
+            config_dir: config_dir.into(),
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Set the time to wait for a node to answer a request.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Return the nodes known to the CLI.
+    ///
+    /// The configuration is read again on every call, so that nodes created
+    /// or deleted with the CLI are picked up.
+    pub fn nodes(&self) -> Result<Vec<NodeConfig>> {
+        let path = self.config_dir.join("config.json");
+        let json = match std::fs::read_to_string(&path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(ApiError::message(e)),
+        };
+        let config: cli::OckamConfig = serde_json::from_str(&json).map_err(ApiError::message)?;
+        Ok(config
+            .nodes
+            .into_iter()
+            .map(|(name, mut node)| {
+                node.name = name;
+                node
+            })
+            .collect())
+    }
+
+    /// Return the health of a node.
+    pub async fn health(&self, name: &str) -> Result<NodeHealth> {
+        let node = self.node(name)?;
+        Ok(self.node_health(&node).await)
+    }
+
+    /// Return the health of all nodes.
+    pub async fn health_all(&self) -> Result<Vec<NodeHealth>> {
+        let mut health = Vec::new();
+        for node in self.nodes()? {
+            health.push(self.node_health(&node).await);
+        }
+        Ok(health)
+    }
+
+    /// Return the tunnels of a node.
+    pub async fn tunnels(&self, name: &str) -> Result<Vec<Tunnel>> {
+        let node = self.node(name)?;
+        self.node_tunnels(&node).await
+    }
+
+    /// Open a tunnel listening on `bind_addr` and forwarding to `outlet_route`.
+    ///
+    /// Passing the `alias` of a closed tunnel reopens it under the same name.
+    pub async fn open_tunnel(
+        &self,
+        name: &str,
+        bind_addr: &str,
+        outlet_route: &MultiAddr,
+        alias: Option<&str>,
+    ) -> Result<Tunnel> {
+        let node = self.node(name)?;
+        let req = Request::post("/node/inlet").body(CreateInlet::new(
+            bind_addr,
+            outlet_route.to_string(),
+            alias.map(|a| a.to_string().into()),
+            false,
+        ));
+        let buf = self.request(&node, "create inlet", req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        let status: InletStatus = dec.decode()?;
+        if res.status() != Some(Status::Ok) {
+            let reason = status
+                .payload
+                .as_deref()
+                .unwrap_or("failed to create inlet");
+            return Err(ApiError::generic(reason));
+        }
+        Ok(status.into())
+    }
+
+    /// Close the tunnel with the given alias.
+    pub async fn close_tunnel(&self, name: &str, alias: &str) -> Result<()> {
+        let node = self.node(name)?;
+        let req = Request::delete(format!("/node/inlet/{}", alias));
+        let buf = self.request(&node, "delete inlet", req).await?;
+        api::check_response(&mut Decoder::new(&buf))
+    }
+
+    /// Get a credential for the node from its project authority.
+    pub async fn enroll(&self, name: &str) -> Result<()> {
+        let node = self.node(name)?;
+        let req =
+            Request::post("/node/credentials/actions/get").body(GetCredentialRequest::new(true));
+        let buf = self.request(&node, "enroll", req).await?;
+        api::check_response(&mut Decoder::new(&buf))
+    }
+}
+
+impl Desktop {
+    fn node(&self, name: &str) -> Result<NodeConfig> {
+        self.nodes()?
+            .into_iter()
+            .find(|n| n.name == name)
+            .ok_or_else(|| ApiError::message(format!("node {} does not exist", name)))
+    }
+
+    pub(crate) async fn node_health(&self, node: &NodeConfig) -> NodeHealth {
+        let state = match self.node_status(node).await {
+            Ok((pid, workers)) => NodeState::Running { pid, workers },
+            Err(e) => NodeState::Down {
+                reason: e.to_string(),
+            },
+        };
+        NodeHealth {
+            name: node.name.clone(),
+            port: node.port,
+            state,
+        }
+    }
+
+    async fn node_status(&self, node: &NodeConfig) -> Result<(i32, u32)> {
+        let buf = self
+            .request(node, "node status", Request::get("/node"))
+            .await?;
+        let status: NodeStatus = decode_response(&buf)?;
+        Ok((status.pid, status.workers))
+    }
+
+    pub(crate) async fn node_tunnels(&self, node: &NodeConfig) -> Result<Vec<Tunnel>> {
+        let buf = self
+            .request(node, "list inlets", Request::get("/node/inlet"))
+            .await?;
+        let list: InletList = decode_response(&buf)?;
+        Ok(list
+            .list
+            .into_iter()
+            .filter(|s| s.payload.is_none())
+            .map(Tunnel::from)
+            .collect())
+    }
+
+    /// Send a request to the node manager of a node and return the raw response.
+    ///
+    /// The connection to a node is shared by the requests to it. It is
+    /// opened again after a failed request, so that nodes which were
+    /// restarted in the meantime are reached.
+    async fn request<T>(
+        &self,
+        node: &NodeConfig,
+        label: &str,
+        req: RequestBuilder<'_, T>,
+    ) -> Result<Vec<u8>>
+    where
+        T: Encode<()>,
+    {
+        let peer = format!("localhost:{}", node.port);
+        {
+            let mut connected = self.connected.lock().await;
+            if !connected.contains(&peer) {
+                self.tcp.connect(&peer).await?;
+                connected.insert(peer.clone());
+            }
+        }
+        let route = route![(TCP, peer.as_str()), NODEMANAGER_ADDR];
+        let res = self.request_impl(route, label, req).await;
+        if res.is_err() && self.connected.lock().await.remove(&peer) {
+            if let Err(e) = self.tcp.disconnect(&peer).await {
+                debug!(%peer, %e, "Failed to disconnect from node");
+            }
+        }
+        res
+    }
+
+    async fn request_impl<T>(
+        &self,
+        route: Route,
+        label: &str,
+        req: RequestBuilder<'_, T>,
+    ) -> Result<Vec<u8>>
+    where
+        T: Encode<()>,
+    {
+        let mut ctx = self.ctx.new_detached(Address::random_local()).await?;
+        trace!(%route, %label, "Sending request");
+        let req = req.timeout(self.timeout).to_vec()?;
+        ctx.send(route, req).await?;
+        let buf = ctx
+            .receive_duration_timeout::<Vec<u8>>(self.timeout)
+            .await?
+            .take()
+            .body();
+        Ok(buf)
+    }
+}
+
+fn decode_response<'a, T>(buf: &'a [u8]) -> Result<T>
+where
+    T: Decode<'a, ()>,
+{
+    let mut dec = Decoder::new(buf);
+    api::check_response(&mut dec)?;
+    Ok(dec.decode()?)
+}
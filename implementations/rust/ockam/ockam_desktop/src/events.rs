@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ockam_node::tokio;
+use ockam_node::tokio::sync::mpsc;
+use serde::Serialize;
+
+use crate::{Desktop, NodeState, Tunnel};
+
+/// A change in the health of a node or of one of its tunnels.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// The node answers requests.
+    NodeUp {
+        /// The name of the node.
+        node: String,
+        /// The process id of the node.
+        pid: i32,
+    },
+    /// The node stopped answering requests, or was deleted.
+    NodeDown {
+        /// The name of the node.
+        node: String,
+        /// Why the node could not be reached.
+        reason: String,
+    },
+    /// A tunnel was opened.
+    TunnelUp {
+        /// The name of the node.
+        node: String,
+        /// The tunnel.
+        tunnel: Tunnel,
+    },
+    /// A tunnel was closed, or its node went down.
+    TunnelDown {
+        /// The name of the node.
+        node: String,
+        /// The alias of the tunnel.
+        alias: String,
+    },
+}
+
+/// The state of all nodes at a point in time.
+#[derive(Default)]
+struct Snapshot {
+    nodes: BTreeMap<String, NodeSnapshot>,
+}
+
+struct NodeSnapshot {
+    state: NodeState,
+    tunnels: BTreeMap<String, Tunnel>,
+}
+
+impl Snapshot {
+    async fn take(desktop: &Desktop) -> Self {
+        let nodes = match desktop.nodes() {
+            Ok(nodes) => nodes,
+            Err(e) => {
+                warn!(%e, "Failed to read the nodes configuration");
+                return Self::default();
+            }
+        };
+        let mut snapshot = Self::default();
+        for node in nodes {
+            let health = desktop.node_health(&node).await;
+            let tunnels = if health.state.is_running() {
+                match desktop.node_tunnels(&node).await {
+                    Ok(tunnels) => tunnels.into_iter().map(|t| (t.alias.clone(), t)).collect(),
+                    Err(e) => {
+                        debug!(node = %node.name, %e, "Failed to list tunnels");
+                        BTreeMap::new()
+                    }
+                }
+            } else {
+                BTreeMap::new()
+            };
+            snapshot.nodes.insert(
+                node.name,
+                NodeSnapshot {
+                    state: health.state,
+                    tunnels,
+                },
+            );
+        }
+        snapshot
+    }
+
+    /// Return the events turning `self` into `next`.
+    fn diff(&self, next: &Snapshot) -> Vec<Event> {
+        let mut events = Vec::new();
+        for (name, node) in &next.nodes {
+            let prev = self.nodes.get(name);
+            let was_running = prev.map(|p| p.state.is_running());
+            match &node.state {
+                NodeState::Running { pid, .. } if was_running != Some(true) => {
+                    events.push(Event::NodeUp {
+                        node: name.clone(),
+                        pid: *pid,
+                    })
+                }
+                NodeState::Down { reason } if was_running != Some(false) => {
+                    events.push(Event::NodeDown {
+                        node: name.clone(),
+                        reason: reason.clone(),
+                    })
+                }
+                _ => {}
+            }
+            let prev_tunnels = prev.map(|p| &p.tunnels);
+            for (alias, tunnel) in &node.tunnels {
+                if prev_tunnels.and_then(|t| t.get(alias)) != Some(tunnel) {
+                    events.push(Event::TunnelUp {
+                        node: name.clone(),
+                        tunnel: tunnel.clone(),
+                    })
+                }
+            }
+            for alias in prev_tunnels.into_iter().flat_map(|t| t.keys()) {
+                if !node.tunnels.contains_key(alias) {
+                    events.push(Event::TunnelDown {
+                        node: name.clone(),
+                        alias: alias.clone(),
+                    })
+                }
+            }
+        }
+        for (name, node) in &self.nodes {
+            if next.nodes.contains_key(name) {
+                continue;
+            }
+            for alias in node.tunnels.keys() {
+                events.push(Event::TunnelDown {
+                    node: name.clone(),
+                    alias: alias.clone(),
+                })
+            }
+            if node.state.is_running() {
+                events.push(Event::NodeDown {
+                    node: name.clone(),
+                    reason: "node was deleted".to_string(),
+                })
+            }
+        }
+        events
+    }
+}
+
+impl Desktop {
+    /// Poll all nodes every `interval` and report the changes.
+    ///
+    /// The first poll reports the current state of every node and tunnel.
+    /// Polling stops when the receiver is dropped.
+    pub fn watch(self: Arc<Self>, interval: Duration) -> mpsc::Receiver<Event> {
+        let (tx, rx) = mpsc::channel(64);
+        tokio::spawn(async move {
+            let mut prev = Snapshot::default();
+            loop {
+                let next = Snapshot::take(&self).await;
+                for event in prev.diff(&next) {
+                    if tx.send(event).await.is_err() {
+                        return;
+                    }
+                }
+                prev = next;
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {}
+                    _ = tx.closed() => return,
+                }
+            }
+        });
+        rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn running(tunnels: &[&str]) -> NodeSnapshot {
+        NodeSnapshot {
+            state: NodeState::Running { pid: 1, workers: 1 },
+            tunnels: tunnels
+                .iter()
+                .map(|a| {
+                    let t = Tunnel {
+                        alias: a.to_string(),
+                        bind_addr: "127.0.0.1:8080".to_string(),
+                    };
+                    (a.to_string(), t)
+                })
+                .collect(),
+        }
+    }
+
+    fn down() -> NodeSnapshot {
+        NodeSnapshot {
+            state: NodeState::Down {
+                reason: "refused".to_string(),
+            },
+            tunnels: BTreeMap::new(),
+        }
+    }
+
+    fn snapshot(nodes: Vec<(&str, NodeSnapshot)>) -> Snapshot {
+        Snapshot {
+            nodes: nodes.into_iter().map(|(n, s)| (n.to_string(), s)).collect(),
+        }
+    }
+
+    #[test]
+    fn first_poll_reports_everything() {
+        let next = snapshot(vec![("n1", running(&["t1"])), ("n2", down())]);
+        let events = Snapshot::default().diff(&next);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[0], Event::NodeUp { node, .. } if node == "n1"));
+        assert!(matches!(&events[1], Event::TunnelUp { tunnel, .. } if tunnel.alias == "t1"));
+        assert!(matches!(&events[2], Event::NodeDown { node, .. } if node == "n2"));
+    }
+
+    #[test]
+    fn unchanged_state_reports_nothing() {
+        let prev = snapshot(vec![("n1", running(&["t1"])), ("n2", down())]);
+        let next = snapshot(vec![("n1", running(&["t1"])), ("n2", down())]);
+        assert!(prev.diff(&next).is_empty());
+    }
+
+    #[test]
+    fn node_going_down_closes_its_tunnels() {
+        let prev = snapshot(vec![("n1", running(&["t1", "t2"]))]);
+        let next = snapshot(vec![("n1", down())]);
+        let events = prev.diff(&next);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[0], Event::NodeDown { .. }));
+        assert!(matches!(&events[1], Event::TunnelDown { alias, .. } if alias == "t1"));
+        assert!(matches!(&events[2], Event::TunnelDown { alias, .. } if alias == "t2"));
+    }
+
+    #[test]
+    fn deleted_node_is_reported_down() {
+        let prev = snapshot(vec![("n1", running(&["t1"]))]);
+        let events = prev.diff(&Snapshot::default());
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], Event::TunnelDown { .. }));
+        assert!(matches!(&events[1], Event::NodeDown { .. }));
+    }
+}
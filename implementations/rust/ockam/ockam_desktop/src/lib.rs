@@ -0,0 +1,25 @@
+//! Library for desktop and tray applications managing local Ockam nodes.
+//!
+//! Nodes are created and indexed by the `ockam` CLI. This crate reads the
+//! CLI's node index and talks to the nodes' APIs directly, so that a GUI can
+//! show their health, open and close tunnels (TCP inlets) and enroll nodes
+//! without shelling out to the CLI.
+//!
+//! [`Desktop::watch`] polls all nodes and reports changes as a stream of
+//! [`Event`]s.
+#![warn(
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications
+)]
+
+#[macro_use]
+extern crate tracing;
+
+mod desktop;
+mod events;
+
+pub use desktop::*;
+pub use events::*;
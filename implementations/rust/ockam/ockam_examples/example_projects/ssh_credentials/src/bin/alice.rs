@@ -1,6 +1,6 @@
 use credentials_example::{BOB_LISTENER_ADDRESS, BOB_TCP_ADDRESS, ECHOER};
 use ockam::identity::{Identity, IdentityTrait, TrustEveryonePolicy};
-use ockam::vault::{SecretAttributes, SecretPersistence, SecretType, SecretVault, Vault};
+use ockam::vault::{SecretAttributes, SecretKey, SecretPersistence, SecretType, SecretVault, Vault};
 use ockam::{route, Context, Result, TcpTransport, TCP};
 use std::{env, fs};
 
@@ -24,7 +24,7 @@ async fn main(mut ctx: Context) -> Result<()> {
 
     let secret_key = vault
         .secret_import(
-            secret_key.as_ref(),
+            SecretKey::new(secret_key.as_ref().to_vec()),
             SecretAttributes::new(SecretType::Ed25519, SecretPersistence::Ephemeral, 32),
         )
         .await?;
@@ -7,7 +7,8 @@ use lazy_static::lazy_static;
 use ockam_core::compat::collections::BTreeMap;
 use ockam_core::compat::sync::Arc;
 use ockam_core::vault::{
-    AsymmetricVault, Hasher, KeyId, PublicKey, SecretAttributes, SecretVault, SymmetricVault,
+    AsymmetricVault, Hasher, KeyId, PublicKey, SecretAttributes, SecretKey, SecretVault,
+    SymmetricVault,
 };
 use ockam_core::{Error, Result};
 use ockam_vault::Vault;
@@ -179,7 +180,10 @@ pub extern "C" fn ockam_vault_secret_import(
 
             let secret_data = unsafe { core::slice::from_raw_parts(input, input_length as usize) };
 
-            let key_id = entry.vault.secret_import(secret_data, atts).await?;
+            let key_id = entry
+                .vault
+                .secret_import(SecretKey::new(secret_data.to_vec()), atts)
+                .await?;
 
             let index = entry.insert(key_id).await;
 
@@ -2,7 +2,7 @@
 use crate::change::IdentityChange::{CreateKey, RotateKey};
 use crate::change::{IdentitySignedChange, SignatureType};
 use crate::{
-    ChangeIdentifier, IdentityError, IdentityIdentifier, IdentityStateConst, IdentityVault,
+    ChangeIdentifier, IdentityError, IdentityIdentifier, IdentityStateConst, IdentityVerifier,
 };
 use core::cmp::Ordering;
 use minicbor::{Decode, Encode};
@@ -90,7 +90,7 @@ impl IdentityChangeHistory {
 
     pub async fn compute_identity_id(
         &self,
-        vault: &impl IdentityVault,
+        vault: &impl IdentityVerifier,
     ) -> Result<IdentityIdentifier> {
         let root_public_key = self.get_first_root_public_key()?;
 
@@ -126,7 +126,7 @@ impl IdentityChangeHistory {
         self.get_public_key(IdentityStateConst::ROOT_LABEL)
     }
 
-    pub async fn verify_all_existing_changes(&self, vault: &impl IdentityVault) -> Result<bool> {
+    pub async fn verify_all_existing_changes(&self, vault: &impl IdentityVerifier) -> Result<bool> {
         for i in 0..self.0.len() {
             let existing_changes = &self.as_ref()[..i];
             let new_change = &self.as_ref()[i];
@@ -192,7 +192,7 @@ impl IdentityChangeHistory {
     pub(crate) async fn verify_change(
         existing_changes: &[IdentitySignedChange],
         new_change: &IdentitySignedChange,
-        vault: &impl IdentityVault,
+        vault: &impl IdentityVerifier,
     ) -> Result<bool> {
         let change_binary = new_change
             .change()
@@ -6,14 +6,35 @@ mod listener;
 pub(crate) use listener::*;
 mod messages;
 pub(crate) use messages::*;
+mod registry;
+pub use registry::SecureChannelStats;
+pub(crate) use registry::*;
 mod trust_policy;
 pub use trust_policy::*;
 pub mod access_control;
 mod local_info;
 pub use local_info::*;
+mod key_exchanger;
+pub use key_exchanger::*;
+mod fragmentation;
+pub use fragmentation::FragmentationPolicy;
+pub(crate) use fragmentation::{fragment, Fragment, Reassembly};
+mod keepalive;
+pub(crate) use keepalive::KeepaliveWorker;
+pub use keepalive::{KeepalivePolicy, SecureChannelDead};
+mod rate_limit;
+pub use rate_limit::HandshakeRateLimit;
+pub(crate) use rate_limit::{handshake_source, HandshakeLimiter};
+mod resumption;
+pub use ockam_channel::{KeyAgreement, RekeyPolicy};
+pub(crate) use resumption::*;
+mod shared_secret;
+pub(crate) use shared_secret::*;
+mod psk;
+pub use psk::PreSharedKey;
 
 use crate::authenticated_storage::AuthenticatedStorage;
-use crate::{Identity, IdentityVault};
+use crate::{Identity, IdentityError, IdentityVault, PublicIdentity};
 use core::time::Duration;
 use ockam_core::compat::sync::Arc;
 use ockam_core::{Address, AsyncTryClone, Result, Route};
@@ -25,9 +46,93 @@ impl<V: IdentityVault> Identity<V> {
         trust_policy: impl TrustPolicy,
         storage: &impl AuthenticatedStorage,
     ) -> Result<()> {
+        self.create_secure_channel_listener_extended(
+            address,
+            trust_policy,
+            storage,
+            KeyAgreement::default(),
+        )
+        .await
+    }
+
+    /// Create a listener whose channels agree on their keys using the
+    /// given [`KeyAgreement`]
+    pub async fn create_secure_channel_listener_extended(
+        &self,
+        address: impl Into<Address>,
+        trust_policy: impl TrustPolicy,
+        storage: &impl AuthenticatedStorage,
+        key_agreement: KeyAgreement,
+    ) -> Result<()> {
+        let identity_clone = self.async_try_clone().await?;
+        let storage_clone = storage.async_try_clone().await?;
+        let rate_limit = *self.handshake_rate_limit.read().await;
+        let listener = IdentityChannelListener::new(
+            trust_policy,
+            identity_clone,
+            storage_clone,
+            key_agreement,
+        )
+        .with_rate_limit(rate_limit);
+        self.ctx.start_worker(address.into(), listener).await?;
+        Ok(())
+    }
+
+    /// Create a listener only accepting the channels created with the
+    /// pre-shared key `psk`, see [`PreSharedKey`]
+    pub async fn create_psk_secure_channel_listener(
+        &self,
+        address: impl Into<Address>,
+        psk: PreSharedKey,
+        trust_policy: impl TrustPolicy,
+        storage: &impl AuthenticatedStorage,
+    ) -> Result<()> {
+        let identity_clone = self.async_try_clone().await?;
+        let storage_clone = storage.async_try_clone().await?;
+        let rate_limit = *self.handshake_rate_limit.read().await;
+        let listener = IdentityChannelListener::new(
+            trust_policy,
+            identity_clone,
+            storage_clone,
+            KeyAgreement::default(),
+        )
+        .with_rate_limit(rate_limit)
+        .with_psk(psk);
+        self.ctx.start_worker(address.into(), listener).await?;
+        Ok(())
+    }
+
+    /// Create a listener with its own trust context: only the credentials
+    /// issued by `authorities` give attributes to the messages of its
+    /// channels, and the credentials presented back to their initiators are
+    /// those of the same authorities. With a pre-shared key `psk`, only the
+    /// channels created with it are accepted
+    pub async fn create_trust_context_secure_channel_listener(
+        &self,
+        address: impl Into<Address>,
+        authorities: Vec<PublicIdentity>,
+        psk: Option<PreSharedKey>,
+        key_agreement: KeyAgreement,
+        trust_policy: impl TrustPolicy,
+        storage: &impl AuthenticatedStorage,
+    ) -> Result<()> {
+        let issuers = authorities.iter().map(|a| a.identifier().clone()).collect();
+        self.add_trusted_authorities(authorities).await;
+
         let identity_clone = self.async_try_clone().await?;
         let storage_clone = storage.async_try_clone().await?;
-        let listener = IdentityChannelListener::new(trust_policy, identity_clone, storage_clone);
+        let rate_limit = *self.handshake_rate_limit.read().await;
+        let mut listener = IdentityChannelListener::new(
+            trust_policy,
+            identity_clone,
+            storage_clone,
+            key_agreement,
+        )
+        .with_rate_limit(rate_limit)
+        .with_trusted_issuers(issuers);
+        if let Some(psk) = psk {
+            listener = listener.with_psk(psk);
+        }
         self.ctx.start_worker(address.into(), listener).await?;
         Ok(())
     }
@@ -48,6 +153,8 @@ impl<V: IdentityVault> Identity<V> {
             storage_clone,
             Arc::new(trust_policy),
             Duration::from_secs(120),
+            KeyAgreement::default(),
+            None,
         )
         .await
     }
@@ -58,6 +165,33 @@ impl<V: IdentityVault> Identity<V> {
         trust_policy: impl TrustPolicy,
         storage: &impl AuthenticatedStorage,
         timeout: Duration,
+        key_agreement: KeyAgreement,
+    ) -> Result<Address> {
+        let identity_clone = self.async_try_clone().await?;
+        let storage_clone = storage.async_try_clone().await?;
+
+        DecryptorWorker::create_initiator(
+            &self.ctx,
+            route.into(),
+            identity_clone,
+            storage_clone,
+            Arc::new(trust_policy),
+            timeout,
+            key_agreement,
+            None,
+        )
+        .await
+    }
+
+    /// Create a secure channel to a listener with the same pre-shared key
+    /// `psk`, see [`PreSharedKey`]
+    pub async fn create_psk_secure_channel(
+        &self,
+        route: impl Into<Route>,
+        psk: PreSharedKey,
+        trust_policy: impl TrustPolicy,
+        storage: &impl AuthenticatedStorage,
+        timeout: Duration,
     ) -> Result<Address> {
         let identity_clone = self.async_try_clone().await?;
         let storage_clone = storage.async_try_clone().await?;
@@ -69,12 +203,191 @@ impl<V: IdentityVault> Identity<V> {
             storage_clone,
             Arc::new(trust_policy),
             timeout,
+            KeyAgreement::default(),
+            Some(psk),
         )
         .await
     }
 
+    /// Create a secure channel to the listener at `route` through relays
+    /// which only learn the next hop of its messages: a secure channel is
+    /// created to the listener of each relay in turn, over the channel to
+    /// the previous one, so that each relay removes one layer of
+    /// encryption. `relays` are the routes to the listeners of the relays,
+    /// each from the previous relay, and `route` is from the last one.
+    ///
+    /// The relays are not checked when their channels are created,
+    /// `trust_policy` can check them with the hops of the channel, e.g.
+    /// with a [`TrustHopsPolicy`]. The channels to the relays are stopped
+    /// with the returned channel.
+    pub async fn create_onion_secure_channel(
+        &self,
+        relays: Vec<Route>,
+        route: impl Into<Route>,
+        trust_policy: impl TrustPolicy,
+        storage: &impl AuthenticatedStorage,
+        timeout: Duration,
+        key_agreement: KeyAgreement,
+    ) -> Result<Address> {
+        let mut carriers: Vec<Address> = Vec::new();
+        let result = async {
+            for mut relay in relays {
+                if let Some(carrier) = carriers.last() {
+                    relay.modify().prepend(carrier.clone());
+                }
+                let carrier = self
+                    .create_secure_channel_extended(
+                        relay,
+                        TrustEveryonePolicy,
+                        storage,
+                        timeout,
+                        key_agreement,
+                    )
+                    .await?;
+                carriers.push(carrier);
+            }
+            let mut route = route.into();
+            if let Some(carrier) = carriers.last() {
+                route.modify().prepend(carrier.clone());
+            }
+            self.create_secure_channel_extended(
+                route,
+                trust_policy,
+                storage,
+                timeout,
+                key_agreement,
+            )
+            .await
+        }
+        .await;
+
+        match result {
+            Ok(channel) => {
+                self.secure_channels.add_carriers(&channel, carriers).await;
+                Ok(channel)
+            }
+            Err(e) => {
+                for carrier in carriers.iter().rev() {
+                    let _ = self.stop_secure_channel(carrier).await;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Set when secure channels created from now on replace their keys
+    pub async fn set_rekey_policy(&self, rekey_policy: RekeyPolicy) {
+        *self.rekey_policy.write().await = rekey_policy;
+    }
+
+    pub async fn rekey_policy(&self) -> RekeyPolicy {
+        *self.rekey_policy.read().await
+    }
+
+    /// Set how secure channels and listeners created from now on split
+    /// large messages, and reassemble those split by the other side
+    pub async fn set_fragmentation_policy(&self, policy: FragmentationPolicy) {
+        *self.fragmentation_policy.write().await = policy;
+    }
+
+    pub async fn fragmentation_policy(&self) -> FragmentationPolicy {
+        *self.fragmentation_policy.read().await
+    }
+
+    /// Limit the handshakes which listeners created from now on start, or
+    /// not with `None`. Handshakes over the limits are dropped
+    pub async fn set_handshake_rate_limit(&self, rate_limit: Option<HandshakeRateLimit>) {
+        *self.handshake_rate_limit.write().await = rate_limit;
+    }
+
+    /// Set how secure channels and listeners created from now on agree on
+    /// their keys
+    pub async fn set_key_exchangers(&self, key_exchangers: impl KeyExchangers<V>) {
+        *self.key_exchangers.write().await = Arc::new(key_exchangers);
+    }
+
+    pub(crate) async fn key_exchangers(&self) -> Arc<dyn KeyExchangers<V>> {
+        self.key_exchangers.read().await.clone()
+    }
+
+    /// Number of messages the secure channel carried so far in both
+    /// directions, `None` if this identity has no such channel
+    pub async fn secure_channel_messages(&self, channel: &Address) -> Option<usize> {
+        self.secure_channels.messages(channel).await
+    }
+
+    /// The identity on the other side of the secure channel and the
+    /// messages it carried so far, `None` if this identity has no such channel
+    pub async fn secure_channel_stats(&self, channel: &Address) -> Option<SecureChannelStats> {
+        self.secure_channels.stats(channel).await
+    }
+
+    /// Let the listeners of this identity issue tickets valid for
+    /// `lifetime` to the initiators of their channels, with which the next
+    /// channel to the same route is created in one round trip instead of a
+    /// full handshake. Initiators always use the tickets they are given.
+    /// `None` stops issuing tickets and forgets the ones issued
+    pub async fn set_secure_channel_resumption(&self, lifetime: Option<Duration>) {
+        self.resumption.set_lifetime(lifetime).await
+    }
+
+    /// Ping the other side of the secure channel whenever it was quiet for
+    /// the interval of `policy`, and consider the channel dead once too
+    /// many pings were not answered: its workers are then stopped and its
+    /// watchers get a [`SecureChannelDead`] message
+    pub async fn start_secure_channel_keepalive(
+        &self,
+        channel: &Address,
+        policy: KeepalivePolicy,
+    ) -> Result<()> {
+        let (ping_route, received) = self
+            .secure_channels
+            .keepalive(channel)
+            .await
+            .ok_or(IdentityError::UnknownSecureChannel)?;
+        let address = Address::random_local();
+        let tick = Address::random_local();
+        let worker = KeepaliveWorker::new(
+            channel.clone(),
+            ping_route,
+            tick.clone(),
+            received,
+            policy,
+            self.secure_channels.clone(),
+        );
+        self.secure_channels
+            .add_worker(channel, address.clone())
+            .await;
+        self.ctx.start_worker(vec![address, tick], worker).await
+    }
+
+    /// Send a [`SecureChannelDead`] message to `watcher` if the keepalive
+    /// of the secure channel finds it dead
+    pub async fn watch_secure_channel(
+        &self,
+        channel: &Address,
+        watcher: impl Into<Address>,
+    ) -> Result<()> {
+        if self
+            .secure_channels
+            .add_watcher(channel, watcher.into())
+            .await
+        {
+            Ok(())
+        } else {
+            Err(IdentityError::UnknownSecureChannel.into())
+        }
+    }
+
+    /// Stop the secure channel and the workers behind it on this side,
+    /// which destroy its keys
     pub async fn stop_secure_channel(&self, channel: &Address) -> Result<()> {
-        self.ctx.stop_worker(channel.clone()).await
+        self.ctx.stop_worker(channel.clone()).await?;
+        for worker in self.secure_channels.remove(channel).await {
+            // Some may have stopped already, e.g. after a failed handshake
+            let _ = self.ctx.stop_worker(worker).await;
+        }
+        Ok(())
     }
 }
 
@@ -86,9 +399,11 @@ mod test {
     use crate::Identity;
     use core::sync::atomic::{AtomicU8, Ordering};
     use core::time::Duration;
+    use ockam_channel::SecureChannelLocalInfo;
     use ockam_core::compat::sync::Arc;
     use ockam_core::{route, Any, Result, Routed, Worker};
     use ockam_node::{Context, WorkerBuilder};
+    use ockam_transport_tcp::{TcpTransport, TCP};
     use ockam_vault::Vault;
     use tokio::time::sleep;
 
@@ -139,6 +454,475 @@ mod test {
         ctx.stop().await
     }
 
+    #[ockam_macros::test]
+    async fn test_channel_survives_reconnection(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        let tcp = TcpTransport::create(ctx).await?;
+        let bob_address = tcp.listen("127.0.0.1:0").await?.to_string();
+
+        bob.create_secure_channel_listener("bob_listener", TrustEveryonePolicy, &bob_storage)
+            .await?;
+
+        let alice_channel = alice
+            .create_secure_channel(
+                route![(TCP, bob_address.clone()), "bob_listener"],
+                TrustEveryonePolicy,
+                &alice_storage,
+            )
+            .await?;
+
+        for i in 0..2 {
+            if i == 1 {
+                // Drop the connection the channel was established over
+                tcp.disconnect(&bob_address).await?;
+                sleep(Duration::from_millis(100)).await;
+            }
+
+            ctx.send(
+                route![alice_channel.clone(), ctx.address()],
+                "Hello, Bob!".to_string(),
+            )
+            .await?;
+            let msg = ctx.receive::<String>().await?.take();
+            let return_route = msg.return_route();
+            assert_eq!("Hello, Bob!", msg.body());
+
+            ctx.send(return_route, "Hello, Alice!".to_string()).await?;
+            let msg = ctx.receive::<String>().await?.take();
+            assert_eq!("Hello, Alice!", msg.body());
+        }
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_channel_rekeys(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        // Replace the keys in both directions every few messages
+        alice
+            .set_rekey_policy(RekeyPolicy::new(Some(200), None))
+            .await;
+        bob.set_rekey_policy(RekeyPolicy::new(Some(200), None))
+            .await;
+
+        bob.create_secure_channel_listener("bob_listener", TrustEveryonePolicy, &bob_storage)
+            .await?;
+
+        let alice_channel = alice
+            .create_secure_channel(route!["bob_listener"], TrustEveryonePolicy, &alice_storage)
+            .await?;
+
+        for _ in 0..20 {
+            ctx.send(
+                route![alice_channel.clone(), ctx.address()],
+                "Hello, Bob!".to_string(),
+            )
+            .await?;
+            let msg = ctx.receive::<String>().await?.take();
+            let return_route = msg.return_route();
+            assert_eq!("Hello, Bob!", msg.body());
+
+            ctx.send(return_route, "Hello, Alice!".to_string()).await?;
+            let msg = ctx.receive::<String>().await?.take();
+            assert_eq!("Hello, Alice!", msg.body());
+        }
+
+        ctx.stop().await
+    }
+
+    #[cfg(feature = "hybrid-kem")]
+    #[ockam_macros::test]
+    async fn test_hybrid_channel(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        bob.create_secure_channel_listener_extended(
+            "bob_listener",
+            TrustEveryonePolicy,
+            &bob_storage,
+            KeyAgreement::Hybrid,
+        )
+        .await?;
+
+        let alice_channel = alice
+            .create_secure_channel_extended(
+                route!["bob_listener"],
+                TrustIdentifierPolicy::new(bob.identifier().clone()),
+                &alice_storage,
+                Duration::from_secs(10),
+                KeyAgreement::Hybrid,
+            )
+            .await?;
+
+        ctx.send(
+            route![alice_channel, ctx.address()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        let local_info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+        assert_eq!(local_info.their_identity_id(), alice.identifier());
+
+        let return_route = msg.return_route();
+        ctx.send(return_route, "Hello, Alice!".to_string()).await?;
+        let msg = ctx.receive::<String>().await?.take();
+        assert_eq!("Hello, Alice!", msg.body());
+
+        ctx.stop().await
+    }
+
+    /// Noise XX key exchanges, counted
+    struct CountedKeyExchangers(Arc<AtomicU8>);
+
+    #[ockam_core::async_trait]
+    impl<V: IdentityVault> KeyExchangers<V> for CountedKeyExchangers {
+        async fn initiator(&self, vault: &V) -> Result<BoxedKeyExchanger> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            KeyExchangers::<V>::initiator(&XXKeyExchangers, vault).await
+        }
+
+        async fn responder(&self, vault: &V) -> Result<BoxedKeyExchanger> {
+            self.0.fetch_add(10, Ordering::Relaxed);
+            KeyExchangers::<V>::responder(&XXKeyExchangers, vault).await
+        }
+    }
+
+    #[ockam_macros::test]
+    async fn test_custom_key_exchangers(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        let exchanges = Arc::new(AtomicU8::new(0));
+        alice
+            .set_key_exchangers(CountedKeyExchangers(exchanges.clone()))
+            .await;
+        bob.set_key_exchangers(CountedKeyExchangers(exchanges.clone()))
+            .await;
+
+        bob.create_secure_channel_listener("bob_listener", TrustEveryonePolicy, &storage)
+            .await?;
+        let alice_channel = alice
+            .create_secure_channel(route!["bob_listener"], TrustEveryonePolicy, &storage)
+            .await?;
+
+        ctx.send(
+            route![alice_channel, ctx.address()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        assert_eq!("Hello, Bob!", msg.body());
+        assert_eq!(exchanges.load(Ordering::Relaxed), 11);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_stopped_channel_frees_its_workers(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        bob.create_secure_channel_listener("bob_listener", TrustEveryonePolicy, &bob_storage)
+            .await?;
+        let workers = ctx.list_workers().await?.len();
+
+        let alice_channel = alice
+            .create_secure_channel(route!["bob_listener"], TrustEveryonePolicy, &alice_storage)
+            .await?;
+
+        ctx.send(
+            route![alice_channel.clone(), ctx.address()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        let bob_channel = msg.return_route().next()?.clone();
+        ctx.send(msg.return_route(), "Hello, Alice!".to_string())
+            .await?;
+        ctx.receive::<String>().await?;
+
+        assert_eq!(alice.secure_channel_messages(&alice_channel).await, Some(2));
+        assert_eq!(bob.secure_channel_messages(&bob_channel).await, Some(2));
+        let stats = alice.secure_channel_stats(&alice_channel).await.unwrap();
+        assert_eq!(&stats.their_identity_id, bob.identifier());
+        assert_eq!((stats.sent, stats.received), (1, 1));
+
+        alice.stop_secure_channel(&alice_channel).await?;
+        bob.stop_secure_channel(&bob_channel).await?;
+        sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(alice.secure_channel_messages(&alice_channel).await, None);
+        assert_eq!(alice.secure_channel_stats(&alice_channel).await, None);
+        assert_eq!(ctx.list_workers().await?.len(), workers);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_channel_resumption(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        bob.set_secure_channel_resumption(Some(Duration::from_secs(60)))
+            .await;
+        bob.create_secure_channel_listener("bob_listener", TrustEveryonePolicy, &bob_storage)
+            .await?;
+
+        let mut key_exchanges = Vec::new();
+        for _ in 0..3 {
+            let alice_channel = alice
+                .create_secure_channel(route!["bob_listener"], TrustEveryonePolicy, &alice_storage)
+                .await?;
+            ctx.send(
+                route![alice_channel.clone(), ctx.address()],
+                "Hello, Bob!".to_string(),
+            )
+            .await?;
+            let msg = ctx.receive::<String>().await?.take();
+            let local_info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+            assert_eq!(local_info.their_identity_id(), alice.identifier());
+            let local_info = SecureChannelLocalInfo::find_info(msg.local_message())?;
+            key_exchanges.push(local_info.key_exchange().to_string());
+
+            // Wait for the ticket of this channel before dropping it
+            sleep(Duration::from_millis(100)).await;
+            alice.stop_secure_channel(&alice_channel).await?;
+        }
+
+        // Every ticket is used once, and each resumed channel gets a new one
+        assert_ne!(key_exchanges[0], "RESUMPTION");
+        assert_eq!(key_exchanges[1], "RESUMPTION");
+        assert_eq!(key_exchanges[2], "RESUMPTION");
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_psk_secure_channel(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        let psk = PreSharedKey::new("sensors", [7u8; 32])?;
+        bob.create_psk_secure_channel_listener(
+            "bob_listener",
+            psk.clone(),
+            TrustEveryonePolicy,
+            &bob_storage,
+        )
+        .await?;
+
+        let alice_channel = alice
+            .create_psk_secure_channel(
+                route!["bob_listener"],
+                psk,
+                TrustEveryonePolicy,
+                &alice_storage,
+                Duration::from_secs(5),
+            )
+            .await?;
+        ctx.send(
+            route![alice_channel, ctx.address()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        let local_info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+        assert_eq!(local_info.their_identity_id(), alice.identifier());
+        let local_info = SecureChannelLocalInfo::find_info(msg.local_message())?;
+        assert_eq!(local_info.key_exchange(), "PSK");
+
+        // Neither another key nor no key at all is accepted
+        let other = PreSharedKey::new("sensors", [8u8; 32])?;
+        let res = alice
+            .create_psk_secure_channel(
+                route!["bob_listener"],
+                other,
+                TrustEveryonePolicy,
+                &alice_storage,
+                Duration::from_secs(1),
+            )
+            .await;
+        assert!(res.is_err());
+        let res = alice
+            .create_secure_channel_extended(
+                route!["bob_listener"],
+                TrustEveryonePolicy,
+                &alice_storage,
+                Duration::from_secs(1),
+                KeyAgreement::default(),
+            )
+            .await;
+        assert!(res.is_err());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_keepalive_finds_dead_channels(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        bob.create_secure_channel_listener("bob_listener", TrustEveryonePolicy, &bob_storage)
+            .await?;
+        let alice_channel = alice
+            .create_secure_channel(route!["bob_listener"], TrustEveryonePolicy, &alice_storage)
+            .await?;
+
+        ctx.send(
+            route![alice_channel.clone(), ctx.address()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        let bob_channel = msg.return_route().next()?.clone();
+
+        let policy = KeepalivePolicy::new(Duration::from_millis(100), 2);
+        alice
+            .start_secure_channel_keepalive(&alice_channel, policy)
+            .await?;
+        alice
+            .watch_secure_channel(&alice_channel, ctx.address())
+            .await?;
+
+        // Answered keepalives keep the channel, and are not counted as
+        // messages
+        sleep(Duration::from_millis(600)).await;
+        assert_eq!(alice.secure_channel_messages(&alice_channel).await, Some(1));
+
+        bob.stop_secure_channel(&bob_channel).await?;
+        let dead = ctx.receive::<SecureChannelDead>().await?.take().body();
+        assert_eq!(dead.channel(), &alice_channel);
+        assert_eq!(alice.secure_channel_messages(&alice_channel).await, None);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_large_messages_are_fragmented(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+        alice
+            .set_fragmentation_policy(FragmentationPolicy::new(1000, 100_000))
+            .await;
+
+        bob.create_secure_channel_listener("bob_listener", TrustEveryonePolicy, &bob_storage)
+            .await?;
+        let alice_channel = alice
+            .create_secure_channel(route!["bob_listener"], TrustEveryonePolicy, &alice_storage)
+            .await?;
+
+        let large = "a".repeat(50_000);
+        ctx.send(route![alice_channel.clone(), ctx.address()], large.clone())
+            .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        let bob_channel = msg.return_route().next()?.clone();
+        assert_eq!(msg.body(), large);
+
+        // Fragmented messages are counted once
+        assert_eq!(alice.secure_channel_messages(&alice_channel).await, Some(1));
+        assert_eq!(bob.secure_channel_messages(&bob_channel).await, Some(1));
+
+        // Messages larger than the reassembly buffer of the other side
+        // are dropped
+        bob.set_fragmentation_policy(FragmentationPolicy::new(1000, 10_000))
+            .await;
+        let small_storage = InMemoryStorage::new();
+        bob.create_secure_channel_listener("bob_small", TrustEveryonePolicy, &small_storage)
+            .await?;
+        let alice_channel = alice
+            .create_secure_channel(route!["bob_small"], TrustEveryonePolicy, &alice_storage)
+            .await?;
+        ctx.send(route![alice_channel.clone(), ctx.address()], large)
+            .await?;
+        ctx.send(route![alice_channel, ctx.address()], "small".to_string())
+            .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        assert_eq!(msg.body(), "small");
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_keepalive_measures_rtt(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        bob.create_secure_channel_listener("bob_listener", TrustEveryonePolicy, &bob_storage)
+            .await?;
+        let alice_channel = alice
+            .create_secure_channel(route!["bob_listener"], TrustEveryonePolicy, &alice_storage)
+            .await?;
+
+        let stats = alice.secure_channel_stats(&alice_channel).await.unwrap();
+        assert_eq!(stats.rtt, None);
+
+        let policy = KeepalivePolicy::new(Duration::from_millis(100), 2).with_rtt();
+        alice
+            .start_secure_channel_keepalive(&alice_channel, policy)
+            .await?;
+
+        // Pings measuring the round-trip time are not counted as messages
+        sleep(Duration::from_millis(400)).await;
+        let stats = alice.secure_channel_stats(&alice_channel).await.unwrap();
+        assert!(stats.rtt.is_some());
+        assert_eq!(stats.sent, 0);
+        assert_eq!(stats.received, 0);
+
+        ctx.stop().await
+    }
+
     #[ockam_macros::test]
     async fn test_tunneled_secure_channel_works(ctx: &mut Context) -> Result<()> {
         let vault = Vault::create();
@@ -192,6 +976,143 @@ mod test {
         ctx.stop().await
     }
 
+    #[ockam_macros::test]
+    async fn test_tunneled_secure_channel_hops(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+        let small_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+        let carol = Identity::create(ctx, &vault).await?;
+
+        bob.create_secure_channel_listener("bob_listener", TrustEveryonePolicy, &bob_storage)
+            .await?;
+        carol
+            .create_secure_channel_listener("carol_listener", TrustEveryonePolicy, &small_storage)
+            .await?;
+
+        let alice_bob_channel = alice
+            .create_secure_channel(route!["bob_listener"], TrustEveryonePolicy, &alice_storage)
+            .await?;
+
+        // Going over a channel with an identity which is not trusted
+        let untrusted = alice
+            .create_secure_channel_extended(
+                route![alice_bob_channel.clone(), "carol_listener"],
+                TrustHopsPolicy::new(TrustIdentifierPolicy::new(carol.identifier().clone())),
+                &alice_storage,
+                Duration::from_secs(1),
+                KeyAgreement::default(),
+            )
+            .await;
+        assert!(untrusted.is_err());
+
+        let alice_carol_channel = alice
+            .create_secure_channel(
+                route![alice_bob_channel, "carol_listener"],
+                TrustHopsPolicy::new(TrustIdentifierPolicy::new(bob.identifier().clone())),
+                &alice_storage,
+            )
+            .await?;
+        let stats = alice
+            .secure_channel_stats(&alice_carol_channel)
+            .await
+            .unwrap();
+        assert_eq!(&stats.their_identity_id, carol.identifier());
+        assert_eq!(stats.hops, vec![bob.identifier().clone()]);
+
+        ctx.send(
+            route![alice_carol_channel, ctx.address()],
+            "Hello, Carol!".to_string(),
+        )
+        .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        let local_info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+        assert_eq!(local_info.their_identity_id(), alice.identifier());
+        // Carol's side got the request through the channel Bob's side has with Alice
+        assert_eq!(local_info.hops(), &[alice.identifier().clone()]);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_onion_secure_channel(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+        let small_storage = InMemoryStorage::new();
+        let dave_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+        let carol = Identity::create(ctx, &vault).await?;
+        let dave = Identity::create(ctx, &vault).await?;
+
+        bob.create_secure_channel_listener("bob_listener", TrustEveryonePolicy, &bob_storage)
+            .await?;
+        carol
+            .create_secure_channel_listener("carol_listener", TrustEveryonePolicy, &small_storage)
+            .await?;
+        dave.create_secure_channel_listener("dave_listener", TrustEveryonePolicy, &dave_storage)
+            .await?;
+
+        let trusted_relays = TrustMultiIdentifiersPolicy::new(vec![
+            bob.identifier().clone(),
+            carol.identifier().clone(),
+        ]);
+        let channel = alice
+            .create_onion_secure_channel(
+                vec![route!["bob_listener"], route!["carol_listener"]],
+                route!["dave_listener"],
+                TrustHopsPolicy::new(trusted_relays),
+                &alice_storage,
+                Duration::from_secs(10),
+                KeyAgreement::default(),
+            )
+            .await?;
+        let stats = alice.secure_channel_stats(&channel).await.unwrap();
+        assert_eq!(&stats.their_identity_id, dave.identifier());
+        assert_eq!(
+            stats.hops,
+            vec![bob.identifier().clone(), carol.identifier().clone()]
+        );
+
+        ctx.send(
+            route![channel.clone(), ctx.address()],
+            "Hello, Dave!".to_string(),
+        )
+        .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        let local_info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+        assert_eq!(local_info.their_identity_id(), alice.identifier());
+        ctx.send(msg.return_route(), "Hello, Alice!".to_string())
+            .await?;
+        assert_eq!(
+            ctx.receive::<String>().await?.take().body(),
+            "Hello, Alice!"
+        );
+
+        // The channels to the relays are stopped with the channel they carry
+        let mut alice_channels = 0;
+        for worker in ctx.list_workers().await? {
+            if alice.secure_channel_stats(&worker).await.is_some() {
+                alice_channels += 1;
+            }
+        }
+        assert_eq!(alice_channels, 3);
+        alice.stop_secure_channel(&channel).await?;
+        sleep(Duration::from_millis(100)).await;
+        for worker in ctx.list_workers().await? {
+            assert_eq!(alice.secure_channel_stats(&worker).await, None);
+        }
+
+        ctx.stop().await
+    }
+
     #[ockam_macros::test]
     async fn test_double_tunneled_secure_channel_works(ctx: &mut Context) -> Result<()> {
         let vault = Vault::create();
@@ -1,11 +1,14 @@
 use crate::authenticated_storage::AuthenticatedStorage;
+use crate::credential::AttributesStorageUtils;
 use crate::{
-    EncryptorWorker, Identity, IdentityChannelMessage, IdentityError, IdentityIdentifier,
-    IdentitySecureChannelLocalInfo, IdentityVault, PublicIdentity, SecureChannelTrustInfo,
-    TrustPolicy,
+    resumption_initiator, BoxedKeyExchanger, ChannelControl, ChannelCounters, EncryptorWorker,
+    FragmentationPolicy, Identity, IdentityChannelMessage, IdentityError, IdentityIdentifier,
+    IdentitySecureChannelLocalInfo, IdentityVault, KeyAgreement, KeyExchangers, PreSharedKey,
+    PublicIdentity, Reassembly, ResumingResponder, SecureChannelTrustInfo, TrustPolicy,
 };
 use core::future::Future;
 use core::pin::Pin;
+use core::sync::atomic::Ordering;
 use core::time::Duration;
 use ockam_channel::{
     CreateResponderChannelMessage, KeyExchangeCompleted, SecureChannel, SecureChannelDecryptor,
@@ -16,15 +19,17 @@ use ockam_core::compat::rand::random;
 use ockam_core::compat::{boxed::Box, sync::Arc, vec::Vec};
 use ockam_core::vault::Signature;
 use ockam_core::{
-    route, Address, Any, Decodable, Encodable, LocalMessage, Message, Result, Route, Routed,
-    TransportMessage, Worker,
+    route, Address, Any, AsyncTryClone, Decodable, Encodable, LocalMessage, Message, Result, Route,
+    Routed, TransportMessage, Worker,
 };
-use ockam_key_exchange_core::NewKeyExchanger;
-use ockam_key_exchange_xx::XXNewKeyExchanger;
 use ockam_node::Context;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
+/// How long an initiator waits for a resumed channel before starting a
+/// new one
+const RESUMPTION_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[derive(Serialize, Deserialize, Message)]
 pub(crate) struct AuthenticationConfirmation(pub Address);
 
@@ -52,13 +57,16 @@ struct InitiatorSendIdentity {
 struct ResponderWaitForIdentity {
     auth_hash: [u8; 32],
     local_secure_channel_address: Address,
+    local_secure_channel_decryptor_address: Address,
 }
 
 #[derive(Clone)]
 struct Initialized {
     local_secure_channel_address: Address,
     their_identity_id: IdentityIdentifier,
+    hops: Vec<IdentityIdentifier>,
     encryptor_address: Address,
+    counters: ChannelCounters,
 }
 
 enum State {
@@ -76,10 +84,25 @@ pub(crate) struct DecryptorWorker<V: IdentityVault, S: AuthenticatedStorage> {
     identity: Identity<V>,
     storage: S,
     trust_policy: Arc<dyn TrustPolicy>,
+    /// Identities at the other side of the secure channels this one goes
+    /// over, outermost first
+    hops: Vec<IdentityIdentifier>,
+    /// Route of an initiator, to resume the channel with the tickets it gets
+    route: Option<Route>,
+    /// Whether a responder issues a ticket to resume the channel
+    resumable: bool,
+    /// The authorities whose credentials give attributes to the messages
+    /// of the channel, any when not set
+    trusted_issuers: Option<Vec<IdentityIdentifier>>,
+    /// How the encryptor splits large messages
+    fragmentation: FragmentationPolicy,
+    /// Messages the other side sent in fragments
+    reassembly: Reassembly,
     state: Option<State>,
 }
 
 impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_initiator(
         ctx: &Context,
         route: Route,
@@ -87,22 +110,113 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
         storage: S,
         trust_policy: Arc<dyn TrustPolicy>,
         timeout: Duration,
+        key_agreement: KeyAgreement,
+        psk: Option<PreSharedKey>,
     ) -> Result<Address> {
+        // Resume the previous channel to the same route if we have a ticket
+        // for it, with a full handshake if that fails. Channels with a
+        // pre-shared key are not resumed, the ticket would replace the key
+        let ticket = match psk {
+            Some(_) => None,
+            None => identity.resumption.take(&route).await,
+        };
+        if let Some(ticket) = ticket {
+            let vault = identity.vault.async_try_clone().await?;
+            let initiator = BoxedKeyExchanger::new(resumption_initiator(vault, ticket));
+            let (self_address, mut child_ctx) = Self::start_initiator(
+                ctx,
+                route.clone(),
+                identity.async_try_clone().await?,
+                storage.async_try_clone().await?,
+                trust_policy.clone(),
+                key_agreement,
+                initiator,
+            )
+            .await?;
+            match child_ctx
+                .receive_timeout::<AuthenticationConfirmation>(
+                    timeout.min(RESUMPTION_TIMEOUT).as_secs(),
+                )
+                .await
+            {
+                Ok(confirmation) => return Ok(confirmation.take().body().0),
+                Err(e) => {
+                    warn!(
+                        "Could not resume SecureChannel to {}, starting a new one: {}",
+                        route, e
+                    );
+                    let _ = ctx.stop_worker(self_address).await;
+                }
+            }
+        }
+
+        let vault = identity.vault.async_try_clone().await?;
+        let initiator = match &psk {
+            Some(psk) => KeyExchangers::<V>::initiator(psk, &vault).await?,
+            None => identity.key_exchangers().await.initiator(&vault).await?,
+        };
+        let (_, mut child_ctx) = Self::start_initiator(
+            ctx,
+            route,
+            identity,
+            storage,
+            trust_policy,
+            key_agreement,
+            initiator,
+        )
+        .await?;
+
+        let encryptor_address = child_ctx
+            .receive_timeout::<AuthenticationConfirmation>(timeout.as_secs())
+            .await?
+            .take()
+            .body()
+            .0;
+
+        Ok(encryptor_address)
+    }
+
+    /// Start the worker of an initiator, returning its address and the
+    /// context receiving its [`AuthenticationConfirmation`]
+    async fn start_initiator(
+        ctx: &Context,
+        route: Route,
+        identity: Identity<V>,
+        storage: S,
+        trust_policy: Arc<dyn TrustPolicy>,
+        key_agreement: KeyAgreement,
+        initiator: BoxedKeyExchanger,
+    ) -> Result<(Address, Context)> {
         let child_address = Address::random_local();
-        let mut child_ctx = ctx.new_detached(child_address.clone()).await?;
+        let child_ctx = ctx.new_detached(child_address.clone()).await?;
 
         let self_address: Address = random();
 
+        // The channel goes over the channel at the start of the route, if any
+        let hops = match route.next() {
+            Ok(first) => identity.secure_channels.chain(first).await,
+            Err(_) => None,
+        }
+        .unwrap_or_default();
+
         let vault = identity.vault.async_try_clone().await?;
-        let initiator = XXNewKeyExchanger::new(vault.async_try_clone().await?)
-            .initiator()
-            .await?;
         // Create regular secure channel and set self address as first responder
         let custom_payload = self_address.encode()?;
+        let rekey_policy = identity.rekey_policy().await;
+        let fragmentation = identity.fragmentation_policy().await;
         let temp_ctx = ctx.new_detached(Address::random_local()).await?;
+        let channel_route = route.clone();
         let channel_future = Box::pin(async move {
-            SecureChannel::create_extended(&temp_ctx, route, Some(custom_payload), initiator, vault)
-                .await
+            SecureChannel::create_extended(
+                &temp_ctx,
+                channel_route,
+                Some(custom_payload),
+                initiator,
+                rekey_policy,
+                key_agreement,
+                vault,
+            )
+            .await
         });
 
         let state = State::InitiatorStartChannel(InitiatorStartChannel {
@@ -117,6 +231,12 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             identity,
             trust_policy,
             storage,
+            hops,
+            route: Some(route),
+            resumable: false,
+            trusted_issuers: None,
+            fragmentation,
+            reassembly: Reassembly::new(fragmentation),
             state: Some(state),
         };
 
@@ -127,25 +247,27 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             &self_address
         );
 
-        let encryptor_address = child_ctx
-            .receive_timeout::<AuthenticationConfirmation>(timeout.as_secs())
-            .await?
-            .take()
-            .body()
-            .0;
-
-        Ok(encryptor_address)
+        Ok((self_address, child_ctx))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn create_responder(
         ctx: &Context,
         identity: Identity<V>,
         storage: S,
         trust_policy: Arc<dyn TrustPolicy>,
+        key_agreement: KeyAgreement,
+        psk: Option<PreSharedKey>,
+        trusted_issuers: Option<Vec<IdentityIdentifier>>,
         msg: Routed<CreateResponderChannelMessage>,
     ) -> Result<()> {
         let return_route = msg.return_route();
         let mut onward_route = msg.onward_route();
+        // The channel goes over the channel the request came through, if any
+        let hops =
+            IdentitySecureChannelLocalInfo::find_info_from_list(msg.local_message().local_info())
+                .map(|info| info.chain())
+                .unwrap_or_default();
         let body = msg.body();
         // This is the address of Worker on the other end, that Initiator gave us to perform further negotiations.
         let custom_payload = body
@@ -157,6 +279,10 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
         let self_address: Address = random();
 
         let vault = identity.vault.async_try_clone().await?;
+        let key_exchangers = identity.key_exchangers().await;
+        let rekey_policy = identity.rekey_policy().await;
+        let fragmentation = identity.fragmentation_policy().await;
+        let resumption = identity.resumption.clone();
         let state = State::ResponderWaitForKex(ResponderWaitForKex {
             first_responder_address,
         });
@@ -168,6 +294,12 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             identity,
             trust_policy,
             storage,
+            hops,
+            route: None,
+            resumable: psk.is_none(),
+            trusted_issuers,
+            fragmentation,
+            reassembly: Reassembly::new(fragmentation),
             kex_callback_address: Some(kex_callback_address.clone()),
             state: Some(state),
         };
@@ -185,14 +317,21 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
 
         let regular_responder_address = Address::random_local();
 
-        let responder = XXNewKeyExchanger::new(vault.async_try_clone().await?)
-            .responder()
-            .await?;
+        let responder = match &psk {
+            Some(psk) => KeyExchangers::<V>::responder(psk, &vault).await?,
+            None => BoxedKeyExchanger::new(ResumingResponder::new(
+                key_exchangers.responder(&vault).await?,
+                resumption,
+                vault.async_try_clone().await?,
+            )),
+        };
 
         let vault = vault.async_try_clone().await?;
         let regular_decryptor =
             SecureChannelDecryptor::new_responder(responder, Some(kex_callback_address), vault)
-                .await?;
+                .await?
+                .with_rekey_policy(rekey_policy)
+                .with_key_agreement(key_agreement);
 
         ctx.start_worker(vec![regular_responder_address.clone()], regular_decryptor)
             .await?;
@@ -213,6 +352,7 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
         msg: Routed<<Self as Worker>::Message>,
         state: ResponderWaitForKex,
     ) -> Result<()> {
+        let local_secure_channel_decryptor_address = msg.return_route().recipient();
         let kex_msg = KeyExchangeCompleted::decode(msg.payload())?;
 
         // Prove we posses Identity key
@@ -236,6 +376,7 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
         self.state = Some(State::ResponderWaitForIdentity(ResponderWaitForIdentity {
             auth_hash: kex_msg.auth_hash(),
             local_secure_channel_address: kex_msg.address().clone(),
+            local_secure_channel_decryptor_address,
         }));
 
         Ok(())
@@ -292,7 +433,8 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             );
 
             // Check our TrustPolicy
-            let trust_info = SecureChannelTrustInfo::new(their_identity_id.clone());
+            let trust_info =
+                SecureChannelTrustInfo::new(their_identity_id.clone()).with_hops(self.hops.clone());
             let trusted = self.trust_policy.check(&trust_info).await?;
             if !trusted {
                 // TODO: Shutdown? Communicate error?
@@ -322,17 +464,41 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             debug!("Sent Authentication response");
 
             let encryptor_address = Address::random_local();
+            let workers = vec![
+                self.self_address.clone(),
+                state.channel.address(),
+                state.channel.decryptor_address(),
+            ];
+            let ping_route = route![
+                state.channel.address(),
+                remote_identity_secure_channel_address.clone()
+            ];
+            let counters = self
+                .identity
+                .secure_channels
+                .register(
+                    encryptor_address.clone(),
+                    workers,
+                    their_identity_id.clone(),
+                    self.hops.clone(),
+                    ping_route,
+                )
+                .await;
 
             self.state = Some(State::Initialized(Initialized {
                 local_secure_channel_address: state.channel.address(),
                 their_identity_id: their_identity_id.clone(),
+                hops: self.hops.clone(),
                 encryptor_address: encryptor_address.clone(),
+                counters: counters.clone(),
             }));
 
             let encryptor = EncryptorWorker::new(
                 self.is_initiator,
                 remote_identity_secure_channel_address,
                 state.channel.address(),
+                counters,
+                self.fragmentation,
             );
 
             ctx.start_worker(encryptor_address.clone(), encryptor)
@@ -406,7 +572,8 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             );
 
             // Check our TrustPolicy
-            let trust_info = SecureChannelTrustInfo::new(their_identity_id.clone());
+            let trust_info =
+                SecureChannelTrustInfo::new(their_identity_id.clone()).with_hops(self.hops.clone());
             let trusted = self.trust_policy.check(&trust_info).await?;
             if !trusted {
                 // TODO: Shutdown? Communicate error?
@@ -420,22 +587,53 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             let remote_identity_secure_channel_address = return_route.recipient();
 
             let encryptor_address = Address::random_local();
+            let workers = vec![
+                self.self_address.clone(),
+                state.local_secure_channel_address.clone(),
+                state.local_secure_channel_decryptor_address,
+            ];
+            let ping_route = route![
+                state.local_secure_channel_address.clone(),
+                remote_identity_secure_channel_address.clone()
+            ];
+            let counters = self
+                .identity
+                .secure_channels
+                .register(
+                    encryptor_address.clone(),
+                    workers,
+                    their_identity_id.clone(),
+                    self.hops.clone(),
+                    ping_route.clone(),
+                )
+                .await;
 
             self.state = Some(State::Initialized(Initialized {
                 local_secure_channel_address: state.local_secure_channel_address.clone(),
                 their_identity_id: their_identity_id.clone(),
+                hops: self.hops.clone(),
                 encryptor_address: encryptor_address.clone(),
+                counters: counters.clone(),
             }));
 
             let encryptor = EncryptorWorker::new(
                 self.is_initiator,
                 remote_identity_secure_channel_address,
                 state.local_secure_channel_address,
+                counters,
+                self.fragmentation,
             );
 
             ctx.start_worker(encryptor_address.clone(), encryptor)
                 .await?;
 
+            // Let the initiator resume this channel after it is dropped
+            if self.resumable {
+                if let Some(ticket) = self.identity.resumption.issue().await {
+                    ctx.send(ping_route, ChannelControl::Ticket(ticket)).await?;
+                }
+            }
+
             info!(
                 "Initialized IdentitySecureChannel Responder at local: {}, remote: {}",
                 &encryptor_address, &self.self_address
@@ -481,12 +679,46 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             return Err(IdentityError::UnknownChannelMsgDestination.into());
         }
 
+        state.counters.received.fetch_add(1, Ordering::Relaxed);
+
         let local_msg = msg.into_local_message();
         let local_info = local_msg.local_info().to_vec();
         let payload = local_msg.into_transport_message().payload;
 
-        // Forward to local workers
+        // Keepalives, tickets and fragments are addressed to this worker only
         let _ = onward_route.step()?;
+        let (onward_route, payload) = if onward_route.next().is_ok() {
+            (onward_route, payload)
+        } else {
+            let mut reassembled = None;
+            match ChannelControl::decode(&payload) {
+                Ok(ChannelControl::Ping) => {
+                    ctx.send(return_route.clone(), ChannelControl::Pong).await?
+                }
+                Ok(ChannelControl::RttPing(t)) => {
+                    ctx.send(return_route.clone(), ChannelControl::RttPong(t))
+                        .await?
+                }
+                Ok(ChannelControl::Ticket(ticket)) => {
+                    if let Some(route) = &self.route {
+                        debug!("Received a ticket to resume SecureChannel to {}", route);
+                        self.identity.resumption.store(route, ticket).await;
+                    }
+                }
+                Ok(ChannelControl::Fragment(fragment)) => {
+                    reassembled = self.reassembly.add(fragment);
+                }
+                _ => {}
+            }
+            match reassembled {
+                Some(message) => (message.onward_route, message.payload),
+                None => return Ok(()),
+            }
+        };
+
+        state.counters.messages.fetch_add(1, Ordering::Relaxed);
+
+        // Forward to local workers
 
         let return_route = return_route
             .modify()
@@ -495,10 +727,27 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
 
         let transport_msg = TransportMessage::v1(onward_route, return_route, payload);
 
+        // The attributes of a credential presented over the channel, if
+        // any and issued by an authority it trusts. A message is rather
+        // missing them than dropped
+        let attributes = AttributesStorageUtils::get_attributes_attested_by(
+            &state.their_identity_id,
+            self.trusted_issuers.as_deref(),
+            &self.storage,
+        )
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+
         // Mark message LocalInfo with IdentitySecureChannelLocalInfo,
         // replacing any pre-existing entries
-        let local_info =
-            IdentitySecureChannelLocalInfo::mark(local_info, state.their_identity_id.clone())?;
+        let local_info = IdentitySecureChannelLocalInfo::mark(
+            local_info,
+            state.their_identity_id.clone(),
+            state.hops.clone(),
+            attributes,
+        )?;
 
         let msg = LocalMessage::new(transport_msg, local_info);
 
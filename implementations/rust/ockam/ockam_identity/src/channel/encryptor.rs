@@ -1,6 +1,10 @@
+use crate::{fragment, ChannelCounters, FragmentationPolicy};
+use core::sync::atomic::Ordering;
 use ockam_core::async_trait;
 use ockam_core::compat::{boxed::Box, vec::Vec};
-use ockam_core::{Address, Any, LocalMessage, Result, Routed, TransportMessage, Worker};
+use ockam_core::{
+    route, Address, Any, Encodable, LocalMessage, Result, Routed, TransportMessage, Worker,
+};
 use ockam_node::Context;
 use tracing::debug;
 
@@ -8,6 +12,11 @@ pub(crate) struct EncryptorWorker {
     is_initiator: bool,
     remote_identity_secure_channel_address: Address,
     local_secure_channel_address: Address,
+    /// Traffic of the channel, shared with its decryptor
+    counters: ChannelCounters,
+    fragmentation: FragmentationPolicy,
+    /// Number of the next message sent in fragments
+    next_fragmented: u64,
 }
 
 impl EncryptorWorker {
@@ -15,11 +24,16 @@ impl EncryptorWorker {
         is_initiator: bool,
         remote_identity_secure_channel_address: Address,
         local_secure_channel_address: Address,
+        counters: ChannelCounters,
+        fragmentation: FragmentationPolicy,
     ) -> Self {
         Self {
             is_initiator,
             remote_identity_secure_channel_address,
             local_secure_channel_address,
+            counters,
+            fragmentation,
+            next_fragmented: 0,
         }
     }
 
@@ -37,12 +51,34 @@ impl EncryptorWorker {
             }
         );
 
+        self.counters.messages.fetch_add(1, Ordering::Relaxed);
+        self.counters.sent.fetch_add(1, Ordering::Relaxed);
+
         let mut onward_route = msg.onward_route();
         let return_route = msg.return_route();
         let payload = msg.payload().to_vec();
 
         // Send to the other party using local regular SecureChannel
         let _ = onward_route.step()?;
+
+        // Messages too large for the transports are sent in fragments,
+        // addressed to the decryptor which reassembles them
+        if payload.len() > self.fragmentation.max_fragment_len {
+            let id = self.next_fragmented;
+            self.next_fragmented = self.next_fragmented.wrapping_add(1);
+            let max_len = self.fragmentation.max_fragment_len;
+            let decryptor = route![
+                self.local_secure_channel_address.clone(),
+                self.remote_identity_secure_channel_address.clone()
+            ];
+            for f in fragment(id, onward_route, payload, max_len)? {
+                let transport_msg =
+                    TransportMessage::v1(decryptor.clone(), return_route.clone(), f.encode()?);
+                ctx.forward(LocalMessage::new(transport_msg, Vec::new()))
+                    .await?;
+            }
+            return Ok(());
+        }
         let onward_route = onward_route
             .modify()
             .prepend(self.remote_identity_secure_channel_address.clone())
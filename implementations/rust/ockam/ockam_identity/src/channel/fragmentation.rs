@@ -0,0 +1,275 @@
+use crate::ChannelControl;
+use ockam_core::compat::{collections::BTreeMap, vec::Vec};
+use ockam_core::{Decodable, Encodable, Message, Result, Route};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Messages reassembled at once, the oldest ones are dropped to reassemble
+/// new ones
+const MAX_PENDING: usize = 16;
+
+/// How secure channels split the messages too large for their transports,
+/// e.g. TCP which carries at most 64KiB, and how much of the messages split
+/// by the other side they buffer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FragmentationPolicy {
+    /// Messages whose payload is longer than this are sent in fragments
+    /// of this length
+    pub max_fragment_len: usize,
+    /// Bytes of incomplete messages buffered at once, the oldest ones are
+    /// dropped to buffer new ones
+    pub max_reassembly_len: usize,
+}
+
+impl FragmentationPolicy {
+    pub fn new(max_fragment_len: usize, max_reassembly_len: usize) -> Self {
+        Self {
+            max_fragment_len,
+            max_reassembly_len,
+        }
+    }
+}
+
+impl Default for FragmentationPolicy {
+    fn default() -> Self {
+        Self::new(48 * 1024, 4 * 1024 * 1024)
+    }
+}
+
+/// A part of a message sent in fragments
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Fragment {
+    /// The message it is a part of, numbered by the sender
+    id: u64,
+    index: u32,
+    count: u32,
+    data: Vec<u8>,
+}
+
+/// A message sent in fragments, without its return route which is that of
+/// its fragments
+#[derive(Serialize, Deserialize, Message)]
+pub(crate) struct Fragmented {
+    pub(crate) onward_route: Route,
+    pub(crate) payload: Vec<u8>,
+}
+
+/// The fragments of the message `id`, each at most `max_len` long
+pub(crate) fn fragment(
+    id: u64,
+    onward_route: Route,
+    payload: Vec<u8>,
+    max_len: usize,
+) -> Result<Vec<ChannelControl>> {
+    let message = Fragmented {
+        onward_route,
+        payload,
+    }
+    .encode()?;
+    let chunks = message.chunks(max_len.max(1));
+    let count = chunks.len() as u32;
+    Ok(chunks
+        .enumerate()
+        .map(|(index, data)| {
+            ChannelControl::Fragment(Fragment {
+                id,
+                index: index as u32,
+                count,
+                data: data.to_vec(),
+            })
+        })
+        .collect())
+}
+
+/// The fragments received of a message
+struct Pending {
+    count: u32,
+    fragments: BTreeMap<u32, Vec<u8>>,
+    len: usize,
+}
+
+/// Messages received in fragments, until all their fragments are received
+pub(crate) struct Reassembly {
+    max_len: usize,
+    /// Fragments of a message, as many as buffering `max_len` bytes needs
+    max_count: u32,
+    /// Bytes buffered for all the pending messages
+    len: usize,
+    /// By id, the oldest first as the sender numbers them in order
+    pending: BTreeMap<u64, Pending>,
+}
+
+impl Reassembly {
+    pub(crate) fn new(policy: FragmentationPolicy) -> Self {
+        let max_len = policy.max_reassembly_len;
+        let fragment_len = policy.max_fragment_len.max(1);
+        let max_count = (max_len + fragment_len - 1) / fragment_len;
+        Self {
+            max_len,
+            max_count: max_count.min(u32::MAX as usize) as u32,
+            len: 0,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Add a fragment, returning its message once all its fragments are
+    /// received
+    pub(crate) fn add(&mut self, fragment: Fragment) -> Option<Fragmented> {
+        let Fragment {
+            id,
+            index,
+            count,
+            data,
+        } = fragment;
+        if index >= count || count > self.max_count || data.is_empty() || data.len() > self.max_len
+        {
+            warn!("Dropping an invalid fragment of message {}", id);
+            self.drop_message(id);
+            return None;
+        }
+        // Make room for the fragment, dropping the oldest messages
+        while self.len + data.len() > self.max_len
+            || (self.pending.len() >= MAX_PENDING && !self.pending.contains_key(&id))
+        {
+            let oldest = *self.pending.keys().next()?;
+            warn!(
+                "Dropping message {}, reassembling it would buffer more than {} bytes or {} messages",
+                oldest, self.max_len, MAX_PENDING
+            );
+            self.drop_message(oldest);
+        }
+
+        let pending = self.pending.entry(id).or_insert_with(|| Pending {
+            count,
+            fragments: BTreeMap::new(),
+            len: 0,
+        });
+        if pending.count != count {
+            warn!(
+                "Dropping message {}, its fragments disagree on their count",
+                id
+            );
+            self.drop_message(id);
+            return None;
+        }
+        let len = data.len();
+        if let Some(previous) = pending.fragments.insert(index, data) {
+            pending.len -= previous.len();
+            self.len -= previous.len();
+        }
+        pending.len += len;
+        self.len += len;
+        if pending.fragments.len() < count as usize {
+            return None;
+        }
+
+        let pending = self.pending.remove(&id)?;
+        self.len -= pending.len;
+        let message = pending
+            .fragments
+            .into_values()
+            .flatten()
+            .collect::<Vec<u8>>();
+        match Fragmented::decode(&message) {
+            Ok(message) => Some(message),
+            Err(e) => {
+                warn!("Dropping message {} which can't be decoded: {}", id, e);
+                None
+            }
+        }
+    }
+
+    fn drop_message(&mut self, id: u64) {
+        if let Some(pending) = self.pending.remove(&id) {
+            self.len -= pending.len
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::route;
+
+    fn fragments(id: u64, payload: Vec<u8>, max_len: usize) -> Vec<Fragment> {
+        fragment(id, route!["app"], payload, max_len)
+            .unwrap()
+            .into_iter()
+            .map(|f| match f {
+                ChannelControl::Fragment(f) => f,
+                _ => panic!("not a fragment"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn messages_are_reassembled_in_any_order() {
+        let payload: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let mut fragments = fragments(1, payload.clone(), 100);
+        assert!(fragments.len() > 10);
+        fragments.reverse();
+
+        let mut reassembly = Reassembly::new(FragmentationPolicy::new(100, 10_000));
+        let mut messages = Vec::new();
+        for f in fragments {
+            messages.extend(reassembly.add(f));
+        }
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].onward_route, route!["app"]);
+        assert_eq!(messages[0].payload, payload);
+        assert_eq!(reassembly.len, 0);
+    }
+
+    #[test]
+    fn the_oldest_messages_are_dropped_to_bound_the_buffer() {
+        let mut reassembly = Reassembly::new(FragmentationPolicy::new(500, 1500));
+        let mut first = fragments(1, vec![1; 1000], 500);
+        let second = fragments(2, vec![2; 1000], 500);
+
+        // The first message is dropped to buffer the second one
+        let last = first.pop().unwrap();
+        for f in first {
+            assert!(reassembly.add(f).is_none());
+        }
+        let mut messages = Vec::new();
+        for f in second {
+            messages.extend(reassembly.add(f));
+        }
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].payload, vec![2; 1000]);
+        assert!(reassembly.add(last).is_none());
+
+        // Messages too large to reassemble are dropped
+        let mut reassembly = Reassembly::new(FragmentationPolicy::new(500, 1000));
+        let mut messages = Vec::new();
+        for f in fragments(3, vec![3; 2000], 500) {
+            messages.extend(reassembly.add(f));
+        }
+        assert!(messages.is_empty());
+    }
+
+    #[test]
+    fn invalid_fragments_are_dropped() {
+        let mut reassembly = Reassembly::new(FragmentationPolicy::new(100, 1000));
+        let fragment = |id, index, count, len| Fragment {
+            id,
+            index,
+            count,
+            data: vec![0; len],
+        };
+        assert!(reassembly.add(fragment(1, 0, 2, 0)).is_none());
+        assert!(reassembly.add(fragment(2, 0, 11, 100)).is_none());
+        assert!(reassembly.add(fragment(3, 2, 2, 100)).is_none());
+        assert!(reassembly.pending.is_empty());
+
+        // Only the latest messages are kept
+        for id in 0..2 * MAX_PENDING as u64 {
+            assert!(reassembly.add(fragment(id, 0, 2, 1)).is_none());
+        }
+        assert_eq!(reassembly.pending.len(), MAX_PENDING);
+        assert_eq!(
+            reassembly.pending.keys().next(),
+            Some(&(MAX_PENDING as u64))
+        );
+    }
+}
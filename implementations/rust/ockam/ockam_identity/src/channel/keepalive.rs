@@ -0,0 +1,203 @@
+use crate::{ChannelControl, SecureChannelRegistry};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+use ockam_core::compat::{boxed::Box, sync::Arc};
+use ockam_core::{async_trait, Address, Any, Decodable, Message, Result, Route, Routed, Worker};
+use ockam_node::{Context, DelayedEvent};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+/// When the other side of a secure channel is considered gone
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeepalivePolicy {
+    /// Quiet time after which the other side is pinged
+    pub interval: Duration,
+    /// Unanswered pings after which the channel is dead
+    pub max_misses: u32,
+    /// Whether the other side is pinged every interval, quiet or not, to
+    /// measure the round-trip time of the channel
+    pub measure_rtt: bool,
+}
+
+impl KeepalivePolicy {
+    pub fn new(interval: Duration, max_misses: u32) -> Self {
+        Self {
+            interval,
+            max_misses,
+            measure_rtt: false,
+        }
+    }
+
+    /// Ping every interval and record the round-trip time in the
+    /// [`SecureChannelStats`](crate::SecureChannelStats) of the channel
+    pub fn with_rtt(mut self) -> Self {
+        self.measure_rtt = true;
+        self
+    }
+}
+
+impl Default for KeepalivePolicy {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30), 3)
+    }
+}
+
+/// Sent to the watchers of a secure channel once its other side stopped
+/// answering, after its workers were stopped
+#[derive(Serialize, Deserialize, Message, Clone, Debug, PartialEq, Eq)]
+pub struct SecureChannelDead {
+    channel: Address,
+}
+
+impl SecureChannelDead {
+    /// The address of the channel
+    pub fn channel(&self) -> &Address {
+        &self.channel
+    }
+}
+
+/// Pings the other side of a channel when it is quiet, and stops the
+/// channel when the pings are not answered.
+pub(crate) struct KeepaliveWorker {
+    channel: Address,
+    ping_route: Route,
+    /// Address of the ticks of `timer`
+    tick: Address,
+    timer: Option<DelayedEvent<()>>,
+    received: Arc<AtomicUsize>,
+    last_received: usize,
+    misses: u32,
+    policy: KeepalivePolicy,
+    registry: SecureChannelRegistry,
+}
+
+impl KeepaliveWorker {
+    pub(crate) fn new(
+        channel: Address,
+        ping_route: Route,
+        tick: Address,
+        received: Arc<AtomicUsize>,
+        policy: KeepalivePolicy,
+        registry: SecureChannelRegistry,
+    ) -> Self {
+        Self {
+            channel,
+            ping_route,
+            tick,
+            timer: None,
+            last_received: received.load(Ordering::Relaxed),
+            received,
+            misses: 0,
+            policy,
+            registry,
+        }
+    }
+
+    async fn stop_channel(&mut self, ctx: &Context) -> Result<()> {
+        warn!(
+            "Secure channel {} missed {} keepalives, stopping it",
+            self.channel, self.misses
+        );
+        let watchers = self.registry.watchers(&self.channel).await;
+        let _ = ctx.stop_worker(self.channel.clone()).await;
+        for worker in self.registry.remove(&self.channel).await {
+            // This worker last
+            if worker != ctx.address() {
+                let _ = ctx.stop_worker(worker).await;
+            }
+        }
+        let dead = SecureChannelDead {
+            channel: self.channel.clone(),
+        };
+        for watcher in watchers {
+            if ctx.send(watcher.clone(), dead.clone()).await.is_err() {
+                debug!(
+                    "Watcher {} of secure channel {} is gone",
+                    watcher, self.channel
+                );
+            }
+        }
+        ctx.stop_worker(ctx.address()).await
+    }
+}
+
+#[async_trait]
+impl Worker for KeepaliveWorker {
+    type Message = Any;
+    type Context = Context;
+
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        let mut timer = DelayedEvent::create(ctx, self.tick.clone(), ()).await?;
+        timer.schedule(self.policy.interval).await?;
+        self.timer = Some(timer);
+        Ok(())
+    }
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        if msg.msg_addr() != self.tick {
+            // A pong
+            match ChannelControl::decode(msg.payload()) {
+                Ok(ChannelControl::Pong) => {
+                    self.received.fetch_add(1, Ordering::Relaxed);
+                }
+                Ok(ChannelControl::RttPong(sent_at)) => {
+                    self.received.fetch_add(1, Ordering::Relaxed);
+                    if let Some(now) = now_micros() {
+                        let rtt = Duration::from_micros(now.saturating_sub(sent_at));
+                        self.registry.set_rtt(&self.channel, rtt).await
+                    }
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        let received = self.received.load(Ordering::Relaxed);
+        let quiet = if received != self.last_received {
+            self.last_received = received;
+            self.misses = 0;
+            false
+        } else if self.misses >= self.policy.max_misses {
+            return self.stop_channel(ctx).await;
+        } else {
+            self.misses += 1;
+            debug!("Secure channel {} is quiet, pinging it", self.channel);
+            true
+        };
+
+        let ping = match now_micros() {
+            Some(now) if self.policy.measure_rtt => Some(ChannelControl::RttPing(now)),
+            _ if quiet => Some(ChannelControl::Ping),
+            _ => None,
+        };
+        if let Some(ping) = ping {
+            if let Err(e) = ctx.send(self.ping_route.clone(), ping).await {
+                debug!("Could not ping secure channel {}: {}", self.channel, e);
+            }
+        }
+
+        if let Some(timer) = &mut self.timer {
+            timer.schedule(self.policy.interval).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Microseconds since the Unix epoch, if there is a clock
+fn now_micros() -> Option<u64> {
+    #[cfg(feature = "std")]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_micros() as u64)
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        None
+    }
+}
@@ -0,0 +1,124 @@
+use crate::IdentityVault;
+use ockam_core::compat::{boxed::Box, string::String, vec::Vec};
+use ockam_core::{async_trait, Result};
+use ockam_key_exchange_core::{CompletedKeyExchange, KeyExchanger, NewKeyExchanger};
+use ockam_key_exchange_xx::XXNewKeyExchanger;
+
+/// Creates the key exchangers with which the secure channels of an
+/// [`Identity`](crate::Identity) agree on their keys, for the channels and
+/// listeners it creates.
+///
+/// Noise XX with the vault of the identity is used by default. Another
+/// implementation, hardware-backed for instance, is registered with
+/// [`Identity::set_key_exchangers`](crate::Identity::set_key_exchangers).
+/// Any [`NewKeyExchanger`] can be registered as is.
+#[async_trait]
+pub trait KeyExchangers<V: IdentityVault>: Send + Sync + 'static {
+    /// A key exchanger for a channel this identity creates
+    async fn initiator(&self, vault: &V) -> Result<BoxedKeyExchanger>;
+    /// A key exchanger for a channel a listener of this identity accepts
+    async fn responder(&self, vault: &V) -> Result<BoxedKeyExchanger>;
+}
+
+/// Noise XX key exchanges, using the vault of the identity
+pub struct XXKeyExchangers;
+
+#[async_trait]
+impl<V: IdentityVault> KeyExchangers<V> for XXKeyExchangers {
+    async fn initiator(&self, vault: &V) -> Result<BoxedKeyExchanger> {
+        let new_key_exchanger = XXNewKeyExchanger::new(vault.async_try_clone().await?);
+        let key_exchanger = NewKeyExchanger::initiator(&new_key_exchanger).await?;
+        Ok(BoxedKeyExchanger::new(key_exchanger))
+    }
+
+    async fn responder(&self, vault: &V) -> Result<BoxedKeyExchanger> {
+        let new_key_exchanger = XXNewKeyExchanger::new(vault.async_try_clone().await?);
+        let key_exchanger = NewKeyExchanger::responder(&new_key_exchanger).await?;
+        Ok(BoxedKeyExchanger::new(key_exchanger))
+    }
+}
+
+#[async_trait]
+impl<V, N> KeyExchangers<V> for N
+where
+    V: IdentityVault,
+    N: NewKeyExchanger + Send + Sync + 'static,
+{
+    async fn initiator(&self, _vault: &V) -> Result<BoxedKeyExchanger> {
+        Ok(BoxedKeyExchanger::new(
+            NewKeyExchanger::initiator(self).await?,
+        ))
+    }
+
+    async fn responder(&self, _vault: &V) -> Result<BoxedKeyExchanger> {
+        Ok(BoxedKeyExchanger::new(
+            NewKeyExchanger::responder(self).await?,
+        ))
+    }
+}
+
+/// A [`KeyExchanger`] of any type
+pub struct BoxedKeyExchanger(Box<dyn DynKeyExchanger>);
+
+impl BoxedKeyExchanger {
+    /// Box `key_exchanger`
+    pub fn new(key_exchanger: impl KeyExchanger + Send + Sync + 'static) -> Self {
+        Self(Box::new(key_exchanger))
+    }
+}
+
+#[async_trait]
+impl KeyExchanger for BoxedKeyExchanger {
+    async fn name(&self) -> Result<String> {
+        self.0.name().await
+    }
+
+    async fn generate_request(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        self.0.generate_request(payload).await
+    }
+
+    async fn handle_response(&mut self, response: &[u8]) -> Result<Vec<u8>> {
+        self.0.handle_response(response).await
+    }
+
+    async fn is_complete(&self) -> Result<bool> {
+        self.0.is_complete().await
+    }
+
+    async fn finalize(self) -> Result<CompletedKeyExchange> {
+        self.0.finalize_boxed().await
+    }
+}
+
+/// [`KeyExchanger`], finalized through a box
+#[async_trait]
+trait DynKeyExchanger: Send + Sync {
+    async fn name(&self) -> Result<String>;
+    async fn generate_request(&mut self, payload: &[u8]) -> Result<Vec<u8>>;
+    async fn handle_response(&mut self, response: &[u8]) -> Result<Vec<u8>>;
+    async fn is_complete(&self) -> Result<bool>;
+    async fn finalize_boxed(self: Box<Self>) -> Result<CompletedKeyExchange>;
+}
+
+#[async_trait]
+impl<K: KeyExchanger + Send + Sync + 'static> DynKeyExchanger for K {
+    async fn name(&self) -> Result<String> {
+        KeyExchanger::name(self).await
+    }
+
+    async fn generate_request(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        KeyExchanger::generate_request(self, payload).await
+    }
+
+    async fn handle_response(&mut self, response: &[u8]) -> Result<Vec<u8>> {
+        KeyExchanger::handle_response(self, response).await
+    }
+
+    async fn is_complete(&self) -> Result<bool> {
+        KeyExchanger::is_complete(self).await
+    }
+
+    async fn finalize_boxed(self: Box<Self>) -> Result<CompletedKeyExchange> {
+        KeyExchanger::finalize(*self).await
+    }
+}
@@ -1,24 +1,59 @@
 use crate::authenticated_storage::AuthenticatedStorage;
-use crate::{DecryptorWorker, Identity, IdentityVault, TrustPolicy};
+use crate::{
+    handshake_source, DecryptorWorker, HandshakeLimiter, HandshakeRateLimit, Identity,
+    IdentityIdentifier, IdentityVault, KeyAgreement, PreSharedKey, TrustPolicy,
+};
 use ockam_channel::CreateResponderChannelMessage;
 use ockam_core::compat::{boxed::Box, sync::Arc};
 use ockam_core::{AsyncTryClone, Result, Routed, Worker};
 use ockam_node::Context;
+use tracing::warn;
 
 pub(crate) struct IdentityChannelListener<V: IdentityVault, S: AuthenticatedStorage> {
     trust_policy: Arc<dyn TrustPolicy>,
     identity: Identity<V>,
     storage: S,
+    key_agreement: KeyAgreement,
+    limiter: Option<HandshakeLimiter>,
+    /// The key the channels must be created with, if any
+    psk: Option<PreSharedKey>,
+    /// The authorities whose credentials give attributes to the messages
+    /// of the channels, any when not set
+    trusted_issuers: Option<Vec<IdentityIdentifier>>,
 }
 
 impl<V: IdentityVault, S: AuthenticatedStorage> IdentityChannelListener<V, S> {
-    pub fn new(trust_policy: impl TrustPolicy, identity: Identity<V>, storage: S) -> Self {
+    pub fn new(
+        trust_policy: impl TrustPolicy,
+        identity: Identity<V>,
+        storage: S,
+        key_agreement: KeyAgreement,
+    ) -> Self {
         IdentityChannelListener {
             trust_policy: Arc::new(trust_policy),
             identity,
             storage,
+            key_agreement,
+            limiter: None,
+            psk: None,
+            trusted_issuers: None,
         }
     }
+
+    pub fn with_rate_limit(mut self, rate_limit: Option<HandshakeRateLimit>) -> Self {
+        self.limiter = rate_limit.map(HandshakeLimiter::new);
+        self
+    }
+
+    pub fn with_psk(mut self, psk: PreSharedKey) -> Self {
+        self.psk = Some(psk);
+        self
+    }
+
+    pub fn with_trusted_issuers(mut self, issuers: Vec<IdentityIdentifier>) -> Self {
+        self.trusted_issuers = Some(issuers);
+        self
+    }
 }
 
 #[ockam_core::worker]
@@ -31,6 +66,14 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Worker for IdentityChannelListen
         ctx: &mut Self::Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
+        if let Some(limiter) = &mut self.limiter {
+            let source = handshake_source(&msg.return_route());
+            if !limiter.allow(&source) {
+                warn!("Dropping SecureChannel handshake over the rate limit from {source}");
+                return Ok(());
+            }
+        }
+
         let trust_policy = Arc::clone(&self.trust_policy);
         let identity = self.identity.async_try_clone().await?;
         DecryptorWorker::create_responder(
@@ -38,6 +81,9 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Worker for IdentityChannelListen
             identity,
             self.storage.async_try_clone().await?,
             trust_policy,
+            self.key_agreement,
+            self.psk.clone(),
+            self.trusted_issuers.clone(),
             msg,
         )
         .await
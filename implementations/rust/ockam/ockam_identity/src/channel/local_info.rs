@@ -1,15 +1,23 @@
 use crate::{IdentityError, IdentityIdentifier};
-use ockam_core::compat::vec::Vec;
-use ockam_core::{Decodable, Encodable, LocalInfo, LocalMessage, Result};
+use ockam_core::compat::{collections::BTreeMap, string::String, vec::Vec};
+use ockam_core::{Decodable, Encodable, LocalInfo, LocalMessage, Message, Result, Routed};
 use serde::{Deserialize, Serialize};
 
 /// Identity SecureChannel LocalInfo unique Identifier
 pub const IDENTITY_SECURE_CHANNEL_IDENTIFIER: &str = "IDENTITY_SECURE_CHANNEL_IDENTIFIER";
 
-/// Identity SecureChannel LocalInfo used for LocalMessage
+/// Identity SecureChannel LocalInfo used for LocalMessage, telling the
+/// workers behind a secure channel who sent a message:
+///
+/// ```ignore
+/// let info = IdentitySecureChannelLocalInfo::find_info_from_routed(&msg)?;
+/// if info.attribute("role") == Some(b"admin") { ... }
+/// ```
 #[derive(Serialize, Deserialize)]
 pub struct IdentitySecureChannelLocalInfo {
     their_identity_id: IdentityIdentifier,
+    hops: Vec<IdentityIdentifier>,
+    attributes: BTreeMap<String, Vec<u8>>,
 }
 
 impl IdentitySecureChannelLocalInfo {
@@ -36,6 +44,10 @@ impl IdentitySecureChannelLocalInfo {
         Self::find_info_from_list(local_msg.local_info())
     }
 
+    pub fn find_info_from_routed<M: Message>(msg: &Routed<M>) -> Result<Self> {
+        Self::find_info(msg.local_message())
+    }
+
     pub fn find_info_from_list(local_info: &[LocalInfo]) -> Result<Self> {
         if let Some(local_info) = local_info
             .iter()
@@ -53,6 +65,29 @@ impl IdentitySecureChannelLocalInfo {
     pub fn their_identity_id(&self) -> &IdentityIdentifier {
         &self.their_identity_id
     }
+
+    /// Identities at the other side of the secure channels the channel
+    /// goes over, outermost first
+    pub fn hops(&self) -> &[IdentityIdentifier] {
+        &self.hops
+    }
+
+    /// Attributes of the credential the other side presented, verified and
+    /// not expired when the message was received
+    pub fn attributes(&self) -> &BTreeMap<String, Vec<u8>> {
+        &self.attributes
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&[u8]> {
+        self.attributes.get(name).map(Vec::as_slice)
+    }
+
+    /// The hops of a channel going over this one
+    pub fn chain(&self) -> Vec<IdentityIdentifier> {
+        let mut chain = self.hops.clone();
+        chain.push(self.their_identity_id.clone());
+        chain
+    }
 }
 
 impl IdentitySecureChannelLocalInfo {
@@ -61,12 +96,21 @@ impl IdentitySecureChannelLocalInfo {
     pub fn mark(
         mut local_info: Vec<LocalInfo>,
         their_identity_id: IdentityIdentifier,
+        hops: Vec<IdentityIdentifier>,
+        attributes: BTreeMap<String, Vec<u8>>,
     ) -> Result<Vec<LocalInfo>> {
         // strip out any pre-existing IdentitySecureChannLocalInfo
         local_info.retain(|x| x.type_identifier() != IDENTITY_SECURE_CHANNEL_IDENTIFIER);
 
         // mark the vector
-        local_info.push(Self { their_identity_id }.to_local_info()?);
+        local_info.push(
+            Self {
+                their_identity_id,
+                hops,
+                attributes,
+            }
+            .to_local_info()?,
+        );
 
         Ok(local_info)
     }
@@ -1,3 +1,4 @@
+use crate::{Fragment, ResumptionTicket};
 use ockam_core::compat::vec::Vec;
 use ockam_core::Message;
 use serde::{Deserialize, Serialize};
@@ -14,3 +15,19 @@ pub(crate) enum IdentityChannelMessage {
     },
     Confirm,
 }
+
+/// Exchanged by the two sides of a channel, through the channel. They are
+/// addressed to the decryptor itself, and never forwarded.
+#[derive(Serialize, Deserialize, Message)]
+pub(crate) enum ChannelControl {
+    Ping,
+    Pong,
+    /// A ticket to resume the channel, from the listener to the initiator
+    Ticket(ResumptionTicket),
+    /// A ping measuring the round-trip time, with the time it was sent at
+    /// in microseconds, echoed back by its pong
+    RttPing(u64),
+    RttPong(u64),
+    /// A part of a message too large to be sent at once
+    Fragment(Fragment),
+}
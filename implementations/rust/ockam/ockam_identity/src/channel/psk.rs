@@ -0,0 +1,162 @@
+use crate::{
+    BoxedKeyExchanger, IdentityError, IdentityVault, KeyExchangers, SharedSecretKeyExchanger,
+    SharedSecretKind,
+};
+use ockam_core::compat::{boxed::Box, string::String, vec::Vec};
+use ockam_core::vault::SecretKey;
+use ockam_core::{async_trait, Result};
+use ockam_key_exchange_core::{CompletedKeyExchange, KeyExchanger};
+
+/// Starts the first message of a pre-shared key handshake, which can't be
+/// mistaken for the ephemeral key starting a Noise XX handshake
+const PSK_MAGIC: &[u8; 8] = b"OCKPSK01";
+const PSK_KEY_INFO: &[u8] = b"ockam secure channel pre-shared key";
+/// Pre-shared keys shorter than this are refused
+const PSK_MIN_LEN: usize = 16;
+/// Ids of pre-shared keys are sent prefixed with their length on one byte
+const PSK_MAX_ID_LEN: usize = 255;
+
+pub(crate) const PSK: SharedSecretKind = SharedSecretKind {
+    name: "PSK",
+    info: PSK_KEY_INFO,
+    error: IdentityError::InvalidPreSharedKey,
+};
+
+/// A symmetric key provisioned on both sides of secure channels, for
+/// devices which can't be enrolled to get credentials
+///
+/// The keys of a channel created with it are derived from ephemeral keys
+/// and the pre-shared key, so that only the holders of the key can create
+/// channels to the listeners using it, and only they can accept the
+/// channels created with it. Both sides still prove their identities, the
+/// trust policy of the channel may then trust everyone knowing the key.
+///
+/// Channels are created with
+/// [`Identity::create_psk_secure_channel`](crate::Identity::create_psk_secure_channel)
+/// and accepted with
+/// [`Identity::create_psk_secure_channel_listener`](crate::Identity::create_psk_secure_channel_listener).
+/// Their [`SecureChannelLocalInfo`](ockam_channel::SecureChannelLocalInfo)
+/// key exchange is `PSK`.
+#[derive(Clone, Debug)]
+pub struct PreSharedKey {
+    /// Names the key, so that a listener refuses channels with another one
+    id: String,
+    secret: SecretKey,
+}
+
+impl PreSharedKey {
+    /// The key `secret`, of at least 16 bytes, named `id`, of at most 255
+    pub fn new(id: impl Into<String>, secret: impl Into<Vec<u8>>) -> Result<Self> {
+        let id = id.into();
+        let secret = secret.into();
+        if id.len() > PSK_MAX_ID_LEN || secret.len() < PSK_MIN_LEN {
+            return Err(IdentityError::InvalidPreSharedKey.into());
+        }
+        Ok(Self {
+            id,
+            secret: SecretKey::new(secret),
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// What the first message of an initiator starts with
+    fn header(&self) -> Vec<u8> {
+        let mut header = PSK_MAGIC.to_vec();
+        header.push(self.id.len() as u8);
+        header.extend_from_slice(self.id.as_bytes());
+        header
+    }
+}
+
+#[async_trait]
+impl<V: IdentityVault> KeyExchangers<V> for PreSharedKey {
+    async fn initiator(&self, vault: &V) -> Result<BoxedKeyExchanger> {
+        let vault = vault.async_try_clone().await?;
+        Ok(BoxedKeyExchanger::new(SharedSecretKeyExchanger::initiator(
+            vault,
+            &PSK,
+            self.header(),
+            self.secret.clone(),
+        )))
+    }
+
+    async fn responder(&self, vault: &V) -> Result<BoxedKeyExchanger> {
+        Ok(BoxedKeyExchanger::new(PskResponder {
+            psk: self.clone(),
+            vault: vault.async_try_clone().await?,
+            inner: None,
+        }))
+    }
+}
+
+/// Responder of a listener with a pre-shared key, refusing the channels
+/// created without the same key
+struct PskResponder<V: IdentityVault> {
+    psk: PreSharedKey,
+    vault: V,
+    /// The key exchange, once the first message named our key
+    inner: Option<SharedSecretKeyExchanger<V>>,
+}
+
+impl<V: IdentityVault> PskResponder<V> {
+    fn inner(&mut self) -> Result<&mut SharedSecretKeyExchanger<V>> {
+        self.inner
+            .as_mut()
+            .ok_or_else(|| IdentityError::InvalidPreSharedKey.into())
+    }
+}
+
+#[async_trait]
+impl<V: IdentityVault> KeyExchanger for PskResponder<V> {
+    async fn name(&self) -> Result<String> {
+        Ok(PSK.name.into())
+    }
+
+    async fn generate_request(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        self.inner()?.generate_request(payload).await
+    }
+
+    async fn handle_response(&mut self, response: &[u8]) -> Result<Vec<u8>> {
+        let rest = match response.strip_prefix(&self.psk.header()[..]) {
+            Some(rest) if self.inner.is_none() => rest,
+            _ => return Err(IdentityError::InvalidPreSharedKey.into()),
+        };
+        let vault = self.vault.async_try_clone().await?;
+        let secret = self.psk.secret.clone();
+        let (inner, payload) =
+            SharedSecretKeyExchanger::responder(vault, &PSK, secret, response, rest)?;
+        self.inner = Some(inner);
+        Ok(payload.to_vec())
+    }
+
+    async fn is_complete(&self) -> Result<bool> {
+        match &self.inner {
+            Some(inner) => inner.is_complete().await,
+            None => Ok(false),
+        }
+    }
+
+    async fn finalize(self) -> Result<CompletedKeyExchange> {
+        match self.inner {
+            Some(inner) => inner.finalize().await,
+            None => Err(IdentityError::InvalidPreSharedKey.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pre_shared_key_lengths() {
+        assert!(PreSharedKey::new("device", [0u8; 15]).is_err());
+        assert!(PreSharedKey::new("a".repeat(256), [0u8; 32]).is_err());
+        let psk = PreSharedKey::new("device", [0u8; 16]).unwrap();
+        assert_eq!(psk.header(), b"OCKPSK01\x06device".to_vec());
+        assert!(!format!("{psk:?}").contains("[0"));
+    }
+}
@@ -0,0 +1,178 @@
+use crate::credential::Timestamp;
+use core::time::Duration;
+use ockam_core::compat::{
+    collections::BTreeMap,
+    string::{String, ToString},
+};
+use ockam_core::{Route, TransportType};
+
+/// The transport type of TCP addresses, whose ports are not part of the
+/// source of a handshake
+const TCP: TransportType = TransportType::new(1);
+
+/// Refused sources are not penalized for longer than this many penalties
+const MAX_PENALTY_DOUBLINGS: u32 = 6;
+
+/// How many handshakes a secure channel listener starts, so that floods of
+/// handshakes do not keep its vault busy
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HandshakeRateLimit {
+    /// Handshakes started per period for one source
+    pub per_source: u32,
+    /// Handshakes started per period for all sources
+    pub global: u32,
+    /// Period of the limits, rounded up to seconds
+    pub period: Duration,
+    /// Time a source going over its limit is refused, doubled each time it
+    /// goes over it again within a penalty of the end of the previous one
+    pub penalty: Duration,
+}
+
+impl HandshakeRateLimit {
+    pub fn new(per_source: u32, global: u32, period: Duration) -> Self {
+        Self {
+            per_source,
+            global,
+            period,
+            penalty: period * 10,
+        }
+    }
+
+    pub fn with_penalty(mut self, penalty: Duration) -> Self {
+        self.penalty = penalty;
+        self
+    }
+}
+
+impl Default for HandshakeRateLimit {
+    fn default() -> Self {
+        Self::new(10, 100, Duration::from_secs(1))
+    }
+}
+
+/// The source of a handshake: the first hop of its return route, without
+/// the port of a TCP address so that the connections of a host share their
+/// limit. Handshakes coming through a relay share the limit of the relay
+pub(crate) fn handshake_source(return_route: &Route) -> String {
+    match return_route.next() {
+        Ok(hop) if hop.transport_type() == TCP => match hop.address().rsplit_once(':') {
+            Some((host, _port)) => host.to_string(),
+            None => hop.address().to_string(),
+        },
+        Ok(hop) => hop.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+/// A source refused until some time
+struct Penalty {
+    until: u64,
+    doublings: u32,
+}
+
+/// Counts the handshakes of a listener in the current period
+pub(crate) struct HandshakeLimiter {
+    limit: HandshakeRateLimit,
+    /// Start of the current period, in seconds
+    start: u64,
+    global: u32,
+    sources: BTreeMap<String, u32>,
+    penalties: BTreeMap<String, Penalty>,
+}
+
+impl HandshakeLimiter {
+    pub(crate) fn new(limit: HandshakeRateLimit) -> Self {
+        Self {
+            limit,
+            start: 0,
+            global: 0,
+            sources: BTreeMap::new(),
+            penalties: BTreeMap::new(),
+        }
+    }
+
+    /// Whether a handshake from `source` may start now. Without a clock,
+    /// every handshake does
+    pub(crate) fn allow(&mut self, source: &str) -> bool {
+        match Timestamp::now() {
+            Some(now) => self.allow_at(source, u64::from(now)),
+            None => true,
+        }
+    }
+
+    fn allow_at(&mut self, source: &str, now: u64) -> bool {
+        let period = self.limit.period.as_secs().max(1);
+        let penalty = self.limit.penalty.as_secs();
+        if now >= self.start + period {
+            self.start = now;
+            self.global = 0;
+            self.sources.clear();
+            // Sources quiet for a whole penalty after theirs are forgiven
+            self.penalties.retain(|_, p| p.until + penalty > now);
+        }
+
+        if let Some(p) = self.penalties.get(source) {
+            if now < p.until {
+                return false;
+            }
+        }
+        if self.global >= self.limit.global {
+            return false;
+        }
+
+        let count = self.sources.entry(source.to_string()).or_insert(0);
+        if *count >= self.limit.per_source {
+            let p = self.penalties.entry(source.to_string()).or_insert(Penalty {
+                until: 0,
+                doublings: 0,
+            });
+            p.until = now + (penalty << p.doublings);
+            p.doublings = (p.doublings + 1).min(MAX_PENALTY_DOUBLINGS);
+            return false;
+        }
+        *count += 1;
+        self.global += 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ockam_core::{route, Address};
+
+    #[test]
+    fn test_limits() {
+        let limit = HandshakeRateLimit::new(2, 3, Duration::from_secs(1))
+            .with_penalty(Duration::from_secs(10));
+        let mut limiter = HandshakeLimiter::new(limit);
+
+        assert!(limiter.allow_at("a", 100));
+        assert!(limiter.allow_at("a", 100));
+        // Over the limit of the source, which is penalized
+        assert!(!limiter.allow_at("a", 100));
+        assert!(limiter.allow_at("b", 100));
+        // Over the global limit
+        assert!(!limiter.allow_at("c", 100));
+
+        // A new period, but "a" is still penalized
+        assert!(limiter.allow_at("c", 101));
+        assert!(!limiter.allow_at("a", 101));
+        assert!(limiter.allow_at("a", 110));
+        assert!(limiter.allow_at("a", 110));
+
+        // Going over the limit again doubles the penalty
+        assert!(!limiter.allow_at("a", 110));
+        assert!(!limiter.allow_at("a", 129));
+        assert!(limiter.allow_at("a", 130));
+    }
+
+    #[test]
+    fn test_sources() {
+        let tcp = Address::new(TCP, "127.0.0.1:51234");
+        assert_eq!(handshake_source(&route![tcp, "listener"]), "127.0.0.1");
+        let tcp = Address::new(TCP, "[::1]:51234");
+        assert_eq!(handshake_source(&route![tcp]), "[::1]");
+        assert_eq!(handshake_source(&route!["relay", "listener"]), "0#relay");
+    }
+}
@@ -0,0 +1,198 @@
+use crate::IdentityIdentifier;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+use ockam_core::compat::{collections::BTreeMap, sync::Arc, vec::Vec};
+use ockam_core::{Address, Route};
+use ockam_node::compat::asynchronous::RwLock;
+
+/// Secure channels created or accepted by an [`Identity`](crate::Identity),
+/// by the address of their encryptor
+#[derive(Clone)]
+pub(crate) struct SecureChannelRegistry {
+    channels: Arc<RwLock<BTreeMap<Address, SecureChannelEntry>>>,
+}
+
+struct SecureChannelEntry {
+    /// The other workers making up the channel on this side
+    workers: Vec<Address>,
+    their_identity_id: IdentityIdentifier,
+    /// Identities at the other side of the channels this one goes over
+    hops: Vec<IdentityIdentifier>,
+    counters: ChannelCounters,
+    /// Route to the decryptor on the other side, through the channel
+    ping_route: Route,
+    /// Round-trip time of the last keepalive measuring it
+    rtt: Option<Duration>,
+    /// Workers to notify if the channel dies
+    watchers: Vec<Address>,
+    /// Channels created only to carry this one, stopped with it
+    carriers: Vec<Address>,
+}
+
+/// Traffic of a channel, shared by its workers
+#[derive(Clone, Default)]
+pub(crate) struct ChannelCounters {
+    /// Messages carried so far, in both directions
+    pub(crate) messages: Arc<AtomicUsize>,
+    /// Messages sent so far
+    pub(crate) sent: Arc<AtomicUsize>,
+    /// Messages received so far, keepalives included
+    pub(crate) received: Arc<AtomicUsize>,
+}
+
+impl SecureChannelRegistry {
+    pub(crate) fn new() -> Self {
+        Self {
+            channels: Arc::new(RwLock::new(BTreeMap::new())),
+        }
+    }
+
+    /// Register a channel and return the counters of its traffic
+    pub(crate) async fn register(
+        &self,
+        encryptor: Address,
+        workers: Vec<Address>,
+        their_identity_id: IdentityIdentifier,
+        hops: Vec<IdentityIdentifier>,
+        ping_route: Route,
+    ) -> ChannelCounters {
+        let counters = ChannelCounters::default();
+        let entry = SecureChannelEntry {
+            workers,
+            their_identity_id,
+            hops,
+            counters: counters.clone(),
+            ping_route,
+            rtt: None,
+            watchers: Vec::new(),
+            carriers: Vec::new(),
+        };
+        self.channels.write().await.insert(encryptor, entry);
+        counters
+    }
+
+    pub(crate) async fn messages(&self, encryptor: &Address) -> Option<usize> {
+        self.channels
+            .read()
+            .await
+            .get(encryptor)
+            .map(|e| e.counters.messages.load(Ordering::Relaxed))
+    }
+
+    pub(crate) async fn stats(&self, encryptor: &Address) -> Option<SecureChannelStats> {
+        self.channels.read().await.get(encryptor).map(|e| {
+            let messages = e.counters.messages.load(Ordering::Relaxed);
+            let sent = e.counters.sent.load(Ordering::Relaxed);
+            SecureChannelStats {
+                their_identity_id: e.their_identity_id.clone(),
+                hops: e.hops.clone(),
+                sent,
+                received: messages.saturating_sub(sent),
+                rtt: e.rtt,
+            }
+        })
+    }
+
+    /// The hops of a channel going over this one
+    pub(crate) async fn chain(&self, encryptor: &Address) -> Option<Vec<IdentityIdentifier>> {
+        self.channels.read().await.get(encryptor).map(|e| {
+            let mut chain = e.hops.clone();
+            chain.push(e.their_identity_id.clone());
+            chain
+        })
+    }
+
+    /// The route to ping the other side with, and the counter of the
+    /// messages received
+    pub(crate) async fn keepalive(&self, encryptor: &Address) -> Option<(Route, Arc<AtomicUsize>)> {
+        self.channels
+            .read()
+            .await
+            .get(encryptor)
+            .map(|e| (e.ping_route.clone(), e.counters.received.clone()))
+    }
+
+    pub(crate) async fn set_rtt(&self, encryptor: &Address, rtt: Duration) {
+        if let Some(e) = self.channels.write().await.get_mut(encryptor) {
+            e.rtt = Some(rtt)
+        }
+    }
+
+    /// Add a worker to stop with the channel, returning `false` if there
+    /// is no such channel
+    pub(crate) async fn add_worker(&self, encryptor: &Address, worker: Address) -> bool {
+        match self.channels.write().await.get_mut(encryptor) {
+            Some(e) => {
+                e.workers.push(worker);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Add a worker to notify if the channel dies, returning `false` if
+    /// there is no such channel
+    pub(crate) async fn add_watcher(&self, encryptor: &Address, watcher: Address) -> bool {
+        match self.channels.write().await.get_mut(encryptor) {
+            Some(e) => {
+                e.watchers.push(watcher);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Add channels to stop with the channel they carry, returning `false`
+    /// if there is no such channel
+    pub(crate) async fn add_carriers(&self, encryptor: &Address, carriers: Vec<Address>) -> bool {
+        match self.channels.write().await.get_mut(encryptor) {
+            Some(e) => {
+                e.carriers.extend(carriers);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) async fn watchers(&self, encryptor: &Address) -> Vec<Address> {
+        self.channels
+            .read()
+            .await
+            .get(encryptor)
+            .map(|e| e.watchers.clone())
+            .unwrap_or_default()
+    }
+
+    /// Forget a channel and the channels carrying it, and return the
+    /// workers left to stop
+    pub(crate) async fn remove(&self, encryptor: &Address) -> Vec<Address> {
+        let mut channels = self.channels.write().await;
+        let (mut workers, mut carriers) = match channels.remove(encryptor) {
+            Some(e) => (e.workers, e.carriers),
+            None => return Vec::new(),
+        };
+        while let Some(carrier) = carriers.pop() {
+            if let Some(e) = channels.remove(&carrier) {
+                workers.push(carrier);
+                workers.extend(e.workers);
+                carriers.extend(e.carriers);
+            }
+        }
+        workers
+    }
+}
+
+/// The other side of a secure channel and the traffic it carried so far,
+/// keepalives excluded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecureChannelStats {
+    pub their_identity_id: IdentityIdentifier,
+    /// Identities at the other side of the channels this one goes over,
+    /// outermost first
+    pub hops: Vec<IdentityIdentifier>,
+    pub sent: usize,
+    pub received: usize,
+    /// Round-trip time of the last keepalive, if the keepalive of the
+    /// channel measures it
+    pub rtt: Option<Duration>,
+}
@@ -0,0 +1,233 @@
+use crate::credential::Timestamp;
+use crate::{
+    BoxedKeyExchanger, IdentityError, IdentityVault, SharedSecretKeyExchanger, SharedSecretKind,
+};
+use core::time::Duration;
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::compat::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use ockam_core::vault::SecretKey;
+use ockam_core::{async_trait, Result, Route};
+use ockam_key_exchange_core::{CompletedKeyExchange, KeyExchanger};
+use ockam_node::compat::asynchronous::RwLock;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+/// Starts the first message of a resumption, which can't be mistaken for
+/// the ephemeral key starting a Noise XX handshake
+const RESUMPTION_MAGIC: &[u8; 8] = b"OCKRSUM1";
+const TICKET_ID_LEN: usize = 16;
+const TICKET_SECRET_LEN: usize = 32;
+const RESUMPTION_KEY_INFO: &[u8] = b"ockam secure channel resumption";
+
+/// Tickets with which secure channels are re-established in one round trip
+///
+/// Listeners issue a ticket to each initiator once its channel is
+/// authenticated, if resumption is enabled. The ticket is sent through the
+/// channel, and the initiator uses it, once, to create the next channel to
+/// the same route. Both sides then only exchange ephemeral keys, the
+/// secret of the ticket authenticating them, before proving their
+/// identities as usual.
+#[derive(Clone, Default)]
+pub(crate) struct ResumptionTickets {
+    state: Arc<RwLock<TicketsState>>,
+}
+
+#[derive(Default)]
+struct TicketsState {
+    /// How long the tickets issued from now on are valid, if any are
+    lifetime: Option<Duration>,
+    /// Tickets issued by listeners, by their id, with when they expire
+    issued: BTreeMap<[u8; TICKET_ID_LEN], (SecretKey, u64)>,
+    /// Tickets received by initiators, by the route of their channel
+    received: BTreeMap<String, (ResumptionTicket, u64)>,
+}
+
+/// A ticket, as sent from the listener to the initiator through the channel
+#[derive(Serialize, Deserialize, Clone, Zeroize)]
+#[zeroize(drop)]
+pub(crate) struct ResumptionTicket {
+    id: [u8; TICKET_ID_LEN],
+    secret: Vec<u8>,
+    /// Seconds the ticket is valid for
+    lifetime: u64,
+}
+
+impl ResumptionTickets {
+    pub(crate) async fn set_lifetime(&self, lifetime: Option<Duration>) {
+        let mut state = self.state.write().await;
+        state.lifetime = lifetime;
+        if lifetime.is_none() {
+            state.issued.clear();
+        }
+    }
+
+    /// Issue a ticket for an initiator, if resumption is enabled
+    pub(crate) async fn issue(&self) -> Option<ResumptionTicket> {
+        let mut state = self.state.write().await;
+        let lifetime = state.lifetime?;
+        let now = now()?;
+        let expires = now + lifetime.as_secs();
+        state.issued.retain(|_, (_, e)| *e > now);
+
+        let mut id = [0u8; TICKET_ID_LEN];
+        let mut secret = [0u8; TICKET_SECRET_LEN];
+        thread_rng().fill_bytes(&mut id);
+        thread_rng().fill_bytes(&mut secret);
+        state
+            .issued
+            .insert(id, (SecretKey::new(secret.to_vec()), expires));
+        let ticket = ResumptionTicket {
+            id,
+            secret: secret.to_vec(),
+            lifetime: lifetime.as_secs(),
+        };
+        secret.zeroize();
+        Some(ticket)
+    }
+
+    /// The secret of a ticket this identity issued, which can't be used again
+    async fn redeem(&self, id: &[u8]) -> Option<SecretKey> {
+        let id: [u8; TICKET_ID_LEN] = id.try_into().ok()?;
+        let (secret, expires) = self.state.write().await.issued.remove(&id)?;
+        (expires > now()?).then(|| secret)
+    }
+
+    /// Keep the ticket an initiator received for the channel to `route`
+    pub(crate) async fn store(&self, route: &Route, ticket: ResumptionTicket) {
+        if let Some(now) = now() {
+            let expires = now + ticket.lifetime;
+            let mut state = self.state.write().await;
+            state.received.retain(|_, (_, e)| *e > now);
+            state.received.insert(route.to_string(), (ticket, expires));
+        }
+    }
+
+    /// Take the ticket for a new channel to `route`, if one is still valid
+    pub(crate) async fn take(&self, route: &Route) -> Option<ResumptionTicket> {
+        let (ticket, expires) = self
+            .state
+            .write()
+            .await
+            .received
+            .remove(&route.to_string())?;
+        (expires > now()?).then(|| ticket)
+    }
+}
+
+/// Seconds since the Unix epoch, if there is a clock
+fn now() -> Option<u64> {
+    Timestamp::now().map(u64::from)
+}
+
+/// Resumptions are key exchanges authenticated by the secret of a ticket
+pub(crate) const RESUMPTION: SharedSecretKind = SharedSecretKind {
+    name: "RESUMPTION",
+    info: RESUMPTION_KEY_INFO,
+    error: IdentityError::InvalidResumption,
+};
+
+/// The key exchange of an initiator resuming a channel with `ticket`
+pub(crate) fn resumption_initiator<V: IdentityVault>(
+    vault: V,
+    ticket: ResumptionTicket,
+) -> SharedSecretKeyExchanger<V> {
+    let mut header = RESUMPTION_MAGIC.to_vec();
+    header.extend_from_slice(&ticket.id);
+    let secret = SecretKey::new(ticket.secret.clone());
+    SharedSecretKeyExchanger::initiator(vault, &RESUMPTION, header, secret)
+}
+
+/// Responder of a listener, resuming channels whose first message carries
+/// a ticket, and running the regular key exchange otherwise
+pub(crate) struct ResumingResponder<V: IdentityVault> {
+    regular: Option<BoxedKeyExchanger>,
+    resumption: Option<SharedSecretKeyExchanger<V>>,
+    tickets: ResumptionTickets,
+    vault: V,
+}
+
+impl<V: IdentityVault> ResumingResponder<V> {
+    pub(crate) fn new(regular: BoxedKeyExchanger, tickets: ResumptionTickets, vault: V) -> Self {
+        Self {
+            regular: Some(regular),
+            resumption: None,
+            tickets,
+            vault,
+        }
+    }
+
+    /// Start a resumption if `request` is the first message of one
+    async fn resume(&mut self, request: &[u8]) -> Result<Option<Vec<u8>>> {
+        let rest = match request.strip_prefix(&RESUMPTION_MAGIC[..]) {
+            Some(rest) if self.resumption.is_none() => rest,
+            _ => return Ok(None),
+        };
+        if rest.len() < TICKET_ID_LEN {
+            return Err(IdentityError::InvalidResumption.into());
+        }
+        let (id, rest) = rest.split_at(TICKET_ID_LEN);
+        let secret = self
+            .tickets
+            .redeem(id)
+            .await
+            .ok_or(IdentityError::InvalidResumption)?;
+        let vault = self.vault.async_try_clone().await?;
+        let (resumption, payload) =
+            SharedSecretKeyExchanger::responder(vault, &RESUMPTION, secret, request, rest)?;
+        self.regular = None;
+        self.resumption = Some(resumption);
+        Ok(Some(payload.to_vec()))
+    }
+}
+
+#[async_trait]
+impl<V: IdentityVault> KeyExchanger for ResumingResponder<V> {
+    async fn name(&self) -> Result<String> {
+        match (&self.resumption, &self.regular) {
+            (Some(r), _) => r.name().await,
+            (None, Some(r)) => r.name().await,
+            (None, None) => Err(IdentityError::InvalidResumption.into()),
+        }
+    }
+
+    async fn generate_request(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        match (&mut self.resumption, &mut self.regular) {
+            (Some(r), _) => r.generate_request(payload).await,
+            (None, Some(r)) => r.generate_request(payload).await,
+            (None, None) => Err(IdentityError::InvalidResumption.into()),
+        }
+    }
+
+    async fn handle_response(&mut self, response: &[u8]) -> Result<Vec<u8>> {
+        if let Some(payload) = self.resume(response).await? {
+            return Ok(payload);
+        }
+        match (&mut self.resumption, &mut self.regular) {
+            (Some(r), _) => r.handle_response(response).await,
+            (None, Some(r)) => r.handle_response(response).await,
+            (None, None) => Err(IdentityError::InvalidResumption.into()),
+        }
+    }
+
+    async fn is_complete(&self) -> Result<bool> {
+        match (&self.resumption, &self.regular) {
+            (Some(r), _) => r.is_complete().await,
+            (None, Some(r)) => r.is_complete().await,
+            (None, None) => Err(IdentityError::InvalidResumption.into()),
+        }
+    }
+
+    async fn finalize(self) -> Result<CompletedKeyExchange> {
+        match (self.resumption, self.regular) {
+            (Some(r), _) => r.finalize().await,
+            (None, Some(r)) => r.finalize().await,
+            (None, None) => Err(IdentityError::InvalidResumption.into()),
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{IdentityError, IdentityVault};
+use ockam_core::compat::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use ockam_core::vault::{
+    HandshakeHash, KeyId, PublicKey, SecretAttributes, SecretKey, SecretPersistence, SecretType,
+    AES256_SECRET_LENGTH_U32, CURVE25519_PUBLIC_LENGTH_USIZE, CURVE25519_SECRET_LENGTH_U32,
+};
+use ockam_core::{async_trait, Result};
+use ockam_key_exchange_core::{CompletedKeyExchange, KeyExchanger};
+
+/// A kind of secret shared by the two sides of a channel
+pub(crate) struct SharedSecretKind {
+    /// Name of the key exchange, as found in the local info of messages
+    pub(crate) name: &'static str,
+    /// Separates the keys derived from different kinds of secrets
+    pub(crate) info: &'static [u8],
+    /// Error of a key exchange which fails
+    pub(crate) error: IdentityError,
+}
+
+/// Key exchange authenticated by a secret both sides know. The initiator
+/// sends a header naming the secret and an ephemeral key, and the
+/// responder its own ephemeral key. The keys of the channel are derived
+/// from the Diffie-Hellman of the two, salted with the secret.
+pub(crate) struct SharedSecretKeyExchanger<V: IdentityVault> {
+    vault: V,
+    kind: &'static SharedSecretKind,
+    is_initiator: bool,
+    /// The header of an initiator, until its first message is sent
+    header: Option<Vec<u8>>,
+    secret: Option<SecretKey>,
+    /// First message, covered by the handshake hash
+    request: Vec<u8>,
+    ephemeral: Option<KeyId>,
+    peer_ephemeral: Option<PublicKey>,
+    completed: Option<CompletedKeyExchange>,
+}
+
+impl<V: IdentityVault> SharedSecretKeyExchanger<V> {
+    pub(crate) fn initiator(
+        vault: V,
+        kind: &'static SharedSecretKind,
+        header: Vec<u8>,
+        secret: SecretKey,
+    ) -> Self {
+        Self {
+            vault,
+            kind,
+            is_initiator: true,
+            header: Some(header),
+            secret: Some(secret),
+            request: Vec::new(),
+            ephemeral: None,
+            peer_ephemeral: None,
+            completed: None,
+        }
+    }
+
+    /// The responder to the first message `request` of an initiator,
+    /// whose ephemeral key starts `rest`, what follows its header. Returns
+    /// the payload of the message too
+    pub(crate) fn responder<'a>(
+        vault: V,
+        kind: &'static SharedSecretKind,
+        secret: SecretKey,
+        request: &[u8],
+        rest: &'a [u8],
+    ) -> Result<(Self, &'a [u8])> {
+        if rest.len() < CURVE25519_PUBLIC_LENGTH_USIZE {
+            return Err(kind.error.into());
+        }
+        let (peer_ephemeral, payload) = rest.split_at(CURVE25519_PUBLIC_LENGTH_USIZE);
+        let responder = Self {
+            vault,
+            kind,
+            is_initiator: false,
+            header: None,
+            secret: Some(secret),
+            request: request.to_vec(),
+            ephemeral: None,
+            peer_ephemeral: Some(PublicKey::new(peer_ephemeral.to_vec(), SecretType::X25519)),
+            completed: None,
+        };
+        Ok((responder, payload))
+    }
+
+    async fn generate_ephemeral(&mut self) -> Result<Vec<u8>> {
+        let attributes = SecretAttributes::new(
+            SecretType::X25519,
+            SecretPersistence::Ephemeral,
+            CURVE25519_SECRET_LENGTH_U32,
+        );
+        let ephemeral = self.vault.secret_generate(attributes).await?;
+        let public_key = self.vault.secret_public_key_get(&ephemeral).await?;
+        self.ephemeral = Some(ephemeral);
+        Ok(public_key.data().to_vec())
+    }
+
+    /// Derive the keys once both ephemeral keys are known, `response`
+    /// being the message of the responder
+    async fn complete(&mut self, response: &[u8]) -> Result<()> {
+        let error = self.kind.error;
+        let ephemeral = self.ephemeral.take().ok_or(error)?;
+        let peer_ephemeral = self.peer_ephemeral.take().ok_or(error)?;
+        let secret = self.secret.take().ok_or(error)?;
+
+        let mut transcript = self.request.clone();
+        transcript.extend_from_slice(response);
+        let h = self.vault.sha256(&transcript).await?;
+
+        let dh = self
+            .vault
+            .ec_diffie_hellman(&ephemeral, &peer_ephemeral)
+            .await?;
+        self.vault.secret_destroy(ephemeral).await?;
+        let buffer = SecretAttributes::new(
+            SecretType::Buffer,
+            SecretPersistence::Ephemeral,
+            secret.as_ref().len() as u32,
+        );
+        let salt = self.vault.secret_import(secret, buffer).await?;
+        let aes = SecretAttributes::new(
+            SecretType::Aes,
+            SecretPersistence::Ephemeral,
+            AES256_SECRET_LENGTH_U32,
+        );
+        let mut info = self.kind.info.to_vec();
+        info.extend_from_slice(&h);
+        let mut keys = self
+            .vault
+            .hkdf_sha256(&salt, &info, Some(&dh), vec![aes, aes])
+            .await?;
+        self.vault.secret_destroy(salt).await?;
+        self.vault.secret_destroy(dh).await?;
+
+        let responder_key = keys.pop().ok_or(error)?;
+        let initiator_key = keys.pop().ok_or(error)?;
+        let (encrypt_key, decrypt_key) = if self.is_initiator {
+            (initiator_key, responder_key)
+        } else {
+            (responder_key, initiator_key)
+        };
+        self.completed = Some(CompletedKeyExchange::new(
+            HandshakeHash::new(h),
+            encrypt_key,
+            decrypt_key,
+        ));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<V: IdentityVault> KeyExchanger for SharedSecretKeyExchanger<V> {
+    async fn name(&self) -> Result<String> {
+        Ok(self.kind.name.to_string())
+    }
+
+    async fn generate_request(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        let ephemeral = self.generate_ephemeral().await?;
+        let mut message = Vec::new();
+        if self.is_initiator {
+            let header = self.header.take().ok_or(self.kind.error)?;
+            message.extend_from_slice(&header);
+            message.extend_from_slice(&ephemeral);
+            message.extend_from_slice(payload);
+            self.request = message.clone();
+        } else {
+            message.extend_from_slice(&ephemeral);
+            message.extend_from_slice(payload);
+            self.complete(&message).await?;
+        }
+        Ok(message)
+    }
+
+    async fn handle_response(&mut self, response: &[u8]) -> Result<Vec<u8>> {
+        if !self.is_initiator || response.len() < CURVE25519_PUBLIC_LENGTH_USIZE {
+            return Err(self.kind.error.into());
+        }
+        let (peer_ephemeral, payload) = response.split_at(CURVE25519_PUBLIC_LENGTH_USIZE);
+        self.peer_ephemeral = Some(PublicKey::new(peer_ephemeral.to_vec(), SecretType::X25519));
+        self.complete(response).await?;
+        Ok(payload.to_vec())
+    }
+
+    async fn is_complete(&self) -> Result<bool> {
+        Ok(self.completed.is_some())
+    }
+
+    async fn finalize(mut self) -> Result<CompletedKeyExchange> {
+        self.completed.take().ok_or_else(|| self.kind.error.into())
+    }
+}
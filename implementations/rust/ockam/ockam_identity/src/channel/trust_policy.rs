@@ -1,7 +1,7 @@
 use crate::IdentityIdentifier;
 use ockam_core::{
     async_trait,
-    compat::{boxed::Box, sync::Arc},
+    compat::{boxed::Box, sync::Arc, vec::Vec},
     Result,
 };
 use serde::{Deserialize, Serialize};
@@ -18,21 +18,40 @@ mod trust_everyone_policy;
 pub use trust_everyone_policy::*;
 mod trust_public_key_policy;
 pub use trust_public_key_policy::*;
+mod trust_attributes_policy;
+pub use trust_attributes_policy::*;
+mod trust_hops_policy;
+pub use trust_hops_policy::*;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SecureChannelTrustInfo {
     their_identity_id: IdentityIdentifier,
+    hops: Vec<IdentityIdentifier>,
 }
 
 impl SecureChannelTrustInfo {
     pub fn their_identity_id(&self) -> &IdentityIdentifier {
         &self.their_identity_id
     }
+
+    /// Identities at the other side of the secure channels the channel
+    /// goes over, outermost first
+    pub fn hops(&self) -> &[IdentityIdentifier] {
+        &self.hops
+    }
 }
 
 impl SecureChannelTrustInfo {
     pub fn new(their_identity_id: IdentityIdentifier) -> Self {
-        Self { their_identity_id }
+        Self {
+            their_identity_id,
+            hops: Vec::new(),
+        }
+    }
+
+    pub fn with_hops(mut self, hops: Vec<IdentityIdentifier>) -> Self {
+        self.hops = hops;
+        self
     }
 }
 
@@ -0,0 +1,43 @@
+use crate::authenticated_storage::AuthenticatedStorage;
+use crate::credential::AttributesStorageUtils;
+use crate::{SecureChannelTrustInfo, TrustPolicy};
+use ockam_core::compat::{string::String, vec::Vec};
+use ockam_core::Result;
+use ockam_core::{async_trait, compat::boxed::Box};
+
+/// Trust the identities which presented a credential with the required
+/// attributes to this node before creating the channel
+#[derive(Clone)]
+pub struct TrustAttributesPolicy<S: AuthenticatedStorage> {
+    required_attributes: Vec<(String, Vec<u8>)>,
+    storage: S,
+}
+
+impl<S: AuthenticatedStorage> TrustAttributesPolicy<S> {
+    pub fn new(required_attributes: &[(String, Vec<u8>)], storage: S) -> Self {
+        Self {
+            required_attributes: required_attributes.to_vec(),
+            storage,
+        }
+    }
+}
+
+#[async_trait]
+impl<S: AuthenticatedStorage> TrustPolicy for TrustAttributesPolicy<S> {
+    async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        let attributes = match AttributesStorageUtils::get_attributes(
+            trust_info.their_identity_id(),
+            &self.storage,
+        )
+        .await?
+        {
+            Some(a) => a,
+            None => return Ok(false),
+        };
+
+        Ok(self
+            .required_attributes
+            .iter()
+            .all(|(key, value)| attributes.get(key) == Some(value)))
+    }
+}
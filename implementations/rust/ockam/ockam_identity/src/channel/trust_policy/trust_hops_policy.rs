@@ -0,0 +1,53 @@
+use crate::{SecureChannelTrustInfo, TrustPolicy};
+use ockam_core::Result;
+use ockam_core::{async_trait, compat::boxed::Box};
+
+/// Trust channels going over secure channels whose identities all satisfy
+/// a policy, e.g. only over relays with known identities. Channels which
+/// do not go over other secure channels are trusted.
+#[derive(Clone)]
+pub struct TrustHopsPolicy<P: TrustPolicy> {
+    policy: P,
+}
+
+impl<P: TrustPolicy> TrustHopsPolicy<P> {
+    pub fn new(policy: P) -> Self {
+        Self { policy }
+    }
+}
+
+#[async_trait]
+impl<P: TrustPolicy> TrustPolicy for TrustHopsPolicy<P> {
+    async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        for hop in trust_info.hops() {
+            let hop = SecureChannelTrustInfo::new(hop.clone());
+            if !self.policy.check(&hop).await? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{IdentityIdentifier, TrustIdentifierPolicy};
+
+    #[tokio::test]
+    async fn test() {
+        let relay = IdentityIdentifier::random();
+        let policy = TrustHopsPolicy::new(TrustIdentifierPolicy::new(relay.clone()));
+
+        let direct = SecureChannelTrustInfo::new(IdentityIdentifier::random());
+        assert!(policy.check(&direct).await.unwrap());
+
+        let over_relay =
+            SecureChannelTrustInfo::new(IdentityIdentifier::random()).with_hops(vec![relay]);
+        assert!(policy.check(&over_relay).await.unwrap());
+
+        let over_stranger = SecureChannelTrustInfo::new(IdentityIdentifier::random())
+            .with_hops(vec![IdentityIdentifier::random()]);
+        assert!(!policy.check(&over_stranger).await.unwrap());
+    }
+}
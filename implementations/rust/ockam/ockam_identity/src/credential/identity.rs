@@ -70,15 +70,18 @@ impl<V: IdentityVault> Identity<V> {
 
     /// Present credential to other party, route shall use secure channel
     pub async fn present_credential(&self, route: impl Into<Route>) -> Result<()> {
-        let credentials = self.credential.read().await;
-        let credential = credentials.as_ref().ok_or_else(|| {
-            Error::new(
-                Origin::Application,
-                Kind::Invalid,
-                "no credential to present",
-            )
-        })?;
+        let credential = self.credential().await.ok_or_else(no_credential)?;
+        self.present_credential_with(route, &credential).await
+    }
 
+    /// Present the given credential to other party, e.g. one issued by the
+    /// authority of a channel with its own trust context, route shall use
+    /// secure channel
+    pub async fn present_credential_with(
+        &self,
+        route: impl Into<Route>,
+        credential: &Credential<'_>,
+    ) -> Result<()> {
         let mut child_ctx = self.ctx.new_detached(Address::random_local()).await?;
         let buf = request(
             &mut child_ctx,
@@ -108,15 +111,21 @@ impl<V: IdentityVault> Identity<V> {
         authorities: impl IntoIterator<Item = &PublicIdentity>,
         authenticated_storage: &impl AuthenticatedStorage,
     ) -> Result<()> {
-        let credentials = self.credential.read().await;
-        let credential = credentials.as_ref().ok_or_else(|| {
-            Error::new(
-                Origin::Application,
-                Kind::Invalid,
-                "no credential to present",
-            )
-        })?;
+        let credential = self.credential().await.ok_or_else(no_credential)?;
+        self.present_credential_mutual_with(route, &credential, authorities, authenticated_storage)
+            .await
+    }
 
+    /// Present the given credential to other party, route shall use secure
+    /// channel. Other party is expected to present a credential issued by
+    /// one of `authorities` in response, otherwise this call errors.
+    pub async fn present_credential_mutual_with(
+        &self,
+        route: impl Into<Route>,
+        credential: &Credential<'_>,
+        authorities: impl IntoIterator<Item = &PublicIdentity>,
+        authenticated_storage: &impl AuthenticatedStorage,
+    ) -> Result<()> {
         let mut child_ctx = self.ctx.new_detached(Address::random_local()).await?;
         let path = "actions/present_mutual";
         let (buf, local_info) = request_with_local_info(
@@ -154,6 +163,67 @@ impl<V: IdentityVault> Identity<V> {
     }
 }
 
+/// Trust contexts, for a node to be a member of several projects with
+/// different authorities at the same time
+impl<V: IdentityVault> Identity<V> {
+    /// Trust the credentials issued by `authorities`, in addition to those
+    /// trusted by the credential exchange worker
+    pub async fn add_trusted_authorities(
+        &self,
+        authorities: impl IntoIterator<Item = PublicIdentity>,
+    ) {
+        let mut trusted = self.authorities.write().await;
+        for authority in authorities {
+            if !trusted
+                .iter()
+                .any(|a| a.identifier() == authority.identifier())
+            {
+                trusted.push(authority)
+            }
+        }
+    }
+
+    /// The authorities added with [`Identity::add_trusted_authorities`]
+    pub async fn trusted_authorities(&self) -> Vec<PublicIdentity> {
+        self.authorities.read().await.clone()
+    }
+
+    /// Keep a credential to present over the channels trusting its issuer,
+    /// replacing the previous one of that issuer
+    pub async fn add_credential(&self, credential: Credential<'static>) -> Result<()> {
+        let issuer = CredentialData::<Unverified>::try_from(&credential)
+            .map_err(|_| IdentityError::InvalidCredentialFormat)?
+            .unverfied_issuer()
+            .clone();
+        self.credentials.write().await.insert(issuer, credential);
+        Ok(())
+    }
+
+    /// The credential issued by `issuer`, if this identity holds one
+    pub async fn credential_issued_by(
+        &self,
+        issuer: &IdentityIdentifier,
+    ) -> Option<Credential<'static>> {
+        if let Some(credential) = self.credentials.read().await.get(issuer) {
+            return Some(credential.clone());
+        }
+        let credential = self.credential.read().await.clone()?;
+        let data = CredentialData::<Unverified>::try_from(&credential).ok()?;
+        if data.unverfied_issuer() != issuer {
+            return None;
+        }
+        Some(credential)
+    }
+}
+
+fn no_credential() -> Error {
+    Error::new(
+        Origin::Application,
+        Kind::Invalid,
+        "no credential to present",
+    )
+}
+
 impl<V: IdentityVault> Identity<V> {
     async fn verify_credential<'a>(
         sender: &IdentityIdentifier,
@@ -182,14 +252,15 @@ impl<V: IdentityVault> Identity<V> {
         Ok(credential_data)
     }
 
+    /// Verify a credential issued to this identity by one of `authorities`.
+    ///
+    /// If successful, the credential data are returned.
     pub async fn verify_self_credential<'a>(
         &self,
         credential: &'a Credential<'a>,
         authorities: impl IntoIterator<Item = &PublicIdentity>,
-    ) -> Result<()> {
-        let _ = Self::verify_credential(self.identifier(), credential, authorities, &self.vault)
-            .await?;
-        Ok(())
+    ) -> Result<CredentialData<'a, Verified>> {
+        Self::verify_credential(self.identifier(), credential, authorities, &self.vault).await
     }
 
     pub(crate) async fn receive_presented_credential(
@@ -198,17 +269,19 @@ impl<V: IdentityVault> Identity<V> {
         credential: Credential<'_>,
         authorities: impl IntoIterator<Item = &PublicIdentity>,
         authenticated_storage: &impl AuthenticatedStorage,
-    ) -> Result<()> {
+    ) -> Result<IdentityIdentifier> {
         let credential_data =
             Self::verify_credential(&sender, &credential, authorities, &self.vault).await?;
+        let issuer = credential_data.issuer().clone();
 
         AttributesStorageUtils::put_attributes(
             &sender,
-            AttributesEntry::new(credential_data.attributes, credential_data.expires),
+            AttributesEntry::new(credential_data.attributes, credential_data.expires)
+                .with_attested_by(issuer.clone()),
             authenticated_storage,
         )
         .await?;
 
-        Ok(())
+        Ok(issuer)
     }
 }
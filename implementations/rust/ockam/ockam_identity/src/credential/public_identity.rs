@@ -1,7 +1,7 @@
 use crate::authenticated_storage::AuthenticatedStorage;
 use crate::credential::{AttributesStorageUtils, Credential, CredentialData, Timestamp, Verified};
 use crate::PublicIdentity;
-use crate::{IdentityIdentifier, IdentityStateConst, IdentityVault};
+use crate::{IdentityIdentifier, IdentityStateConst, IdentityVerifier};
 use ockam_core::compat::collections::BTreeMap;
 use ockam_core::compat::{string::String, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
@@ -16,7 +16,7 @@ impl PublicIdentity {
         &self,
         credential: &'b Credential<'b>,
         subject: &IdentityIdentifier,
-        vault: &impl IdentityVault,
+        vault: &impl IdentityVerifier,
     ) -> Result<CredentialData<'a, Verified>> {
         let dat = CredentialData::try_from(credential)?;
         if dat.unverfied_key_label() != IdentityStateConst::ROOT_LABEL {
@@ -16,11 +16,22 @@ use ockam_core::Result;
 pub struct AttributesEntry<'a> {
     #[b(1)] attrs: Attributes<'a>,
     #[n(2)] expires: Timestamp,
+    /// The authority which issued the credential, unknown for the entries
+    /// stored before it was recorded
+    #[n(3)] attested_by: Option<IdentityIdentifier>,
 }
 
 impl<'a> AttributesEntry<'a> {
     pub fn new(attrs: Attributes<'a>, expires: Timestamp) -> Self {
-        Self { attrs, expires }
+        Self {
+            attrs,
+            expires,
+            attested_by: None,
+        }
+    }
+    pub fn with_attested_by(mut self, issuer: IdentityIdentifier) -> Self {
+        self.attested_by = Some(issuer);
+        self
     }
     pub fn attrs(&self) -> &Attributes<'a> {
         &self.attrs
@@ -28,6 +39,9 @@ impl<'a> AttributesEntry<'a> {
     pub fn expires(&self) -> Timestamp {
         self.expires
     }
+    pub fn attested_by(&self) -> Option<&IdentityIdentifier> {
+        self.attested_by.as_ref()
+    }
 }
 
 pub struct AttributesStorageUtils;
@@ -37,6 +51,16 @@ impl AttributesStorageUtils {
     pub async fn get_attributes(
         identity_id: &IdentityIdentifier,
         authenticated_storage: &impl AuthenticatedStorage,
+    ) -> Result<Option<BTreeMap<String, Vec<u8>>>> {
+        Self::get_attributes_attested_by(identity_id, None, authenticated_storage).await
+    }
+
+    /// Return authenticated non-expired attributes attached to that Identity,
+    /// only if one of `issuers` attested them when given
+    pub async fn get_attributes_attested_by(
+        identity_id: &IdentityIdentifier,
+        issuers: Option<&[IdentityIdentifier]>,
+        authenticated_storage: &impl AuthenticatedStorage,
     ) -> Result<Option<BTreeMap<String, Vec<u8>>>> {
         let id = identity_id.to_string();
         let entry = match authenticated_storage
@@ -59,11 +83,44 @@ impl AttributesStorageUtils {
             return Ok(None);
         }
 
+        if let Some(issuers) = issuers {
+            match entry.attested_by() {
+                Some(issuer) if issuers.contains(issuer) => {}
+                _ => return Ok(None),
+            }
+        }
+
         let attrs = entry.attrs().to_owned();
 
         Ok(Some(attrs))
     }
 
+    /// Return when the authenticated attributes attached to that Identity
+    /// expire, if it has non-expired attributes
+    pub async fn get_expiry(
+        identity_id: &IdentityIdentifier,
+        authenticated_storage: &impl AuthenticatedStorage,
+    ) -> Result<Option<Timestamp>> {
+        let entry = match authenticated_storage
+            .get(&identity_id.to_string(), IdentityStateConst::ATTRIBUTES_KEY)
+            .await?
+        {
+            Some(e) => e,
+            None => return Ok(None),
+        };
+
+        let entry: AttributesEntry = minicbor::decode(&entry)?;
+
+        let now = Timestamp::now().ok_or_else(|| {
+            ockam_core::Error::new(Origin::Core, Kind::Internal, "invalid system time")
+        })?;
+        if entry.expires() <= now {
+            return Ok(None);
+        }
+
+        Ok(Some(entry.expires()))
+    }
+
     pub(crate) async fn put_attributes(
         sender: &IdentityIdentifier,
         entry: AttributesEntry<'_>,
@@ -38,6 +38,21 @@ impl<S: AuthenticatedStorage, V: IdentityVault> CredentialExchangeWorker<S, V> {
 }
 
 impl<S: AuthenticatedStorage, V: IdentityVault> CredentialExchangeWorker<S, V> {
+    /// The authorities of the worker, and those of the channels with their
+    /// own trust context
+    async fn authorities(&self) -> Vec<PublicIdentity> {
+        let mut authorities = self.authorities.clone();
+        for authority in self.identity.trusted_authorities().await {
+            if !authorities
+                .iter()
+                .any(|a| a.identifier() == authority.identifier())
+            {
+                authorities.push(authority)
+            }
+        }
+        authorities
+    }
+
     /// Create a generic bad request response.
     pub fn bad_request<'a>(id: Id, path: &'a str, msg: &'a str) -> ResponseBuilder<Error<'a>> {
         let e = Error::new(path).with_message(msg);
@@ -80,18 +95,19 @@ impl<S: AuthenticatedStorage, V: IdentityVault> CredentialExchangeWorker<S, V> {
                 );
                 let credential: Credential = dec.decode()?;
 
+                let authorities = self.authorities().await;
                 let res = self
                     .identity
                     .receive_presented_credential(
                         sender.clone(),
                         credential,
-                        self.authorities.iter(),
+                        authorities.iter(),
                         &self.authenticated_storage,
                     )
                     .await;
 
                 match res {
-                    Ok(()) => {
+                    Ok(_) => {
                         debug!("One-way credential presentation request processed successfully with {}", sender);
                         Response::ok(req.id()).to_vec()?
                     }
@@ -111,36 +127,45 @@ impl<S: AuthenticatedStorage, V: IdentityVault> CredentialExchangeWorker<S, V> {
                 );
                 let credential: Credential = dec.decode()?;
 
+                let authorities = self.authorities().await;
                 let res = self
                     .identity
                     .receive_presented_credential(
                         sender.clone(),
                         credential,
-                        self.authorities.iter(),
+                        authorities.iter(),
                         &self.authenticated_storage,
                     )
                     .await;
 
-                if let Err(err) = res {
-                    debug!(
-                        "Mutual credential presentation request processing error: {} from {}",
-                        err, sender
-                    );
-                    Self::bad_request(req.id(), req.path(), &err.to_string()).to_vec()?
-                } else {
-                    debug!(
-                        "Mutual credential presentation request processed successfully with {}",
-                        sender
-                    );
-                    let credentials = self.identity.credential.read().await;
-                    match credentials.as_ref() {
-                        Some(p) if self.present_back => {
-                            warn!("Mutual credential presentation request processed successfully with {}. Responding with own credential...", sender);
-                            Response::ok(req.id()).body(p).to_vec()?
-                        }
-                        _ => {
-                            warn!("Mutual credential presentation request processed successfully with {}. No credential to respond!", sender);
-                            Response::ok(req.id()).to_vec()?
+                match res {
+                    Err(err) => {
+                        debug!(
+                            "Mutual credential presentation request processing error: {} from {}",
+                            err, sender
+                        );
+                        Self::bad_request(req.id(), req.path(), &err.to_string()).to_vec()?
+                    }
+                    Ok(issuer) => {
+                        debug!(
+                            "Mutual credential presentation request processed successfully with {}",
+                            sender
+                        );
+                        // Respond within the same trust context, with a
+                        // credential of the authority which issued theirs
+                        let credential = match self.identity.credential_issued_by(&issuer).await {
+                            Some(credential) => Some(credential),
+                            None => self.identity.credential.read().await.clone(),
+                        };
+                        match credential.as_ref() {
+                            Some(p) if self.present_back => {
+                                warn!("Mutual credential presentation request processed successfully with {}. Responding with own credential...", sender);
+                                Response::ok(req.id()).body(p).to_vec()?
+                            }
+                            _ => {
+                                warn!("Mutual credential presentation request processed successfully with {}. No credential to respond!", sender);
+                                Response::ok(req.id()).to_vec()?
+                            }
                         }
                     }
                 }
@@ -20,6 +20,9 @@ pub enum IdentityError {
     InvalidCredentialFormat,
     UnknownAuthority,
     CredentialVerificationFailed,
+    UnknownSecureChannel,
+    InvalidResumption,
+    InvalidPreSharedKey,
 }
 
 impl ockam_core::compat::error::Error for IdentityError {}
@@ -1,6 +1,7 @@
 use crate::{IdentityError, IdentityStateConst};
 use core::fmt::{Display, Formatter};
 use core::str::FromStr;
+use data_encoding::BASE32_NOPAD;
 use minicbor::decode::{self, Decoder};
 use minicbor::{Decode, Encode};
 use ockam_core::compat::borrow::Cow;
@@ -8,6 +9,7 @@ use ockam_core::compat::string::{String, ToString};
 use ockam_core::vault::{Hasher, KeyId};
 use ockam_core::{Error, Result};
 use serde::{Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
 
 /// An identifier of an Identity.
 #[allow(clippy::derive_hash_xor_eq)] // we manually implement a constant time Eq
@@ -18,6 +20,12 @@ pub struct IdentityIdentifier(#[n(0)] KeyId);
 /// Unique [`crate::Identity`] identifier, computed as SHA256 of root public key
 impl IdentityIdentifier {
     const PREFIX: &'static str = "P";
+    /// Prefix of the base32 form
+    const BASE32_PREFIX: &'static str = "I";
+    /// Length of the key ids which have a base32 form
+    const KEY_ID_LEN: usize = 32;
+    /// Length of the checksum of the base32 form
+    const CHECKSUM_LEN: usize = 4;
 
     /// Create an IdentityIdentifier from a KeyId
     pub fn from_key_id(key_id: &str) -> Self {
@@ -29,6 +37,42 @@ impl IdentityIdentifier {
         &self.0[Self::PREFIX.len()..]
     }
 
+    /// Return the shorter base32 form of this identifier, e.g. `Imfrg...`.
+    ///
+    /// It ends with a checksum, so that a mistyped identifier is rejected
+    /// instead of naming another identity. Only identifiers of a SHA-256
+    /// key id, as computed for identities, have a base32 form.
+    pub fn to_base32(&self) -> Option<String> {
+        let mut buf = hex::decode(self.key_id()).ok()?;
+        if buf.len() != Self::KEY_ID_LEN {
+            return None;
+        }
+        let sum = Self::checksum(&buf);
+        buf.extend_from_slice(&sum);
+        let b32 = BASE32_NOPAD.encode(&buf).to_ascii_lowercase();
+        Some(format!("{}{}", Self::BASE32_PREFIX, b32))
+    }
+
+    fn from_base32(b32: &str) -> Result<Self> {
+        let buf = BASE32_NOPAD
+            .decode(b32.to_ascii_uppercase().as_bytes())
+            .map_err(|_| IdentityError::InvalidIdentityId)?;
+        if buf.len() != Self::KEY_ID_LEN + Self::CHECKSUM_LEN {
+            return Err(IdentityError::InvalidIdentityId.into());
+        }
+        let (key_id, sum) = buf.split_at(Self::KEY_ID_LEN);
+        if Self::checksum(key_id) != sum {
+            return Err(IdentityError::InvalidIdentityId.into());
+        }
+        Ok(Self::from_key_id(&hex::encode(key_id)))
+    }
+
+    fn checksum(key_id: &[u8]) -> [u8; Self::CHECKSUM_LEN] {
+        let mut sum = [0; Self::CHECKSUM_LEN];
+        sum.copy_from_slice(&Sha256::digest(key_id)[..Self::CHECKSUM_LEN]);
+        sum
+    }
+
     pub(crate) fn ct_eq(&self, o: &Self) -> subtle::Choice {
         use subtle::ConstantTimeEq;
         self.0.as_bytes().ct_eq(o.0.as_bytes())
@@ -82,10 +126,13 @@ impl From<&IdentityIdentifier> for String {
 impl TryFrom<&str> for IdentityIdentifier {
     type Error = Error;
 
+    /// Parse an identifier in its default or its base32 form.
     fn try_from(value: &str) -> Result<Self> {
         let value = value.trim();
         if value.starts_with(Self::PREFIX) {
             Ok(Self(value.to_string()))
+        } else if let Some(b32) = value.strip_prefix(Self::BASE32_PREFIX) {
+            Self::from_base32(b32)
         } else {
             Err(IdentityError::InvalidIdentityId.into())
         }
@@ -139,6 +186,7 @@ impl ChangeIdentifier {
 #[cfg(test)]
 mod test {
     use super::IdentityIdentifier;
+    use core::str::FromStr;
     use quickcheck::{quickcheck, Arbitrary, Gen};
     use serde::de::{value, Deserialize, IntoDeserializer};
 
@@ -185,5 +233,24 @@ mod test {
         fn prop_prefix(val: Id) -> bool {
             val.0.0.starts_with(IdentityIdentifier::PREFIX)
         }
+
+        fn prop_base32(key_id: Vec<u8>) -> bool {
+            let i = IdentityIdentifier::from_key_id(&hex::encode(&key_id));
+            match i.to_base32() {
+                Some(s) => IdentityIdentifier::from_str(&s).unwrap() == i,
+                None => key_id.len() != IdentityIdentifier::KEY_ID_LEN,
+            }
+        }
+    }
+
+    #[test]
+    fn base32_rejects_typos() {
+        let i = IdentityIdentifier::from_key_id(&hex::encode([7; 32]));
+        let s = i.to_base32().unwrap();
+        assert!(s.len() < i.to_string().len());
+        let mut typo = s.into_bytes();
+        typo[10] = if typo[10] == b'a' { b'b' } else { b'a' };
+        let typo = String::from_utf8(typo).unwrap();
+        assert!(IdentityIdentifier::from_str(&typo).is_err());
     }
 }
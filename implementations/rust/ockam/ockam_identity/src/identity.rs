@@ -3,11 +3,13 @@ use crate::change::IdentitySignedChange;
 use crate::change_history::{IdentityChangeHistory, IdentityHistoryComparison};
 use crate::credential::Credential;
 use crate::{
-    ChangeIdentifier, IdentityError, IdentityIdentifier, IdentityVault, KeyAttributes,
-    PublicIdentity,
+    ChangeIdentifier, FragmentationPolicy, HandshakeRateLimit, IdentityError, IdentityIdentifier,
+    IdentityVault, KeyAttributes, KeyExchangers, PublicIdentity, RekeyPolicy, ResumptionTickets,
+    SecureChannelRegistry, XXKeyExchangers,
 };
 use ockam_core::compat::{
     boxed::Box,
+    collections::BTreeMap,
     string::{String, ToString},
     sync::Arc,
     vec::Vec,
@@ -25,6 +27,18 @@ use ockam_vault::{KeyId, SecretAttributes};
 pub struct Identity<V: IdentityVault> {
     id: IdentityIdentifier,
     pub(crate) credential: Arc<RwLock<Option<Credential<'static>>>>,
+    /// Credentials presented over the channels with their own trust
+    /// context, by issuer
+    pub(crate) credentials: Arc<RwLock<BTreeMap<IdentityIdentifier, Credential<'static>>>>,
+    /// Authorities trusted by the channels with their own trust context,
+    /// in addition to those of the credential exchange worker
+    pub(crate) authorities: Arc<RwLock<Vec<PublicIdentity>>>,
+    pub(crate) rekey_policy: Arc<RwLock<RekeyPolicy>>,
+    pub(crate) fragmentation_policy: Arc<RwLock<FragmentationPolicy>>,
+    pub(crate) handshake_rate_limit: Arc<RwLock<Option<HandshakeRateLimit>>>,
+    pub(crate) key_exchangers: Arc<RwLock<Arc<dyn KeyExchangers<V>>>>,
+    pub(crate) secure_channels: SecureChannelRegistry,
+    pub(crate) resumption: ResumptionTickets,
     pub(crate) change_history: Arc<RwLock<IdentityChangeHistory>>,
     pub(crate) ctx: Context,
     pub(crate) vault: V,
@@ -57,6 +71,14 @@ impl<V: IdentityVault> Identity<V> {
         Self {
             id,
             credential: Arc::new(RwLock::new(None)),
+            credentials: Arc::new(RwLock::new(BTreeMap::new())),
+            authorities: Arc::new(RwLock::new(Vec::new())),
+            rekey_policy: Arc::new(RwLock::new(RekeyPolicy::default())),
+            fragmentation_policy: Arc::new(RwLock::new(FragmentationPolicy::default())),
+            handshake_rate_limit: Arc::new(RwLock::new(None)),
+            key_exchangers: Arc::new(RwLock::new(Arc::new(XXKeyExchangers))),
+            secure_channels: SecureChannelRegistry::new(),
+            resumption: ResumptionTickets::default(),
             change_history: Arc::new(RwLock::new(change_history)),
             ctx,
             vault,
@@ -93,6 +115,16 @@ impl<V: IdentityVault> Identity<V> {
 
     /// Create Identity
     pub async fn create(ctx: &Context, vault: &V) -> Result<Self> {
+        Self::create_impl(ctx, vault, None).await
+    }
+
+    /// Create Identity with the Ed25519 secret `key_id` of `vault` as its
+    /// root key, e.g. to recreate the same identity from an imported secret.
+    pub async fn create_with_key(ctx: &Context, vault: &V, key_id: &KeyId) -> Result<Self> {
+        Self::create_impl(ctx, vault, Some(key_id)).await
+    }
+
+    async fn create_impl(ctx: &Context, vault: &V, key_id: Option<&KeyId>) -> Result<Self> {
         let child_ctx = ctx.new_detached(Address::random_local()).await?;
         let initial_change_id = ChangeIdentifier::initial(vault).await;
 
@@ -106,7 +138,7 @@ impl<V: IdentityVault> Identity<V> {
         );
 
         let create_key_change = Self::make_create_key_change_static(
-            None,
+            key_id,
             initial_change_id,
             key_attribs.clone(),
             None,
@@ -115,7 +115,11 @@ impl<V: IdentityVault> SecretVault for CrazyVault<V> {
         self.vault.secret_generate(attributes).await
     }
 
-    async fn secret_import(&self, secret: &[u8], attributes: SecretAttributes) -> Result<KeyId> {
+    async fn secret_import(
+        &self,
+        secret: SecretKey,
+        attributes: SecretAttributes,
+    ) -> Result<KeyId> {
         self.vault.secret_import(secret, attributes).await
     }
 
@@ -19,6 +19,7 @@ extern crate core;
 extern crate alloc;
 
 use ockam_channel::SecureChannelVault;
+use ockam_core::vault::AsymmetricVault;
 use ockam_core::AsyncTryClone;
 use ockam_vault::{Hasher, SecretVault, Signer, Verifier};
 
@@ -69,3 +70,12 @@ impl<D> IdentityVault for D where
         + 'static
 {
 }
+
+/// Traits required to verify identities and credentials
+///
+/// Only public key operations are needed, so that nodes which do not
+/// have an identity of their own, e.g. relays, can use a vault
+/// without secrets like `ockam_vault::VerifyingVault`.
+pub trait IdentityVerifier: AsymmetricVault + Hasher + Verifier + Send + Sync {}
+
+impl<D> IdentityVerifier for D where D: AsymmetricVault + Hasher + Verifier + Send + Sync {}
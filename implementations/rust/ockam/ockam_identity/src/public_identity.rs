@@ -1,5 +1,5 @@
 use crate::change_history::{IdentityChangeHistory, IdentityHistoryComparison};
-use crate::{IdentityError, IdentityIdentifier, IdentityVault};
+use crate::{IdentityError, IdentityIdentifier, IdentityVerifier};
 use ockam_core::compat::vec::Vec;
 use ockam_core::vault::Signature;
 use ockam_core::Result;
@@ -21,7 +21,7 @@ impl PublicIdentity {
         self.change_history.export()
     }
 
-    pub async fn import(data: &[u8], vault: &impl IdentityVault) -> Result<Self> {
+    pub async fn import(data: &[u8], vault: &impl IdentityVerifier) -> Result<Self> {
         let change_history = IdentityChangeHistory::import(data)?;
         if !change_history.verify_all_existing_changes(vault).await? {
             return Err(IdentityError::IdentityVerificationFailed.into());
@@ -59,7 +59,7 @@ impl PublicIdentity {
         signature: &Signature,
         data: &[u8],
         key_label: Option<&str>,
-        vault: &impl IdentityVault,
+        vault: &impl IdentityVerifier,
     ) -> Result<bool> {
         let public_key = match key_label {
             Some(label) => self.get_public_key(label)?,
@@ -4,9 +4,12 @@ use ockam_core::{route, Result, Routed, Worker};
 use ockam_identity::authenticated_storage::mem::InMemoryStorage;
 use ockam_identity::credential::access_control::CredentialAccessControl;
 use ockam_identity::credential::{AttributesStorageUtils, Credential};
-use ockam_identity::{Identity, TrustEveryonePolicy, TrustIdentifierPolicy};
+use ockam_identity::{
+    Identity, IdentitySecureChannelLocalInfo, KeyAgreement, PublicIdentity, TrustAttributesPolicy,
+    TrustEveryonePolicy, TrustIdentifierPolicy,
+};
 use ockam_node::{Context, WorkerBuilder};
-use ockam_vault::Vault;
+use ockam_vault::{Vault, VerifyingVault};
 use std::sync::atomic::{AtomicI8, Ordering};
 use std::time::Duration;
 
@@ -66,6 +69,58 @@ async fn full_flow_oneway(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn local_info_has_attributes(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+
+    let authority = Identity::create(ctx, &vault).await?;
+
+    let server = Identity::create(ctx, &vault).await?;
+    let server_storage = InMemoryStorage::new();
+
+    server
+        .create_secure_channel_listener("listener", TrustEveryonePolicy, &server_storage)
+        .await?;
+    server
+        .start_credentials_exchange_worker(
+            vec![authority.to_public().await?],
+            "credential_exchange",
+            false,
+            server_storage.clone(),
+        )
+        .await?;
+
+    let client = Identity::create(ctx, &vault).await?;
+    let client_storage = InMemoryStorage::new();
+    let channel = client
+        .create_secure_channel(route!["listener"], TrustEveryonePolicy, &client_storage)
+        .await?;
+
+    // No credential presented yet
+    ctx.send(route![channel.clone(), ctx.address()], "Hello".to_string())
+        .await?;
+    let msg = ctx.receive::<String>().await?.take();
+    let info = IdentitySecureChannelLocalInfo::find_info_from_routed(&msg)?;
+    assert_eq!(info.their_identity_id(), client.identifier());
+    assert!(info.attributes().is_empty());
+
+    let credential =
+        Credential::builder(client.identifier().clone()).with_attribute("role", b"admin");
+    let credential = authority.issue_credential(credential).await?;
+    client.set_credential(Some(credential)).await;
+    client
+        .present_credential(route![channel.clone(), "credential_exchange"])
+        .await?;
+
+    ctx.send(route![channel, ctx.address()], "Hello".to_string())
+        .await?;
+    let msg = ctx.receive::<String>().await?.take();
+    let info = IdentitySecureChannelLocalInfo::find_info_from_routed(&msg)?;
+    assert_eq!(info.attribute("role"), Some(b"admin".as_slice()));
+
+    ctx.stop().await
+}
+
 #[ockam_macros::test]
 async fn full_flow_twoway(ctx: &mut Context) -> Result<()> {
     let vault = Vault::create();
@@ -131,6 +186,95 @@ async fn full_flow_twoway(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn trust_contexts(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+
+    let authority_a = Identity::create(ctx, &vault).await?;
+    let authority_b = Identity::create(ctx, &vault).await?;
+    let authority_b_public = authority_b.to_public().await?;
+
+    // The server is a member of both projects, its listener trusts B only
+    let server = Identity::create(ctx, &vault).await?;
+    let server_storage = InMemoryStorage::new();
+    let credential =
+        Credential::builder(server.identifier().clone()).with_attribute("project", b"a");
+    let credential = authority_a.issue_credential(credential).await?;
+    server.set_credential(Some(credential)).await;
+    let credential =
+        Credential::builder(server.identifier().clone()).with_attribute("project", b"b");
+    server
+        .add_credential(authority_b.issue_credential(credential).await?)
+        .await?;
+
+    server
+        .create_trust_context_secure_channel_listener(
+            "listener",
+            vec![authority_b_public.clone()],
+            None,
+            KeyAgreement::default(),
+            TrustEveryonePolicy,
+            &server_storage,
+        )
+        .await?;
+    server
+        .start_credentials_exchange_worker(
+            vec![authority_a.to_public().await?],
+            "credential_exchange",
+            true,
+            server_storage.clone(),
+        )
+        .await?;
+
+    let client = Identity::create(ctx, &vault).await?;
+    let client_storage = InMemoryStorage::new();
+    let credential = Credential::builder(client.identifier().clone()).with_attribute("role", b"b");
+    let credential = authority_b.issue_credential(credential).await?;
+    let channel = client
+        .create_secure_channel(route!["listener"], TrustEveryonePolicy, &client_storage)
+        .await?;
+
+    // The server presents back its credential of the same project
+    client
+        .present_credential_mutual_with(
+            route![channel.clone(), "credential_exchange"],
+            &credential,
+            [&authority_b_public],
+            &client_storage,
+        )
+        .await?;
+    let attrs = AttributesStorageUtils::get_attributes(server.identifier(), &client_storage)
+        .await?
+        .unwrap();
+    assert_eq!(attrs.get("project").unwrap().as_slice(), b"b");
+
+    ctx.send(route![channel, ctx.address()], "Hello".to_string())
+        .await?;
+    let msg = ctx.receive::<String>().await?.take();
+    let info = IdentitySecureChannelLocalInfo::find_info_from_routed(&msg)?;
+    assert_eq!(info.attribute("role"), Some(b"b".as_slice()));
+
+    // Credentials of the node-wide authority give no attributes to the
+    // channels of the listener
+    let other = Identity::create(ctx, &vault).await?;
+    let credential = Credential::builder(other.identifier().clone()).with_attribute("role", b"a");
+    let credential = authority_a.issue_credential(credential).await?;
+    let channel = other
+        .create_secure_channel(route!["listener"], TrustEveryonePolicy, &client_storage)
+        .await?;
+    other
+        .present_credential_with(route![channel.clone(), "credential_exchange"], &credential)
+        .await?;
+
+    ctx.send(route![channel, ctx.address()], "Hello".to_string())
+        .await?;
+    let msg = ctx.receive::<String>().await?.take();
+    let info = IdentitySecureChannelLocalInfo::find_info_from_routed(&msg)?;
+    assert!(info.attributes().is_empty());
+
+    ctx.stop().await
+}
+
 struct CountingWorker {
     msgs_count: Arc<AtomicI8>,
 }
@@ -223,3 +367,107 @@ async fn access_control(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+#[ockam_macros::test]
+async fn trust_attributes_policy(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+
+    let authority = Identity::create(ctx, &vault).await?;
+
+    let server = Identity::create(ctx, &vault).await?;
+    let server_storage = InMemoryStorage::new();
+
+    server
+        .create_secure_channel_listener("listener", TrustEveryonePolicy, &server_storage)
+        .await?;
+    let required_attributes = vec![("is_superuser".to_string(), b"true".to_vec())];
+    server
+        .create_secure_channel_listener(
+            "superusers",
+            TrustAttributesPolicy::new(&required_attributes, server_storage.clone()),
+            &server_storage,
+        )
+        .await?;
+
+    let authorities = vec![authority.to_public().await?];
+
+    server
+        .start_credentials_exchange_worker(
+            authorities,
+            "credential_exchange",
+            false,
+            server_storage.clone(),
+        )
+        .await?;
+
+    let client = Identity::create(ctx, &vault).await?;
+    let client_storage = InMemoryStorage::new();
+    let trust_policy = TrustIdentifierPolicy::new(server.identifier().clone());
+
+    let credential_builder = Credential::builder(client.identifier().clone());
+    let credential = credential_builder.with_attribute("is_superuser", b"true");
+    let credential = authority.issue_credential(credential).await?;
+    client.set_credential(Some(credential)).await;
+
+    let counter = Arc::new(AtomicI8::new(0));
+
+    let worker = CountingWorker {
+        msgs_count: counter.clone(),
+    };
+
+    ctx.start_worker("counter", worker).await?;
+
+    // The server does not complete the channel before the credential is presented
+    let channel = client
+        .create_secure_channel(route!["superusers"], trust_policy.clone(), &client_storage)
+        .await?;
+    ctx.send(route![channel, "counter"], "Hello".to_string())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(counter.load(Ordering::Relaxed), 0);
+
+    let channel = client
+        .create_secure_channel(route!["listener"], trust_policy.clone(), &client_storage)
+        .await?;
+    client
+        .present_credential(route![channel, "credential_exchange"])
+        .await?;
+
+    let channel = client
+        .create_secure_channel(route!["superusers"], trust_policy, &client_storage)
+        .await?;
+    ctx.send(route![channel, "counter"], "Hello".to_string())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(counter.load(Ordering::Relaxed), 1);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn verify_credential_without_secrets(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+
+    let authority = Identity::create(ctx, &vault).await?;
+    let client = Identity::create(ctx, &vault).await?;
+
+    let credential =
+        Credential::builder(client.identifier().clone()).with_attribute("is_relay", b"true");
+    let credential = authority.issue_credential(credential).await?;
+
+    // A node without secrets can still check the authority and its credentials
+    let verifier = VerifyingVault;
+    let authority = PublicIdentity::import(&authority.export().await?, &verifier).await?;
+    let data = authority
+        .verify_credential(&credential, client.identifier(), &verifier)
+        .await?;
+    assert_eq!(data.attributes().get("is_relay"), Some(&b"true"[..]));
+
+    let other = Identity::create(ctx, &vault).await?;
+    assert!(authority
+        .verify_credential(&credential, other.identifier(), &verifier)
+        .await
+        .is_err());
+
+    ctx.stop().await
+}
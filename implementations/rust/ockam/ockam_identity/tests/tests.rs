@@ -1,5 +1,5 @@
 use ockam_core::errcode::{Kind, Origin};
-use ockam_core::vault::{SecretAttributes, SecretPersistence, SecretType, SecretVault};
+use ockam_core::vault::{SecretAttributes, SecretKey, SecretPersistence, SecretType, SecretVault};
 use ockam_core::{Error, Result};
 use ockam_identity::authenticated_storage::mem::InMemoryStorage;
 use ockam_identity::Identity;
@@ -180,3 +180,24 @@ async fn add_key(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+#[ockam_macros::test]
+async fn create_with_key(ctx: &mut Context) -> Result<()> {
+    let attributes = SecretAttributes::new(SecretType::Ed25519, SecretPersistence::Persistent, 32);
+    let mut secret = [0u8; 32];
+    thread_rng().fill_bytes(&mut secret);
+
+    // The same secret gives the same identity, in any vault
+    let mut identifiers = Vec::new();
+    for _ in 0..2 {
+        let vault = Vault::create();
+        let key = vault
+            .secret_import(SecretKey::new(secret.to_vec()), attributes)
+            .await?;
+        let identity = Identity::create_with_key(ctx, &vault, &key).await?;
+        identifiers.push(identity.identifier().clone());
+    }
+    assert_eq!(identifiers[0], identifiers[1]);
+
+    ctx.stop().await
+}
@@ -22,7 +22,8 @@ extern crate core;
 extern crate alloc;
 
 use ockam_core::compat::{string::String, vec::Vec};
-use ockam_core::{async_trait, compat::boxed::Box, vault::KeyId, Result};
+use ockam_core::vault::{HandshakeHash, KeyId};
+use ockam_core::{async_trait, compat::boxed::Box, Result};
 use zeroize::Zeroize;
 
 /// A trait implemented by both Initiator and Responder peers.
@@ -58,14 +59,14 @@ pub trait NewKeyExchanger {
 #[derive(Debug, Zeroize)]
 #[zeroize(drop)]
 pub struct CompletedKeyExchange {
-    h: [u8; 32],
+    h: HandshakeHash,
     encrypt_key: KeyId,
     decrypt_key: KeyId,
 }
 
 impl CompletedKeyExchange {
     /// The state hash.
-    pub fn h(&self) -> &[u8; 32] {
+    pub fn h(&self) -> &HandshakeHash {
         &self.h
     }
     /// The derived encryption key.
@@ -80,7 +81,7 @@ impl CompletedKeyExchange {
 
 impl CompletedKeyExchange {
     /// Build a CompletedKeyExchange comprised of the input parameters.
-    pub fn new(h: [u8; 32], encrypt_key: KeyId, decrypt_key: KeyId) -> Self {
+    pub fn new(h: HandshakeHash, encrypt_key: KeyId, decrypt_key: KeyId) -> Self {
         CompletedKeyExchange {
             h,
             encrypt_key,
@@ -6,8 +6,8 @@ use ockam_core::compat::{
     vec::Vec,
 };
 use ockam_core::vault::{
-    KeyId, PublicKey, SecretAttributes, SecretPersistence, SecretType, AES256_SECRET_LENGTH_U32,
-    CURVE25519_SECRET_LENGTH_U32,
+    HandshakeHash, KeyId, PublicKey, SecretAttributes, SecretKey, SecretPersistence, SecretType,
+    AES256_SECRET_LENGTH_U32, CURVE25519_SECRET_LENGTH_U32,
 };
 use ockam_core::Result;
 use ockam_core::{async_trait, compat::boxed::Box};
@@ -173,22 +173,23 @@ impl<V: X3dhVault> KeyExchanger for Responder<V> {
                 ikm_bytes.extend_from_slice(self.vault.secret_export(&dh2).await?.as_ref());
                 ikm_bytes.extend_from_slice(self.vault.secret_export(&dh3).await?.as_ref());
                 ikm_bytes.extend_from_slice(self.vault.secret_export(&dh4).await?.as_ref());
+                let ikm_bytes = SecretKey::new(ikm_bytes);
 
                 let ikm = self
                     .vault
                     .secret_import(
-                        &ikm_bytes,
+                        ikm_bytes.clone(),
                         SecretAttributes::new(
                             SecretType::Buffer,
                             SecretPersistence::Ephemeral,
-                            ikm_bytes.len() as u32,
+                            ikm_bytes.as_ref().len() as u32,
                         ),
                     )
                     .await?;
                 let salt = self
                     .vault
                     .secret_import(
-                        &[0u8; 32],
+                        SecretKey::new(vec![0u8; 32]),
                         SecretAttributes::new(
                             SecretType::Buffer,
                             SecretPersistence::Ephemeral,
@@ -209,11 +210,12 @@ impl<V: X3dhVault> KeyExchanger for Responder<V> {
                 let decrypt_key = keyrefs.pop().ok_or(X3DHError::InvalidState)?;
                 let encrypt_key = keyrefs.pop().ok_or(X3DHError::InvalidState)?;
                 let mut state_hash = self.vault.sha256(CSUITE).await?.to_vec();
-                state_hash.append(&mut ikm_bytes);
-                let state_hash = self.vault.sha256(state_hash.as_slice()).await?;
+                state_hash.extend_from_slice(ikm_bytes.as_ref());
+                let state_hash = SecretKey::new(state_hash);
+                let state_hash = self.vault.sha256(state_hash.as_ref()).await?;
 
                 self.completed_key_exchange = Some(CompletedKeyExchange::new(
-                    state_hash,
+                    HandshakeHash::new(state_hash),
                     encrypt_key,
                     decrypt_key,
                 ));
@@ -16,12 +16,20 @@ enum InitiatorState {
 }
 
 /// Represents an XX initiator
-#[derive(Debug)]
 pub struct Initiator<V: XXVault> {
     state: InitiatorState,
     state_data: State<V>,
 }
 
+impl<V: XXVault> core::fmt::Debug for Initiator<V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Initiator")
+            .field("state", &self.state)
+            .field("state_data", &self.state_data)
+            .finish()
+    }
+}
+
 impl<V: XXVault> Initiator<V> {
     pub(crate) fn new(state_data: State<V>) -> Self {
         Initiator {
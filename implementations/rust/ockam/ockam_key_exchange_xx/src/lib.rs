@@ -67,6 +67,7 @@ use ockam_core::vault::{AsymmetricVault, Hasher, SecretVault, SymmetricVault};
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ockam_core::vault::test_support::assert_redacted;
     use ockam_key_exchange_core::{KeyExchanger, NewKeyExchanger};
     use ockam_vault::Vault;
 
@@ -118,4 +119,37 @@ mod tests {
         })
         .unwrap();
     }
+
+    #[test]
+    fn handshake_state_is_not_debug_formatted() {
+        let (mut ctx, mut exec) = ockam_node::NodeBuilder::without_access_control().build();
+        exec.execute(async move {
+            let vault = Vault::create();
+
+            let key_exchanger = XXNewKeyExchanger::new(vault.async_try_clone().await.unwrap());
+
+            let mut initiator = key_exchanger.initiator().await.unwrap();
+            let mut responder = key_exchanger.responder().await.unwrap();
+
+            let m1 = initiator.generate_request(&[]).await.unwrap();
+            let _ = responder.handle_response(&m1).await.unwrap();
+            let m2 = responder.generate_request(&[]).await.unwrap();
+            let _ = initiator.handle_response(&m2).await.unwrap();
+            let m3 = initiator.generate_request(&[]).await.unwrap();
+            let _ = responder.handle_response(&m3).await.unwrap();
+
+            let debug = format!("{:?} {:?}", initiator, responder);
+            let initiator = initiator.finalize().await.unwrap();
+            for key in [initiator.encrypt_key(), initiator.decrypt_key()] {
+                assert!(!debug.contains(key.as_str()));
+                let secret = vault.secret_export(key).await.unwrap();
+                assert_redacted(&debug, secret.as_ref());
+            }
+            assert_redacted(&debug, initiator.h().as_ref());
+            assert_redacted(&initiator, initiator.h().as_ref());
+
+            ctx.stop().await.unwrap();
+        })
+        .unwrap();
+    }
 }
@@ -16,12 +16,20 @@ enum ResponderState {
 }
 
 /// Represents an XX responder
-#[derive(Debug)]
 pub struct Responder<V: XXVault> {
     state: ResponderState,
     state_data: State<V>,
 }
 
+impl<V: XXVault> core::fmt::Debug for Responder<V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Responder")
+            .field("state", &self.state)
+            .field("state_data", &self.state_data)
+            .finish()
+    }
+}
+
 impl<V: XXVault> Responder<V> {
     pub(crate) fn new(state_data: State<V>) -> Self {
         Responder {
@@ -1,7 +1,7 @@
 use crate::{XXError, XXVault, AES_GCM_TAGSIZE_USIZE, SHA256_SIZE_USIZE};
 use ockam_core::vault::{
-    KeyId, PublicKey, SecretAttributes, SecretPersistence, SecretType, AES256_SECRET_LENGTH_U32,
-    CURVE25519_PUBLIC_LENGTH_USIZE, CURVE25519_SECRET_LENGTH_U32,
+    HandshakeHash, KeyId, PublicKey, SecretAttributes, SecretPersistence, SecretType,
+    AES256_SECRET_LENGTH_U32, CURVE25519_PUBLIC_LENGTH_USIZE, CURVE25519_SECRET_LENGTH_U32,
 };
 use ockam_core::{compat::vec::Vec, Result};
 use ockam_key_exchange_core::CompletedKeyExchange;
@@ -24,16 +24,12 @@ pub(crate) struct State<V: XXVault> {
     vault: V,
 }
 
+/// Omits the hash and the keys of the handshake, which must not be logged.
 impl<V: XXVault> core::fmt::Debug for State<V> {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        write!(
-            f,
-            "SymmetricState {{ key: {:?}, nonce: {:?}, h: {:?}, ck: {:?} }}",
-            self.dh_state.key(),
-            self.nonce,
-            self.h,
-            self.dh_state.ck()
-        )
+        f.debug_struct("State")
+            .field("nonce", &self.nonce)
+            .finish_non_exhaustive()
     }
 }
 
@@ -188,7 +184,11 @@ impl<V: XXVault> State<V> {
     fn finalize(self, encrypt_key: KeyId, decrypt_key: KeyId) -> Result<CompletedKeyExchange> {
         let h = self.h.ok_or(XXError::InvalidState)?;
 
-        Ok(CompletedKeyExchange::new(h, encrypt_key, decrypt_key))
+        Ok(CompletedKeyExchange::new(
+            HandshakeHash::new(h),
+            encrypt_key,
+            decrypt_key,
+        ))
     }
 }
 
@@ -386,7 +386,7 @@ mod tests {
     use crate::{Initiator, Responder, XXVault};
     use hex::{decode, encode};
     use ockam_core::vault::{
-        SecretAttributes, SecretPersistence, SecretType, SecretVault, SymmetricVault,
+        SecretAttributes, SecretKey, SecretPersistence, SecretType, SecretVault, SymmetricVault,
         CURVE25519_SECRET_LENGTH_U32,
     };
     use ockam_key_exchange_core::KeyExchanger;
@@ -615,26 +615,41 @@ mod tests {
             let bob = res.unwrap();
             assert_eq!(alice.h(), bob.h());
             let res = vault
-                .aead_aes_gcm_encrypt(alice.encrypt_key(), b"hello bob", &[0u8; 12], alice.h())
+                .aead_aes_gcm_encrypt(
+                    alice.encrypt_key(),
+                    b"hello bob",
+                    &[0u8; 12],
+                    alice.h().as_ref(),
+                )
                 .await;
 
             assert!(res.is_ok());
             let ciphertext = res.unwrap();
 
             let res = vault
-                .aead_aes_gcm_decrypt(bob.decrypt_key(), &ciphertext, &[0u8; 12], bob.h())
+                .aead_aes_gcm_decrypt(bob.decrypt_key(), &ciphertext, &[0u8; 12], bob.h().as_ref())
                 .await;
             assert!(res.is_ok());
             let plaintext = res.unwrap();
             assert_eq!(plaintext, b"hello bob");
 
             let res = vault
-                .aead_aes_gcm_encrypt(bob.encrypt_key(), b"hello alice", &[1u8; 12], bob.h())
+                .aead_aes_gcm_encrypt(
+                    bob.encrypt_key(),
+                    b"hello alice",
+                    &[1u8; 12],
+                    bob.h().as_ref(),
+                )
                 .await;
             assert!(res.is_ok());
             let ciphertext = res.unwrap();
             let res = vault
-                .aead_aes_gcm_decrypt(alice.decrypt_key(), &ciphertext, &[1u8; 12], alice.h())
+                .aead_aes_gcm_decrypt(
+                    alice.decrypt_key(),
+                    &ciphertext,
+                    &[1u8; 12],
+                    alice.h().as_ref(),
+                )
                 .await;
             assert!(res.is_ok());
             let plaintext = res.unwrap();
@@ -657,7 +672,7 @@ mod tests {
         );
         // Static x25519 for this handshake, `s`
         let static_secret_handle = vault
-            .secret_import(&decode(static_private).unwrap(), attributes)
+            .secret_import(SecretKey::new(decode(static_private).unwrap()), attributes)
             .await
             .unwrap();
         let static_public_key = vault
@@ -667,7 +682,10 @@ mod tests {
 
         // Ephemeral x25519 for this handshake, `e`
         let ephemeral_secret_handle = vault
-            .secret_import(&decode(ephemeral_private).unwrap(), attributes)
+            .secret_import(
+                SecretKey::new(decode(ephemeral_private).unwrap()),
+                attributes,
+            )
             .await
             .unwrap();
         let ephemeral_public_key = vault
@@ -686,7 +704,10 @@ mod tests {
             SecretPersistence::Ephemeral,
             ck.len() as u32,
         );
-        let ck = vault.secret_import(&ck[..], attributes).await.unwrap();
+        let ck = vault
+            .secret_import(SecretKey::new(ck.to_vec()), attributes)
+            .await
+            .unwrap();
 
         State {
             run_prologue: false,
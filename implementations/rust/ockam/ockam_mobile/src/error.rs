@@ -0,0 +1,40 @@
+use core::fmt;
+
+/// Errors returned to the mobile application.
+#[derive(Debug, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum MobileError {
+    /// The embedded node failed to start or was already stopped.
+    Node(String),
+    /// The node rejected the request.
+    Request(String),
+    /// An invalid argument was passed by the application.
+    InvalidArgument(String),
+}
+
+impl std::error::Error for MobileError {}
+
+impl fmt::Display for MobileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Node(e) => write!(f, "node error: {}", e),
+            Self::Request(e) => write!(f, "request failed: {}", e),
+            Self::InvalidArgument(e) => write!(f, "invalid argument: {}", e),
+        }
+    }
+}
+
+impl From<ockam_core::Error> for MobileError {
+    fn from(e: ockam_core::Error) -> Self {
+        Self::Node(e.to_string())
+    }
+}
+
+impl From<minicbor::decode::Error> for MobileError {
+    fn from(e: minicbor::decode::Error) -> Self {
+        Self::Request(e.to_string())
+    }
+}
+
+/// Result type of the mobile API.
+pub type Result<T> = core::result::Result<T, MobileError>;
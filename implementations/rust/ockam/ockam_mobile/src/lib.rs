@@ -0,0 +1,29 @@
+//! Android and iOS bindings for Ockam nodes and portals.
+//!
+//! This crate exposes a small, mobile friendly API on top of an embedded Ockam
+//! node: creating an identity, enrolling it with a project and creating TCP
+//! inlets to services reachable through Ockam relays. Kotlin (JNI) and Swift
+//! bindings are generated with [UniFFI](https://mozilla.github.io/uniffi-rs/):
+//!
+//! ```text
+//! cargo run -p ockam_mobile --features cli --bin uniffi-bindgen -- \
+//!     generate --library target/debug/libockam_mobile.so --language kotlin --out-dir out
+//! ```
+#![warn(
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications
+)]
+
+#[macro_use]
+extern crate tracing;
+
+mod error;
+mod node;
+
+pub use error::*;
+pub use node::*;
+
+uniffi::setup_scaffolding!();
@@ -0,0 +1,339 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::thread;
+
+use minicbor::{Decoder, Encode};
+use ockam::{Context, NodeBuilder, TcpTransport};
+use ockam_api::config::cli::{AuthoritiesConfig, Authority};
+use ockam_api::nodes::models::credentials::GetCredentialRequest;
+use ockam_api::nodes::models::identity::ShortIdentityResponse;
+use ockam_api::nodes::models::portal::{CreateInlet, InletStatus};
+use ockam_api::nodes::models::transport::{TransportMode, TransportType};
+use ockam_api::nodes::{NodeManager, NodeManagerWorker, NODEMANAGER_ADDR};
+use ockam_core::api::{self, Request, RequestBuilder, Response, Status};
+use ockam_core::{route, Address};
+use ockam_identity::PublicIdentity;
+use ockam_multiaddr::MultiAddr;
+use ockam_node::tokio::runtime::Handle;
+use ockam_vault::Vault;
+
+use crate::{MobileError, Result};
+
+const NODE_NAME: &str = "mobile";
+
+/// The project a mobile node gets its credential from.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct Project {
+    /// The project id.
+    pub id: String,
+    /// The hex encoded identity of the project authority.
+    pub authority_identity: String,
+    /// The route to the project authority, as a multiaddr.
+    pub authority_route: String,
+}
+
+/// An inlet created by the application.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct Inlet {
+    /// The alias of the inlet.
+    pub alias: String,
+    /// The local address the inlet listens on.
+    pub bind_addr: String,
+    /// The route to the outlet, as a multiaddr.
+    pub outlet_route: String,
+}
+
+#[derive(Default)]
+struct State {
+    inlets: BTreeMap<String, Inlet>,
+    background: bool,
+}
+
+/// An Ockam node embedded in a mobile application.
+///
+/// Methods block the calling thread until the node has handled the request,
+/// so they should not be called from the UI thread.
+///
+/// Mobile operating systems tear down the sockets of suspended applications.
+/// Call [`MobileNode::on_background`] when the application is backgrounded to
+/// release the inlet listeners, and [`MobileNode::on_foreground`] when it
+/// comes back to recreate them with the same aliases and addresses.
+#[derive(uniffi::Object)]
+pub struct MobileNode {
+    ctx: Mutex<Option<Context>>,
+    runtime: Handle,
+    state: Mutex<State>,
+}
+
+#[uniffi::export]
+impl MobileNode {
+    /// Start a node storing its vault and identity in `state_dir`.
+    ///
+    /// An identity is created on first start and reused afterwards. If a
+    /// `project` is given, the node can [`enroll`](MobileNode::enroll) with
+    /// the project authority.
+    #[uniffi::constructor]
+    pub fn start(state_dir: String, project: Option<Project>) -> Result<Arc<Self>> {
+        let node_dir = PathBuf::from(state_dir);
+        std::fs::create_dir_all(&node_dir).map_err(|e| MobileError::Node(e.to_string()))?;
+
+        let (ctx, mut executor) = NodeBuilder::without_access_control().no_logging().build();
+        let (tx, rx) = mpsc::channel();
+        thread::Builder::new()
+            .name("ockam-node".to_string())
+            .spawn(move || {
+                let res = executor.execute(async move {
+                    let mut ctx = ctx;
+                    match start_node_manager(&ctx, node_dir, project).await {
+                        Ok(()) => {
+                            let _ = tx.send(Ok(ctx));
+                        }
+                        Err(e) => {
+                            let _ = ctx.stop().await;
+                            let _ = tx.send(Err(e));
+                        }
+                    }
+                });
+                if let Err(e) = res {
+                    error!(%e, "Mobile node stopped with an error");
+                }
+            })
+            .map_err(|e| MobileError::Node(e.to_string()))?;
+
+        let ctx = rx
+            .recv()
+            .map_err(|_| MobileError::Node("node stopped while starting".to_string()))??;
+        Ok(Arc::new(Self {
+            runtime: ctx.runtime().clone(),
+            ctx: Mutex::new(Some(ctx)),
+            state: Default::default(),
+        }))
+    }
+
+    /// Return the identifier of the node's identity.
+    pub fn identifier(&self) -> Result<String> {
+        let req = Request::post("/node/identity/actions/show/short");
+        let buf = self.request("show identity", req)?;
+        let mut dec = Decoder::new(&buf);
+        check_response(&mut dec)?;
+        let res: ShortIdentityResponse = dec.decode()?;
+        Ok(res.identity_id.to_string())
+    }
+
+    /// Get a credential from the project authority.
+    ///
+    /// The identity must have been added as a member of the project first,
+    /// e.g. by an enroller using `ockam project enroll --member <identifier>`.
+    pub fn enroll(&self) -> Result<()> {
+        let req =
+            Request::post("/node/credentials/actions/get").body(GetCredentialRequest::new(true));
+        let buf = self.request("enroll", req)?;
+        check_response(&mut Decoder::new(&buf))
+    }
+
+    /// Create an inlet listening on `bind_addr` and forwarding to `outlet_route`.
+    pub fn create_inlet(&self, bind_addr: String, outlet_route: String) -> Result<Inlet> {
+        MultiAddr::try_from(outlet_route.as_str())
+            .map_err(|e| MobileError::InvalidArgument(format!("outlet route: {}", e)))?;
+        let mut state = self.state()?;
+        if state.background {
+            return Err(MobileError::Node("node is in the background".to_string()));
+        }
+        let inlet = self.start_inlet(&bind_addr, &outlet_route, None)?;
+        state.inlets.insert(inlet.alias.clone(), inlet.clone());
+        Ok(inlet)
+    }
+
+    /// Delete the inlet with the given alias.
+    pub fn delete_inlet(&self, alias: String) -> Result<()> {
+        let mut state = self.state()?;
+        if state.inlets.remove(&alias).is_none() {
+            return Err(MobileError::InvalidArgument(format!(
+                "unknown inlet {}",
+                alias
+            )));
+        }
+        if !state.background {
+            self.stop_inlet(&alias)?;
+        }
+        Ok(())
+    }
+
+    /// Return the inlets created by the application.
+    pub fn inlets(&self) -> Result<Vec<Inlet>> {
+        Ok(self.state()?.inlets.values().cloned().collect())
+    }
+
+    /// Release the inlet listeners before the application is suspended.
+    pub fn on_background(&self) -> Result<()> {
+        let mut state = self.state()?;
+        if state.background {
+            return Ok(());
+        }
+        state.background = true;
+        for alias in state.inlets.keys() {
+            if let Err(e) = self.stop_inlet(alias) {
+                warn!(%alias, %e, "Failed to stop inlet");
+            }
+        }
+        Ok(())
+    }
+
+    /// Recreate the inlets when the application is resumed.
+    ///
+    /// All inlets are recreated even if some of them fail, in which case the
+    /// first error is returned.
+    pub fn on_foreground(&self) -> Result<()> {
+        let mut state = self.state()?;
+        if !state.background {
+            return Ok(());
+        }
+        state.background = false;
+        let mut result = Ok(());
+        for inlet in state.inlets.values() {
+            if let Err(e) =
+                self.start_inlet(&inlet.bind_addr, &inlet.outlet_route, Some(&inlet.alias))
+            {
+                warn!(alias = %inlet.alias, %e, "Failed to recreate inlet");
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+
+    /// Stop the node.
+    ///
+    /// Any further call on this node returns an error.
+    pub fn stop(&self) -> Result<()> {
+        let ctx = self
+            .ctx
+            .lock()
+            .map_err(|_| MobileError::Node("node lock poisoned".to_string()))?
+            .take();
+        if let Some(mut ctx) = ctx {
+            self.runtime.block_on(async move { ctx.stop().await })?;
+        }
+        Ok(())
+    }
+}
+
+impl MobileNode {
+    fn state(&self) -> Result<MutexGuard<'_, State>> {
+        self.state
+            .lock()
+            .map_err(|_| MobileError::Node("node lock poisoned".to_string()))
+    }
+
+    fn start_inlet(
+        &self,
+        bind_addr: &str,
+        outlet_route: &str,
+        alias: Option<&str>,
+    ) -> Result<Inlet> {
+        let req = Request::post("/node/inlet").body(CreateInlet::new(
+            bind_addr,
+            outlet_route,
+            alias.map(|a| a.to_string().into()),
+            false,
+        ));
+        let buf = self.request("create inlet", req)?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        let status: InletStatus = dec.decode()?;
+        if res.status() != Some(Status::Ok) {
+            let reason = status.payload.map(|p| p.to_string()).unwrap_or_default();
+            return Err(MobileError::Request(reason));
+        }
+        Ok(Inlet {
+            alias: status.alias.to_string(),
+            bind_addr: status.bind_addr.to_string(),
+            outlet_route: outlet_route.to_string(),
+        })
+    }
+
+    fn stop_inlet(&self, alias: &str) -> Result<()> {
+        let req = Request::delete(format!("/node/inlet/{}", alias));
+        let buf = self.request("delete inlet", req)?;
+        check_response(&mut Decoder::new(&buf))
+    }
+
+    /// Send a request to the node manager and return the raw response.
+    fn request<T>(&self, label: &str, req: RequestBuilder<'_, T>) -> Result<Vec<u8>>
+    where
+        T: Encode<()>,
+    {
+        let guard = self
+            .ctx
+            .lock()
+            .map_err(|_| MobileError::Node("node lock poisoned".to_string()))?;
+        let ctx = guard
+            .as_ref()
+            .ok_or_else(|| MobileError::Node("node is stopped".to_string()))?;
+        let buf = self.runtime.block_on(async {
+            let mut ctx = ctx.new_detached(Address::random_local()).await?;
+            ockam_node::api::request(&mut ctx, label, None, route![NODEMANAGER_ADDR], req).await
+        })?;
+        Ok(buf)
+    }
+}
+
+impl Drop for MobileNode {
+    fn drop(&mut self) {
+        if let Err(e) = self.stop() {
+            warn!(%e, "Failed to stop mobile node");
+        }
+    }
+}
+
+/// Check the response status, returning the error message of failed requests.
+fn check_response(dec: &mut Decoder<'_>) -> Result<()> {
+    api::check_response(dec).map_err(|e| MobileError::Request(e.to_string()))
+}
+
+async fn start_node_manager(
+    ctx: &Context,
+    node_dir: PathBuf,
+    project: Option<Project>,
+) -> Result<()> {
+    let (authorities, project_id) = match project {
+        Some(p) => (Some(authorities(&p).await?), Some(p.id.into_bytes())),
+        None => (None, None),
+    };
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let bind = tcp.listen("127.0.0.1:0").await?.to_string();
+    let node_manager = NodeManager::create(
+        ctx,
+        NODE_NAME.to_string(),
+        node_dir,
+        None,
+        false,
+        false,
+        authorities.as_ref(),
+        project_id,
+        (TransportType::Tcp, TransportMode::Listen, bind),
+        tcp,
+    )
+    .await?;
+
+    ctx.start_worker(NODEMANAGER_ADDR, NodeManagerWorker::new(node_manager))
+        .await?;
+    Ok(())
+}
+
+async fn authorities(project: &Project) -> Result<AuthoritiesConfig> {
+    let identity = hex::decode(&project.authority_identity)
+        .map_err(|e| MobileError::InvalidArgument(format!("authority identity: {}", e)))?;
+    let route = MultiAddr::try_from(project.authority_route.as_str())
+        .map_err(|e| MobileError::InvalidArgument(format!("authority route: {}", e)))?;
+    let identifier = PublicIdentity::import(&identity, &Vault::default())
+        .await?
+        .identifier()
+        .clone();
+    let mut config = AuthoritiesConfig::default();
+    config.add_authority(identifier, Authority::new(identity, route));
+    Ok(config)
+}
@@ -0,0 +1,65 @@
+use std::net::TcpListener;
+use std::thread::sleep;
+use std::time::Duration;
+
+use ockam_mobile::MobileNode;
+
+fn free_addr() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+/// Listeners are closed asynchronously once an inlet is stopped.
+fn wait_until_released(addr: &str) {
+    for _ in 0..50 {
+        if TcpListener::bind(addr).is_ok() {
+            return;
+        }
+        sleep(Duration::from_millis(20));
+    }
+    panic!("{} is still in use", addr);
+}
+
+#[test]
+fn identity_is_persisted() {
+    let dir = tempfile::tempdir().unwrap();
+    let state_dir = dir.path().to_string_lossy().to_string();
+
+    let node = MobileNode::start(state_dir.clone(), None).unwrap();
+    let identifier = node.identifier().unwrap();
+    assert!(identifier.starts_with('P'));
+    node.stop().unwrap();
+    assert!(node.identifier().is_err());
+
+    let node = MobileNode::start(state_dir, None).unwrap();
+    assert_eq!(node.identifier().unwrap(), identifier);
+    node.stop().unwrap();
+}
+
+#[test]
+fn inlets_are_released_in_background() {
+    let dir = tempfile::tempdir().unwrap();
+    let node = MobileNode::start(dir.path().to_string_lossy().to_string(), None).unwrap();
+
+    let bind_addr = free_addr();
+    let inlet = node
+        .create_inlet(bind_addr.clone(), "/service/outlet".to_string())
+        .unwrap();
+    assert!(TcpListener::bind(&bind_addr).is_err());
+
+    node.on_background().unwrap();
+    wait_until_released(&bind_addr);
+    assert!(node
+        .create_inlet(free_addr(), "/service/outlet".to_string())
+        .is_err());
+
+    node.on_foreground().unwrap();
+    assert!(TcpListener::bind(&bind_addr).is_err());
+    let inlets = node.inlets().unwrap();
+    assert_eq!(inlets.len(), 1);
+    assert_eq!(inlets[0].alias, inlet.alias);
+
+    node.delete_inlet(inlet.alias).unwrap();
+    wait_until_released(&bind_addr);
+    node.stop().unwrap();
+}
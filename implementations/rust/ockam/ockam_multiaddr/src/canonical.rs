@@ -0,0 +1,139 @@
+//! Canonical forms and comparison of [`MultiAddr`]s.
+//!
+//! Different multi-addresses can denote the same destination, e.g.
+//! `/dnsaddr/Example.COM./tcp/4000` and `/dnsaddr/example.com/tcp/4000`.
+//! The canonical form of an address picks a single representative so
+//! that equivalent addresses compare equal.
+
+use crate::proto::{DnsAddr, Tcp};
+use crate::{Code, Error, MultiAddr, Protocol};
+use alloc::string::String;
+
+#[cfg(feature = "std")]
+use crate::proto::{Ip4, Ip6};
+
+impl MultiAddr {
+    /// Return the canonical form of this address.
+    ///
+    /// - DNS names are lowercased and a trailing dot is removed.
+    /// - DNS names which are IP addresses become `/ip4` or `/ip6` values.
+    /// - IPv4-mapped IPv6 addresses become `/ip4` values.
+    ///
+    /// All other protocol values are kept as they are.
+    pub fn canonical(&self) -> Result<MultiAddr, Error> {
+        self.canonicalize(None)
+    }
+
+    /// Like [`MultiAddr::canonical`] but also removes `/tcp` values equal
+    /// to `port` which directly follow a host.
+    ///
+    /// The result is meant for comparisons, where a host without a port
+    /// stands for the host at its default port.
+    pub fn canonical_with_default_port(&self, port: u16) -> Result<MultiAddr, Error> {
+        self.canonicalize(Some(port))
+    }
+
+    /// Are both addresses equal once in canonical form?
+    ///
+    /// Costs are ignored, as they do not change the destination.
+    pub fn is_equivalent(&self, other: &MultiAddr) -> bool {
+        let canonical = |a: &MultiAddr| a.without_cost()?.canonical();
+        match (canonical(self), canonical(other)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => self == other,
+        }
+    }
+
+    /// Does this address start with the protocol values of `prefix`?
+    pub fn starts_with(&self, prefix: &MultiAddr) -> bool {
+        let mut this = self.iter();
+        for p in prefix.iter() {
+            match this.next() {
+                Some(q) if p.code() == q.code() && *p.data() == *q.data() => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// Does this address start with `prefix` once both are in canonical form?
+    pub fn starts_with_equivalent(&self, prefix: &MultiAddr) -> bool {
+        match (self.canonical(), prefix.canonical()) {
+            (Ok(a), Ok(b)) => a.starts_with(&b),
+            _ => self.starts_with(prefix),
+        }
+    }
+
+    fn canonicalize(&self, default_port: Option<u16>) -> Result<MultiAddr, Error> {
+        let mut ma = MultiAddr::new(self.registry().clone());
+        let mut after_host = false;
+        for p in self.iter() {
+            let is_host = is_host(p.code());
+            match p.code() {
+                DnsAddr::CODE => {
+                    let name = p
+                        .cast::<DnsAddr>()
+                        .ok_or_else(|| Error::message("invalid dnsaddr value"))?;
+                    push_host(&mut ma, &name)?
+                }
+                #[cfg(feature = "std")]
+                Ip6::CODE => {
+                    let ip = p
+                        .cast::<Ip6>()
+                        .ok_or_else(|| Error::message("invalid ip6 value"))?;
+                    push_ip6(&mut ma, *ip)?
+                }
+                Tcp::CODE if after_host => {
+                    let port = p
+                        .cast::<Tcp>()
+                        .ok_or_else(|| Error::message("invalid tcp value"))?;
+                    if Some(*port) != default_port {
+                        ma.push_back(port)?
+                    }
+                }
+                _ => ma.push_back_value(&p)?,
+            }
+            after_host = is_host;
+        }
+        Ok(ma)
+    }
+}
+
+fn is_host(c: Code) -> bool {
+    #[cfg(feature = "std")]
+    if c == Ip4::CODE || c == Ip6::CODE {
+        return true;
+    }
+    c == DnsAddr::CODE
+}
+
+fn push_host(ma: &mut MultiAddr, name: &str) -> Result<(), Error> {
+    let name = name.strip_suffix('.').unwrap_or(name);
+    #[cfg(feature = "std")]
+    {
+        let ip = name
+            .strip_prefix('[')
+            .and_then(|n| n.strip_suffix(']'))
+            .unwrap_or(name);
+        if let Ok(ip) = ip.parse::<std::net::Ipv4Addr>() {
+            return ma.push_back(Ip4(ip));
+        }
+        if let Ok(ip) = ip.parse::<std::net::Ipv6Addr>() {
+            return push_ip6(ma, ip);
+        }
+    }
+    let name: String = name.to_ascii_lowercase();
+    ma.push_back(DnsAddr::new(name))
+}
+
+#[cfg(feature = "std")]
+fn push_ip6(ma: &mut MultiAddr, ip: std::net::Ipv6Addr) -> Result<(), Error> {
+    match ip.segments() {
+        [0, 0, 0, 0, 0, 0xffff, hi, lo] => {
+            let [a, b] = hi.to_be_bytes();
+            let [c, d] = lo.to_be_bytes();
+            ma.push_back(Ip4(std::net::Ipv4Addr::new(a, b, c, d)))
+        }
+        _ => ma.push_back(Ip6(ip)),
+    }
+}
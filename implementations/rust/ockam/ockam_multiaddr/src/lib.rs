@@ -11,6 +11,7 @@
 
 extern crate alloc;
 
+mod canonical;
 mod error;
 mod registry;
 
@@ -523,6 +524,23 @@ impl MultiAddr {
         Ok(self)
     }
 
+    /// The total cost of this address, i.e. the sum of its `/cost` values.
+    ///
+    /// An address without cost values has a cost of 0.
+    pub fn cost(&self) -> u32 {
+        self.iter()
+            .filter(|p| p.code() == proto::Cost::CODE)
+            .filter_map(|p| p.cast::<proto::Cost>())
+            .fold(0, |sum, c| sum.saturating_add(*c))
+    }
+
+    /// This address without its `/cost` values.
+    pub fn without_cost(&self) -> Result<MultiAddr, Error> {
+        let mut ma = MultiAddr::new(self.reg.clone());
+        ma.try_extend(self.iter().filter(|p| p.code() != proto::Cost::CODE))?;
+        Ok(ma)
+    }
+
     /// Check if the protocol codes match the given sequence.
     pub fn matches<'a, I>(&self, start: usize, codes: I) -> bool
     where
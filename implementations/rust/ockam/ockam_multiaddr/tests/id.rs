@@ -1,5 +1,7 @@
 use core::fmt;
-use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp};
+use ockam_multiaddr::proto::{
+    Contact, Cost, DnsAddr, Ip4, Ip6, Node, Project, Quic, Secure, Service, Space, Tcp, Udp,
+};
 use ockam_multiaddr::{Code, Match, MultiAddr, Protocol};
 use quickcheck::{quickcheck, Arbitrary, Gen};
 use rand::distributions::{Alphanumeric, DistString};
@@ -70,6 +72,17 @@ quickcheck! {
         a.0 == ma
     }
 
+    fn canonical_is_idempotent(a: Addr) -> bool {
+        let c = a.0.canonical().unwrap();
+        c == c.canonical().unwrap() && a.0.is_equivalent(&c)
+    }
+
+    fn prefixes(a: Addr, n: usize) -> bool {
+        let mut prefix = MultiAddr::default();
+        prefix.try_extend(a.0.iter().take(n)).unwrap();
+        a.0.starts_with(&prefix) && a.0.starts_with_equivalent(&prefix)
+    }
+
     fn operations(ops: Vec<Op>) -> bool {
         let mut gen = rand::thread_rng();
         let mut addr = MultiAddr::default();
@@ -100,6 +113,18 @@ quickcheck! {
                         addr.push_back(Tcp::new(0)).unwrap();
                         prot.push_back(Tcp::CODE);
                     }
+                    Udp::CODE => {
+                        addr.push_back(Udp::new(0)).unwrap();
+                        prot.push_back(Udp::CODE);
+                    }
+                    Quic::CODE => {
+                        addr.push_back(Quic::new(0)).unwrap();
+                        prot.push_back(Quic::CODE);
+                    }
+                    Cost::CODE => {
+                        addr.push_back(Cost::new(10)).unwrap();
+                        prot.push_back(Cost::CODE);
+                    }
                     DnsAddr::CODE => {
                         addr.push_back(DnsAddr::new("localhost")).unwrap();
                         prot.push_back(DnsAddr::CODE);
@@ -132,6 +157,10 @@ quickcheck! {
                         addr.push_back(Space::new("space")).unwrap();
                         prot.push_back(Space::CODE);
                     }
+                    Contact::CODE => {
+                        addr.push_back(Contact::new("contact")).unwrap();
+                        prot.push_back(Contact::CODE);
+                    }
                     _ => unreachable!()
                 }
             }
@@ -143,8 +172,75 @@ quickcheck! {
     }
 }
 
+#[test]
+fn canonical_forms() {
+    let canonical = |s: &str| {
+        MultiAddr::from_str(s)
+            .unwrap()
+            .canonical()
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!(
+        canonical("/dnsaddr/Node.Example.COM./tcp/4000/service/api"),
+        "/dnsaddr/node.example.com/tcp/4000/service/api"
+    );
+    assert_eq!(
+        canonical("/dnsaddr/127.0.0.1/tcp/4000"),
+        "/ip4/127.0.0.1/tcp/4000"
+    );
+    assert_eq!(canonical("/dnsaddr/[::1]/tcp/4000"), "/ip6/::1/tcp/4000");
+    assert_eq!(
+        canonical("/ip6/::ffff:10.0.0.1/tcp/4000"),
+        "/ip4/10.0.0.1/tcp/4000"
+    );
+    assert_eq!(canonical("/service/Api"), "/service/Api");
+
+    let a = MultiAddr::from_str("/dnsaddr/EXAMPLE.com/tcp/4000/service/api").unwrap();
+    assert_eq!(
+        a.canonical_with_default_port(4000).unwrap().to_string(),
+        "/dnsaddr/example.com/service/api"
+    );
+    assert_eq!(
+        a.canonical_with_default_port(443).unwrap().to_string(),
+        "/dnsaddr/example.com/tcp/4000/service/api"
+    );
+}
+
+#[test]
+fn costs() {
+    let a = MultiAddr::from_str("/dnsaddr/relay/tcp/4000/cost/20/service/fwd/cost/5").unwrap();
+    assert_eq!(a.cost(), 25);
+    assert_eq!(
+        a.without_cost().unwrap().to_string(),
+        "/dnsaddr/relay/tcp/4000/service/fwd"
+    );
+    assert!(a.is_equivalent(&a.without_cost().unwrap()));
+    assert_eq!(MultiAddr::from_str("/service/api").unwrap().cost(), 0);
+    let max = MultiAddr::from_str("/cost/4294967295/cost/1").unwrap();
+    assert_eq!(max.cost(), u32::MAX)
+}
+
+#[test]
+fn equivalence_and_prefixes() {
+    let a = MultiAddr::from_str("/dnsaddr/Example.com./tcp/4000/service/api").unwrap();
+    let b = MultiAddr::from_str("/dnsaddr/example.com/tcp/4000/service/api").unwrap();
+    let p = MultiAddr::from_str("/dnsaddr/EXAMPLE.COM/tcp/4000").unwrap();
+    assert_ne!(a, b);
+    assert!(a.is_equivalent(&b));
+    assert!(!a.starts_with(&p));
+    assert!(a.starts_with_equivalent(&p));
+    assert!(b.starts_with(&MultiAddr::default()));
+    assert!(!p.starts_with(&b));
+    let c = MultiAddr::from_str("/dnsaddr/example.com/tcp/4001/service/api").unwrap();
+    assert!(!a.is_equivalent(&c))
+}
+
 const PROTOS: &[Code] = &[
     Tcp::CODE,
+    Udp::CODE,
+    Quic::CODE,
+    Cost::CODE,
     DnsAddr::CODE,
     Ip4::CODE,
     Ip6::CODE,
@@ -153,6 +249,7 @@ const PROTOS: &[Code] = &[
     Node::CODE,
     Project::CODE,
     Space::CODE,
+    Contact::CODE,
 ];
 
 impl Arbitrary for Addr {
@@ -161,6 +258,9 @@ impl Arbitrary for Addr {
         for _ in 0..g.size() {
             match *g.choose(PROTOS).unwrap() {
                 Tcp::CODE => a.push_back(Tcp::new(u16::arbitrary(g))).unwrap(),
+                Udp::CODE => a.push_back(Udp::new(u16::arbitrary(g))).unwrap(),
+                Quic::CODE => a.push_back(Quic::new(u16::arbitrary(g))).unwrap(),
+                Cost::CODE => a.push_back(Cost::new(u32::arbitrary(g))).unwrap(),
                 DnsAddr::CODE => a.push_back(DnsAddr::new(gen_hostname())).unwrap(),
                 Ip4::CODE => a.push_back(Ip4::new(Ipv4Addr::arbitrary(g))).unwrap(),
                 Ip6::CODE => a.push_back(Ip6::new(Ipv6Addr::arbitrary(g))).unwrap(),
@@ -169,6 +269,7 @@ impl Arbitrary for Addr {
                 Project::CODE => a.push_back(Project::new(gen_string())).unwrap(),
                 Space::CODE => a.push_back(Space::new(gen_string())).unwrap(),
                 Node::CODE => a.push_back(Node::new(gen_string())).unwrap(),
+                Contact::CODE => a.push_back(Contact::new(gen_string())).unwrap(),
                 _ => unreachable!(),
             }
         }
@@ -0,0 +1,98 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+use ockam_core::compat::{collections::BTreeMap, sync::Mutex};
+use ockam_core::Address;
+
+/// What a worker consumed handling its messages, see
+/// [`Context::worker_stats`](crate::Context::worker_stats)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WorkerStats {
+    /// Messages handled
+    pub messages: u64,
+    /// Cumulative time spent handling them, zero without `std`
+    pub handler_time: Duration,
+    /// Bytes of the messages decoded for the worker, an estimate of the
+    /// memory it allocated to handle them
+    pub allocated_bytes: u64,
+}
+
+/// When a worker started handling a message
+pub(crate) struct Started {
+    #[cfg(feature = "std")]
+    at: std::time::Instant,
+}
+
+impl Started {
+    fn elapsed(&self) -> Duration {
+        #[cfg(feature = "std")]
+        {
+            self.at.elapsed()
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            Duration::ZERO
+        }
+    }
+}
+
+/// The [`WorkerStats`] of the workers of a node, shared by all its
+/// contexts
+///
+/// Accounting is disabled by default, as it costs a lock per message.
+pub(crate) struct WorkerAccounting {
+    enabled: AtomicBool,
+    stats: Mutex<BTreeMap<Address, WorkerStats>>,
+}
+
+impl WorkerAccounting {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled: AtomicBool::new(enabled),
+            stats: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Enable or disable accounting, disabling it forgets the stats
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            if let Ok(mut stats) = self.stats.lock() {
+                stats.clear()
+            }
+        }
+    }
+
+    /// Start accounting for the handling of a message, if enabled
+    pub(crate) fn start(&self) -> Option<Started> {
+        self.is_enabled().then(|| Started {
+            #[cfg(feature = "std")]
+            at: std::time::Instant::now(),
+        })
+    }
+
+    /// Account for a message of `len` bytes handled by `worker`
+    pub(crate) fn record(&self, worker: &Address, started: Started, len: usize) {
+        let elapsed = started.elapsed();
+        if let Ok(mut stats) = self.stats.lock() {
+            let s = stats.entry(worker.clone()).or_default();
+            s.messages += 1;
+            s.handler_time += elapsed;
+            s.allocated_bytes += len as u64;
+        }
+    }
+
+    /// Forget a stopped worker
+    pub(crate) fn remove(&self, worker: &Address) {
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.remove(worker);
+        }
+    }
+
+    pub(crate) fn stats(&self) -> BTreeMap<Address, WorkerStats> {
+        self.stats.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
@@ -2,6 +2,7 @@ use crate::async_drop::AsyncDrop;
 use crate::channel_types::{message_channel, small_channel, SmallReceiver, SmallSender};
 use crate::tokio::{self, runtime::Handle, time::timeout};
 use crate::{
+    accounting::{WorkerAccounting, WorkerStats},
     error::*,
     parser,
     relay::{CtrlSignal, ProcessorRelay, RelayMessage},
@@ -12,11 +13,11 @@ use core::{
     sync::atomic::{AtomicUsize, Ordering},
     time::Duration,
 };
-use ockam_core::compat::{boxed::Box, string::String, sync::Arc, vec::Vec};
+use ockam_core::compat::{boxed::Box, collections::BTreeMap, string::String, sync::Arc, vec::Vec};
 use ockam_core::{
     errcode::{Kind, Origin},
     Address, AddressSet, AllowAll, AsyncTryClone, Error, LocalMessage, Mailbox, Mailboxes, Message,
-    Processor, Result, Route, TransportMessage, TransportType, Worker,
+    Processor, Result, Route, RouteLimits, TransportMessage, TransportType, Worker,
 };
 use ockam_core::{AccessControl, LocalInfo};
 
@@ -37,6 +38,34 @@ impl AddressType {
     }
 }
 
+/// The [`RouteLimits`] of a node, shared by all its contexts
+pub(crate) struct SharedRouteLimits {
+    max_hops: AtomicUsize,
+    max_repeats: AtomicUsize,
+}
+
+impl SharedRouteLimits {
+    pub(crate) fn new(limits: RouteLimits) -> Self {
+        Self {
+            max_hops: AtomicUsize::new(limits.max_hops),
+            max_repeats: AtomicUsize::new(limits.max_repeats),
+        }
+    }
+
+    fn get(&self) -> RouteLimits {
+        RouteLimits::new(
+            self.max_hops.load(Ordering::Relaxed),
+            self.max_repeats.load(Ordering::Relaxed),
+        )
+    }
+
+    fn set(&self, limits: RouteLimits) {
+        self.max_hops.store(limits.max_hops, Ordering::Relaxed);
+        self.max_repeats
+            .store(limits.max_repeats, Ordering::Relaxed);
+    }
+}
+
 /// A special sender type that connects a type to an AsyncDrop handler
 pub type AsyncDropSender = crate::tokio::sync::oneshot::Sender<Address>;
 
@@ -56,6 +85,8 @@ pub struct Context {
     receiver: SmallReceiver<RelayMessage>,
     async_drop_sender: Option<AsyncDropSender>,
     mailbox_count: Arc<AtomicUsize>,
+    route_limits: Arc<SharedRouteLimits>,
+    accounting: Arc<WorkerAccounting>,
 }
 
 impl Drop for Context {
@@ -92,6 +123,45 @@ impl Context {
         &self.sender
     }
 
+    pub(crate) fn shared_route_limits(&self) -> Arc<SharedRouteLimits> {
+        self.route_limits.clone()
+    }
+
+    /// Limit the onward routes along which the workers of this node send
+    /// and forward messages
+    pub fn set_route_limits(&self, limits: RouteLimits) {
+        self.route_limits.set(limits)
+    }
+
+    /// The limits of the onward routes of the messages of this node
+    pub fn route_limits(&self) -> RouteLimits {
+        self.route_limits.get()
+    }
+
+    pub(crate) fn shared_accounting(&self) -> Arc<WorkerAccounting> {
+        self.accounting.clone()
+    }
+
+    /// Enable or disable the accounting of the messages handled by the
+    /// workers of this node, see [`Context::worker_stats`]
+    ///
+    /// Disabling it forgets the stats accounted so far.
+    pub fn set_worker_accounting(&self, enabled: bool) {
+        self.accounting.set_enabled(enabled)
+    }
+
+    /// Whether the workers of this node are accounted for
+    pub fn worker_accounting(&self) -> bool {
+        self.accounting.is_enabled()
+    }
+
+    /// The stats of the running workers of this node, by address, empty
+    /// unless [worker accounting](Context::set_worker_accounting) is
+    /// enabled
+    pub fn worker_stats(&self) -> BTreeMap<Address, WorkerStats> {
+        self.accounting.stats()
+    }
+
     /// Wait for the next message from the mailbox
     pub(crate) async fn receiver_next(&mut self) -> Result<Option<RelayMessage>> {
         loop {
@@ -135,6 +205,8 @@ impl Context {
         sender: SmallSender<NodeMessage>,
         mailboxes: Mailboxes,
         async_drop_sender: Option<AsyncDropSender>,
+        route_limits: Arc<SharedRouteLimits>,
+        accounting: Arc<WorkerAccounting>,
     ) -> (Self, SenderPair, SmallReceiver<CtrlSignal>) {
         let (mailbox_tx, receiver) = message_channel();
         let (ctrl_tx, ctrl_rx) = small_channel();
@@ -146,6 +218,8 @@ impl Context {
                 receiver,
                 async_drop_sender,
                 mailbox_count: Arc::new(0.into()),
+                route_limits,
+                accounting,
             },
             SenderPair {
                 msgs: mailbox_tx,
@@ -227,6 +301,8 @@ impl Context {
             self.sender.clone(),
             mailboxes,
             Some(drop_sender),
+            self.route_limits.clone(),
+            self.accounting.clone(),
         );
 
         // Create a "detached relay" and register it with the router
@@ -313,8 +389,14 @@ impl Context {
         let main_mailbox = Mailbox::new(addr, Arc::new(AllowAll)); // TODO FIXME
         let mailboxes = Mailboxes::new(main_mailbox, vec![]);
 
-        let (ctx, senders, ctrl_rx) =
-            Context::new(self.rt.clone(), self.sender.clone(), mailboxes, None);
+        let (ctx, senders, ctrl_rx) = Context::new(
+            self.rt.clone(),
+            self.sender.clone(),
+            mailboxes,
+            None,
+            self.route_limits.clone(),
+            self.accounting.clone(),
+        );
 
         // Initialise the processor relay with the ctrl receiver
         ProcessorRelay::<P>::init(&self.rt, processor, ctx, ctrl_rx);
@@ -571,6 +653,8 @@ impl Context {
             return Err(Error::new_without_cause(Origin::Node, Kind::Invalid));
         }
 
+        self.route_limits.get().check(&route)?;
+
         // First resolve the next hop in the route
         let (reply_tx, mut reply_rx) = small_channel();
         let next = route.next().unwrap(); // TODO: communicate bad routes
@@ -614,7 +698,19 @@ impl Context {
     ///
     /// [`Context::send`]: crate::Context::send
     /// [`TransportMessage`]: ockam_core::TransportMessage
-    pub async fn forward(&self, local_msg: LocalMessage) -> Result<()> {
+    pub async fn forward(&self, mut local_msg: LocalMessage) -> Result<()> {
+        // Messages looping between nodes are dropped rather than relayed
+        let onward_route = &local_msg.transport().onward_route;
+        if let Err(e) = self.route_limits.get().check(onward_route) {
+            debug!("Not forwarding message along {}: {}", onward_route, e);
+            return Err(e.into());
+        }
+        if let Err(e) = local_msg.transport_mut().decrement_ttl() {
+            let onward_route = &local_msg.transport().onward_route;
+            debug!("Not forwarding message along {}: {}", onward_route, e);
+            return Err(e.into());
+        }
+
         // First resolve the next hop in the route
         let (reply_tx, mut reply_rx) = small_channel();
         let next = local_msg.transport().onward_route.next().unwrap(); // TODO: communicate bad routes
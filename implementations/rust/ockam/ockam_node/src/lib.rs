@@ -44,6 +44,7 @@ pub mod access_control;
 /// Api helpers
 pub mod api;
 
+mod accounting;
 mod async_drop;
 mod cancel;
 mod context;
@@ -58,6 +59,7 @@ mod relay;
 mod router;
 mod worker_builder;
 
+pub use accounting::WorkerStats;
 pub use cancel::*;
 pub use context::*;
 pub use delayed::*;
@@ -1,6 +1,7 @@
-use crate::{Context, Executor};
+use crate::accounting::WorkerAccounting;
+use crate::{Context, Executor, SharedRouteLimits};
 use ockam_core::compat::sync::Arc;
-use ockam_core::{AccessControl, Address, AllowAll, Mailbox, Mailboxes};
+use ockam_core::{AccessControl, Address, AllowAll, Mailbox, Mailboxes, RouteLimits};
 
 /// A minimal worker implementation that does nothing
 pub struct NullWorker;
@@ -21,6 +22,8 @@ where
 {
     access_control: AC,
     logging: bool,
+    route_limits: RouteLimits,
+    worker_accounting: bool,
 }
 
 impl NodeBuilder<AllowAll> {
@@ -29,6 +32,8 @@ impl NodeBuilder<AllowAll> {
         Self {
             access_control: AllowAll,
             logging: true,
+            route_limits: RouteLimits::default(),
+            worker_accounting: false,
         }
     }
 }
@@ -42,6 +47,8 @@ where
         Self {
             access_control,
             logging: true,
+            route_limits: RouteLimits::default(),
+            worker_accounting: false,
         }
     }
 
@@ -53,6 +60,24 @@ where
         }
     }
 
+    /// Limit the onward routes along which the workers of the node send
+    /// and forward messages, see [`Context::set_route_limits`]
+    pub fn with_route_limits(self, route_limits: RouteLimits) -> Self {
+        Self {
+            route_limits,
+            ..self
+        }
+    }
+
+    /// Account for the messages handled by the workers of the node from
+    /// the start, see [`Context::set_worker_accounting`]
+    pub fn with_worker_accounting(self) -> Self {
+        Self {
+            worker_accounting: true,
+            ..self
+        }
+    }
+
     /// Consume this builder and yield a new Ockam Node
     #[inline]
     pub fn build(self) -> (Context, Executor) {
@@ -75,6 +100,8 @@ where
             exe.sender(),
             Mailboxes::new(Mailbox::new(addr, Arc::new(self.access_control)), vec![]),
             None,
+            Arc::new(SharedRouteLimits::new(self.route_limits)),
+            Arc::new(WorkerAccounting::new(self.worker_accounting)),
         );
 
         // Register this mailbox handle with the executor
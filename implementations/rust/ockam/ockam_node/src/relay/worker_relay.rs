@@ -84,8 +84,15 @@ where
         }
 
         // Call the worker handle function - pass errors up
+        let accounting = self.ctx.shared_accounting();
+        let started = accounting.start();
         let routed = Self::wrap_direct_message(&relay_msg)?;
-        self.worker.handle_message(&mut self.ctx, routed).await?;
+        let handled = self.worker.handle_message(&mut self.ctx, routed).await;
+        if let Some(started) = started {
+            let len = relay_msg.local_msg.transport().payload.len();
+            accounting.record(&self.ctx.address(), started, len);
+        }
+        handled?;
 
         // Signal to the outer loop that we would like to run again
         Ok(true)
@@ -165,6 +172,8 @@ where
             }
         }
 
+        self.ctx.shared_accounting().remove(&address);
+
         // Finally send the router a stop ACK -- log errors
         trace!("Sending shutdown ACK");
         if let Err(e) = self.ctx.send_stop_ack().await {
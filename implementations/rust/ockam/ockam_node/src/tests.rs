@@ -7,7 +7,10 @@ use ockam_core::compat::{
     string::{String, ToString},
     sync::Arc,
 };
-use ockam_core::{async_trait, Address, Any, Decodable, Message, LOCAL};
+use ockam_core::{
+    async_trait, Address, Any, Decodable, LocalMessage, Message, RouteLimits, TransportMessage,
+    LOCAL,
+};
 use ockam_core::{route, Processor, Result, Routed, Worker};
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicI8, AtomicU32};
@@ -560,3 +563,95 @@ async fn starting_worker_with_dup_address_should_fail(ctx: &mut Context) -> Resu
     assert!(ctx.start_worker("dummy_worker", DummyWorker).await.is_err());
     ctx.stop().await
 }
+
+#[ockam_macros::test(crate = "crate")]
+async fn routes_over_the_limits_are_refused(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echo", DummyWorker).await?;
+    assert_eq!(ctx.route_limits(), RouteLimits::default());
+
+    // The limits are shared by all the contexts of the node
+    let child = ctx.new_detached(Address::random_local()).await?;
+    child.set_route_limits(RouteLimits::new(3, 1));
+    assert_eq!(ctx.route_limits(), RouteLimits::new(3, 1));
+
+    assert!(ctx.send(route!["a", "b", "c", "echo"], ()).await.is_err());
+    assert!(ctx.send(route!["echo", "a", "echo"], ()).await.is_err());
+    let msg = TransportMessage::v1(route!["echo", "echo"], route![], vec![]);
+    assert!(ctx.forward(LocalMessage::new(msg, vec![])).await.is_err());
+
+    ctx.send(route!["echo"], "Hello".to_string()).await?;
+    assert_eq!(ctx.receive::<String>().await?.take().body(), "Hello");
+
+    ctx.stop().await
+}
+
+/// Forwards every message to another worker
+struct Bouncer {
+    to: &'static str,
+    hops: Arc<AtomicU32>,
+}
+
+#[async_trait]
+impl Worker for Bouncer {
+    type Message = Any;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        self.hops.fetch_add(1, Ordering::Relaxed);
+        let mut local_msg = msg.into_local_message();
+        local_msg.transport_mut().onward_route = route![self.to];
+        ctx.forward(local_msg).await
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn looping_messages_expire(ctx: &mut Context) -> Result<()> {
+    let hops = Arc::new(AtomicU32::new(0));
+    let ping = Bouncer {
+        to: "pong",
+        hops: hops.clone(),
+    };
+    let pong = Bouncer {
+        to: "ping",
+        hops: hops.clone(),
+    };
+    ctx.start_worker("ping", ping).await?;
+    ctx.start_worker("pong", pong).await?;
+
+    ctx.send(route!["ping"], ()).await?;
+    sleep(Duration::from_millis(500)).await;
+    assert_eq!(
+        hops.load(Ordering::Relaxed),
+        ockam_core::DEFAULT_TTL as u32 + 1
+    );
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn workers_are_accounted_for(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echo", DummyWorker).await?;
+    ctx.send(route!["echo"], "Hello".to_string()).await?;
+    ctx.receive::<String>().await?;
+    assert!(!ctx.worker_accounting());
+    assert!(ctx.worker_stats().is_empty());
+
+    ctx.set_worker_accounting(true);
+    for _ in 0..3 {
+        ctx.send(route!["echo"], "Hello".to_string()).await?;
+        ctx.receive::<String>().await?;
+    }
+    let stats = ctx.worker_stats();
+    let echo = stats.get(&"echo".into()).unwrap();
+    assert_eq!(echo.messages, 3);
+    assert!(echo.allocated_bytes >= 3 * "Hello".len() as u64);
+
+    // Stopped workers are forgotten
+    ctx.stop_worker("echo").await?;
+    sleep(Duration::from_millis(100)).await;
+    assert!(!ctx.worker_stats().contains_key(&"echo".into()));
+
+    ctx.set_worker_accounting(false);
+    assert!(ctx.worker_stats().is_empty());
+    ctx.stop().await
+}
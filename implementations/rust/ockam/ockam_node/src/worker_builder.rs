@@ -94,6 +94,8 @@ where
             context.sender().clone(),
             mailboxes,
             None,
+            context.shared_route_limits(),
+            context.shared_accounting(),
         );
 
         // Then initialise the worker message relay
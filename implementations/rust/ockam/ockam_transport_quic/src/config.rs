@@ -0,0 +1,104 @@
+//! TLS and transport parameters of QUIC endpoints
+
+use core::time::Duration;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use ockam_transport_tcp::tls::SelfSignedCert;
+use quinn::{ClientConfig, IdleTimeout, ServerConfig, TransportConfig};
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, PrivateKey, ServerName};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Protocol negotiated by QUIC endpoints, so that they only talk to
+/// other Ockam nodes
+const ALPN: &[u8] = b"ockam";
+
+/// Name of the certificates of listeners, unverified
+pub(crate) const SERVER_NAME: &str = "localhost";
+
+/// Connections without any packet for this long are closed
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Time between two keep-alive packets, sent while a connection is idle so
+/// that neither its idle timeout nor NAT bindings expire
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+pub(crate) fn quic_error(e: impl core::fmt::Display) -> Error {
+    Error::new(Origin::Transport, Kind::Io, format!("QUIC: {}", e))
+}
+
+fn transport_config() -> Result<Arc<TransportConfig>> {
+    let idle_timeout = IdleTimeout::try_from(IDLE_TIMEOUT).map_err(quic_error)?;
+    let mut config = TransportConfig::default();
+    config
+        .max_idle_timeout(Some(idle_timeout))
+        .keep_alive_interval(Some(KEEP_ALIVE_INTERVAL));
+    Ok(Arc::new(config))
+}
+
+/// Configuration of listeners, with a new self-signed certificate
+///
+/// Listeners accept the 0-RTT data of the clients which connected to them
+/// before, and follow the clients whose address changes.
+pub(crate) fn server_config() -> Result<ServerConfig> {
+    let cert = SelfSignedCert::generate(&[SERVER_NAME])?;
+    let certs = rustls_pemfile::certs(&mut cert.cert_pem.as_bytes())
+        .map_err(quic_error)?
+        .into_iter()
+        .map(Certificate)
+        .collect();
+    let key = rustls_pemfile::pkcs8_private_keys(&mut cert.key_pem.as_bytes())
+        .map_err(quic_error)?
+        .pop()
+        .map(PrivateKey)
+        .ok_or_else(|| quic_error("no private key generated"))?;
+
+    let mut crypto = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(quic_error)?;
+    crypto.alpn_protocols = vec![ALPN.to_vec()];
+    crypto.max_early_data_size = u32::MAX;
+
+    let mut config = ServerConfig::with_crypto(Arc::new(crypto));
+    config.transport_config(transport_config()?).migration(true);
+    Ok(config)
+}
+
+/// Configuration of the connections to listeners
+///
+/// The sessions of the connections are kept to reconnect with 0-RTT.
+pub(crate) fn client_config() -> Result<ClientConfig> {
+    let mut crypto = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(SkipServerVerification))
+        .with_no_client_auth();
+    crypto.alpn_protocols = vec![ALPN.to_vec()];
+    crypto.enable_early_data = true;
+
+    let mut config = ClientConfig::new(Arc::new(crypto));
+    config.transport_config(transport_config()?);
+    Ok(config)
+}
+
+/// Accepts the certificate of any listener
+///
+/// The certificates of listeners are self-signed, the peers are
+/// authenticated by the secure channels created over the transport.
+struct SkipServerVerification;
+
+impl ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> core::result::Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
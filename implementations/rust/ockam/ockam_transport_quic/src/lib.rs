@@ -0,0 +1,52 @@
+//! QUIC Transport utilities for Ockam's routing framework
+//!
+//! The `ockam_node` crate sits at the core
+//! of the Ockam routing framework, with transport specific
+//! abstraction plugins.  This crate implements a QUIC connection
+//! plugin for this architecture.
+//!
+//! You can use Ockam's routing mechanism for cryptographic protocols,
+//! key lifecycle, credential exchange, enrollment, etc, without having
+//! to worry about the transport specifics.
+#![deny(unsafe_code)]
+#![warn(
+    missing_docs,
+    dead_code,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications
+)]
+
+mod config;
+mod router;
+mod workers;
+
+pub(crate) use config::*;
+pub(crate) use router::*;
+pub(crate) use workers::*;
+
+mod transport;
+
+pub use transport::*;
+
+use ockam_core::compat::net::SocketAddr;
+use ockam_core::{Result, TransportType};
+use ockam_transport_core::TransportError;
+
+/// QUIC address type constant
+pub const QUIC: TransportType = TransportType::new(5);
+
+pub(crate) const CLUSTER_NAME: &str = "_internals.transport.quic";
+
+/// Largest message sent or received
+///
+/// Every message is sent on a stream of its own, which is read to its end
+/// before the message is decoded.
+pub const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+fn parse_socket_addr<S: AsRef<str>>(s: S) -> Result<SocketAddr> {
+    Ok(s.as_ref()
+        .parse()
+        .map_err(|_| TransportError::InvalidAddress)?)
+}
@@ -0,0 +1,122 @@
+use crate::{
+    parse_socket_addr, QuicListenProcessor, QuicRouterRequest, QuicRouterResponse, WorkerPair, QUIC,
+};
+use ockam_core::compat::net::{SocketAddr, ToSocketAddrs};
+use ockam_core::{async_trait, compat::boxed::Box};
+use ockam_core::{Address, AsyncTryClone, Result};
+use ockam_node::Context;
+use ockam_transport_core::TransportError;
+
+/// A handle to connect to a QuicRouter
+///
+/// Dropping this handle is harmless.
+pub(crate) struct QuicRouterHandle {
+    ctx: Context,
+    api_addr: Address,
+}
+
+#[async_trait]
+impl AsyncTryClone for QuicRouterHandle {
+    async fn async_try_clone(&self) -> Result<Self> {
+        let child_ctx = self.ctx.new_detached(Address::random_local()).await?;
+        Ok(Self::new(child_ctx, self.api_addr.clone()))
+    }
+}
+
+impl QuicRouterHandle {
+    /// Create a new `QuicRouterHandle` with the given address
+    pub(crate) fn new(ctx: Context, api_addr: Address) -> Self {
+        QuicRouterHandle { ctx, api_addr }
+    }
+
+    async fn request(&self, req: QuicRouterRequest) -> Result<QuicRouterResponse> {
+        let mut child_ctx = self.ctx.new_detached(Address::random_local()).await?;
+        child_ctx.send(self.api_addr.clone(), req).await?;
+        Ok(child_ctx
+            .receive::<QuicRouterResponse>()
+            .await?
+            .take()
+            .body())
+    }
+}
+
+impl QuicRouterHandle {
+    /// Bind an incoming connection listener for this router
+    pub async fn bind(&self, addr: impl Into<SocketAddr>) -> Result<SocketAddr> {
+        let socket_addr = addr.into();
+        QuicListenProcessor::start(&self.ctx, self.async_try_clone().await?, socket_addr).await
+    }
+
+    /// Establish an outgoing QUIC connection on an existing transport
+    pub async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<Address> {
+        let peer = peer.as_ref().to_string();
+        match self.request(QuicRouterRequest::Connect { peer }).await? {
+            QuicRouterResponse::Connect(res) => res,
+            _ => Err(TransportError::InvalidRouterResponseType.into()),
+        }
+    }
+
+    /// Close an outgoing QUIC connection on an existing transport
+    pub async fn disconnect<S: AsRef<str>>(&self, peer: S) -> Result<()> {
+        let peer = peer.as_ref().to_string();
+        match self.request(QuicRouterRequest::Disconnect { peer }).await? {
+            QuicRouterResponse::Disconnect(res) => res,
+            _ => Err(TransportError::InvalidRouterResponseType.into()),
+        }
+    }
+
+    /// Move the outgoing connections to a new local address
+    pub async fn rebind<S: AsRef<str>>(&self, bind_addr: S) -> Result<()> {
+        let bind_addr = bind_addr.as_ref().to_string();
+        match self
+            .request(QuicRouterRequest::Rebind { bind_addr })
+            .await?
+        {
+            QuicRouterResponse::Rebind(res) => res,
+            _ => Err(TransportError::InvalidRouterResponseType.into()),
+        }
+    }
+
+    /// Register a new connection worker with this router
+    pub async fn register(&self, pair: &WorkerPair) -> Result<()> {
+        let mut accepts = vec![Address::new(QUIC, pair.peer().to_string())];
+        accepts.extend(pair.hostnames().iter().map(|x| Address::new(QUIC, x)));
+        let self_addr = pair.tx_addr();
+        match self
+            .request(QuicRouterRequest::Register { accepts, self_addr })
+            .await?
+        {
+            QuicRouterResponse::Register(res) => res,
+            _ => Err(TransportError::InvalidRouterResponseType.into()),
+        }
+    }
+
+    /// Unregister the connection worker for the given `Address`
+    pub async fn unregister(&self, self_addr: Address) -> Result<()> {
+        match self
+            .request(QuicRouterRequest::Unregister { self_addr })
+            .await?
+        {
+            QuicRouterResponse::Unregister(res) => res,
+            _ => Err(TransportError::InvalidRouterResponseType.into()),
+        }
+    }
+
+    /// Resolve the given peer to a [`SocketAddr`](std::net::SocketAddr)
+    pub(crate) fn resolve_peer(peer: impl Into<String>) -> Result<(SocketAddr, Vec<String>)> {
+        let peer_str = peer.into();
+
+        // Try to parse as SocketAddr
+        if let Ok(peer_addr) = parse_socket_addr(&peer_str) {
+            return Ok((peer_addr, vec![]));
+        }
+
+        // Try to resolve hostname
+        let peer_addr = peer_str
+            .to_socket_addrs()
+            .map_err(|_| TransportError::InvalidAddress)?
+            .next()
+            .ok_or(TransportError::InvalidAddress)?;
+        Ok((peer_addr, vec![peer_str]))
+    }
+}
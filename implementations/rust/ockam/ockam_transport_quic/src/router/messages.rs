@@ -0,0 +1,33 @@
+use ockam_core::{Address, Message, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Message)]
+pub enum QuicRouterRequest {
+    /// Register a new client to this routing scope.
+    Register {
+        /// Specify an accept scope for this client.
+        accepts: Vec<Address>,
+        /// The clients own worker bus address.
+        self_addr: Address,
+    },
+    /// Connect
+    Connect { peer: String },
+    /// Disconnect
+    Disconnect { peer: String },
+    /// Unregister (usually, after disconnection)
+    Unregister {
+        /// The clients own worker bus address.
+        self_addr: Address,
+    },
+    /// Move the outgoing connections to a new local address
+    Rebind { bind_addr: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, Message)]
+pub enum QuicRouterResponse {
+    Register(Result<()>),
+    Connect(Result<Address>),
+    Disconnect(Result<()>),
+    Unregister(Result<()>),
+    Rebind(Result<()>),
+}
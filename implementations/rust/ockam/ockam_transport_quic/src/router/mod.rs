@@ -0,0 +1,7 @@
+mod handle;
+mod messages;
+mod quic_router;
+
+pub(crate) use handle::*;
+pub(crate) use messages::*;
+pub(crate) use quic_router::*;
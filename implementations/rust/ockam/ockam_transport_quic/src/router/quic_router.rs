@@ -0,0 +1,316 @@
+use crate::{
+    client_config, parse_socket_addr, quic_error, QuicRouterHandle, QuicRouterRequest,
+    QuicRouterResponse, QuicSendWorker, QUIC,
+};
+use core::ops::Deref;
+use ockam_core::compat::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use ockam_core::{async_trait, Any};
+use ockam_core::{Address, Decodable, LocalMessage, Result, Routed, Worker};
+use ockam_node::Context;
+use ockam_transport_core::TransportError;
+use quinn::Endpoint;
+use std::collections::BTreeMap;
+use tracing::{debug, error, trace};
+
+/// A QUIC address router and connection listener
+///
+/// In order to create new QUIC connection workers you need a router to
+/// map remote addresses of `type = 5` to worker addresses.  This type
+/// facilitates this.
+///
+/// The outgoing connections of each IP version share an endpoint, i.e. a
+/// UDP socket, which is bound once the first one is opened.
+pub(crate) struct QuicRouter {
+    ctx: Context,
+    main_addr: Address,
+    api_addr: Address,
+    map: BTreeMap<Address, Address>,
+    allow_auto_connection: bool,
+    client_v4: Option<Endpoint>,
+    client_v6: Option<Endpoint>,
+}
+
+impl QuicRouter {
+    /// Create and register a new QUIC router with the node context
+    pub async fn register(ctx: &Context) -> Result<QuicRouterHandle> {
+        let main_addr = Address::random_local();
+        let api_addr = Address::random_local();
+        debug!("Initialising new QuicRouter with address {}", &main_addr);
+
+        let child_ctx = ctx.new_detached(Address::random_local()).await?;
+
+        let router = Self {
+            ctx: child_ctx,
+            main_addr: main_addr.clone(),
+            api_addr: api_addr.clone(),
+            map: BTreeMap::new(),
+            allow_auto_connection: true,
+            client_v4: None,
+            client_v6: None,
+        };
+
+        let handle = router.create_self_handle().await?;
+
+        ctx.start_worker(vec![main_addr.clone(), api_addr], router)
+            .await?;
+        trace!("Registering QUIC router for type = {}", QUIC);
+        ctx.register(QUIC, main_addr).await?;
+
+        Ok(handle)
+    }
+
+    /// Create a new `QuicRouterHandle` representing this router
+    async fn create_self_handle(&self) -> Result<QuicRouterHandle> {
+        let handle_ctx = self.ctx.new_detached(Address::random_local()).await?;
+        let handle = QuicRouterHandle::new(handle_ctx, self.api_addr.clone());
+        Ok(handle)
+    }
+
+    fn client_slot(&mut self, ipv4: bool) -> &mut Option<Endpoint> {
+        if ipv4 {
+            &mut self.client_v4
+        } else {
+            &mut self.client_v6
+        }
+    }
+
+    /// The endpoint of the outgoing connections to `peer`
+    fn client_endpoint(&mut self, peer: &SocketAddr) -> Result<Endpoint> {
+        let slot = self.client_slot(peer.is_ipv4());
+        if let Some(endpoint) = slot {
+            return Ok(endpoint.clone());
+        }
+        let bind_addr = if peer.is_ipv4() {
+            SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))
+        } else {
+            SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0))
+        };
+        let mut endpoint = Endpoint::client(bind_addr).map_err(TransportError::from)?;
+        endpoint.set_default_client_config(client_config()?);
+        *slot = Some(endpoint.clone());
+        Ok(endpoint)
+    }
+}
+
+impl QuicRouter {
+    /// Handle any [`QuicRouterRequest::Register`] messages received by
+    /// this node's worker
+    async fn handle_register(&mut self, accepts: Vec<Address>, self_addr: Address) -> Result<()> {
+        if let Some(f) = accepts.first().cloned() {
+            trace!("QUIC registration request: {} => {}", f, self_addr);
+        } else {
+            error!("QUIC registration request failed due to an invalid address list. Please provide at least one valid Address.");
+            return Err(TransportError::InvalidAddress.into());
+        }
+
+        for accept in &accepts {
+            if self.map.contains_key(accept) {
+                error!(
+                    "QUIC registration request failed, this address is already connected: {}",
+                    accept
+                );
+                return Err(TransportError::AlreadyConnected.into());
+            }
+        }
+
+        for accept in accepts {
+            self.map.insert(accept.clone(), self_addr.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Handle any [`QuicRouterRequest::Unregister`] messages received by
+    /// this node's worker
+    async fn handle_unregister(&mut self, self_addr: Address) -> Result<()> {
+        trace!("QUIC unregistration request: {}", &self_addr);
+
+        self.map.retain(|_, self_addr_i| self_addr_i != &self_addr);
+
+        Ok(())
+    }
+
+    /// Handle any [`QuicRouterRequest::Connect`] messages received by this
+    /// nodes worker
+    ///
+    /// This handler starts a `(QuicSendWorker, QuicRecvProcessor)` pair
+    /// that open and manage a connection to the given peer and
+    /// finally register the given peer with this `QuicRouter`.
+    async fn handle_connect(&mut self, peer: String) -> Result<Address> {
+        // Resolve peer address
+        let (peer_addr, hostnames) = QuicRouterHandle::resolve_peer(peer)?;
+        let endpoint = self.client_endpoint(&peer_addr)?;
+
+        // Start a new `WorkerPair` for the given peer containing a
+        // `QuicSendWorker` and `QuicRecvProcessor`
+        let router_handle = self.create_self_handle().await?;
+        let pair = QuicSendWorker::start_pair(
+            &self.ctx,
+            router_handle,
+            Some(endpoint),
+            None,
+            peer_addr,
+            hostnames.clone(),
+        )
+        .await?;
+
+        let mut accepts = vec![Address::new(QUIC, pair.peer().to_string())];
+        accepts.extend(hostnames.iter().map(|x| Address::new(QUIC, x)));
+        let self_addr = pair.tx_addr();
+
+        self.handle_register(accepts, self_addr.clone()).await?;
+
+        Ok(self_addr)
+    }
+
+    /// Handle any [`QuicRouterRequest::Disconnect`] messages received by this
+    /// nodes worker
+    async fn handle_disconnect(&mut self, peer: String) -> Result<()> {
+        let (peer_addr, _hostnames) = QuicRouterHandle::resolve_peer(peer)?;
+        let quic_address = Address::new(QUIC, peer_addr.to_string());
+
+        let self_address = if let Some(self_address) = self.map.get(&quic_address) {
+            self_address.clone()
+        } else {
+            error!("Failed to disconnect, peer not found: {}", quic_address);
+            return Err(TransportError::PeerNotFound.into());
+        };
+
+        self.handle_unregister(self_address.clone()).await?;
+
+        self.ctx.stop_worker(self_address).await?;
+
+        Ok(())
+    }
+
+    /// Handle any [`QuicRouterRequest::Rebind`] messages received by this
+    /// nodes worker
+    ///
+    /// The outgoing connections migrate to the new socket, their peers
+    /// follow them once they receive packets from its address.
+    async fn handle_rebind(&mut self, bind_addr: String) -> Result<()> {
+        let bind_addr = parse_socket_addr(bind_addr)?;
+        let socket = std::net::UdpSocket::bind(bind_addr).map_err(TransportError::from)?;
+        match self.client_slot(bind_addr.is_ipv4()) {
+            Some(endpoint) => endpoint.rebind(socket).map_err(quic_error)?,
+            slot @ None => {
+                let mut endpoint =
+                    Endpoint::new(Default::default(), None, socket, quinn::TokioRuntime)
+                        .map_err(quic_error)?;
+                endpoint.set_default_client_config(client_config()?);
+                *slot = Some(endpoint)
+            }
+        }
+        debug!("Outgoing QUIC connections rebound to {}", bind_addr);
+        Ok(())
+    }
+
+    /// Handle any [`RouterMessage::Route`] messages received by this
+    /// nodes worker
+    async fn handle_route(&mut self, ctx: &Context, mut msg: LocalMessage) -> Result<()> {
+        trace!(
+            "QUIC route request: {:?}",
+            msg.transport().onward_route.next()
+        );
+
+        // Get the next hop
+        let onward = msg.transport().onward_route.next()?;
+
+        // Resolve route to the connection worker responsible for the next hop
+        let next = self.resolve_route(onward).await?;
+
+        // Modify the transport message route
+        let _ = msg.transport_mut().onward_route.step()?;
+        msg.transport_mut()
+            .onward_route
+            .modify()
+            .prepend(next.clone());
+
+        // Send the transport message to the connection worker
+        ctx.send(next.clone(), msg).await?;
+
+        Ok(())
+    }
+
+    /// Resolve the route to the provided onward address
+    async fn resolve_route(&mut self, onward: &Address) -> Result<Address> {
+        // Check if the connection already exists
+        if let Some(n) = self.map.get(onward) {
+            return Ok(n.clone());
+        }
+
+        // Try resolve a socket address for the onward address
+        let peer =
+            String::from_utf8(onward.deref().clone()).map_err(|_| TransportError::UnknownRoute)?;
+        let (peer_addr, hostnames) = QuicRouterHandle::resolve_peer(peer.clone())?;
+        let quic_address = Address::new(QUIC, peer_addr.to_string());
+
+        // Check for existing connection under different name
+        if let Some(n) = self.map.get(&quic_address).cloned() {
+            // Add new aliases for existing connection
+            for accept in hostnames.iter().map(|x| Address::new(QUIC, x)) {
+                self.map.insert(accept, n.clone());
+            }
+
+            return Ok(n);
+        }
+
+        // No existing connection
+        if self.allow_auto_connection {
+            self.handle_connect(peer).await
+        } else {
+            error!(
+                "Failed to resolve route, no existing connection to peer: {}",
+                peer
+            );
+            Err(TransportError::UnknownRoute.into())
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for QuicRouter {
+    type Context = Context;
+    type Message = Any;
+
+    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        ctx.set_cluster(crate::CLUSTER_NAME).await?;
+        Ok(())
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let return_route = msg.return_route();
+        let msg_addr = msg.msg_addr();
+
+        if msg_addr == self.main_addr {
+            self.handle_route(ctx, msg.into_local_message()).await?;
+        } else if msg_addr == self.api_addr {
+            let res = match QuicRouterRequest::decode(msg.payload())? {
+                QuicRouterRequest::Register { accepts, self_addr } => {
+                    QuicRouterResponse::Register(self.handle_register(accepts, self_addr).await)
+                }
+                QuicRouterRequest::Unregister { self_addr } => {
+                    QuicRouterResponse::Unregister(self.handle_unregister(self_addr).await)
+                }
+                QuicRouterRequest::Connect { peer } => {
+                    QuicRouterResponse::Connect(self.handle_connect(peer).await)
+                }
+                QuicRouterRequest::Disconnect { peer } => {
+                    QuicRouterResponse::Disconnect(self.handle_disconnect(peer).await)
+                }
+                QuicRouterRequest::Rebind { bind_addr } => {
+                    QuicRouterResponse::Rebind(self.handle_rebind(bind_addr).await)
+                }
+            };
+            ctx.send(return_route, res).await?;
+        } else {
+            error!(
+                "QUIC router received a message for an invalid address: {}",
+                msg_addr
+            );
+            return Err(TransportError::InvalidAddress.into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use ockam_core::compat::{boxed::Box, net::SocketAddr};
+use ockam_core::{Address, AsyncTryClone, Result};
+use ockam_node::Context;
+
+use crate::{parse_socket_addr, QuicRouter, QuicRouterHandle};
+
+/// High level management interface for QUIC transports
+///
+/// Be aware that only one `QuicTransport` can exist per node, as it
+/// registers itself as a router for the `QUIC` address type.  Multiple
+/// calls to [`QuicTransport::create`](crate::QuicTransport::create)
+/// will fail.
+///
+/// To listen for incoming connections use
+/// [`quic.listen()`](crate::QuicTransport::listen).
+///
+/// To register additional connections on an already initialised
+/// `QuicTransport`, use [`quic.connect()`](crate::QuicTransport::connect).
+/// This step is optional because the underlying QuicRouter is capable of
+/// lazily establishing a connection upon arrival of an initial message.
+///
+/// ```rust
+/// use ockam_transport_quic::QuicTransport;
+/// # use ockam_node::Context;
+/// # use ockam_core::Result;
+/// # async fn test(ctx: Context) -> Result<()> {
+/// let quic = QuicTransport::create(&ctx).await?;
+/// quic.listen("127.0.0.1:8000").await?; // Listen on UDP port 8000
+/// quic.connect("127.0.0.1:5000").await?; // And connect to UDP port 5000
+/// # Ok(()) }
+/// ```
+#[derive(AsyncTryClone)]
+#[async_try_clone(crate = "ockam_core")]
+pub struct QuicTransport {
+    router_handle: QuicRouterHandle,
+}
+
+impl QuicTransport {
+    /// Create a new QUIC transport and router for the current node
+    ///
+    /// ```rust
+    /// use ockam_transport_quic::QuicTransport;
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let quic = QuicTransport::create(&ctx).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create(ctx: &Context) -> Result<Self> {
+        let router = QuicRouter::register(ctx).await?;
+
+        Ok(Self {
+            router_handle: router,
+        })
+    }
+
+    /// Manually establish an outgoing QUIC connection on an existing transport.
+    /// This step is optional because the underlying QuicRouter is capable of lazily establishing
+    /// a connection upon arrival of the initial message.
+    ///
+    /// Reconnecting to a peer sends the first messages as 0-RTT data,
+    /// without waiting for the end of the handshake.
+    ///
+    /// ```rust
+    /// use ockam_transport_quic::QuicTransport;
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let quic = QuicTransport::create(&ctx).await?;
+    /// quic.connect("127.0.0.1:5000").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<Address> {
+        self.router_handle.connect(peer.as_ref()).await
+    }
+
+    /// Disconnect from peer
+    pub async fn disconnect<S: AsRef<str>>(&self, peer: S) -> Result<()> {
+        self.router_handle.disconnect(peer.as_ref()).await
+    }
+
+    /// Start listening to incoming connections on an existing transport
+    ///
+    /// Returns the local address that this transport is bound to.
+    ///
+    /// This can be useful, for example, when binding to port 0 to figure out
+    /// which port was actually bound.
+    ///
+    /// ```rust
+    /// use ockam_transport_quic::QuicTransport;
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let quic = QuicTransport::create(&ctx).await?;
+    /// quic.listen("127.0.0.1:8000").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn listen<S: AsRef<str>>(&self, bind_addr: S) -> Result<SocketAddr> {
+        let bind_addr = parse_socket_addr(bind_addr.as_ref())?;
+        self.router_handle.bind(bind_addr).await
+    }
+
+    /// Move the outgoing connections to a new local address, e.g. after
+    /// the network interface they used went down
+    ///
+    /// The connections are migrated, their peers follow them to the new
+    /// address without a new handshake.
+    ///
+    /// ```rust
+    /// use ockam_transport_quic::QuicTransport;
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let quic = QuicTransport::create(&ctx).await?;
+    /// quic.connect("127.0.0.1:5000").await?;
+    /// quic.rebind("0.0.0.0:0").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn rebind<S: AsRef<str>>(&self, bind_addr: S) -> Result<()> {
+        self.router_handle.rebind(bind_addr).await
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{server_config, QuicRouterHandle, QuicSendWorker};
+use ockam_core::{async_trait, compat::net::SocketAddr, AsyncTryClone};
+use ockam_core::{Address, Processor, Result};
+use ockam_node::Context;
+use ockam_transport_core::TransportError;
+use quinn::{Connecting, Endpoint};
+use tracing::{debug, trace, warn};
+
+/// A QUIC Listen processor
+///
+/// QUIC listen processors are created by `QuicTransport`
+/// after a call is made to
+/// [`QuicTransport::listen`](crate::QuicTransport::listen).
+pub(crate) struct QuicListenProcessor {
+    endpoint: Endpoint,
+    router_handle: QuicRouterHandle,
+}
+
+impl QuicListenProcessor {
+    pub(crate) async fn start(
+        ctx: &Context,
+        router_handle: QuicRouterHandle,
+        addr: SocketAddr,
+    ) -> Result<SocketAddr> {
+        debug!("Binding QUIC endpoint to {}", addr);
+        let endpoint = Endpoint::server(server_config()?, addr).map_err(TransportError::from)?;
+        let saddr = endpoint.local_addr().map_err(TransportError::from)?;
+        let worker = Self {
+            endpoint,
+            router_handle,
+        };
+
+        ctx.start_processor(Address::random_local(), worker).await?;
+
+        Ok(saddr)
+    }
+}
+
+#[async_trait]
+impl Processor for QuicListenProcessor {
+    type Context = Context;
+
+    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        ctx.set_cluster(crate::CLUSTER_NAME).await
+    }
+
+    async fn shutdown(&mut self, _ctx: &mut Context) -> Result<()> {
+        self.endpoint.close(0u32.into(), b"closed");
+        Ok(())
+    }
+
+    async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
+        debug!("Waiting for incoming QUIC connection...");
+
+        // Wait for an incoming connection
+        let connecting = match self.endpoint.accept().await {
+            Some(connecting) => connecting,
+            None => return Ok(false),
+        };
+        let peer = connecting.remote_address();
+
+        // Complete the handshake in the background, so that a slow or
+        // malicious peer does not hold up the other incoming connections
+        let ctx = ctx.new_detached(Address::random_local()).await?;
+        let router_handle = self.router_handle.async_try_clone().await?;
+        tokio::spawn(async move {
+            if let Err(e) = accept(ctx, router_handle, connecting, peer).await {
+                warn!(%peer, err = %e, "Failed to accept a QUIC connection");
+            }
+        });
+
+        Ok(true)
+    }
+}
+
+/// Complete the handshake of an incoming connection, then register it with
+/// the local QuicRouter and start its worker.
+async fn accept(
+    ctx: Context,
+    router_handle: QuicRouterHandle,
+    connecting: Connecting,
+    peer: SocketAddr,
+) -> Result<()> {
+    // Accept the 0-RTT data of the peers which connected before, the
+    // handshake is then completed in the background
+    let connection = match connecting.into_0rtt() {
+        Ok((connection, _)) => connection,
+        Err(connecting) => match connecting.await {
+            Ok(connection) => connection,
+            Err(e) => {
+                warn!(%peer, err = %e, "QUIC handshake failed");
+                return Ok(());
+            }
+        },
+    };
+    debug!("QUIC connection accepted");
+
+    let handle_clone = router_handle.async_try_clone().await?;
+    // And create a connection worker for it
+    let (worker, pair) =
+        QuicSendWorker::new_pair(handle_clone, None, Some(connection), peer, Vec::new());
+
+    // Register the connection with the local QuicRouter
+    router_handle.register(&pair).await?;
+    debug!(%peer, "QUIC connection registered");
+
+    trace! {
+        peer = %peer,
+        tx_addr = %pair.tx_addr(),
+        int_addr = %worker.internal_addr(),
+        "starting quic connection worker"
+    };
+
+    ctx.start_worker(vec![pair.tx_addr(), worker.internal_addr().clone()], worker)
+        .await
+}
@@ -0,0 +1,7 @@
+mod listener;
+mod receiver;
+mod sender;
+
+pub(crate) use listener::*;
+pub(crate) use receiver::*;
+pub(crate) use sender::*;
@@ -0,0 +1,117 @@
+use crate::{QuicSendWorkerMsg, MAX_MESSAGE_SIZE, QUIC};
+use ockam_core::async_trait;
+use ockam_core::{Address, Decodable, LocalMessage, Processor, Result, TransportMessage};
+use ockam_node::{Context, ExternalLocalInfo};
+use ockam_transport_core::TransportError;
+use quinn::Connection;
+use tokio::sync::mpsc;
+use tracing::{info, trace, warn};
+
+/// A QUIC receiving message processor
+///
+/// Create this processor type by calling
+/// [`QuicSendWorker::start_pair`](crate::QuicSendWorker::start_pair)
+///
+/// This half of the worker is created when spawning a new connection
+/// worker pair, and accepts the streams opened by the peer, each carrying
+/// a message to relay into the node message system.
+pub(crate) struct QuicRecvProcessor {
+    connection: Connection,
+    peer_addr: Address,
+    sender_internal_address: Address,
+    /// The messages read from the streams, each stream is read in a task of
+    /// its own so that a slow stream doesn't hold back the others
+    messages_tx: mpsc::Sender<Vec<u8>>,
+    messages_rx: mpsc::Receiver<Vec<u8>>,
+}
+
+impl QuicRecvProcessor {
+    /// Create a new `QuicRecvProcessor`
+    pub fn new(
+        connection: Connection,
+        peer_addr: Address,
+        sender_internal_address: Address,
+    ) -> Self {
+        let (messages_tx, messages_rx) = mpsc::channel(32);
+        Self {
+            connection,
+            peer_addr,
+            sender_internal_address,
+            messages_tx,
+            messages_rx,
+        }
+    }
+
+    /// Decode a message and forward it to the next hop in its route
+    async fn forward(&self, ctx: &Context, buf: Vec<u8>) -> Result<()> {
+        // Deserialize the message now
+        let mut msg = TransportMessage::decode(&buf).map_err(|_| TransportError::RecvBadMessage)?;
+
+        // Insert the peer address into the return route so that
+        // reply routing can be properly resolved
+        msg.return_route.modify().prepend(self.peer_addr.clone());
+
+        trace!("Message onward route: {}", msg.onward_route);
+        trace!("Message return route: {}", msg.return_route);
+
+        // Mark that message originates from some other node
+        let local_info = ExternalLocalInfo::new(QUIC).to_local_info()?;
+
+        // Forward the message to the next hop in the route
+        ctx.forward(LocalMessage::new(msg, vec![local_info])).await
+    }
+}
+
+#[async_trait]
+impl Processor for QuicRecvProcessor {
+    type Context = Context;
+
+    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        ctx.set_cluster(crate::CLUSTER_NAME).await
+    }
+
+    /// Accept the next stream, or forward the next message read from the
+    /// streams accepted before.
+    async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
+        tokio::select! {
+            stream = self.connection.accept_uni() => {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        info!(
+                            "Connection to peer '{}' was closed; dropping stream: {}",
+                            self.peer_addr, e
+                        );
+
+                        // Notify sender the connection is closed
+                        ctx.send(
+                            self.sender_internal_address.clone(),
+                            QuicSendWorkerMsg::ConnectionClosed,
+                        )
+                        .await?;
+
+                        return Ok(false);
+                    }
+                };
+                let messages_tx = self.messages_tx.clone();
+                let peer_addr = self.peer_addr.clone();
+                tokio::spawn(async move {
+                    match stream.read_to_end(MAX_MESSAGE_SIZE).await {
+                        Ok(buf) => {
+                            let _ = messages_tx.send(buf).await;
+                        }
+                        Err(e) => warn!("Failed to receive message from peer '{}': {}", peer_addr, e),
+                    }
+                });
+            }
+            Some(buf) = self.messages_rx.recv() => {
+                trace!("Received message of {} bytes", buf.len());
+                if let Err(e) = self.forward(ctx, buf).await {
+                    warn!("Dropping message from peer '{}': {}", self.peer_addr, e);
+                }
+            }
+        }
+
+        Ok(true)
+    }
+}
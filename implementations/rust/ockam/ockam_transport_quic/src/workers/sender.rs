@@ -0,0 +1,289 @@
+use crate::{QuicRecvProcessor, QuicRouterHandle, MAX_MESSAGE_SIZE, SERVER_NAME};
+use ockam_core::{async_trait, compat::net::SocketAddr, Any, Decodable, LocalMessage};
+use ockam_core::{Address, Encodable, Message, Result, Routed, Worker};
+use ockam_node::Context;
+use ockam_transport_core::TransportError;
+use quinn::{Connection, Endpoint, WriteError};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace, warn};
+
+/// Provides the transmit and receive parts of a QUIC connection
+#[derive(Debug)]
+pub(crate) struct WorkerPair {
+    hostnames: Vec<String>,
+    peer: SocketAddr,
+    tx_addr: Address,
+}
+
+impl WorkerPair {
+    /// Return a reference to the peer's hostname(s)
+    pub fn hostnames(&self) -> &[String] {
+        &self.hostnames
+    }
+
+    /// Return a reference to the peer's [`SocketAddr`](std::net::SocketAddr)
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// Return a clone of the transmit [`Address`]
+    pub fn tx_addr(&self) -> Address {
+        self.tx_addr.clone()
+    }
+}
+
+#[derive(Serialize, Deserialize, Message, Clone)]
+pub(crate) enum QuicSendWorkerMsg {
+    ConnectionClosed,
+}
+
+/// A QUIC sending message worker
+///
+/// Create this worker type by calling
+/// [`QuicSendWorker::start_pair`](crate::QuicSendWorker::start_pair)
+///
+/// This half of the worker is created when spawning a new connection
+/// worker pair, and listens for messages from the node message system
+/// to dispatch to a remote peer.
+///
+/// Each message is sent on a unidirectional stream of its own, so that a
+/// message whose packets are lost doesn't hold back the next ones.
+pub(crate) struct QuicSendWorker {
+    router_handle: QuicRouterHandle,
+    /// The endpoint to connect from, if the connection is not open yet
+    endpoint: Option<Endpoint>,
+    connection: Option<Connection>,
+    peer: SocketAddr,
+    internal_addr: Address,
+    rx_addr: Option<Address>,
+}
+
+impl QuicSendWorker {
+    /// Create a new `QuicSendWorker`
+    fn new(
+        router_handle: QuicRouterHandle,
+        endpoint: Option<Endpoint>,
+        connection: Option<Connection>,
+        peer: SocketAddr,
+        internal_addr: Address,
+    ) -> Self {
+        Self {
+            router_handle,
+            endpoint,
+            connection,
+            peer,
+            internal_addr,
+            rx_addr: None,
+        }
+    }
+
+    pub(crate) fn internal_addr(&self) -> &Address {
+        &self.internal_addr
+    }
+
+    /// Create a `(QuicSendWorker, WorkerPair)` without spawning the worker.
+    pub(crate) fn new_pair(
+        router_handle: QuicRouterHandle,
+        endpoint: Option<Endpoint>,
+        connection: Option<Connection>,
+        peer: SocketAddr,
+        hostnames: Vec<String>,
+    ) -> (Self, WorkerPair) {
+        let tx_addr = Address::random_local();
+        let sender = QuicSendWorker::new(
+            router_handle,
+            endpoint,
+            connection,
+            peer,
+            Address::random_local(),
+        );
+        (
+            sender,
+            WorkerPair {
+                hostnames,
+                peer,
+                tx_addr,
+            },
+        )
+    }
+
+    /// Start a `(QuicSendWorker, QuicRecvProcessor)` pair that opens and
+    /// manages the connection with the given peer
+    pub(crate) async fn start_pair(
+        ctx: &Context,
+        router_handle: QuicRouterHandle,
+        endpoint: Option<Endpoint>,
+        connection: Option<Connection>,
+        peer: SocketAddr,
+        hostnames: Vec<String>,
+    ) -> Result<WorkerPair> {
+        trace!("Creating new QUIC worker pair");
+        let (worker, pair) = Self::new_pair(router_handle, endpoint, connection, peer, hostnames);
+        ctx.start_worker(vec![pair.tx_addr(), worker.internal_addr().clone()], worker)
+            .await?;
+        Ok(pair)
+    }
+
+    /// Open a connection to the peer from the endpoint
+    ///
+    /// The connection is used before the end of its handshake when the
+    /// peer was connected to before, the messages sent meanwhile are
+    /// 0-RTT data.
+    async fn connect(&self, endpoint: &Endpoint) -> Result<Connection> {
+        let connecting = endpoint
+            .connect(self.peer, SERVER_NAME)
+            .map_err(crate::quic_error)?;
+        match connecting.into_0rtt() {
+            Ok((connection, _accepted)) => {
+                debug!(addr = %self.peer, "Connected with 0-RTT");
+                Ok(connection)
+            }
+            Err(connecting) => {
+                let connection = connecting.await.map_err(crate::quic_error)?;
+                debug!(addr = %self.peer, "Connected");
+                Ok(connection)
+            }
+        }
+    }
+
+    async fn stop_and_unregister(&self, ctx: &Context) -> Result<()> {
+        self.router_handle.unregister(ctx.address()).await?;
+
+        ctx.stop_worker(ctx.address()).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Worker for QuicSendWorker {
+    type Context = Context;
+    type Message = Any;
+
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        ctx.set_cluster(crate::CLUSTER_NAME).await?;
+
+        if let Some(endpoint) = self.endpoint.take() {
+            debug!(addr = %self.peer, "Connecting");
+            match self.connect(&endpoint).await {
+                Ok(connection) => self.connection = Some(connection),
+                Err(e) => {
+                    debug!(addr = %self.peer, err = %e, "Failed to connect");
+                    self.stop_and_unregister(ctx).await?;
+
+                    return Err(e);
+                }
+            }
+        }
+
+        let connection = self.connection.clone().ok_or(TransportError::GenericIo)?;
+
+        let rx_addr = Address::random_local();
+        let receiver = QuicRecvProcessor::new(
+            connection,
+            format!("{}#{}", crate::QUIC, self.peer).into(),
+            self.internal_addr.clone(),
+        );
+        ctx.start_processor(rx_addr.clone(), receiver).await?;
+
+        self.rx_addr = Some(rx_addr);
+
+        Ok(())
+    }
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        if let Some(rx_addr) = self.rx_addr.take() {
+            let _ = ctx.stop_processor(rx_addr).await;
+        }
+        if let Some(connection) = self.connection.take() {
+            connection.close(0u32.into(), b"closed");
+        }
+
+        Ok(())
+    }
+
+    // QuicSendWorker will receive messages from the QuicRouter to send
+    // across the QUIC connection to our friend
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        let connection = match &self.connection {
+            Some(connection) => connection.clone(),
+            None => return Err(TransportError::PeerNotFound.into()),
+        };
+
+        let recipient = msg.msg_addr();
+        if recipient == self.internal_addr {
+            match QuicSendWorkerMsg::decode(msg.payload())? {
+                QuicSendWorkerMsg::ConnectionClosed => {
+                    warn!("Stopping sender due to closed connection {}", self.peer);
+                    // No need to stop Receiver as it notified us about connection drop and will
+                    // stop itself
+                    self.rx_addr = None;
+                    self.stop_and_unregister(ctx).await?;
+                }
+            }
+            return Ok(());
+        }
+
+        let mut msg = LocalMessage::decode(msg.payload())?.into_transport_message();
+        // Remove our own address from the route so the other end
+        // knows what to do with the incoming message
+        msg.onward_route.step()?;
+        let msg = msg.encode().map_err(|_| TransportError::SendBadMessage)?;
+        if msg.len() > MAX_MESSAGE_SIZE {
+            warn!(
+                "Dropping a message of {} bytes to peer {}, the maximum is {}",
+                msg.len(),
+                self.peer,
+                MAX_MESSAGE_SIZE
+            );
+            return Ok(());
+        }
+
+        // Open the stream before handling the next message, so that the
+        // peer accepts the streams in the order of their messages
+        let stream = match connection.open_uni().await {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("Failed to send message to peer {}: {}", self.peer, e);
+                self.stop_and_unregister(ctx).await?;
+
+                return Ok(());
+            }
+        };
+
+        // Writing the message is awaited in a task of its own, not to
+        // block the next messages while this one is retransmitted
+        let peer = self.peer;
+        tokio::spawn(async move {
+            let mut stream = stream;
+            loop {
+                let res = match stream.write_all(&msg).await {
+                    Ok(()) => stream.finish().await,
+                    Err(e) => Err(e),
+                };
+                match res {
+                    Ok(()) => break,
+                    // The 0-RTT data was rejected, the message is sent
+                    // again now that the handshake is done
+                    Err(WriteError::ZeroRttRejected) => match connection.open_uni().await {
+                        Ok(s) => stream = s,
+                        Err(e) => {
+                            warn!("Failed to send message to peer {}: {}", peer, e);
+                            break;
+                        }
+                    },
+                    Err(e) => {
+                        warn!("Failed to send message to peer {}: {}", peer, e);
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use ockam_core::compat::rand::{self, Rng};
+use ockam_core::{route, Address, Result, Routed, Worker};
+use ockam_node::Context;
+
+use ockam_transport_quic::{QuicTransport, QUIC};
+
+fn random_message(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+#[ockam_macros::test]
+async fn send_receive(ctx: &mut Context) -> Result<()> {
+    let transport = QuicTransport::create(ctx).await?;
+    let listener_address = transport.listen("127.0.0.1:0").await?;
+    ctx.start_worker("echoer", Echoer).await?;
+
+    // Sender, with messages larger than a datagram
+    for len in [256, 64 * 1024] {
+        let msg = random_message(len);
+
+        let r = route![(QUIC, listener_address.to_string()), "echoer"];
+
+        let reply = ctx.send_and_receive::<_, _, String>(r, msg.clone()).await?;
+
+        assert_eq!(reply, msg, "Should receive the same message");
+    }
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+pub struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn quic_lifecycle__reconnect_and_rebind__should_not_error(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = QuicTransport::create(ctx).await?;
+    let listener_address = transport.listen("127.0.0.1:0").await?.to_string();
+
+    let mut child_ctx = ctx.new_detached(Address::random_local()).await?;
+    let r = route![(QUIC, listener_address.clone()), "echoer"];
+
+    let tx_address = transport.connect(&listener_address).await?;
+    let msg = random_message(256);
+    child_ctx.send(r.clone(), msg.clone()).await?;
+    let reply = child_ctx.receive::<String>().await?;
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    // The connection follows the new local address
+    transport.rebind("127.0.0.1:0").await?;
+    let msg = random_message(256);
+    child_ctx.send(r.clone(), msg.clone()).await?;
+    let reply = child_ctx.receive::<String>().await?;
+    assert_eq!(reply, msg, "Should receive the same message after rebind");
+
+    transport.disconnect(&listener_address).await?;
+
+    // QuicSendWorker address should not exist
+    let res = child_ctx.send(tx_address, "TEST".to_string()).await;
+    assert!(res.is_err());
+
+    // Reconnecting resumes the session with 0-RTT
+    transport.connect(&listener_address).await?;
+    let msg = random_message(256);
+    child_ctx.send(r, msg.clone()).await?;
+    let reply = child_ctx.receive::<String>().await?;
+    assert_eq!(
+        reply, msg,
+        "Should receive the same message after reconnect"
+    );
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn quic_lifecycle__rebind_before_connect__should_not_error(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = QuicTransport::create(ctx).await?;
+    let listener_address = transport.listen("127.0.0.1:0").await?.to_string();
+
+    // Without outgoing connections yet, rebinding creates the client endpoint
+    transport.rebind("127.0.0.1:0").await?;
+
+    let r = route![(QUIC, listener_address), "echoer"];
+    let msg = random_message(256);
+    let reply = ctx.send_and_receive::<_, _, String>(r, msg.clone()).await?;
+    assert_eq!(reply, msg, "Should receive the same message after rebind");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -26,17 +26,24 @@ extern crate core;
 extern crate alloc;
 
 mod portal;
+mod proxy;
 mod router;
+mod socket;
 mod workers;
 
 pub(crate) use portal::*;
+pub use proxy::{EgressPolicy, ProxyProtocol, ProxyRoutes, UpstreamProxy, NO_PROXY_ENV, PROXY_ENV};
 pub(crate) use router::*;
+pub use socket::{ListenerOptions, ReconnectBackoff, TcpConnectionOptions};
 pub(crate) use workers::*;
 
 mod transport;
 
 pub use transport::*;
 
+#[cfg(feature = "tls")]
+pub mod tls;
+
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::{Result, TransportType};
 use ockam_transport_core::TransportError;
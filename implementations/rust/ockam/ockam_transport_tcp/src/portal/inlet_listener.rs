@@ -1,12 +1,13 @@
-use crate::TcpPortalWorker;
+use crate::{split_tcp, InletOptions, InletRoute, ProxyProtocol, ProxyRoutes, TcpPortalWorker};
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::compat::sync::Arc;
 use ockam_core::{async_trait, compat::boxed::Box, AccessControl};
-use ockam_core::{Address, Processor, Result, Route};
+use ockam_core::{Address, Processor, Result};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
+use tokio::io::AsyncWriteExt;
 use tokio::net::TcpListener;
-use tracing::debug;
+use tracing::{debug, warn};
 
 /// A TCP Portal Inlet listen processor
 ///
@@ -15,29 +16,56 @@ use tracing::debug;
 /// [`TcpTransport::create_inlet`](crate::TcpTransport::create_inlet).
 pub(crate) struct TcpInletListenProcessor {
     inner: TcpListener,
-    outlet_listener_route: Route,
+    outlet_listener_route: InletRoute,
     access_control: Arc<dyn AccessControl>,
+    proxy: Option<ProxyProtocol>,
+    proxy_routes: ProxyRoutes,
+    #[cfg(feature = "tls")]
+    tls: Option<Arc<crate::tls::InletTls>>,
+    /// The other accept workers of the inlet, stopped with this one
+    siblings: Vec<Address>,
 }
 
 impl TcpInletListenProcessor {
     /// Start a new `TcpInletListenProcessor`
     pub(crate) async fn start(
         ctx: &Context,
-        outlet_listener_route: Route,
         addr: SocketAddr,
-        access_control: Arc<dyn AccessControl>,
+        options: InletOptions,
     ) -> Result<(Address, SocketAddr)> {
         let waddr = Address::random_local();
 
         debug!("Binding TcpPortalListenerWorker to {}", addr);
-        let inner = TcpListener::bind(addr)
-            .await
-            .map_err(TransportError::from)?;
+        let mut listeners = options.listener.bind_all(addr)?.into_iter();
+        let inner = listeners.next().ok_or(TransportError::BindFailed)?;
         let saddr = inner.local_addr().map_err(TransportError::from)?;
+
+        let mut siblings = Vec::new();
+        for inner in listeners {
+            let processor = Self {
+                inner,
+                outlet_listener_route: options.outlet_route.clone(),
+                access_control: options.access_control.clone(),
+                proxy: options.proxy,
+                proxy_routes: options.proxy_routes.clone(),
+                #[cfg(feature = "tls")]
+                tls: options.tls.clone(),
+                siblings: Vec::new(),
+            };
+            let address = Address::random_local();
+            ctx.start_processor(address.clone(), processor).await?;
+            siblings.push(address);
+        }
+
         let processor = Self {
             inner,
-            outlet_listener_route,
-            access_control,
+            outlet_listener_route: options.outlet_route,
+            access_control: options.access_control,
+            proxy: options.proxy,
+            proxy_routes: options.proxy_routes,
+            #[cfg(feature = "tls")]
+            tls: options.tls,
+            siblings,
         };
 
         ctx.start_processor(waddr.clone(), processor).await?;
@@ -50,14 +78,63 @@ impl TcpInletListenProcessor {
 impl Processor for TcpInletListenProcessor {
     type Context = Context;
 
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        for address in self.siblings.drain(..) {
+            if let Err(e) = ctx.stop_processor(address.clone()).await {
+                debug!(%address, err = %e, "Failed to stop an accept worker");
+            }
+        }
+        Ok(())
+    }
+
     async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
         let (stream, peer) = self.inner.accept().await.map_err(TransportError::from)?;
+
+        #[cfg(feature = "tls")]
+        let mut stream = match &self.tls {
+            Some(tls) => match tls.accept(stream).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("Rejected inlet connection from {}: {}", peer, e);
+                    return Ok(true);
+                }
+            },
+            None => split_tcp(stream),
+        };
+        #[cfg(not(feature = "tls"))]
+        let mut stream = split_tcp(stream);
+
+        let mut route = self.outlet_listener_route.get();
+        let proxy = match &self.proxy {
+            Some(protocol) => match protocol.handshake(&mut stream.0, &mut stream.1).await {
+                Ok(Some(mut request)) => {
+                    if let Some(r) = self.proxy_routes.get(&request.target) {
+                        route = r;
+                        request.direct = true;
+                    } else if route.next().is_err() {
+                        warn!("Proxy denied the connection to {}", request.target);
+                        let _ = stream.1.write_all(&request.denied).await;
+                        let _ = stream.1.shutdown().await;
+                        return Ok(true);
+                    }
+                    Some(request)
+                }
+                Ok(None) => return Ok(true),
+                Err(e) => {
+                    warn!("Rejected proxy connection from {}: {}", peer, e);
+                    return Ok(true);
+                }
+            },
+            None => None,
+        };
+
         TcpPortalWorker::start_new_inlet(
             ctx,
             stream,
             peer,
-            self.outlet_listener_route.clone(),
+            route,
             self.access_control.clone(),
+            proxy,
         )
         .await?;
 
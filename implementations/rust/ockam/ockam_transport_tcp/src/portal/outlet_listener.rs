@@ -1,9 +1,11 @@
-use crate::{PortalMessage, TcpPortalWorker, TcpRouterHandle};
+use crate::{
+    EgressPolicy, OutletOptions, OutletTarget, PortalMessage, TcpPortalWorker, TcpRouterHandle,
+};
 use ockam_core::{async_trait, AccessControl, Result, Routed, Worker};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
 use std::sync::Arc;
-use tracing::debug;
+use tracing::{debug, warn};
 
 /// A TCP Portal Outlet listen worker
 ///
@@ -11,16 +13,22 @@ use tracing::debug;
 /// after a call is made to
 /// [`TcpTransport::create_outlet`](crate::TcpTransport::create_outlet).
 pub(crate) struct TcpOutletListenWorker {
-    peer: String,
+    peer: Option<String>,
     access_control: Arc<dyn AccessControl>,
+    egress: Option<EgressPolicy>,
+    #[cfg(feature = "tls")]
+    tls: Option<Arc<crate::tls::OutletTls>>,
 }
 
 impl TcpOutletListenWorker {
     /// Create a new `TcpOutletListenWorker`
-    pub(crate) fn new(peer: String, access_control: Arc<dyn AccessControl>) -> Self {
+    pub(crate) fn new(options: OutletOptions) -> Self {
         Self {
-            peer,
-            access_control,
+            peer: options.peer,
+            access_control: options.access_control,
+            egress: options.egress,
+            #[cfg(feature = "tls")]
+            tls: options.tls,
         }
     }
 }
@@ -37,20 +45,37 @@ impl Worker for TcpOutletListenWorker {
     ) -> Result<()> {
         let return_route = msg.return_route();
 
-        if let PortalMessage::Ping = msg.body() {
-        } else {
-            return Err(TransportError::Protocol.into());
-        }
-
-        let (peer_addr, _) = TcpRouterHandle::resolve_peer(self.peer.clone())?;
-
-        let address = TcpPortalWorker::start_new_outlet(
-            ctx,
-            peer_addr,
-            return_route.clone(),
-            self.access_control.clone(),
-        )
-        .await?;
+        let address = match (msg.body(), &self.peer, &self.egress) {
+            (PortalMessage::Ping, Some(peer), _) => {
+                let (peer_addr, _) = TcpRouterHandle::resolve_peer(peer.clone())?;
+                TcpPortalWorker::start_new_outlet(
+                    ctx,
+                    OutletTarget::Peer(peer_addr),
+                    return_route.clone(),
+                    self.access_control.clone(),
+                    #[cfg(feature = "tls")]
+                    self.tls.clone(),
+                )
+                .await?
+            }
+            (PortalMessage::Connect(target), _, Some(egress)) => {
+                if !egress.allows_target(&target) {
+                    warn!("Proxy Outlet denied the connection to {}", target);
+                    ctx.send(return_route, PortalMessage::Disconnect).await?;
+                    return Ok(());
+                }
+                TcpPortalWorker::start_new_outlet(
+                    ctx,
+                    OutletTarget::Dynamic(target),
+                    return_route.clone(),
+                    self.access_control.clone(),
+                    #[cfg(feature = "tls")]
+                    None,
+                )
+                .await?
+            }
+            _ => return Err(TransportError::Protocol.into()),
+        };
 
         debug!("Created Tcp Outlet at {}", &address);
 
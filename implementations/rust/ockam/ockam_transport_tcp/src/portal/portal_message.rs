@@ -13,6 +13,12 @@ pub enum PortalMessage {
     Disconnect,
     /// Message with binary payload
     Payload(Vec<u8>),
+    /// First message that a proxy Inlet sends to a proxy Outlet, with the
+    /// `host:port` to connect to
+    Connect(String),
+    /// Message to indicate that the other side finished sending, but
+    /// still reads what is sent to it
+    Eof,
 }
 
 /// An internal message type for a Portal
@@ -20,4 +26,6 @@ pub enum PortalMessage {
 pub enum PortalInternalMessage {
     /// Connection was dropped
     Disconnect,
+    /// Connection was closed for reading
+    Eof,
 }
@@ -1,9 +1,9 @@
-use crate::{PortalInternalMessage, PortalMessage};
+use crate::{PortalInternalMessage, PortalMessage, PortalReader};
 use ockam_core::compat::vec::Vec;
 use ockam_core::{async_trait, Encodable, LocalMessage, Route, TransportMessage};
 use ockam_core::{route, Address, Processor, Result};
 use ockam_node::Context;
-use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
+use tokio::io::AsyncReadExt;
 use tracing::{error, warn};
 
 const MAX_PAYLOAD_SIZE: usize = 48 * 1024;
@@ -15,14 +15,14 @@ const MAX_PAYLOAD_SIZE: usize = 48 * 1024;
 /// [`TcpPortalWorker::start_receiver`](crate::TcpPortalWorker::start_receiver)
 pub(crate) struct TcpPortalRecvProcessor {
     buf: Vec<u8>,
-    rx: OwnedReadHalf,
+    rx: PortalReader,
     sender_address: Address,
     onward_route: Route,
 }
 
 impl TcpPortalRecvProcessor {
     /// Create a new `TcpPortalRecvProcessor`
-    pub fn new(rx: OwnedReadHalf, sender_address: Address, onward_route: Route) -> Self {
+    pub fn new(rx: PortalReader, sender_address: Address, onward_route: Route) -> Self {
         Self {
             buf: Vec::with_capacity(MAX_PAYLOAD_SIZE),
             rx,
@@ -30,6 +30,31 @@ impl TcpPortalRecvProcessor {
             onward_route,
         }
     }
+
+    /// Notify Sender and the other side that reading stopped
+    async fn notify(
+        &self,
+        ctx: &Context,
+        internal: PortalInternalMessage,
+        remote: PortalMessage,
+    ) -> Result<()> {
+        if let Err(err) = ctx
+            .send(route![self.sender_address.clone()], internal)
+            .await
+        {
+            warn!(
+                "Error notifying Tcp Portal Sender about closed connection {}",
+                err
+            );
+        }
+
+        let msg = TransportMessage::v1(
+            self.onward_route.clone(),
+            self.sender_address.clone(),
+            remote.encode()?,
+        );
+        ctx.forward(LocalMessage::new(msg, vec![])).await
+    }
 }
 
 #[async_trait]
@@ -39,36 +64,22 @@ impl Processor for TcpPortalRecvProcessor {
     async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
         self.buf.clear();
 
-        let _len = match self.rx.read_buf(&mut self.buf).await {
-            Ok(len) => len,
-            Err(err) => {
-                error!("Tcp Portal connection read failed with error: {}", err);
-                return Ok(false);
-            }
-        };
+        if let Err(err) = self.rx.read_buf(&mut self.buf).await {
+            error!("Tcp Portal connection read failed with error: {}", err);
+            self.notify(
+                ctx,
+                PortalInternalMessage::Disconnect,
+                PortalMessage::Disconnect,
+            )
+            .await?;
+            return Ok(false);
+        }
 
         if self.buf.is_empty() {
-            // Notify Sender that connection was closed
-            if let Err(err) = ctx
-                .send(
-                    route![self.sender_address.clone()],
-                    PortalInternalMessage::Disconnect,
-                )
-                .await
-            {
-                warn!(
-                    "Error notifying Tcp Portal Sender about dropped connection {}",
-                    err
-                );
-            }
-
-            let msg = TransportMessage::v1(
-                self.onward_route.clone(),
-                self.sender_address.clone(),
-                PortalMessage::Disconnect.encode()?,
-            );
-            ctx.forward(LocalMessage::new(msg, vec![])).await?;
-
+            // The peer is done sending, but may still read what the other
+            // side sends, so only this direction is closed
+            self.notify(ctx, PortalInternalMessage::Eof, PortalMessage::Eof)
+                .await?;
             return Ok(false);
         }
 
@@ -6,11 +6,48 @@ use ockam_core::{Address, Any, Result, Route, Routed, Worker};
 use ockam_node::{Context, WorkerBuilder};
 use ockam_transport_core::TransportError;
 use std::sync::Arc;
-use tokio::io::AsyncWriteExt;
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
 use tokio::net::TcpStream;
 use tracing::{debug, info, trace, warn};
 
+/// The reading half of a portal or transport connection, plain or TLS
+pub(crate) type PortalReader = Box<dyn AsyncRead + Send + Sync + Unpin>;
+
+/// The writing half of a portal or transport connection, plain or TLS
+pub(crate) type PortalWriter = Box<dyn AsyncWrite + Send + Sync + Unpin>;
+
+/// Split a plain TCP connection into portal halves
+pub(crate) fn split_tcp(stream: TcpStream) -> (PortalReader, PortalWriter) {
+    let (rx, tx) = stream.into_split();
+    (Box::new(rx), Box::new(tx))
+}
+
+/// The target an outlet connects to
+pub(crate) enum OutletTarget {
+    /// A resolved socket address
+    Peer(SocketAddr),
+    /// A `host:port` to resolve when connecting, for proxy outlets
+    Dynamic(String),
+}
+
+/// A connection of a proxy inlet, for which the client picks the target
+pub(crate) struct ProxyRequest {
+    /// The `host:port` the client wants to connect to
+    pub(crate) target: String,
+    /// Whether the outlet is dedicated to the target, so that it connects
+    /// to its own target instead of the requested one
+    pub(crate) direct: bool,
+    /// Sent to the client once the outlet is connected
+    pub(crate) connected: Vec<u8>,
+    /// Sent to the client if the outlet could not connect
+    pub(crate) refused: Vec<u8>,
+    /// Sent to the client if no outlet may connect to the target
+    pub(crate) denied: Vec<u8>,
+    /// Sent to the target once the outlet is connected, before what the
+    /// client sends
+    pub(crate) request: Vec<u8>,
+}
+
 /// Enumerate all `TcpPortalWorker` states
 ///
 /// Possible state transitions are:
@@ -40,93 +77,110 @@ enum TypeName {
 /// after a new connection has been accepted.
 pub(crate) struct TcpPortalWorker {
     state: State,
-    tx: Option<OwnedWriteHalf>,
-    rx: Option<OwnedReadHalf>,
+    tx: Option<PortalWriter>,
+    rx: Option<PortalReader>,
     peer: SocketAddr,
     internal_address: Address,
     remote_address: Address,
     receiver_address: Address,
     remote_route: Option<Route>,
     is_disconnecting: bool,
+    /// The local peer finished sending
+    read_closed: bool,
+    /// The other side finished sending, and so did we to the local peer
+    write_closed: bool,
     type_name: TypeName,
+    proxy: Option<ProxyRequest>,
+    target: Option<OutletTarget>,
+    #[cfg(feature = "tls")]
+    tls: Option<Arc<crate::tls::OutletTls>>,
 }
 
 impl TcpPortalWorker {
     /// Start a new `TcpPortalWorker` of type [`TypeName::Inlet`]
+    ///
+    /// With a `proxy` request, the outlet is asked to connect to its
+    /// target and the client is told whether that succeeded.
     pub(crate) async fn start_new_inlet(
         ctx: &Context,
-        stream: TcpStream,
+        stream: (PortalReader, PortalWriter),
         peer: SocketAddr,
         ping_route: Route,
         access_control: Arc<dyn AccessControl>,
+        proxy: Option<ProxyRequest>,
     ) -> Result<Address> {
-        Self::start(
-            ctx,
+        let mut worker = Self::new(
             peer,
             State::SendPing { ping_route },
             Some(stream),
             TypeName::Inlet,
-            access_control,
-        )
-        .await
+        );
+        worker.proxy = proxy;
+        worker.start(ctx, access_control).await
     }
 
     /// Start a new `TcpPortalWorker` of type [`TypeName::Outlet`]
     pub(crate) async fn start_new_outlet(
         ctx: &Context,
-        peer: SocketAddr,
+        target: OutletTarget,
         pong_route: Route,
         access_control: Arc<dyn AccessControl>,
+        #[cfg(feature = "tls")] tls: Option<Arc<crate::tls::OutletTls>>,
     ) -> Result<Address> {
-        Self::start(
-            ctx,
-            peer,
-            State::SendPong { pong_route },
-            None,
-            TypeName::Outlet,
-            access_control,
-        )
-        .await
+        let peer = match &target {
+            OutletTarget::Peer(peer) => *peer,
+            // Only known once connected
+            OutletTarget::Dynamic(_) => SocketAddr::from(([0, 0, 0, 0], 0)),
+        };
+        let mut worker = Self::new(peer, State::SendPong { pong_route }, None, TypeName::Outlet);
+        worker.target = Some(target);
+        #[cfg(feature = "tls")]
+        {
+            worker.tls = tls;
+        }
+        worker.start(ctx, access_control).await
     }
 
-    /// Start a new `TcpPortalWorker`
-    async fn start(
-        ctx: &Context,
+    fn new(
         peer: SocketAddr,
         state: State,
-        stream: Option<TcpStream>,
+        stream: Option<(PortalReader, PortalWriter)>,
         type_name: TypeName,
-        access_control: Arc<dyn AccessControl>,
-    ) -> Result<Address> {
-        let internal_addr = Address::random_local();
-        let remote_addr = Address::random_local();
-        let receiver_address = Address::random_local();
-
-        info!(
-            "Creating new {:?} at internal: {}, remote: {}",
-            type_name, internal_addr, remote_addr
-        );
-
+    ) -> Self {
         let (rx, tx) = match stream {
-            Some(s) => {
-                let (rx, tx) = s.into_split();
-                (Some(rx), Some(tx))
-            }
+            Some((rx, tx)) => (Some(rx), Some(tx)),
             None => (None, None),
         };
 
-        let sender = Self {
+        Self {
             state,
             tx,
             rx,
             peer,
-            internal_address: internal_addr.clone(),
-            remote_address: remote_addr.clone(),
+            internal_address: Address::random_local(),
+            remote_address: Address::random_local(),
             remote_route: None,
-            receiver_address,
+            receiver_address: Address::random_local(),
             is_disconnecting: false,
+            read_closed: false,
+            write_closed: false,
             type_name,
-        };
+            proxy: None,
+            target: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+    /// Start a new `TcpPortalWorker`
+    async fn start(self, ctx: &Context, access_control: Arc<dyn AccessControl>) -> Result<Address> {
+        let internal_addr = self.internal_address.clone();
+        let remote_addr = self.remote_address.clone();
+
+        info!(
+            "Creating new {:?} at internal: {}, remote: {}",
+            self.type_name, internal_addr, remote_addr
+        );
 
         let main_internal_mailbox = Mailbox::new(
             internal_addr,
@@ -134,7 +188,7 @@ impl TcpPortalWorker {
         );
         let remote_mailbox = Mailbox::new(remote_addr.clone(), access_control);
         let mailboxes = Mailboxes::new(main_internal_mailbox, vec![remote_mailbox]);
-        WorkerBuilder::with_mailboxes(mailboxes, sender)
+        WorkerBuilder::with_mailboxes(mailboxes, self)
             .start(ctx)
             .await?;
 
@@ -244,9 +298,30 @@ impl TcpPortalWorker {
         Ok(())
     }
 
+    /// Stop the portal once both directions of the connection were closed
+    async fn stop_if_closed(&mut self, ctx: &Context) -> Result<()> {
+        if !(self.read_closed && self.write_closed) {
+            return Ok(());
+        }
+
+        self.is_disconnecting = true;
+        ctx.stop_worker(self.internal_address.clone()).await?;
+
+        info!(
+            "{:?} at: {} stopped after the connection was closed",
+            self.type_name, self.internal_address
+        );
+
+        Ok(())
+    }
+
     async fn handle_send_ping(&self, ctx: &Context, ping_route: Route) -> Result<State> {
         // Force creation of Outlet on the other side
-        ctx.send_from_address(ping_route, PortalMessage::Ping, self.remote_address.clone())
+        let msg = match &self.proxy {
+            Some(proxy) if !proxy.direct => PortalMessage::Connect(proxy.target.clone()),
+            _ => PortalMessage::Ping,
+        };
+        ctx.send_from_address(ping_route, msg, self.remote_address.clone())
             .await?;
 
         debug!("Inlet at: {} sent ping", self.internal_address);
@@ -255,6 +330,35 @@ impl TcpPortalWorker {
     }
 
     async fn handle_send_pong(&mut self, ctx: &Context, pong_route: Route) -> Result<State> {
+        // Connect before answering, so that the Inlet learns about failures
+        if self.tx.is_none() {
+            match self.connect().await {
+                Ok((rx, tx)) => {
+                    self.tx = Some(tx);
+                    self.rx = Some(rx);
+                    debug!(
+                        "Outlet at: {} successfully connected",
+                        self.internal_address
+                    );
+                }
+                Err(e) => {
+                    warn!(
+                        "Outlet at: {} failed to connect to its target: {}",
+                        self.internal_address, e
+                    );
+                    ctx.send_from_address(
+                        pong_route,
+                        PortalMessage::Disconnect,
+                        self.remote_address.clone(),
+                    )
+                    .await?;
+                    self.is_disconnecting = true;
+                    ctx.stop_worker(self.internal_address.clone()).await?;
+                    return Ok(State::Initialized);
+                }
+            }
+        }
+
         // Respond to Inlet
         ctx.send_from_address(
             pong_route.clone(),
@@ -263,27 +367,30 @@ impl TcpPortalWorker {
         )
         .await?;
 
-        if self.tx.is_none() {
-            let stream = TcpStream::connect(self.peer)
-                .await
-                .map_err(TransportError::from)?;
-            let (rx, tx) = stream.into_split();
-            self.tx = Some(tx);
-            self.rx = Some(rx);
-
-            self.start_receiver(ctx, pong_route.clone()).await?;
-
-            debug!(
-                "Outlet at: {} successfully connected",
-                self.internal_address
-            );
-        }
+        self.start_receiver(ctx, pong_route.clone()).await?;
 
         debug!("Outlet at: {} sent pong", self.internal_address);
 
         self.remote_route = Some(pong_route);
         Ok(State::Initialized)
     }
+
+    /// Connect an Outlet to its target
+    async fn connect(&mut self) -> Result<(PortalReader, PortalWriter)> {
+        let stream = match &self.target {
+            Some(OutletTarget::Dynamic(target)) => TcpStream::connect(target.as_str()).await,
+            _ => TcpStream::connect(self.peer).await,
+        }
+        .map_err(TransportError::from)?;
+        if let Ok(peer) = stream.peer_addr() {
+            self.peer = peer
+        }
+        #[cfg(feature = "tls")]
+        if let Some(tls) = &self.tls {
+            return tls.connect(stream).await;
+        }
+        Ok(split_tcp(stream))
+    }
 }
 
 #[async_trait]
@@ -337,9 +444,41 @@ impl Worker for TcpPortalWorker {
 
                 let msg = PortalMessage::decode(msg.payload())?;
 
-                if let PortalMessage::Pong = msg {
-                } else {
-                    return Err(TransportError::Protocol.into());
+                match msg {
+                    PortalMessage::Pong => {}
+                    PortalMessage::Disconnect => {
+                        // The Outlet could not connect to its target
+                        info!(
+                            "Outlet refused the connection of Inlet at: {}",
+                            self.internal_address
+                        );
+                        self.is_disconnecting = true;
+                        if let (Some(tx), Some(proxy)) = (&mut self.tx, &self.proxy) {
+                            let _ = tx.write_all(&proxy.refused).await;
+                            let _ = tx.shutdown().await;
+                        }
+                        ctx.stop_worker(self.internal_address.clone()).await?;
+                        return Ok(());
+                    }
+                    _ => return Err(TransportError::Protocol.into()),
+                }
+
+                if let (Some(tx), Some(proxy)) = (&mut self.tx, &self.proxy) {
+                    let res = match tx.write_all(&proxy.connected).await {
+                        Ok(()) => tx.flush().await,
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = res {
+                        warn!("Failed to answer proxy client {}: {}", self.peer, e);
+                    }
+                    if !proxy.request.is_empty() {
+                        ctx.send_from_address(
+                            return_route.clone(),
+                            PortalMessage::Payload(proxy.request.clone()),
+                            self.remote_address.clone(),
+                        )
+                        .await?;
+                    }
                 }
 
                 self.start_receiver(ctx, return_route.clone()).await?;
@@ -368,6 +507,14 @@ impl Worker for TcpPortalWorker {
                             self.start_disconnection(ctx, DisconnectionReason::FailedRx)
                                 .await?;
                         }
+                        PortalInternalMessage::Eof => {
+                            debug!(
+                                "Tcp stream was closed for reading for {:?} at: {}",
+                                self.type_name, self.internal_address
+                            );
+                            self.read_closed = true;
+                            self.stop_if_closed(ctx).await?;
+                        }
                     }
                 } else {
                     trace!(
@@ -382,7 +529,12 @@ impl Worker for TcpPortalWorker {
                     match msg {
                         PortalMessage::Payload(payload) => {
                             if let Some(tx) = &mut self.tx {
-                                match tx.write_all(&payload).await {
+                                // TLS streams buffer writes until flushed
+                                let res = match tx.write_all(&payload).await {
+                                    Ok(()) => tx.flush().await,
+                                    Err(err) => Err(err),
+                                };
+                                match res {
                                     Ok(()) => {}
                                     Err(err) => {
                                         warn!(
@@ -404,7 +556,20 @@ impl Worker for TcpPortalWorker {
                             self.start_disconnection(ctx, DisconnectionReason::Remote)
                                 .await?;
                         }
-                        PortalMessage::Ping | PortalMessage::Pong => {
+                        PortalMessage::Eof => {
+                            // Forward the FIN, the local peer may still send
+                            if let Some(tx) = &mut self.tx {
+                                if let Err(err) = tx.shutdown().await {
+                                    debug!(
+                                        "Failed to close connection to peer {} for writing: {}",
+                                        self.peer, err
+                                    );
+                                }
+                            }
+                            self.write_closed = true;
+                            self.stop_if_closed(ctx).await?;
+                        }
+                        PortalMessage::Ping | PortalMessage::Pong | PortalMessage::Connect(_) => {
                             return Err(TransportError::Protocol.into());
                         }
                     }
@@ -0,0 +1,189 @@
+use super::{proxy_error, split_host_port};
+use core::fmt;
+use core::str::FromStr;
+use ockam_core::Result;
+use std::net::IpAddr;
+
+/// The targets a proxy outlet may connect to
+///
+/// A policy is a list of rules, and a target is allowed if any rule
+/// matches it.  A rule is a host with an optional port:
+///
+/// - `*` matches any host, e.g. `*:443`
+/// - `*.example.com` matches the subdomains of `example.com`
+/// - `example.com`, `10.0.0.1` or `[::1]` only match that host
+///
+/// Hosts are matched by name, as requested by the client, before they are
+/// resolved.  The empty policy denies everything.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EgressPolicy {
+    rules: Vec<Rule>,
+}
+
+/// A host with an optional port, see [`EgressPolicy`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Rule {
+    host: Host,
+    port: Option<u16>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Host {
+    Any,
+    Subdomains(String),
+    Exact(String),
+}
+
+impl EgressPolicy {
+    /// Allow every target
+    pub fn allow_all() -> Self {
+        Self {
+            rules: vec![Rule {
+                host: Host::Any,
+                port: None,
+            }],
+        }
+    }
+
+    /// Parse a list of rules
+    pub fn parse<S: AsRef<str>>(rules: &[S]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|r| r.as_ref().parse())
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Whether the policy allows connecting to `host` on `port`
+    pub fn allows(&self, host: &str, port: u16) -> bool {
+        let host = normalize(host);
+        self.rules.iter().any(|r| r.matches(&host, port))
+    }
+
+    /// Whether the policy allows connecting to a `host:port` target
+    pub fn allows_target(&self, target: &str) -> bool {
+        self.rules.iter().any(|r| r.matches_target(target))
+    }
+}
+
+impl Rule {
+    /// Whether the rule matches a `host:port` target
+    pub(crate) fn matches_target(&self, target: &str) -> bool {
+        match split_host_port(target) {
+            (host, Some(port)) => port
+                .parse()
+                .map_or(false, |p| self.matches(&normalize(host), p)),
+            _ => false,
+        }
+    }
+
+    fn matches(&self, host: &str, port: u16) -> bool {
+        if self.port.map_or(false, |p| p != port) {
+            return false;
+        }
+        match &self.host {
+            Host::Any => true,
+            Host::Subdomains(domain) => host
+                .strip_suffix(domain.as_str())
+                .map_or(false, |sub| sub.len() > 1 && sub.ends_with('.')),
+            Host::Exact(h) => h == host,
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ockam_core::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || proxy_error(format!("invalid egress rule: {}", s));
+        let (host, port) = split_host_port(s.trim());
+        let port = match port {
+            None | Some("*") => None,
+            Some(p) => Some(p.parse().map_err(|_| invalid())?),
+        };
+        let host = match host {
+            "" => return Err(invalid()),
+            "*" => Host::Any,
+            h => match h.strip_prefix("*.") {
+                Some("") => return Err(invalid()),
+                Some(domain) if !domain.contains('*') => Host::Subdomains(normalize(domain)),
+                Some(_) => return Err(invalid()),
+                None if h.contains('*') => return Err(invalid()),
+                None => Host::Exact(normalize(h)),
+            },
+        };
+        Ok(Rule { host, port })
+    }
+}
+
+impl fmt::Display for EgressPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rules: Vec<String> = self.rules.iter().map(|r| r.to_string()).collect();
+        write!(f, "{}", rules.join(","))
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.host {
+            Host::Any => write!(f, "*")?,
+            Host::Subdomains(d) => write!(f, "*.{}", d)?,
+            Host::Exact(h) if h.contains(':') => write!(f, "[{}]", h)?,
+            Host::Exact(h) => write!(f, "{}", h)?,
+        }
+        match self.port {
+            Some(p) => write!(f, ":{}", p),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Lowercase a host name without its trailing dot, or format an IP address
+/// in its canonical form.
+fn normalize(host: &str) -> String {
+    match host.parse::<IpAddr>() {
+        Ok(ip) => ip.to_string(),
+        Err(_) => host.trim_end_matches('.').to_ascii_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_match_rules() {
+        let policy =
+            EgressPolicy::parse(&["*.Example.com.", "db.internal:5432", "10.0.0.1", "[::1]:22"])
+                .unwrap();
+        assert_eq!(
+            policy.to_string(),
+            "*.example.com,db.internal:5432,10.0.0.1,[::1]:22"
+        );
+
+        assert!(policy.allows("www.example.com", 443));
+        assert!(policy.allows("A.B.EXAMPLE.COM.", 80));
+        assert!(!policy.allows("example.com", 443));
+        assert!(!policy.allows("badexample.com", 443));
+        assert!(policy.allows_target("db.internal:5432"));
+        assert!(!policy.allows_target("db.internal:22"));
+        assert!(policy.allows_target("10.0.0.1:8080"));
+        assert!(policy.allows_target("[0:0::1]:22"));
+        assert!(!policy.allows_target("[::1]:23"));
+        assert!(!policy.allows_target("10.0.0.1"));
+    }
+
+    #[test]
+    fn allow_all_and_deny_all() {
+        assert!(EgressPolicy::allow_all().allows("anything", 1));
+        assert!(!EgressPolicy::default().allows("anything", 1));
+        assert!(EgressPolicy::parse(&["*:443"]).unwrap().allows("x", 443));
+    }
+
+    #[test]
+    fn reject_invalid_rules() {
+        for rule in ["", "*.", "a.*.com", "host:port", "host:70000", "*x"] {
+            assert!(EgressPolicy::parse(&[rule]).is_err(), "{}", rule);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! The server side of HTTP `CONNECT` tunnels (RFC 9110, section 9.3.6),
+//! and of plain requests sent to a proxy, with an absolute URI. The client
+//! side of `CONNECT` tunnels connects through an upstream proxy.
+//!
+//! A plain request is rewritten for the origin server, which then gets
+//! the connection as is. Bodies, chunked or not, and connections upgraded
+//! to WebSockets are streamed without being looked at. Since the
+//! connection goes to the host of the first request, it is closed after
+//! the response unless it is upgraded.
+
+use super::{proxy_error, split_host_port};
+use crate::{PortalReader, PortalWriter, ProxyRequest};
+use ockam_core::Result;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Maximum size of the request line and headers of a client.
+const MAX_HEADER_SIZE: usize = 8192;
+
+/// Headers of the client meant for the proxy, which are not forwarded.
+const HOP_BY_HOP: [&str; 4] = [
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "proxy-authorization",
+];
+
+/// Read the request of a client.
+pub(super) async fn handshake(
+    rx: &mut PortalReader,
+    tx: &mut PortalWriter,
+) -> Result<Option<ProxyRequest>> {
+    let io = |e: std::io::Error| proxy_error(format!("HTTP proxy handshake failed: {}", e));
+
+    // Read byte by byte up to the end of the headers, since anything after
+    // them belongs to the tunnel.
+    let mut header = Vec::new();
+    while !header.ends_with(b"\r\n\r\n") {
+        if header.len() == MAX_HEADER_SIZE {
+            tx.write_all(&reply("431 Request Header Fields Too Large"))
+                .await
+                .map_err(io)?;
+            return Ok(None);
+        }
+        header.push(rx.read_u8().await.map_err(io)?);
+    }
+
+    let header = String::from_utf8_lossy(&header);
+    let mut request_line = header.lines().next().unwrap_or_default().split(' ');
+    let (method, target, version) = (
+        request_line.next().unwrap_or_default(),
+        request_line.next().unwrap_or_default(),
+        request_line.next().unwrap_or_default(),
+    );
+    if !version.starts_with("HTTP/1.") {
+        return Err(proxy_error("not an HTTP/1 client"));
+    }
+    if method == "CONNECT" {
+        return Ok(Some(ProxyRequest {
+            target: target.to_string(),
+            direct: false,
+            connected: reply("200 Connection Established"),
+            refused: reply("502 Bad Gateway"),
+            denied: reply("403 Forbidden"),
+            request: Vec::new(),
+        }));
+    }
+
+    let (authority, path) = match target.strip_prefix("http://") {
+        Some(uri) => match uri.find('/') {
+            Some(i) => uri.split_at(i),
+            None => (uri, "/"),
+        },
+        None => {
+            // Only absolute URIs tell which host to connect to
+            tx.write_all(&reply("400 Bad Request")).await.map_err(io)?;
+            return Ok(None);
+        }
+    };
+    let target = match split_host_port(authority) {
+        (_, Some(_)) => authority.to_string(),
+        (_, None) => format!("{}:80", authority),
+    };
+
+    Ok(Some(ProxyRequest {
+        target,
+        direct: false,
+        connected: Vec::new(),
+        refused: reply("502 Bad Gateway"),
+        denied: reply("403 Forbidden"),
+        request: origin_request(&header, method, path, version),
+    }))
+}
+
+/// Rewrite the header of a request sent to a proxy for the origin server.
+fn origin_request(header: &str, method: &str, path: &str, version: &str) -> Vec<u8> {
+    let fields: Vec<&str> = header
+        .split("\r\n")
+        .skip(1)
+        .take_while(|l| !l.is_empty())
+        .collect();
+    let is_upgrade = fields.iter().any(|f| {
+        f.split_once(':').map_or(false, |(name, _)| {
+            name.trim().eq_ignore_ascii_case("upgrade")
+        })
+    });
+
+    let mut request = format!("{} {} {}\r\n", method, path, version);
+    for field in fields {
+        let name = field.split_once(':').map_or(field, |(name, _)| name).trim();
+        let hop_by_hop = HOP_BY_HOP.iter().any(|h| name.eq_ignore_ascii_case(h));
+        // An upgrade needs its `Connection: upgrade`
+        if !hop_by_hop || (is_upgrade && name.eq_ignore_ascii_case("connection")) {
+            request.push_str(field);
+            request.push_str("\r\n");
+        }
+    }
+    if !is_upgrade {
+        request.push_str("connection: close\r\n");
+    }
+    request.push_str("\r\n");
+    request.into_bytes()
+}
+
+/// Ask the HTTP proxy at the other end of `stream` for a tunnel to a
+/// `host:port` target.
+pub(super) async fn connect<S>(stream: &mut S, target: &str) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let io = |e: std::io::Error| proxy_error(format!("HTTP proxy handshake failed: {}", e));
+
+    let request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n\r\n", target);
+    stream.write_all(request.as_bytes()).await.map_err(io)?;
+    stream.flush().await.map_err(io)?;
+
+    // Anything after the headers belongs to the tunnel
+    let mut header = Vec::new();
+    while !header.ends_with(b"\r\n\r\n") {
+        if header.len() == MAX_HEADER_SIZE {
+            return Err(proxy_error("the HTTP proxy reply is too large"));
+        }
+        header.push(stream.read_u8().await.map_err(io)?);
+    }
+    let header = String::from_utf8_lossy(&header);
+    let status_line = header.lines().next().unwrap_or_default();
+    let mut status = status_line.split(' ');
+    match (status.next(), status.next()) {
+        (Some(version), Some(code)) if version.starts_with("HTTP/1.") && code.starts_with('2') => {
+            Ok(())
+        }
+        _ => Err(proxy_error(format!(
+            "the HTTP proxy failed to connect to {}: {}",
+            target, status_line
+        ))),
+    }
+}
+
+fn reply(status: &str) -> Vec<u8> {
+    format!("HTTP/1.1 {}\r\ncontent-length: 0\r\n\r\n", status).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::origin_request;
+
+    #[test]
+    fn rewrite_requests_for_the_origin_server() {
+        let header = "GET http://example.com/a?b HTTP/1.1\r\n\
+                      Host: example.com\r\n\
+                      Proxy-Connection: keep-alive\r\n\
+                      Connection: keep-alive\r\n\
+                      Transfer-Encoding: chunked\r\n\r\n";
+        let request = origin_request(header, "GET", "/a?b", "HTTP/1.1");
+        assert_eq!(
+            String::from_utf8(request).unwrap(),
+            "GET /a?b HTTP/1.1\r\n\
+             Host: example.com\r\n\
+             Transfer-Encoding: chunked\r\n\
+             connection: close\r\n\r\n"
+        );
+
+        let header = "GET http://example.com/ws HTTP/1.1\r\n\
+                      Host: example.com\r\n\
+                      Connection: Upgrade\r\n\
+                      Upgrade: websocket\r\n\r\n";
+        let request = origin_request(header, "GET", "/ws", "HTTP/1.1");
+        assert_eq!(
+            String::from_utf8(request).unwrap(),
+            "GET /ws HTTP/1.1\r\n\
+             Host: example.com\r\n\
+             Connection: Upgrade\r\n\
+             Upgrade: websocket\r\n\r\n"
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! Proxy portals.
+//!
+//! A proxy inlet speaks a proxy protocol with its clients, e.g. SOCKS5,
+//! and asks a proxy outlet to connect to the target each client requested.
+//! The outlet only connects to the targets allowed by its [`EgressPolicy`].
+//! With [`ProxyRoutes`], the inlet sends the connections to some targets
+//! to outlets dedicated to them instead.
+//!
+//! The other way around, a transport connects to its peers through an
+//! [`UpstreamProxy`] speaking one of these protocols.
+
+mod egress;
+mod http;
+mod socks;
+mod upstream;
+
+pub use egress::EgressPolicy;
+pub(crate) use upstream::SharedProxy;
+pub use upstream::{UpstreamProxy, NO_PROXY_ENV, PROXY_ENV};
+
+use crate::{PortalReader, PortalWriter, ProxyRequest};
+use core::time::Duration;
+use egress::Rule;
+use ockam_core::{Result, Route};
+use tokio::time::timeout;
+
+/// Time a client has to request a target.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The protocol a proxy inlet speaks with its clients
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProxyProtocol {
+    /// SOCKS5, with the `CONNECT` command and no authentication
+    Socks5,
+    /// HTTP `CONNECT` tunnels, as used by clients configured with an
+    /// `https_proxy` variable, and plain requests of clients configured
+    /// with an `http_proxy` variable
+    HttpConnect,
+}
+
+impl ProxyProtocol {
+    /// Read the target a new client wants to connect to.
+    ///
+    /// Returns `None` if the client was already told that its request is
+    /// not supported.
+    pub(crate) async fn handshake(
+        &self,
+        rx: &mut PortalReader,
+        tx: &mut PortalWriter,
+    ) -> Result<Option<ProxyRequest>> {
+        let handshake: Handshake<'_> = match self {
+            ProxyProtocol::Socks5 => Box::pin(socks::handshake(rx, tx)),
+            ProxyProtocol::HttpConnect => Box::pin(http::handshake(rx, tx)),
+        };
+        timeout(HANDSHAKE_TIMEOUT, handshake)
+            .await
+            .map_err(|_| proxy_error("proxy handshake timed out"))?
+    }
+}
+
+type Handshake<'a> = core::pin::Pin<
+    Box<dyn core::future::Future<Output = Result<Option<ProxyRequest>>> + Send + 'a>,
+>;
+
+/// Routes of a proxy inlet to the outlets dedicated to some targets
+///
+/// Each route is used for the targets matched by its rule, which has the
+/// syntax of the rules of an [`EgressPolicy`].  The first matching route
+/// is used, and its outlet connects to its own target.
+#[derive(Clone, Debug, Default)]
+pub struct ProxyRoutes {
+    routes: Vec<(Rule, Route)>,
+}
+
+impl ProxyRoutes {
+    /// Constructor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send the connections to the targets matched by `rule` to the outlet
+    /// at `route`
+    pub fn add(mut self, rule: &str, route: Route) -> Result<Self> {
+        self.routes.push((rule.parse()?, route));
+        Ok(self)
+    }
+
+    /// Whether there are no routes
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    /// The route to the outlet dedicated to a `host:port` target, if any
+    pub(crate) fn get(&self, target: &str) -> Option<Route> {
+        self.routes
+            .iter()
+            .find(|(rule, _)| rule.matches_target(target))
+            .map(|(_, route)| route.clone())
+    }
+}
+
+/// Split a `host:port` target, where an IPv6 host is within brackets.
+///
+/// The port is `None` if there is none, or if the host is an IPv6
+/// address without brackets.
+pub(crate) fn split_host_port(target: &str) -> (&str, Option<&str>) {
+    if let Some(rest) = target.strip_prefix('[') {
+        if let Some((host, port)) = rest.split_once(']') {
+            return (host, port.strip_prefix(':'));
+        }
+    }
+    match target.split_once(':') {
+        Some((host, port)) if !port.contains(':') => (host, Some(port)),
+        _ => (target, None),
+    }
+}
+
+fn proxy_error(msg: impl Into<String>) -> ockam_core::Error {
+    use ockam_core::errcode::{Kind, Origin};
+    ockam_core::Error::new(Origin::Transport, Kind::Protocol, msg.into())
+}
@@ -0,0 +1,161 @@
+//! The server side of SOCKS5 (RFC 1928), and the client side to connect
+//! through an upstream proxy.
+
+use super::proxy_error;
+use crate::{PortalReader, PortalWriter, ProxyRequest};
+use ockam_core::Result;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+const VERSION: u8 = 5;
+const NO_AUTHENTICATION: u8 = 0;
+const NO_ACCEPTABLE_METHODS: u8 = 0xff;
+const CONNECT: u8 = 1;
+const ATYP_IPV4: u8 = 1;
+const ATYP_DOMAIN: u8 = 3;
+const ATYP_IPV6: u8 = 4;
+const SUCCEEDED: u8 = 0;
+const NOT_ALLOWED: u8 = 2;
+const CONNECTION_REFUSED: u8 = 5;
+const COMMAND_NOT_SUPPORTED: u8 = 7;
+const ADDRESS_TYPE_NOT_SUPPORTED: u8 = 8;
+
+/// Negotiate the method and read the `CONNECT` request of a client.
+pub(super) async fn handshake(
+    rx: &mut PortalReader,
+    tx: &mut PortalWriter,
+) -> Result<Option<ProxyRequest>> {
+    let io = |e: std::io::Error| proxy_error(format!("SOCKS5 handshake failed: {}", e));
+
+    let mut header = [0u8; 2];
+    rx.read_exact(&mut header).await.map_err(io)?;
+    if header[0] != VERSION {
+        return Err(proxy_error("not a SOCKS5 client"));
+    }
+    let mut methods = vec![0u8; header[1] as usize];
+    rx.read_exact(&mut methods).await.map_err(io)?;
+    if !methods.contains(&NO_AUTHENTICATION) {
+        tx.write_all(&[VERSION, NO_ACCEPTABLE_METHODS])
+            .await
+            .map_err(io)?;
+        return Ok(None);
+    }
+    tx.write_all(&[VERSION, NO_AUTHENTICATION])
+        .await
+        .map_err(io)?;
+    tx.flush().await.map_err(io)?;
+
+    let mut request = [0u8; 4];
+    rx.read_exact(&mut request).await.map_err(io)?;
+    let [version, command, _, atyp] = request;
+    if version != VERSION {
+        return Err(proxy_error("not a SOCKS5 request"));
+    }
+    let host = match atyp {
+        ATYP_IPV4 => {
+            let mut ip = [0u8; 4];
+            rx.read_exact(&mut ip).await.map_err(io)?;
+            Ipv4Addr::from(ip).to_string()
+        }
+        ATYP_IPV6 => {
+            let mut ip = [0u8; 16];
+            rx.read_exact(&mut ip).await.map_err(io)?;
+            format!("[{}]", Ipv6Addr::from(ip))
+        }
+        ATYP_DOMAIN => {
+            let len = rx.read_u8().await.map_err(io)?;
+            let mut name = vec![0u8; len as usize];
+            rx.read_exact(&mut name).await.map_err(io)?;
+            String::from_utf8(name).map_err(|_| proxy_error("invalid SOCKS5 domain name"))?
+        }
+        _ => {
+            tx.write_all(&reply(ADDRESS_TYPE_NOT_SUPPORTED))
+                .await
+                .map_err(io)?;
+            return Ok(None);
+        }
+    };
+    let port = rx.read_u16().await.map_err(io)?;
+    if command != CONNECT {
+        tx.write_all(&reply(COMMAND_NOT_SUPPORTED))
+            .await
+            .map_err(io)?;
+        return Ok(None);
+    }
+
+    Ok(Some(ProxyRequest {
+        target: format!("{}:{}", host, port),
+        direct: false,
+        connected: reply(SUCCEEDED),
+        refused: reply(CONNECTION_REFUSED),
+        denied: reply(NOT_ALLOWED),
+        request: Vec::new(),
+    }))
+}
+
+/// A reply with an unspecified bound address, since the connection to
+/// the target is made by the outlet.
+fn reply(status: u8) -> Vec<u8> {
+    vec![VERSION, status, 0, ATYP_IPV4, 0, 0, 0, 0, 0, 0]
+}
+
+/// Ask the SOCKS5 proxy at the other end of `stream` to connect to `host`
+/// on `port`.
+pub(super) async fn connect<S>(stream: &mut S, host: &str, port: u16) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let io = |e: std::io::Error| proxy_error(format!("SOCKS5 handshake failed: {}", e));
+
+    stream
+        .write_all(&[VERSION, 1, NO_AUTHENTICATION])
+        .await
+        .map_err(io)?;
+    let mut method = [0u8; 2];
+    stream.read_exact(&mut method).await.map_err(io)?;
+    if method != [VERSION, NO_AUTHENTICATION] {
+        return Err(proxy_error("the SOCKS5 proxy requires an authentication"));
+    }
+
+    let mut request = vec![VERSION, CONNECT, 0];
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            request.push(ATYP_IPV4);
+            request.extend_from_slice(&ip.octets())
+        }
+        Ok(IpAddr::V6(ip)) => {
+            request.push(ATYP_IPV6);
+            request.extend_from_slice(&ip.octets())
+        }
+        Err(_) if host.len() <= u8::MAX as usize => {
+            request.extend_from_slice(&[ATYP_DOMAIN, host.len() as u8]);
+            request.extend_from_slice(host.as_bytes())
+        }
+        Err(_) => return Err(proxy_error(format!("host name too long: {}", host))),
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await.map_err(io)?;
+
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await.map_err(io)?;
+    let [version, status, _, atyp] = reply;
+    if version != VERSION {
+        return Err(proxy_error("not a SOCKS5 proxy"));
+    }
+    if status != SUCCEEDED {
+        return Err(proxy_error(format!(
+            "the SOCKS5 proxy failed to connect to {}:{}, with the reply {}",
+            host, port, status
+        )));
+    }
+    // Skip the bound address and port
+    let len = match atyp {
+        ATYP_IPV4 => 4 + 2,
+        ATYP_IPV6 => 16 + 2,
+        ATYP_DOMAIN => stream.read_u8().await.map_err(io)? as usize + 2,
+        _ => return Err(proxy_error("invalid SOCKS5 reply")),
+    };
+    let mut bound = vec![0u8; len];
+    stream.read_exact(&mut bound).await.map_err(io)?;
+    Ok(())
+}
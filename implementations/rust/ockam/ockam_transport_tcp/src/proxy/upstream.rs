@@ -0,0 +1,207 @@
+//! Outgoing connections through an upstream proxy.
+
+use super::{http, socks};
+use super::{proxy_error, split_host_port, EgressPolicy, ProxyProtocol, HANDSHAKE_TIMEOUT};
+use core::fmt;
+use core::str::FromStr;
+use ockam_core::Result;
+use ockam_transport_core::TransportError;
+use std::sync::{Arc, RwLock};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+/// The variable giving the URL of the upstream proxy of TCP transports
+pub const PROXY_ENV: &str = "OCKAM_TCP_PROXY";
+
+/// The variable giving the targets which TCP transports connect to without
+/// their upstream proxy, as a comma separated list of rules
+pub const NO_PROXY_ENV: &str = "OCKAM_TCP_NO_PROXY";
+
+/// Port of a proxy whose URL has none, as curl does
+const DEFAULT_PORT: u16 = 1080;
+
+/// The upstream proxy of a router, shared with its handles
+pub(crate) type SharedProxy = Arc<RwLock<Option<Arc<UpstreamProxy>>>>;
+
+/// A proxy which a TCP transport connects to its peers through, e.g. on a
+/// network which only reaches the internet through a corporate proxy
+///
+/// A proxy is given by its URL, `socks5://host:port` for a SOCKS5 proxy or
+/// `http://host:port` for an HTTP proxy supporting `CONNECT` tunnels. The
+/// loopback addresses, where local nodes listen, and the targets matched by
+/// its bypass rules, which have the syntax of the rules of an
+/// [`EgressPolicy`], are connected to directly.
+///
+/// ```rust
+/// use ockam_transport_tcp::{EgressPolicy, UpstreamProxy};
+/// # fn main() -> ockam_core::Result<()> {
+/// let proxy: UpstreamProxy = "http://proxy.example.com:3128".parse()?;
+/// let bypass = EgressPolicy::parse(&["localhost", "*.internal.example.com"])?;
+/// let proxy = proxy.with_bypass(bypass);
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpstreamProxy {
+    protocol: ProxyProtocol,
+    addr: String,
+    loopback: EgressPolicy,
+    bypass: EgressPolicy,
+}
+
+impl UpstreamProxy {
+    /// Connect through the proxy at a `host:port` address, which speaks
+    /// `protocol`
+    pub fn new(protocol: ProxyProtocol, addr: impl Into<String>) -> Self {
+        Self {
+            protocol,
+            addr: addr.into(),
+            loopback: EgressPolicy::parse(&["localhost", "127.0.0.1", "[::1]"])
+                .expect("the loopback rules are valid"),
+            bypass: EgressPolicy::default(),
+        }
+    }
+
+    /// Connect directly to the targets matched by `bypass`, as well as to the
+    /// loopback addresses
+    pub fn with_bypass(mut self, bypass: EgressPolicy) -> Self {
+        self.bypass = bypass;
+        self
+    }
+
+    /// The proxy given by the `OCKAM_TCP_PROXY` variable, if any, with the
+    /// bypass rules given by the `OCKAM_TCP_NO_PROXY` variable
+    pub fn from_env() -> Result<Option<Self>> {
+        let url = match std::env::var(PROXY_ENV) {
+            Ok(url) if !url.trim().is_empty() => url,
+            _ => return Ok(None),
+        };
+        let mut proxy: Self = url.trim().parse()?;
+        if let Ok(rules) = std::env::var(NO_PROXY_ENV) {
+            let rules: Vec<&str> = rules
+                .split(',')
+                .map(str::trim)
+                .filter(|r| !r.is_empty())
+                .collect();
+            proxy = proxy.with_bypass(EgressPolicy::parse(&rules)?);
+        }
+        Ok(Some(proxy))
+    }
+
+    /// The protocol the proxy speaks
+    pub fn protocol(&self) -> ProxyProtocol {
+        self.protocol
+    }
+
+    /// The `host:port` address of the proxy
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    /// Whether connections to a `host:port` target go through the proxy
+    pub fn is_used_for(&self, target: &str) -> bool {
+        !self.loopback.allows_target(target) && !self.bypass.allows_target(target)
+    }
+
+    /// Connect to a `host:port` target through the proxy
+    pub(crate) async fn connect(&self, target: &str) -> Result<TcpStream> {
+        let (host, port) = match split_host_port(target) {
+            (host, Some(port)) => match port.parse::<u16>() {
+                Ok(port) => (host, port),
+                Err(_) => return Err(TransportError::InvalidAddress.into()),
+            },
+            _ => return Err(TransportError::InvalidAddress.into()),
+        };
+        let mut stream = TcpStream::connect(self.addr.as_str())
+            .await
+            .map_err(TransportError::from)?;
+        let handshake = async {
+            match self.protocol {
+                ProxyProtocol::Socks5 => socks::connect(&mut stream, host, port).await,
+                ProxyProtocol::HttpConnect => http::connect(&mut stream, target).await,
+            }
+        };
+        timeout(HANDSHAKE_TIMEOUT, handshake)
+            .await
+            .map_err(|_| proxy_error("proxy handshake timed out"))??;
+        Ok(stream)
+    }
+}
+
+impl FromStr for UpstreamProxy {
+    type Err = ockam_core::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || proxy_error(format!("invalid proxy URL: {}", s));
+        let (protocol, addr) = if let Some(addr) = s.strip_prefix("socks5://") {
+            (ProxyProtocol::Socks5, addr)
+        } else if let Some(addr) = s.strip_prefix("socks5h://") {
+            (ProxyProtocol::Socks5, addr)
+        } else if let Some(addr) = s.strip_prefix("http://") {
+            (ProxyProtocol::HttpConnect, addr)
+        } else {
+            return Err(invalid());
+        };
+        let addr = addr.trim_end_matches('/');
+        let addr = match split_host_port(addr) {
+            ("", _) => return Err(invalid()),
+            (_, Some(port)) if port.parse::<u16>().is_ok() => addr.to_string(),
+            (host, None) if !host.contains(':') || addr.starts_with('[') => {
+                format!("{}:{}", addr, DEFAULT_PORT)
+            }
+            _ => return Err(invalid()),
+        };
+        Ok(Self::new(protocol, addr))
+    }
+}
+
+impl fmt::Display for UpstreamProxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.protocol {
+            ProxyProtocol::Socks5 => write!(f, "socks5://{}", self.addr),
+            ProxyProtocol::HttpConnect => write!(f, "http://{}", self.addr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proxies_are_parsed_from_urls() {
+        let proxy: UpstreamProxy = "socks5://proxy.example.com:1081".parse().unwrap();
+        assert_eq!(proxy.protocol(), ProxyProtocol::Socks5);
+        assert_eq!(proxy.addr(), "proxy.example.com:1081");
+        assert_eq!(proxy.to_string(), "socks5://proxy.example.com:1081");
+
+        let proxy: UpstreamProxy = "http://10.0.0.1/".parse().unwrap();
+        assert_eq!(proxy.protocol(), ProxyProtocol::HttpConnect);
+        assert_eq!(proxy.addr(), "10.0.0.1:1080");
+
+        let proxy: UpstreamProxy = "socks5h://[::1]".parse().unwrap();
+        assert_eq!(proxy.addr(), "[::1]:1080");
+
+        assert!("proxy.example.com:3128".parse::<UpstreamProxy>().is_err());
+        assert!("https://proxy.example.com"
+            .parse::<UpstreamProxy>()
+            .is_err());
+        assert!("http://proxy.example.com:http"
+            .parse::<UpstreamProxy>()
+            .is_err());
+    }
+
+    #[test]
+    fn loopback_targets_always_bypass_the_proxy() {
+        let proxy: UpstreamProxy = "http://proxy.example.com:3128".parse().unwrap();
+        assert!(proxy.is_used_for("node.example.com:4000"));
+        assert!(proxy.is_used_for("10.0.0.1:4000"));
+        assert!(!proxy.is_used_for("127.0.0.1:4000"));
+        assert!(!proxy.is_used_for("localhost:4000"));
+
+        let proxy = proxy.with_bypass(EgressPolicy::parse(&["*.example.com"]).unwrap());
+        assert!(!proxy.is_used_for("node.example.com:4000"));
+        assert!(proxy.is_used_for("10.0.0.1:4000"));
+        assert!(!proxy.is_used_for("127.0.0.1:4000"));
+        assert!(!proxy.is_used_for("localhost:4000"));
+    }
+}
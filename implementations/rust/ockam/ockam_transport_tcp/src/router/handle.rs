@@ -1,10 +1,13 @@
+use crate::proxy::SharedProxy;
+use crate::socket::PeerOptions;
 use crate::{
-    parse_socket_addr, TcpInletListenProcessor, TcpListenProcessor, TcpRouterRequest,
-    TcpRouterResponse, WorkerPair, TCP,
+    parse_socket_addr, InletOptions, ListenerOptions, Listeners, TcpConnectionOptions,
+    TcpInletListenProcessor, TcpListenProcessor, TcpRouterRequest, TcpRouterResponse,
+    UpstreamProxy, WorkerPair, TCP,
 };
 use ockam_core::compat::net::{SocketAddr, ToSocketAddrs};
-use ockam_core::{async_trait, compat::boxed::Box, AccessControl};
-use ockam_core::{Address, AsyncTryClone, Result, Route};
+use ockam_core::{async_trait, compat::boxed::Box};
+use ockam_core::{Address, AsyncTryClone, Result};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
 use std::sync::Arc;
@@ -15,20 +18,64 @@ use std::sync::Arc;
 pub(crate) struct TcpRouterHandle {
     ctx: Context,
     api_addr: Address,
+    proxy: SharedProxy,
+    peer_options: PeerOptions,
+    listeners: Listeners,
+    #[cfg(feature = "tls")]
+    tls_peers: crate::tls::TlsPeers,
 }
 
 #[async_trait]
 impl AsyncTryClone for TcpRouterHandle {
     async fn async_try_clone(&self) -> Result<Self> {
         let child_ctx = self.ctx.new_detached(Address::random_local()).await?;
-        Ok(Self::new(child_ctx, self.api_addr.clone()))
+        let handle = Self::new(child_ctx, self.api_addr.clone())
+            .with_proxy(self.proxy.clone())
+            .with_peer_options(self.peer_options.clone())
+            .with_listeners(self.listeners.clone());
+        #[cfg(feature = "tls")]
+        let handle = handle.with_tls_peers(self.tls_peers.clone());
+        Ok(handle)
     }
 }
 
 impl TcpRouterHandle {
     /// Create a new `TcpRouterHandle` with the given address
     pub(crate) fn new(ctx: Context, api_addr: Address) -> Self {
-        TcpRouterHandle { ctx, api_addr }
+        TcpRouterHandle {
+            ctx,
+            api_addr,
+            proxy: Default::default(),
+            peer_options: Default::default(),
+            listeners: Default::default(),
+            #[cfg(feature = "tls")]
+            tls_peers: Default::default(),
+        }
+    }
+
+    /// Share the upstream proxy of a router
+    pub(crate) fn with_proxy(mut self, proxy: SharedProxy) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Share the connection options of the peers of a router
+    pub(crate) fn with_peer_options(mut self, peer_options: PeerOptions) -> Self {
+        self.peer_options = peer_options;
+        self
+    }
+
+    /// Share the listeners of a router
+    pub(crate) fn with_listeners(mut self, listeners: Listeners) -> Self {
+        self.listeners = listeners;
+        self
+    }
+
+    /// Share the TLS configurations of the peers of a router
+    #[cfg(feature = "tls")]
+    pub(crate) fn with_tls_peers(mut self, tls_peers: crate::tls::TlsPeers) -> Self {
+        self.tls_peers = tls_peers;
+        self
     }
 
     /// Return a reference to the router handle's [`Context`]
@@ -40,12 +87,108 @@ impl TcpRouterHandle {
 impl TcpRouterHandle {
     /// Bind an incoming connection listener for this router
     pub async fn bind(&self, addr: impl Into<SocketAddr>) -> Result<SocketAddr> {
+        self.bind_with_options(addr, &ListenerOptions::default())
+            .await
+    }
+
+    /// Bind an incoming connection listener for this router, on a socket
+    /// with the given options
+    pub async fn bind_with_options(
+        &self,
+        addr: impl Into<SocketAddr>,
+        options: &ListenerOptions,
+    ) -> Result<SocketAddr> {
         let socket_addr = addr.into();
-        TcpListenProcessor::start(&self.ctx, self.async_try_clone().await?, socket_addr).await
+        let handle = self.async_try_clone().await?;
+        let (socket_addr, listener) =
+            TcpListenProcessor::start(&self.ctx, handle, socket_addr, options).await?;
+        self.listeners
+            .lock()
+            .unwrap()
+            .entry(socket_addr)
+            .or_default()
+            .push(listener);
+        Ok(socket_addr)
+    }
+
+    /// Connect to peers through `proxy`, or directly without one
+    ///
+    /// Only the connections opened afterwards are affected.
+    pub fn set_proxy(&self, proxy: Option<UpstreamProxy>) {
+        *self.proxy.write().unwrap() = proxy.map(Arc::new);
+    }
+
+    /// The upstream proxy peers are connected to through, if any
+    pub fn proxy(&self) -> Option<UpstreamProxy> {
+        self.proxy.read().unwrap().as_deref().cloned()
+    }
+
+    /// Stop the incoming connection listeners bound to `addr`
+    ///
+    /// Returns once their sockets are closed, so that the address can be
+    /// bound again.
+    pub async fn unbind(&self, addr: SocketAddr) -> Result<()> {
+        let listeners = self.listeners.lock().unwrap().remove(&addr);
+        let listeners = listeners.ok_or(TransportError::InvalidAddress)?;
+        for mut listener in listeners {
+            self.ctx.stop_processor(listener.address).await?;
+            // The processors are only dropped after they are told to stop
+            listener.closed.recv().await;
+        }
+        Ok(())
     }
 
     /// Establish an outgoing TCP connection on an existing transport
     pub async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<Address> {
+        if let Ok((peer_addr, _)) = Self::resolve_peer(peer.as_ref()) {
+            self.peer_options.lock().unwrap().remove(&peer_addr);
+            #[cfg(feature = "tls")]
+            self.tls_peers.lock().unwrap().remove(&peer_addr);
+        }
+        self.send_connect(peer).await
+    }
+
+    /// Establish an outgoing TCP connection with the given options on an
+    /// existing transport
+    ///
+    /// Connections opened again to the same peer, e.g. when a message is
+    /// routed to it after a disconnection, have these options too.
+    pub async fn connect_with_options<S: AsRef<str>>(
+        &self,
+        peer: S,
+        options: TcpConnectionOptions,
+    ) -> Result<Address> {
+        let (peer_addr, _) = Self::resolve_peer(peer.as_ref())?;
+        #[cfg(feature = "tls")]
+        self.tls_peers.lock().unwrap().remove(&peer_addr);
+        self.peer_options
+            .lock()
+            .unwrap()
+            .insert(peer_addr, Arc::new(options));
+        self.send_connect(peer).await
+    }
+
+    /// Establish an outgoing TCP connection wrapped in TLS on an existing
+    /// transport
+    ///
+    /// Connections opened again to the same peer, e.g. when a message is
+    /// routed to it after a disconnection, use TLS too.
+    #[cfg(feature = "tls")]
+    pub async fn connect_with_tls<S: AsRef<str>>(
+        &self,
+        peer: S,
+        tls: crate::tls::OutletTls,
+    ) -> Result<Address> {
+        let (peer_addr, _) = Self::resolve_peer(peer.as_ref())?;
+        self.peer_options.lock().unwrap().remove(&peer_addr);
+        self.tls_peers
+            .lock()
+            .unwrap()
+            .insert(peer_addr, Arc::new(tls));
+        self.send_connect(peer).await
+    }
+
+    async fn send_connect<S: AsRef<str>>(&self, peer: S) -> Result<Address> {
         let response = self
             .ctx
             .send_and_receive(
@@ -166,18 +309,10 @@ impl TcpRouterHandle {
     /// Bind an incoming portal inlet connection listener for this router
     pub async fn bind_inlet(
         &self,
-        outlet_listener_route: impl Into<Route>,
-        addr: impl Into<SocketAddr>,
-        access_control: Arc<dyn AccessControl>,
+        addr: SocketAddr,
+        options: InletOptions,
     ) -> Result<(Address, SocketAddr)> {
-        let socket_addr = addr.into();
-        TcpInletListenProcessor::start(
-            &self.ctx,
-            outlet_listener_route.into(),
-            socket_addr,
-            access_control,
-        )
-        .await
+        TcpInletListenProcessor::start(&self.ctx, addr, options).await
     }
 
     /// Stop the inlet's [`TcpInletListenProcessor`]
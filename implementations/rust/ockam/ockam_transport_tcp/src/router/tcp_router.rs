@@ -1,11 +1,17 @@
-use crate::{TcpRouterHandle, TcpRouterRequest, TcpRouterResponse, TcpSendWorker, TCP};
+use crate::proxy::SharedProxy;
+use crate::socket::PeerOptions;
+use crate::{
+    Listeners, TcpRouterHandle, TcpRouterRequest, TcpRouterResponse, TcpSendWorker, UpstreamProxy,
+    PROXY_ENV, TCP,
+};
 use core::ops::Deref;
 use ockam_core::{async_trait, Any};
 use ockam_core::{Address, Decodable, LocalMessage, Result, Routed, Worker};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
 use std::collections::BTreeMap;
-use tracing::{debug, error, trace};
+use std::sync::{Arc, RwLock};
+use tracing::{debug, error, trace, warn};
 
 /// A TCP address router and connection listener
 ///
@@ -21,6 +27,22 @@ pub(crate) struct TcpRouter {
     api_addr: Address,
     map: BTreeMap<Address, Address>,
     allow_auto_connection: bool,
+    proxy: SharedProxy,
+    peer_options: PeerOptions,
+    listeners: Listeners,
+    #[cfg(feature = "tls")]
+    tls_peers: crate::tls::TlsPeers,
+}
+
+/// The upstream proxy given by the environment, if any
+fn proxy_from_env() -> Option<Arc<UpstreamProxy>> {
+    match UpstreamProxy::from_env() {
+        Ok(proxy) => proxy.map(Arc::new),
+        Err(e) => {
+            warn!(err = %e, "Ignoring the upstream proxy given by {}", PROXY_ENV);
+            None
+        }
+    }
 }
 
 impl TcpRouter {
@@ -38,6 +60,11 @@ impl TcpRouter {
             api_addr: api_addr.clone(),
             map: BTreeMap::new(),
             allow_auto_connection: true,
+            proxy: Arc::new(RwLock::new(proxy_from_env())),
+            peer_options: Default::default(),
+            listeners: Default::default(),
+            #[cfg(feature = "tls")]
+            tls_peers: Default::default(),
         };
 
         let handle = router.create_self_handle().await?;
@@ -53,7 +80,12 @@ impl TcpRouter {
     /// Create a new `TcpRouterHandle` representing this router
     async fn create_self_handle(&self) -> Result<TcpRouterHandle> {
         let handle_ctx = self.ctx.new_detached(Address::random_local()).await?;
-        let handle = TcpRouterHandle::new(handle_ctx, self.api_addr.clone());
+        let handle = TcpRouterHandle::new(handle_ctx, self.api_addr.clone())
+            .with_proxy(self.proxy.clone())
+            .with_peer_options(self.peer_options.clone())
+            .with_listeners(self.listeners.clone());
+        #[cfg(feature = "tls")]
+        let handle = handle.with_tls_peers(self.tls_peers.clone());
         Ok(handle)
     }
 }
@@ -111,9 +143,17 @@ impl TcpRouter {
         // Start a new `WorkerPair` for the given peer containing a
         // `TcpSendWorker` and `TcpRecvprocessor`
         let router_handle = self.create_self_handle().await?;
-        let pair =
-            TcpSendWorker::start_pair(&self.ctx, router_handle, None, peer_addr, hostnames.clone())
+        let (worker, pair) =
+            TcpSendWorker::new_pair(&self.ctx, router_handle, None, peer_addr, hostnames.clone())
                 .await?;
+        let worker = worker
+            .with_proxy(self.proxy.read().unwrap().clone())
+            .with_options(self.peer_options.lock().unwrap().get(&peer_addr).cloned());
+        #[cfg(feature = "tls")]
+        let worker = worker.with_tls(self.tls_peers.lock().unwrap().get(&peer_addr).cloned());
+        self.ctx
+            .start_worker(vec![pair.tx_addr(), worker.internal_addr().clone()], worker)
+            .await?;
 
         // Send this `TcpRouter` a `TcpRouterRequest::Register` message
         // containing the registration request
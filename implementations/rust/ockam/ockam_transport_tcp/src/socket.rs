@@ -0,0 +1,411 @@
+use core::time::Duration;
+use listenfd::ListenFd;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::net::SocketAddr;
+use ockam_core::compat::string::String;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use ockam_transport_core::TransportError;
+use once_cell::sync::Lazy;
+use socket2::{Domain, Protocol, SockRef, Socket, TcpKeepalive, Type};
+use std::sync::Mutex;
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, warn};
+
+/// The listening sockets passed by the service manager, e.g. with systemd
+/// socket activation, which are not used yet
+static ACTIVATED: Lazy<Mutex<Vec<std::net::TcpListener>>> = Lazy::new(|| Mutex::new(activated()));
+
+/// Options of the sockets TCP listeners and inlets listen on
+///
+/// ```rust
+/// use ockam_transport_tcp::ListenerOptions;
+/// // Share the port with the listeners of other nodes, the kernel
+/// // spreads the connections between them
+/// let options = ListenerOptions::new().with_reuse_port(true).with_backlog(4096);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ListenerOptions {
+    interface: Option<String>,
+    only_v6: Option<bool>,
+    reuse_port: bool,
+    backlog: u32,
+    socket_activation: bool,
+    accept_workers: usize,
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<Arc<crate::tls::InletTls>>,
+}
+
+impl Default for ListenerOptions {
+    fn default() -> Self {
+        Self {
+            interface: None,
+            only_v6: None,
+            reuse_port: false,
+            backlog: 1024,
+            socket_activation: false,
+            accept_workers: 1,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+}
+
+impl ListenerOptions {
+    /// The options of [`TcpListener::bind`](tokio::net::TcpListener::bind)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only accept connections received on the network interface with
+    /// this name, e.g. `eth1` of a multi-homed gateway
+    ///
+    /// This is only supported on Linux and Android.
+    pub fn with_interface(mut self, interface: impl Into<String>) -> Self {
+        self.interface = Some(interface.into());
+        self
+    }
+
+    /// Whether a listener on an IPv6 address only accepts IPv6
+    /// connections, or IPv4 ones too, instead of the system default
+    pub fn with_only_v6(mut self, only_v6: bool) -> Self {
+        self.only_v6 = Some(only_v6);
+        self
+    }
+
+    /// Let other sockets listen on the same address, e.g. the listeners of
+    /// several nodes behind one port
+    ///
+    /// This is only supported on Unix.
+    pub fn with_reuse_port(mut self, reuse_port: bool) -> Self {
+        self.reuse_port = reuse_port;
+        self
+    }
+
+    /// The maximum number of connections waiting to be accepted
+    pub fn with_backlog(mut self, backlog: u32) -> Self {
+        self.backlog = backlog;
+        self
+    }
+
+    /// Listen on the socket passed by the service manager for the address,
+    /// if any, instead of creating one
+    ///
+    /// With systemd socket activation (`LISTEN_FDS`), the node doesn't need
+    /// to run as root to listen on a privileged port. The address matches
+    /// a socket listening on the same IP address, or on any IP address if
+    /// it is unspecified (e.g. `0.0.0.0`), and on the same port, or on any
+    /// port if it is 0. Each socket is used once, with the options of its
+    /// `.socket` unit rather than these ones. Without a matching socket, a
+    /// new one is created.
+    pub fn with_socket_activation(mut self, socket_activation: bool) -> Self {
+        self.socket_activation = socket_activation;
+        self
+    }
+
+    /// Accept connections with `n` processors, each listening on its own
+    /// socket bound to the address with `SO_REUSEPORT`, e.g. on a busy
+    /// inlet node
+    ///
+    /// The kernel spreads the connections between the sockets, and the
+    /// processors, which are tasks of their own, accept them and run their
+    /// handshakes on any thread of the runtime. So do the processors
+    /// receiving on the connections. Sockets passed by the service manager
+    /// are not used by several processors. This is only supported on Unix.
+    pub fn with_accept_workers(mut self, n: usize) -> Self {
+        self.accept_workers = n.max(1);
+        self
+    }
+
+    /// Accept TLS connections only, e.g. from nodes connecting with
+    /// [`TcpTransport::connect_with_tls`](crate::TcpTransport::connect_with_tls)
+    ///
+    /// This only applies to transport listeners, inlets are configured with
+    /// [`InletOptions::with_tls`](crate::InletOptions::with_tls).
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, tls: crate::tls::InletTls) -> Self {
+        self.tls = Some(Arc::new(tls));
+        self
+    }
+
+    /// Create the sockets of the accept workers and listen on `addr`
+    pub(crate) fn bind_all(&self, addr: SocketAddr) -> Result<Vec<TcpListener>> {
+        if self.accept_workers <= 1 {
+            return Ok(vec![self.bind(addr)?]);
+        }
+        let options = Self {
+            reuse_port: true,
+            socket_activation: false,
+            ..self.clone()
+        };
+        let first = options.bind(addr)?;
+        // Bind the other sockets to the port picked for the first one
+        let addr = first.local_addr().map_err(TransportError::from)?;
+        let mut listeners = vec![first];
+        for _ in 1..self.accept_workers {
+            listeners.push(options.bind(addr)?);
+        }
+        Ok(listeners)
+    }
+
+    /// Create a socket with these options and listen on `addr`
+    pub(crate) fn bind(&self, addr: SocketAddr) -> Result<TcpListener> {
+        if self.socket_activation {
+            if let Some(listener) = take_activated(addr) {
+                debug!(%addr, "Listening on a socket passed by the service manager");
+                listener
+                    .set_nonblocking(true)
+                    .map_err(TransportError::from)?;
+                let listener = TcpListener::from_std(listener).map_err(TransportError::from)?;
+                return Ok(listener);
+            }
+        }
+        let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))
+            .map_err(TransportError::from)?;
+        #[cfg(unix)]
+        socket
+            .set_reuse_address(true)
+            .map_err(TransportError::from)?;
+        if self.reuse_port {
+            #[cfg(unix)]
+            socket.set_reuse_port(true).map_err(TransportError::from)?;
+            #[cfg(not(unix))]
+            return Err(unsupported("SO_REUSEPORT"));
+        }
+        if let (Some(only_v6), true) = (self.only_v6, addr.is_ipv6()) {
+            socket.set_only_v6(only_v6).map_err(TransportError::from)?;
+        }
+        if let Some(interface) = &self.interface {
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            socket
+                .bind_device(Some(interface.as_bytes()))
+                .map_err(TransportError::from)?;
+            #[cfg(not(any(target_os = "android", target_os = "linux")))]
+            return Err(unsupported(&format!(
+                "binding to the interface {}",
+                interface
+            )));
+        }
+        socket.set_nonblocking(true).map_err(TransportError::from)?;
+        socket.bind(&addr.into()).map_err(TransportError::from)?;
+        let backlog = self.backlog.min(i32::MAX as u32) as i32;
+        socket.listen(backlog).map_err(TransportError::from)?;
+        let listener = TcpListener::from_std(socket.into()).map_err(TransportError::from)?;
+        Ok(listener)
+    }
+}
+
+/// The options of the connections to some peers, shared by a router and
+/// its handles, so that reconnecting to a peer, e.g. when a message is
+/// routed to it, uses them again
+pub(crate) type PeerOptions = Arc<Mutex<BTreeMap<SocketAddr, Arc<TcpConnectionOptions>>>>;
+
+/// Options of the connections TCP transports open to their peers
+///
+/// ```rust
+/// use core::time::Duration;
+/// use ockam_transport_tcp::{ReconnectBackoff, TcpConnectionOptions};
+/// // Notice a relay which stopped answering within a minute, and connect
+/// // to it again for about ten minutes
+/// let options = TcpConnectionOptions::new()
+///     .with_keepalive(Duration::from_secs(15))
+///     .with_user_timeout(Duration::from_secs(60))
+///     .with_nodelay(true)
+///     .with_reconnect(ReconnectBackoff::new(Duration::from_secs(1), Duration::from_secs(60), 15));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TcpConnectionOptions {
+    keepalive: Option<Duration>,
+    user_timeout: Option<Duration>,
+    nodelay: bool,
+    reconnect: Option<ReconnectBackoff>,
+}
+
+impl TcpConnectionOptions {
+    /// The options of [`TcpStream::connect`](tokio::net::TcpStream::connect),
+    /// and no reconnection once a connection is lost
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Probe the peer once a connection is idle for `interval`, then every
+    /// `interval` until it answers, instead of relying on the heartbeats
+    /// sent every five minutes
+    pub fn with_keepalive(mut self, interval: Duration) -> Self {
+        self.keepalive = Some(interval);
+        self
+    }
+
+    /// Close a connection whose data, keepalive probes included, is not
+    /// acknowledged by the peer within `timeout` (`TCP_USER_TIMEOUT`)
+    ///
+    /// This is only supported on Linux and Android.
+    pub fn with_user_timeout(mut self, timeout: Duration) -> Self {
+        self.user_timeout = Some(timeout);
+        self
+    }
+
+    /// Send small messages right away, without waiting to coalesce them
+    /// (`TCP_NODELAY`)
+    pub fn with_nodelay(mut self, nodelay: bool) -> Self {
+        self.nodelay = nodelay;
+        self
+    }
+
+    /// Connect to the peer again once a connection is lost, waiting for
+    /// the delays of `backoff` between attempts
+    ///
+    /// The messages sent meanwhile are sent once the connection is open
+    /// again, the message whose sending failed included. The connection is
+    /// closed for good once all the attempts failed.
+    pub fn with_reconnect(mut self, backoff: ReconnectBackoff) -> Self {
+        self.reconnect = Some(backoff);
+        self
+    }
+
+    /// The backoff of the reconnections, if connections are opened again
+    pub fn reconnect(&self) -> Option<&ReconnectBackoff> {
+        self.reconnect.as_ref()
+    }
+
+    /// Set these options on the socket of a connection
+    pub(crate) fn apply(&self, stream: &TcpStream) -> Result<()> {
+        let socket = SockRef::from(stream);
+        if let Some(interval) = self.keepalive {
+            let keepalive = TcpKeepalive::new().with_time(interval);
+            #[cfg(any(
+                target_os = "android",
+                target_os = "freebsd",
+                target_os = "linux",
+                target_vendor = "apple",
+                windows
+            ))]
+            let keepalive = keepalive.with_interval(interval);
+            socket
+                .set_tcp_keepalive(&keepalive)
+                .map_err(TransportError::from)?;
+        }
+        if let Some(timeout) = self.user_timeout {
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            socket
+                .set_tcp_user_timeout(Some(timeout))
+                .map_err(TransportError::from)?;
+            #[cfg(not(any(target_os = "android", target_os = "linux")))]
+            return Err(unsupported(&format!("a user timeout of {:?}", timeout)));
+        }
+        if self.nodelay {
+            stream.set_nodelay(true).map_err(TransportError::from)?;
+        }
+        Ok(())
+    }
+}
+
+/// How lost connections are opened again
+///
+/// The delay before an attempt doubles after each failed one, from
+/// `initial_delay` up to `max_delay`, without jitter so that the attempts
+/// happen at predictable times.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReconnectBackoff {
+    /// Delay before the first attempt
+    pub initial_delay: Duration,
+    /// Maximum delay between two attempts
+    pub max_delay: Duration,
+    /// Number of attempts before the connection is closed for good
+    pub max_attempts: u32,
+}
+
+impl ReconnectBackoff {
+    /// Make up to `max_attempts` attempts, waiting from `initial_delay` to
+    /// `max_delay` before each
+    pub fn new(initial_delay: Duration, max_delay: Duration, max_attempts: u32) -> Self {
+        Self {
+            initial_delay,
+            max_delay,
+            max_attempts,
+        }
+    }
+
+    /// The delay before the attempt `attempt`, counted from 1
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(31);
+        self.initial_delay
+            .saturating_mul(1 << exp)
+            .min(self.max_delay)
+    }
+}
+
+impl Default for ReconnectBackoff {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1), Duration::from_secs(60), 10)
+    }
+}
+
+/// The TCP sockets passed by the service manager to this process
+fn activated() -> Vec<std::net::TcpListener> {
+    let mut fds = ListenFd::from_env();
+    let mut listeners = Vec::new();
+    for i in 0..fds.len() {
+        match fds.take_tcp_listener(i) {
+            Ok(Some(listener)) => listeners.push(listener),
+            Ok(None) => {}
+            Err(e) => {
+                warn!(fd = i + 3, err = %e, "Skipping a passed socket which is not a TCP listener")
+            }
+        }
+    }
+    listeners
+}
+
+/// Take the socket passed by the service manager which listens on `addr`
+fn take_activated(addr: SocketAddr) -> Option<std::net::TcpListener> {
+    let mut listeners = ACTIVATED.lock().unwrap();
+    let i = listeners
+        .iter()
+        .position(|listener| match listener.local_addr() {
+            Ok(local) => listens_on(addr, local),
+            Err(_) => false,
+        })?;
+    Some(listeners.remove(i))
+}
+
+/// Whether a socket bound to `local` listens on the address `addr`
+fn listens_on(addr: SocketAddr, local: SocketAddr) -> bool {
+    (addr.ip().is_unspecified() || addr.ip() == local.ip())
+        && (addr.port() == 0 || addr.port() == local.port())
+}
+
+#[cfg(not(all(unix, any(target_os = "android", target_os = "linux"))))]
+fn unsupported(what: &str) -> ockam_core::Error {
+    use ockam_core::errcode::{Kind, Origin};
+    let msg = format!("{} is not supported on this platform", what);
+    ockam_core::Error::new(Origin::Transport, Kind::Unsupported, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconnections_back_off_exponentially() {
+        let backoff = ReconnectBackoff::new(Duration::from_secs(1), Duration::from_secs(10), 6);
+        let delays: Vec<u64> = (1..=6).map(|n| backoff.delay(n).as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 10, 10]);
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn addresses_match_the_sockets_listening_on_them() {
+        let local: SocketAddr = "127.0.0.1:443".parse().unwrap();
+        assert!(listens_on(local, local));
+        assert!(listens_on("0.0.0.0:443".parse().unwrap(), local));
+        assert!(listens_on("127.0.0.1:0".parse().unwrap(), local));
+        assert!(!listens_on("127.0.0.1:80".parse().unwrap(), local));
+        assert!(!listens_on("10.0.0.1:443".parse().unwrap(), local));
+
+        let local: SocketAddr = "[::]:443".parse().unwrap();
+        assert!(listens_on("0.0.0.0:443".parse().unwrap(), local));
+        assert!(!listens_on("127.0.0.1:443".parse().unwrap(), local));
+    }
+}
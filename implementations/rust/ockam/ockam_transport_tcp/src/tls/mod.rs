@@ -0,0 +1,200 @@
+//! TLS for portals and transport connections.
+//!
+//! An inlet configured with [`InletTls`] accepts TLS connections from local
+//! clients and forwards the decrypted stream through the portal. The
+//! certificate and key are read from PEM files, which are reloaded whenever
+//! they change, so certificates renewed by an ACME client (e.g. certbot or
+//! lego) are picked up without restarting the inlet. Alternatively a
+//! [`SelfSignedCert`] can be generated and written to those files.
+//!
+//! An outlet configured with [`OutletTls`] connects to its target over TLS,
+//! optionally authenticating itself with a client certificate.
+//!
+//! The same configurations wrap the connections of the transport itself,
+//! for networks which only let TLS through: a listener created with
+//! [`ListenerOptions::with_tls`](crate::ListenerOptions::with_tls) accepts
+//! TLS connections, and
+//! [`TcpTransport::connect_with_tls`](crate::TcpTransport::connect_with_tls)
+//! connects to such a listener. Ockam messages keep being encrypted end to
+//! end by secure channels on top of TLS.
+
+mod outlet;
+mod self_signed;
+
+pub use outlet::OutletTls;
+pub use self_signed::SelfSignedCert;
+
+use crate::{PortalReader, PortalWriter};
+use core::fmt;
+use core::time::Duration;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::net::SocketAddr;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use tokio_rustls::rustls::{Certificate, PrivateKey, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+use tracing::{info, warn};
+
+/// Time a client has to complete the TLS handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// TLS configuration of a portal inlet
+pub struct InletTls {
+    cert: PathBuf,
+    key: PathBuf,
+    acceptor: Reloading<TlsAcceptor>,
+}
+
+impl InletTls {
+    /// Serve the PEM encoded certificate chain and private key found in
+    /// the given files.
+    ///
+    /// The files are read once to check that they are valid.
+    pub fn from_pem_files(cert: impl Into<PathBuf>, key: impl Into<PathBuf>) -> Result<Self> {
+        let cert = cert.into();
+        let key = key.into();
+        let acceptor = Reloading::new(&[&cert, &key], || load(&cert, &key))?;
+        Ok(Self {
+            cert,
+            key,
+            acceptor,
+        })
+    }
+
+    /// Path of the certificate file.
+    pub fn cert_path(&self) -> &Path {
+        &self.cert
+    }
+
+    /// Path of the private key file.
+    pub fn key_path(&self) -> &Path {
+        &self.key
+    }
+
+    /// Run the server side of a TLS handshake on an accepted connection.
+    pub(crate) async fn accept(&self, stream: TcpStream) -> Result<(PortalReader, PortalWriter)> {
+        let acceptor = self
+            .acceptor
+            .get(&[&self.cert, &self.key], || load(&self.cert, &self.key));
+        let stream = timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream))
+            .await
+            .map_err(|_| tls_error("TLS handshake timed out"))?
+            .map_err(|e| tls_error(format!("TLS handshake failed: {}", e)))?;
+        let (rx, tx) = tokio::io::split(stream);
+        Ok((Box::new(rx), Box::new(tx)))
+    }
+}
+
+impl fmt::Debug for InletTls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InletTls")
+            .field("cert", &self.cert)
+            .field("key", &self.key)
+            .finish()
+    }
+}
+
+/// The TLS configurations of the connections to some peers, shared by a
+/// router and its handles, so that reconnecting to a peer, e.g. when a
+/// message is routed to it, uses TLS again
+pub(crate) type TlsPeers = Arc<Mutex<BTreeMap<SocketAddr, Arc<OutletTls>>>>;
+
+/// A value loaded from files, which is loaded again when they change.
+struct Reloading<T> {
+    loaded: Mutex<(Vec<Option<SystemTime>>, T)>,
+}
+
+impl<T: Clone> Reloading<T> {
+    fn new(paths: &[&Path], load: impl FnOnce() -> Result<T>) -> Result<Self> {
+        let modified = paths.iter().map(|p| modified(p)).collect();
+        Ok(Self {
+            loaded: Mutex::new((modified, load()?)),
+        })
+    }
+
+    /// The current value.
+    ///
+    /// If the files changed since they were last read, they are loaded
+    /// again. Invalid files, e.g. while a renewal is still being written,
+    /// are ignored and the previous value keeps being used.
+    fn get(&self, paths: &[&Path], load: impl FnOnce() -> Result<T>) -> T {
+        let modified: Vec<_> = paths.iter().map(|p| modified(p)).collect();
+        let mut loaded = self.loaded.lock().unwrap();
+        if loaded.0 != modified {
+            match load() {
+                Ok(value) => {
+                    info!(files = ?paths, "reloaded TLS files");
+                    *loaded = (modified, value)
+                }
+                Err(e) => warn!(files = ?paths, err = %e, "failed to reload TLS files"),
+            }
+        }
+        loaded.1.clone()
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    path.metadata().and_then(|m| m.modified()).ok()
+}
+
+fn load(cert: &Path, key: &Path) -> Result<TlsAcceptor> {
+    let certs = read_certs(cert)?;
+    let key = read_key(key)?;
+    let config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| tls_error(format!("invalid certificate or key: {}", e)))?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// Read a PEM encoded certificate chain.
+pub(crate) fn read_certs(path: &Path) -> Result<Vec<Certificate>> {
+    let mut reader = open(path)?;
+    let certs = rustls_pemfile::certs(&mut reader)
+        .map_err(|e| tls_error(format!("{}: {}", path.display(), e)))?;
+    if certs.is_empty() {
+        return Err(tls_error(format!(
+            "{}: no certificate found",
+            path.display()
+        )));
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+/// Read the first PEM encoded private key of a file.
+pub(crate) fn read_key(path: &Path) -> Result<PrivateKey> {
+    use rustls_pemfile::Item;
+    let mut reader = open(path)?;
+    loop {
+        match rustls_pemfile::read_one(&mut reader)
+            .map_err(|e| tls_error(format!("{}: {}", path.display(), e)))?
+        {
+            Some(Item::PKCS8Key(k) | Item::RSAKey(k) | Item::ECKey(k)) => return Ok(PrivateKey(k)),
+            Some(_) => continue,
+            None => {
+                return Err(tls_error(format!(
+                    "{}: no private key found",
+                    path.display()
+                )))
+            }
+        }
+    }
+}
+
+fn open(path: &Path) -> Result<BufReader<File>> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| tls_error(format!("{}: {}", path.display(), e)))
+}
+
+fn tls_error(msg: impl Into<String>) -> Error {
+    Error::new(Origin::Transport, Kind::Invalid, msg.into())
+}
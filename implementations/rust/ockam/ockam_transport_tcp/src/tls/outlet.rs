@@ -0,0 +1,105 @@
+use super::{read_certs, read_key, tls_error, Reloading, HANDSHAKE_TIMEOUT};
+use crate::{PortalReader, PortalWriter};
+use ockam_core::Result;
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use tokio_rustls::rustls::{Certificate, ClientConfig, RootCertStore, ServerName};
+use tokio_rustls::TlsConnector;
+
+/// TLS configuration of a portal outlet
+pub struct OutletTls {
+    server_name: ServerName,
+    ca: Option<PathBuf>,
+    client: Option<(PathBuf, PathBuf)>,
+    connector: Reloading<TlsConnector>,
+}
+
+impl OutletTls {
+    /// Connect to a target which presents a certificate for `server_name`.
+    ///
+    /// The certificate is verified with the PEM encoded CA certificates of
+    /// `ca`, or with the root certificates of the system if none are given.
+    /// With a `client` certificate chain and private key, the outlet
+    /// authenticates itself to the target (mutual TLS).
+    ///
+    /// The files are read once to check that they are valid, and again
+    /// whenever they change.
+    pub fn new(
+        server_name: &str,
+        ca: Option<PathBuf>,
+        client: Option<(PathBuf, PathBuf)>,
+    ) -> Result<Self> {
+        let server_name = ServerName::try_from(server_name)
+            .map_err(|_| tls_error(format!("invalid server name: {}", server_name)))?;
+        let connector = Reloading::new(&paths(&ca, &client), || load(&ca, &client))?;
+        Ok(Self {
+            server_name,
+            ca,
+            client,
+            connector,
+        })
+    }
+
+    /// Run the client side of a TLS handshake on a new connection.
+    pub(crate) async fn connect(&self, stream: TcpStream) -> Result<(PortalReader, PortalWriter)> {
+        let connector = self.connector.get(&paths(&self.ca, &self.client), || {
+            load(&self.ca, &self.client)
+        });
+        let stream = timeout(
+            HANDSHAKE_TIMEOUT,
+            connector.connect(self.server_name.clone(), stream),
+        )
+        .await
+        .map_err(|_| tls_error("TLS handshake timed out"))?
+        .map_err(|e| tls_error(format!("TLS handshake failed: {}", e)))?;
+        let (rx, tx) = tokio::io::split(stream);
+        Ok((Box::new(rx), Box::new(tx)))
+    }
+}
+
+fn paths<'a>(ca: &'a Option<PathBuf>, client: &'a Option<(PathBuf, PathBuf)>) -> Vec<&'a Path> {
+    let mut paths: Vec<&Path> = ca.iter().map(|p| p.as_path()).collect();
+    if let Some((cert, key)) = client {
+        paths.push(cert);
+        paths.push(key)
+    }
+    paths
+}
+
+fn load(ca: &Option<PathBuf>, client: &Option<(PathBuf, PathBuf)>) -> Result<TlsConnector> {
+    let mut roots = RootCertStore::empty();
+    let certs = match ca {
+        Some(ca) => read_certs(ca)?,
+        None => rustls_native_certs::load_native_certs()
+            .map_err(|e| {
+                tls_error(format!(
+                    "failed to load the system root certificates: {}",
+                    e
+                ))
+            })?
+            .into_iter()
+            .map(|c| Certificate(c.0))
+            .collect(),
+    };
+    for c in &certs {
+        // Unsupported system certificates are skipped, like other clients do.
+        if let Err(e) = roots.add(c) {
+            if ca.is_some() {
+                return Err(tls_error(format!("invalid CA certificate: {}", e)));
+            }
+        }
+    }
+    let builder = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots);
+    let config = match client {
+        Some((cert, key)) => builder
+            .with_single_cert(read_certs(cert)?, read_key(key)?)
+            .map_err(|e| tls_error(format!("invalid client certificate or key: {}", e)))?,
+        None => builder.with_no_client_auth(),
+    };
+    Ok(TlsConnector::from(Arc::new(config)))
+}
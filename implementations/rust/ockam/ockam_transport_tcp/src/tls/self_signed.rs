@@ -0,0 +1,88 @@
+//! Generation of self-signed certificates.
+//!
+//! The certificate is an X.509 v3 certificate with an ECDSA P-256 key.
+
+use super::tls_error;
+use ockam_core::Result;
+use rcgen::{Certificate, CertificateParams, DnType, SanType};
+use std::net::IpAddr;
+use std::path::Path;
+use time::{Duration, OffsetDateTime};
+
+/// How long a generated certificate is valid.
+const VALIDITY_DAYS: i64 = 365;
+
+/// A self-signed certificate and its private key, PEM encoded
+#[derive(Debug, Clone)]
+pub struct SelfSignedCert {
+    /// The certificate
+    pub cert_pem: String,
+    /// The PKCS#8 private key
+    pub key_pem: String,
+}
+
+impl SelfSignedCert {
+    /// Generate a certificate valid for the given host names and IP addresses.
+    pub fn generate(names: &[&str]) -> Result<Self> {
+        let mut params = CertificateParams::default();
+        let name = names.first().copied().unwrap_or("localhost");
+        params.distinguished_name.push(DnType::CommonName, name);
+        params.subject_alt_names = names
+            .iter()
+            .map(|n| match n.parse::<IpAddr>() {
+                Ok(ip) => SanType::IpAddress(ip),
+                Err(_) => SanType::DnsName(n.to_string()),
+            })
+            .collect();
+        let now = OffsetDateTime::now_utc();
+        params.not_before = now - Duration::days(1);
+        params.not_after = now + Duration::days(VALIDITY_DAYS);
+
+        let cert = Certificate::from_params(params)
+            .map_err(|e| tls_error(format!("failed to generate a certificate: {}", e)))?;
+        let cert_pem = cert
+            .serialize_pem()
+            .map_err(|e| tls_error(format!("failed to sign the certificate: {}", e)))?;
+        Ok(Self {
+            cert_pem,
+            key_pem: cert.serialize_private_key_pem(),
+        })
+    }
+
+    /// Write the certificate and the key to the given files.
+    pub fn write(&self, cert: &Path, key: &Path) -> Result<()> {
+        let write = |path: &Path, data: &str| {
+            std::fs::write(path, data).map_err(|e| tls_error(format!("{}: {}", path.display(), e)))
+        };
+        write(key, &self.key_pem)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let perms = std::fs::Permissions::from_mode(0o600);
+            std::fs::set_permissions(key, perms)
+                .map_err(|e| tls_error(format!("{}: {}", key.display(), e)))?
+        }
+        write(cert, &self.cert_pem)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_certificate_can_be_loaded() {
+        let cert = SelfSignedCert::generate(&["localhost", "127.0.0.1"]).unwrap();
+        let certs = rustls_pemfile::certs(&mut cert.cert_pem.as_bytes()).unwrap();
+        let keys = rustls_pemfile::pkcs8_private_keys(&mut cert.key_pem.as_bytes()).unwrap();
+        assert_eq!((certs.len(), keys.len()), (1, 1));
+        let config = tokio_rustls::rustls::ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![tokio_rustls::rustls::Certificate(certs[0].clone())],
+                tokio_rustls::rustls::PrivateKey(keys[0].clone()),
+            );
+        assert!(config.is_ok())
+    }
+}
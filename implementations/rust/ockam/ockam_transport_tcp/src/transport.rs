@@ -2,9 +2,12 @@ use ockam_core::access_control::AccessControl;
 use ockam_core::compat::{boxed::Box, net::SocketAddr};
 use ockam_core::{Address, AllowAll, AsyncTryClone, Result, Route};
 use ockam_node::Context;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
-use crate::{parse_socket_addr, TcpOutletListenWorker, TcpRouter, TcpRouterHandle};
+use crate::{
+    parse_socket_addr, EgressPolicy, ListenerOptions, ProxyProtocol, ProxyRoutes,
+    TcpConnectionOptions, TcpOutletListenWorker, TcpRouter, TcpRouterHandle, UpstreamProxy,
+};
 
 /// High level management interface for TCP transports
 ///
@@ -87,11 +90,95 @@ impl TcpTransport {
         self.router_handle.connect(peer.as_ref()).await
     }
 
+    /// Establish an outgoing TCP connection with the given options, e.g. to
+    /// notice quickly that a relay stopped answering and connect to it
+    /// again
+    ///
+    /// Reconnections to the peer have these options too, until
+    /// [`TcpTransport::connect`] is called for it.
+    ///
+    /// ```rust
+    /// use core::time::Duration;
+    /// use ockam_transport_tcp::{ReconnectBackoff, TcpConnectionOptions, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// let options = TcpConnectionOptions::new()
+    ///     .with_keepalive(Duration::from_secs(15))
+    ///     .with_reconnect(ReconnectBackoff::default());
+    /// tcp.connect_with_options("relay.example.com:4000", options).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn connect_with_options<S: AsRef<str>>(
+        &self,
+        peer: S,
+        options: TcpConnectionOptions,
+    ) -> Result<Address> {
+        self.router_handle
+            .connect_with_options(peer.as_ref(), options)
+            .await
+    }
+
+    /// Establish an outgoing TCP connection wrapped in TLS
+    ///
+    /// The peer, e.g. a listener created with
+    /// [`ListenerOptions::with_tls`](crate::ListenerOptions::with_tls),
+    /// must present a certificate for the server name of `tls`, verified
+    /// with its CA certificates. Reconnections to the peer use TLS too,
+    /// until [`TcpTransport::connect`] is called for it.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{tls::OutletTls, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// let tls = OutletTls::new("node.example.com", Some("ca.pem".into()), None)?;
+    /// tcp.connect_with_tls("node.example.com:4000", tls).await?;
+    /// # Ok(()) }
+    /// ```
+    #[cfg(feature = "tls")]
+    pub async fn connect_with_tls<S: AsRef<str>>(
+        &self,
+        peer: S,
+        tls: crate::tls::OutletTls,
+    ) -> Result<Address> {
+        self.router_handle
+            .connect_with_tls(peer.as_ref(), tls)
+            .await
+    }
+
     /// Disconnect from peer
     pub async fn disconnect<S: AsRef<str>>(&self, peer: S) -> Result<()> {
         self.router_handle.disconnect(peer.as_ref()).await
     }
 
+    /// Connect to peers through an upstream proxy, or directly with `None`
+    ///
+    /// The proxy applies to the connections opened afterwards, including
+    /// those opened lazily when a message is routed to a peer.  A transport
+    /// starts with the proxy of the `OCKAM_TCP_PROXY` variable, if any.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::TcpTransport;
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// tcp.set_proxy(Some("socks5://127.0.0.1:1080".parse()?));
+    /// tcp.connect("node.example.com:4000").await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn set_proxy(&self, proxy: Option<UpstreamProxy>) {
+        self.router_handle.set_proxy(proxy)
+    }
+
+    /// The upstream proxy of the transport, if any
+    pub fn proxy(&self) -> Option<UpstreamProxy> {
+        self.router_handle.proxy()
+    }
+
     /// Start listening to incoming connections on an existing transport
     ///
     /// Returns the local address that this transport is bound to.
@@ -111,13 +198,92 @@ impl TcpTransport {
         let bind_addr = parse_socket_addr(bind_addr.as_ref())?;
         self.router_handle.bind(bind_addr).await
     }
+
+    /// Start listening to incoming connections on a socket with the given
+    /// options, e.g. to share a port with other nodes
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{ListenerOptions, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// let options = ListenerOptions::new().with_reuse_port(true);
+    /// tcp.listen_with_options("0.0.0.0:8000", options).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn listen_with_options<S: AsRef<str>>(
+        &self,
+        bind_addr: S,
+        options: ListenerOptions,
+    ) -> Result<SocketAddr> {
+        let bind_addr = parse_socket_addr(bind_addr.as_ref())?;
+        self.router_handle
+            .bind_with_options(bind_addr, &options)
+            .await
+    }
+
+    /// Stop listening on the local address returned by
+    /// [`listen`](crate::TcpTransport::listen)
+    ///
+    /// The connections accepted before are kept open.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::TcpTransport;
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// let addr = tcp.listen("127.0.0.1:0").await?;
+    /// tcp.stop_listener(addr).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn stop_listener(&self, addr: SocketAddr) -> Result<()> {
+        self.router_handle.unbind(addr).await
+    }
+}
+
+/// The route from an inlet to its outlet
+///
+/// The route can be changed while the inlet runs, e.g. to move to
+/// another path to the outlet.  Only the connections accepted after the
+/// change use the new route.
+#[derive(Clone, Debug)]
+pub struct InletRoute(Arc<RwLock<Route>>);
+
+impl InletRoute {
+    /// Constructor
+    pub fn new(route: Route) -> Self {
+        Self(Arc::new(RwLock::new(route)))
+    }
+
+    /// The current route
+    pub fn get(&self) -> Route {
+        self.0.read().unwrap().clone()
+    }
+
+    /// Replace the route used by new connections
+    pub fn set(&self, route: Route) {
+        *self.0.write().unwrap() = route
+    }
+}
+
+impl From<Route> for InletRoute {
+    fn from(route: Route) -> Self {
+        Self::new(route)
+    }
 }
 
 /// Args to start an Inlet
 pub struct InletOptions {
     bind_addr: String,
-    outlet_route: Route,
-    access_control: Arc<dyn AccessControl>,
+    pub(crate) outlet_route: InletRoute,
+    pub(crate) access_control: Arc<dyn AccessControl>,
+    pub(crate) proxy: Option<ProxyProtocol>,
+    pub(crate) proxy_routes: ProxyRoutes,
+    pub(crate) listener: ListenerOptions,
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<Arc<crate::tls::InletTls>>,
 }
 
 impl InletOptions {
@@ -130,17 +296,60 @@ impl InletOptions {
     ) -> Self {
         Self {
             bind_addr,
-            outlet_route,
+            outlet_route: outlet_route.into(),
             access_control,
+            proxy: None,
+            proxy_routes: ProxyRoutes::new(),
+            listener: ListenerOptions::default(),
+            #[cfg(feature = "tls")]
+            tls: None,
         }
     }
+
+    /// Listen on a socket with the given options
+    pub fn with_listener_options(mut self, listener: ListenerOptions) -> Self {
+        self.listener = listener;
+        self
+    }
+
+    /// Terminate TLS on the inlet, so that clients connect to it over TLS
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, tls: crate::tls::InletTls) -> Self {
+        self.tls = Some(Arc::new(tls));
+        self
+    }
+
+    /// Speak a proxy protocol with clients, and ask a proxy outlet to
+    /// connect to the target each client requests
+    pub fn with_proxy(mut self, proxy: ProxyProtocol) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Send the connections of a proxy inlet to some targets to the outlets
+    /// dedicated to them.
+    ///
+    /// The other targets are requested from the proxy outlet at the outlet
+    /// route, or refused if that route is empty.
+    pub fn with_proxy_routes(mut self, routes: ProxyRoutes) -> Self {
+        self.proxy_routes = routes;
+        self
+    }
+
+    /// The route to the outlet, which can be changed once the inlet runs
+    pub fn outlet_route(&self) -> &InletRoute {
+        &self.outlet_route
+    }
 }
 
 /// Args to start an Outlet
 pub struct OutletOptions {
     address: Address,
-    peer: String,
-    access_control: Arc<dyn AccessControl>,
+    pub(crate) peer: Option<String>,
+    pub(crate) access_control: Arc<dyn AccessControl>,
+    pub(crate) egress: Option<EgressPolicy>,
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<Arc<crate::tls::OutletTls>>,
 }
 
 impl OutletOptions {
@@ -149,10 +358,37 @@ impl OutletOptions {
     pub fn new(address: Address, peer: String, access_control: Arc<dyn AccessControl>) -> Self {
         Self {
             address,
-            peer,
+            peer: Some(peer),
+            access_control,
+            egress: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+    /// Constructor of a proxy outlet, which connects to the targets
+    /// requested by proxy inlets if `egress` allows them
+    pub fn proxy(
+        address: Address,
+        egress: EgressPolicy,
+        access_control: Arc<dyn AccessControl>,
+    ) -> Self {
+        Self {
+            address,
+            peer: None,
             access_control,
+            egress: Some(egress),
+            #[cfg(feature = "tls")]
+            tls: None,
         }
     }
+
+    /// Connect to the target over TLS
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, tls: crate::tls::OutletTls) -> Self {
+        self.tls = Some(Arc::new(tls));
+        self
+    }
 }
 
 impl TcpTransport {
@@ -161,10 +397,8 @@ impl TcpTransport {
         &self,
         options: InletOptions,
     ) -> Result<(Address, SocketAddr)> {
-        let bind_addr = parse_socket_addr(options.bind_addr)?;
-        self.router_handle
-            .bind_inlet(options.outlet_route, bind_addr, options.access_control)
-            .await
+        let bind_addr = parse_socket_addr(&options.bind_addr)?;
+        self.router_handle.bind_inlet(bind_addr, options).await
     }
 
     /// Create Tcp Inlet that listens on bind_addr, transforms Tcp stream into Ockam Routable
@@ -218,10 +452,11 @@ impl TcpTransport {
 
     /// Create an Outlet
     pub async fn create_outlet_extended(&self, options: OutletOptions) -> Result<()> {
-        let worker = TcpOutletListenWorker::new(options.peer, options.access_control);
+        let address = options.address.clone();
+        let worker = TcpOutletListenWorker::new(options);
         self.router_handle
             .ctx()
-            .start_worker(options.address, worker)
+            .start_worker(address, worker)
             .await?;
 
         Ok(())
@@ -254,6 +489,69 @@ impl TcpTransport {
         self.create_outlet_extended(options).await
     }
 
+    /// Create a Tcp Inlet that speaks SOCKS5 with its clients, and asks the proxy Outlet at
+    /// outlet_route to connect to the target each client requests.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{EgressPolicy, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{Result, route};
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// tcp.create_proxy_outlet("proxy", EgressPolicy::parse(&["*.example.com:443"])?)
+    ///     .await?;
+    /// tcp.create_socks_inlet("127.0.0.1:1080", route!["proxy"]).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_socks_inlet(
+        &self,
+        bind_addr: impl Into<String>,
+        outlet_route: impl Into<Route>,
+    ) -> Result<(Address, SocketAddr)> {
+        let options = InletOptions::new(bind_addr.into(), outlet_route.into(), Arc::new(AllowAll))
+            .with_proxy(ProxyProtocol::Socks5);
+
+        self.create_inlet_extended(options).await
+    }
+
+    /// Create a Tcp Inlet that accepts HTTP CONNECT tunnels and plain HTTP requests, and
+    /// sends them to the outlet routes picks for their target.  The other targets are refused.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{ProxyRoutes, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{Result, route};
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// tcp.create_outlet("db", "10.0.0.5:5432").await?;
+    /// let routes = ProxyRoutes::new().add("db.internal:5432", route!["db"])?;
+    /// tcp.create_http_proxy_inlet("127.0.0.1:3128", routes).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_http_proxy_inlet(
+        &self,
+        bind_addr: impl Into<String>,
+        routes: ProxyRoutes,
+    ) -> Result<(Address, SocketAddr)> {
+        let options = InletOptions::new(bind_addr.into(), Route::new().into(), Arc::new(AllowAll))
+            .with_proxy(ProxyProtocol::HttpConnect)
+            .with_proxy_routes(routes);
+
+        self.create_inlet_extended(options).await
+    }
+
+    /// Create a proxy Outlet at address, which connects to the targets requested by proxy
+    /// Inlets, if the egress policy allows them.
+    pub async fn create_proxy_outlet(
+        &self,
+        address: impl Into<Address>,
+        egress: EgressPolicy,
+    ) -> Result<()> {
+        let options = OutletOptions::proxy(address.into(), egress, Arc::new(AllowAll));
+
+        self.create_outlet_extended(options).await
+    }
+
     /// Stop outlet at addr
     /// ```rust
     /// use ockam_transport_tcp::TcpTransport;
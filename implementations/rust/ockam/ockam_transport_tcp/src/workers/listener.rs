@@ -1,10 +1,13 @@
-use crate::{TcpRouterHandle, TcpSendWorker};
+use crate::{split_tcp, ListenerOptions, TcpRouterHandle, TcpSendWorker};
 use ockam_core::{async_trait, compat::net::SocketAddr, AsyncTryClone};
 use ockam_core::{Address, Processor, Result};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
 use tokio::net::TcpListener;
-use tracing::{debug, trace};
+use tokio::sync::mpsc;
+use tracing::{debug, trace, warn};
 
 /// A TCP Listen processor
 ///
@@ -13,28 +16,73 @@ use tracing::{debug, trace};
 /// [`TcpTransport::listen`](crate::TcpTransport::listen).
 pub(crate) struct TcpListenProcessor {
     inner: TcpListener,
+    /// Dropped after `inner`, to tell when the socket is closed
+    _open: mpsc::Sender<()>,
     router_handle: TcpRouterHandle,
+    #[cfg(feature = "tls")]
+    tls: Option<Arc<crate::tls::InletTls>>,
+    /// The other accept workers of the address, stopped with this one
+    siblings: Vec<Address>,
 }
 
+/// A listener started by [`TcpListenProcessor::start`]
+pub(crate) struct Listener {
+    /// The address of its main processor
+    pub(crate) address: Address,
+    /// Gets `None` once all its processors are dropped, their sockets
+    /// with them
+    pub(crate) closed: mpsc::Receiver<()>,
+}
+
+/// The listeners of a router and its handles, by local address
+///
+/// Several listeners share an address with `SO_REUSEPORT`.
+pub(crate) type Listeners = Arc<Mutex<BTreeMap<SocketAddr, Vec<Listener>>>>;
+
 impl TcpListenProcessor {
     pub(crate) async fn start(
         ctx: &Context,
         router_handle: TcpRouterHandle,
         addr: SocketAddr,
-    ) -> Result<SocketAddr> {
+        options: &ListenerOptions,
+    ) -> Result<(SocketAddr, Listener)> {
         debug!("Binding TcpListener to {}", addr);
-        let inner = TcpListener::bind(addr)
-            .await
-            .map_err(TransportError::from)?;
+        let mut listeners = options.bind_all(addr)?.into_iter();
+        let inner = listeners.next().ok_or(TransportError::BindFailed)?;
         let saddr = inner.local_addr().map_err(TransportError::from)?;
+        let (open, closed) = mpsc::channel(1);
+
+        let mut siblings = Vec::new();
+        for inner in listeners {
+            let worker = Self {
+                inner,
+                _open: open.clone(),
+                router_handle: router_handle.async_try_clone().await?,
+                #[cfg(feature = "tls")]
+                tls: options.tls.clone(),
+                siblings: Vec::new(),
+            };
+            let address = Address::random_local();
+            ctx.start_processor(address.clone(), worker).await?;
+            siblings.push(address);
+        }
+        if !siblings.is_empty() {
+            debug!(%saddr, "Accepting TCP connections with {} workers", siblings.len() + 1);
+        }
+
         let worker = Self {
             inner,
+            _open: open,
             router_handle,
+            #[cfg(feature = "tls")]
+            tls: options.tls.clone(),
+            siblings,
         };
 
-        ctx.start_processor(Address::random_local(), worker).await?;
+        let address = Address::random_local();
+        ctx.start_processor(address.clone(), worker).await?;
 
-        Ok(saddr)
+        Ok((saddr, Listener { address, closed }))
     }
 }
 
@@ -46,6 +94,15 @@ impl Processor for TcpListenProcessor {
         ctx.set_cluster(crate::CLUSTER_NAME).await
     }
 
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        for address in self.siblings.drain(..) {
+            if let Err(e) = ctx.stop_processor(address.clone()).await {
+                debug!(%address, err = %e, "Failed to stop an accept worker");
+            }
+        }
+        Ok(())
+    }
+
     async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
         debug!("Waiting for incoming TCP connection...");
 
@@ -53,6 +110,20 @@ impl Processor for TcpListenProcessor {
         let (stream, peer) = self.inner.accept().await.map_err(TransportError::from)?;
         debug!("TCP connection accepted");
 
+        #[cfg(feature = "tls")]
+        let stream = match &self.tls {
+            Some(tls) => match tls.accept(stream).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!(%peer, err = %e, "Rejected TCP connection");
+                    return Ok(true);
+                }
+            },
+            None => split_tcp(stream),
+        };
+        #[cfg(not(feature = "tls"))]
+        let stream = split_tcp(stream);
+
         let handle_clone = self.router_handle.async_try_clone().await?;
         // And create a connection worker for it
         let (worker, pair) =
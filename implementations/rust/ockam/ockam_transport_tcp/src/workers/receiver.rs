@@ -1,28 +1,28 @@
-use crate::{TcpSendWorkerMsg, TCP};
+use crate::{PortalReader, TcpSendWorkerMsg, TCP};
 use ockam_core::async_trait;
 use ockam_core::{Address, Decodable, LocalMessage, Processor, Result, TransportMessage};
 use ockam_node::{Context, ExternalLocalInfo};
 use ockam_transport_core::TransportError;
-use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
+use tokio::io::AsyncReadExt;
 use tracing::{error, info, trace};
 
 /// A TCP receiving message processor
 ///
 /// Create this processor type by calling
-/// [`TcpSendWorker::start_pair`](crate::TcpSendWorker::start_pair)
+/// [`TcpSendWorker::new_pair`](crate::TcpSendWorker::new_pair)
 ///
 /// This half of the worker is created when spawning a new connection
 /// worker pair, and listens for incoming TCP packets, to relay into
 /// the node message system.
 pub(crate) struct TcpRecvProcessor {
-    rx: OwnedReadHalf,
+    rx: PortalReader,
     peer_addr: Address,
     sender_internal_address: Address,
 }
 
 impl TcpRecvProcessor {
     /// Create a new `TcpRecvProcessor`
-    pub fn new(rx: OwnedReadHalf, peer_addr: Address, sender_internal_address: Address) -> Self {
+    pub fn new(rx: PortalReader, peer_addr: Address, sender_internal_address: Address) -> Self {
         Self {
             rx,
             peer_addr,
@@ -1,14 +1,17 @@
-use crate::{TcpRecvProcessor, TcpRouterHandle};
+use crate::{
+    split_tcp, PortalReader, PortalWriter, TcpConnectionOptions, TcpRecvProcessor, TcpRouterHandle,
+    UpstreamProxy,
+};
 use core::time::Duration;
 use ockam_core::{async_trait, compat::net::SocketAddr, route, Any, Decodable, LocalMessage};
 use ockam_core::{Address, Encodable, Message, Result, Routed, TransportMessage, Worker};
 use ockam_node::{Context, DelayedEvent};
 use ockam_transport_core::TransportError;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use tokio::io::AsyncWriteExt;
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
-use tracing::{debug, trace, warn};
+use tracing::{debug, info, trace, warn};
 
 /// Provides the transmit and receive parts of a TCP connection
 #[derive(Debug)]
@@ -44,36 +47,43 @@ pub(crate) enum TcpSendWorkerMsg {
 /// A TCP sending message worker
 ///
 /// Create this worker type by calling
-/// [`TcpSendWorker::start_pair`](crate::TcpSendWorker::start_pair)
+/// [`TcpSendWorker::new_pair`](crate::TcpSendWorker::new_pair)
 ///
 /// This half of the worker is created when spawning a new connection
 /// worker pair, and listens for messages from the node message system
 /// to dispatch to a remote peer.
 pub(crate) struct TcpSendWorker {
     router_handle: TcpRouterHandle,
-    rx: Option<OwnedReadHalf>,
-    tx: Option<OwnedWriteHalf>,
+    rx: Option<PortalReader>,
+    tx: Option<PortalWriter>,
     peer: SocketAddr,
+    /// The `host:port` of the peer as it was given, to connect to it
+    /// through a proxy
+    target: String,
     internal_addr: Address,
     rx_addr: Option<Address>,
     heartbeat: DelayedEvent<TcpSendWorkerMsg>,
     heartbeat_interval: Option<Duration>,
+    proxy: Option<Arc<UpstreamProxy>>,
+    /// Options of the connection opened by this worker
+    options: Option<Arc<TcpConnectionOptions>>,
+    /// Wrap the connection opened by this worker in TLS
+    #[cfg(feature = "tls")]
+    tls: Option<Arc<crate::tls::OutletTls>>,
 }
 
 impl TcpSendWorker {
     /// Create a new `TcpSendWorker`
     fn new(
         router_handle: TcpRouterHandle,
-        stream: Option<TcpStream>,
+        stream: Option<(PortalReader, PortalWriter)>,
         peer: SocketAddr,
+        target: String,
         internal_addr: Address,
         heartbeat: DelayedEvent<TcpSendWorkerMsg>,
     ) -> Self {
         let (rx, tx) = match stream {
-            Some(s) => {
-                let (rx, tx) = s.into_split();
-                (Some(rx), Some(tx))
-            }
+            Some((rx, tx)) => (Some(rx), Some(tx)),
             None => (None, None),
         };
 
@@ -82,13 +92,37 @@ impl TcpSendWorker {
             rx,
             tx,
             peer,
+            target,
             internal_addr,
             rx_addr: None,
             heartbeat,
             heartbeat_interval: Some(Duration::from_secs(5 * 60)),
+            proxy: None,
+            options: None,
+            #[cfg(feature = "tls")]
+            tls: None,
         }
     }
 
+    /// Connect to the peer through an upstream proxy, unless it bypasses it
+    pub(crate) fn with_proxy(mut self, proxy: Option<Arc<UpstreamProxy>>) -> Self {
+        self.proxy = proxy.filter(|p| p.is_used_for(&self.target));
+        self
+    }
+
+    /// Open the connection to the peer with these options
+    pub(crate) fn with_options(mut self, options: Option<Arc<TcpConnectionOptions>>) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Connect to the peer over TLS
+    #[cfg(feature = "tls")]
+    pub(crate) fn with_tls(mut self, tls: Option<Arc<crate::tls::OutletTls>>) -> Self {
+        self.tls = tls;
+        self
+    }
+
     pub(crate) fn internal_addr(&self) -> &Address {
         &self.internal_addr
     }
@@ -97,16 +131,21 @@ impl TcpSendWorker {
     pub(crate) async fn new_pair(
         ctx: &Context,
         router_handle: TcpRouterHandle,
-        stream: Option<TcpStream>,
+        stream: Option<(PortalReader, PortalWriter)>,
         peer: SocketAddr,
         hostnames: Vec<String>,
     ) -> Result<(Self, WorkerPair)> {
         let tx_addr = Address::random_local();
         let int_addr = Address::random_local();
+        let target = hostnames
+            .first()
+            .cloned()
+            .unwrap_or_else(|| peer.to_string());
         let sender = TcpSendWorker::new(
             router_handle,
             stream,
             peer,
+            target,
             int_addr.clone(),
             DelayedEvent::create(ctx, int_addr.clone(), TcpSendWorkerMsg::Heartbeat).await?,
         );
@@ -120,22 +159,6 @@ impl TcpSendWorker {
         ))
     }
 
-    /// Start a `(TcpSendWorker, TcpRecvProcessor)` pair that opens and
-    /// manages the connection with the given peer
-    pub(crate) async fn start_pair(
-        ctx: &Context,
-        router_handle: TcpRouterHandle,
-        stream: Option<TcpStream>,
-        peer: SocketAddr,
-        hostnames: Vec<String>,
-    ) -> Result<WorkerPair> {
-        trace!("Creating new TCP worker pair");
-        let (worker, pair) = Self::new_pair(ctx, router_handle, stream, peer, hostnames).await?;
-        ctx.start_worker(vec![pair.tx_addr(), worker.internal_addr().clone()], worker)
-            .await?;
-        Ok(pair)
-    }
-
     /// Schedule a heartbeat
     async fn schedule_heartbeat(&mut self) -> Result<()> {
         let heartbeat_interval = match &self.heartbeat_interval {
@@ -146,6 +169,95 @@ impl TcpSendWorker {
         self.heartbeat.schedule(heartbeat_interval).await
     }
 
+    /// Open a connection to the peer, through the proxy if there is one
+    async fn connect(&self) -> Result<TcpStream> {
+        let stream = match &self.proxy {
+            Some(proxy) => {
+                debug!(addr = %self.peer, proxy = %proxy, "Connecting through a proxy");
+                proxy.connect(&self.target).await?
+            }
+            None => TcpStream::connect(self.peer)
+                .await
+                .map_err(TransportError::from)?,
+        };
+        if let Some(options) = &self.options {
+            options.apply(&stream)?;
+        }
+        Ok(stream)
+    }
+
+    /// Open a connection to the peer, over TLS if it is configured
+    async fn open(&self) -> Result<(PortalReader, PortalWriter)> {
+        let connection = self.connect().await?;
+        #[cfg(feature = "tls")]
+        if let Some(tls) = &self.tls {
+            return tls.connect(connection).await;
+        }
+        Ok(split_tcp(connection))
+    }
+
+    /// Start the processor receiving the messages of the connection
+    async fn start_receiver(&mut self, ctx: &Context, rx: PortalReader) -> Result<()> {
+        let rx_addr = Address::random_local();
+        let receiver = TcpRecvProcessor::new(
+            rx,
+            format!("{}#{}", crate::TCP, self.peer).into(),
+            self.internal_addr.clone(),
+        );
+        ctx.start_processor(rx_addr.clone(), receiver).await?;
+        self.rx_addr = Some(rx_addr);
+        Ok(())
+    }
+
+    /// Open the connection again once it is lost, if its options allow
+    /// it, and return whether it is open
+    async fn reconnect(&mut self, ctx: &Context) -> Result<bool> {
+        let backoff = match self.options.as_ref().and_then(|o| o.reconnect()) {
+            Some(backoff) => *backoff,
+            None => return Ok(false),
+        };
+        if let Some(rx_addr) = self.rx_addr.take() {
+            let _ = ctx.stop_processor(rx_addr).await;
+        }
+        self.tx = None;
+        for attempt in 1..=backoff.max_attempts {
+            ctx.sleep(backoff.delay(attempt)).await;
+            match self.open().await {
+                Ok((rx, tx)) => {
+                    info!(addr = %self.peer, attempt, "Reconnected");
+                    self.tx = Some(tx);
+                    self.start_receiver(ctx, rx).await?;
+                    return Ok(true);
+                }
+                Err(e) => debug!(addr = %self.peer, attempt, err = %e, "Failed to reconnect"),
+            }
+        }
+        warn!(
+            "Failed to reconnect to peer {} after {} attempts",
+            self.peer, backoff.max_attempts
+        );
+        Ok(false)
+    }
+
+    /// Write `buf` to the peer, once more after reconnecting if the
+    /// connection is lost, and return whether it was written
+    ///
+    /// The worker is stopped when it wasn't.
+    async fn send(&mut self, ctx: &Context, buf: &[u8]) -> Result<bool> {
+        let mut reconnected = false;
+        loop {
+            let tx = self.tx.as_mut().ok_or(TransportError::PeerNotFound)?;
+            if tx.write_all(buf).await.is_ok() {
+                return Ok(true);
+            }
+            if reconnected || !self.reconnect(ctx).await? {
+                self.stop_and_unregister(ctx).await?;
+                return Ok(false);
+            }
+            reconnected = true;
+        }
+    }
+
     async fn stop_and_unregister(&self, ctx: &Context) -> Result<()> {
         self.router_handle.unregister(ctx.address()).await?;
 
@@ -165,34 +277,23 @@ impl Worker for TcpSendWorker {
 
         if self.tx.is_none() {
             debug!(addr = %self.peer, "Connecting");
-            let connection = match TcpStream::connect(self.peer).await {
-                Ok(c) => {
+            match self.open().await {
+                Ok((rx, tx)) => {
                     debug!(addr = %self.peer, "Connected");
-                    c
+                    self.tx = Some(tx);
+                    self.rx = Some(rx);
                 }
                 Err(e) => {
                     debug!(addr = %self.peer, err = %e, "Failed to connect");
                     self.stop_and_unregister(ctx).await?;
 
-                    return Err(TransportError::from(e).into());
+                    return Err(e);
                 }
-            };
-            let (rx, tx) = connection.into_split();
-            self.tx = Some(tx);
-            self.rx = Some(rx);
+            }
         }
 
         let rx = self.rx.take().ok_or(TransportError::GenericIo)?;
-
-        let rx_addr = Address::random_local();
-        let receiver = TcpRecvProcessor::new(
-            rx,
-            format!("{}#{}", crate::TCP, self.peer).into(),
-            self.internal_addr.clone(),
-        );
-        ctx.start_processor(rx_addr.clone(), receiver).await?;
-
-        self.rx_addr = Some(rx_addr);
+        self.start_receiver(ctx, rx).await?;
 
         self.schedule_heartbeat().await?;
 
@@ -216,13 +317,13 @@ impl Worker for TcpSendWorker {
     ) -> Result<()> {
         self.heartbeat.cancel();
 
-        let tx = match &mut self.tx {
-            Some(tx) => tx,
-            None => return Err(TransportError::PeerNotFound.into()),
-        };
+        if self.tx.is_none() {
+            return Err(TransportError::PeerNotFound.into());
+        }
 
         let recipient = msg.msg_addr();
         if recipient == self.internal_addr {
+            let from = msg.return_route().recipient();
             let msg = TcpSendWorkerMsg::decode(msg.payload())?;
 
             match msg {
@@ -230,9 +331,8 @@ impl Worker for TcpSendWorker {
                     let msg = TransportMessage::v1(route![], route![], vec![]);
                     let msg = prepare_message(msg)?;
                     // Sending empty heartbeat
-                    if tx.write_all(&msg).await.is_err() {
+                    if !self.send(ctx, &msg).await? {
                         warn!("Failed to send heartbeat to peer {}", self.peer);
-                        self.stop_and_unregister(ctx).await?;
 
                         return Ok(());
                     }
@@ -240,13 +340,25 @@ impl Worker for TcpSendWorker {
                     debug!("Sent heartbeat to peer {}", self.peer);
                 }
                 TcpSendWorkerMsg::ConnectionClosed => {
-                    warn!("Stopping sender due to closed connection {}", self.peer);
+                    // The receiver of a connection replaced by a
+                    // reconnection may notice that it is closed late
+                    if self.rx_addr.as_ref() != Some(&from) {
+                        trace!(
+                            "Ignoring the closing of a previous connection to {}",
+                            self.peer
+                        );
+                        self.schedule_heartbeat().await?;
+                        return Ok(());
+                    }
                     // No need to stop Receiver as it notified us about connection drop and will
                     // stop itself
                     self.rx_addr = None;
-                    self.stop_and_unregister(ctx).await?;
+                    if !self.reconnect(ctx).await? {
+                        warn!("Stopping sender due to closed connection {}", self.peer);
+                        self.stop_and_unregister(ctx).await?;
 
-                    return Ok(());
+                        return Ok(());
+                    }
                 }
             }
         } else {
@@ -257,9 +369,8 @@ impl Worker for TcpSendWorker {
             // Create a message buffer with pre-pended length
             let msg = prepare_message(msg)?;
 
-            if tx.write_all(msg.as_slice()).await.is_err() {
+            if !self.send(ctx, &msg).await? {
                 warn!("Failed to send message to peer {}", self.peer);
-                self.stop_and_unregister(ctx).await?;
 
                 return Ok(());
             }
@@ -0,0 +1,317 @@
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use ockam_core::compat::rand::random;
+use ockam_core::{route, Result, Routed, Worker};
+use ockam_node::Context;
+use ockam_transport_tcp::{
+    EgressPolicy, ProxyProtocol, ProxyRoutes, TcpTransport, UpstreamProxy, TCP,
+};
+
+/// Run the client side of a SOCKS5 handshake to `host:port`, and return
+/// the reply status.
+async fn socks5_connect(stream: &mut TcpStream, host: &str, port: u16) -> u8 {
+    stream.write_all(&[5, 1, 0]).await.unwrap();
+    let mut method = [0u8; 2];
+    stream.read_exact(&mut method).await.unwrap();
+    assert_eq!(method, [5, 0]);
+
+    let mut request = vec![5, 1, 0, 3, host.len() as u8];
+    request.extend_from_slice(host.as_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await.unwrap();
+    let mut reply = [0u8; 10];
+    stream.read_exact(&mut reply).await.unwrap();
+    reply[1]
+}
+
+/// Send an HTTP CONNECT request for `target`, and return the status line of
+/// the reply.
+async fn http_connect(stream: &mut TcpStream, target: &str) -> String {
+    let request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n\r\n", target);
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut reply = Vec::new();
+    while !reply.ends_with(b"\r\n\r\n") {
+        reply.push(stream.read_u8().await.unwrap());
+    }
+    let reply = String::from_utf8(reply).unwrap();
+    reply.lines().next().unwrap().to_string()
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__socks_inlet__should_connect_to_allowed_targets(ctx: &mut Context) -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let tcp = TcpTransport::create(ctx).await?;
+    tcp.create_proxy_outlet("proxy", EgressPolicy::parse(&["localhost"])?)
+        .await?;
+    let (_, inlet_addr) = tcp
+        .create_socks_inlet("127.0.0.1:0", route!["proxy"])
+        .await?;
+
+    let payload1: [u8; 32] = random();
+    let payload2: [u8; 32] = random();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 32];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, payload1);
+        stream.write_all(&payload2).await.unwrap();
+    });
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    assert_eq!(socks5_connect(&mut stream, "localhost", port).await, 0);
+    stream.write_all(&payload1).await.unwrap();
+    let mut buf = [0u8; 32];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(buf, payload2);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__socks_inlet__should_refuse_denied_and_unreachable_targets(
+    ctx: &mut Context,
+) -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    // Nothing listens on that port anymore
+    let closed_port = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let tcp = TcpTransport::create(ctx).await?;
+    tcp.create_proxy_outlet("proxy", EgressPolicy::parse(&["127.0.0.1"])?)
+        .await?;
+    let (_, inlet_addr) = tcp
+        .create_socks_inlet("127.0.0.1:0", route!["proxy"])
+        .await?;
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    assert_eq!(socks5_connect(&mut stream, "localhost", port).await, 5);
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    assert_eq!(
+        socks5_connect(&mut stream, "127.0.0.1", closed_port).await,
+        5
+    );
+
+    // Nothing was accepted
+    let accepted = tokio::time::timeout(Duration::from_millis(100), listener.accept()).await;
+    assert!(accepted.is_err());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__http_proxy_inlet__should_route_hosts_to_their_outlets(
+    ctx: &mut Context,
+) -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+    let tcp = TcpTransport::create(ctx).await?;
+    tcp.create_outlet("db", listener.local_addr().unwrap().to_string())
+        .await?;
+    let routes = ProxyRoutes::new().add("db.internal:5432", route!["db"])?;
+    let (_, inlet_addr) = tcp.create_http_proxy_inlet("127.0.0.1:0", routes).await?;
+
+    let payload1: [u8; 32] = random();
+    let payload2: [u8; 32] = random();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 32];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, payload1);
+        stream.write_all(&payload2).await.unwrap();
+    });
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    let status = http_connect(&mut stream, "DB.internal:5432").await;
+    assert_eq!(status, "HTTP/1.1 200 Connection Established");
+    stream.write_all(&payload1).await.unwrap();
+    let mut buf = [0u8; 32];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(buf, payload2);
+
+    // Hosts without a route are refused
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    let status = http_connect(&mut stream, "db.internal:22").await;
+    assert_eq!(status, "HTTP/1.1 403 Forbidden");
+
+    // Requests must name their host
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    stream.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).await.unwrap();
+    assert!(reply.starts_with("HTTP/1.1 400"));
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+    Ok(())
+}
+
+/// Read the header of a request or response, without what follows.
+async fn read_header(stream: &mut TcpStream) -> String {
+    let mut header = Vec::new();
+    while !header.ends_with(b"\r\n\r\n") {
+        header.push(stream.read_u8().await.unwrap());
+    }
+    String::from_utf8(header).unwrap()
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__http_proxy_inlet__should_forward_plain_requests(ctx: &mut Context) -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+    let tcp = TcpTransport::create(ctx).await?;
+    tcp.create_outlet("web", listener.local_addr().unwrap().to_string())
+        .await?;
+    let routes = ProxyRoutes::new().add("web.internal:80", route!["web"])?;
+    let (_, inlet_addr) = tcp.create_http_proxy_inlet("127.0.0.1:0", routes).await?;
+
+    let body = "5\r\nhello\r\n0\r\n\r\n";
+    let frame: [u8; 32] = random();
+    tokio::spawn(async move {
+        // A chunked upload
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let header = read_header(&mut stream).await;
+        assert_eq!(
+            header,
+            "POST /upload?x=1 HTTP/1.1\r\n\
+             Host: web.internal\r\n\
+             Transfer-Encoding: chunked\r\n\
+             connection: close\r\n\r\n"
+        );
+        let mut buf = vec![0u8; body.len()];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, body.as_bytes());
+        stream
+            .write_all(b"HTTP/1.1 204 No Content\r\n\r\n")
+            .await
+            .unwrap();
+        drop(stream);
+
+        // A WebSocket, streamed as is after the upgrade
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let header = read_header(&mut stream).await;
+        assert!(header.starts_with("GET /ws HTTP/1.1\r\n"));
+        assert!(header.contains("Connection: Upgrade\r\n"));
+        stream
+            .write_all(b"HTTP/1.1 101 Switching Protocols\r\nConnection: Upgrade\r\nUpgrade: websocket\r\n\r\n")
+            .await
+            .unwrap();
+        let mut buf = [0u8; 32];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, frame);
+        stream.write_all(&frame).await.unwrap();
+    });
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    let request = format!(
+        "POST http://web.internal/upload?x=1 HTTP/1.1\r\n\
+         Host: web.internal\r\n\
+         Proxy-Connection: keep-alive\r\n\
+         Transfer-Encoding: chunked\r\n\r\n{}",
+        body
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).await.unwrap();
+    assert_eq!(reply, "HTTP/1.1 204 No Content\r\n\r\n");
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    stream
+        .write_all(
+            b"GET http://web.internal/ws HTTP/1.1\r\n\
+              Host: web.internal\r\n\
+              Connection: Upgrade\r\n\
+              Upgrade: websocket\r\n\r\n",
+        )
+        .await
+        .unwrap();
+    let header = read_header(&mut stream).await;
+    assert!(header.starts_with("HTTP/1.1 101"));
+    stream.write_all(&frame).await.unwrap();
+    let mut buf = [0u8; 32];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(buf, frame);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+    Ok(())
+}
+
+struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn transport__upstream_proxy__should_connect_to_peers(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echoer", Echoer).await?;
+    let tcp = TcpTransport::create(ctx).await?;
+    let listener_address = tcp.listen("127.0.0.1:0").await?.to_string();
+
+    // The proxies are portals of the same node
+    tcp.create_proxy_outlet("proxy", EgressPolicy::allow_all())
+        .await?;
+    let (_, socks_addr) = tcp
+        .create_socks_inlet("127.0.0.1:0", route!["proxy"])
+        .await?;
+    tcp.create_outlet("node", listener_address.clone()).await?;
+    let routes = ProxyRoutes::new().add(&listener_address, route!["node"])?;
+    let (_, http_addr) = tcp.create_http_proxy_inlet("127.0.0.1:0", routes).await?;
+
+    let proxies = [
+        UpstreamProxy::new(ProxyProtocol::Socks5, socks_addr.to_string()),
+        UpstreamProxy::new(ProxyProtocol::HttpConnect, http_addr.to_string()),
+    ];
+    for proxy in proxies {
+        // Connections to loopback addresses go through the proxy too
+        let proxy = proxy.with_bypass(EgressPolicy::default());
+        tcp.set_proxy(Some(proxy.clone()));
+        assert_eq!(tcp.proxy(), Some(proxy));
+
+        tcp.connect(&listener_address).await?;
+        let msg = format!("{:x}", random::<u128>());
+        ctx.send(
+            route![(TCP, listener_address.clone()), "echoer"],
+            msg.clone(),
+        )
+        .await?;
+        let reply = ctx.receive::<String>().await?;
+        assert_eq!(reply.take().body(), msg);
+        tcp.disconnect(&listener_address).await?;
+    }
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+    Ok(())
+}
@@ -93,3 +93,129 @@ async fn tcp_lifecycle__reconnect__should_not_error(ctx: &mut Context) -> Result
 
     Ok(())
 }
+
+#[ockam_macros::test]
+async fn stopped_listeners_release_their_address(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let listener_address = transport.listen("127.0.0.1:0").await?;
+    assert!(transport
+        .listen(listener_address.to_string())
+        .await
+        .is_err());
+
+    transport.stop_listener(listener_address).await?;
+    let rebound = transport.listen(listener_address.to_string()).await?;
+    assert_eq!(rebound, listener_address);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[ockam_macros::test]
+async fn listeners_share_a_port_with_reuse_port(ctx: &mut Context) -> Result<()> {
+    use ockam_transport_tcp::ListenerOptions;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let options = ListenerOptions::new()
+        .with_reuse_port(true)
+        .with_backlog(16);
+    let listener_address = transport
+        .listen_with_options("127.0.0.1:0", options.clone())
+        .await?;
+    let shared = transport
+        .listen_with_options(listener_address.to_string(), options)
+        .await?;
+    assert_eq!(shared, listener_address);
+
+    // Listeners without the option can't share it
+    assert!(transport
+        .listen(listener_address.to_string())
+        .await
+        .is_err());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[ockam_macros::test(timeout = 5000)]
+async fn connections_with_a_reconnect_backoff_are_opened_again(ctx: &mut Context) -> Result<()> {
+    use core::time::Duration;
+    use ockam_transport_tcp::{ReconnectBackoff, TcpConnectionOptions};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::oneshot;
+
+    ctx.start_worker("echoer", Echoer).await?;
+    let transport = TcpTransport::create(ctx).await?;
+    let listener_address = transport.listen("127.0.0.1:0").await?;
+
+    // The peer closes the first connection, and relays the next one to the
+    // listener
+    let peer = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let peer_address = peer.local_addr().unwrap().to_string();
+    let (reconnected, is_reconnected) = oneshot::channel();
+    tokio::spawn(async move {
+        drop(peer.accept().await.unwrap());
+        let (mut inbound, _) = peer.accept().await.unwrap();
+        let mut outbound = TcpStream::connect(listener_address).await.unwrap();
+        reconnected.send(()).unwrap();
+        let _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+    });
+
+    let backoff = ReconnectBackoff::new(Duration::from_millis(10), Duration::from_millis(100), 5);
+    let options = TcpConnectionOptions::new()
+        .with_keepalive(Duration::from_secs(15))
+        .with_nodelay(true)
+        .with_reconnect(backoff);
+    transport
+        .connect_with_options(&peer_address, options)
+        .await?;
+    is_reconnected.await.unwrap();
+
+    let msg = "reconnected".to_string();
+    ctx.send(route![(TCP, peer_address), "echoer"], msg.clone())
+        .await?;
+    let reply = ctx.receive::<String>().await?;
+    assert_eq!(reply.take().body(), msg);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[ockam_macros::test(timeout = 5000)]
+async fn listeners_accept_connections_with_several_workers(ctx: &mut Context) -> Result<()> {
+    use ockam_transport_tcp::ListenerOptions;
+
+    ctx.start_worker("echoer", Echoer).await?;
+    let transport = TcpTransport::create(ctx).await?;
+    let options = ListenerOptions::new().with_accept_workers(4);
+    let listener_address = transport
+        .listen_with_options("127.0.0.1:0", options)
+        .await?;
+
+    let msg = "accepted".to_string();
+    let r = route![(TCP, listener_address.to_string()), "echoer"];
+    let reply = ctx.send_and_receive::<_, _, String>(r, msg.clone()).await?;
+    assert_eq!(reply, msg);
+
+    // Stopping the listener closes the sockets of all its workers
+    transport.stop_listener(listener_address).await?;
+    let rebound = transport.listen(listener_address.to_string()).await?;
+    assert_eq!(rebound, listener_address);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,201 @@
+#![cfg(feature = "tls")]
+
+use std::convert::TryFrom;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::rustls::server::AllowAnyAuthenticatedClient;
+use tokio_rustls::rustls::{
+    Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig, ServerName,
+};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+use ockam_core::compat::rand::random;
+use ockam_core::{route, AllowAll, Result, Routed, Worker};
+use ockam_node::Context;
+use ockam_transport_tcp::tls::{InletTls, OutletTls, SelfSignedCert};
+use ockam_transport_tcp::{InletOptions, ListenerOptions, OutletOptions, TcpTransport, TCP};
+
+fn certs(path: &Path) -> Vec<Certificate> {
+    let pem = std::fs::read(path).unwrap();
+    let certs = rustls_pemfile::certs(&mut pem.as_slice()).unwrap();
+    certs.into_iter().map(Certificate).collect()
+}
+
+fn roots(path: &Path) -> RootCertStore {
+    let mut roots = RootCertStore::empty();
+    for c in certs(path) {
+        roots.add(&c).unwrap();
+    }
+    roots
+}
+
+fn connector(cert: &Path) -> TlsConnector {
+    let roots = roots(cert);
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    TlsConnector::from(Arc::new(config))
+}
+
+fn temp_dir() -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("ockam-tls-{}", random::<u32>()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__tls_inlet__should_terminate_tls(ctx: &mut Context) -> Result<()> {
+    let dir = temp_dir();
+    let (cert, key) = (dir.join("inlet.crt"), dir.join("inlet.key"));
+    SelfSignedCert::generate(&["localhost", "127.0.0.1"])?.write(&cert, &key)?;
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    tcp.create_outlet("outlet", listener.local_addr().unwrap().to_string())
+        .await?;
+    let options = InletOptions::new("127.0.0.1:0".into(), route!["outlet"], Arc::new(AllowAll))
+        .with_tls(InletTls::from_pem_files(&cert, &key)?);
+    let (_, inlet_addr) = tcp.create_inlet_extended(options).await?;
+
+    let payload1: [u8; 32] = random();
+    let payload2: [u8; 32] = random();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 32];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, payload1);
+        stream.write_all(&payload2).await.unwrap();
+    });
+
+    let stream = TcpStream::connect(inlet_addr).await.unwrap();
+    let name = ServerName::try_from("localhost").unwrap();
+    let mut stream = connector(&cert).connect(name, stream).await.unwrap();
+    stream.write_all(&payload1).await.unwrap();
+    stream.flush().await.unwrap();
+    let mut buf = [0u8; 32];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(buf, payload2);
+
+    // A renewed certificate is served to new connections
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    SelfSignedCert::generate(&["localhost"])?.write(&cert, &key)?;
+    let stream = TcpStream::connect(inlet_addr).await.unwrap();
+    let name = ServerName::try_from("localhost").unwrap();
+    assert!(connector(&cert).connect(name, stream).await.is_ok());
+
+    let _ = std::fs::remove_dir_all(&dir);
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__mtls_outlet__should_authenticate_to_target(ctx: &mut Context) -> Result<()> {
+    let dir = temp_dir();
+    let (server_cert, server_key) = (dir.join("server.crt"), dir.join("server.key"));
+    let (client_cert, client_key) = (dir.join("client.crt"), dir.join("client.key"));
+    SelfSignedCert::generate(&["target.local"])?.write(&server_cert, &server_key)?;
+    SelfSignedCert::generate(&["outlet"])?.write(&client_cert, &client_key)?;
+
+    // The target only accepts clients with the client certificate
+    let key =
+        rustls_pemfile::pkcs8_private_keys(&mut std::fs::read(&server_key).unwrap().as_slice())
+            .unwrap()
+            .remove(0);
+    let config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots(&client_cert)))
+        .with_single_cert(certs(&server_cert), PrivateKey(key))
+        .unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let target = listener.local_addr().unwrap().to_string();
+
+    let payload1: [u8; 32] = random();
+    let payload2: [u8; 32] = random();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut stream = acceptor.accept(stream).await.unwrap();
+        assert!(stream.get_ref().1.peer_certificates().is_some());
+        let mut buf = [0u8; 32];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, payload1);
+        stream.write_all(&payload2).await.unwrap();
+        stream.flush().await.unwrap();
+    });
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let tls = OutletTls::new(
+        "target.local",
+        Some(server_cert.clone()),
+        Some((client_cert.clone(), client_key.clone())),
+    )?;
+    let options = OutletOptions::new("outlet".into(), target, Arc::new(AllowAll)).with_tls(tls);
+    tcp.create_outlet_extended(options).await?;
+    let (_, inlet_addr) = tcp.create_inlet("127.0.0.1:0", route!["outlet"]).await?;
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    stream.write_all(&payload1).await.unwrap();
+    let mut buf = [0u8; 32];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(buf, payload2);
+
+    let _ = std::fs::remove_dir_all(&dir);
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+    Ok(())
+}
+
+struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn transport__tls_connection__should_send_and_receive(ctx: &mut Context) -> Result<()> {
+    let dir = temp_dir();
+    let (cert, key) = (dir.join("node.crt"), dir.join("node.key"));
+    SelfSignedCert::generate(&["localhost"])?.write(&cert, &key)?;
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let options = ListenerOptions::new().with_tls(InletTls::from_pem_files(&cert, &key)?);
+    let listener_address = tcp
+        .listen_with_options("127.0.0.1:0", options)
+        .await?
+        .to_string();
+
+    let tls = OutletTls::new("localhost", Some(cert.clone()), None)?;
+    tcp.connect_with_tls(&listener_address, tls).await?;
+    let msg = format!("{:x}", random::<u128>());
+    ctx.send(
+        route![(TCP, listener_address.clone()), "echoer"],
+        msg.clone(),
+    )
+    .await?;
+    let reply = ctx.receive::<String>().await?;
+    assert_eq!(reply.take().body(), msg);
+
+    let _ = std::fs::remove_dir_all(&dir);
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+    Ok(())
+}
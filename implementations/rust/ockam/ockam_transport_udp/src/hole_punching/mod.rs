@@ -0,0 +1,16 @@
+//! NAT hole punching
+//!
+//! Two nodes behind NATs can't reach each other directly, as their NATs
+//! drop the datagrams they didn't ask for. Both nodes send datagrams to
+//! a [`RendezvousService`] which they can reach, e.g. on a public node,
+//! to learn each other's reflexive address, i.e. the address of their
+//! socket on the far side of their NATs. Then a [`UdpHolePuncher`] on
+//! each node sends datagrams to the other one from the same socket: once
+//! both NATs have seen datagrams going out to the other node, they let
+//! the ones coming from it in.
+
+pub use puncher::*;
+pub use rendezvous::*;
+
+mod puncher;
+mod rendezvous;
@@ -0,0 +1,269 @@
+use std::time::Duration;
+
+use ockam_core::{
+    async_trait, route, Address, Any, AsyncTryClone, Decodable, Message, Result, Route, Routed,
+    Worker,
+};
+use ockam_node::{Context, DelayedEvent};
+use ockam_transport_core::TransportError;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use tracing::{debug, info};
+
+use super::{RendezvousRequest, RendezvousResponse};
+use crate::{UdpTransport, UDP};
+
+/// Time between the datagrams sent to the peer until the hole is open
+const PUNCH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Time between the datagrams keeping the hole open, well below the
+/// time after which NATs usually forget their UDP mappings
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Sent to each other by the punchers of two nodes
+#[derive(Serialize, Deserialize, Debug, Clone, Message)]
+enum PunchMessage {
+    Ping,
+    Pong,
+}
+
+/// Punches a hole through the NATs between this node and another one,
+/// so that they exchange datagrams directly
+///
+/// Both nodes start a puncher, named after each other, with the same
+/// [`RendezvousService`](crate::RendezvousService). The hole is kept
+/// open until the puncher is stopped.
+///
+/// ```rust
+/// use ockam_transport_udp::{UdpHolePuncher, UdpTransport, UDP};
+/// # use ockam_core::{route, Result, Route};
+/// # use ockam_node::Context;
+/// # use std::time::Duration;
+/// # async fn test(ctx: Context) -> Result<()> {
+/// let udp = UdpTransport::create(&ctx).await?;
+/// let rendezvous = route![(UDP, "rendezvous.example.com:4000"), "rendezvous"];
+/// let mut puncher = UdpHolePuncher::create(&ctx, &udp, "alice", "bob", rendezvous).await?;
+///
+/// // Relay the messages to bob through a forwarder if the NATs can't be punched
+/// let relay = route!["relay", "forward_to_bob"];
+/// let mut to_bob = puncher.peer_route_or(relay, Duration::from_secs(5)).await;
+/// let to_app: Route = to_bob.modify().append("app").into();
+/// ctx.send(to_app, "Hello Bob".to_string()).await?;
+/// # Ok(()) }
+/// ```
+pub struct UdpHolePuncher {
+    address: Address,
+    peer_route: watch::Receiver<Option<Route>>,
+}
+
+impl UdpHolePuncher {
+    /// Start a puncher named `name` punching a hole to the puncher named
+    /// `peer_name`, learning its address from the rendezvous service at
+    /// `rendezvous_route`
+    ///
+    /// The puncher runs at the address `name`. The first hop of
+    /// `rendezvous_route` must be over UDP.
+    pub async fn create(
+        ctx: &Context,
+        udp: &UdpTransport,
+        name: impl Into<String>,
+        peer_name: impl Into<String>,
+        rendezvous_route: impl Into<Route>,
+    ) -> Result<Self> {
+        let rendezvous_route = rendezvous_route.into();
+        let via = rendezvous_route.next()?.clone();
+        if via.transport_type() != UDP {
+            return Err(TransportError::InvalidAddress.into());
+        }
+
+        let name = name.into();
+        let address = Address::from(name.clone());
+        let (tx, rx) = watch::channel(None);
+        let worker = PuncherWorker {
+            name,
+            peer_name: peer_name.into(),
+            udp: udp.async_try_clone().await?,
+            rendezvous_route,
+            via,
+            rendezvous_addr: Address::random_local(),
+            tick: Address::random_local(),
+            timer: None,
+            peer: None,
+            opened: None,
+            peer_route: tx,
+        };
+        let addresses = vec![
+            address.clone(),
+            worker.rendezvous_addr.clone(),
+            worker.tick.clone(),
+        ];
+        ctx.start_worker(addresses, worker).await?;
+
+        Ok(Self {
+            address,
+            peer_route: rx,
+        })
+    }
+
+    /// The address of the puncher, to stop it
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// The direct route to the peer node, once the hole is open
+    pub fn peer_route(&self) -> Option<Route> {
+        self.peer_route.borrow().clone()
+    }
+
+    /// Wait up to `timeout` for the hole to open, returning the direct
+    /// route to the peer node
+    pub async fn wait_for_peer_route(&mut self, timeout: Duration) -> Option<Route> {
+        let peer_route = &mut self.peer_route;
+        let opened = async {
+            loop {
+                let route = peer_route.borrow().clone();
+                if route.is_some() {
+                    return route;
+                }
+                if peer_route.changed().await.is_err() {
+                    return None;
+                }
+            }
+        };
+        tokio::time::timeout(timeout, opened).await.ok().flatten()
+    }
+
+    /// The direct route to the peer node if the hole opens within
+    /// `timeout`, `fallback` otherwise, e.g. the route to the peer
+    /// through a relay
+    pub async fn peer_route_or(&mut self, fallback: impl Into<Route>, timeout: Duration) -> Route {
+        match self.wait_for_peer_route(timeout).await {
+            Some(route) => route,
+            None => {
+                info!("Could not punch a hole to the peer, using the fallback route");
+                fallback.into()
+            }
+        }
+    }
+}
+
+struct PuncherWorker {
+    name: String,
+    peer_name: String,
+    udp: UdpTransport,
+    rendezvous_route: Route,
+    /// First hop of `rendezvous_route`, whose socket sends to the peer
+    via: Address,
+    /// Address of the responses of the rendezvous service
+    rendezvous_addr: Address,
+    /// Address of the ticks of `timer`
+    tick: Address,
+    timer: Option<DelayedEvent<()>>,
+    /// Reflexive address of the peer, once known
+    peer: Option<Address>,
+    /// Direct route to the peer, once the hole is open
+    opened: Option<Route>,
+    peer_route: watch::Sender<Option<Route>>,
+}
+
+impl PuncherWorker {
+    async fn on_tick(&mut self, ctx: &Context) -> Result<()> {
+        // Keep the mapping of the NATs and learn the address of the peer,
+        // which may change when its NAT forgets its mapping
+        let requests = [
+            RendezvousRequest::Update {
+                name: self.name.clone(),
+            },
+            RendezvousRequest::Query {
+                name: self.peer_name.clone(),
+            },
+        ];
+        for request in requests {
+            let route = self.rendezvous_route.clone();
+            if let Err(e) = ctx
+                .send_from_address(route, request, self.rendezvous_addr.clone())
+                .await
+            {
+                debug!("Could not reach the rendezvous service: {}", e);
+            }
+        }
+        self.ping(ctx).await;
+
+        let interval = if self.opened.is_some() {
+            KEEPALIVE_INTERVAL
+        } else {
+            PUNCH_INTERVAL
+        };
+        if let Some(timer) = &mut self.timer {
+            timer.schedule(interval).await?;
+        }
+        Ok(())
+    }
+
+    async fn ping(&self, ctx: &Context) {
+        if let Some(peer) = &self.peer {
+            let route = route![peer.clone(), self.peer_name.clone()];
+            if let Err(e) = ctx.send(route, PunchMessage::Ping).await {
+                debug!("Could not ping {} at {}: {}", self.peer_name, peer, e);
+            }
+        }
+    }
+
+    async fn on_rendezvous(&mut self, ctx: &Context, msg: RendezvousResponse) -> Result<()> {
+        match msg {
+            RendezvousResponse::Update(reflexive) => {
+                debug!("Reflexive address of {}: {}", self.name, reflexive);
+            }
+            RendezvousResponse::Query(Some(peer)) if self.peer.as_ref() != Some(&peer) => {
+                debug!("Reflexive address of {}: {}", self.peer_name, peer);
+                // The peer expects datagrams from the address the
+                // rendezvous service told it
+                self.udp.share(self.via.clone(), peer.clone()).await?;
+                self.peer = Some(peer);
+                self.ping(ctx).await;
+            }
+            RendezvousResponse::Query(_) => {}
+        }
+        Ok(())
+    }
+
+    async fn on_peer(&mut self, ctx: &Context, msg: Routed<Any>) -> Result<()> {
+        let return_route = msg.return_route();
+        if let PunchMessage::Ping = PunchMessage::decode(msg.payload())? {
+            ctx.send(return_route.clone(), PunchMessage::Pong).await?;
+        }
+
+        // Datagrams of the peer get through, the hole is open
+        let peer_route = route![return_route.next()?.clone()];
+        if self.opened.as_ref() != Some(&peer_route) {
+            info!("Punched a hole to {} at {}", self.peer_name, peer_route);
+            self.opened = Some(peer_route.clone());
+            // The handle of the puncher may be gone
+            let _ = self.peer_route.send(Some(peer_route));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Worker for PuncherWorker {
+    type Message = Any;
+    type Context = Context;
+
+    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        self.timer = Some(DelayedEvent::create(ctx, self.tick.clone(), ()).await?);
+        self.on_tick(ctx).await
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let msg_addr = msg.msg_addr();
+        if msg_addr == self.tick {
+            self.on_tick(ctx).await
+        } else if msg_addr == self.rendezvous_addr {
+            let msg = RendezvousResponse::decode(msg.payload())?;
+            self.on_rendezvous(ctx, msg).await
+        } else {
+            self.on_peer(ctx, msg).await
+        }
+    }
+}
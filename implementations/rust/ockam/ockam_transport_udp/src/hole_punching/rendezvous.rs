@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use ockam_core::{async_trait, Address, Message, Result, Routed, Worker};
+use ockam_node::Context;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::UDP;
+
+/// Time after which the address recorded under a name is forgotten, unless
+/// it is updated again, several times the keepalive interval of punchers
+const ENTRY_TTL: Duration = Duration::from_secs(60);
+
+/// Maximum number of names recorded at once
+const MAX_ENTRIES: usize = 10_000;
+
+/// A request to a [`RendezvousService`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Message)]
+pub enum RendezvousRequest {
+    /// Record the reflexive address of the sender under a name
+    Update { name: String },
+    /// The reflexive address recorded under a name
+    Query { name: String },
+}
+
+/// A response of a [`RendezvousService`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Message)]
+pub enum RendezvousResponse {
+    /// The reflexive address of the sender
+    Update(Address),
+    /// The reflexive address recorded under the name, if any
+    Query(Option<Address>),
+}
+
+/// Records the reflexive addresses of nodes, i.e. the addresses their
+/// datagrams come from, so that other nodes can send datagrams to them
+///
+/// The service must be reached over UDP, usually on a public node.
+///
+/// A name belongs to the address which recorded it until the address is
+/// no longer updated for a minute: the updates of the same name from
+/// other addresses are ignored in the meantime.
+pub struct RendezvousService {
+    addresses: BTreeMap<String, Entry>,
+    ttl: Duration,
+    max_entries: usize,
+}
+
+struct Entry {
+    address: Address,
+    expires: Instant,
+}
+
+impl Default for RendezvousService {
+    fn default() -> Self {
+        Self {
+            addresses: BTreeMap::new(),
+            ttl: ENTRY_TTL,
+            max_entries: MAX_ENTRIES,
+        }
+    }
+}
+
+impl RendezvousService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `address` under `name`, returning whether it was recorded
+    fn update(&mut self, name: String, address: Address, now: Instant) -> bool {
+        match self.addresses.get_mut(&name) {
+            Some(e) if e.expires > now && e.address != address => false,
+            Some(e) => {
+                e.address = address;
+                e.expires = now + self.ttl;
+                true
+            }
+            None => {
+                if self.addresses.len() >= self.max_entries {
+                    self.addresses.retain(|_, e| e.expires > now);
+                }
+                if self.addresses.len() >= self.max_entries {
+                    return false;
+                }
+                let expires = now + self.ttl;
+                self.addresses.insert(name, Entry { address, expires });
+                true
+            }
+        }
+    }
+
+    /// The address recorded under `name`, if it has not expired
+    fn query(&self, name: &str, now: Instant) -> Option<Address> {
+        self.addresses
+            .get(name)
+            .filter(|e| e.expires > now)
+            .map(|e| e.address.clone())
+    }
+}
+
+#[async_trait]
+impl Worker for RendezvousService {
+    type Message = RendezvousRequest;
+    type Context = Context;
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Context,
+        msg: Routed<RendezvousRequest>,
+    ) -> Result<()> {
+        let return_route = msg.return_route();
+        match msg.body() {
+            RendezvousRequest::Update { name } => {
+                // The first hop back is the socket the datagram came from
+                let reflexive = match return_route.next() {
+                    Ok(addr) if addr.transport_type() == UDP => addr.clone(),
+                    _ => {
+                        warn!("Ignoring update of {} which did not come over UDP", name);
+                        return Ok(());
+                    }
+                };
+                if !self.update(name.clone(), reflexive.clone(), Instant::now()) {
+                    warn!("Ignoring update of {} from {}", name, reflexive);
+                    return Ok(());
+                }
+                debug!("Rendezvous update: {} => {}", name, reflexive);
+                ctx.send(return_route, RendezvousResponse::Update(reflexive))
+                    .await
+            }
+            RendezvousRequest::Query { name } => {
+                let reflexive = self.query(&name, Instant::now());
+                ctx.send(return_route, RendezvousResponse::Query(reflexive))
+                    .await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn udp(addr: &str) -> Address {
+        Address::new(UDP, addr)
+    }
+
+    #[test]
+    fn names_belong_to_their_address_until_they_expire() {
+        let mut service = RendezvousService::new();
+        let now = Instant::now();
+        assert!(service.update("alice".into(), udp("1.1.1.1:4000"), now));
+        assert!(!service.update("alice".into(), udp("6.6.6.6:4000"), now));
+        assert_eq!(service.query("alice", now), Some(udp("1.1.1.1:4000")));
+
+        // The owner keeps its name by refreshing it
+        let later = now + ENTRY_TTL / 2;
+        assert!(service.update("alice".into(), udp("1.1.1.1:4000"), later));
+        assert!(!service.update("alice".into(), udp("6.6.6.6:4000"), now + ENTRY_TTL));
+
+        // Once expired, the name can be recorded from anywhere
+        let expired = later + ENTRY_TTL;
+        assert_eq!(service.query("alice", expired), None);
+        assert!(service.update("alice".into(), udp("2.2.2.2:4000"), expired));
+        assert_eq!(service.query("alice", expired), Some(udp("2.2.2.2:4000")));
+    }
+
+    #[test]
+    fn the_number_of_names_is_capped() {
+        let mut service = RendezvousService {
+            max_entries: 2,
+            ..Default::default()
+        };
+        let now = Instant::now();
+        assert!(service.update("alice".into(), udp("1.1.1.1:4000"), now));
+        assert!(service.update("bob".into(), udp("2.2.2.2:4000"), now));
+        assert!(!service.update("carol".into(), udp("3.3.3.3:4000"), now));
+
+        // Expired names make room for new ones
+        let expired = now + ENTRY_TTL;
+        assert!(service.update("carol".into(), udp("3.3.3.3:4000"), expired));
+        assert_eq!(service.addresses.len(), 1);
+    }
+}
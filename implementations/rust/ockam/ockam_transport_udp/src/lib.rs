@@ -1,9 +1,11 @@
 use std::net::SocketAddr;
 
+pub use hole_punching::*;
 use ockam_core::{Result, TransportType};
 use ockam_transport_core::TransportError;
 pub use transport::*;
 
+mod hole_punching;
 mod router;
 mod transport;
 mod workers;
@@ -12,6 +14,12 @@ pub const UDP: TransportType = TransportType::new(2);
 
 pub const CLUSTER_NAME: &str = "_internals.transport.udp";
 
+/// Largest UDP payload over IPv4
+///
+/// A message is sent in a single datagram, along with its length, so
+/// the messages which don't fit are refused rather than fragmented by IP.
+pub const MAX_DATAGRAM_SIZE: usize = 65507;
+
 fn parse_socket_addr<S: AsRef<str>>(s: S) -> Result<SocketAddr> {
     Ok(s.as_ref()
         .parse()
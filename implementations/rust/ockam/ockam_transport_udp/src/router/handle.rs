@@ -1,7 +1,4 @@
-use std::{
-    net::{SocketAddr, ToSocketAddrs},
-    str::FromStr,
-};
+use std::net::{SocketAddr, ToSocketAddrs};
 
 use futures_util::stream::StreamExt;
 use ockam_core::{async_trait, Address, AsyncTryClone, Result};
@@ -16,7 +13,7 @@ use crate::{
     UdpAddress,
 };
 
-use super::UdpRouterMessage;
+use super::{UdpRouterMessage, UdpRouterResponse};
 
 /// A handle to connect to a UdpRouter
 ///
@@ -68,10 +65,13 @@ impl UdpRouterHandle {
     }
 
     /// Bind a listener with given address for this router
-    pub async fn bind(&self, addr: impl Into<SocketAddr>) -> Result<()> {
+    ///
+    /// Returns the local address the socket is bound to.
+    pub async fn bind(&self, addr: impl Into<SocketAddr>) -> Result<SocketAddr> {
         let socket = UdpSocket::bind(addr.into())
             .await
             .map_err(TransportError::from)?;
+        let bind_addr = socket.local_addr().map_err(TransportError::from)?;
         let (sink, stream) = UdpFramed::new(socket, TransportMessageCodec).split();
 
         let tx_addr = Address::random_local();
@@ -80,7 +80,32 @@ impl UdpRouterHandle {
         UdpListenProcessor::start(&self.ctx, stream, tx_addr, self.async_try_clone().await?)
             .await?;
 
-        Ok(())
+        Ok(bind_addr)
+    }
+
+    /// Open a socket to send datagrams to the given peer
+    ///
+    /// Returns the address to route messages to the peer with.
+    pub async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<Address> {
+        let response = self
+            .ctx
+            .send_and_receive(
+                self.api_addr.clone(),
+                UdpRouterMessage::Connect {
+                    peer: peer.as_ref().to_string(),
+                },
+            )
+            .await?;
+
+        let UdpRouterResponse::Connect(res) = response;
+        res
+    }
+
+    /// Send to `peer` from the socket which sends to `via`
+    pub(crate) async fn share(&self, via: Address, peer: Address) -> Result<()> {
+        self.ctx
+            .send(self.api_addr.clone(), UdpRouterMessage::Share { via, peer })
+            .await
     }
 
     /// Register a new worker with this router
@@ -90,8 +115,7 @@ impl UdpRouterHandle {
         accepts.extend(
             hostnames
                 .iter()
-                .filter_map(|s| UdpAddress::from_str(s).ok())
-                .map(|addr| addr.into()),
+                .map(|s| Address::new(crate::UDP, s.clone())),
         );
 
         // TODO: should we send a router request instead
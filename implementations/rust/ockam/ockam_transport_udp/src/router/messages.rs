@@ -1,4 +1,4 @@
-use ockam_core::{Address, Message};
+use ockam_core::{Address, Message, Result};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, Ord, PartialOrd, Eq, PartialEq, Message)]
@@ -10,4 +10,19 @@ pub(crate) enum UdpRouterMessage {
         /// The clients own worker bus address.
         self_addr: Address,
     },
+    /// Connect
+    Connect { peer: String },
+    /// Send to `peer` from the socket which sends to `via`, so that
+    /// the peer sees the address `via` sees, e.g. to punch NATs.
+    Share {
+        /// An address with a registered sender.
+        via: Address,
+        /// The address to register with the same sender.
+        peer: Address,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Message)]
+pub(crate) enum UdpRouterResponse {
+    Connect(Result<Address>),
 }
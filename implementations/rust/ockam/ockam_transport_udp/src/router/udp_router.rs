@@ -1,5 +1,6 @@
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::ops::Deref;
-use std::{collections::BTreeMap, str::FromStr};
 
 use futures_util::StreamExt;
 use ockam_core::{async_trait, Address, Any, Decodable, LocalMessage, Result, Routed, Worker};
@@ -10,7 +11,7 @@ use tokio::net::UdpSocket;
 use tokio_util::udp::UdpFramed;
 use tracing::{error, trace};
 
-use crate::router::{UdpRouterHandle, UdpRouterMessage};
+use crate::router::{UdpRouterHandle, UdpRouterMessage, UdpRouterResponse};
 use crate::transport::UdpAddress;
 use crate::workers::{TransportMessageCodec, UdpListenProcessor, UdpSendWorker};
 
@@ -119,8 +120,40 @@ impl UdpRouter {
         Ok(())
     }
 
+    /// Handle any [`UdpRouterMessage::Connect`] messages received by
+    /// this node's worker
+    ///
+    /// Returns the address to route messages to the peer with, opening
+    /// a socket for it unless there is already one.
+    async fn handle_connect(&mut self, peer: String) -> Result<Address> {
+        let (peer_addr, _) = UdpRouterHandle::resolve_peer(peer.clone())?;
+        let addr: Address = UdpAddress::from(peer_addr).into();
+        if !self.map.contains_key(&addr) {
+            self.connect(peer).await?;
+        }
+        Ok(addr)
+    }
+
+    /// Handle any [`UdpRouterMessage::Share`] messages received by
+    /// this node's worker
+    async fn handle_share(&mut self, via: Address, peer: Address) -> Result<()> {
+        let sender = match self.map.get(&via) {
+            Some(sender) => sender.clone(),
+            None => return Err(TransportError::UnknownRoute.into()),
+        };
+        trace!("UDP share request: {} => {} (as {})", peer, sender, via);
+        self.map.insert(peer, sender);
+        Ok(())
+    }
+
     async fn connect(&mut self, peer: String) -> Result<Address> {
-        let socket = UdpSocket::bind("127.0.0.1:0")
+        let (peer_addr, hostnames) = UdpRouterHandle::resolve_peer(peer)?;
+        // Send from any interface which can reach the peer
+        let ip = match peer_addr {
+            SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        let socket = UdpSocket::bind(SocketAddr::new(ip, 0))
             .await
             .map_err(TransportError::from)?;
         let (sink, stream) = UdpFramed::new(socket, TransportMessageCodec).split();
@@ -136,13 +169,11 @@ impl UdpRouter {
         )
         .await?;
 
-        let (peer, hostnames) = UdpRouterHandle::resolve_peer(peer)?;
-        let mut accepts: Vec<Address> = vec![UdpAddress::from(peer).into()];
+        let mut accepts: Vec<Address> = vec![UdpAddress::from(peer_addr).into()];
         accepts.extend(
             hostnames
                 .iter()
-                .filter_map(|s| UdpAddress::from_str(s).ok())
-                .map(|addr| addr.into()),
+                .map(|s| Address::new(crate::UDP, s.clone())),
         );
 
         self.handle_register(accepts, tx_addr.clone()).await?;
@@ -167,12 +198,21 @@ impl Worker for UdpRouter {
         if msg_addr == self.main_addr {
             self.handle_route(ctx, msg.into_local_message()).await?;
         } else if msg_addr == self.api_addr {
+            let return_route = msg.return_route();
             let msg = UdpRouterMessage::decode(msg.payload())?;
             match msg {
                 UdpRouterMessage::Register { accepts, self_addr } => {
                     trace!("handle_message register: {:?} => {:?}", accepts, self_addr);
                     self.handle_register(accepts, self_addr).await?;
                 }
+                UdpRouterMessage::Share { via, peer } => {
+                    self.handle_share(via, peer).await?;
+                }
+                UdpRouterMessage::Connect { peer } => {
+                    let res = self.handle_connect(peer).await;
+                    ctx.send(return_route, UdpRouterResponse::Connect(res))
+                        .await?;
+                }
             };
         } else {
             return Err(TransportError::InvalidAddress.into());
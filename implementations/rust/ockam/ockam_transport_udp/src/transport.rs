@@ -1,7 +1,7 @@
 use std::fmt;
 use std::{net::SocketAddr, str::FromStr};
 
-use ockam_core::{Address, Result};
+use ockam_core::{Address, AsyncTryClone, Result};
 use ockam_node::Context;
 
 use crate::{
@@ -11,6 +11,31 @@ use crate::{
 };
 
 /// High level management interface for UDP transports
+///
+/// Messages are sent in single datagrams, without retries or ordering,
+/// which suits low-latency traffic over lossy links. Messages larger than
+/// [`MAX_DATAGRAM_SIZE`](crate::MAX_DATAGRAM_SIZE) are refused.
+///
+/// To listen for incoming datagrams use
+/// [`udp.listen()`](crate::UdpTransport::listen).
+///
+/// To open a socket to a peer ahead of the first message, use
+/// [`udp.connect()`](crate::UdpTransport::connect).
+/// This step is optional because the underlying UdpRouter opens one
+/// upon arrival of an initial message.
+///
+/// ```rust
+/// use ockam_transport_udp::UdpTransport;
+/// # use ockam_node::Context;
+/// # use ockam_core::Result;
+/// # async fn test(ctx: Context) -> Result<()> {
+/// let udp = UdpTransport::create(&ctx).await?;
+/// udp.listen("127.0.0.1:8000").await?; // Listen on port 8000
+/// udp.connect("127.0.0.1:5000").await?; // And send to port 5000
+/// # Ok(()) }
+/// ```
+#[derive(AsyncTryClone)]
+#[async_try_clone(crate = "ockam_core")]
 pub struct UdpTransport {
     router_handle: UdpRouterHandle,
 }
@@ -22,13 +47,25 @@ impl UdpTransport {
         Ok(Self { router_handle })
     }
 
+    /// Open a socket to send datagrams to `peer` on an existing transport
+    ///
+    /// Returns the address of the peer, the first hop of the routes to it.
+    pub async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<Address> {
+        self.router_handle.connect(peer).await
+    }
+
     /// Start listening to incoming datagrams on an existing transport
-    pub async fn listen<S: AsRef<str>>(&self, bind_addr: S) -> Result<()> {
+    ///
+    /// Returns the local address that this transport is bound to.
+    pub async fn listen<S: AsRef<str>>(&self, bind_addr: S) -> Result<SocketAddr> {
         let bind_addr = parse_socket_addr(bind_addr)?;
         self.router_handle.bind(bind_addr).await
     }
 
-    // TODO: connect method for manually connecting.
+    /// Send to `peer` from the socket which sends to `via`
+    pub(crate) async fn share(&self, via: Address, peer: Address) -> Result<()> {
+        self.router_handle.share(via, peer).await
+    }
 }
 
 #[derive(Clone)]
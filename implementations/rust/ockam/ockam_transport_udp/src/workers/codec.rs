@@ -4,6 +4,11 @@ use ockam_core::{Decodable, Encodable};
 use ockam_transport_core::TransportError;
 use tokio_util::codec::{Decoder, Encoder};
 
+use crate::MAX_DATAGRAM_SIZE;
+
+/// Length of the prefix of the messages in a datagram
+const LENGTH_SIZE: usize = 2;
+
 pub(crate) struct TransportMessageCodec;
 
 impl Encoder<TransportMessage> for TransportMessageCodec {
@@ -11,6 +16,9 @@ impl Encoder<TransportMessage> for TransportMessageCodec {
     fn encode(&mut self, item: TransportMessage, dst: &mut BytesMut) -> Result<(), Self::Error> {
         let msg_buf = item.encode().map_err(|_| TransportError::SendBadMessage)?;
         let len = msg_buf.len();
+        if LENGTH_SIZE + len > MAX_DATAGRAM_SIZE {
+            return Err(TransportError::Capacity);
+        }
         dst.put_u16(len as u16);
         dst.put(&msg_buf[..]);
         Ok(())
@@ -25,10 +33,45 @@ impl Decoder for TransportMessageCodec {
             return Ok(None);
         }
 
+        // A truncated datagram is dropped, so that the next one is read
+        if src.len() < LENGTH_SIZE {
+            src.clear();
+            return Err(TransportError::RecvBadMessage);
+        }
         let len = src.get_u16() as usize;
+        if src.len() < len {
+            src.clear();
+            return Err(TransportError::RecvBadMessage);
+        }
         let msg = TransportMessage::decode(&src.split_to(len)[..])
             .map_err(|_| TransportError::RecvBadMessage)?;
 
         Ok(Some(msg))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::route;
+
+    #[test]
+    fn datagrams_are_bounded() {
+        let mut codec = TransportMessageCodec;
+        let mut buf = BytesMut::new();
+
+        let msg = TransportMessage::v1(route!["a"], route![], vec![1; 1000]);
+        codec.encode(msg, &mut buf).unwrap();
+        assert!(buf.len() < MAX_DATAGRAM_SIZE);
+        let msg = TransportMessage::v1(route!["a"], route![], vec![1; MAX_DATAGRAM_SIZE]);
+        assert_eq!(
+            codec.encode(msg, &mut BytesMut::new()),
+            Err(TransportError::Capacity)
+        );
+
+        // Truncated datagrams are dropped
+        buf.truncate(100);
+        assert_eq!(codec.decode(&mut buf), Err(TransportError::RecvBadMessage));
+        assert!(buf.is_empty());
+    }
+}
@@ -2,8 +2,9 @@ use futures_util::stream::SplitStream;
 use futures_util::StreamExt;
 use ockam_core::{async_trait, Address, LocalMessage, Processor, Result};
 use ockam_node::Context;
+use ockam_transport_core::TransportError;
 use tokio_util::udp::UdpFramed;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use crate::{router::UdpRouterHandle, transport::UdpAddress};
 
@@ -55,6 +56,11 @@ impl Processor for UdpListenProcessor {
         let (mut msg, addr) = match self.stream.next().await {
             Some(res) => match res {
                 Ok((msg, addr)) => (msg, addr),
+                // Datagrams may be truncated or forged, the next ones are read
+                Err(TransportError::RecvBadMessage) => {
+                    warn!("Dropping a malformed UDP datagram.");
+                    return Ok(true);
+                }
                 Err(_e) => {
                     info!("Failed to read message from UDP socket.");
                     return Ok(false);
@@ -51,11 +51,20 @@ impl Worker for UdpSendWorker {
             Err(_e) => return Err(TransportError::UnknownRoute.into()),
         };
 
-        if self.sink.send((msg, peer_addr)).await.is_err() {
-            warn!("Failed to send message to peer {}", peer_addr);
-            ctx.stop_worker(ctx.address()).await?;
+        match self.sink.send((msg, peer_addr)).await {
+            Ok(()) => Ok(()),
+            // The socket is still usable for the messages which fit in a datagram
+            Err(TransportError::Capacity) => {
+                warn!(
+                    "Dropping message to peer {}, it is larger than a datagram",
+                    peer_addr
+                );
+                Err(TransportError::Capacity.into())
+            }
+            Err(_) => {
+                warn!("Failed to send message to peer {}", peer_addr);
+                ctx.stop_worker(ctx.address()).await
+            }
         }
-
-        Ok(())
     }
 }
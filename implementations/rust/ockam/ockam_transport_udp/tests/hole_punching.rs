@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use ockam_core::{route, Result};
+use ockam_node::Context;
+
+use ockam_transport_udp::{RendezvousService, UdpHolePuncher, UdpTransport, UDP};
+
+#[ockam_macros::test]
+async fn punch_hole_through_rendezvous(ctx: &mut Context) -> Result<()> {
+    let udp = UdpTransport::create(ctx).await?;
+    let bind_address = udp.listen("127.0.0.1:0").await?;
+    ctx.start_worker("rendezvous", RendezvousService::new())
+        .await?;
+    let rendezvous = route![(UDP, bind_address.to_string()), "rendezvous"];
+
+    let mut alice = UdpHolePuncher::create(ctx, &udp, "alice", "bob", rendezvous.clone()).await?;
+    let mut bob = UdpHolePuncher::create(ctx, &udp, "bob", "alice", rendezvous.clone()).await?;
+    let timeout = Duration::from_secs(5);
+    assert!(alice.wait_for_peer_route(timeout).await.is_some());
+    assert!(bob.wait_for_peer_route(timeout).await.is_some());
+
+    // Without a peer the fallback route is used
+    let mut carol = UdpHolePuncher::create(ctx, &udp, "carol", "dave", rendezvous).await?;
+    let route = carol
+        .peer_route_or(route!["relay"], Duration::from_millis(500))
+        .await;
+    assert_eq!(route, route!["relay"]);
+
+    ctx.stop().await
+}
@@ -39,6 +39,26 @@ async fn send_receive(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+#[ockam_macros::test]
+async fn connect_then_send(ctx: &mut Context) -> Result<()> {
+    let transport = UdpTransport::create(ctx).await?;
+    let bind_address = transport.listen("127.0.0.1:0").await?;
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let peer = transport.connect(bind_address.to_string()).await?;
+    assert_eq!(peer, Address::new(UDP, bind_address.to_string()));
+    // Connecting again reuses the socket
+    assert_eq!(transport.connect(bind_address.to_string()).await?, peer);
+
+    let mut child = ctx.new_detached(Address::random_local()).await?;
+    child
+        .send(route![peer.clone(), "echoer"], "Hello".to_string())
+        .await?;
+    assert_eq!(child.receive::<String>().await?, "Hello".to_string());
+
+    ctx.stop().await
+}
+
 pub struct Echoer;
 
 #[ockam_core::worker]
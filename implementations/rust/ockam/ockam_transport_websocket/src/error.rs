@@ -3,7 +3,10 @@ use ockam_core::{
     Error,
 };
 use ockam_transport_core::TransportError;
+#[cfg(not(target_arch = "wasm32"))]
 use tokio_tungstenite::tungstenite::Error as TungsteniteError;
+#[cfg(target_arch = "wasm32")]
+use ws_stream_wasm::WsErr;
 
 /// A WebSocket connection worker specific error type.
 #[derive(Clone, Copy, Debug)]
@@ -40,6 +43,7 @@ impl From<WebSocketError> for Error {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl From<TungsteniteError> for WebSocketError {
     fn from(e: TungsteniteError) -> Self {
         match e {
@@ -58,7 +62,22 @@ impl From<TungsteniteError> for WebSocketError {
     }
 }
 
-#[cfg(test)]
+#[cfg(target_arch = "wasm32")]
+impl From<WsErr> for WebSocketError {
+    fn from(e: WsErr) -> Self {
+        match e {
+            WsErr::InvalidUrl { .. } | WsErr::ForbiddenPort => {
+                Self::Transport(TransportError::InvalidAddress)
+            }
+            WsErr::ConnectionFailed { .. } | WsErr::ConnectionNotOpen => {
+                Self::Transport(TransportError::ConnectionDrop)
+            }
+            _ => Self::Transport(TransportError::Protocol),
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
 mod test {
     use super::*;
     use ockam_core::compat::collections::HashMap;
@@ -1,13 +1,19 @@
 use core::str::FromStr;
+#[cfg(not(target_arch = "wasm32"))]
 use std::net::{SocketAddr, ToSocketAddrs};
 
 use ockam_core::{async_trait, Address, AsyncTryClone, Result};
 use ockam_node::Context;
+#[cfg(not(target_arch = "wasm32"))]
 use ockam_transport_core::TransportError;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::parse_socket_addr;
 use crate::router::WebSocketRouterMessage;
-use crate::workers::{WebSocketListenProcessor, WorkerPair};
-use crate::{parse_socket_addr, WebSocketAddress};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::workers::WebSocketListenProcessor;
+use crate::workers::WorkerPair;
+use crate::WebSocketAddress;
 
 /// A handle to connect to a WebSocketRouter.
 ///
@@ -50,12 +56,14 @@ impl WebSocketRouterHandle {
     }
 
     /// Bind an incoming connection listener for this router.
+    #[cfg(not(target_arch = "wasm32"))]
     pub(crate) async fn bind(&self, addr: impl Into<SocketAddr>) -> Result<SocketAddr> {
         let socket_addr = addr.into();
         WebSocketListenProcessor::start(&self.ctx, self.async_try_clone().await?, socket_addr).await
     }
 
     /// Return the peer's `SocketAddr` and `hostnames` given a plain `String` address.
+    #[cfg(not(target_arch = "wasm32"))]
     pub(crate) fn resolve_peer(peer: impl Into<String>) -> Result<(SocketAddr, Vec<String>)> {
         let peer_str = peer.into();
         let peer_addr;
@@ -84,6 +92,7 @@ impl WebSocketRouterHandle {
     }
 
     /// Establish an outgoing WS connection on an existing transport.
+    #[cfg(not(target_arch = "wasm32"))]
     pub(crate) async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<()> {
         // Get peer address and connect to it.
         let (peer_addr, hostnames) = Self::resolve_peer(peer.as_ref())?;
@@ -95,4 +104,12 @@ impl WebSocketRouterHandle {
         // Handle node's register request.
         self.register(&pair).await
     }
+
+    /// Establish an outgoing WS connection from a browser, to a URL or
+    /// to a `host:port` pair.
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<()> {
+        let pair = WorkerPair::from_browser(&self.ctx, peer.as_ref().to_string()).await?;
+        self.register(&pair).await
+    }
 }
@@ -182,12 +182,16 @@ impl WebSocketRouter {
     }
 
     async fn connect(&mut self, peer: String) -> Result<Address> {
-        // Get peer address and connect to it.
-        let (peer_addr, hostnames) = WebSocketRouterHandle::resolve_peer(peer)?;
-
         // Create a new `WorkerPair` for the given peer, initializing a new pair
         // of sender worker and receiver processor.
-        let pair = WorkerPair::from_client(&self.ctx, peer_addr, hostnames).await?;
+        #[cfg(not(target_arch = "wasm32"))]
+        let pair = {
+            // Get peer address and connect to it.
+            let (peer_addr, hostnames) = WebSocketRouterHandle::resolve_peer(peer)?;
+            WorkerPair::from_client(&self.ctx, peer_addr, hostnames).await?
+        };
+        #[cfg(target_arch = "wasm32")]
+        let pair = WorkerPair::from_browser(&self.ctx, peer).await?;
 
         // Handle node's register request.
         let mut accepts = vec![pair.peer()];
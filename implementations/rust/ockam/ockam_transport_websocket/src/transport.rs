@@ -22,6 +22,10 @@ use crate::{parse_socket_addr, WebSocketRouter, WebSocketRouterHandle, WS};
 /// This step is optional because the underlying WebSocketRouter is capable of lazily
 /// establishing a connection upon arrival of an initial message.
 ///
+/// Built for `wasm32`, e.g. to run in a browser, the transport connects with
+/// the WebSocket API of the browser, to `host:port` pairs or to `ws://` and
+/// `wss://` URLs, and can't listen.
+///
 /// ```rust
 /// use ockam_transport_websocket::WebSocketTransport;
 /// # use ockam_core::Result;
@@ -96,6 +100,7 @@ impl WebSocketTransport {
     /// let ws = WebSocketTransport::create(&ctx).await?;
     /// ws.listen("127.0.0.1:8000").await?;
     /// # Ok(()) }
+    #[cfg(not(target_arch = "wasm32"))]
     pub async fn listen<S: AsRef<str>>(&self, bind_addr: S) -> Result<SocketAddr> {
         let bind_addr = parse_socket_addr(bind_addr)?;
         self.router_handle.bind(bind_addr).await
@@ -0,0 +1,166 @@
+use futures_util::stream::{SplitSink, SplitStream};
+use futures_util::{SinkExt, StreamExt};
+use send_wrapper::SendWrapper;
+use ws_stream_wasm::{WsMessage, WsMeta, WsStream};
+
+use crate::error::WebSocketError;
+use ockam_core::{
+    async_trait, Address, Any, Decodable, Encodable, LocalMessage, Processor, Result, Routed,
+    TransportMessage, Worker,
+};
+use ockam_node::Context;
+use ockam_transport_core::TransportError;
+
+use crate::workers::WorkerPair;
+use crate::WS;
+
+/// The URL of a peer, `ws://` unless its scheme is given, e.g. to connect
+/// with `wss://` to a node behind a TLS terminating proxy
+pub(crate) fn peer_url(peer: &str) -> String {
+    if peer.contains("://") {
+        peer.to_string()
+    } else {
+        format!("ws://{}", peer)
+    }
+}
+
+impl WorkerPair {
+    /// Spawn a `BrowserSendWorker` connecting to `peer` with the WebSocket
+    /// API of the browser, and returns a `WorkerPair` instance that will be
+    /// registered by the `WebSocketRouter`.
+    ///
+    /// The `BrowserRecvProcessor` is started once the connection is open.
+    pub(crate) async fn from_browser(ctx: &Context, peer: String) -> Result<WorkerPair> {
+        trace!("Creating new WS worker pair from a browser");
+
+        let peer_addr = Address::new(WS, peer.clone());
+        let sender = BrowserSendWorker {
+            url: peer_url(&peer),
+            peer: peer_addr.clone(),
+            ws_meta: None,
+            ws_sink: None,
+        };
+
+        let tx_addr = Address::random_local();
+        ctx.start_worker(tx_addr.clone(), sender).await?;
+
+        // Return a handle to the worker pair
+        Ok(WorkerPair {
+            hostnames: vec![],
+            peer: peer_addr,
+            tx_addr,
+        })
+    }
+}
+
+/// The sending half of a WebSocket connection opened by a browser.
+///
+/// JavaScript values can't leave the thread which created them, which is
+/// the only thread of a browser tab, so they are wrapped to be owned by
+/// workers.
+pub(crate) struct BrowserSendWorker {
+    url: String,
+    peer: Address,
+    /// Kept for the connection to stay open as long as the worker
+    ws_meta: Option<SendWrapper<WsMeta>>,
+    ws_sink: Option<SendWrapper<SplitSink<WsStream, WsMessage>>>,
+}
+
+#[async_trait::async_trait]
+impl Worker for BrowserSendWorker {
+    type Message = Any;
+    type Context = Context;
+
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        ctx.set_cluster(crate::CLUSTER_NAME).await?;
+
+        let (meta, stream) = SendWrapper::new(WsMeta::connect(self.url.as_str(), None))
+            .await
+            .map_err(WebSocketError::from)?;
+        let (ws_sink, ws_stream) = stream.split();
+        self.ws_meta = Some(SendWrapper::new(meta));
+        self.ws_sink = Some(SendWrapper::new(ws_sink));
+
+        let receiver = BrowserRecvProcessor {
+            ws_stream: SendWrapper::new(ws_stream),
+            peer: self.peer.clone(),
+        };
+        ctx.start_processor(Address::random_local(), receiver).await
+    }
+
+    /// Receive messages from the `WebSocketRouter` to send
+    /// to the remote peer.
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let ws_sink = match &mut self.ws_sink {
+            Some(ws_sink) => ws_sink,
+            None => return Err(TransportError::PeerNotFound.into()),
+        };
+
+        let mut msg = LocalMessage::decode(msg.payload())?.into_transport_message();
+
+        // Remove our own address from the route so the other end
+        // knows what to do with the incoming message
+        msg.onward_route.step()?;
+
+        let msg = WsMessage::Binary(msg.encode()?);
+        if SendWrapper::new(ws_sink.send(msg)).await.is_err() {
+            warn!("Failed to send message to peer {}", self.peer);
+            ctx.stop_worker(ctx.address()).await?;
+            return Ok(());
+        }
+        debug!("Sent message to peer {}", self.peer);
+        Ok(())
+    }
+}
+
+/// The receiving half of a WebSocket connection opened by a browser.
+pub(crate) struct BrowserRecvProcessor {
+    ws_stream: SendWrapper<SplitStream<WsStream>>,
+    peer: Address,
+}
+
+#[async_trait::async_trait]
+impl Processor for BrowserRecvProcessor {
+    type Context = Context;
+
+    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        ctx.set_cluster(crate::CLUSTER_NAME).await
+    }
+
+    /// Get next message from the WebSocket stream if there is
+    /// any available, and forward it to the next hop in the route.
+    async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
+        let encoded_msg = match SendWrapper::new(self.ws_stream.next()).await {
+            Some(WsMessage::Binary(data)) => data,
+            Some(WsMessage::Text(_)) => {
+                warn!("Dropping a text message from peer '{}'", self.peer);
+                return Ok(true);
+            }
+            None => {
+                info!(
+                    "Connection to peer '{}' was closed; dropping stream",
+                    self.peer
+                );
+                return Ok(false);
+            }
+        };
+
+        // Deserialize the message
+        let mut msg =
+            TransportMessage::decode(&encoded_msg).map_err(|_| TransportError::RecvBadMessage)?;
+
+        // Heartbeat message
+        if msg.onward_route.next().is_err() {
+            trace!("Got heartbeat message from: {}", self.peer);
+        }
+
+        // Insert the peer address into the return route so that
+        // reply routing can be properly resolved
+        msg.return_route.modify().prepend(self.peer.clone());
+
+        // Forward the message to the next hop in the route
+        ctx.forward(LocalMessage::new(msg, Vec::new())).await?;
+
+        Ok(true)
+    }
+}
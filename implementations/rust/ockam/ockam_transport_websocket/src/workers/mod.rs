@@ -1,9 +1,41 @@
+use ockam_core::Address;
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) use browser::*;
+#[cfg(not(target_arch = "wasm32"))]
 pub(crate) use listener::*;
+#[cfg(not(target_arch = "wasm32"))]
 pub(crate) use receiver::*;
-pub(crate) use sender::*;
+#[cfg(not(target_arch = "wasm32"))]
 pub(crate) use stream::*;
 
+#[cfg(target_arch = "wasm32")]
+mod browser;
+#[cfg(not(target_arch = "wasm32"))]
 mod listener;
+#[cfg(not(target_arch = "wasm32"))]
 mod receiver;
+#[cfg(not(target_arch = "wasm32"))]
 mod sender;
+#[cfg(not(target_arch = "wasm32"))]
 mod stream;
+
+/// Transmit and receive peers of a WebSocket connection.
+#[derive(Debug)]
+pub(crate) struct WorkerPair {
+    hostnames: Vec<String>,
+    peer: Address,
+    tx_addr: Address,
+}
+
+impl WorkerPair {
+    pub(crate) fn hostnames(&self) -> &[String] {
+        &self.hostnames
+    }
+    pub(crate) fn peer(&self) -> Address {
+        self.peer.clone()
+    }
+    pub(crate) fn tx_addr(&self) -> Address {
+        self.tx_addr.clone()
+    }
+}
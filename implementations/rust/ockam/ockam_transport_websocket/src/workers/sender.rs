@@ -15,28 +15,11 @@ use ockam_transport_core::TransportError;
 
 use crate::workers::{
     AsyncStream, TcpClientStream, TcpServerStream, WebSocketRecvProcessor, WebSocketStream,
+    WorkerPair,
 };
 use crate::WebSocketAddress;
 
-/// Transmit and receive peers of a WebSocket connection.
-#[derive(Debug)]
-pub(crate) struct WorkerPair {
-    hostnames: Vec<String>,
-    peer: Address,
-    tx_addr: Address,
-}
-
 impl WorkerPair {
-    pub(crate) fn hostnames(&self) -> &[String] {
-        &self.hostnames
-    }
-    pub(crate) fn peer(&self) -> Address {
-        self.peer.clone()
-    }
-    pub(crate) fn tx_addr(&self) -> Address {
-        self.tx_addr.clone()
-    }
-
     /// Spawn instances of `WebSocketSendWorker` and `WebSocketRecvProcessor` and
     /// returns a `WorkerPair` instance that will be registered by the `WebSocketRouter`.
     ///
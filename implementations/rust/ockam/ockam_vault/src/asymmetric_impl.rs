@@ -1,7 +1,7 @@
 use crate::{Vault, VaultError};
 use arrayref::array_ref;
 use ockam_core::vault::{
-    AsymmetricVault, Buffer, Hasher, KeyId, PublicKey, SecretAttributes, SecretPersistence,
+    AsymmetricVault, Hasher, KeyId, PublicKey, SecretAttributes, SecretKey, SecretPersistence,
     SecretType, SecretVault, VaultEntry, CURVE25519_PUBLIC_LENGTH_USIZE,
     CURVE25519_SECRET_LENGTH_USIZE,
 };
@@ -9,7 +9,7 @@ use ockam_core::Result;
 use ockam_core::{async_trait, compat::boxed::Box};
 
 impl Vault {
-    fn ecdh_internal(vault_entry: &VaultEntry, peer_public_key: &PublicKey) -> Result<Buffer<u8>> {
+    fn ecdh_internal(vault_entry: &VaultEntry, peer_public_key: &PublicKey) -> Result<SecretKey> {
         let key = vault_entry.key();
         match vault_entry.key_attributes().stype() {
             SecretType::X25519 => {
@@ -30,7 +30,7 @@ impl Vault {
                     CURVE25519_PUBLIC_LENGTH_USIZE
                 ));
                 let secret = sk.diffie_hellman(&pk_t);
-                Ok(secret.as_bytes().to_vec())
+                Ok(SecretKey::new(secret.as_bytes().to_vec()))
             }
             #[cfg(feature = "bls")]
             SecretType::Bls => Err(VaultError::UnknownEcdhKeyType.into()),
@@ -59,9 +59,9 @@ impl AsymmetricVault for Vault {
         let attributes = SecretAttributes::new(
             SecretType::Buffer,
             SecretPersistence::Ephemeral,
-            dh.len() as u32,
+            dh.as_ref().len() as u32,
         );
-        self.secret_import(&dh, attributes).await
+        self.secret_import(dh, attributes).await
     }
 
     async fn compute_key_id_for_public_key(&self, public_key: &PublicKey) -> Result<KeyId> {
@@ -0,0 +1,89 @@
+//! Selection of the crypto backends used by the software vault.
+//!
+//! AES-GCM uses the CPU's AES and carry-less multiplication instructions when
+//! they are available at runtime, and a constant-time software implementation
+//! otherwise:
+//!
+//! - `x86`/`x86_64`: AES-NI and CLMUL are detected at runtime.
+//! - `aarch64`: the ARMv8 crypto extensions are detected at runtime on Linux,
+//!   Android and macOS when the `armv8` feature is enabled (requires nightly).
+//! - `armv7`, `riscv64` and other targets: software implementation.
+//!
+//! The `force_soft` feature disables hardware acceleration altogether.
+
+use core::fmt;
+
+/// The implementation used for a cryptographic primitive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CryptoBackend {
+    /// Dedicated CPU instructions.
+    Hardware,
+    /// Portable software implementation.
+    Software,
+}
+
+impl fmt::Display for CryptoBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoBackend::Hardware => f.write_str("hardware"),
+            CryptoBackend::Software => f.write_str("software"),
+        }
+    }
+}
+
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(any(feature = "force_soft", feature = "no_std"))
+))]
+cpufeatures::new!(aes_gcm_intrinsics, "aes", "pclmulqdq");
+
+#[cfg(all(
+    target_arch = "aarch64",
+    any(target_os = "linux", target_os = "android", target_os = "macos"),
+    feature = "armv8",
+    not(any(feature = "force_soft", feature = "no_std"))
+))]
+cpufeatures::new!(aes_gcm_intrinsics, "aes");
+
+/// Return the backend used for AES-GCM on the current CPU.
+pub fn aes_gcm_backend() -> CryptoBackend {
+    cfg_if::cfg_if! {
+        if #[cfg(any(feature = "force_soft", feature = "no_std"))] {
+            CryptoBackend::Software
+        } else if #[cfg(any(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            all(
+                target_arch = "aarch64",
+                any(target_os = "linux", target_os = "android", target_os = "macos"),
+                feature = "armv8"
+            )
+        ))] {
+            if aes_gcm_intrinsics::get() {
+                CryptoBackend::Hardware
+            } else {
+                CryptoBackend::Software
+            }
+        } else {
+            CryptoBackend::Software
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aes_gcm_backend_is_stable() {
+        assert_eq!(aes_gcm_backend(), aes_gcm_backend());
+    }
+
+    #[cfg(any(
+        feature = "force_soft",
+        not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))
+    ))]
+    #[test]
+    fn software_fallback() {
+        assert_eq!(aes_gcm_backend(), CryptoBackend::Software);
+    }
+}
@@ -47,6 +47,8 @@ pub enum VaultError {
     StorageError,
     /// Invalid Storage data
     InvalidStorageData,
+    /// The vault only verifies signatures and holds no secrets
+    NoSecrets,
 }
 
 impl ockam_core::compat::error::Error for VaultError {}
@@ -73,6 +75,7 @@ impl core::fmt::Display for VaultError {
             Self::InvalidSecretAttributes => write!(f, "invalid secret attributes"),
             Self::StorageError => write!(f, "invalid storage"),
             Self::InvalidStorageData => write!(f, "invalid storage data"),
+            Self::NoSecrets => write!(f, "vault only verifies signatures and holds no secrets"),
         }
     }
 }
@@ -90,6 +93,7 @@ impl From<VaultError> for Error {
             | InvalidPrivateKeyLen
             | InvalidX25519SecretLength => Kind::Misuse,
             UnknownEcdhKeyType | EntryNotFound | SecretNotFound => Kind::NotFound,
+            NoSecrets => Kind::Unsupported,
             _ => Kind::Invalid,
         };
 
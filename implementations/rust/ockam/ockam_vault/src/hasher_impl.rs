@@ -3,8 +3,8 @@ use crate::VaultError;
 use arrayref::array_ref;
 use ockam_core::compat::vec::Vec;
 use ockam_core::vault::{
-    Hasher, KeyId, SecretAttributes, SecretType, SecretVault, AES128_SECRET_LENGTH_USIZE,
-    AES256_SECRET_LENGTH_USIZE,
+    Hasher, KeyId, SecretAttributes, SecretKey, SecretType, SecretVault,
+    AES128_SECRET_LENGTH_USIZE, AES256_SECRET_LENGTH_USIZE,
 };
 use ockam_core::{async_trait, compat::boxed::Box, Result};
 use sha2::{Digest, Sha256};
@@ -62,7 +62,7 @@ impl Hasher for Vault {
 
             prk.expand(info, okm.as_mut_slice())
                 .map_err(|_| Into::<ockam_core::Error>::into(VaultError::HkdfExpandError))?;
-            okm
+            SecretKey::new(okm)
         };
 
         // Prevent dead-lock by freeing entries lock, since we don't need it
@@ -80,7 +80,7 @@ impl Hasher for Vault {
             } else if attributes.stype() != SecretType::Buffer {
                 return Err(VaultError::InvalidHkdfOutputType.into());
             }
-            let secret = &okm[index..index + length];
+            let secret = SecretKey::new(okm.as_ref()[index..index + length].to_vec());
             let secret = self.secret_import(secret, attributes).await?;
 
             secrets.push(secret);
@@ -22,6 +22,7 @@ extern crate alloc;
 pub use ockam_core;
 
 mod asymmetric_impl;
+mod backend;
 mod error;
 mod hasher_impl;
 mod secret_impl;
@@ -34,6 +35,7 @@ pub mod storage;
 mod symmetric_impl;
 mod vault;
 mod verifier_impl;
+mod verifying_vault;
 mod xeddsa;
 
 // Re-export types commonly used by higher level APIs
@@ -42,10 +44,11 @@ pub use ockam_core::vault::{
 };
 
 pub use asymmetric_impl::*;
+pub use backend::*;
 pub use error::*;
 pub use hasher_impl::*;
 pub use secret_impl::*;
 pub use signer_impl::*;
 pub use symmetric_impl::*;
 pub use vault::*;
-pub use verifier_impl::*;
+pub use verifying_vault::*;
@@ -170,13 +170,15 @@ impl SecretVault for Vault {
     }
 
     #[tracing::instrument(skip_all, err)]
-    async fn secret_import(&self, secret: &[u8], attributes: SecretAttributes) -> Result<KeyId> {
-        self.check_secret(secret, &attributes)?;
-        let key_id = self.compute_key_id(secret, &attributes).await?;
-
-        let secret_key = SecretKey::new(secret.to_vec());
-
-        let entry = VaultEntry::new(attributes, secret_key);
+    async fn secret_import(
+        &self,
+        secret: SecretKey,
+        attributes: SecretAttributes,
+    ) -> Result<KeyId> {
+        self.check_secret(secret.as_ref(), &attributes)?;
+        let key_id = self.compute_key_id(secret.as_ref(), &attributes).await?;
+
+        let entry = VaultEntry::new(attributes, secret);
         self.store_secret(&key_id, &entry).await?;
 
         self.data
@@ -288,7 +290,9 @@ impl SecretVault for Vault {
 #[cfg(test)]
 mod tests {
     use crate::{
-        ockam_core::vault::{SecretPersistence, SecretType, CURVE25519_SECRET_LENGTH_U32},
+        ockam_core::vault::{
+            SecretKey, SecretPersistence, SecretType, CURVE25519_SECRET_LENGTH_U32,
+        },
         SecretAttributes, SecretVault, Vault,
     };
     use cfg_if::cfg_if;
@@ -309,6 +313,9 @@ mod tests {
     #[ockam_macros::vault_test]
     fn secret_attributes_get() {}
 
+    #[ockam_macros::vault_test]
+    fn secret_export_is_redacted() {}
+
     fn new_x255519_attrs() -> Option<SecretAttributes> {
         Some(SecretAttributes::new(
             SecretType::X25519,
@@ -326,7 +333,10 @@ mod tests {
         ];
         let attrs = new_x255519_attrs().unwrap();
         let vault = new_vault();
-        let key_id = vault.secret_import(bytes_c25519, attrs).await.unwrap();
+        let key_id = vault
+            .secret_import(SecretKey::new(bytes_c25519.to_vec()), attrs)
+            .await
+            .unwrap();
         assert_eq!(
             "f0e6821043434a9353e6c213a098f6d75ac916b23b3632c7c4c9c6d2e1fa1cf8",
             &key_id
@@ -13,9 +13,25 @@ use tracing::debug;
 struct LegacyVaultEntry {
     key_id: Option<String>,
     key_attributes: SecretAttributes,
+    #[serde(with = "secret_key")]
     key: SecretKey,
 }
 
+/// Serialization of the secret keys, which are only ever serialized to
+/// the storage file and thus do not implement `Serialize` themselves.
+mod secret_key {
+    use ockam_core::vault::SecretKey;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(key: &SecretKey, s: S) -> Result<S::Ok, S::Error> {
+        key.as_ref().serialize(s)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<SecretKey, D::Error> {
+        Vec::<u8>::deserialize(d).map(SecretKey::new)
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(tag = "version")]
 #[non_exhaustive]
@@ -45,6 +45,7 @@ pub(crate) struct VaultData {
 impl Vault {
     /// Create a new SoftwareVault
     pub fn new(storage: Option<Arc<dyn Storage>>) -> Self {
+        tracing::trace!(aes_gcm = %crate::aes_gcm_backend(), "Selected vault crypto backends");
         Self {
             data: Default::default(),
             storage,
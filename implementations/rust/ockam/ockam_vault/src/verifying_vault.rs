@@ -0,0 +1,124 @@
+use crate::verifier_impl::verify_signature;
+use crate::VaultError;
+use arrayref::array_ref;
+use ockam_core::vault::{
+    AsymmetricVault, Hasher, KeyId, PublicKey, SecretAttributes, Signature, SmallBuffer, Verifier,
+};
+use ockam_core::{async_trait, compat::boxed::Box, Result};
+use sha2::{Digest, Sha256};
+
+/// Vault only performing public key operations.
+///
+/// It can verify identities, their signatures and credentials but
+/// holds no secrets: every operation needing one fails with
+/// [`VaultError::NoSecrets`].  It is meant for nodes which only
+/// forward messages and never need to sign or decrypt anything.
+///
+/// # Examples
+/// ```
+/// use ockam_vault::{Vault, VerifyingVault};
+/// use ockam_core::Result;
+/// use ockam_core::vault::{SecretAttributes, SecretType, SecretPersistence, CURVE25519_SECRET_LENGTH_U32, SecretVault, Signer, Verifier};
+///
+/// async fn example() -> Result<()> {
+///     let mut vault = Vault::default();
+///     let attributes = SecretAttributes::new(
+///         SecretType::Ed25519,
+///         SecretPersistence::Ephemeral,
+///         CURVE25519_SECRET_LENGTH_U32,
+///     );
+///     let secret = vault.secret_generate(attributes).await?;
+///     let public = vault.secret_public_key_get(&secret).await?;
+///
+///     let data = "Very important stuff".as_bytes();
+///     let signature = vault.sign(&secret, data).await?;
+///
+///     assert!(VerifyingVault.verify(&signature, &public, data).await?);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct VerifyingVault;
+
+#[async_trait]
+impl Verifier for VerifyingVault {
+    async fn verify(
+        &self,
+        signature: &Signature,
+        public_key: &PublicKey,
+        data: &[u8],
+    ) -> Result<bool> {
+        verify_signature(signature, public_key, data)
+    }
+}
+
+#[async_trait]
+impl Hasher for VerifyingVault {
+    async fn sha256(&self, data: &[u8]) -> Result<[u8; 32]> {
+        let digest = Sha256::digest(data);
+        Ok(*array_ref![digest, 0, 32])
+    }
+
+    async fn hkdf_sha256(
+        &self,
+        _salt: &KeyId,
+        _info: &[u8],
+        _ikm: Option<&KeyId>,
+        _output_attributes: SmallBuffer<SecretAttributes>,
+    ) -> Result<SmallBuffer<KeyId>> {
+        Err(VaultError::NoSecrets.into())
+    }
+}
+
+#[async_trait]
+impl AsymmetricVault for VerifyingVault {
+    async fn ec_diffie_hellman(
+        &self,
+        _secret: &KeyId,
+        _peer_public_key: &PublicKey,
+    ) -> Result<KeyId> {
+        Err(VaultError::NoSecrets.into())
+    }
+
+    async fn compute_key_id_for_public_key(&self, public_key: &PublicKey) -> Result<KeyId> {
+        let key_id = self.sha256(public_key.data()).await?;
+        Ok(hex::encode(key_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vault;
+    use ockam_core::vault::{
+        SecretPersistence, SecretType, SecretVault, Signer, CURVE25519_SECRET_LENGTH_U32,
+    };
+
+    #[tokio::test]
+    async fn verify_without_secrets() {
+        let vault = Vault::default();
+        let attributes = SecretAttributes::new(
+            SecretType::Ed25519,
+            SecretPersistence::Ephemeral,
+            CURVE25519_SECRET_LENGTH_U32,
+        );
+        let secret = vault.secret_generate(attributes).await.unwrap();
+        let public = vault.secret_public_key_get(&secret).await.unwrap();
+        let signature = vault.sign(&secret, b"data").await.unwrap();
+
+        let verifier = VerifyingVault;
+        assert!(verifier.verify(&signature, &public, b"data").await.unwrap());
+        assert!(!verifier
+            .verify(&signature, &public, b"other")
+            .await
+            .unwrap());
+        assert_eq!(
+            verifier
+                .compute_key_id_for_public_key(&public)
+                .await
+                .unwrap(),
+            vault.compute_key_id_for_public_key(&public).await.unwrap()
+        );
+        assert!(verifier.ec_diffie_hellman(&secret, &public).await.is_err());
+    }
+}